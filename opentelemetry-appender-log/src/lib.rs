@@ -42,6 +42,24 @@
 //! This section outlines how log records produced by `log` are mapped into OpenTelemetry log records.
 //! Each subsection deals with a different property on `opentelemetry::logs::LogRecord`.
 //!
+//! ## Instrumentation Scope
+//!
+//! Log records are emitted with the `opentelemetry-log-appender` instrumentation scope, and their
+//! [`log::Record::target`] is set as the target of the log record.
+//!
+//! A bridge created with [`OpenTelemetryLogBridge::with_target_scopes`] instead emits log records
+//! with a logger whose instrumentation scope name is the target, so the module emitting a log record
+//! is preserved by exporters without support for targets:
+//!
+//! ```
+//! # use opentelemetry_sdk::logs::LoggerProvider;
+//! # use opentelemetry_appender_log::OpenTelemetryLogBridge;
+//! # let logger_provider = LoggerProvider::builder().build();
+//! let otel_log_appender = OpenTelemetryLogBridge::with_target_scopes(&logger_provider);
+//! ```
+//!
+//! A logger is created for each distinct target, and kept for the lifetime of the bridge.
+//!
 //! ## Body
 //!
 //! The body is the stringified message ([`log::Record::args`]).
@@ -100,6 +118,8 @@ use opentelemetry::{
     Key,
 };
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
 
 pub struct OpenTelemetryLogBridge<P, L>
 where
@@ -107,7 +127,42 @@ where
     L: Logger + Send + Sync,
 {
     logger: L,
-    _phantom: std::marker::PhantomData<P>, // P is not used in this struct
+    target_loggers: Option<TargetLoggers<P, L>>,
+}
+
+/// Loggers whose instrumentation scope name is the target of log records.
+struct TargetLoggers<P, L> {
+    provider: P,
+    loggers: RwLock<HashMap<String, Arc<L>>>,
+}
+
+impl<P, L> TargetLoggers<P, L>
+where
+    P: LoggerProvider<Logger = L>,
+{
+    /// Calls `f` with the logger of `target`, after releasing the lock so
+    /// that emitting never blocks the loggers of other targets.
+    fn emit(&self, target: &str, f: impl FnOnce(&L)) {
+        let logger = self.logger(target);
+        f(&logger)
+    }
+
+    fn logger(&self, target: &str) -> Arc<L> {
+        if let Some(logger) = self
+            .loggers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(target)
+        {
+            return Arc::clone(logger);
+        }
+
+        let mut loggers = self.loggers.write().unwrap_or_else(PoisonError::into_inner);
+        let logger = loggers
+            .entry(target.to_string())
+            .or_insert_with(|| Arc::new(self.provider.logger_builder(target.to_string()).build()));
+        Arc::clone(logger)
+    }
 }
 
 impl<P, L> log::Log for OpenTelemetryLogBridge<P, L>
@@ -126,14 +181,10 @@ where
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
-            let mut log_record = self.logger.create_log_record();
-            log_record.set_severity_number(severity_of_level(record.level()));
-            log_record.set_severity_text(record.level().as_str().into());
-            log_record.set_body(AnyValue::from(record.args().to_string()));
-            log_record.add_attributes(log_attributes(record.key_values()));
-            log_record.set_target(record.metadata().target().to_string());
-
-            self.logger.emit(log_record);
+            match &self.target_loggers {
+                Some(loggers) => loggers.emit(record.target(), |logger| emit(logger, record)),
+                None => emit(&self.logger, record),
+            }
         }
     }
 
@@ -151,9 +202,35 @@ where
                 .logger_builder("opentelemetry-log-appender")
                 .with_version(Cow::Borrowed(env!("CARGO_PKG_VERSION")))
                 .build(),
-            _phantom: Default::default(),
+            target_loggers: None,
         }
     }
+
+    /// Creates a bridge emitting log records with a logger whose instrumentation scope name is
+    /// the target of the log record.
+    pub fn with_target_scopes(provider: &P) -> Self
+    where
+        P: Clone,
+    {
+        OpenTelemetryLogBridge {
+            target_loggers: Some(TargetLoggers {
+                provider: provider.clone(),
+                loggers: RwLock::new(HashMap::new()),
+            }),
+            ..Self::new(provider)
+        }
+    }
+}
+
+fn emit<L: Logger>(logger: &L, record: &Record) {
+    let mut log_record = logger.create_log_record();
+    log_record.set_severity_number(severity_of_level(record.level()));
+    log_record.set_severity_text(record.level().as_str().into());
+    log_record.set_body(AnyValue::from(record.args().to_string()));
+    log_record.add_attributes(log_attributes(record.key_values()));
+    log_record.set_target(record.metadata().target().to_string());
+
+    logger.emit(log_record);
 }
 
 const fn severity_of_level(level: Level) -> Severity {
@@ -1119,6 +1196,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn logbridge_target_scopes() {
+        let exporter = InMemoryLogsExporter::default();
+
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        let otel_log_appender = OpenTelemetryLogBridge::with_target_scopes(&logger_provider);
+
+        for target in ["my_app::db", "my_app::http", "my_app::db"] {
+            otel_log_appender.log(
+                &log::RecordBuilder::new()
+                    .level(log::Level::Info)
+                    .target(target)
+                    .args(format_args!("message"))
+                    .build(),
+            );
+        }
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        let scopes: Vec<_> = logs
+            .iter()
+            .map(|log| log.instrumentation.name.as_ref())
+            .collect();
+        assert_eq!(scopes, vec!["my_app::db", "my_app::http", "my_app::db"]);
+        assert_eq!(
+            otel_log_appender
+                .target_loggers
+                .as_ref()
+                .unwrap()
+                .loggers
+                .read()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn logbridge_target_scopes_emit_without_holding_the_lock() {
+        let exporter = InMemoryLogsExporter::default();
+
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        let otel_log_appender = OpenTelemetryLogBridge::with_target_scopes(&logger_provider);
+        let loggers = otel_log_appender.target_loggers.as_ref().unwrap();
+
+        // e.g. an exporter logging while a record of another target is emitted
+        loggers.emit("my_app::db", |_| {
+            loggers.emit("my_app::exporter", |logger| {
+                super::emit(
+                    logger,
+                    &log::RecordBuilder::new()
+                        .target("my_app::exporter")
+                        .args(format_args!("message"))
+                        .build(),
+                )
+            });
+        });
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].instrumentation.name, "my_app::exporter");
+    }
+
     #[test]
     fn test_flush() {
         let exporter = InMemoryLogsExporter::default();
@@ -0,0 +1,216 @@
+//! Record panics as OpenTelemetry log records.
+//!
+//! This library installs a [panic hook] emitting a log record through the
+//! [Logs Bridge API] for every panic, then force-flushes the
+//! [`LoggerProvider`] so the record is exported before the process aborts or
+//! exits.
+//!
+//! # Getting Started
+//!
+//! ```
+//! # use opentelemetry_sdk::logs::LoggerProvider;
+//! # let exporter = opentelemetry_sdk::testing::logs::InMemoryLogsExporter::default();
+//! let logger_provider = LoggerProvider::builder()
+//!     .with_simple_exporter(exporter)
+//!     .build();
+//!
+//! opentelemetry_appender_panic::install(&logger_provider);
+//! ```
+//!
+//! The panic hook that was installed before, by default the one printing the
+//! panic to stderr, is still called after the log record is emitted.
+//!
+//! # Mapping Panics
+//!
+//! | Field                                         | Value                                           |
+//! | --------------------------------------------- | ----------------------------------------------- |
+//! | Severity Number                               | [`Severity::Fatal`]                             |
+//! | Severity Text                                 | `FATAL`                                         |
+//! | Body                                          | The panic message                               |
+//! | Trace Context                                 | The span active on the panicking thread, if any |
+//! | `exception.type`                              | `panic`                                         |
+//! | `exception.message`                           | The panic message                               |
+//! | `exception.stacktrace`                        | The backtrace of the panicking thread           |
+//! | `code.filepath`, `code.lineno`, `code.column` | The location of the panic                       |
+//! | `thread.name`                                 | The name of the panicking thread, if it has one |
+//!
+//! The backtrace is always captured, regardless of `RUST_BACKTRACE`.
+//!
+//! Panics whose payload is neither a `&str` nor a `String`, e.g. those raised
+//! with [`std::panic::panic_any`], are recorded with the message
+//! `Box<dyn Any>`, as the default panic hook does.
+//!
+//! [panic hook]: std::panic::set_hook
+//! [Logs Bridge API]: https://opentelemetry.io/docs/specs/otel/logs/bridge-api/
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::borrow::Cow;
+use std::panic::Location;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, LoggerProvider as _, Severity};
+use opentelemetry_sdk::logs::LoggerProvider;
+
+/// How long [`install`] waits for the logger provider to flush.
+pub const DEFAULT_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Install a panic hook recording panics as log records of `provider`.
+///
+/// The provider is flushed for at most [`DEFAULT_FLUSH_TIMEOUT`] after each
+/// panic.
+pub fn install(provider: &LoggerProvider) {
+    install_with_flush_timeout(provider, DEFAULT_FLUSH_TIMEOUT)
+}
+
+/// Install a panic hook recording panics as log records of `provider`,
+/// waiting at most `flush_timeout` for the provider to flush after each
+/// panic.
+///
+/// Flushing continues in the background once the timeout elapsed, but the
+/// panic is no longer held up by it.
+pub fn install_with_flush_timeout(provider: &LoggerProvider, flush_timeout: Duration) {
+    let provider = provider.clone();
+    let logger = provider
+        .logger_builder("opentelemetry-appender-panic")
+        .with_version(Cow::Borrowed(env!("CARGO_PKG_VERSION")))
+        .build();
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info.payload());
+        let mut log_record = logger.create_log_record();
+        log_record.set_severity_number(Severity::Fatal);
+        log_record.set_severity_text(Severity::Fatal.name().into());
+        log_record.add_attributes(panic_attributes(message, info.location()));
+        log_record.set_body(AnyValue::from(message.to_string()));
+        logger.emit(log_record);
+
+        flush(&provider, flush_timeout);
+        previous(info);
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+fn panic_attributes(
+    message: &str,
+    location: Option<&Location<'_>>,
+) -> Vec<(&'static str, AnyValue)> {
+    let mut attributes = vec![
+        ("exception.type", AnyValue::from("panic")),
+        ("exception.message", AnyValue::from(message.to_string())),
+        (
+            "exception.stacktrace",
+            AnyValue::from(Backtrace::force_capture().to_string()),
+        ),
+    ];
+    if let Some(location) = location {
+        attributes.push(("code.filepath", AnyValue::from(location.file().to_string())));
+        attributes.push(("code.lineno", AnyValue::from(i64::from(location.line()))));
+        attributes.push(("code.column", AnyValue::from(i64::from(location.column()))));
+    }
+    if let Some(name) = std::thread::current().name() {
+        attributes.push(("thread.name", AnyValue::from(name.to_string())));
+    }
+    attributes
+}
+
+/// Flush `provider` on another thread, so a processor that is stuck or slow
+/// can't keep the panic from proceeding past `timeout`.
+fn flush(provider: &LoggerProvider, timeout: Duration) {
+    let provider = provider.clone();
+    let (done, flushed) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("opentelemetry-panic-flush".to_string())
+        .spawn(move || {
+            let _ = provider.force_flush();
+            let _ = done.send(());
+        });
+    if spawned.is_ok() {
+        let _ = flushed.recv_timeout(timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::logs::{AnyValue, Severity};
+    use opentelemetry::testing::trace::TestSpan;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+    use opentelemetry::Context;
+    use opentelemetry_sdk::{logs::LoggerProvider, testing::logs::InMemoryLogsExporter};
+
+    #[test]
+    fn panic_is_recorded_as_fatal_log_record() {
+        let exporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        super::install(&logger_provider);
+
+        let span_context = SpanContext::new(
+            TraceId::from_u128(42),
+            SpanId::from_u64(7),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        );
+        let result = std::thread::Builder::new()
+            .name("panicking".to_string())
+            .spawn(move || {
+                let _guard = Context::current_with_span(TestSpan(span_context)).attach();
+                panic!("something went {}", "wrong");
+            })
+            .unwrap()
+            .join();
+        // only this test installs the hook, restore the default one
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(logs.len(), 1);
+        let record = &logs[0].record;
+        assert_eq!(record.severity_number, Some(Severity::Fatal));
+        assert_eq!(record.severity_text.as_deref(), Some("FATAL"));
+        assert_eq!(
+            record.body,
+            Some(AnyValue::String("something went wrong".into()))
+        );
+
+        let trace_context = record.trace_context.as_ref().unwrap();
+        assert_eq!(trace_context.trace_id, TraceId::from_u128(42));
+        assert_eq!(trace_context.span_id, SpanId::from_u64(7));
+
+        let attributes = record.attributes.as_ref().unwrap();
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|(k, _)| k.as_str() == key)
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(
+            attribute("exception.message"),
+            Some(AnyValue::String("something went wrong".into()))
+        );
+        assert!(attribute("exception.stacktrace").is_some());
+        assert_eq!(
+            attribute("code.filepath"),
+            Some(AnyValue::String(file!().into()))
+        );
+        assert_eq!(
+            attribute("thread.name"),
+            Some(AnyValue::String("panicking".into()))
+        );
+    }
+}
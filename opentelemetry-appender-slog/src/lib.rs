@@ -0,0 +1,498 @@
+//! Bridge `slog` into OpenTelemetry.
+//!
+//! This library implements a log appender for the [`slog`] crate using the [Logs Bridge API].
+//!
+//! # Getting Started
+//!
+//! The bridge requires configuration on both the `slog` and OpenTelemetry sides.
+//!
+//! For OpenTelemetry, configure a [`LoggerProvider`] with the desired exporter:
+//!
+//! ```
+//! # #[tokio::main] async fn main() {
+//! # use opentelemetry_sdk::logs::{BatchLogProcessor, LoggerProvider};
+//! # use opentelemetry_sdk::runtime;
+//! let exporter = opentelemetry_stdout::LogExporterBuilder::default().build();
+//!
+//! let logger_provider = LoggerProvider::builder()
+//!     .with_log_processor(BatchLogProcessor::builder(exporter, runtime::Tokio).build())
+//!     .build();
+//! # }
+//! ```
+//!
+//! For `slog`, use an [`OpenTelemetrySlogBridge`] created from the `LoggerProvider` as the drain
+//! of the root logger:
+//!
+//! ```
+//! # #[tokio::main] async fn main() {
+//! # use opentelemetry_sdk::logs::{BatchLogProcessor, LoggerProvider};
+//! # use opentelemetry_sdk::runtime;
+//! # use opentelemetry_appender_slog::OpenTelemetrySlogBridge;
+//! # let exporter = opentelemetry_stdout::LogExporterBuilder::default().build();
+//! # let logger_provider = LoggerProvider::builder()
+//! #     .with_log_processor(BatchLogProcessor::builder(exporter, runtime::Tokio).build())
+//! #     .build();
+//! let otel_log_appender = OpenTelemetrySlogBridge::new(&logger_provider);
+//!
+//! let logger = slog::Logger::root(otel_log_appender, slog::o!("component" => "api"));
+//! slog::info!(logger, "hello from {}", "slog"; "price" => 2.99);
+//! # }
+//! ```
+//!
+//! # Mapping Log Records
+//!
+//! This section outlines how log records produced by `slog` are mapped into OpenTelemetry log records.
+//! Each subsection deals with a different property on `opentelemetry::logs::LogRecord`.
+//!
+//! ## Body
+//!
+//! The body is the formatted message ([`slog::Record::msg`]).
+//!
+//! ## Target
+//!
+//! The target is the record's tag ([`slog::Record::tag`]) if set, otherwise the module the
+//! record was logged from ([`slog::Record::module`]).
+//!
+//! ## Severity
+//!
+//! The severity number and text are mapped from the [`slog::Level`] ([`slog::Record::level`]):
+//!
+//! | `slog::Level` | Severity Text | Severity Number |
+//! | ------------- | ------------- | --------------- |
+//! | `Critical`    | CRITICAL      | 21              |
+//! | `Error`       | ERROR         | 17              |
+//! | `Warning`     | WARN          | 13              |
+//! | `Info`        | INFO          | 9               |
+//! | `Debug`       | DEBUG         | 5               |
+//! | `Trace`       | TRACE         | 1               |
+//!
+//! # Attributes
+//!
+//! The key-values of the record and of the logger it was logged with are converted into
+//! attributes, the record's key-values first:
+//!
+//! | Type            | Result                | Notes                                                                                  |
+//! | --------------- | --------------------- | -------------------------------------------------------------------------------------- |
+//! | `i8`-`i128`     | [`AnyValue::Int`]     | If the value is too big then it will be stringified using [`std::fmt::Display`]        |
+//! | `u8`-`u128`     | [`AnyValue::Int`]     | If the value is too big then it will be stringified using [`std::fmt::Display`]        |
+//! | `f32`-`f64`     | [`AnyValue::Double`]  |                                                                                        |
+//! | `bool`          | [`AnyValue::Boolean`] |                                                                                        |
+//! | `char`, `str`   | [`AnyValue::String`]  |                                                                                        |
+//! | `()`, `None`    | -                     | Unit and `None` values are discarded                                                   |
+//! | Serde values    | Any                   | Requires the `with-serde` feature, see below                                           |
+//! | Other values    | [`AnyValue::String`]  | Stringified using the value's formatting                                               |
+//!
+//! With the `with-serde` feature, nested values logged using [`slog::Serde`] keep their structure:
+//! sequences are converted into [`AnyValue::ListAny`], maps and structs into [`AnyValue::Map`]
+//! and `null`s are discarded.
+//!
+//! # Feature Flags
+//!
+//! This library provides the following Cargo features:
+//!
+//! - `logs_level_enabled`: Allow users to control the log level.
+//! - `with-serde`: Support nested values as attributes without stringifying them.
+//!
+//! [Logs Bridge API]: https://opentelemetry.io/docs/specs/otel/logs/bridge-api/
+//! [`LoggerProvider`]: opentelemetry::logs::LoggerProvider
+
+use opentelemetry::{
+    logs::{AnyValue, LogRecord, Logger, LoggerProvider, Severity},
+    Key,
+};
+use slog::{Level, OwnedKVList, Record, KV};
+use std::borrow::Cow;
+use std::fmt;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+/// A [`slog::Drain`] emitting the records it receives as OpenTelemetry logs.
+pub struct OpenTelemetrySlogBridge<P, L>
+where
+    P: LoggerProvider<Logger = L> + Send + Sync,
+    L: Logger + Send + Sync,
+{
+    logger: L,
+    _phantom: std::marker::PhantomData<P>, // P is not used in this struct
+}
+
+impl<P, L> OpenTelemetrySlogBridge<P, L>
+where
+    P: LoggerProvider<Logger = L> + Send + Sync,
+    L: Logger + Send + Sync,
+{
+    /// Create a bridge emitting logs using a logger from `provider`.
+    pub fn new(provider: &P) -> Self {
+        OpenTelemetrySlogBridge {
+            logger: provider
+                .logger_builder("opentelemetry-appender-slog")
+                .with_version(Cow::Borrowed(env!("CARGO_PKG_VERSION")))
+                .build(),
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<P, L> fmt::Debug for OpenTelemetrySlogBridge<P, L>
+where
+    P: LoggerProvider<Logger = L> + Send + Sync,
+    L: Logger + Send + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OpenTelemetrySlogBridge")
+    }
+}
+
+// slog requires root drains to be unwind safe. A panic while emitting a log
+// cannot leave the bridge itself in an inconsistent state.
+impl<P, L> UnwindSafe for OpenTelemetrySlogBridge<P, L>
+where
+    P: LoggerProvider<Logger = L> + Send + Sync,
+    L: Logger + Send + Sync,
+{
+}
+
+impl<P, L> RefUnwindSafe for OpenTelemetrySlogBridge<P, L>
+where
+    P: LoggerProvider<Logger = L> + Send + Sync,
+    L: Logger + Send + Sync,
+{
+}
+
+impl<P, L> slog::Drain for OpenTelemetrySlogBridge<P, L>
+where
+    P: LoggerProvider<Logger = L> + Send + Sync,
+    L: Logger + Send + Sync,
+{
+    type Ok = ();
+    type Err = slog::Never;
+
+    fn log(&self, record: &Record<'_>, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        if !self.is_enabled(record.level()) {
+            return Ok(());
+        }
+
+        let mut log_record = self.logger.create_log_record();
+        log_record.set_severity_number(severity_of_level(record.level()));
+        log_record.set_severity_text(record.level().as_str().into());
+        log_record.set_body(AnyValue::from(record.msg().to_string()));
+        let target = if record.tag().is_empty() {
+            record.module()
+        } else {
+            record.tag()
+        };
+        log_record.set_target(target.to_string());
+
+        let mut serializer = AttributeSerializer(Vec::new());
+        let _ = record.kv().serialize(record, &mut serializer);
+        let _ = values.serialize(record, &mut serializer);
+        log_record.add_attributes(serializer.0);
+
+        self.logger.emit(log_record);
+        Ok(())
+    }
+
+    fn is_enabled(&self, _level: Level) -> bool {
+        #[cfg(feature = "logs_level_enabled")]
+        return self
+            .logger
+            .event_enabled(severity_of_level(_level), "opentelemetry-appender-slog");
+        #[cfg(not(feature = "logs_level_enabled"))]
+        true
+    }
+}
+
+const fn severity_of_level(level: Level) -> Severity {
+    match level {
+        Level::Critical => Severity::Fatal,
+        Level::Error => Severity::Error,
+        Level::Warning => Severity::Warn,
+        Level::Info => Severity::Info,
+        Level::Debug => Severity::Debug,
+        Level::Trace => Severity::Trace,
+    }
+}
+
+struct AttributeSerializer(Vec<(Key, AnyValue)>);
+
+impl AttributeSerializer {
+    fn push(&mut self, key: slog::Key, value: impl Into<AnyValue>) -> slog::Result {
+        self.0.push((Key::from(key), value.into()));
+        Ok(())
+    }
+}
+
+impl slog::Serializer for AttributeSerializer {
+    fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments<'_>) -> slog::Result {
+        self.push(key, val.to_string())
+    }
+
+    fn emit_usize(&mut self, key: slog::Key, val: usize) -> slog::Result {
+        self.emit_u128(key, val as u128)
+    }
+
+    fn emit_isize(&mut self, key: slog::Key, val: isize) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_bool(&mut self, key: slog::Key, val: bool) -> slog::Result {
+        self.push(key, val)
+    }
+
+    fn emit_char(&mut self, key: slog::Key, val: char) -> slog::Result {
+        self.push(key, val.to_string())
+    }
+
+    fn emit_u8(&mut self, key: slog::Key, val: u8) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_i8(&mut self, key: slog::Key, val: i8) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_u16(&mut self, key: slog::Key, val: u16) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_i16(&mut self, key: slog::Key, val: i16) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_u32(&mut self, key: slog::Key, val: u32) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_i32(&mut self, key: slog::Key, val: i32) -> slog::Result {
+        self.emit_i64(key, val as i64)
+    }
+
+    fn emit_f32(&mut self, key: slog::Key, val: f32) -> slog::Result {
+        self.emit_f64(key, val as f64)
+    }
+
+    fn emit_u64(&mut self, key: slog::Key, val: u64) -> slog::Result {
+        self.emit_u128(key, val as u128)
+    }
+
+    fn emit_i64(&mut self, key: slog::Key, val: i64) -> slog::Result {
+        self.push(key, val)
+    }
+
+    fn emit_f64(&mut self, key: slog::Key, val: f64) -> slog::Result {
+        self.push(key, val)
+    }
+
+    fn emit_u128(&mut self, key: slog::Key, val: u128) -> slog::Result {
+        match i64::try_from(val) {
+            Ok(val) => self.emit_i64(key, val),
+            Err(_) => self.push(key, val.to_string()),
+        }
+    }
+
+    fn emit_i128(&mut self, key: slog::Key, val: i128) -> slog::Result {
+        match i64::try_from(val) {
+            Ok(val) => self.emit_i64(key, val),
+            Err(_) => self.push(key, val.to_string()),
+        }
+    }
+
+    fn emit_str(&mut self, key: slog::Key, val: &str) -> slog::Result {
+        self.push(key, val.to_string())
+    }
+
+    fn emit_unit(&mut self, _key: slog::Key) -> slog::Result {
+        Ok(())
+    }
+
+    fn emit_none(&mut self, _key: slog::Key) -> slog::Result {
+        Ok(())
+    }
+
+    #[cfg(feature = "with-serde")]
+    fn emit_serde(&mut self, key: slog::Key, value: &dyn slog::SerdeValue) -> slog::Result {
+        match any_value::serialize(value.as_serde()) {
+            Some(value) => self.push(key, value),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "with-serde")]
+mod any_value {
+    use std::collections::HashMap;
+
+    use opentelemetry::{logs::AnyValue, Key};
+    use serde_json::Value;
+
+    /// Serialize an arbitrary `serde::Serialize` into an `AnyValue`.
+    ///
+    /// Integers that don't fit in a `i64` are converted into strings, `null`s
+    /// are discarded.
+    pub(crate) fn serialize(value: impl serde::Serialize) -> Option<AnyValue> {
+        serde_json::to_value(value).ok().and_then(from_json)
+    }
+
+    fn from_json(value: Value) -> Option<AnyValue> {
+        match value {
+            Value::Null => None,
+            Value::Bool(value) => Some(AnyValue::Boolean(value)),
+            Value::Number(number) => Some(if let Some(value) = number.as_i64() {
+                AnyValue::Int(value)
+            } else if number.is_u64() {
+                AnyValue::from(number.to_string())
+            } else {
+                AnyValue::Double(number.as_f64().unwrap_or_default())
+            }),
+            Value::String(value) => Some(AnyValue::from(value)),
+            Value::Array(values) => Some(AnyValue::ListAny(
+                values.into_iter().filter_map(from_json).collect(),
+            )),
+            Value::Object(entries) => Some(AnyValue::Map(
+                entries
+                    .into_iter()
+                    .filter_map(|(key, value)| Some((Key::from(key), from_json(value)?)))
+                    .collect::<HashMap<_, _>>(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OpenTelemetrySlogBridge;
+
+    use opentelemetry::logs::{AnyValue, Severity};
+    use opentelemetry::StringValue;
+    use opentelemetry_sdk::{logs::LoggerProvider, testing::logs::InMemoryLogsExporter};
+
+    fn logger(exporter: &InMemoryLogsExporter) -> slog::Logger {
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        slog::Logger::root(
+            OpenTelemetrySlogBridge::new(&logger_provider),
+            slog::o!("logger_value" => "from logger"),
+        )
+    }
+
+    #[test]
+    fn slogbridge_maps_level_body_and_target() {
+        let exporter = InMemoryLogsExporter::default();
+        let logger = logger(&exporter);
+
+        slog::crit!(logger, "CRITICAL");
+        slog::error!(logger, "ERROR");
+        slog::warn!(logger, "WARN");
+        slog::info!(logger, "INFO");
+        slog::info!(logger, #"my-tag", "INFO");
+
+        let logs = exporter.get_emitted_logs().unwrap();
+
+        assert_eq!(logs.len(), 5);
+        for log in &logs {
+            let body: String = match log.record.body.as_ref().unwrap() {
+                AnyValue::String(s) => s.to_string(),
+                _ => panic!("AnyValue::String expected"),
+            };
+            assert_eq!(body, log.record.severity_text.as_deref().unwrap());
+        }
+        assert_eq!(logs[0].record.severity_number, Some(Severity::Fatal));
+        assert_eq!(logs[2].record.severity_number, Some(Severity::Warn));
+        assert_eq!(logs[3].record.target.as_deref(), Some(module_path!()));
+        assert_eq!(logs[4].record.target.as_deref(), Some("my-tag"));
+    }
+
+    #[test]
+    fn slogbridge_attributes() {
+        #[derive(Clone, serde::Serialize)]
+        struct User {
+            id: u64,
+            name: &'static str,
+            roles: Vec<&'static str>,
+            manager: Option<&'static str>,
+        }
+
+        let exporter = InMemoryLogsExporter::default();
+        let logger = logger(&exporter);
+
+        slog::info!(logger, "body";
+            "str_value" => "a string",
+            "char_value" => 'c',
+            "u8_value" => 1u8,
+            "u64_value" => 2147483660u64,
+            "u64_big_value" => 9223372036854775820u64,
+            "i32_value" => -42i32,
+            "f64_value" => 4.2f64,
+            "bool_value" => true,
+            "none_value" => None::<i32>,
+            "some_value" => Some(42),
+            "fmt_value" => %"displayed",
+            "user" => slog::Serde(User {
+                id: 7,
+                name: "user",
+                roles: vec!["admin"],
+                manager: None,
+            }),
+        );
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        let attributes = &logs[0].record.attributes.as_ref().unwrap();
+
+        let get = |needle: &str| {
+            attributes.iter().find_map(|(k, v)| {
+                if k.as_str() == needle {
+                    Some(v.clone())
+                } else {
+                    None
+                }
+            })
+        };
+
+        assert_eq!(
+            AnyValue::String(StringValue::from("a string")),
+            get("str_value").unwrap()
+        );
+        assert_eq!(
+            AnyValue::String(StringValue::from("c")),
+            get("char_value").unwrap()
+        );
+        assert_eq!(AnyValue::Int(1), get("u8_value").unwrap());
+        assert_eq!(AnyValue::Int(2147483660), get("u64_value").unwrap());
+        assert_eq!(
+            AnyValue::String(StringValue::from("9223372036854775820")),
+            get("u64_big_value").unwrap()
+        );
+        assert_eq!(AnyValue::Int(-42), get("i32_value").unwrap());
+        assert_eq!(AnyValue::Double(4.2), get("f64_value").unwrap());
+        assert_eq!(AnyValue::Boolean(true), get("bool_value").unwrap());
+        assert_eq!(None, get("none_value"));
+        assert_eq!(AnyValue::Int(42), get("some_value").unwrap());
+        assert_eq!(
+            AnyValue::String(StringValue::from("displayed")),
+            get("fmt_value").unwrap()
+        );
+        assert_eq!(
+            AnyValue::String(StringValue::from("from logger")),
+            get("logger_value").unwrap()
+        );
+
+        #[cfg(feature = "with-serde")]
+        assert_eq!(
+            AnyValue::Map({
+                let mut map = std::collections::HashMap::new();
+                map.insert(opentelemetry::Key::from("id"), AnyValue::Int(7));
+                map.insert(
+                    opentelemetry::Key::from("name"),
+                    AnyValue::String(StringValue::from("user")),
+                );
+                map.insert(
+                    opentelemetry::Key::from("roles"),
+                    AnyValue::ListAny(vec![AnyValue::String(StringValue::from("admin"))]),
+                );
+                map
+            }),
+            get("user").unwrap()
+        );
+    }
+}
@@ -2,7 +2,10 @@ use opentelemetry::{
     logs::{AnyValue, LogRecord, Logger, LoggerProvider, Severity},
     Key,
 };
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 use tracing_core::{Level, Metadata};
 #[cfg(feature = "experimental_metadata_attributes")]
 use tracing_log::NormalizeEvent;
@@ -10,9 +13,53 @@ use tracing_subscriber::Layer;
 
 const INSTRUMENTATION_LIBRARY_NAME: &str = "opentelemetry-appender-tracing";
 
+// Stable source code attributes of the semantic conventions.
+const CODE_FILE_PATH: Key = Key::from_static_str("code.file.path");
+const CODE_LINE_NUMBER: Key = Key::from_static_str("code.line.number");
+
+/// How the bridge records an event field whose name was already recorded by
+/// the same event, e.g. `tag` in `info!(tag = "a", tag = "b")`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateFieldPolicy {
+    /// Record every value of the field as a separate attribute, like the
+    /// fields of different names.
+    #[default]
+    KeepAll,
+    /// Record the last value of the field.
+    KeepLast,
+    /// Record the first value of the field.
+    KeepFirst,
+    /// Record all values of the field as an `AnyValue::ListAny`, in the order
+    /// they were recorded.
+    CollectIntoList,
+}
+
+/// An event field buffered until all fields of an event with repeated field
+/// names are recorded.
+struct BufferedField {
+    name: &'static str,
+    value: AnyValue,
+    // whether `value` is the list of values of a repeated field
+    collected: bool,
+}
+
+/// The fields of an event with repeated field names, in the order their names
+/// were first recorded.
+#[derive(Default)]
+struct BufferedFields {
+    fields: Vec<BufferedField>,
+    // the index of the field of each name in `fields`
+    index: HashMap<&'static str, usize>,
+}
+
 /// Visitor to record the fields from the event record.
 struct EventVisitor<'a, LR: LogRecord> {
     log_record: &'a mut LR,
+    duplicate_fields: DuplicateFieldPolicy,
+    // the fields of events with repeated field names, merged before they are
+    // added to the record
+    buffered: Option<BufferedFields>,
 }
 
 /// Logs from the log crate have duplicated attributes that we removed here.
@@ -37,7 +84,74 @@ fn get_filename(filepath: &str) -> &str {
 
 impl<'a, LR: LogRecord> EventVisitor<'a, LR> {
     fn new(log_record: &'a mut LR) -> Self {
-        EventVisitor { log_record }
+        EventVisitor {
+            log_record,
+            duplicate_fields: DuplicateFieldPolicy::default(),
+            buffered: None,
+        }
+    }
+
+    /// Merges the values of repeated fields of the event according to
+    /// `policy`, fields of other events are added to the record as they are
+    /// visited.
+    fn with_duplicate_fields(
+        mut self,
+        policy: DuplicateFieldPolicy,
+        event: &tracing::Event<'_>,
+    ) -> Self {
+        self.duplicate_fields = policy;
+        if policy == DuplicateFieldPolicy::KeepAll {
+            return self;
+        }
+
+        let fields = event.metadata().fields();
+        let mut names = HashSet::with_capacity(fields.len());
+        let has_duplicates = !fields.iter().all(|field| names.insert(field.name()));
+        self.buffered = has_duplicates.then(BufferedFields::default);
+        self
+    }
+
+    fn record(&mut self, name: &'static str, value: AnyValue) {
+        let Some(buffered) = self.buffered.as_mut() else {
+            self.log_record.add_attribute(Key::new(name), value);
+            return;
+        };
+        let Some(&index) = buffered.index.get(name) else {
+            buffered.index.insert(name, buffered.fields.len());
+            buffered.fields.push(BufferedField {
+                name,
+                value,
+                collected: false,
+            });
+            return;
+        };
+        let field = &mut buffered.fields[index];
+        match self.duplicate_fields {
+            // fields are only buffered for the other policies
+            DuplicateFieldPolicy::KeepAll => {}
+            DuplicateFieldPolicy::KeepLast => field.value = value,
+            DuplicateFieldPolicy::KeepFirst => {}
+            DuplicateFieldPolicy::CollectIntoList => match &mut field.value {
+                AnyValue::ListAny(values) if field.collected => values.push(value),
+                first => {
+                    let first = std::mem::replace(first, AnyValue::ListAny(Vec::new()));
+                    field.value = AnyValue::ListAny(vec![first, value]);
+                    field.collected = true;
+                }
+            },
+        }
+    }
+
+    /// Adds the buffered fields to the record.
+    fn finish(self) {
+        for field in self
+            .buffered
+            .into_iter()
+            .flat_map(|buffered| buffered.fields)
+        {
+            self.log_record
+                .add_attribute(Key::new(field.name), field.value);
+        }
     }
     fn visit_metadata(&mut self, meta: &Metadata) {
         self.log_record
@@ -47,6 +161,17 @@ impl<'a, LR: LogRecord> EventVisitor<'a, LR> {
         self.visit_experimental_metadata(meta);
     }
 
+    fn visit_code_location(&mut self, filepath: Option<Cow<'static, str>>, line: Option<u32>) {
+        if let Some(filepath) = filepath {
+            self.log_record
+                .add_attribute(CODE_FILE_PATH, AnyValue::from(filepath));
+        }
+        if let Some(line) = line {
+            self.log_record
+                .add_attribute(CODE_LINE_NUMBER, AnyValue::from(line));
+        }
+    }
+
     #[cfg(feature = "experimental_metadata_attributes")]
     fn visit_experimental_metadata(&mut self, meta: &Metadata) {
         self.log_record.add_attribute(
@@ -62,15 +187,23 @@ impl<'a, LR: LogRecord> EventVisitor<'a, LR> {
         }
 
         if let Some(filepath) = meta.file() {
-            self.log_record.add_attribute(
-                Key::new("code.filepath"),
-                AnyValue::from(filepath.to_owned()),
-            );
             self.log_record.add_attribute(
                 Key::new("code.filename"),
                 AnyValue::from(get_filename(filepath).to_owned()),
             );
         }
+    }
+
+    /// The experimental counterparts of `code.file.path` and
+    /// `code.line.number`, only recorded when the code attributes are not.
+    #[cfg(feature = "experimental_metadata_attributes")]
+    fn visit_experimental_code_location(&mut self, meta: &Metadata) {
+        if let Some(filepath) = meta.file() {
+            self.log_record.add_attribute(
+                Key::new("code.filepath"),
+                AnyValue::from(filepath.to_owned()),
+            );
+        }
 
         if let Some(line) = meta.line() {
             self.log_record
@@ -88,8 +221,7 @@ impl<'a, LR: LogRecord> tracing::field::Visit for EventVisitor<'a, LR> {
         if field.name() == "message" {
             self.log_record.set_body(format!("{:?}", value).into());
         } else {
-            self.log_record
-                .add_attribute(Key::new(field.name()), AnyValue::from(format!("{value:?}")));
+            self.record(field.name(), AnyValue::from(format!("{value:?}")));
         }
     }
 
@@ -98,18 +230,15 @@ impl<'a, LR: LogRecord> tracing::field::Visit for EventVisitor<'a, LR> {
         if is_duplicated_metadata(field.name()) {
             return;
         }
-        self.log_record
-            .add_attribute(Key::new(field.name()), AnyValue::from(value.to_owned()));
+        self.record(field.name(), AnyValue::from(value.to_owned()));
     }
 
     fn record_bool(&mut self, field: &tracing_core::Field, value: bool) {
-        self.log_record
-            .add_attribute(Key::new(field.name()), AnyValue::from(value));
+        self.record(field.name(), AnyValue::from(value));
     }
 
     fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
-        self.log_record
-            .add_attribute(Key::new(field.name()), AnyValue::from(value));
+        self.record(field.name(), AnyValue::from(value));
     }
 
     fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
@@ -117,11 +246,133 @@ impl<'a, LR: LogRecord> tracing::field::Visit for EventVisitor<'a, LR> {
         if is_duplicated_metadata(field.name()) {
             return;
         }
-        self.log_record
-            .add_attribute(Key::new(field.name()), AnyValue::from(value));
+        self.record(field.name(), AnyValue::from(value));
+    }
+
+    fn record_bytes(&mut self, field: &tracing_core::Field, value: &[u8]) {
+        self.record(field.name(), AnyValue::Bytes(value.to_vec()));
+    }
+
+    /// Records fields implementing `valuable::Valuable`, e.g. recorded with
+    /// `info!(items = items.as_value())`, as structured values.
+    #[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+    fn record_value(&mut self, field: &tracing_core::Field, value: valuable::Value<'_>) {
+        self.record(field.name(), any_value_of(value));
+    }
+}
+
+/// Converts a `valuable` value to an `AnyValue`.
+///
+/// Lists, tuples and unnamed fields become `ListAny`, maps and named fields
+/// become `Map`. Enum values are recorded as their variant name, or as a map
+/// from their variant name to their fields. Integers which don't fit in an
+/// `i64` are recorded as strings.
+#[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+fn any_value_of(value: valuable::Value<'_>) -> AnyValue {
+    use valuable::{Valuable, Value};
+
+    match value {
+        Value::Bool(value) => value.into(),
+        Value::Char(value) => value.to_string().into(),
+        Value::F32(value) => f64::from(value).into(),
+        Value::F64(value) => value.into(),
+        Value::I8(value) => i64::from(value).into(),
+        Value::I16(value) => i64::from(value).into(),
+        Value::I32(value) => i64::from(value).into(),
+        Value::I64(value) => value.into(),
+        Value::U8(value) => i64::from(value).into(),
+        Value::U16(value) => i64::from(value).into(),
+        Value::U32(value) => i64::from(value).into(),
+        Value::I128(value) => i64_or_string(value),
+        Value::Isize(value) => i64_or_string(value),
+        Value::U64(value) => i64_or_string(value),
+        Value::U128(value) => i64_or_string(value),
+        Value::Usize(value) => i64_or_string(value),
+        Value::String(value) => value.to_owned().into(),
+        Value::Path(value) => value.display().to_string().into(),
+        Value::Error(value) => value.to_string().into(),
+        Value::Listable(value) => AnyValueVisitor::collect(value),
+        Value::Tuplable(value) => AnyValueVisitor::collect(value),
+        Value::Mappable(value) => AnyValueVisitor::collect(value),
+        Value::Structable(value) => AnyValueVisitor::collect(value),
+        Value::Enumerable(enumerable) => {
+            let mut visitor = AnyValueVisitor::default();
+            enumerable.visit(&mut visitor);
+            let variant = enumerable.variant().name().to_owned();
+            if visitor.values.is_empty() && visitor.entries.is_empty() {
+                variant.into()
+            } else {
+                AnyValue::Map(
+                    [(Key::new(variant), visitor.into_any_value())]
+                        .into_iter()
+                        .collect(),
+                )
+            }
+        }
+        value => format!("{value:?}").into(),
+    }
+}
+
+#[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+fn i64_or_string<T: TryInto<i64> + ToString + Copy>(value: T) -> AnyValue {
+    match value.try_into() {
+        Ok(value) => AnyValue::from(value),
+        Err(_) => value.to_string().into(),
+    }
+}
+
+/// Collects the elements or the entries of a `valuable` value.
+#[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+#[derive(Default)]
+struct AnyValueVisitor {
+    values: Vec<AnyValue>,
+    entries: std::collections::HashMap<Key, AnyValue>,
+}
+
+#[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+impl AnyValueVisitor {
+    /// Converts the elements or the entries of `value`.
+    ///
+    /// Visiting a `valuable::Value` itself would only visit the value again.
+    fn collect<V: valuable::Valuable + ?Sized>(value: &V) -> AnyValue {
+        let mut visitor = AnyValueVisitor::default();
+        value.visit(&mut visitor);
+        visitor.into_any_value()
+    }
+
+    fn into_any_value(self) -> AnyValue {
+        if self.entries.is_empty() {
+            AnyValue::ListAny(self.values)
+        } else {
+            AnyValue::Map(self.entries)
+        }
+    }
+}
+
+#[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+impl valuable::Visit for AnyValueVisitor {
+    fn visit_value(&mut self, value: valuable::Value<'_>) {
+        self.values.push(any_value_of(value));
+    }
+
+    fn visit_named_fields(&mut self, named_values: &valuable::NamedValues<'_>) {
+        for (field, value) in named_values {
+            self.entries
+                .insert(Key::new(field.name().to_owned()), any_value_of(*value));
+        }
+    }
+
+    fn visit_unnamed_fields(&mut self, values: &[valuable::Value<'_>]) {
+        self.values.extend(values.iter().copied().map(any_value_of));
     }
 
-    // TODO: Remaining field types from AnyValue : Bytes, ListAny, Boolean
+    fn visit_entry(&mut self, key: valuable::Value<'_>, value: valuable::Value<'_>) {
+        let key = match key {
+            valuable::Value::String(key) => key.to_owned(),
+            key => format!("{key:?}"),
+        };
+        self.entries.insert(Key::new(key), any_value_of(value));
+    }
 }
 
 pub struct OpenTelemetryTracingBridge<P, L>
@@ -130,6 +381,10 @@ where
     L: Logger + Send + Sync,
 {
     logger: L,
+    code_attributes: bool,
+    min_severity: Option<Severity>,
+    target_prefixes: Vec<Cow<'static, str>>,
+    duplicate_fields: DuplicateFieldPolicy,
     _phantom: std::marker::PhantomData<P>, // P is not used.
 }
 
@@ -144,9 +399,82 @@ where
                 .logger_builder(INSTRUMENTATION_LIBRARY_NAME)
                 .with_version(Cow::Borrowed(env!("CARGO_PKG_VERSION")))
                 .build(),
+            code_attributes: false,
+            min_severity: None,
+            target_prefixes: Vec::new(),
+            duplicate_fields: DuplicateFieldPolicy::default(),
             _phantom: Default::default(),
         }
     }
+
+    /// Record the source code location of events as the `code.file.path`
+    /// and `code.line.number` attributes, disabled by default.
+    ///
+    /// When enabled, the `code.filepath` and `code.lineno` attributes of the
+    /// `experimental_metadata_attributes` feature are not recorded, since they
+    /// carry the same values. `code.function.name` is not recorded since
+    /// tracing metadata does not carry the name of the function emitting the
+    /// event.
+    pub fn with_code_attributes(mut self, enabled: bool) -> Self {
+        self.code_attributes = enabled;
+        self
+    }
+
+    /// Only forward events with a severity of at least `min_severity`.
+    ///
+    /// Other events are discarded before a log record is created for them.
+    /// With the `logs_level_enabled` feature they are disabled in
+    /// `Layer::event_enabled`, like the events disabled by the logger, which
+    /// disables them for the other layers of the subscriber as well.
+    pub fn with_severity_filter(mut self, min_severity: Severity) -> Self {
+        self.min_severity = Some(min_severity);
+        self
+    }
+
+    /// Only forward events whose target starts with one of `prefixes`, all
+    /// targets are forwarded if no prefix is given.
+    ///
+    /// Other events are discarded like the events filtered by
+    /// [`with_severity_filter`](Self::with_severity_filter).
+    pub fn with_target_filter<I, T>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.target_prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// How to record a field recorded several times by the same event,
+    /// [`DuplicateFieldPolicy::KeepAll`] by default.
+    ///
+    /// Only events with repeated field names are affected, fields of other
+    /// events are recorded as they are visited. With another policy than
+    /// `KeepAll`, the field names of each event are checked for duplicates.
+    pub fn with_duplicate_field_policy(mut self, policy: DuplicateFieldPolicy) -> Self {
+        self.duplicate_fields = policy;
+        self
+    }
+
+    /// Whether an event with `severity` and `target` passes the filters of
+    /// the bridge and is enabled by the logger.
+    fn is_enabled(&self, severity: Severity, target: &str) -> bool {
+        if matches!(self.min_severity, Some(min) if severity < min) {
+            return false;
+        }
+        if !self.target_prefixes.is_empty()
+            && !self
+                .target_prefixes
+                .iter()
+                .any(|prefix| target.starts_with(prefix.as_ref()))
+        {
+            return false;
+        }
+        #[cfg(feature = "logs_level_enabled")]
+        return self.logger.event_enabled(severity, target);
+        #[cfg(not(feature = "logs_level_enabled"))]
+        true
+    }
 }
 
 impl<S, P, L> Layer<S> for OpenTelemetryTracingBridge<P, L>
@@ -168,16 +496,36 @@ where
         #[cfg(not(feature = "experimental_metadata_attributes"))]
         let meta = event.metadata();
 
+        let severity = severity_of_level(meta.level());
+        if !self.is_enabled(severity, meta.target()) {
+            return;
+        }
+
         //let mut log_record: LogRecord = LogRecord::default();
         let mut log_record = self.logger.create_log_record();
-        log_record.set_severity_number(severity_of_level(meta.level()));
+        log_record.set_severity_number(severity);
         log_record.set_severity_text(meta.level().to_string().into());
         log_record.set_target(meta.target().to_string());
 
-        let mut visitor = EventVisitor::new(&mut log_record);
+        let mut visitor =
+            EventVisitor::new(&mut log_record).with_duplicate_fields(self.duplicate_fields, event);
         visitor.visit_metadata(meta);
+        if self.code_attributes {
+            // the file path of events emitted with tracing macros is static,
+            // only the normalized metadata of log records needs a copy
+            let filepath = match event.metadata().file() {
+                Some(filepath) => Some(Cow::Borrowed(filepath)),
+                None => meta.file().map(|filepath| Cow::Owned(filepath.to_owned())),
+            };
+            visitor.visit_code_location(filepath, meta.line());
+        }
+        #[cfg(feature = "experimental_metadata_attributes")]
+        if !self.code_attributes {
+            visitor.visit_experimental_code_location(meta);
+        }
         // Visit fields.
         event.record(&mut visitor);
+        visitor.finish();
 
         self.logger.emit(log_record);
     }
@@ -188,9 +536,17 @@ where
         _event: &tracing_core::Event<'_>,
         _ctx: tracing_subscriber::layer::Context<'_, S>,
     ) -> bool {
-        let severity = severity_of_level(_event.metadata().level());
-        self.logger
-            .event_enabled(severity, _event.metadata().target())
+        #[cfg(feature = "experimental_metadata_attributes")]
+        let normalized_meta = _event.normalized_metadata();
+        #[cfg(feature = "experimental_metadata_attributes")]
+        let meta = normalized_meta
+            .as_ref()
+            .unwrap_or_else(|| _event.metadata());
+
+        #[cfg(not(feature = "experimental_metadata_attributes"))]
+        let meta = _event.metadata();
+
+        self.is_enabled(severity_of_level(meta.level()), meta.target())
     }
 }
 
@@ -236,7 +592,7 @@ mod tests {
 
         // Act
         error!(name: "my-event-name", target: "my-system", event_id = 20, user_name = "otel", user_email = "otel@opentelemetry.io");
-        logger_provider.force_flush();
+        logger_provider.force_flush().unwrap();
 
         // Assert TODO: move to helper methods
         let exported_logs = exporter
@@ -316,7 +672,7 @@ mod tests {
             (trace_id, span_id)
         });
 
-        logger_provider.force_flush();
+        logger_provider.force_flush().unwrap();
 
         // Assert TODO: move to helper methods
         let exported_logs = exporter
@@ -400,7 +756,7 @@ mod tests {
 
         // Act
         log::error!("log from log crate");
-        logger_provider.force_flush();
+        logger_provider.force_flush().unwrap();
 
         // Assert TODO: move to helper methods
         let exported_logs = exporter
@@ -480,7 +836,7 @@ mod tests {
             (trace_id, span_id)
         });
 
-        logger_provider.force_flush();
+        logger_provider.force_flush().unwrap();
 
         // Assert TODO: move to helper methods
         let exported_logs = exporter
@@ -544,4 +900,221 @@ mod tests {
             assert!(attributes_key.contains(&Key::new("log.target")));
         }
     }
+
+    #[test]
+    fn tracing_appender_with_code_attributes() {
+        // Arrange
+        let exporter: InMemoryLogsExporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        let layer =
+            layer::OpenTelemetryTracingBridge::new(&logger_provider).with_code_attributes(true);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        // avoiding setting tracing subscriber as global as that does not
+        // play well with unit tests.
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Act
+        let line = line!() + 1;
+        error!(name: "my-event-name", target: "my-system", event_id = 20);
+        logger_provider.force_flush().unwrap();
+
+        // Assert
+        let exported_logs = exporter
+            .get_emitted_logs()
+            .expect("Logs are expected to be exported.");
+        let attributes: Vec<(Key, AnyValue)> = exported_logs[0]
+            .record
+            .attributes
+            .clone()
+            .expect("Attributes are expected");
+        assert!(attributes.contains(&(Key::new("code.file.path"), file!().into())));
+        assert!(attributes.contains(&(Key::new("code.line.number"), line.into())));
+        #[cfg(feature = "experimental_metadata_attributes")]
+        assert!(!attributes
+            .iter()
+            .any(|(key, _)| key.as_str() == "code.filepath" || key.as_str() == "code.lineno"));
+    }
+
+    #[test]
+    fn tracing_appender_with_filters() {
+        // Arrange
+        let exporter: InMemoryLogsExporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        let layer = layer::OpenTelemetryTracingBridge::new(&logger_provider)
+            .with_severity_filter(Severity::Warn)
+            .with_target_filter(["my-system", "other-system::db"]);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        // avoiding setting tracing subscriber as global as that does not
+        // play well with unit tests.
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Act
+        error!(target: "my-system", "kept");
+        tracing::warn!(target: "other-system::db", "kept");
+        tracing::info!(target: "my-system", "below the severity filter");
+        error!(target: "other-system::http", "outside the target filter");
+        logger_provider.force_flush().unwrap();
+
+        // Assert
+        let exported_logs = exporter
+            .get_emitted_logs()
+            .expect("Logs are expected to be exported.");
+        let targets: Vec<_> = exported_logs
+            .iter()
+            .map(|log| log.record.target.as_deref())
+            .collect();
+        assert_eq!(targets, vec![Some("my-system"), Some("other-system::db")]);
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    #[test]
+    fn tracing_appender_filters_in_event_enabled() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tracing_subscriber::Layer;
+
+        struct CountingLayer(Arc<AtomicUsize>);
+
+        impl<S: tracing::Subscriber> Layer<S> for CountingLayer {
+            fn on_event(
+                &self,
+                _event: &tracing::Event<'_>,
+                _ctx: tracing_subscriber::layer::Context<'_, S>,
+            ) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // Arrange
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(InMemoryLogsExporter::default())
+            .build();
+        let layer = layer::OpenTelemetryTracingBridge::new(&logger_provider)
+            .with_severity_filter(Severity::Warn)
+            .with_target_filter(["my-system"]);
+        let events = Arc::new(AtomicUsize::new(0));
+        let subscriber = tracing_subscriber::registry()
+            .with(CountingLayer(events.clone()))
+            .with(layer);
+
+        // avoiding setting tracing subscriber as global as that does not
+        // play well with unit tests.
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Act
+        error!(target: "my-system", "kept");
+        tracing::info!(target: "my-system", "below the severity filter");
+        error!(target: "other-system", "outside the target filter");
+
+        // Assert
+        assert_eq!(events.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn tracing_appender_with_duplicate_fields() {
+        let policies = [
+            (
+                layer::DuplicateFieldPolicy::KeepAll,
+                vec!["a".into(), "b".into(), "c".into()],
+            ),
+            (layer::DuplicateFieldPolicy::KeepLast, vec!["c".into()]),
+            (layer::DuplicateFieldPolicy::KeepFirst, vec!["a".into()]),
+            (
+                layer::DuplicateFieldPolicy::CollectIntoList,
+                vec![AnyValue::ListAny(vec!["a".into(), "b".into(), "c".into()])],
+            ),
+        ];
+        for (policy, expected) in policies {
+            // Arrange
+            let exporter: InMemoryLogsExporter = InMemoryLogsExporter::default();
+            let logger_provider = LoggerProvider::builder()
+                .with_simple_exporter(exporter.clone())
+                .build();
+
+            let layer = layer::OpenTelemetryTracingBridge::new(&logger_provider)
+                .with_duplicate_field_policy(policy);
+            let subscriber = tracing_subscriber::registry().with(layer);
+
+            // avoiding setting tracing subscriber as global as that does not
+            // play well with unit tests.
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            // Act
+            error!(tag = "a", tag = "b", event_id = 20, tag = "c", "message");
+            logger_provider.force_flush().unwrap();
+
+            // Assert
+            let exported_logs = exporter
+                .get_emitted_logs()
+                .expect("Logs are expected to be exported.");
+            let attributes: Vec<(Key, AnyValue)> = exported_logs[0]
+                .record
+                .attributes
+                .clone()
+                .expect("Attributes are expected");
+            let tags: Vec<AnyValue> = attributes
+                .iter()
+                .filter(|(key, _)| key.as_str() == "tag")
+                .map(|(_, value)| value.clone())
+                .collect();
+            assert_eq!(tags, expected, "{policy:?}");
+            assert!(attributes.contains(&(Key::new("event_id"), 20.into())));
+        }
+    }
+
+    #[cfg(all(tracing_unstable, feature = "experimental_valuable"))]
+    #[test]
+    fn tracing_appender_with_valuable_fields() {
+        use std::collections::HashMap;
+        use valuable::Valuable;
+
+        // Arrange
+        let exporter: InMemoryLogsExporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        let layer = layer::OpenTelemetryTracingBridge::new(&logger_provider);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        // avoiding setting tracing subscriber as global as that does not
+        // play well with unit tests.
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Act
+        let items = vec!["apple", "pear"];
+        let counts = HashMap::from([("apple", 2)]);
+        error!(
+            items = items.as_value(),
+            counts = counts.as_value(),
+            "order failed"
+        );
+        logger_provider.force_flush().unwrap();
+
+        // Assert
+        let exported_logs = exporter
+            .get_emitted_logs()
+            .expect("Logs are expected to be exported.");
+        let attributes: Vec<(Key, AnyValue)> = exported_logs[0]
+            .record
+            .attributes
+            .clone()
+            .expect("Attributes are expected");
+        assert!(attributes.contains(&(
+            Key::new("items"),
+            AnyValue::ListAny(vec!["apple".into(), "pear".into()])
+        )));
+        assert!(attributes.contains(&(
+            Key::new("counts"),
+            AnyValue::Map(HashMap::from([(Key::new("apple"), 2.into())]))
+        )));
+    }
 }
@@ -0,0 +1,105 @@
+//! Conversions shared by the telemetry of all signals.
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use opentelemetry::{Key, KeyValue};
+use opentelemetry_sdk::Resource;
+
+use crate::models::{Properties, Tags};
+
+/// The SDK version reported with every telemetry item.
+const SDK_VERSION: &str = concat!("otelrs:", env!("CARGO_PKG_VERSION"));
+
+/// Formats `time` as an ISO 8601 UTC timestamp with microseconds.
+pub(crate) fn format_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Formats `duration` as the `d.hh:mm:ss.ffffff` time span of the track API.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}.{:02}:{:02}:{:02}.{:06}",
+        seconds / 86_400,
+        seconds / 3_600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+        duration.subsec_micros()
+    )
+}
+
+/// The context tags identifying the application described by `resource`.
+///
+/// The cloud role is the `service.name`, prefixed with the
+/// `service.namespace` if any, and the cloud role instance the
+/// `service.instance.id`, or the `host.name` without instance id.
+pub(crate) fn resource_tags(resource: &Resource) -> Tags {
+    let mut tags = Tags::new();
+    tags.insert("ai.internal.sdkVersion", SDK_VERSION.to_owned());
+
+    let service_name = resource.get(Key::from_static_str("service.name"));
+    let service_namespace = resource.get(Key::from_static_str("service.namespace"));
+    match (service_namespace, service_name) {
+        (Some(namespace), Some(name)) => {
+            tags.insert("ai.cloud.role", format!("{namespace}.{name}"));
+        }
+        (None, Some(name)) => {
+            tags.insert("ai.cloud.role", name.to_string());
+        }
+        _ => {}
+    }
+
+    if let Some(instance) = resource
+        .get(Key::from_static_str("service.instance.id"))
+        .or_else(|| resource.get(Key::from_static_str("host.name")))
+    {
+        tags.insert("ai.cloud.roleInstance", instance.to_string());
+    }
+
+    tags
+}
+
+/// Converts attributes to the custom dimensions of a telemetry item.
+pub(crate) fn properties<'a>(attributes: impl IntoIterator<Item = &'a KeyValue>) -> Properties {
+    attributes
+        .into_iter()
+        .map(|kv| (kv.key.to_string(), kv.value.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::Resource;
+
+    use super::{format_duration, format_time, resource_tags};
+
+    #[test]
+    fn format_times_and_durations() {
+        assert_eq!(
+            format_time(SystemTime::UNIX_EPOCH + Duration::from_micros(1_500_000)),
+            "1970-01-01T00:00:01.500000Z"
+        );
+        assert_eq!(
+            format_duration(Duration::from_micros(1_500_000)),
+            "0.00:00:01.500000"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86_400 + 3 * 3_600 + 4 * 60 + 5)),
+            "2.03:04:05.000000"
+        );
+    }
+
+    #[test]
+    fn cloud_role_from_resource() {
+        let tags = resource_tags(&Resource::new([
+            KeyValue::new("service.namespace", "shop"),
+            KeyValue::new("service.name", "cart"),
+            KeyValue::new("host.name", "host-1"),
+        ]));
+        assert_eq!(tags["ai.cloud.role"], "shop.cart");
+        assert_eq!(tags["ai.cloud.roleInstance"], "host-1");
+    }
+}
@@ -0,0 +1,105 @@
+//! Parsing Application Insights connection strings.
+use crate::Error;
+
+/// The ingestion endpoint of connection strings without endpoint.
+const DEFAULT_INGESTION_ENDPOINT: &str = "https://dc.services.visualstudio.com";
+
+/// The settings of an Application Insights resource.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ConnectionString {
+    pub(crate) instrumentation_key: String,
+    pub(crate) ingestion_endpoint: String,
+}
+
+impl ConnectionString {
+    /// Parses a connection string such as
+    /// `InstrumentationKey=...;IngestionEndpoint=https://...`.
+    ///
+    /// Keys are case-insensitive. The ingestion endpoint is either given
+    /// explicitly or derived from the `EndpointSuffix` and `Location` keys.
+    pub(crate) fn parse(value: &str) -> Result<Self, Error> {
+        let mut instrumentation_key = None;
+        let mut ingestion_endpoint = None;
+        let mut endpoint_suffix = None;
+        let mut location = None;
+
+        for pair in value
+            .split(';')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                Error::InvalidConnectionString(format!("`{pair}` is not a key=value pair"))
+            })?;
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "instrumentationkey" => instrumentation_key = Some(value),
+                "ingestionendpoint" => ingestion_endpoint = Some(value),
+                "endpointsuffix" => endpoint_suffix = Some(value),
+                "location" => location = Some(value),
+                _ => {}
+            }
+        }
+
+        let instrumentation_key = instrumentation_key
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| {
+                Error::InvalidConnectionString("the instrumentation key is missing".into())
+            })?;
+        let ingestion_endpoint = match (ingestion_endpoint, endpoint_suffix) {
+            (Some(endpoint), _) => endpoint.trim_end_matches('/').to_owned(),
+            (None, Some(suffix)) => match location {
+                Some(location) => format!("https://{location}.dc.{suffix}"),
+                None => format!("https://dc.{suffix}"),
+            },
+            (None, None) => DEFAULT_INGESTION_ENDPOINT.to_owned(),
+        };
+
+        Ok(ConnectionString {
+            instrumentation_key: instrumentation_key.to_owned(),
+            ingestion_endpoint,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConnectionString;
+
+    #[test]
+    fn parse_connection_strings() {
+        assert_eq!(
+            ConnectionString::parse(
+                "InstrumentationKey=key;IngestionEndpoint=https://westeurope-5.in.applicationinsights.azure.com/;LiveEndpoint=https://live"
+            )
+            .unwrap(),
+            ConnectionString {
+                instrumentation_key: "key".into(),
+                ingestion_endpoint: "https://westeurope-5.in.applicationinsights.azure.com"
+                    .into(),
+            }
+        );
+        assert_eq!(
+            ConnectionString::parse(
+                "instrumentationkey=key; EndpointSuffix=ai.contoso.com; Location=westus2"
+            )
+            .unwrap()
+            .ingestion_endpoint,
+            "https://westus2.dc.ai.contoso.com"
+        );
+        assert_eq!(
+            ConnectionString::parse("InstrumentationKey=key")
+                .unwrap()
+                .ingestion_endpoint,
+            "https://dc.services.visualstudio.com"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_connection_strings() {
+        assert!(ConnectionString::parse("").is_err());
+        assert!(ConnectionString::parse("InstrumentationKey=").is_err());
+        assert!(ConnectionString::parse("InstrumentationKey").is_err());
+        assert!(ConnectionString::parse("IngestionEndpoint=https://host").is_err());
+    }
+}
@@ -0,0 +1,595 @@
+//! # OpenTelemetry Azure Monitor Exporter
+//!
+//! Exports spans, logs and metrics to [Azure Monitor Application Insights]
+//! through its track API.
+//!
+//! Spans of server and consumer kind become requests, other spans become
+//! dependencies and span events become traces or, for `exception` events,
+//! exceptions. Log records become traces, or exceptions when they carry
+//! `exception.*` attributes, and metric data points become metrics. Span links
+//! are listed in the `_MS.links` property of requests and dependencies.
+//!
+//! The trace id of spans and log records is the operation id of their
+//! telemetry and the parent span id its operation parent id, so Application
+//! Insights correlates them in its end-to-end transaction views. The cloud role
+//! and role instance are read from the `service.namespace`, `service.name`
+//! and `service.instance.id` resource attributes.
+//!
+//! [Azure Monitor Application Insights]: https://learn.microsoft.com/azure/azure-monitor/app/app-insights-overview
+//!
+//! ## Quickstart
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "trace", feature = "reqwest-client"))]
+//! # {
+//! use opentelemetry::trace::{Tracer, TracerProvider as _};
+//! use opentelemetry_sdk::{runtime, trace::TracerProvider};
+//!
+//! let exporter = opentelemetry_azure_monitor::Exporter::builder(
+//!     "InstrumentationKey=00000000-0000-0000-0000-000000000000",
+//! )
+//! .build()
+//! .expect("valid connection string");
+//!
+//! let provider = TracerProvider::builder()
+//!     .with_batch_exporter(exporter, runtime::Tokio)
+//!     .build();
+//! let tracer = provider.tracer("my-app");
+//!
+//! tracer.in_span("doing_work", |_cx| {
+//!     // Traced app logic here...
+//! });
+//! # }
+//! ```
+//!
+//! ## Crate Feature Flags
+//!
+//! * `trace`, `logs` and `metrics`: export the corresponding signal, enabled
+//!   by default.
+//! * `reqwest-client`: send requests with an async [`reqwest`] client unless
+//!   another client is set with [`ExporterBuilder::with_http_client`].
+//!
+//! [`reqwest`]: https://crates.io/crates/reqwest
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(
+    docsrs,
+    feature(doc_cfg, doc_auto_cfg),
+    deny(rustdoc::broken_intra_doc_links)
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
+)]
+#![cfg_attr(test, deny(warnings))]
+// the telemetry models and conversions are shared by the signals
+#![cfg_attr(
+    not(all(feature = "trace", feature = "logs", feature = "metrics")),
+    allow(dead_code)
+)]
+
+use std::{sync::Arc, time::Duration};
+
+use http::{header::CONTENT_TYPE, Method, Request, StatusCode, Uri};
+use opentelemetry::ExportError;
+use opentelemetry_http::{HttpClient, HttpError};
+use opentelemetry_sdk::Resource;
+
+mod common;
+mod connection_string;
+#[cfg(feature = "logs")]
+mod logs;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod models;
+mod retry;
+#[cfg(feature = "trace")]
+mod trace;
+
+use connection_string::ConnectionString;
+use models::{Envelope, Tags, TrackError, TrackResponse};
+
+/// Exports telemetry to Azure Monitor Application Insights.
+///
+/// The exporter implements the span, log and metric exporter traits of the
+/// SDK for the enabled signals, a clone can be used for each of them.
+#[derive(Clone, Debug)]
+pub struct Exporter {
+    uploader: Uploader,
+    instrumentation_key: String,
+    resource_tags: Arc<Tags>,
+}
+
+impl Exporter {
+    /// Creates a builder for an exporter sending telemetry to the
+    /// Application Insights resource of `connection_string`.
+    ///
+    /// The connection string is found in the overview of the resource in the
+    /// Azure portal, and is usually provided to applications through the
+    /// `APPLICATIONINSIGHTS_CONNECTION_STRING` environment variable.
+    pub fn builder(connection_string: impl Into<String>) -> ExporterBuilder {
+        ExporterBuilder {
+            connection_string: connection_string.into(),
+            client: None,
+            max_attempts: retry::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Wraps `data` with the context of the exported application into an
+    /// envelope.
+    fn envelope(
+        &self,
+        data: models::Data,
+        time: String,
+        resource: Option<&Resource>,
+        operation: Tags,
+    ) -> Envelope {
+        let mut tags = match resource {
+            Some(resource) => common::resource_tags(resource),
+            None => Tags::clone(&self.resource_tags),
+        };
+        tags.extend(operation);
+
+        Envelope {
+            name: data.envelope_name(),
+            time,
+            instrumentation_key: self.instrumentation_key.clone(),
+            tags,
+            data,
+        }
+    }
+
+    /// Updates the context tags describing the exported application.
+    fn set_resource_tags(&mut self, resource: &Resource) {
+        self.resource_tags = Arc::new(common::resource_tags(resource));
+    }
+}
+
+/// Builder for [`Exporter`].
+#[derive(Debug)]
+pub struct ExporterBuilder {
+    connection_string: String,
+    client: Option<Arc<dyn HttpClient>>,
+    max_attempts: usize,
+}
+
+impl ExporterBuilder {
+    /// Sets the client sending the telemetry.
+    pub fn with_http_client<T: HttpClient + 'static>(mut self, client: T) -> Self {
+        self.client = Some(Arc::new(client));
+        self
+    }
+
+    /// Sets the maximum number of attempts to send a batch, including the
+    /// first one, 3 by default.
+    ///
+    /// Telemetry the track API rejects with a transient error, e.g. because
+    /// it throttles the application with `429 Too Many Requests` or is
+    /// unavailable, is sent again with exponential backoff starting at 1
+    /// second, or after the delay the API asks for. Set to 1 to never send
+    /// telemetry again.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Creates the exporter.
+    ///
+    /// Returns an error if the connection string is invalid, or if no client
+    /// was set and the `reqwest-client` feature is disabled.
+    pub fn build(self) -> Result<Exporter, Error> {
+        let connection_string = ConnectionString::parse(&self.connection_string)?;
+        let endpoint = format!("{}/v2.1/track", connection_string.ingestion_endpoint).parse()?;
+
+        #[cfg(feature = "reqwest-client")]
+        let client = self
+            .client
+            .unwrap_or_else(|| Arc::new(reqwest::Client::new()));
+        #[cfg(not(feature = "reqwest-client"))]
+        let client = self.client.ok_or(Error::NoHttpClient)?;
+
+        Ok(Exporter {
+            uploader: Uploader {
+                client,
+                endpoint,
+                max_attempts: self.max_attempts,
+                initial_backoff: retry::INITIAL_BACKOFF,
+            },
+            instrumentation_key: connection_string.instrumentation_key,
+            resource_tags: Arc::new(common::resource_tags(&Resource::empty())),
+        })
+    }
+}
+
+/// Sends telemetry items to the track API.
+#[derive(Clone, Debug)]
+struct Uploader {
+    client: Arc<dyn HttpClient>,
+    endpoint: Uri,
+    max_attempts: usize,
+    initial_backoff: Duration,
+}
+
+/// The outcome of one attempt to send telemetry items.
+enum Attempt {
+    /// All items were accepted, or the request failed permanently.
+    Done(Result<(), Error>),
+    /// Some or all items failed transiently and may be sent again.
+    Retry {
+        /// The error to report if the items are not sent again.
+        error: Error,
+        /// The indices of the items to send again.
+        items: Vec<usize>,
+        /// Items rejected permanently, which are not sent again.
+        rejected: Option<Error>,
+        /// The delay the track API asked for before the next attempt.
+        delay: Option<Duration>,
+    },
+}
+
+impl Uploader {
+    /// Sends `envelopes`, sending the items failing with a transient error
+    /// again with exponential backoff, at most `max_attempts` times.
+    async fn send(&self, mut envelopes: Vec<Envelope>) -> Result<(), Error> {
+        let mut attempts = 1;
+        let mut backoff = self.initial_backoff;
+        let mut rejected = None;
+        loop {
+            let (error, items, delay) = match self.attempt(&envelopes).await {
+                Attempt::Done(result) => return rejected.map_or(result, Err),
+                Attempt::Retry {
+                    error,
+                    items,
+                    rejected: newly_rejected,
+                    delay,
+                } => {
+                    rejected = rejected.or(newly_rejected);
+                    (error, items, delay)
+                }
+            };
+            if attempts >= self.max_attempts {
+                return Err(error);
+            }
+
+            let mut index = 0;
+            envelopes.retain(|_| {
+                index += 1;
+                items.contains(&(index - 1))
+            });
+            retry::sleep(delay.unwrap_or(backoff)).await;
+            attempts += 1;
+            backoff = (backoff * 2).min(retry::MAX_BACKOFF);
+        }
+    }
+
+    async fn attempt(&self, envelopes: &[Envelope]) -> Attempt {
+        if envelopes.is_empty() {
+            return Attempt::Done(Ok(()));
+        }
+        match self.post(envelopes).await {
+            Ok(attempt) => attempt,
+            Err(err) => Attempt::Done(Err(err)),
+        }
+    }
+
+    async fn post(&self, envelopes: &[Envelope]) -> Result<Attempt, Error> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(self.endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(envelopes)?)?;
+        let response = self
+            .client
+            .send(request)
+            .await
+            .map_err(Error::RequestFailed)?;
+        let delay = retry::retry_after(response.headers());
+
+        match response.status() {
+            StatusCode::OK => Ok(Attempt::Done(Ok(()))),
+            // some items were rejected, the response tells which ones
+            StatusCode::PARTIAL_CONTENT => {
+                let track: TrackResponse = serde_json::from_slice(response.body())?;
+                if track.items_accepted == track.items_received {
+                    return Ok(Attempt::Done(Ok(())));
+                }
+                let partially_accepted = |error: Option<&TrackError>| Error::PartiallyAccepted {
+                    accepted: track.items_accepted,
+                    received: track.items_received,
+                    message: error
+                        .map(|error| {
+                            format!(
+                                "item {} rejected with status {}: {}",
+                                error.index, error.status_code, error.message
+                            )
+                        })
+                        .unwrap_or_default(),
+                };
+                let (retryable, permanent): (Vec<_>, Vec<_>) =
+                    track.errors.iter().partition(|error| {
+                        StatusCode::from_u16(error.status_code).is_ok_and(retry::is_retryable)
+                    });
+                if retryable.is_empty() {
+                    return Ok(Attempt::Done(Err(partially_accepted(track.errors.first()))));
+                }
+                Ok(Attempt::Retry {
+                    error: partially_accepted(retryable.first().copied()),
+                    items: retryable.iter().map(|error| error.index).collect(),
+                    rejected: permanent
+                        .first()
+                        .map(|error| partially_accepted(Some(error))),
+                    delay,
+                })
+            }
+            status if retry::is_retryable(status) => Ok(Attempt::Retry {
+                error: Error::Rejected(status),
+                items: (0..envelopes.len()).collect(),
+                rejected: None,
+                delay,
+            }),
+            status => Ok(Attempt::Done(Err(Error::Rejected(status)))),
+        }
+    }
+}
+
+/// Errors of the Azure Monitor exporter.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The connection string does not identify an Application Insights
+    /// resource.
+    #[error("invalid connection string: {0}")]
+    InvalidConnectionString(String),
+
+    /// No http client implementation found. User should provide one or enable
+    /// the `reqwest-client` feature.
+    #[error("http client must be set, users can enable the reqwest-client feature to use http client implementation within crate")]
+    NoHttpClient,
+
+    /// The ingestion endpoint is not a valid uri.
+    #[error("invalid uri")]
+    InvalidUri(#[from] http::uri::InvalidUri),
+
+    /// The request could not be built.
+    #[error("http request failed with {0}")]
+    InvalidRequest(#[from] http::Error),
+
+    /// The request could not be sent.
+    #[error("failed to send telemetry: {0}")]
+    RequestFailed(#[source] HttpError),
+
+    /// The telemetry or the response could not be (de)serialized.
+    #[error("serialization failed with {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// The track API answered with an unexpected status.
+    #[error("telemetry rejected with status {0}")]
+    Rejected(StatusCode),
+
+    /// The track API rejected some of the telemetry items.
+    #[error("{accepted} of {received} telemetry items accepted, {message}")]
+    PartiallyAccepted {
+        /// The number of accepted items.
+        accepted: usize,
+        /// The number of items sent.
+        received: usize,
+        /// The reason the first item was rejected.
+        message: String,
+    },
+}
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "azure-monitor"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use http::{Request, Response, StatusCode};
+    use opentelemetry_http::{HttpClient, HttpError};
+
+    use crate::{models::Envelope, Error, Exporter};
+
+    /// Records the requests and answers with the given responses, repeating
+    /// the last one.
+    #[derive(Clone, Debug)]
+    pub(crate) struct TestClient {
+        pub(crate) requests: Arc<Mutex<Vec<Request<Vec<u8>>>>>,
+        responses: Arc<Mutex<VecDeque<(StatusCode, &'static str)>>>,
+    }
+
+    impl TestClient {
+        pub(crate) fn new(status: StatusCode, body: &'static str) -> Self {
+            Self::with_responses(vec![(status, body)])
+        }
+
+        pub(crate) fn with_responses(responses: Vec<(StatusCode, &'static str)>) -> Self {
+            TestClient {
+                requests: Default::default(),
+                responses: Arc::new(Mutex::new(responses.into())),
+            }
+        }
+
+        /// The telemetry items sent by all requests.
+        pub(crate) fn items(&self) -> Vec<serde_json::Value> {
+            self.requests
+                .lock()
+                .unwrap()
+                .iter()
+                .flat_map(|request| {
+                    serde_json::from_slice::<Vec<serde_json::Value>>(request.body()).unwrap()
+                })
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for TestClient {
+        async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
+            self.requests.lock().unwrap().push(request);
+            let mut responses = self.responses.lock().unwrap();
+            let (status, body) = if responses.len() > 1 {
+                responses.pop_front().unwrap()
+            } else {
+                responses[0]
+            };
+            Ok(Response::builder()
+                .status(status)
+                .body(Bytes::from_static(body.as_bytes()))?)
+        }
+    }
+
+    pub(crate) fn exporter(client: TestClient) -> Exporter {
+        let mut exporter =
+            Exporter::builder("InstrumentationKey=key;IngestionEndpoint=https://ingest.test/")
+                .with_http_client(client)
+                .build()
+                .unwrap();
+        exporter.uploader.initial_backoff = Duration::from_millis(1);
+        exporter
+    }
+
+    fn message(exporter: &Exporter, message: &str) -> Envelope {
+        let data = crate::models::Data::Message(crate::models::MessageData {
+            ver: 2,
+            message: message.into(),
+            severity_level: None,
+            properties: Default::default(),
+        });
+        exporter.envelope(data, "time".into(), None, Default::default())
+    }
+
+    #[test]
+    fn send_to_track_endpoint() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let exporter = exporter(client.clone());
+
+        let data = crate::models::Data::Message(crate::models::MessageData {
+            ver: 2,
+            message: "hello".into(),
+            severity_level: None,
+            properties: Default::default(),
+        });
+        let envelope = exporter.envelope(data, "time".into(), None, Default::default());
+        futures_executor::block_on(exporter.uploader.send(vec![envelope])).unwrap();
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests[0].uri(), "https://ingest.test/v2.1/track");
+        drop(requests);
+        let items = client.items();
+        assert_eq!(items[0]["name"], "Microsoft.ApplicationInsights.Message");
+        assert_eq!(items[0]["iKey"], "key");
+        assert_eq!(items[0]["data"]["baseType"], "MessageData");
+        assert_eq!(items[0]["data"]["baseData"]["message"], "hello");
+    }
+
+    #[test]
+    fn report_partially_accepted_telemetry() {
+        let client = TestClient::new(
+            StatusCode::PARTIAL_CONTENT,
+            r#"{"itemsReceived":2,"itemsAccepted":1,"errors":[{"index":1,"statusCode":400,"message":"invalid"}]}"#,
+        );
+        let exporter = exporter(client);
+
+        let envelopes = (0..2)
+            .map(|_| {
+                let data = crate::models::Data::Message(crate::models::MessageData {
+                    ver: 2,
+                    message: "hello".into(),
+                    severity_level: None,
+                    properties: Default::default(),
+                });
+                exporter.envelope(data, "time".into(), None, Default::default())
+            })
+            .collect();
+        let result = futures_executor::block_on(exporter.uploader.send(envelopes));
+
+        assert!(matches!(
+            result,
+            Err(Error::PartiallyAccepted {
+                accepted: 1,
+                received: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn retry_throttled_and_failed_requests() {
+        let client = TestClient::with_responses(vec![
+            (StatusCode::TOO_MANY_REQUESTS, ""),
+            (StatusCode::SERVICE_UNAVAILABLE, ""),
+            (StatusCode::OK, ""),
+        ]);
+        let exporter = exporter(client.clone());
+
+        let envelopes = vec![message(&exporter, "hello")];
+        futures_executor::block_on(exporter.uploader.send(envelopes)).unwrap();
+
+        assert_eq!(client.requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn give_up_after_max_attempts() {
+        let client = TestClient::new(StatusCode::SERVICE_UNAVAILABLE, "");
+        let exporter = exporter(client.clone());
+
+        let envelopes = vec![message(&exporter, "hello")];
+        let result = futures_executor::block_on(exporter.uploader.send(envelopes));
+
+        assert!(matches!(
+            result,
+            Err(Error::Rejected(StatusCode::SERVICE_UNAVAILABLE))
+        ));
+        assert_eq!(client.requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn retry_only_transiently_rejected_items() {
+        let client = TestClient::with_responses(vec![
+            (
+                StatusCode::PARTIAL_CONTENT,
+                r#"{"itemsReceived":3,"itemsAccepted":1,"errors":[{"index":0,"statusCode":400,"message":"invalid"},{"index":2,"statusCode":500,"message":"retry"}]}"#,
+            ),
+            (StatusCode::OK, ""),
+        ]);
+        let exporter = exporter(client.clone());
+
+        let envelopes = ["invalid", "accepted", "throttled"]
+            .into_iter()
+            .map(|text| message(&exporter, text))
+            .collect();
+        let result = futures_executor::block_on(exporter.uploader.send(envelopes));
+
+        // the permanently rejected item is still reported
+        assert!(matches!(
+            result,
+            Err(Error::PartiallyAccepted {
+                accepted: 1,
+                received: 3,
+                ..
+            })
+        ));
+        let messages: Vec<_> = client
+            .items()
+            .iter()
+            .map(|item| item["data"]["baseData"]["message"].clone())
+            .collect();
+        assert_eq!(messages, ["invalid", "accepted", "throttled", "throttled"]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Exporting log records as traces and exceptions.
+use std::{borrow::Cow, time::SystemTime};
+
+use async_trait::async_trait;
+use opentelemetry::{
+    logs::{AnyValue, LogError, LogResult, Severity},
+    Key,
+};
+use opentelemetry_sdk::{
+    export::logs::{LogData, LogExporter},
+    Resource,
+};
+
+use crate::{
+    common::format_time,
+    models::{
+        Data, Envelope, ExceptionData, ExceptionDetails, MessageData, Properties, SeverityLevel,
+        Tags,
+    },
+    Exporter,
+};
+
+#[async_trait]
+impl LogExporter for Exporter {
+    async fn export<'a>(&mut self, batch: Vec<Cow<'a, LogData>>) -> LogResult<()> {
+        let envelopes = batch.iter().map(|log| self.log_envelope(log)).collect();
+        self.uploader.send(envelopes).await.map_err(LogError::from)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.set_resource_tags(resource);
+    }
+}
+
+impl Exporter {
+    /// Converts a log record to a telemetry item.
+    fn log_envelope(&self, log: &LogData) -> Envelope {
+        let record = &log.record;
+        let mut operation = Tags::new();
+        if let Some(trace_context) = &record.trace_context {
+            operation.insert("ai.operation.id", trace_context.trace_id.to_string());
+            operation.insert("ai.operation.parentId", trace_context.span_id.to_string());
+        }
+
+        let attributes = record.attributes.as_deref().unwrap_or_default();
+        let properties: Properties = attributes
+            .iter()
+            .map(|(key, value)| (key.to_string(), any_value_to_string(value)))
+            .collect();
+        let severity_level = record.severity_number.map(severity_level);
+        let attribute = |name: &'static str| {
+            let name = Key::from_static_str(name);
+            attributes
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| any_value_to_string(value))
+        };
+
+        let data = match (attribute("exception.type"), attribute("exception.message")) {
+            (None, None) => Data::Message(MessageData {
+                ver: 2,
+                message: record
+                    .body
+                    .as_ref()
+                    .map(any_value_to_string)
+                    .or_else(|| record.event_name.as_ref().map(|name| name.to_string()))
+                    .unwrap_or_default(),
+                severity_level,
+                properties,
+            }),
+            (type_name, message) => {
+                let stack = attribute("exception.stacktrace");
+                Data::Exception(ExceptionData {
+                    ver: 2,
+                    exceptions: vec![ExceptionDetails {
+                        type_name: type_name.unwrap_or_else(|| "<no type>".to_owned()),
+                        message: message.unwrap_or_else(|| "<no message>".to_owned()),
+                        has_full_stack: stack.is_some(),
+                        stack,
+                    }],
+                    severity_level,
+                    properties,
+                })
+            }
+        };
+
+        let time = record
+            .timestamp
+            .or(record.observed_timestamp)
+            .unwrap_or_else(SystemTime::now);
+        self.envelope(data, format_time(time), log.resource.as_ref(), operation)
+    }
+}
+
+/// Maps the OpenTelemetry severity ranges to the Application Insights levels.
+fn severity_level(severity: Severity) -> SeverityLevel {
+    match severity as u8 {
+        0..=8 => SeverityLevel::Verbose,
+        9..=12 => SeverityLevel::Information,
+        13..=16 => SeverityLevel::Warning,
+        17..=20 => SeverityLevel::Error,
+        _ => SeverityLevel::Critical,
+    }
+}
+
+/// Formats strings as is, and lists and maps as JSON.
+fn any_value_to_string(value: &AnyValue) -> String {
+    match value {
+        AnyValue::String(value) => value.to_string(),
+        value => any_value_to_json(value).to_string(),
+    }
+}
+
+fn any_value_to_json(value: &AnyValue) -> serde_json::Value {
+    match value {
+        AnyValue::Int(value) => (*value).into(),
+        AnyValue::Double(value) => (*value).into(),
+        AnyValue::String(value) => value.as_str().into(),
+        AnyValue::Boolean(value) => (*value).into(),
+        AnyValue::Bytes(value) => value.as_slice().into(),
+        AnyValue::ListAny(values) => values.iter().map(any_value_to_json).collect(),
+        AnyValue::Map(values) => values
+            .iter()
+            .map(|(key, value)| (key.to_string(), any_value_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        borrow::Cow,
+        time::{Duration, SystemTime},
+    };
+
+    use http::StatusCode;
+    use opentelemetry::{
+        logs::{AnyValue, Severity},
+        trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState},
+        InstrumentationLibrary, Key,
+    };
+    use opentelemetry_sdk::{
+        export::logs::{LogData, LogExporter},
+        logs::{LogRecord, TraceContext},
+    };
+
+    use crate::tests::{exporter, TestClient};
+
+    fn log(record: LogRecord) -> LogData {
+        LogData {
+            record,
+            instrumentation: InstrumentationLibrary::default(),
+            resource: None,
+        }
+    }
+
+    #[test]
+    fn export_record_as_message() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let mut exporter = exporter(client.clone());
+
+        let mut record = LogRecord::default();
+        record.timestamp = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        record.severity_number = Some(Severity::Warn2);
+        record.body = Some("cart is empty".into());
+        record.attributes = Some(vec![(
+            Key::new("items"),
+            AnyValue::ListAny(vec![1.into(), "two".into()]),
+        )]);
+        record.trace_context = Some(TraceContext::from(&SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(2),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        )));
+        futures_executor::block_on(exporter.export(vec![Cow::Owned(log(record))])).unwrap();
+
+        let items = client.items();
+        assert_eq!(items[0]["time"], "1970-01-01T00:00:01.000000Z");
+        assert_eq!(
+            items[0]["tags"]["ai.operation.id"],
+            "00000000000000000000000000000001"
+        );
+        assert_eq!(
+            items[0]["tags"]["ai.operation.parentId"],
+            "0000000000000002"
+        );
+        let data = &items[0]["data"]["baseData"];
+        assert_eq!(items[0]["data"]["baseType"], "MessageData");
+        assert_eq!(data["message"], "cart is empty");
+        assert_eq!(data["severityLevel"], "Warning");
+        assert_eq!(data["properties"]["items"], r#"[1,"two"]"#);
+    }
+
+    #[test]
+    fn export_record_with_exception_as_exception() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let mut exporter = exporter(client.clone());
+
+        let mut record = LogRecord::default();
+        record.severity_number = Some(Severity::Fatal);
+        record.attributes = Some(vec![
+            (Key::new("exception.type"), "Panic".into()),
+            (Key::new("exception.message"), "out of memory".into()),
+        ]);
+        futures_executor::block_on(exporter.export(vec![Cow::Owned(log(record))])).unwrap();
+
+        let items = client.items();
+        let data = &items[0]["data"]["baseData"];
+        assert_eq!(items[0]["data"]["baseType"], "ExceptionData");
+        assert_eq!(data["severityLevel"], "Critical");
+        assert_eq!(data["exceptions"][0]["typeName"], "Panic");
+        assert_eq!(data["exceptions"][0]["message"], "out of memory");
+        assert_eq!(data["exceptions"][0]["hasFullStack"], false);
+    }
+}
@@ -0,0 +1,352 @@
+//! Exporting metric data points as metrics.
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use opentelemetry::{
+    global,
+    metrics::{MetricsError, Result},
+    KeyValue,
+};
+use opentelemetry_sdk::metrics::{
+    data::{self, ExponentialHistogram, Gauge, Histogram, ResourceMetrics, Sum, Temporality},
+    exporter::PushMetricsExporter,
+    reader::{AggregationSelector, DefaultAggregationSelector, TemporalitySelector},
+    Aggregation, InstrumentKind,
+};
+
+use crate::{
+    common::{format_time, properties, resource_tags},
+    models::{Data, Envelope, MetricData, MetricDataPoint, Tags},
+    Exporter,
+};
+
+impl TemporalitySelector for Exporter {
+    /// Application Insights aggregates the values of each interval, only up
+    /// down counters are reported as their current value.
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        match kind {
+            InstrumentKind::UpDownCounter | InstrumentKind::ObservableUpDownCounter => {
+                Temporality::Cumulative
+            }
+            _ => Temporality::Delta,
+        }
+    }
+}
+
+impl AggregationSelector for Exporter {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        DefaultAggregationSelector::new().aggregation(kind)
+    }
+}
+
+#[async_trait]
+impl PushMetricsExporter for Exporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        let tags = resource_tags(&metrics.resource);
+        let mut envelopes = Vec::new();
+        for metric in metrics
+            .scope_metrics
+            .iter()
+            .flat_map(|scope| &scope.metrics)
+        {
+            let data = metric.data.as_any();
+            if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+                self.push_data_points(&mut envelopes, &tags, &metric.name, &sum.data_points);
+            } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+                self.push_data_points(&mut envelopes, &tags, &metric.name, &sum.data_points);
+            } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+                self.push_data_points(&mut envelopes, &tags, &metric.name, &sum.data_points);
+            } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+                self.push_data_points(&mut envelopes, &tags, &metric.name, &gauge.data_points);
+            } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+                self.push_data_points(&mut envelopes, &tags, &metric.name, &gauge.data_points);
+            } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+                self.push_data_points(&mut envelopes, &tags, &metric.name, &gauge.data_points);
+            } else if let Some(histogram) = data.downcast_ref::<Histogram<u64>>() {
+                self.push_histogram(&mut envelopes, &tags, &metric.name, histogram);
+            } else if let Some(histogram) = data.downcast_ref::<Histogram<i64>>() {
+                self.push_histogram(&mut envelopes, &tags, &metric.name, histogram);
+            } else if let Some(histogram) = data.downcast_ref::<Histogram<f64>>() {
+                self.push_histogram(&mut envelopes, &tags, &metric.name, histogram);
+            } else if let Some(histogram) = data.downcast_ref::<ExponentialHistogram<u64>>() {
+                self.push_exponential_histogram(&mut envelopes, &tags, &metric.name, histogram);
+            } else if let Some(histogram) = data.downcast_ref::<ExponentialHistogram<i64>>() {
+                self.push_exponential_histogram(&mut envelopes, &tags, &metric.name, histogram);
+            } else if let Some(histogram) = data.downcast_ref::<ExponentialHistogram<f64>>() {
+                self.push_exponential_histogram(&mut envelopes, &tags, &metric.name, histogram);
+            } else {
+                // don't fail the export of the other metrics
+                global::handle_error(MetricsError::Other(format!(
+                    "skipping metric {}, its aggregation is not supported",
+                    metric.name
+                )));
+            }
+        }
+
+        self.uploader
+            .send(envelopes)
+            .await
+            .map_err(MetricsError::from)
+    }
+
+    async fn force_flush(&self) -> Result<()> {
+        // metrics are sent as they are exported, nothing to flush
+        Ok(())
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Exporter {
+    fn push_data_points<T: Number>(
+        &self,
+        envelopes: &mut Vec<Envelope>,
+        tags: &Tags,
+        name: &str,
+        data_points: &[data::DataPoint<T>],
+    ) {
+        for data_point in data_points {
+            let point = MetricDataPoint {
+                name: name.to_owned(),
+                value: data_point.value.into_f64(),
+                count: None,
+                min: None,
+                max: None,
+            };
+            envelopes.push(self.metric_envelope(
+                tags,
+                point,
+                data_point.time.unwrap_or_else(SystemTime::now),
+                &data_point.attributes,
+            ));
+        }
+    }
+
+    fn push_histogram<T: Number>(
+        &self,
+        envelopes: &mut Vec<Envelope>,
+        tags: &Tags,
+        name: &str,
+        histogram: &Histogram<T>,
+    ) {
+        for data_point in &histogram.data_points {
+            let point = MetricDataPoint {
+                name: name.to_owned(),
+                value: data_point.sum.into_f64(),
+                count: Some(data_point.count),
+                min: data_point.min.map(Number::into_f64),
+                max: data_point.max.map(Number::into_f64),
+            };
+            envelopes.push(self.metric_envelope(
+                tags,
+                point,
+                data_point.time,
+                &data_point.attributes,
+            ));
+        }
+    }
+
+    fn push_exponential_histogram<T: Number>(
+        &self,
+        envelopes: &mut Vec<Envelope>,
+        tags: &Tags,
+        name: &str,
+        histogram: &ExponentialHistogram<T>,
+    ) {
+        for data_point in &histogram.data_points {
+            let point = MetricDataPoint {
+                name: name.to_owned(),
+                value: data_point.sum.into_f64(),
+                count: Some(data_point.count as u64),
+                min: data_point.min.map(Number::into_f64),
+                max: data_point.max.map(Number::into_f64),
+            };
+            envelopes.push(self.metric_envelope(
+                tags,
+                point,
+                data_point.time,
+                &data_point.attributes,
+            ));
+        }
+    }
+
+    /// Wraps a data point of the resource described by `tags` into an
+    /// envelope, with its attributes as custom dimensions.
+    fn metric_envelope(
+        &self,
+        tags: &Tags,
+        point: MetricDataPoint,
+        time: SystemTime,
+        attributes: &[KeyValue],
+    ) -> Envelope {
+        let data = Data::Metric(MetricData {
+            ver: 2,
+            metrics: vec![point],
+            properties: properties(attributes),
+        });
+        Envelope {
+            name: data.envelope_name(),
+            time: format_time(time),
+            instrumentation_key: self.instrumentation_key.clone(),
+            tags: tags.clone(),
+            data,
+        }
+    }
+}
+
+/// The value types of instruments.
+trait Number: Copy {
+    fn into_f64(self) -> f64;
+}
+
+impl Number for u64 {
+    fn into_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Number for i64 {
+    fn into_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Number for f64 {
+    fn into_f64(self) -> f64 {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt,
+        time::{Duration, SystemTime},
+    };
+
+    use http::StatusCode;
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::{
+        metrics::{
+            data::{
+                DataPoint, Gauge, Histogram, HistogramDataPoint, Metric, ResourceMetrics,
+                ScopeMetrics, Sum, Temporality,
+            },
+            exporter::PushMetricsExporter,
+        },
+        Resource,
+    };
+
+    use crate::tests::{exporter, TestClient};
+
+    #[test]
+    fn export_sum_and_histogram() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let exporter = exporter(client.clone());
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::new([KeyValue::new("service.name", "cart")]),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Default::default(),
+                metrics: vec![
+                    Metric {
+                        name: "orders".into(),
+                        description: "".into(),
+                        unit: "".into(),
+                        data: Box::new(Sum {
+                            data_points: vec![DataPoint {
+                                attributes: vec![KeyValue::new("region", "eu")],
+                                start_time: None,
+                                time: Some(time),
+                                value: 5u64,
+                                exemplars: vec![],
+                            }],
+                            temporality: Temporality::Delta,
+                            is_monotonic: true,
+                        }),
+                    },
+                    Metric {
+                        name: "latency".into(),
+                        description: "".into(),
+                        unit: "ms".into(),
+                        data: Box::new(Histogram {
+                            data_points: vec![HistogramDataPoint {
+                                attributes: vec![],
+                                start_time: time,
+                                time,
+                                count: 2,
+                                bounds: vec![],
+                                bucket_counts: vec![2],
+                                min: Some(1.0),
+                                max: Some(3.0),
+                                sum: 4.0f64,
+                                exemplars: vec![],
+                            }],
+                            temporality: Temporality::Delta,
+                        }),
+                    },
+                ],
+            }],
+        };
+        futures_executor::block_on(exporter.export(&mut metrics)).unwrap();
+
+        let items = client.items();
+        assert_eq!(items.len(), 2);
+
+        let orders = &items[0];
+        assert_eq!(orders["name"], "Microsoft.ApplicationInsights.Metric");
+        assert_eq!(orders["time"], "1970-01-01T00:00:01.000000Z");
+        assert_eq!(orders["tags"]["ai.cloud.role"], "cart");
+        assert_eq!(orders["data"]["baseData"]["metrics"][0]["name"], "orders");
+        assert_eq!(orders["data"]["baseData"]["metrics"][0]["value"], 5.0);
+        assert_eq!(orders["data"]["baseData"]["properties"]["region"], "eu");
+
+        let point = &items[1]["data"]["baseData"]["metrics"][0];
+        assert_eq!(point["name"], "latency");
+        assert_eq!(point["value"], 4.0);
+        assert_eq!(point["count"], 2);
+        assert_eq!(point["min"], 1.0);
+        assert_eq!(point["max"], 3.0);
+    }
+
+    #[test]
+    fn skip_metrics_with_unsupported_aggregation() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let exporter = exporter(client.clone());
+        fn gauge<T: fmt::Debug + Send + Sync + 'static>(name: &'static str, value: T) -> Metric {
+            Metric {
+                name: name.into(),
+                description: "".into(),
+                unit: "".into(),
+                data: Box::new(Gauge {
+                    data_points: vec![DataPoint {
+                        attributes: vec![],
+                        start_time: None,
+                        time: None,
+                        value,
+                        exemplars: vec![],
+                    }],
+                }),
+            }
+        }
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Default::default(),
+                // gauges of i32 are not produced by the SDK
+                metrics: vec![gauge("unsupported", 1i32), gauge("queue_length", 2i64)],
+            }],
+        };
+        futures_executor::block_on(exporter.export(&mut metrics)).unwrap();
+
+        let items = client.items();
+        assert_eq!(items.len(), 1);
+        let point = &items[0]["data"]["baseData"]["metrics"][0];
+        assert_eq!(point["name"], "queue_length");
+        assert_eq!(point["value"], 2.0);
+    }
+}
@@ -0,0 +1,179 @@
+//! The telemetry items of the Application Insights track API.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Custom dimensions of a telemetry item.
+pub(crate) type Properties = BTreeMap<String, String>;
+
+/// Context tags of a telemetry item, e.g. `ai.operation.id`.
+pub(crate) type Tags = BTreeMap<&'static str, String>;
+
+/// A telemetry item with its context.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Envelope {
+    pub(crate) name: &'static str,
+    pub(crate) time: String,
+    #[serde(rename = "iKey")]
+    pub(crate) instrumentation_key: String,
+    pub(crate) tags: Tags,
+    pub(crate) data: Data,
+}
+
+/// The data of a telemetry item, tagged with its type.
+#[derive(Debug, Serialize)]
+#[serde(tag = "baseType", content = "baseData")]
+pub(crate) enum Data {
+    #[serde(rename = "RequestData")]
+    Request(RequestData),
+    #[serde(rename = "RemoteDependencyData")]
+    RemoteDependency(RemoteDependencyData),
+    #[serde(rename = "MessageData")]
+    Message(MessageData),
+    #[serde(rename = "ExceptionData")]
+    Exception(ExceptionData),
+    #[serde(rename = "MetricData")]
+    Metric(MetricData),
+}
+
+impl Data {
+    /// The name of the envelopes carrying this type of data.
+    pub(crate) fn envelope_name(&self) -> &'static str {
+        match self {
+            Data::Request(_) => "Microsoft.ApplicationInsights.Request",
+            Data::RemoteDependency(_) => "Microsoft.ApplicationInsights.RemoteDependency",
+            Data::Message(_) => "Microsoft.ApplicationInsights.Message",
+            Data::Exception(_) => "Microsoft.ApplicationInsights.Exception",
+            Data::Metric(_) => "Microsoft.ApplicationInsights.Metric",
+        }
+    }
+}
+
+/// An incoming request handled by the application.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RequestData {
+    pub(crate) ver: u8,
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) duration: String,
+    pub(crate) response_code: String,
+    pub(crate) success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<String>,
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    pub(crate) properties: Properties,
+}
+
+/// An outgoing call of the application, or an internal operation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteDependencyData {
+    pub(crate) ver: u8,
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) duration: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) result_code: Option<String>,
+    pub(crate) success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) target: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub(crate) dependency_type: Option<String>,
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    pub(crate) properties: Properties,
+}
+
+/// A trace message.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageData {
+    pub(crate) ver: u8,
+    pub(crate) message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) severity_level: Option<SeverityLevel>,
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    pub(crate) properties: Properties,
+}
+
+/// An exception raised by the application.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExceptionData {
+    pub(crate) ver: u8,
+    pub(crate) exceptions: Vec<ExceptionDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) severity_level: Option<SeverityLevel>,
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    pub(crate) properties: Properties,
+}
+
+/// The type, message and stack of an exception.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExceptionDetails {
+    pub(crate) type_name: String,
+    pub(crate) message: String,
+    pub(crate) has_full_stack: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) stack: Option<String>,
+}
+
+/// Aggregated metric values.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MetricData {
+    pub(crate) ver: u8,
+    pub(crate) metrics: Vec<MetricDataPoint>,
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    pub(crate) properties: Properties,
+}
+
+/// A single aggregated metric value.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MetricDataPoint {
+    pub(crate) name: String,
+    pub(crate) value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max: Option<f64>,
+}
+
+/// The severity levels of messages and exceptions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub(crate) enum SeverityLevel {
+    Verbose,
+    Information,
+    Warning,
+    Error,
+    Critical,
+}
+
+/// The response of the track API when only some items were accepted.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrackResponse {
+    pub(crate) items_received: usize,
+    pub(crate) items_accepted: usize,
+    #[serde(default)]
+    pub(crate) errors: Vec<TrackError>,
+}
+
+/// An item rejected by the track API.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrackError {
+    pub(crate) index: usize,
+    pub(crate) status_code: u16,
+    #[serde(default)]
+    pub(crate) message: String,
+}
@@ -0,0 +1,113 @@
+//! Retrying telemetry the track API failed to accept transiently.
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use http::{header::RETRY_AFTER, HeaderMap, StatusCode};
+
+/// The default maximum number of attempts to send a batch, including the
+/// first one.
+pub(crate) const DEFAULT_MAX_ATTEMPTS: usize = 3;
+
+/// The delay before the first retry, doubled for each further retry.
+pub(crate) const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The maximum delay between two attempts.
+pub(crate) const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Whether telemetry rejected with `status` may be accepted when sent again,
+/// i.e. the track API timed out, throttled the application or failed.
+pub(crate) fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+/// The delay the track API asked for before sending telemetry again, if any.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Sleep for `duration` without depending on an async runtime.
+///
+/// The exporter does not know the runtime it runs on, so the delay is driven
+/// by a thread, which is fine for the rare backoffs between retries.
+pub(crate) fn sleep(duration: Duration) -> impl Future<Output = ()> {
+    ThreadDelay {
+        duration,
+        state: None,
+    }
+}
+
+// whether the delay elapsed and the waker to notify when it does
+type DelayState = Arc<Mutex<(bool, Option<Waker>)>>;
+
+struct ThreadDelay {
+    duration: Duration,
+    state: Option<DelayState>,
+}
+
+impl Future for ThreadDelay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let duration = self.duration;
+        let state = self.state.get_or_insert_with(|| {
+            let state: DelayState = Arc::new(Mutex::new((false, None)));
+            let timer_state = Arc::clone(&state);
+            std::thread::spawn(move || {
+                std::thread::sleep(duration);
+                if let Ok(mut state) = timer_state.lock() {
+                    state.0 = true;
+                    if let Some(waker) = state.1.take() {
+                        waker.wake();
+                    }
+                }
+            });
+            state
+        });
+
+        match state.lock() {
+            Ok(mut state) if !state.0 => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            // a poisoned lock means the timer thread is gone, don't wait forever
+            _ => Poll::Ready(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::{header::RETRY_AFTER, HeaderMap, HeaderValue, StatusCode};
+
+    use super::{is_retryable, retry_after};
+
+    #[test]
+    fn retry_throttled_and_failed_requests() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+    }
+}
@@ -0,0 +1,335 @@
+//! Exporting spans as requests, dependencies, traces and exceptions.
+use futures_core::future::BoxFuture;
+use opentelemetry::{
+    trace::{Event, Link, SpanId, SpanKind, Status, TraceError},
+    Key, KeyValue,
+};
+use opentelemetry_sdk::{
+    export::trace::{ExportResult, SpanData, SpanExporter},
+    Resource,
+};
+
+use crate::{
+    common::{format_duration, format_time, properties},
+    models::{
+        Data, Envelope, ExceptionData, ExceptionDetails, MessageData, RemoteDependencyData,
+        RequestData, Tags,
+    },
+    Exporter,
+};
+
+impl SpanExporter for Exporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let envelopes = batch
+            .into_iter()
+            .flat_map(|span| self.span_envelopes(span))
+            .collect();
+        let uploader = self.uploader.clone();
+        Box::pin(async move { uploader.send(envelopes).await.map_err(TraceError::from) })
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.set_resource_tags(resource);
+    }
+}
+
+impl Exporter {
+    /// Converts a span and its events to telemetry items.
+    fn span_envelopes(&self, span: SpanData) -> Vec<Envelope> {
+        let span_id = span.span_context.span_id().to_string();
+        let mut operation = Tags::new();
+        operation.insert("ai.operation.id", span.span_context.trace_id().to_string());
+
+        let mut envelopes = Vec::with_capacity(1 + span.events.len());
+        // events are children of the span in the operation
+        for event in span.events.iter() {
+            let mut event_operation = operation.clone();
+            event_operation.insert("ai.operation.parentId", span_id.clone());
+            envelopes.push(self.envelope(
+                event_data(event),
+                format_time(event.timestamp),
+                span.resource.as_ref(),
+                event_operation,
+            ));
+        }
+
+        if span.parent_span_id != SpanId::INVALID {
+            operation.insert("ai.operation.parentId", span.parent_span_id.to_string());
+        }
+        let duration = format_duration(
+            span.end_time
+                .duration_since(span.start_time)
+                .unwrap_or_default(),
+        );
+        let success = !matches!(span.status, Status::Error { .. });
+        let mut properties = properties(&span.attributes);
+        if !span.links.is_empty() {
+            properties.insert(LINKS_PROPERTY.to_owned(), links_json(&span.links));
+        }
+        let data = match span.span_kind {
+            SpanKind::Server | SpanKind::Consumer => {
+                operation.insert("ai.operation.name", span.name.to_string());
+                Data::Request(RequestData {
+                    ver: 2,
+                    id: span_id,
+                    name: span.name.into_owned(),
+                    duration,
+                    response_code: attribute(
+                        &span.attributes,
+                        &["http.response.status_code", "http.status_code"],
+                    )
+                    .unwrap_or_else(|| "0".to_owned()),
+                    success,
+                    url: attribute(&span.attributes, &["url.full", "http.url"]),
+                    source: None,
+                    properties,
+                })
+            }
+            _ => Data::RemoteDependency(RemoteDependencyData {
+                ver: 2,
+                id: span_id,
+                name: span.name.into_owned(),
+                duration,
+                result_code: attribute(
+                    &span.attributes,
+                    &["http.response.status_code", "http.status_code"],
+                ),
+                success,
+                data: attribute(&span.attributes, &["url.full", "http.url", "db.statement"]),
+                target: attribute(
+                    &span.attributes,
+                    &["server.address", "net.peer.name", "peer.service"],
+                ),
+                dependency_type: dependency_type(&span.span_kind, &span.attributes),
+                properties,
+            }),
+        };
+        envelopes.push(self.envelope(
+            data,
+            format_time(span.start_time),
+            span.resource.as_ref(),
+            operation,
+        ));
+
+        envelopes
+    }
+}
+
+/// The property listing the linked spans of requests and dependencies, as
+/// understood by Application Insights.
+const LINKS_PROPERTY: &str = "_MS.links";
+
+/// Encodes the trace and span id of linked spans as the JSON array expected
+/// in the [`LINKS_PROPERTY`] property.
+fn links_json(links: &[Link]) -> String {
+    let links: Vec<_> = links
+        .iter()
+        .map(|link| {
+            serde_json::json!({
+                "operation_Id": link.span_context.trace_id().to_string(),
+                "id": link.span_context.span_id().to_string(),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(links).to_string()
+}
+
+/// Converts an `exception` event to an exception, and other events to traces.
+fn event_data(event: &Event) -> Data {
+    if event.name == "exception" {
+        let type_name = attribute(&event.attributes, &["exception.type"]);
+        let message = attribute(&event.attributes, &["exception.message"]);
+        let stack = attribute(&event.attributes, &["exception.stacktrace"]);
+        Data::Exception(ExceptionData {
+            ver: 2,
+            exceptions: vec![ExceptionDetails {
+                type_name: type_name.unwrap_or_else(|| "<no type>".to_owned()),
+                message: message.unwrap_or_else(|| "<no message>".to_owned()),
+                has_full_stack: stack.is_some(),
+                stack,
+            }],
+            severity_level: None,
+            properties: properties(&event.attributes),
+        })
+    } else {
+        Data::Message(MessageData {
+            ver: 2,
+            message: event.name.to_string(),
+            severity_level: None,
+            properties: properties(&event.attributes),
+        })
+    }
+}
+
+/// The type of the dependency, e.g. the database or messaging system.
+fn dependency_type(kind: &SpanKind, attributes: &[KeyValue]) -> Option<String> {
+    if let Some(system) = attribute(attributes, &["db.system"]) {
+        return Some(system);
+    }
+    if attribute(attributes, &["http.request.method", "http.method"]).is_some() {
+        return Some("HTTP".to_owned());
+    }
+    if let Some(system) = attribute(attributes, &["rpc.system", "messaging.system"]) {
+        return Some(system);
+    }
+    match kind {
+        SpanKind::Internal => Some("InProc".to_owned()),
+        _ => None,
+    }
+}
+
+/// The value of the first of `keys` found in `attributes`.
+fn attribute(attributes: &[KeyValue], keys: &[&'static str]) -> Option<String> {
+    keys.iter().find_map(|key| {
+        let key = Key::from_static_str(key);
+        attributes
+            .iter()
+            .find(|kv| kv.key == key)
+            .map(|kv| kv.value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use http::StatusCode;
+    use opentelemetry::{
+        trace::{
+            Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+        },
+        KeyValue,
+    };
+    use opentelemetry_sdk::{
+        export::trace::{SpanData, SpanExporter},
+        trace::{SpanEvents, SpanLinks},
+        InstrumentationLibrary, Resource,
+    };
+
+    use crate::tests::{exporter, TestClient};
+
+    fn span(kind: SpanKind, attributes: Vec<KeyValue>, events: Vec<Event>) -> SpanData {
+        let start_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        let mut span_events = SpanEvents::default();
+        span_events.events = events;
+        SpanData {
+            span_context: SpanContext::new(
+                TraceId::from_u128(1),
+                SpanId::from_u64(2),
+                TraceFlags::SAMPLED,
+                false,
+                TraceState::default(),
+            ),
+            parent_span_id: SpanId::from_u64(3),
+            span_kind: kind,
+            name: "GET /cart".into(),
+            start_time,
+            end_time: start_time + Duration::from_millis(250),
+            attributes,
+            dropped_attributes_count: 0,
+            events: span_events,
+            links: SpanLinks::default(),
+            status: Status::Unset,
+            instrumentation_lib: InstrumentationLibrary::default(),
+            resource: None,
+        }
+    }
+
+    #[test]
+    fn export_server_span_as_request() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let mut exporter = exporter(client.clone());
+        exporter.set_resource(&Resource::new([KeyValue::new("service.name", "cart")]));
+
+        let span = span(
+            SpanKind::Server,
+            vec![
+                KeyValue::new("http.response.status_code", 200),
+                KeyValue::new("url.full", "https://shop/cart"),
+            ],
+            vec![Event::new(
+                "exception",
+                SystemTime::UNIX_EPOCH,
+                vec![
+                    KeyValue::new("exception.type", "Timeout"),
+                    KeyValue::new("exception.message", "took too long"),
+                ],
+                0,
+            )],
+        );
+        futures_executor::block_on(exporter.export(vec![span])).unwrap();
+
+        let items = client.items();
+        assert_eq!(items.len(), 2);
+
+        let exception = &items[0];
+        assert_eq!(exception["data"]["baseType"], "ExceptionData");
+        assert_eq!(
+            exception["data"]["baseData"]["exceptions"][0]["typeName"],
+            "Timeout"
+        );
+        assert_eq!(
+            exception["tags"]["ai.operation.parentId"],
+            "0000000000000002"
+        );
+
+        let request = &items[1];
+        assert_eq!(request["data"]["baseType"], "RequestData");
+        assert_eq!(request["time"], "1970-01-01T00:00:01.000000Z");
+        assert_eq!(request["tags"]["ai.cloud.role"], "cart");
+        assert_eq!(
+            request["tags"]["ai.operation.id"],
+            "00000000000000000000000000000001"
+        );
+        assert_eq!(request["tags"]["ai.operation.parentId"], "0000000000000003");
+        assert_eq!(request["tags"]["ai.operation.name"], "GET /cart");
+        let data = &request["data"]["baseData"];
+        assert_eq!(data["id"], "0000000000000002");
+        assert_eq!(data["duration"], "0.00:00:00.250000");
+        assert_eq!(data["responseCode"], "200");
+        assert_eq!(data["success"], true);
+        assert_eq!(data["url"], "https://shop/cart");
+    }
+
+    #[test]
+    fn export_client_span_as_dependency() {
+        let client = TestClient::new(StatusCode::OK, "");
+        let mut exporter = exporter(client.clone());
+
+        let mut span = span(
+            SpanKind::Client,
+            vec![
+                KeyValue::new("db.system", "postgresql"),
+                KeyValue::new("db.statement", "SELECT 1"),
+                KeyValue::new("server.address", "db"),
+            ],
+            vec![],
+        );
+        span.status = Status::error("connection reset");
+        span.links.links.push(Link::new(
+            SpanContext::new(
+                TraceId::from_u128(5),
+                SpanId::from_u64(6),
+                TraceFlags::SAMPLED,
+                true,
+                TraceState::default(),
+            ),
+            vec![],
+            0,
+        ));
+        futures_executor::block_on(exporter.export(vec![span])).unwrap();
+
+        let items = client.items();
+        let data = &items[0]["data"]["baseData"];
+        assert_eq!(items[0]["data"]["baseType"], "RemoteDependencyData");
+        assert_eq!(data["type"], "postgresql");
+        assert_eq!(data["data"], "SELECT 1");
+        assert_eq!(data["target"], "db");
+        assert_eq!(data["success"], false);
+        assert!(items[0]["tags"].get("ai.operation.name").is_none());
+        assert_eq!(
+            data["properties"]["_MS.links"],
+            r#"[{"id":"0000000000000006","operation_Id":"00000000000000000000000000000005"}]"#
+        );
+    }
+}
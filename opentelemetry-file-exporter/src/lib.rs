@@ -0,0 +1,62 @@
+//! # OpenTelemetry File Exporter
+//!
+//! Writes spans to a local file as OTLP JSON lines, capturing traces in
+//! environments where no collector is reachable. The files can be replayed
+//! later, for example with the OTLP JSON file receiver of the OpenTelemetry
+//! Collector.
+//!
+//! [`FileSpanExporter`] writes one `ExportTraceServiceRequest` per exported
+//! batch. The file is rotated once it reaches a maximum size or age, and a
+//! bounded number of rotated files is kept.
+//!
+//! ## Quickstart
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use opentelemetry::trace::{Tracer, TracerProvider as _};
+//! use opentelemetry_file_exporter::FileSpanExporter;
+//! use opentelemetry_sdk::trace::TracerProvider;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let exporter = FileSpanExporter::builder("spans.jsonl")
+//!         .with_max_file_size(64 * 1024 * 1024)
+//!         .with_rotation_interval(Duration::from_secs(60 * 60))
+//!         .with_max_rotated_files(10)
+//!         .build()?;
+//!
+//!     let provider = TracerProvider::builder()
+//!         .with_simple_exporter(exporter)
+//!         .build();
+//!     let tracer = provider.tracer("my-app");
+//!
+//!     tracer.in_span("doing_work", |_cx| {
+//!         // Traced app logic here...
+//!     });
+//!
+//!     Ok(())
+//! }
+//! ```
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(
+    docsrs,
+    feature(doc_cfg, doc_auto_cfg),
+    deny(rustdoc::broken_intra_doc_links)
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
+)]
+#![cfg_attr(test, deny(warnings))]
+
+mod rotation;
+mod trace;
+
+pub use trace::{FileSpanExporter, FileSpanExporterBuilder};
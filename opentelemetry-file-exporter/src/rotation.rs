@@ -0,0 +1,207 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// When to rotate the written file and how many rotated files to keep.
+#[derive(Clone, Debug)]
+pub(crate) struct RotationPolicy {
+    pub(crate) max_file_size: Option<u64>,
+    pub(crate) rotation_interval: Option<Duration>,
+    pub(crate) max_rotated_files: usize,
+}
+
+/// A file written line by line, rotated according to a [`RotationPolicy`].
+///
+/// On rotation `path` is renamed to `path.1`, the previously rotated files
+/// are shifted to `path.2`, `path.3`, … and the oldest beyond the policy's
+/// limit is deleted.
+#[derive(Debug)]
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    policy: RotationPolicy,
+    file: File,
+    size: u64,
+    opened_at: Instant,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating it if needed.
+    ///
+    /// The age of an existing file is counted from the time it is opened.
+    pub(crate) fn open(path: PathBuf, policy: RotationPolicy) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path,
+            policy,
+            file,
+            size,
+            opened_at: Instant::now(),
+        })
+    }
+
+    /// Append `line` and a newline, rotating the file first if appending
+    /// would exceed the size limit or the file reached the maximum age.
+    ///
+    /// A line larger than the size limit is written to its own file.
+    pub(crate) fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.should_rotate(len) {
+            self.rotate()?;
+        }
+
+        let mut buf = Vec::with_capacity(line.len() + 1);
+        buf.extend_from_slice(line);
+        buf.push(b'\n');
+        self.file.write_all(&buf)?;
+        self.size += len;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn should_rotate(&self, len: u64) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        let too_large = self
+            .policy
+            .max_file_size
+            .is_some_and(|max| self.size + len > max);
+        let too_old = self
+            .policy
+            .rotation_interval
+            .is_some_and(|interval| self.opened_at.elapsed() >= interval);
+        too_large || too_old
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let max = self.policy.max_rotated_files;
+        if max == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            remove_if_exists(&rotated_path(&self.path, max))?;
+            for index in (1..max).rev() {
+                rename_if_exists(
+                    &rotated_path(&self.path, index),
+                    &rotated_path(&self.path, index + 1),
+                )?;
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        self.opened_at = Instant::now();
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{index}"));
+    PathBuf::from(rotated)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Duration;
+
+    use super::{rotated_path, RotatingFile, RotationPolicy};
+
+    fn policy(max_file_size: Option<u64>, rotation_interval: Option<Duration>) -> RotationPolicy {
+        RotationPolicy {
+            max_file_size,
+            rotation_interval,
+            max_rotated_files: 2,
+        }
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spans.jsonl");
+        let mut file = RotatingFile::open(path.clone(), policy(Some(8), None)).unwrap();
+
+        for line in ["aaa", "bbb", "ccc", "ddd", "eee"] {
+            file.write_line(line.as_bytes()).unwrap();
+        }
+
+        // two lines of 4 bytes fit in each file
+        assert_eq!(fs::read_to_string(&path).unwrap(), "eee\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "ccc\nddd\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "aaa\nbbb\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+
+        file.write_line(b"fff").unwrap();
+        file.write_line(b"ggg").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ggg\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "ccc\nddd\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spans.jsonl");
+        let mut file =
+            RotatingFile::open(path.clone(), policy(None, Some(Duration::from_millis(50))))
+                .unwrap();
+
+        file.write_line(b"old").unwrap();
+        std::thread::sleep(Duration::from_millis(60));
+        file.write_line(b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "old\n");
+    }
+
+    #[test]
+    fn appends_to_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spans.jsonl");
+        fs::write(&path, "aaa\n").unwrap();
+
+        let mut file = RotatingFile::open(path.clone(), policy(Some(8), None)).unwrap();
+        file.write_line(b"bbb").unwrap();
+        file.write_line(b"ccc").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ccc\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "aaa\nbbb\n"
+        );
+    }
+}
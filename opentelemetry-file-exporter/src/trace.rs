@@ -0,0 +1,193 @@
+use core::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use opentelemetry::trace::TraceError;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
+use opentelemetry_proto::transform::trace::tonic::group_spans_by_resource_and_scope;
+use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use opentelemetry_sdk::Resource;
+
+use crate::rotation::{RotatingFile, RotationPolicy};
+
+const DEFAULT_MAX_ROTATED_FILES: usize = 5;
+
+/// A [`SpanExporter`] writing spans to a file as OTLP JSON lines.
+///
+/// Each exported batch is written as a single line containing an
+/// `ExportTraceServiceRequest`, the format read by the OpenTelemetry
+/// Collector's OTLP JSON file receiver.
+///
+/// Use [`FileSpanExporter::builder`] to configure the file and its rotation.
+pub struct FileSpanExporter {
+    file: Option<RotatingFile>,
+    resource: ResourceAttributesWithSchema,
+}
+
+impl fmt::Debug for FileSpanExporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FileSpanExporter")
+    }
+}
+
+impl FileSpanExporter {
+    /// Create a builder for an exporter writing to `path`.
+    ///
+    /// Rotated files are written next to it, with `.1`, `.2`, … appended to
+    /// the file name, `.1` being the most recent.
+    pub fn builder(path: impl Into<PathBuf>) -> FileSpanExporterBuilder {
+        FileSpanExporterBuilder {
+            path: path.into(),
+            policy: RotationPolicy {
+                max_file_size: None,
+                rotation_interval: None,
+                max_rotated_files: DEFAULT_MAX_ROTATED_FILES,
+            },
+        }
+    }
+
+    fn write_batch(&mut self, batch: Vec<SpanData>) -> ExportResult {
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| TraceError::Other("exporter is already shut down".into()))?;
+
+        let request = ExportTraceServiceRequest {
+            resource_spans: group_spans_by_resource_and_scope(batch, &self.resource),
+        };
+        let line = serde_json::to_vec(&request).map_err(|err| TraceError::Other(Box::new(err)))?;
+        file.write_line(&line)
+            .map_err(|err| TraceError::Other(Box::new(err)))
+    }
+}
+
+impl SpanExporter for FileSpanExporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        Box::pin(std::future::ready(self.write_batch(batch)))
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(mut file) = self.file.take() {
+            let _ = file.flush();
+        }
+    }
+
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        let res = match self.file.as_mut() {
+            Some(file) => file.flush().map_err(|err| TraceError::Other(Box::new(err))),
+            None => Ok(()),
+        };
+        Box::pin(std::future::ready(res))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.into();
+    }
+}
+
+/// Configuration for a [`FileSpanExporter`].
+///
+/// By default the file is never rotated. Once rotation is enabled, the 5 most
+/// recently rotated files are kept.
+#[derive(Debug)]
+pub struct FileSpanExporterBuilder {
+    path: PathBuf,
+    policy: RotationPolicy,
+}
+
+impl FileSpanExporterBuilder {
+    /// Rotate the file before writing a batch would make it larger than
+    /// `bytes`.
+    ///
+    /// A single batch larger than `bytes` is still written, to a file of its
+    /// own.
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.policy.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Rotate the file on the first write after it has been written to for
+    /// `interval`.
+    pub fn with_rotation_interval(mut self, interval: Duration) -> Self {
+        self.policy.rotation_interval = Some(interval);
+        self
+    }
+
+    /// Set the number of rotated files to keep, older files are deleted.
+    ///
+    /// With `0` the file is discarded instead of rotated.
+    pub fn with_max_rotated_files(mut self, count: usize) -> Self {
+        self.policy.max_rotated_files = count;
+        self
+    }
+
+    /// Open the file and create the exporter.
+    ///
+    /// Spans are appended if the file already exists.
+    pub fn build(self) -> io::Result<FileSpanExporter> {
+        Ok(FileSpanExporter {
+            file: Some(RotatingFile::open(self.path, self.policy)?),
+            resource: ResourceAttributesWithSchema::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use futures_executor::block_on;
+    use opentelemetry::KeyValue;
+    use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+    use opentelemetry_sdk::export::trace::SpanExporter;
+    use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+    use opentelemetry_sdk::Resource;
+
+    use super::FileSpanExporter;
+
+    #[test]
+    fn writes_batches_as_otlp_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spans.jsonl");
+        let mut exporter = FileSpanExporter::builder(&path).build().unwrap();
+        exporter.set_resource(&Resource::new(vec![KeyValue::new(
+            "service.name",
+            "file-test",
+        )]));
+
+        block_on(exporter.export(vec![new_test_export_span_data()])).unwrap();
+        block_on(exporter.export(vec![
+            new_test_export_span_data(),
+            new_test_export_span_data(),
+        ]))
+        .unwrap();
+        exporter.shutdown();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let requests: Vec<ExportTraceServiceRequest> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(requests.len(), 2);
+
+        let resource_spans = &requests[1].resource_spans[0];
+        let resource = resource_spans.resource.as_ref().unwrap();
+        assert_eq!(resource.attributes[0].key, "service.name");
+        assert_eq!(resource_spans.scope_spans[0].spans.len(), 2);
+        assert_eq!(resource_spans.scope_spans[0].spans[0].name, "opentelemetry");
+    }
+
+    #[test]
+    fn export_fails_after_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut exporter = FileSpanExporter::builder(dir.path().join("spans.jsonl"))
+            .build()
+            .unwrap();
+        exporter.shutdown();
+
+        assert!(block_on(exporter.export(vec![new_test_export_span_data()])).is_err());
+    }
+}
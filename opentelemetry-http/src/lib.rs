@@ -66,20 +66,22 @@ pub trait HttpClient: Debug + Send + Sync {
 
 #[cfg(feature = "reqwest")]
 mod reqwest {
+    use crate::ResponseExt;
+
     use super::{async_trait, Bytes, HttpClient, HttpError, Request, Response};
 
     #[async_trait]
     impl HttpClient for reqwest::Client {
         async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
             let request = request.try_into()?;
-            let mut response = self.execute(request).await?.error_for_status()?;
+            let mut response = self.execute(request).await?;
             let headers = std::mem::take(response.headers_mut());
             let mut http_response = Response::builder()
                 .status(response.status())
                 .body(response.bytes().await?)?;
             *http_response.headers_mut() = headers;
 
-            Ok(http_response)
+            Ok(http_response.error_for_status()?)
         }
     }
 
@@ -87,14 +89,14 @@ mod reqwest {
     impl HttpClient for reqwest::blocking::Client {
         async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
             let request = request.try_into()?;
-            let mut response = self.execute(request)?.error_for_status()?;
+            let mut response = self.execute(request)?;
             let headers = std::mem::take(response.headers_mut());
             let mut http_response = Response::builder()
                 .status(response.status())
                 .body(response.bytes()?)?;
             *http_response.headers_mut() = headers;
 
-            Ok(http_response)
+            Ok(http_response.error_for_status()?)
         }
     }
 }
@@ -202,11 +204,44 @@ impl<T> ResponseExt for Response<T> {
         if self.status().is_success() {
             Ok(self)
         } else {
-            Err(format!("request failed with status {}", self.status()).into())
+            Err(Box::new(HttpStatusError {
+                status: self.status(),
+                headers: self.headers().clone(),
+            }))
         }
     }
 }
 
+/// The error returned for responses whose status does not indicate success.
+///
+/// The clients provided by this crate fail with this error, it keeps the
+/// response headers to allow inspecting hints like `Retry-After`.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+}
+
+impl HttpStatusError {
+    /// The status of the response.
+    pub fn status(&self) -> http::StatusCode {
+        self.status
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+}
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request failed with status {}", self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,6 +258,26 @@ mod tests {
         )
     }
 
+    #[test]
+    fn error_for_status_keeps_status_and_headers() {
+        let response = Response::builder()
+            .status(http::StatusCode::TOO_MANY_REQUESTS)
+            .header(http::header::RETRY_AFTER, "3")
+            .body(())
+            .unwrap();
+
+        let err = response.error_for_status().unwrap_err();
+        let err = err.downcast_ref::<HttpStatusError>().unwrap();
+        assert_eq!(err.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(err.headers()[http::header::RETRY_AFTER], "3");
+        assert_eq!(
+            err.to_string(),
+            "request failed with status 429 Too Many Requests"
+        );
+
+        assert!(Response::new(()).error_for_status().is_ok());
+    }
+
     #[test]
     fn http_headers_keys() {
         let mut carrier = http::HeaderMap::new();
@@ -0,0 +1,80 @@
+//! # OpenTelemetry Live
+//!
+//! Streams spans to local debugging clients while the application is running,
+//! making it possible to inspect traces during development without deploying a
+//! collector and a tracing backend.
+//!
+//! [`LiveSpanProcessor`] serves a WebSocket endpoint. Every client connecting
+//! to it first receives a snapshot of the spans that are currently active and of
+//! the most recently ended spans, followed by a message for each span that
+//! starts or ends afterwards. Spans are only tracked while at least one client
+//! is connected, so the processor costs next to nothing otherwise.
+//!
+//! ## Quickstart
+//!
+//! ```no_run
+//! use opentelemetry::trace::{Tracer, TracerProvider as _};
+//! use opentelemetry_live::LiveSpanProcessor;
+//! use opentelemetry_sdk::trace::TracerProvider;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), opentelemetry_live::Error> {
+//!     // must be called from within a tokio runtime
+//!     let processor = LiveSpanProcessor::builder()
+//!         .with_endpoint("127.0.0.1:4320")
+//!         .build()?;
+//!
+//!     let provider = TracerProvider::builder()
+//!         .with_span_processor(processor)
+//!         .build();
+//!     let tracer = provider.tracer("my-app");
+//!
+//!     tracer.in_span("doing_work", |_cx| {
+//!         // Traced app logic here...
+//!     });
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Any WebSocket client can then be used to follow the spans, for example
+//! `websocat ws://127.0.0.1:4320`.
+//!
+//! ## Protocol
+//!
+//! Each WebSocket text message is a JSON object whose `type` field is one of:
+//!
+//! * `snapshot`: sent once when a client connects. `active` lists the spans
+//!   that have started but not yet ended, `recent` the most recently ended
+//!   spans, both oldest first and limited to the spans seen while clients were
+//!   connected.
+//! * `spanStarted`: a span started, carried in `span`.
+//! * `spanEnded`: a span ended, carried in `span`.
+//! * `lagged`: the client did not keep up and `skipped` messages were dropped.
+//!
+//! Spans are encoded as OTLP/JSON `Span` objects. Active spans reflect the
+//! span as it was when it started, attributes and events added later are only
+//! visible once the span ends.
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(
+    docsrs,
+    feature(doc_cfg, doc_auto_cfg),
+    deny(rustdoc::broken_intra_doc_links)
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
+)]
+#![cfg_attr(test, deny(warnings))]
+
+mod processor;
+mod server;
+
+pub use processor::{Error, LiveSpanProcessor, LiveSpanProcessorBuilder};
@@ -0,0 +1,325 @@
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+
+use opentelemetry::trace::TraceResult;
+use opentelemetry::{Context, ExportError};
+use opentelemetry_sdk::export::trace::SpanData;
+use opentelemetry_sdk::trace::{Span, SpanProcessor};
+use tokio::runtime::Handle;
+use tokio::sync::watch;
+
+use crate::server::{self, Hub};
+
+const DEFAULT_ENDPOINT: &str = "127.0.0.1:4320";
+const DEFAULT_ACTIVE_CAPACITY: usize = 2048;
+const DEFAULT_RECENT_CAPACITY: usize = 512;
+const DEFAULT_CLIENT_BUFFER_SIZE: usize = 1024;
+
+/// Errors returned when starting a [`LiveSpanProcessor`].
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The endpoint could not be bound.
+    #[error("failed to bind endpoint: {0}")]
+    Bind(#[from] std::io::Error),
+
+    /// The processor was built outside of a tokio runtime.
+    #[error("the live span processor must be built from within a tokio runtime")]
+    NoRuntime,
+}
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "live"
+    }
+}
+
+/// A [`SpanProcessor`] streaming started and ended spans to WebSocket clients.
+///
+/// While clients are connected, the processor keeps track of a bounded number
+/// of the spans that are currently active and of recently ended spans, so that
+/// clients connecting later see the current state of the application. Without
+/// connected clients spans are neither tracked nor serialized. See the [crate
+/// level documentation](crate) for the messages sent to clients.
+///
+/// The server runs on the tokio runtime the processor was built in and stops
+/// when the processor is shut down or dropped.
+#[derive(Debug)]
+pub struct LiveSpanProcessor {
+    hub: Arc<Hub>,
+    local_addr: SocketAddr,
+    shutdown: watch::Sender<bool>,
+}
+
+impl LiveSpanProcessor {
+    /// Create a new [`LiveSpanProcessorBuilder`].
+    pub fn builder() -> LiveSpanProcessorBuilder {
+        LiveSpanProcessorBuilder::default()
+    }
+
+    /// The address the server is listening on.
+    ///
+    /// Useful when the processor was configured with port `0`.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The number of active spans evicted from the snapshot sent to new
+    /// clients, to stay within the configured active capacity.
+    pub fn dropped_active_spans(&self) -> u64 {
+        self.hub.dropped_active()
+    }
+}
+
+impl SpanProcessor for LiveSpanProcessor {
+    fn on_start(&self, span: &mut Span, _cx: &Context) {
+        if !self.hub.has_clients() {
+            return;
+        }
+        if let Some(data) = span.exported_data() {
+            self.hub.span_started(data);
+        }
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if !self.hub.has_clients() {
+            return;
+        }
+        self.hub.span_ended(span);
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> TraceResult<()> {
+        // the server is already stopped if all receivers are gone
+        let _ = self.shutdown.send(true);
+        Ok(())
+    }
+}
+
+/// Builder for [`LiveSpanProcessor`].
+#[derive(Debug)]
+pub struct LiveSpanProcessorBuilder {
+    endpoint: String,
+    active_capacity: usize,
+    recent_capacity: usize,
+    client_buffer_size: usize,
+}
+
+impl Default for LiveSpanProcessorBuilder {
+    fn default() -> Self {
+        LiveSpanProcessorBuilder {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            active_capacity: DEFAULT_ACTIVE_CAPACITY,
+            recent_capacity: DEFAULT_RECENT_CAPACITY,
+            client_buffer_size: DEFAULT_CLIENT_BUFFER_SIZE,
+        }
+    }
+}
+
+impl LiveSpanProcessorBuilder {
+    /// Set the address the server listens on.
+    ///
+    /// Defaults to `127.0.0.1:4320`. Spans may contain sensitive data, so the
+    /// server should only be exposed on trusted interfaces.
+    pub fn with_endpoint<T: Into<String>>(mut self, endpoint: T) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Set the number of active spans sent to newly connected clients.
+    ///
+    /// The oldest active span is evicted when a span starts while the limit is
+    /// reached, see [`LiveSpanProcessor::dropped_active_spans`]. Defaults to
+    /// 2048.
+    pub fn with_active_capacity(mut self, active_capacity: usize) -> Self {
+        self.active_capacity = active_capacity;
+        self
+    }
+
+    /// Set the number of recently ended spans sent to newly connected clients.
+    ///
+    /// Defaults to 512.
+    pub fn with_recent_capacity(mut self, recent_capacity: usize) -> Self {
+        self.recent_capacity = recent_capacity;
+        self
+    }
+
+    /// Set the number of messages buffered for each client.
+    ///
+    /// Clients falling further behind miss messages and are sent a `lagged`
+    /// message instead. Defaults to 1024.
+    pub fn with_client_buffer_size(mut self, client_buffer_size: usize) -> Self {
+        self.client_buffer_size = client_buffer_size.max(1);
+        self
+    }
+
+    /// Bind the endpoint and start serving clients on the current tokio
+    /// runtime.
+    pub fn build(self) -> Result<LiveSpanProcessor, Error> {
+        let runtime = Handle::try_current().map_err(|_| Error::NoRuntime)?;
+
+        let listener = TcpListener::bind(self.endpoint.as_str())?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let hub = Arc::new(Hub::new(
+            self.active_capacity,
+            self.recent_capacity,
+            self.client_buffer_size,
+        ));
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        runtime.spawn(server::serve(listener, hub.clone(), shutdown_rx));
+
+        Ok(LiveSpanProcessor {
+            hub,
+            local_addr,
+            shutdown,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use opentelemetry::trace::{Span as _, Tracer, TracerProvider as _};
+    use opentelemetry_sdk::trace::TracerProvider;
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn next_json(client: &mut Client) -> serde_json::Value {
+        match client.next().await {
+            Some(Ok(WsMessage::Text(text))) => serde_json::from_str(&text).unwrap(),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    /// Connects a client, returning it once it is subscribed along with its
+    /// snapshot.
+    async fn connect(addr: SocketAddr) -> (Client, serde_json::Value) {
+        let url = format!("ws://{}", addr);
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let snapshot = next_json(&mut client).await;
+        assert_eq!(snapshot["type"], "snapshot");
+        (client, snapshot)
+    }
+
+    fn build_processor(active_capacity: usize) -> LiveSpanProcessor {
+        LiveSpanProcessor::builder()
+            .with_endpoint("127.0.0.1:0")
+            .with_active_capacity(active_capacity)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn build_requires_runtime() {
+        let result = LiveSpanProcessor::builder()
+            .with_endpoint("127.0.0.1:0")
+            .build();
+        assert!(matches!(result, Err(Error::NoRuntime)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn streams_started_and_ended_spans() {
+        let processor = build_processor(DEFAULT_ACTIVE_CAPACITY);
+        let addr = processor.local_addr();
+        let provider = TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+        let tracer = provider.tracer("live-test");
+
+        let (mut client, snapshot) = connect(addr).await;
+        assert_eq!(snapshot["active"].as_array().unwrap().len(), 0);
+        assert_eq!(snapshot["recent"].as_array().unwrap().len(), 0);
+
+        let mut span = tracer.start("live");
+        let started = next_json(&mut client).await;
+        assert_eq!(started["type"], "spanStarted");
+        assert_eq!(started["span"]["name"], "live");
+
+        span.end();
+        let ended = next_json(&mut client).await;
+        assert_eq!(ended["type"], "spanEnded");
+        assert_eq!(ended["span"]["name"], "live");
+
+        let (_late, snapshot) = connect(addr).await;
+        assert_eq!(snapshot["active"].as_array().unwrap().len(), 0);
+        assert_eq!(snapshot["recent"][0]["name"], "live");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn snapshot_includes_active_spans() {
+        let processor = build_processor(DEFAULT_ACTIVE_CAPACITY);
+        let addr = processor.local_addr();
+        let provider = TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+        let tracer = provider.tracer("live-test");
+
+        let (mut client, _) = connect(addr).await;
+        let mut span = tracer.start("active");
+        assert_eq!(next_json(&mut client).await["type"], "spanStarted");
+
+        let (mut late, snapshot) = connect(addr).await;
+        assert_eq!(snapshot["active"][0]["name"], "active");
+        assert_eq!(snapshot["recent"].as_array().unwrap().len(), 0);
+
+        span.end();
+        let ended = next_json(&mut late).await;
+        assert_eq!(ended["type"], "spanEnded");
+        assert_eq!(ended["span"]["name"], "active");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn spans_are_not_tracked_without_clients() {
+        let processor = build_processor(DEFAULT_ACTIVE_CAPACITY);
+        let addr = processor.local_addr();
+        let hub = processor.hub.clone();
+        let provider = TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+        let tracer = provider.tracer("live-test");
+
+        let _active = tracer.start("active");
+        tracer.start("ended").end();
+        assert!(!hub.has_clients());
+
+        let (_client, snapshot) = connect(addr).await;
+        assert_eq!(snapshot["active"].as_array().unwrap().len(), 0);
+        assert_eq!(snapshot["recent"].as_array().unwrap().len(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn evicts_oldest_active_spans() {
+        let processor = build_processor(2);
+        let addr = processor.local_addr();
+        let hub = processor.hub.clone();
+        let provider = TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+        let tracer = provider.tracer("live-test");
+
+        let (mut client, _) = connect(addr).await;
+        let _spans = ["first", "second", "third"]
+            .into_iter()
+            .map(|name| tracer.start(name))
+            .collect::<Vec<_>>();
+        for _ in 0..3 {
+            assert_eq!(next_json(&mut client).await["type"], "spanStarted");
+        }
+
+        let (_late, snapshot) = connect(addr).await;
+        let active = snapshot["active"].as_array().unwrap();
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0]["name"], "second");
+        assert_eq!(active[1]["name"], "third");
+        assert_eq!(hub.dropped_active(), 1);
+    }
+}
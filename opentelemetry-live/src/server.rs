@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::TcpListener as StdTcpListener;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt, StreamExt};
+use opentelemetry::global;
+use opentelemetry::trace::{SpanId, TraceError, TraceId};
+use opentelemetry_proto::tonic::trace::v1::Span;
+use opentelemetry_sdk::export::trace::SpanData;
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+/// A message sent to connected clients.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Message<'a> {
+    Snapshot {
+        active: Vec<&'a Span>,
+        recent: Vec<&'a Span>,
+    },
+    SpanStarted {
+        span: &'a Span,
+    },
+    SpanEnded {
+        span: &'a Span,
+    },
+    Lagged {
+        skipped: u64,
+    },
+}
+
+impl Message<'_> {
+    fn to_json(&self) -> Arc<str> {
+        // serializing the generated types into a string cannot fail
+        serde_json::to_string(self).unwrap_or_default().into()
+    }
+}
+
+type SpanKey = (TraceId, SpanId);
+
+#[derive(Debug, Default)]
+struct State {
+    /// The active spans along with their start sequence number.
+    active: HashMap<SpanKey, (u64, SpanData)>,
+    /// The keys of the active spans, oldest first.
+    started: BTreeMap<u64, SpanKey>,
+    next_sequence: u64,
+    recent: VecDeque<SpanData>,
+}
+
+impl State {
+    fn clear_active(&mut self) {
+        self.active.clear();
+        self.started.clear();
+    }
+}
+
+/// Tracks the active and recently ended spans and fans span events out to the
+/// connected clients.
+///
+/// Spans are only tracked while clients are connected. They are converted and
+/// serialized outside of the state lock, except for the rare case of a client
+/// subscribing concurrently with a span event.
+#[derive(Debug)]
+pub(crate) struct Hub {
+    state: Mutex<State>,
+    active_capacity: usize,
+    recent_capacity: usize,
+    dropped_active: AtomicU64,
+    events: broadcast::Sender<Arc<str>>,
+}
+
+impl Hub {
+    pub(crate) fn new(
+        active_capacity: usize,
+        recent_capacity: usize,
+        client_buffer_size: usize,
+    ) -> Self {
+        let (events, _) = broadcast::channel(client_buffer_size);
+        Hub {
+            state: Mutex::new(State {
+                recent: VecDeque::with_capacity(recent_capacity),
+                ..Default::default()
+            }),
+            active_capacity,
+            recent_capacity,
+            dropped_active: AtomicU64::new(0),
+            events,
+        }
+    }
+
+    /// Whether any client is connected.
+    pub(crate) fn has_clients(&self) -> bool {
+        self.events.receiver_count() > 0
+    }
+
+    /// The number of active spans evicted to stay within the active capacity.
+    pub(crate) fn dropped_active(&self) -> u64 {
+        self.dropped_active.load(Ordering::Relaxed)
+    }
+
+    /// Serializes the event for `span` if any client is subscribed.
+    fn event(&self, span: &SpanData, ended: bool) -> Option<Arc<str>> {
+        if self.events.receiver_count() == 0 {
+            return None;
+        }
+
+        let span = Span::from(span.clone());
+        let message = if ended {
+            Message::SpanEnded { span: &span }
+        } else {
+            Message::SpanStarted { span: &span }
+        };
+        Some(message.to_json())
+    }
+
+    /// Sends the event for `span`, `event` being the event serialized before
+    /// the state lock was taken, if any.
+    ///
+    /// Must be called while holding the state lock, so clients never miss an
+    /// event between their snapshot and their subscription.
+    fn send(&self, event: Option<Arc<str>>, span: &SpanData, ended: bool) {
+        // a client may have subscribed since the event was serialized
+        if let Some(event) = event.or_else(|| self.event(span, ended)) {
+            let _ = self.events.send(event);
+        }
+    }
+
+    pub(crate) fn span_started(&self, span: SpanData) {
+        let key = (span.span_context.trace_id(), span.span_context.span_id());
+        let event = self.event(&span, false);
+        if let Ok(mut state) = self.state.lock() {
+            self.send(event, &span, false);
+            if self.active_capacity == 0 {
+                return;
+            }
+            if state.active.len() == self.active_capacity {
+                // evict the oldest active span
+                if let Some((_, oldest)) = state.started.pop_first() {
+                    state.active.remove(&oldest);
+                    self.dropped_active.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.started.insert(sequence, key);
+            if let Some((replaced, _)) = state.active.insert(key, (sequence, span)) {
+                state.started.remove(&replaced);
+            }
+        }
+    }
+
+    pub(crate) fn span_ended(&self, span: SpanData) {
+        let key = (span.span_context.trace_id(), span.span_context.span_id());
+        let event = self.event(&span, true);
+        if let Ok(mut state) = self.state.lock() {
+            self.send(event, &span, true);
+            if let Some((sequence, _)) = state.active.remove(&key) {
+                state.started.remove(&sequence);
+            }
+            if self.recent_capacity == 0 {
+                return;
+            }
+            if state.recent.len() == self.recent_capacity {
+                state.recent.pop_front();
+            }
+            state.recent.push_back(span);
+        }
+    }
+
+    /// Returns the current snapshot along with a receiver for all subsequent
+    /// events.
+    fn subscribe(&self) -> Option<(Arc<str>, broadcast::Receiver<Arc<str>>)> {
+        let (active, recent, events) = {
+            let state = self.state.lock().ok()?;
+            (
+                state
+                    .started
+                    .values()
+                    .filter_map(|key| state.active.get(key))
+                    .map(|(_, span)| span.clone())
+                    .collect::<Vec<_>>(),
+                state.recent.iter().cloned().collect::<Vec<_>>(),
+                self.events.subscribe(),
+            )
+        };
+
+        let active = active.into_iter().map(Span::from).collect::<Vec<_>>();
+        let recent = recent.into_iter().map(Span::from).collect::<Vec<_>>();
+        let snapshot = Message::Snapshot {
+            active: active.iter().collect(),
+            recent: recent.iter().collect(),
+        }
+        .to_json();
+
+        Some((snapshot, events))
+    }
+
+    /// Drops the receiver of a disconnected client.
+    ///
+    /// Spans are not tracked without clients, so the active spans are
+    /// forgotten once the last client disconnects, as their end is missed.
+    fn unsubscribe(&self, events: broadcast::Receiver<Arc<str>>) {
+        if let Ok(mut state) = self.state.lock() {
+            drop(events);
+            if !self.has_clients() {
+                state.clear_active();
+            }
+        }
+    }
+}
+
+/// Accepts client connections until `shutdown` is signalled or its sender is
+/// dropped.
+pub(crate) async fn serve(
+    listener: StdTcpListener,
+    hub: Arc<Hub>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            global::handle_error(TraceError::Other(Box::new(err)));
+            return;
+        }
+    };
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_client(stream, hub.clone(), shutdown.clone()));
+                }
+                Err(err) => global::handle_error(TraceError::Other(Box::new(err))),
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+async fn handle_client(stream: TcpStream, hub: Arc<Hub>, mut shutdown: watch::Receiver<bool>) {
+    let mut socket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(socket) => socket,
+        Err(err) => {
+            global::handle_error(TraceError::Other(Box::new(err)));
+            return;
+        }
+    };
+
+    let Some((snapshot, mut events)) = hub.subscribe() else {
+        return;
+    };
+    if socket
+        .send(WsMessage::Text(snapshot.to_string()))
+        .await
+        .is_ok()
+    {
+        forward_events(&mut socket, &mut events, &mut shutdown).await;
+    }
+    hub.unsubscribe(events);
+}
+
+/// Sends the span events to the client until it disconnects or the server is
+/// shut down.
+async fn forward_events(
+    socket: &mut WebSocketStream<TcpStream>,
+    events: &mut broadcast::Receiver<Arc<str>>,
+    shutdown: &mut watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let text = match event {
+                    Ok(text) => text,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        Message::Lagged { skipped }.to_json()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(WsMessage::Text(text.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.next() => match incoming {
+                // messages from clients are ignored, pings are answered by
+                // the websocket implementation
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = shutdown.changed() => {
+                let _ = socket.close(None).await;
+                break;
+            }
+        }
+    }
+}
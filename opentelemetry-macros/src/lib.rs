@@ -0,0 +1,261 @@
+//! Derive macros for OpenTelemetry.
+//!
+//! # Recording structs as attributes
+//!
+//! `#[derive(OtelAttributes)]` generates an `into_attributes` method on a
+//! struct with named fields, returning its fields as a
+//! `Vec<opentelemetry::KeyValue>`. The same attributes can then be set on
+//! spans and added to log records.
+//!
+//! ```
+//! use opentelemetry::trace::{Span, Tracer};
+//! use opentelemetry_macros::OtelAttributes;
+//!
+//! #[derive(OtelAttributes)]
+//! struct Order {
+//!     #[otel(rename = "order.id")]
+//!     id: String,
+//!     #[otel(redact)]
+//!     customer_email: String,
+//!     #[otel(skip)]
+//!     items: Vec<String>,
+//!     item_count: u32,
+//!     discount: Option<f64>,
+//! }
+//!
+//! let order = Order {
+//!     id: "1234".to_string(),
+//!     customer_email: "jane@example.com".to_string(),
+//!     items: vec!["book".to_string()],
+//!     item_count: 1,
+//!     discount: None,
+//! };
+//!
+//! let tracer = opentelemetry::global::tracer("shop");
+//! let mut span = tracer.start("checkout");
+//! span.set_attributes(order.into_attributes());
+//! ```
+//!
+//! Fields are recorded under their name, their values are converted with
+//! `Into<opentelemetry::Value>`, integers are converted to `i64`. Optional
+//! fields are only recorded when set. The following options can be set on
+//! fields with the `otel` attribute:
+//!
+//! | Option              | Effect                                                       |
+//! | ------------------- | ------------------------------------------------------------ |
+//! | `rename = "key"`    | Records the field under `key` instead of its name.           |
+//! | `skip`              | Does not record the field.                                   |
+//! | `redact`            | Records `[REDACTED]` instead of the value of the field.      |
+//! | `display`           | Records the value of the field formatted with `Display`.     |
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(test, deny(warnings))]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Field, Fields, GenericArgument, LitStr,
+    PathArguments, Result, Type,
+};
+
+/// The value recorded for redacted fields.
+const REDACTED: &str = "[REDACTED]";
+
+/// Generates an `into_attributes` method returning the fields of a struct as
+/// OpenTelemetry attributes.
+///
+/// See the [crate documentation](crate) for the supported field options.
+#[proc_macro_derive(OtelAttributes, attributes(otel))]
+pub fn derive_otel_attributes(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "OtelAttributes can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "OtelAttributes can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut records = Vec::with_capacity(fields.len());
+    for field in fields {
+        if let Some(record) = record_field(field)? {
+            records.push(record);
+        }
+    }
+    let capacity = records.len();
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Converts the fields of this value into OpenTelemetry attributes.
+            pub fn into_attributes(self) -> ::std::vec::Vec<::opentelemetry::KeyValue> {
+                let mut attributes = ::std::vec::Vec::with_capacity(#capacity);
+                #(#records)*
+                attributes
+            }
+        }
+    })
+}
+
+/// The options of a field, set with the `otel` attribute.
+#[derive(Default)]
+struct FieldOptions {
+    rename: Option<LitStr>,
+    skip: bool,
+    redact: bool,
+    display: bool,
+}
+
+impl FieldOptions {
+    fn parse(field: &Field) -> Result<Self> {
+        let mut options = FieldOptions::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("otel"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    options.rename = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else if meta.path.is_ident("redact") {
+                    options.redact = true;
+                } else if meta.path.is_ident("display") {
+                    options.display = true;
+                } else {
+                    return Err(meta.error("unsupported otel option"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// The statement recording `field`, `None` if it is skipped.
+fn record_field(field: &Field) -> Result<Option<TokenStream2>> {
+    let options = FieldOptions::parse(field)?;
+    if options.skip {
+        return Ok(None);
+    }
+
+    // named fields always have an identifier
+    let ident = field.ident.as_ref().expect("named field");
+    let key = match &options.rename {
+        Some(rename) => rename.clone(),
+        None => LitStr::new(
+            ident.to_string().trim_start_matches("r#"),
+            Span::call_site(),
+        ),
+    };
+
+    let (ty, optional) = match option_inner(&field.ty) {
+        Some(inner) => (inner, true),
+        None => (&field.ty, false),
+    };
+    if options.redact {
+        // the value is not converted, only referenced so that the field is
+        // not reported as never read
+        let redacted = quote!(::opentelemetry::KeyValue::new(#key, #REDACTED));
+        return Ok(Some(if optional {
+            quote! {
+                if self.#ident.is_some() {
+                    attributes.push(#redacted);
+                }
+            }
+        } else {
+            quote! {
+                let _ = &self.#ident;
+                attributes.push(#redacted);
+            }
+        }));
+    }
+
+    let value = quote!(value);
+    let converted = if options.display {
+        quote!(::opentelemetry::Value::from(::std::string::ToString::to_string(&#value)))
+    } else {
+        convert(ty, &value)
+    };
+
+    Ok(Some(if optional {
+        quote! {
+            if let ::std::option::Option::Some(#value) = self.#ident {
+                attributes.push(::opentelemetry::KeyValue::new(#key, #converted));
+            }
+        }
+    } else {
+        quote! {
+            let #value = self.#ident;
+            attributes.push(::opentelemetry::KeyValue::new(#key, #converted));
+        }
+    }))
+}
+
+/// The type wrapped in an `Option`, if `ty` is one.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Converts `value` of type `ty` into an `opentelemetry::Value`.
+///
+/// Attribute values only hold `i64` and `f64` numbers, narrower numbers are
+/// widened and wider integers saturate.
+fn convert(ty: &Type, value: &TokenStream2) -> TokenStream2 {
+    let ident = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.get_ident().map(|i| i.to_string()),
+        _ => None,
+    };
+
+    match ident.as_deref() {
+        Some("i8" | "i16" | "i32" | "u8" | "u16" | "u32") => {
+            quote!(::opentelemetry::Value::I64(::std::primitive::i64::from(#value)))
+        }
+        Some("u64" | "usize" | "isize") => quote! {
+            ::opentelemetry::Value::I64(
+                ::std::convert::TryFrom::try_from(#value).unwrap_or(::std::primitive::i64::MAX),
+            )
+        },
+        Some("f32") => quote!(::opentelemetry::Value::F64(::std::primitive::f64::from(#value))),
+        _ => quote!(::std::convert::Into::<::opentelemetry::Value>::into(#value)),
+    }
+}
@@ -0,0 +1,95 @@
+// redacted fields must not be reported as never read
+#![deny(dead_code)]
+
+use opentelemetry::KeyValue;
+use opentelemetry_macros::OtelAttributes;
+use std::fmt;
+
+#[derive(Clone, Copy)]
+enum Tier {
+    Gold,
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tier::Gold => f.write_str("gold"),
+        }
+    }
+}
+
+#[derive(OtelAttributes)]
+struct Customer {
+    #[otel(rename = "customer.id")]
+    id: u64,
+    #[otel(redact)]
+    email: String,
+    #[otel(redact)]
+    phone: Option<String>,
+    #[otel(skip)]
+    #[allow(dead_code)]
+    password_hash: String,
+    #[otel(display)]
+    tier: Tier,
+    age: u8,
+    score: f32,
+    verified: bool,
+    nickname: Option<&'static str>,
+    referrer: Option<String>,
+}
+
+#[derive(OtelAttributes)]
+struct Wrapper<T: Into<opentelemetry::Value>> {
+    inner: T,
+}
+
+#[test]
+fn derive_otel_attributes() {
+    let customer = Customer {
+        id: 42,
+        email: "jane@example.com".to_string(),
+        phone: Some("555-0100".to_string()),
+        password_hash: "secret".to_string(),
+        tier: Tier::Gold,
+        age: 37,
+        score: 0.5,
+        verified: true,
+        nickname: None,
+        referrer: Some("newsletter".to_string()),
+    };
+
+    assert_eq!(
+        customer.into_attributes(),
+        vec![
+            KeyValue::new("customer.id", 42_i64),
+            KeyValue::new("email", "[REDACTED]"),
+            KeyValue::new("phone", "[REDACTED]"),
+            KeyValue::new("tier", "gold"),
+            KeyValue::new("age", 37_i64),
+            KeyValue::new("score", 0.5),
+            KeyValue::new("verified", true),
+            KeyValue::new("referrer", "newsletter"),
+        ]
+    );
+}
+
+#[test]
+fn derive_otel_attributes_saturates_integers() {
+    #[derive(OtelAttributes)]
+    struct Counters {
+        count: u64,
+    }
+
+    assert_eq!(
+        Counters { count: u64::MAX }.into_attributes(),
+        vec![KeyValue::new("count", i64::MAX)]
+    );
+}
+
+#[test]
+fn derive_otel_attributes_generic() {
+    assert_eq!(
+        Wrapper { inner: "value" }.into_attributes(),
+        vec![KeyValue::new("inner", "value")]
+    );
+}
@@ -0,0 +1,254 @@
+//! Deterministic ordering of the attributes of export requests.
+//!
+//! The order of attributes, and of the scopes grouped in a request, otherwise
+//! depends on the order they were recorded in and on hash map iteration. Once
+//! sorted, the same telemetry is always encoded to the same bytes.
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+
+/// Sorts the attributes of a request, and of everything it contains, by key.
+pub(crate) trait SortAttributes {
+    fn sort_attributes(&mut self);
+}
+
+fn sort_key_values(attributes: &mut [KeyValue]) {
+    // stable, the order of duplicate keys is kept
+    attributes.sort_by(|a, b| a.key.cmp(&b.key));
+    for kv in attributes {
+        if let Some(value) = &mut kv.value {
+            sort_any_value(value);
+        }
+    }
+}
+
+fn sort_any_value(value: &mut AnyValue) {
+    match &mut value.value {
+        Some(any_value::Value::KvlistValue(list)) => sort_key_values(&mut list.values),
+        Some(any_value::Value::ArrayValue(array)) => {
+            array.values.iter_mut().for_each(sort_any_value)
+        }
+        _ => {}
+    }
+}
+
+fn sort_resource(resource: &mut Option<Resource>) {
+    if let Some(resource) = resource {
+        sort_key_values(&mut resource.attributes);
+    }
+}
+
+fn sort_scope(scope: &mut Option<InstrumentationScope>) {
+    if let Some(scope) = scope {
+        sort_key_values(&mut scope.attributes);
+    }
+}
+
+/// The key scopes are ordered by, scopes are expected to be unique in a
+/// resource.
+fn scope_key(scope: &Option<InstrumentationScope>) -> Option<(&str, &str)> {
+    scope
+        .as_ref()
+        .map(|scope| (scope.name.as_str(), scope.version.as_str()))
+}
+
+#[cfg(feature = "trace")]
+impl SortAttributes
+    for opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest
+{
+    fn sort_attributes(&mut self) {
+        for resource_spans in &mut self.resource_spans {
+            sort_resource(&mut resource_spans.resource);
+            resource_spans
+                .scope_spans
+                .sort_by(|a, b| scope_key(&a.scope).cmp(&scope_key(&b.scope)));
+            for scope_spans in &mut resource_spans.scope_spans {
+                sort_scope(&mut scope_spans.scope);
+                for span in &mut scope_spans.spans {
+                    sort_key_values(&mut span.attributes);
+                    for event in &mut span.events {
+                        sort_key_values(&mut event.attributes);
+                    }
+                    for link in &mut span.links {
+                        sort_key_values(&mut link.attributes);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "logs")]
+impl SortAttributes for opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest {
+    fn sort_attributes(&mut self) {
+        for resource_logs in &mut self.resource_logs {
+            sort_resource(&mut resource_logs.resource);
+            resource_logs
+                .scope_logs
+                .sort_by(|a, b| scope_key(&a.scope).cmp(&scope_key(&b.scope)));
+            for scope_logs in &mut resource_logs.scope_logs {
+                sort_scope(&mut scope_logs.scope);
+                for record in &mut scope_logs.log_records {
+                    sort_key_values(&mut record.attributes);
+                    if let Some(body) = &mut record.body {
+                        sort_any_value(body);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl SortAttributes
+    for opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest
+{
+    fn sort_attributes(&mut self) {
+        use opentelemetry_proto::tonic::metrics::v1::{metric::Data, Exemplar};
+
+        fn sort_exemplars(exemplars: &mut [Exemplar]) {
+            for exemplar in exemplars {
+                sort_key_values(&mut exemplar.filtered_attributes);
+            }
+        }
+
+        for resource_metrics in &mut self.resource_metrics {
+            sort_resource(&mut resource_metrics.resource);
+            resource_metrics
+                .scope_metrics
+                .sort_by(|a, b| scope_key(&a.scope).cmp(&scope_key(&b.scope)));
+            for scope_metrics in &mut resource_metrics.scope_metrics {
+                sort_scope(&mut scope_metrics.scope);
+                for metric in &mut scope_metrics.metrics {
+                    sort_key_values(&mut metric.metadata);
+                    match &mut metric.data {
+                        Some(Data::Gauge(gauge)) => {
+                            for dp in &mut gauge.data_points {
+                                sort_key_values(&mut dp.attributes);
+                                sort_exemplars(&mut dp.exemplars);
+                            }
+                        }
+                        Some(Data::Sum(sum)) => {
+                            for dp in &mut sum.data_points {
+                                sort_key_values(&mut dp.attributes);
+                                sort_exemplars(&mut dp.exemplars);
+                            }
+                        }
+                        Some(Data::Histogram(histogram)) => {
+                            for dp in &mut histogram.data_points {
+                                sort_key_values(&mut dp.attributes);
+                                sort_exemplars(&mut dp.exemplars);
+                            }
+                        }
+                        Some(Data::ExponentialHistogram(histogram)) => {
+                            for dp in &mut histogram.data_points {
+                                sort_key_values(&mut dp.attributes);
+                                sort_exemplars(&mut dp.exemplars);
+                            }
+                        }
+                        Some(Data::Summary(summary)) => {
+                            for dp in &mut summary.data_points {
+                                sort_key_values(&mut dp.attributes);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+    use opentelemetry_proto::tonic::common::v1::KeyValueList;
+    use opentelemetry_proto::tonic::trace::v1::{span::Event, ResourceSpans, ScopeSpans, Span};
+
+    fn kv(key: &str, value: Option<any_value::Value>) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue { value }),
+        }
+    }
+
+    fn string(value: &str) -> Option<any_value::Value> {
+        Some(any_value::Value::StringValue(value.to_string()))
+    }
+
+    fn keys(attributes: &[KeyValue]) -> Vec<&str> {
+        attributes.iter().map(|kv| kv.key.as_str()).collect()
+    }
+
+    fn scope(name: &str) -> Option<InstrumentationScope> {
+        Some(InstrumentationScope {
+            name: name.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_sort_trace_request_attributes() {
+        let mut request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: Some(Resource {
+                    attributes: vec![kv("service.name", string("svc")), kv("host", string("h"))],
+                    dropped_attributes_count: 0,
+                }),
+                scope_spans: vec![
+                    ScopeSpans {
+                        scope: scope("b"),
+                        spans: vec![Span {
+                            attributes: vec![
+                                kv("z", string("1")),
+                                kv(
+                                    "nested",
+                                    Some(any_value::Value::KvlistValue(KeyValueList {
+                                        values: vec![kv("y", None), kv("x", None)],
+                                    })),
+                                ),
+                                kv("a", string("first")),
+                                kv("a", string("second")),
+                            ],
+                            events: vec![Event {
+                                attributes: vec![kv("m", None), kv("l", None)],
+                                ..Default::default()
+                            }],
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    },
+                    ScopeSpans {
+                        scope: scope("a"),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+        };
+
+        request.sort_attributes();
+
+        let resource_spans = &request.resource_spans[0];
+        assert_eq!(
+            keys(&resource_spans.resource.as_ref().unwrap().attributes),
+            vec!["host", "service.name"]
+        );
+        assert_eq!(resource_spans.scope_spans[0].scope, scope("a"));
+        let span = &resource_spans.scope_spans[1].spans[0];
+        assert_eq!(keys(&span.attributes), vec!["a", "a", "nested", "z"]);
+        assert_eq!(
+            span.attributes[0].value,
+            Some(AnyValue {
+                value: string("first")
+            })
+        );
+        match &span.attributes[2].value.as_ref().unwrap().value {
+            Some(any_value::Value::KvlistValue(list)) => {
+                assert_eq!(keys(&list.values), vec!["x", "y"])
+            }
+            other => panic!("unexpected value {other:?}"),
+        }
+        assert_eq!(keys(&span.events[0].attributes), vec!["l", "m"]);
+    }
+}
@@ -1,11 +1,10 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use http::{header::CONTENT_TYPE, Method};
 use opentelemetry::logs::{LogError, LogResult};
 use opentelemetry_sdk::export::logs::{LogData, LogExporter};
 
-use super::OtlpHttpClient;
+use super::{send_with_retry, OtlpHttpClient};
 
 #[async_trait]
 impl LogExporter for OtlpHttpClient {
@@ -26,19 +25,17 @@ impl LogExporter for OtlpHttpClient {
             .collect::<Vec<LogData>>();
 
         let (body, content_type) = { self.build_logs_export_body(owned_batch)? };
-        let mut request = http::Request::builder()
-            .method(Method::POST)
-            .uri(&self.collector_endpoint)
-            .header(CONTENT_TYPE, content_type)
-            .body(body)
-            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
-
-        for (k, v) in &self.headers {
-            request.headers_mut().insert(k.clone(), v.clone());
-        }
+        let request = self.build_request(body, content_type)?;
 
         let request_uri = request.uri().to_string();
-        let response = client.send(request).await?;
+        let response = send_with_retry(
+            client,
+            request,
+            self.retry.as_ref(),
+            self.observer.as_ref(),
+            self.runtime_delay.as_ref(),
+        )
+        .await?;
 
         if !response.status().is_success() {
             let error = format!(
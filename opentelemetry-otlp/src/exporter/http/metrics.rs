@@ -1,13 +1,12 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use http::{header::CONTENT_TYPE, Method};
 use opentelemetry::metrics::{MetricsError, Result};
 use opentelemetry_sdk::metrics::data::ResourceMetrics;
 
 use crate::{metric::MetricsClient, Error};
 
-use super::OtlpHttpClient;
+use super::{send_with_retry, OtlpHttpClient};
 
 #[async_trait]
 impl MetricsClient for OtlpHttpClient {
@@ -22,21 +21,17 @@ impl MetricsClient for OtlpHttpClient {
             })?;
 
         let (body, content_type) = self.build_metrics_export_body(metrics)?;
-        let mut request = http::Request::builder()
-            .method(Method::POST)
-            .uri(&self.collector_endpoint)
-            .header(CONTENT_TYPE, content_type)
-            .body(body)
-            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
-
-        for (k, v) in &self.headers {
-            request.headers_mut().insert(k.clone(), v.clone());
-        }
-
-        client
-            .send(request)
-            .await
-            .map_err(|e| MetricsError::ExportErr(Box::new(Error::RequestFailed(e))))?;
+        let request = self.build_request(body, content_type)?;
+
+        send_with_retry(
+            client,
+            request,
+            self.retry.as_ref(),
+            self.observer.as_ref(),
+            self.runtime_delay.as_ref(),
+        )
+        .await
+        .map_err(|e| MetricsError::ExportErr(Box::new(Error::RequestFailed(e))))?;
 
         Ok(())
     }
@@ -1,13 +1,16 @@
+use super::canonical::SortAttributes;
+use super::retry::{retry, RetryConfig, RetryDecision, RuntimeDelay};
 use super::{
-    default_headers, default_protocol, parse_header_string,
-    OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT,
+    default_headers, default_protocol, parse_header_string, Compression, ExportResponse,
+    ResponseObserver, OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT,
 };
 use crate::{
-    ExportConfig, Protocol, OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_HEADERS,
-    OTEL_EXPORTER_OTLP_TIMEOUT,
+    ExportConfig, Protocol, OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_ENDPOINT,
+    OTEL_EXPORTER_OTLP_HEADERS, OTEL_EXPORTER_OTLP_TIMEOUT,
 };
-use http::{HeaderName, HeaderValue, Uri};
-use opentelemetry_http::HttpClient;
+use http::header::{CONTENT_ENCODING, CONTENT_TYPE, RETRY_AFTER};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+use opentelemetry_http::{Bytes, HttpClient, HttpError, HttpStatusError};
 use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
 #[cfg(feature = "logs")]
 use opentelemetry_proto::transform::logs::tonic::group_logs_by_resource_and_scope;
@@ -19,6 +22,7 @@ use opentelemetry_sdk::export::logs::LogData;
 use opentelemetry_sdk::export::trace::SpanData;
 #[cfg(feature = "metrics")]
 use opentelemetry_sdk::metrics::data::ResourceMetrics;
+use opentelemetry_sdk::runtime::Runtime;
 use prost::Message;
 use std::collections::HashMap;
 use std::env;
@@ -37,38 +41,59 @@ mod trace;
 
 /// Configuration of the http transport
 #[cfg(any(feature = "http-proto", feature = "http-json"))]
-#[derive(Debug)]
-#[cfg_attr(
-    all(
-        not(feature = "reqwest-client"),
-        not(feature = "reqwest-blocking-client")
-    ),
-    derive(Default)
-)]
+#[derive(Debug, Default)]
 pub(crate) struct HttpConfig {
-    /// Select the HTTP client
+    /// Select the HTTP client, the default client of the enabled reqwest
+    /// feature is created when building the exporter if not set.
     client: Option<Arc<dyn HttpClient>>,
 
     /// Additional headers to send to the collector.
     headers: Option<HashMap<String, String>>,
+
+    /// The compression algorithm to use when sending data to the collector.
+    compression: Option<Compression>,
+
+    /// Observer of the responses of the collector.
+    response_observer: Option<ResponseObserver>,
+
+    /// Delay between retried exports.
+    runtime_delay: Option<RuntimeDelay>,
 }
 
-#[cfg(any(feature = "reqwest-blocking-client", feature = "reqwest-client",))]
-impl Default for HttpConfig {
-    fn default() -> Self {
-        HttpConfig {
-            #[cfg(feature = "reqwest-blocking-client")]
-            client: Some(Arc::new(reqwest::blocking::Client::new())),
-            #[cfg(all(not(feature = "reqwest-blocking-client"), feature = "reqwest-client"))]
-            client: Some(Arc::new(reqwest::Client::new())),
-            #[cfg(all(
-                not(feature = "reqwest-client"),
-                not(feature = "reqwest-blocking-client")
-            ))]
-            client: None,
-            headers: None,
+/// Create the client of the enabled reqwest feature, applying the exporter
+/// timeouts.
+#[allow(unused_variables)]
+fn default_http_client(
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+) -> Result<Option<Arc<dyn HttpClient>>, crate::Error> {
+    #[cfg(feature = "reqwest-blocking-client")]
+    {
+        let mut builder = reqwest::blocking::Client::builder().timeout(timeout);
+        if let Some(connect_timeout) = connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
         }
+        let client = builder
+            .build()
+            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
+        Ok(Some(Arc::new(client)))
     }
+    #[cfg(all(not(feature = "reqwest-blocking-client"), feature = "reqwest-client"))]
+    {
+        let mut builder = reqwest::Client::builder().timeout(timeout);
+        if let Some(connect_timeout) = connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
+        Ok(Some(Arc::new(client)))
+    }
+    #[cfg(all(
+        not(feature = "reqwest-client"),
+        not(feature = "reqwest-blocking-client")
+    ))]
+    Ok(None)
 }
 
 /// Configuration for the OTLP HTTP exporter.
@@ -149,12 +174,45 @@ impl HttpExporterBuilder {
         self
     }
 
+    /// Set the compression algorithm to use when sending data to the collector.
+    ///
+    /// Gzip requires the `gzip-http` feature and zstd the `zstd-http` feature,
+    /// building the exporter fails if the matching feature is not enabled.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.http_config.compression = Some(compression);
+        self
+    }
+
+    /// Call `observer` with the headers of every response of the collector,
+    /// including those of failed and retried attempts.
+    ///
+    /// The observer is called on the task exporting the batch and should
+    /// return quickly.
+    pub fn with_response_observer<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&ExportResponse<'_>) + Send + Sync + 'static,
+    {
+        self.http_config.response_observer = Some(ResponseObserver::new(observer));
+        self
+    }
+
+    /// Delay retried exports with the timer of `runtime`.
+    ///
+    /// The http exporters can run on any runtime. Unless the runtime is known,
+    /// each delay between retries is driven by a short-lived thread. The
+    /// pipelines' `install_batch` methods set the runtime they are given.
+    pub fn with_runtime<R: Runtime>(mut self, runtime: R) -> Self {
+        self.http_config.runtime_delay = Some(RuntimeDelay::new(runtime));
+        self
+    }
+
     fn build_client(
         &mut self,
         signal_endpoint_var: &str,
         signal_endpoint_path: &str,
         signal_timeout_var: &str,
         signal_http_headers_var: &str,
+        signal_compression_var: &str,
     ) -> Result<OtlpHttpClient, crate::Error> {
         let endpoint = resolve_http_endpoint(
             signal_endpoint_var,
@@ -173,12 +231,6 @@ impl HttpExporterBuilder {
             None => self.exporter_config.timeout,
         };
 
-        let http_client = self
-            .http_config
-            .client
-            .take()
-            .ok_or(crate::Error::NoHttpClient)?;
-
         #[allow(clippy::mutable_key_type)] // http headers are not mutated
         let mut headers: HashMap<HeaderName, HeaderValue> = self
             .http_config
@@ -186,13 +238,8 @@ impl HttpExporterBuilder {
             .take()
             .unwrap_or_default()
             .into_iter()
-            .filter_map(|(k, v)| {
-                Some((
-                    HeaderName::from_str(&k).ok()?,
-                    HeaderValue::from_str(&v).ok()?,
-                ))
-            })
-            .collect();
+            .map(|(k, v)| Ok((HeaderName::from_str(&k)?, HeaderValue::from_str(&v)?)))
+            .collect::<Result<_, crate::Error>>()?;
 
         // read headers from env var - signal specific env var is preferred over general
         if let Ok(input) =
@@ -201,13 +248,28 @@ impl HttpExporterBuilder {
             add_header_from_string(&input, &mut headers);
         }
 
-        Ok(OtlpHttpClient::new(
+        let compression =
+            resolve_compression(self.http_config.compression, signal_compression_var)?;
+
+        let http_client = match self.http_config.client.take() {
+            Some(client) => client,
+            None => default_http_client(timeout, self.exporter_config.connect_timeout)?
+                .ok_or(crate::Error::NoHttpClient)?,
+        };
+
+        let mut client = OtlpHttpClient::new(
             http_client,
             endpoint,
             headers,
             self.exporter_config.protocol,
             timeout,
-        ))
+            compression,
+            RetryConfig::from_export_config(&self.exporter_config),
+        );
+        client.observer = self.http_config.response_observer.take();
+        client.runtime_delay = self.http_config.runtime_delay.take();
+        client.sort_attributes = self.exporter_config.sort_attributes;
+        Ok(client)
     }
 
     /// Create a log exporter with the current configuration
@@ -216,8 +278,8 @@ impl HttpExporterBuilder {
         mut self,
     ) -> Result<crate::SpanExporter, opentelemetry::trace::TraceError> {
         use crate::{
-            OTEL_EXPORTER_OTLP_TRACES_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_HEADERS,
-            OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+            OTEL_EXPORTER_OTLP_TRACES_COMPRESSION, OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+            OTEL_EXPORTER_OTLP_TRACES_HEADERS, OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
         };
 
         let client = self.build_client(
@@ -225,6 +287,7 @@ impl HttpExporterBuilder {
             "/v1/traces",
             OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
             OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
         )?;
 
         Ok(crate::SpanExporter::new(client))
@@ -234,8 +297,8 @@ impl HttpExporterBuilder {
     #[cfg(feature = "logs")]
     pub fn build_log_exporter(mut self) -> opentelemetry::logs::LogResult<crate::LogExporter> {
         use crate::{
-            OTEL_EXPORTER_OTLP_LOGS_ENDPOINT, OTEL_EXPORTER_OTLP_LOGS_HEADERS,
-            OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
+            OTEL_EXPORTER_OTLP_LOGS_COMPRESSION, OTEL_EXPORTER_OTLP_LOGS_ENDPOINT,
+            OTEL_EXPORTER_OTLP_LOGS_HEADERS, OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
         };
 
         let client = self.build_client(
@@ -243,6 +306,7 @@ impl HttpExporterBuilder {
             "/v1/logs",
             OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
             OTEL_EXPORTER_OTLP_LOGS_HEADERS,
+            OTEL_EXPORTER_OTLP_LOGS_COMPRESSION,
         )?;
 
         Ok(crate::LogExporter::new(client))
@@ -256,8 +320,8 @@ impl HttpExporterBuilder {
         temporality_selector: Box<dyn opentelemetry_sdk::metrics::reader::TemporalitySelector>,
     ) -> opentelemetry::metrics::Result<crate::MetricsExporter> {
         use crate::{
-            OTEL_EXPORTER_OTLP_METRICS_ENDPOINT, OTEL_EXPORTER_OTLP_METRICS_HEADERS,
-            OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
+            OTEL_EXPORTER_OTLP_METRICS_COMPRESSION, OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
+            OTEL_EXPORTER_OTLP_METRICS_HEADERS, OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
         };
 
         let client = self.build_client(
@@ -265,6 +329,7 @@ impl HttpExporterBuilder {
             "/v1/metrics",
             OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
             OTEL_EXPORTER_OTLP_METRICS_HEADERS,
+            OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
         )?;
 
         Ok(crate::MetricsExporter::new(
@@ -282,6 +347,11 @@ struct OtlpHttpClient {
     headers: HashMap<HeaderName, HeaderValue>,
     protocol: Protocol,
     _timeout: Duration,
+    compression: Option<Compression>,
+    retry: Option<RetryConfig>,
+    observer: Option<ResponseObserver>,
+    runtime_delay: Option<RuntimeDelay>,
+    sort_attributes: bool,
     #[allow(dead_code)]
     // <allow dead> would be removed once we support set_resource for metrics and traces.
     resource: opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema,
@@ -295,6 +365,8 @@ impl OtlpHttpClient {
         headers: HashMap<HeaderName, HeaderValue>,
         protocol: Protocol,
         timeout: Duration,
+        compression: Option<Compression>,
+        retry: Option<RetryConfig>,
     ) -> Self {
         OtlpHttpClient {
             client: Mutex::new(Some(client)),
@@ -302,10 +374,45 @@ impl OtlpHttpClient {
             headers,
             protocol,
             _timeout: timeout,
+            compression,
+            retry,
+            observer: None,
+            runtime_delay: None,
+            sort_attributes: false,
             resource: ResourceAttributesWithSchema::default(),
         }
     }
 
+    /// Build the export request for an encoded `body`, compressing it if
+    /// configured.
+    fn build_request(
+        &self,
+        body: Vec<u8>,
+        content_type: &'static str,
+    ) -> Result<http::Request<Vec<u8>>, crate::Error> {
+        let (body, content_encoding) = match self.compression {
+            Some(compression) => (compress(compression, body)?, Some(compression)),
+            None => (body, None),
+        };
+
+        let mut builder = http::Request::builder()
+            .method(Method::POST)
+            .uri(&self.collector_endpoint)
+            .header(CONTENT_TYPE, content_type);
+        if let Some(compression) = content_encoding {
+            builder = builder.header(CONTENT_ENCODING, compression.to_string());
+        }
+        let mut request = builder
+            .body(body)
+            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
+
+        for (k, v) in &self.headers {
+            request.headers_mut().insert(k.clone(), v.clone());
+        }
+
+        Ok(request)
+    }
+
     #[cfg(feature = "trace")]
     fn build_trace_export_body(
         &self,
@@ -314,7 +421,10 @@ impl OtlpHttpClient {
         use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
         let resource_spans = group_spans_by_resource_and_scope(spans, &self.resource);
 
-        let req = ExportTraceServiceRequest { resource_spans };
+        let mut req = ExportTraceServiceRequest { resource_spans };
+        if self.sort_attributes {
+            req.sort_attributes();
+        }
         match self.protocol {
             #[cfg(feature = "http-json")]
             Protocol::HttpJson => match serde_json::to_string_pretty(&req) {
@@ -332,7 +442,10 @@ impl OtlpHttpClient {
     ) -> opentelemetry::logs::LogResult<(Vec<u8>, &'static str)> {
         use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
         let resource_logs = group_logs_by_resource_and_scope(logs, &self.resource);
-        let req = ExportLogsServiceRequest { resource_logs };
+        let mut req = ExportLogsServiceRequest { resource_logs };
+        if self.sort_attributes {
+            req.sort_attributes();
+        }
 
         match self.protocol {
             #[cfg(feature = "http-json")]
@@ -351,7 +464,10 @@ impl OtlpHttpClient {
     ) -> opentelemetry::metrics::Result<(Vec<u8>, &'static str)> {
         use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
 
-        let req: ExportMetricsServiceRequest = (&*metrics).into();
+        let mut req: ExportMetricsServiceRequest = (&*metrics).into();
+        if self.sort_attributes {
+            req.sort_attributes();
+        }
 
         match self.protocol {
             #[cfg(feature = "http-json")]
@@ -364,6 +480,169 @@ impl OtlpHttpClient {
     }
 }
 
+/// Resolve the compression algorithm, the builder setting is preferred over
+/// the signal specific env var, which is preferred over the general one.
+fn resolve_compression(
+    compression: Option<Compression>,
+    signal_compression_var: &str,
+) -> Result<Option<Compression>, crate::Error> {
+    let compression = match compression {
+        Some(compression) => compression,
+        None => match env::var(signal_compression_var)
+            .or_else(|_| env::var(OTEL_EXPORTER_OTLP_COMPRESSION))
+        {
+            Ok(compression) => compression.parse()?,
+            Err(_) => return Ok(None),
+        },
+    };
+
+    match compression {
+        #[cfg(feature = "gzip-http")]
+        Compression::Gzip => Ok(Some(compression)),
+        #[cfg(feature = "zstd-http")]
+        Compression::Zstd => Ok(Some(compression)),
+        #[allow(unreachable_patterns)]
+        _ => Err(crate::Error::UnsupportedCompressionAlgorithm(
+            compression.to_string(),
+        )),
+    }
+}
+
+/// Compress `body` with `compression`, which [`resolve_compression`] has
+/// checked to be enabled.
+#[allow(unused_variables)]
+fn compress(compression: Compression, body: Vec<u8>) -> Result<Vec<u8>, crate::Error> {
+    match compression {
+        #[cfg(feature = "gzip-http")]
+        Compression::Gzip => {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(&body)
+                .map_err(crate::Error::CompressionFailed)?;
+            encoder.finish().map_err(crate::Error::CompressionFailed)
+        }
+        #[cfg(feature = "zstd-http")]
+        Compression::Zstd => {
+            zstd::bulk::compress(&body, 0).map_err(crate::Error::CompressionFailed)
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(crate::Error::UnsupportedCompressionAlgorithm(
+            compression.to_string(),
+        )),
+    }
+}
+
+/// Send `request` with `client`, retrying failures the collector reports as
+/// transient according to `retry_config`.
+///
+/// Attempts are bounded by the timeout of the client, which is the exporter
+/// timeout for the default clients, and delayed with `runtime_delay`. The
+/// response of every attempt is passed to `observer`.
+async fn send_with_retry(
+    client: Arc<dyn HttpClient>,
+    request: http::Request<Vec<u8>>,
+    retry_config: Option<&RetryConfig>,
+    observer: Option<&ResponseObserver>,
+    runtime_delay: Option<&RuntimeDelay>,
+) -> Result<http::Response<Bytes>, HttpError> {
+    let send = |client: Arc<dyn HttpClient>, request: http::Request<Vec<u8>>| async move {
+        let result = client.send(request).await;
+        if let Some(observer) = observer {
+            observe_response(observer, &result);
+        }
+        result
+    };
+
+    let Some(retry_config) = retry_config else {
+        return send(client, request).await;
+    };
+
+    retry(
+        retry_config,
+        |_| send(Arc::clone(&client), clone_request(&request)),
+        retry_decision,
+        |duration| RuntimeDelay::sleep(runtime_delay, duration),
+    )
+    .await
+}
+
+fn observe_response(
+    observer: &ResponseObserver,
+    result: &Result<http::Response<Bytes>, HttpError>,
+) {
+    let (status, headers) = match result {
+        Ok(response) => (response.status(), response.headers()),
+        Err(err) => match err.downcast_ref::<HttpStatusError>() {
+            Some(err) => (err.status(), err.headers()),
+            // no response was received
+            None => return,
+        },
+    };
+    observer.observe(&ExportResponse {
+        headers,
+        accepted: status.is_success(),
+        retry_after: retry_after(headers),
+    });
+}
+
+fn clone_request(request: &http::Request<Vec<u8>>) -> http::Request<Vec<u8>> {
+    let mut clone = http::Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    clone
+}
+
+/// Whether an export may succeed if retried, see
+/// <https://github.com/open-telemetry/opentelemetry-proto/blob/main/docs/specification.md#retryable-response-codes>
+///
+/// A `Retry-After` header in seconds sets the delay before retrying. Failures
+/// other than unsuccessful responses are retried, unless the request could
+/// not be built.
+fn retry_decision(result: &Result<http::Response<Bytes>, HttpError>) -> RetryDecision {
+    match result {
+        Ok(response) => status_retry_decision(response.status(), response.headers()),
+        Err(err) => {
+            if let Some(err) = err.downcast_ref::<HttpStatusError>() {
+                return status_retry_decision(err.status(), err.headers());
+            }
+            #[cfg(feature = "reqwest")]
+            if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+                if err.is_builder() {
+                    return RetryDecision::Stop;
+                }
+            }
+            RetryDecision::Backoff
+        }
+    }
+}
+
+fn status_retry_decision(status: StatusCode, headers: &HeaderMap) -> RetryDecision {
+    if !matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    ) {
+        return RetryDecision::Stop;
+    }
+    retry_after(headers).map_or(RetryDecision::Backoff, RetryDecision::After)
+}
+
+/// The delay of a `Retry-After` header in seconds.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
 fn build_endpoint_uri(endpoint: &str, path: &str) -> Result<Uri, crate::Error> {
     let path = if endpoint.ends_with('/') && path.starts_with('/') {
         path.strip_prefix('/').unwrap()
@@ -419,9 +698,12 @@ fn add_header_from_string(input: &str, headers: &mut HashMap<HeaderName, HeaderV
 mod tests {
     use crate::exporter::tests::run_env_test;
     use crate::{
-        new_exporter, WithExportConfig, OTEL_EXPORTER_OTLP_ENDPOINT,
-        OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+        new_exporter, Compression, WithExportConfig, OTEL_EXPORTER_OTLP_COMPRESSION,
+        OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+        OTEL_EXPORTER_OTLP_TRACES_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+        OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
     };
+    use std::collections::HashMap;
 
     use super::{build_endpoint_uri, resolve_http_endpoint};
 
@@ -656,4 +938,206 @@ mod tests {
             assert_eq!(url, "http://localhost:4318/v1/tracesbutnotreally");
         });
     }
+
+    #[test]
+    fn test_invalid_header_is_rejected() {
+        run_env_test(vec![], || {
+            let mut exporter = new_exporter().http().with_headers(HashMap::from([(
+                "invalid header".to_string(),
+                "value".to_string(),
+            )]));
+
+            let result = exporter.build_client(
+                OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+                "/v1/traces",
+                OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+                OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+                OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+            );
+
+            assert!(matches!(result, Err(crate::Error::InvalidHeaderName(_))));
+        });
+    }
+
+    #[test]
+    fn test_resolve_compression() {
+        use super::resolve_compression;
+
+        run_env_test(vec![], || {
+            assert_eq!(
+                resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION).unwrap(),
+                None
+            );
+        });
+
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_COMPRESSION, "snappy")], || {
+            assert!(matches!(
+                resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION),
+                Err(crate::Error::UnsupportedCompressionAlgorithm(_))
+            ));
+        });
+
+        run_env_test(
+            vec![(OTEL_EXPORTER_OTLP_TRACES_COMPRESSION, "gzip")],
+            || {
+                let result = resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION);
+                #[cfg(feature = "gzip-http")]
+                assert_eq!(result.unwrap(), Some(Compression::Gzip));
+                #[cfg(not(feature = "gzip-http"))]
+                assert!(matches!(
+                    result,
+                    Err(crate::Error::UnsupportedCompressionAlgorithm(_))
+                ));
+            },
+        );
+
+        // builder setting takes precedence over env vars
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_COMPRESSION, "gzip")], || {
+            let result = resolve_compression(
+                Some(Compression::Zstd),
+                OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+            );
+            #[cfg(feature = "zstd-http")]
+            assert_eq!(result.unwrap(), Some(Compression::Zstd));
+            #[cfg(not(feature = "zstd-http"))]
+            assert!(matches!(
+                result,
+                Err(crate::Error::UnsupportedCompressionAlgorithm(_))
+            ));
+        });
+    }
+
+    #[cfg(feature = "gzip-http")]
+    #[test]
+    fn test_gzip_compression_round_trip() {
+        use std::io::Read;
+
+        let body = b"opentelemetry".repeat(16);
+        let compressed = super::compress(Compression::Gzip, body.clone()).unwrap();
+
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+    }
+
+    #[cfg(feature = "zstd-http")]
+    #[test]
+    fn test_zstd_compression_round_trip() {
+        let body = b"opentelemetry".repeat(16);
+        let compressed = super::compress(Compression::Zstd, body.clone()).unwrap();
+
+        assert_eq!(zstd::decode_all(compressed.as_slice()).unwrap(), body);
+    }
+
+    #[test]
+    fn test_retryable_responses() {
+        use super::{clone_request, retry_decision, RetryDecision};
+        use http::header::RETRY_AFTER;
+        use http::{Method, Response, StatusCode};
+        use opentelemetry_http::ResponseExt;
+        use std::time::Duration;
+
+        let response = |status: StatusCode| {
+            Response::builder()
+                .status(status)
+                .body(Default::default())
+                .unwrap()
+        };
+        let decision = |status| retry_decision(&Ok(response(status)));
+        assert_eq!(
+            decision(StatusCode::SERVICE_UNAVAILABLE),
+            RetryDecision::Backoff
+        );
+        assert_eq!(
+            decision(StatusCode::TOO_MANY_REQUESTS),
+            RetryDecision::Backoff
+        );
+        assert_eq!(decision(StatusCode::OK), RetryDecision::Stop);
+        assert_eq!(decision(StatusCode::BAD_REQUEST), RetryDecision::Stop);
+
+        let mut throttled = response(StatusCode::TOO_MANY_REQUESTS);
+        throttled
+            .headers_mut()
+            .insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(
+            retry_decision(&throttled.error_for_status()),
+            RetryDecision::After(Duration::from_secs(7))
+        );
+
+        // http dates are not supported, the backoff is used instead
+        let mut unavailable = response(StatusCode::SERVICE_UNAVAILABLE);
+        unavailable.headers_mut().insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(
+            retry_decision(&unavailable.error_for_status()),
+            RetryDecision::Backoff
+        );
+
+        let request = http::Request::builder()
+            .method(Method::POST)
+            .uri("http://localhost:4318/v1/traces")
+            .header("content-type", "application/x-protobuf")
+            .body(vec![1, 2, 3])
+            .unwrap();
+        let clone = clone_request(&request);
+        assert_eq!(clone.method(), request.method());
+        assert_eq!(clone.uri(), request.uri());
+        assert_eq!(clone.headers(), request.headers());
+        assert_eq!(clone.body(), request.body());
+    }
+
+    #[test]
+    fn test_observe_responses() {
+        use super::{observe_response, ResponseObserver};
+        use http::header::RETRY_AFTER;
+        use http::{Response, StatusCode};
+        use opentelemetry_http::ResponseExt;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let observer = {
+            let observed = Arc::clone(&observed);
+            ResponseObserver::new(move |response| {
+                observed.lock().unwrap().push((
+                    response.accepted,
+                    response.retry_after,
+                    response.headers.get("x-ratelimit-remaining").cloned(),
+                ))
+            })
+        };
+
+        let mut accepted = Response::builder()
+            .status(StatusCode::OK)
+            .body(Default::default())
+            .unwrap();
+        accepted
+            .headers_mut()
+            .insert("x-ratelimit-remaining", "10".parse().unwrap());
+        observe_response(&observer, &Ok(accepted));
+
+        let mut throttled = Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .body(Default::default())
+            .unwrap();
+        throttled
+            .headers_mut()
+            .insert(RETRY_AFTER, "7".parse().unwrap());
+        observe_response(&observer, &throttled.error_for_status());
+
+        // failures without a response are not observed
+        observe_response(&observer, &Err("connection refused".into()));
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            vec![
+                (true, None, Some("10".parse().unwrap())),
+                (false, Some(Duration::from_secs(7)), None),
+            ]
+        );
+    }
 }
@@ -1,11 +1,10 @@
 use std::sync::Arc;
 
 use futures_core::future::BoxFuture;
-use http::{header::CONTENT_TYPE, Method};
 use opentelemetry::trace::TraceError;
 use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
 
-use super::OtlpHttpClient;
+use super::{send_with_retry, OtlpHttpClient};
 
 impl SpanExporter for OtlpHttpClient {
     fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
@@ -26,28 +25,24 @@ impl SpanExporter for OtlpHttpClient {
             Err(e) => return Box::pin(std::future::ready(Err(e))),
         };
 
-        let mut request = match http::Request::builder()
-            .method(Method::POST)
-            .uri(&self.collector_endpoint)
-            .header(CONTENT_TYPE, content_type)
-            .body(body)
-        {
+        let request = match self.build_request(body, content_type) {
             Ok(req) => req,
-            Err(e) => {
-                return Box::pin(std::future::ready(Err(crate::Error::RequestFailed(
-                    Box::new(e),
-                )
-                .into())))
-            }
+            Err(e) => return Box::pin(std::future::ready(Err(e.into()))),
         };
 
-        for (k, v) in &self.headers {
-            request.headers_mut().insert(k.clone(), v.clone());
-        }
-
+        let retry = self.retry.clone();
+        let observer = self.observer.clone();
+        let runtime_delay = self.runtime_delay.clone();
         Box::pin(async move {
             let request_uri = request.uri().to_string();
-            let response = client.send(request).await?;
+            let response = send_with_retry(
+                client,
+                request,
+                retry.as_ref(),
+                observer.as_ref(),
+                runtime_delay.as_ref(),
+            )
+            .await?;
 
             if !response.status().is_success() {
                 let error = format!(
@@ -59,8 +59,12 @@ pub const OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT: u64 = 10;
 const OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT: &str = "http://localhost:4317";
 const OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT: &str = "http://localhost:4318";
 
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+pub(crate) mod canonical;
 #[cfg(any(feature = "http-proto", feature = "http-json"))]
 pub(crate) mod http;
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+pub(crate) mod retry;
 #[cfg(feature = "grpc-tonic")]
 pub(crate) mod tonic;
 
@@ -74,8 +78,31 @@ pub struct ExportConfig {
     /// The protocol to use when communicating with the collector.
     pub protocol: Protocol,
 
-    /// The timeout to the collector.
+    /// The timeout of each attempt to export a batch to the collector.
     pub timeout: Duration,
+
+    /// The timeout of establishing a connection to the collector, bounded by
+    /// [`timeout`](Self::timeout) if not set.
+    pub connect_timeout: Option<Duration>,
+
+    /// The deadline to export a batch across all attempts.
+    ///
+    /// If set, attempts failing with a retryable error are retried according
+    /// to the [`retry_policy`](Self::retry_policy), or the default
+    /// [`RetryPolicy`] if none is set, until the deadline.
+    pub export_deadline: Option<Duration>,
+
+    /// The policy to retry attempts failing with a retryable error.
+    ///
+    /// Without a retry policy or an [export
+    /// deadline](Self::export_deadline) each batch is exported in a single
+    /// attempt.
+    pub retry_policy: Option<RetryPolicy>,
+
+    /// Whether to sort attributes by key before encoding export requests.
+    ///
+    /// The same telemetry is then always encoded to the same bytes.
+    pub sort_attributes: bool,
 }
 
 impl Default for ExportConfig {
@@ -88,22 +115,118 @@ impl Default for ExportConfig {
             // won't know if user provided a value
             protocol,
             timeout: Duration::from_secs(OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT),
+            connect_timeout: None,
+            export_deadline: None,
+            retry_policy: None,
+            sort_attributes: false,
         }
     }
 }
 
+/// How exports failing with a transient error are retried.
+///
+/// Failures the collector reports as transient, e.g. because it is
+/// unavailable or overloaded, are retried with exponential backoff. The
+/// backoff starts at [`initial_backoff`](Self::initial_backoff), doubles
+/// after every attempt up to [`max_backoff`](Self::max_backoff) and is
+/// randomly shortened by up to the [`jitter`](Self::jitter) fraction of it.
+///
+/// Collectors can ask for a specific delay with a `RetryInfo` in gRPC status
+/// details or a `Retry-After` header in seconds for HTTP, which is used
+/// instead of the backoff.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts to export a batch, including the first
+    /// one. Defaults to 5.
+    pub max_attempts: usize,
+
+    /// The delay before the first retry. Defaults to 1 second.
+    pub initial_backoff: Duration,
+
+    /// The maximum delay between two attempts. Defaults to 5 seconds.
+    pub max_backoff: Duration,
+
+    /// The fraction of each delay, between 0 and 1, that is randomized to
+    /// keep exporters from retrying in lockstep. Defaults to 0.2.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.2,
+        }
+    }
+}
+
+/// Metadata of a response of the collector to an export attempt.
+///
+/// Responses are passed to the observer set with `with_response_observer` on
+/// the exporter builders, e.g. to adapt the batch size to the rate limits of
+/// the collector before exports start failing.
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ExportResponse<'a> {
+    /// The headers of the HTTP response, or the metadata of the gRPC
+    /// response.
+    pub headers: &'a ::http::HeaderMap,
+
+    /// Whether the collector accepted the exported batch.
+    pub accepted: bool,
+
+    /// The delay before retrying the collector asked for, with a
+    /// `Retry-After` header in seconds for HTTP or a `RetryInfo` in gRPC
+    /// status details.
+    pub retry_after: Option<Duration>,
+}
+
+/// Calls a user function with every response of the collector.
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+#[derive(Clone)]
+pub(crate) struct ResponseObserver(
+    std::sync::Arc<dyn Fn(&ExportResponse<'_>) + Send + Sync + 'static>,
+);
+
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+impl ResponseObserver {
+    pub(crate) fn new<F>(observer: F) -> Self
+    where
+        F: Fn(&ExportResponse<'_>) + Send + Sync + 'static,
+    {
+        ResponseObserver(std::sync::Arc::new(observer))
+    }
+
+    pub(crate) fn observe(&self, response: &ExportResponse<'_>) {
+        (self.0)(response)
+    }
+}
+
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+impl std::fmt::Debug for ResponseObserver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ResponseObserver(..)")
+    }
+}
+
 /// The compression algorithm to use when sending data.
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Compression {
     /// Compresses data using gzip.
     Gzip,
+    /// Compresses data using zstd.
+    Zstd,
 }
 
 impl Display for Compression {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
         }
     }
 }
@@ -114,6 +237,7 @@ impl FromStr for Compression {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
             _ => Err(Error::UnsupportedCompressionAlgorithm(s.to_string())),
         }
     }
@@ -185,8 +309,32 @@ pub trait WithExportConfig {
     /// ## Note
     /// All exporters in this crate are only support one protocol thus choosing the protocol is an no-op at the moment
     fn with_protocol(self, protocol: Protocol) -> Self;
-    /// Set the timeout to the collector.
+    /// Set the timeout of each attempt to export a batch to the collector.
     fn with_timeout(self, timeout: Duration) -> Self;
+    /// Set the timeout of establishing a connection to the collector.
+    ///
+    /// The http exporters only apply it to their default clients, custom http
+    /// clients must be configured directly.
+    fn with_connect_timeout(self, timeout: Duration) -> Self;
+    /// Retry failed exports until `deadline` elapses since the first attempt.
+    ///
+    /// Exports are retried when the collector is unavailable, overloaded or
+    /// the attempt timed out, with exponential backoff between attempts.
+    /// Unlike the [timeout](Self::with_timeout) of each attempt, the deadline
+    /// bounds the total time spent exporting a batch.
+    fn with_export_deadline(self, deadline: Duration) -> Self;
+    /// Retry exports failing with a transient error according to `policy`.
+    ///
+    /// Combined with an [export deadline](Self::with_export_deadline) the
+    /// retries stop at whichever limit is reached first.
+    fn with_retry_policy(self, policy: RetryPolicy) -> Self;
+    /// Sort the attributes of spans, logs, metrics, their scopes and resources
+    /// by key before encoding them.
+    ///
+    /// Scopes are sorted by name and version as well, so that the same
+    /// telemetry is always encoded to the same bytes, e.g. to compare
+    /// payloads in tests or deduplicate them by content hash.
+    fn with_sorted_attributes(self) -> Self;
     /// Set export config. This will override all previous configuration.
     fn with_export_config(self, export_config: ExportConfig) -> Self;
 }
@@ -207,10 +355,34 @@ impl<B: HasExportConfig> WithExportConfig for B {
         self
     }
 
+    fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.export_config().connect_timeout = Some(timeout);
+        self
+    }
+
+    fn with_export_deadline(mut self, deadline: Duration) -> Self {
+        self.export_config().export_deadline = Some(deadline);
+        self
+    }
+
+    fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.export_config().retry_policy = Some(policy);
+        self
+    }
+
+    fn with_sorted_attributes(mut self) -> Self {
+        self.export_config().sort_attributes = true;
+        self
+    }
+
     fn with_export_config(mut self, exporter_config: ExportConfig) -> Self {
         self.export_config().endpoint = exporter_config.endpoint;
         self.export_config().protocol = exporter_config.protocol;
         self.export_config().timeout = exporter_config.timeout;
+        self.export_config().connect_timeout = exporter_config.connect_timeout;
+        self.export_config().export_deadline = exporter_config.export_deadline;
+        self.export_config().retry_policy = exporter_config.retry_policy;
+        self.export_config().sort_attributes = exporter_config.sort_attributes;
         self
     }
 }
@@ -300,6 +472,53 @@ mod tests {
         assert_eq!(exporter_builder.exporter_config.endpoint, "");
     }
 
+    #[cfg(feature = "grpc-tonic")]
+    #[test]
+    fn test_with_export_config_copies_timeouts() {
+        use crate::{ExportConfig, RetryPolicy, WithExportConfig};
+        use std::time::Duration;
+
+        let mut exporter_builder = crate::new_exporter()
+            .tonic()
+            .with_connect_timeout(Duration::from_secs(1))
+            .with_export_deadline(Duration::from_secs(30))
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                ..RetryPolicy::default()
+            })
+            .with_sorted_attributes();
+        assert!(exporter_builder.exporter_config.sort_attributes);
+        assert_eq!(
+            exporter_builder.exporter_config.connect_timeout,
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            exporter_builder.exporter_config.export_deadline,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            exporter_builder
+                .exporter_config
+                .retry_policy
+                .as_ref()
+                .map(|policy| policy.max_attempts),
+            Some(3)
+        );
+
+        exporter_builder = exporter_builder.with_export_config(ExportConfig {
+            timeout: Duration::from_secs(2),
+            ..ExportConfig::default()
+        });
+        assert_eq!(
+            exporter_builder.exporter_config.timeout,
+            Duration::from_secs(2)
+        );
+        assert_eq!(exporter_builder.exporter_config.connect_timeout, None);
+        assert_eq!(exporter_builder.exporter_config.export_deadline, None);
+        assert_eq!(exporter_builder.exporter_config.retry_policy, None);
+        assert!(!exporter_builder.exporter_config.sort_attributes);
+    }
+
     #[test]
     fn test_default_protocol() {
         #[cfg(all(
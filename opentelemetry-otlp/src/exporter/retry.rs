@@ -0,0 +1,399 @@
+//! Retrying failed exports according to the retry policy and export deadline.
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use std::pin::Pin;
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use std::sync::{Arc, Mutex};
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use futures_core::future::BoxFuture;
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use opentelemetry_sdk::runtime::Runtime;
+
+use crate::{ExportConfig, RetryPolicy};
+
+/// How failed exports are retried, if they are.
+#[derive(Clone, Debug)]
+pub(crate) struct RetryConfig {
+    policy: RetryPolicy,
+    deadline: Option<Duration>,
+}
+
+impl RetryConfig {
+    /// The retry configuration of `config`, `None` if exports are attempted
+    /// only once.
+    pub(crate) fn from_export_config(config: &ExportConfig) -> Option<Self> {
+        if config.retry_policy.is_none() && config.export_deadline.is_none() {
+            return None;
+        }
+        Some(RetryConfig {
+            policy: config.retry_policy.clone().unwrap_or_default(),
+            deadline: config.export_deadline,
+        })
+    }
+}
+
+/// Whether and when a failed attempt should be retried.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RetryDecision {
+    /// The result is final.
+    Stop,
+    /// Retry after the backoff of the policy.
+    Backoff,
+    /// Retry after the delay the collector asked for.
+    After(Duration),
+}
+
+/// The retry state of a single export, deciding whether and after which
+/// delay each failed attempt is retried.
+#[derive(Debug)]
+pub(crate) struct Backoff<'a> {
+    policy: &'a RetryPolicy,
+    deadline: Option<Instant>,
+    backoff: Duration,
+    attempts: usize,
+}
+
+impl RetryConfig {
+    /// Start retrying an export whose first attempt starts now.
+    pub(crate) fn backoff(&self) -> Backoff<'_> {
+        Backoff {
+            policy: &self.policy,
+            deadline: self.deadline.map(|deadline| Instant::now() + deadline),
+            backoff: self.policy.initial_backoff,
+            attempts: 1,
+        }
+    }
+}
+
+impl Backoff<'_> {
+    /// The time remaining until the deadline, if any.
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// The delay before the next attempt, given the `decision` for the result
+    /// of the last one, or `None` if it is final because retries stop or the
+    /// retry policy or deadline is exhausted.
+    pub(crate) fn next_delay(&mut self, decision: RetryDecision) -> Option<Duration> {
+        let delay = match decision {
+            RetryDecision::Stop => return None,
+            RetryDecision::Backoff => with_jitter(self.backoff, self.policy.jitter),
+            RetryDecision::After(delay) => delay,
+        };
+        if self.attempts >= self.policy.max_attempts
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() + delay >= deadline)
+        {
+            return None;
+        }
+        self.attempts += 1;
+        self.backoff = (self.backoff * 2).min(self.policy.max_backoff);
+        Some(delay)
+    }
+}
+
+/// Run `attempt` until `decide` stops retrying its result or the retry
+/// policy or deadline of `config` is exhausted, returning the last result.
+///
+/// Each attempt is passed the time remaining until the deadline, if any.
+pub(crate) async fn retry<T, A, AFut, S, SFut>(
+    config: &RetryConfig,
+    mut attempt: A,
+    decide: impl Fn(&T) -> RetryDecision,
+    sleep: S,
+) -> T
+where
+    A: FnMut(Option<Duration>) -> AFut,
+    AFut: Future<Output = T>,
+    S: Fn(Duration) -> SFut,
+    SFut: Future<Output = ()>,
+{
+    let mut backoff = config.backoff();
+    loop {
+        let result = attempt(backoff.remaining()).await;
+        match backoff.next_delay(decide(&result)) {
+            Some(delay) => sleep(delay).await,
+            None => return result,
+        }
+    }
+}
+
+/// Shorten `backoff` by a random share of up to `jitter` of it.
+fn with_jitter(backoff: Duration, jitter: f64) -> Duration {
+    let jitter = if jitter.is_nan() {
+        0.0
+    } else {
+        jitter.clamp(0.0, 1.0)
+    };
+    // RandomState is randomly seeded, good enough to spread out retries
+    // without depending on a random number generator
+    let random = RandomState::new().build_hasher().finish();
+    let unit = (random >> 11) as f64 / (1u64 << 53) as f64;
+    backoff.mul_f64(1.0 - jitter * unit)
+}
+
+/// Sleeps between retried http exports with the timer of the runtime the
+/// exporter runs on.
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+#[derive(Clone)]
+pub(crate) struct RuntimeDelay(Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>);
+
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+impl RuntimeDelay {
+    pub(crate) fn new<R: Runtime>(runtime: R) -> Self {
+        RuntimeDelay(Arc::new(move |duration| {
+            let delay = runtime.delay(duration);
+            Box::pin(async move {
+                delay.await;
+            })
+        }))
+    }
+
+    /// Sleep for `duration`, with the thread driven delay of
+    /// [`runtime_agnostic_sleep`] if no runtime is known.
+    pub(crate) fn sleep(
+        delay: Option<&RuntimeDelay>,
+        duration: Duration,
+    ) -> BoxFuture<'static, ()> {
+        match delay {
+            Some(delay) => (delay.0)(duration),
+            None => Box::pin(runtime_agnostic_sleep(duration)),
+        }
+    }
+}
+
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+impl std::fmt::Debug for RuntimeDelay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RuntimeDelay(..)")
+    }
+}
+
+/// Sleep for `duration` without depending on an async runtime, for http
+/// exporters whose runtime is unknown, e.g. exporters driven by the simple
+/// processors.
+///
+/// The delay is driven by a thread, which is fine for the rare backoffs
+/// between retries.
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+pub(crate) fn runtime_agnostic_sleep(duration: Duration) -> impl Future<Output = ()> {
+    ThreadDelay {
+        duration,
+        state: None,
+    }
+}
+
+// whether the delay elapsed and the waker to notify when it does
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+type DelayState = Arc<Mutex<(bool, Option<Waker>)>>;
+
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+struct ThreadDelay {
+    duration: Duration,
+    state: Option<DelayState>,
+}
+
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+impl Future for ThreadDelay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let duration = self.duration;
+        let state = self.state.get_or_insert_with(|| {
+            let state: DelayState = Arc::new(Mutex::new((false, None)));
+            let timer_state = Arc::clone(&state);
+            std::thread::spawn(move || {
+                std::thread::sleep(duration);
+                if let Ok(mut state) = timer_state.lock() {
+                    state.0 = true;
+                    if let Some(waker) = state.1.take() {
+                        waker.wake();
+                    }
+                }
+            });
+            state
+        });
+
+        match state.lock() {
+            Ok(mut state) if !state.0 => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            // a poisoned lock means the timer thread is gone, don't wait forever
+            _ => Poll::Ready(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "grpc-tonic"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    use super::{retry, with_jitter, RetryConfig, RetryDecision};
+    use crate::{ExportConfig, RetryPolicy};
+
+    fn config(policy: RetryPolicy, deadline: Option<Duration>) -> RetryConfig {
+        RetryConfig::from_export_config(&ExportConfig {
+            export_deadline: deadline,
+            retry_policy: Some(policy),
+            ..ExportConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn fast_policy(max_attempts: usize) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+            jitter: 0.0,
+        }
+    }
+
+    fn retry_errors(result: &Result<(), ()>) -> RetryDecision {
+        match result {
+            Ok(()) => RetryDecision::Stop,
+            Err(()) => RetryDecision::Backoff,
+        }
+    }
+
+    #[test]
+    fn no_retries_without_policy_or_deadline() {
+        assert!(RetryConfig::from_export_config(&ExportConfig::default()).is_none());
+
+        let config = RetryConfig::from_export_config(&ExportConfig {
+            export_deadline: Some(Duration::from_secs(1)),
+            ..ExportConfig::default()
+        })
+        .unwrap();
+        assert_eq!(config.policy, RetryPolicy::default());
+    }
+
+    #[tokio::test]
+    async fn retries_until_success_or_max_attempts() {
+        let attempts = AtomicUsize::new(0);
+        let result = retry(
+            &config(fast_policy(5), None),
+            |remaining| {
+                assert_eq!(remaining, None);
+                let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if attempt < 2 {
+                        Err(())
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+            retry_errors,
+            tokio::time::sleep,
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+        let attempts = AtomicUsize::new(0);
+        let result = retry(
+            &config(fast_policy(4), None),
+            |_| {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                async { Err::<(), ()>(()) }
+            },
+            retry_errors,
+            tokio::time::sleep,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 4);
+    }
+
+    #[tokio::test]
+    async fn stops_retrying_at_deadline() {
+        let start = Instant::now();
+        let result = retry(
+            &config(fast_policy(usize::MAX), Some(Duration::from_millis(200))),
+            |remaining| {
+                assert!(remaining.unwrap() <= Duration::from_millis(200));
+                async { Err::<(), ()>(()) }
+            },
+            retry_errors,
+            tokio::time::sleep,
+        )
+        .await;
+        assert!(result.is_err());
+        // no backoff is planned past the deadline, but sleeps may overshoot
+        // it slightly
+        assert!(start.elapsed() < Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn honors_requested_delay() {
+        let attempts = AtomicUsize::new(0);
+        let start = Instant::now();
+        let result = retry(
+            &config(fast_policy(2), None),
+            |_| {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                async { Err::<(), ()>(()) }
+            },
+            |_: &Result<(), ()>| RetryDecision::After(Duration::from_millis(100)),
+            tokio::time::sleep,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // a delay past the deadline is not waited for
+        let attempts = AtomicUsize::new(0);
+        let _ = retry(
+            &config(fast_policy(2), Some(Duration::from_millis(50))),
+            |_| {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                async { Err::<(), ()>(()) }
+            },
+            |_: &Result<(), ()>| RetryDecision::After(Duration::from_secs(60)),
+            tokio::time::sleep,
+        )
+        .await;
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn jitter_shortens_backoff() {
+        let backoff = Duration::from_secs(1);
+        assert_eq!(with_jitter(backoff, 0.0), backoff);
+        assert_eq!(with_jitter(backoff, f64::NAN), backoff);
+        for _ in 0..100 {
+            let jittered = with_jitter(backoff, 0.2);
+            assert!(jittered <= backoff);
+            assert!(jittered >= Duration::from_millis(800));
+        }
+    }
+
+    #[cfg(any(feature = "http-proto", feature = "http-json"))]
+    #[tokio::test]
+    async fn runtime_delay_elapses() {
+        let delay = super::RuntimeDelay::new(opentelemetry_sdk::runtime::Tokio);
+        let start = Instant::now();
+        super::RuntimeDelay::sleep(Some(&delay), Duration::from_millis(50)).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[cfg(any(feature = "http-proto", feature = "http-json"))]
+    #[tokio::test]
+    async fn runtime_agnostic_sleep_elapses() {
+        let start = Instant::now();
+        super::runtime_agnostic_sleep(Duration::from_millis(50)).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -9,10 +9,17 @@ use tonic::{codegen::CompressionEncoding, service::Interceptor, transport::Chann
 
 use opentelemetry_proto::transform::logs::tonic::group_logs_by_resource_and_scope;
 
-use super::BoxInterceptor;
+use super::{export_with_retry, BoxInterceptor, ReadinessProbe, SharedInterceptor};
+use crate::exporter::canonical::SortAttributes;
+use crate::exporter::retry::RetryConfig;
+use crate::exporter::ResponseObserver;
 
 pub(crate) struct TonicLogsClient {
     inner: Option<ClientInner>,
+    probe: Option<ReadinessProbe>,
+    retry: Option<RetryConfig>,
+    observer: Option<ResponseObserver>,
+    sort_attributes: bool,
     #[allow(dead_code)]
     // <allow dead> would be removed once we support set_resource for metrics.
     resource: opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema,
@@ -20,7 +27,7 @@ pub(crate) struct TonicLogsClient {
 
 struct ClientInner {
     client: LogsServiceClient<Channel>,
-    interceptor: BoxInterceptor,
+    interceptor: SharedInterceptor,
 }
 
 impl fmt::Debug for TonicLogsClient {
@@ -34,6 +41,10 @@ impl TonicLogsClient {
         channel: Channel,
         interceptor: BoxInterceptor,
         compression: Option<CompressionEncoding>,
+        probe: Option<ReadinessProbe>,
+        retry: Option<RetryConfig>,
+        observer: Option<ResponseObserver>,
+        sort_attributes: bool,
     ) -> Self {
         let mut client = LogsServiceClient::new(channel);
         if let Some(compression) = compression {
@@ -45,8 +56,12 @@ impl TonicLogsClient {
         TonicLogsClient {
             inner: Some(ClientInner {
                 client,
-                interceptor,
+                interceptor: SharedInterceptor::new(interceptor),
             }),
+            probe,
+            retry,
+            observer,
+            sort_attributes,
             resource: Default::default(),
         }
     }
@@ -55,14 +70,14 @@ impl TonicLogsClient {
 #[async_trait]
 impl LogExporter for TonicLogsClient {
     async fn export<'a>(&mut self, batch: Vec<std::borrow::Cow<'a, LogData>>) -> LogResult<()> {
-        let (mut client, metadata, extensions) = match &mut self.inner {
+        let (client, interceptor, metadata, extensions) = match &mut self.inner {
             Some(inner) => {
                 let (m, e, _) = inner
                     .interceptor
                     .call(Request::new(()))
                     .map_err(|e| LogError::Other(Box::new(e)))?
                     .into_parts();
-                (inner.client.clone(), m, e)
+                (inner.client.clone(), inner.interceptor.clone(), m, e)
             }
             None => return Err(LogError::Other("exporter is already shut down".into())),
         };
@@ -74,15 +89,29 @@ impl LogExporter for TonicLogsClient {
             .collect::<Vec<LogData>>();
 
         let resource_logs = group_logs_by_resource_and_scope(owned_batch, &self.resource);
+        let mut request = ExportLogsServiceRequest { resource_logs };
+        if self.sort_attributes {
+            request.sort_attributes();
+        }
+
+        if let Some(probe) = &self.probe {
+            probe.wait_before_first_export().await;
+        }
 
-        client
-            .export(Request::from_parts(
-                metadata,
-                extensions,
-                ExportLogsServiceRequest { resource_logs },
-            ))
-            .await
-            .map_err(crate::Error::from)?;
+        export_with_retry(
+            self.retry.as_ref(),
+            self.observer.as_ref(),
+            interceptor,
+            metadata,
+            extensions,
+            request,
+            |request| {
+                let mut client = client.clone();
+                async move { client.export(request).await }
+            },
+        )
+        .await
+        .map_err(crate::Error::from)?;
 
         Ok(())
     }
@@ -0,0 +1,604 @@
+//! Experimental export of logs over a long-lived gRPC stream.
+//!
+//! Instead of one unary `Export` call per batch, batches are written to a
+//! bidirectional streaming call kept open across exports, and each batch is
+//! acknowledged by the collector with one response. The streaming method is
+//! not part of OTLP, it is defined by `proto/logs_stream.proto` and requires a
+//! collector receiver implementing it.
+//!
+//! An export completes once the collector acknowledged its batch. If the
+//! stream fails with a transient status, the export opens a new stream and
+//! sends the batch again according to the retry policy of the exporter.
+use core::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use http::uri::PathAndQuery;
+use opentelemetry::global;
+use opentelemetry::logs::{LogError, LogResult};
+use opentelemetry_proto::tonic::collector::logs::v1::{
+    ExportLogsServiceRequest, ExportLogsServiceResponse,
+};
+use opentelemetry_proto::transform::logs::tonic::group_logs_by_resource_and_scope;
+use opentelemetry_sdk::export::logs::{LogData, LogExporter};
+use tokio::sync::mpsc;
+use tonic::client::Grpc;
+use tonic::codec::{CompressionEncoding, ProstCodec};
+use tonic::service::Interceptor;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Response, Status};
+
+use super::{observe_response, retry_decision, BoxInterceptor, ReadinessProbe};
+use crate::exporter::canonical::SortAttributes;
+use crate::exporter::retry::RetryConfig;
+use crate::exporter::ResponseObserver;
+
+/// The default method of the collector receiver accepting streamed logs, the
+/// `Export` method of the `LogsStreamService` of `proto/logs_stream.proto`.
+const DEFAULT_LOG_STREAM_PATH: &str = "/opentelemetry.rust.logs.v1.LogsStreamService/Export";
+
+/// Configuration of the experimental streaming export of logs, see
+/// [`TonicExporterBuilder::with_log_stream`].
+///
+/// [`TonicExporterBuilder::with_log_stream`]: crate::TonicExporterBuilder::with_log_stream
+#[derive(Debug, Clone)]
+pub struct LogStreamConfig {
+    path: PathAndQuery,
+}
+
+impl Default for LogStreamConfig {
+    fn default() -> Self {
+        LogStreamConfig {
+            path: PathAndQuery::from_static(DEFAULT_LOG_STREAM_PATH),
+        }
+    }
+}
+
+impl LogStreamConfig {
+    /// Set the path of the streaming gRPC method of the collector receiver.
+    ///
+    /// The method must implement the `Export` method of the
+    /// `LogsStreamService` defined in `proto/logs_stream.proto`, replying with
+    /// one `ExportLogsServiceResponse` per request, in order. Defaults to
+    /// `/opentelemetry.rust.logs.v1.LogsStreamService/Export`.
+    pub fn with_path(mut self, path: &str) -> Result<Self, crate::Error> {
+        self.path = path.parse().map_err(crate::Error::from)?;
+        Ok(self)
+    }
+}
+
+pub(crate) struct TonicLogsStreamClient {
+    inner: Option<ClientInner>,
+    stream: Option<OpenStream>,
+    config: LogStreamConfig,
+    timeout: Duration,
+    probe: Option<ReadinessProbe>,
+    retry: Option<RetryConfig>,
+    observer: Option<ResponseObserver>,
+    sort_attributes: bool,
+    resource: opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema,
+}
+
+struct ClientInner {
+    channel: Channel,
+    interceptor: BoxInterceptor,
+    compression: Option<CompressionEncoding>,
+}
+
+/// A stream open to the collector.
+struct OpenStream {
+    requests: mpsc::UnboundedSender<ExportLogsServiceRequest>,
+    // The responses of the collector, in the order of the requests and along
+    // with the metadata of the call, followed by the status the stream failed
+    // with
+    responses: mpsc::UnboundedReceiver<Result<Response<ExportLogsServiceResponse>, Status>>,
+}
+
+impl fmt::Debug for TonicLogsStreamClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TonicLogsStreamClient")
+    }
+}
+
+impl TonicLogsStreamClient {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn new(
+        channel: Channel,
+        interceptor: BoxInterceptor,
+        compression: Option<CompressionEncoding>,
+        probe: Option<ReadinessProbe>,
+        retry: Option<RetryConfig>,
+        observer: Option<ResponseObserver>,
+        config: LogStreamConfig,
+        timeout: Duration,
+        sort_attributes: bool,
+    ) -> Self {
+        TonicLogsStreamClient {
+            inner: Some(ClientInner {
+                channel,
+                interceptor,
+                compression,
+            }),
+            stream: None,
+            config,
+            timeout,
+            probe,
+            retry,
+            observer,
+            sort_attributes,
+            resource: Default::default(),
+        }
+    }
+
+    /// Returns the open stream, opening a new one if there is none.
+    fn stream(&mut self) -> LogResult<&mut OpenStream> {
+        let inner = match &mut self.inner {
+            Some(inner) => inner,
+            None => return Err(LogError::Other("exporter is already shut down".into())),
+        };
+
+        if self.stream.is_none() {
+            let (metadata, extensions, _) = inner
+                .interceptor
+                .call(Request::new(()))
+                .map_err(|e| LogError::Other(Box::new(e)))?
+                .into_parts();
+
+            let (requests, receiver) = mpsc::unbounded_channel();
+            let (sender, responses) = mpsc::unbounded_channel();
+            let mut grpc = Grpc::new(inner.channel.clone());
+            if let Some(compression) = inner.compression {
+                grpc = grpc
+                    .send_compressed(compression)
+                    .accept_compressed(compression);
+            }
+            let request = Request::from_parts(metadata, extensions, RequestStream(receiver));
+            tokio::spawn(drive_stream(
+                grpc,
+                request,
+                self.config.path.clone(),
+                sender,
+            ));
+
+            self.stream = Some(OpenStream {
+                requests,
+                responses,
+            });
+        }
+
+        // the stream was opened above if missing
+        self.stream
+            .as_mut()
+            .ok_or_else(|| LogError::Other("log stream is closed".into()))
+    }
+
+    /// Sends `request` on the open stream and waits for the collector to
+    /// acknowledge it, at most for `timeout`.
+    async fn send(
+        &mut self,
+        request: ExportLogsServiceRequest,
+        timeout: Duration,
+    ) -> Result<(), StreamError> {
+        let observer = self.observer.clone();
+        let stream = self.stream().map_err(StreamError::Closed)?;
+        // If the stream ended, its status is the next response
+        let _ = stream.requests.send(request);
+
+        let response = tokio::time::timeout(timeout, stream.responses.recv()).await;
+        if let (Some(observer), Ok(Some(result))) = (&observer, &response) {
+            observe_response(observer, result);
+        }
+        match response {
+            Ok(Some(Ok(response))) => {
+                if let Some(partial_success) = response.into_inner().partial_success {
+                    if partial_success.rejected_log_records > 0 {
+                        global::handle_error(LogError::Other(
+                            format!(
+                                "collector rejected {} log records: {}",
+                                partial_success.rejected_log_records, partial_success.error_message
+                            )
+                            .into(),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Ok(Some(Err(status))) => Err(StreamError::Failed(status)),
+            Ok(None) => Err(StreamError::Failed(Status::unavailable(
+                "log stream ended before acknowledging the batch",
+            ))),
+            Err(_) => Err(StreamError::Closed(LogError::ExportTimedOut(timeout))),
+        }
+    }
+}
+
+/// The reason a batch was not acknowledged.
+enum StreamError {
+    /// The stream failed with a status.
+    Failed(Status),
+    /// The batch could not be sent or was not acknowledged in time.
+    Closed(LogError),
+}
+
+#[async_trait]
+impl LogExporter for TonicLogsStreamClient {
+    async fn export<'a>(&mut self, batch: Vec<std::borrow::Cow<'a, LogData>>) -> LogResult<()> {
+        let owned_batch = batch
+            .into_iter()
+            .map(|cow_log_data| cow_log_data.into_owned())
+            .collect::<Vec<LogData>>();
+
+        let resource_logs = group_logs_by_resource_and_scope(owned_batch, &self.resource);
+
+        if let Some(probe) = &self.probe {
+            probe.wait_before_first_export().await;
+        }
+
+        let mut request = ExportLogsServiceRequest { resource_logs };
+        if self.sort_attributes {
+            request.sort_attributes();
+        }
+
+        let retry = self.retry.clone();
+        let mut backoff = retry.as_ref().map(RetryConfig::backoff);
+        loop {
+            let timeout = match backoff.as_ref().and_then(|backoff| backoff.remaining()) {
+                Some(remaining) => remaining.min(self.timeout),
+                None => self.timeout,
+            };
+            let result = self.send(request.clone(), timeout).await;
+            if result.is_err() {
+                // A late acknowledgement must not be taken for the one of the
+                // next batch, the next batch is sent on a new stream.
+                self.stream = None;
+            }
+            let status = match result {
+                Ok(()) => return Ok(()),
+                Err(StreamError::Failed(status)) => status,
+                Err(StreamError::Closed(err)) => return Err(err),
+            };
+            // the batch is sent again on a new stream
+            match backoff
+                .as_mut()
+                .and_then(|backoff| backoff.next_delay(retry_decision(&status)))
+            {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(LogError::from(crate::Error::from(status))),
+            }
+        }
+    }
+
+    fn shutdown(&mut self) {
+        // Closing the request stream ends the call.
+        let _ = self.stream.take();
+        let _ = self.inner.take();
+    }
+
+    fn set_resource(&mut self, resource: &opentelemetry_sdk::Resource) {
+        self.resource = resource.into();
+    }
+}
+
+/// Runs the streaming call, forwarding the responses of the collector and
+/// the status the call failed with.
+async fn drive_stream(
+    mut grpc: Grpc<Channel>,
+    request: Request<RequestStream>,
+    path: PathAndQuery,
+    responses: mpsc::UnboundedSender<Result<Response<ExportLogsServiceResponse>, Status>>,
+) {
+    let result = async {
+        grpc.ready()
+            .await
+            .map_err(|e| Status::new(Code::Unavailable, format!("service was not ready: {e}")))?;
+        let codec = ProstCodec::<ExportLogsServiceRequest, ExportLogsServiceResponse>::default();
+        let (metadata, mut stream, _) = grpc.streaming(request, path, codec).await?.into_parts();
+
+        while let Some(response) = stream.message().await? {
+            let response = Response::from_parts(metadata.clone(), response, Default::default());
+            if responses.send(Ok(response)).is_err() {
+                // the exporter closed the stream
+                break;
+            }
+        }
+        Ok::<_, Status>(())
+    }
+    .await;
+
+    if let Err(status) = result {
+        let _ = responses.send(Err(status));
+    }
+}
+
+/// The stream of requests sent to the collector.
+struct RequestStream(mpsc::UnboundedReceiver<ExportLogsServiceRequest>);
+
+impl futures_core::Stream for RequestStream {
+    type Item = ExportLogsServiceRequest;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+
+    use opentelemetry_sdk::export::logs::LogData;
+    use tokio_stream::wrappers::{TcpListenerStream, UnboundedReceiverStream};
+    use tonic::codegen::{empty_body, http, Body, BoxFuture, StdError};
+
+    use super::*;
+    use crate::{RetryPolicy, WithExportConfig};
+
+    /// How the test collector replies to the requests of a stream.
+    #[derive(Clone, Copy)]
+    enum Reply {
+        /// Acknowledge every request.
+        Ack,
+        /// Fail the first stream with a status once it received a request,
+        /// acknowledge the requests of later streams.
+        FailFirstStream(Code),
+        /// Never acknowledge requests.
+        Ignore,
+    }
+
+    /// An in-process collector implementing the `LogsStreamService` of
+    /// `proto/logs_stream.proto`.
+    #[derive(Clone)]
+    struct StreamCollector {
+        reply: Reply,
+        // the index of the stream each request was received on
+        received: Arc<Mutex<Vec<usize>>>,
+        streams: Arc<Mutex<usize>>,
+    }
+
+    impl StreamCollector {
+        fn new(reply: Reply) -> Self {
+            StreamCollector {
+                reply,
+                received: Default::default(),
+                streams: Default::default(),
+            }
+        }
+
+        fn received(&self) -> Vec<usize> {
+            self.received.lock().unwrap().clone()
+        }
+
+        /// Serves the collector, returning its endpoint.
+        async fn start(&self) -> String {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+            let service = self.clone();
+            tokio::spawn(async move {
+                tonic::transport::Server::builder()
+                    .add_service(service)
+                    .serve_with_incoming(TcpListenerStream::new(listener))
+                    .await
+            });
+            endpoint
+        }
+    }
+
+    type ResponseStream = UnboundedReceiverStream<Result<ExportLogsServiceResponse, Status>>;
+
+    impl tonic::server::StreamingService<ExportLogsServiceRequest> for StreamCollector {
+        type Response = ExportLogsServiceResponse;
+        type ResponseStream = ResponseStream;
+        type Future = BoxFuture<tonic::Response<ResponseStream>, Status>;
+
+        fn call(
+            &mut self,
+            request: Request<tonic::Streaming<ExportLogsServiceRequest>>,
+        ) -> Self::Future {
+            let collector = self.clone();
+            let stream = {
+                let mut streams = self.streams.lock().unwrap();
+                *streams += 1;
+                *streams - 1
+            };
+            let mut requests = request.into_inner();
+            let (responses, receiver) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                while let Ok(Some(_)) = requests.message().await {
+                    collector.received.lock().unwrap().push(stream);
+                    let response = match collector.reply {
+                        Reply::FailFirstStream(code) if stream == 0 => {
+                            Err(Status::new(code, "collector failed"))
+                        }
+                        Reply::Ack | Reply::FailFirstStream(_) => {
+                            Ok(ExportLogsServiceResponse::default())
+                        }
+                        Reply::Ignore => continue,
+                    };
+                    let failed = response.is_err();
+                    if responses.send(response).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+            Box::pin(
+                async move { Ok(tonic::Response::new(UnboundedReceiverStream::new(receiver))) },
+            )
+        }
+    }
+
+    impl<B> tonic::codegen::Service<http::Request<B>> for StreamCollector
+    where
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            if req.uri().path() != DEFAULT_LOG_STREAM_PATH {
+                return Box::pin(async move {
+                    // grpc-status 12 is UNIMPLEMENTED
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12")
+                        .header("content-type", "application/grpc")
+                        .body(empty_body())
+                        .expect("valid response"))
+                });
+            }
+
+            let method = self.clone();
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(ProstCodec::default());
+                Ok(grpc.streaming(method, req).await)
+            })
+        }
+    }
+
+    impl tonic::server::NamedService for StreamCollector {
+        const NAME: &'static str = "opentelemetry.rust.logs.v1.LogsStreamService";
+    }
+
+    fn builder(endpoint: String, timeout: Duration) -> crate::TonicExporterBuilder {
+        crate::TonicExporterBuilder::default()
+            .with_endpoint(endpoint)
+            .with_timeout(timeout)
+            .with_streaming_export()
+    }
+
+    fn exporter(endpoint: String, timeout: Duration) -> crate::logs::LogExporter {
+        builder(endpoint, timeout).build_log_exporter().unwrap()
+    }
+
+    fn retrying_exporter(endpoint: String, timeout: Duration) -> crate::logs::LogExporter {
+        builder(endpoint, timeout)
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: Duration::from_millis(10),
+                ..Default::default()
+            })
+            .build_log_exporter()
+            .unwrap()
+    }
+
+    fn batch() -> Vec<Cow<'static, LogData>> {
+        let mut record = opentelemetry_sdk::logs::LogRecord::default();
+        record.observed_timestamp = Some(std::time::SystemTime::now());
+        vec![Cow::Owned(LogData {
+            record,
+            instrumentation: Default::default(),
+            resource: None,
+        })]
+    }
+
+    #[test]
+    fn test_log_stream_config() {
+        let config = LogStreamConfig::default();
+        assert_eq!(config.path.as_str(), DEFAULT_LOG_STREAM_PATH);
+
+        let config = LogStreamConfig::default()
+            .with_path("/custom.LogsStream/Export")
+            .unwrap();
+        assert_eq!(config.path.as_str(), "/custom.LogsStream/Export");
+
+        assert!(LogStreamConfig::default().with_path("not a path").is_err());
+    }
+
+    #[tokio::test]
+    async fn batches_share_a_stream_and_complete_once_acknowledged() {
+        let collector = StreamCollector::new(Reply::Ack);
+        let mut exporter = exporter(collector.start().await, Duration::from_secs(5));
+
+        exporter.export(batch()).await.unwrap();
+        exporter.export(batch()).await.unwrap();
+
+        assert_eq!(collector.received(), vec![0, 0]);
+    }
+
+    #[tokio::test]
+    async fn exports_wait_for_the_acknowledgement() {
+        let collector = StreamCollector::new(Reply::Ignore);
+        let mut exporter = exporter(collector.start().await, Duration::from_millis(200));
+
+        let result = exporter.export(batch()).await;
+
+        assert!(matches!(result, Err(LogError::ExportTimedOut(_))));
+        assert_eq!(collector.received(), vec![0]);
+    }
+
+    #[tokio::test]
+    async fn batches_are_sent_again_on_a_new_stream_after_transient_failures() {
+        let collector = StreamCollector::new(Reply::FailFirstStream(Code::Unavailable));
+        let mut exporter = retrying_exporter(collector.start().await, Duration::from_secs(5));
+
+        exporter.export(batch()).await.unwrap();
+        exporter.export(batch()).await.unwrap();
+
+        // the first batch was not acknowledged on the first stream
+        assert_eq!(collector.received(), vec![0, 1, 1]);
+    }
+
+    #[tokio::test]
+    async fn transient_failures_fail_the_export_without_retry_policy() {
+        let collector = StreamCollector::new(Reply::FailFirstStream(Code::Unavailable));
+        let mut exporter = exporter(collector.start().await, Duration::from_secs(5));
+
+        assert!(exporter.export(batch()).await.is_err());
+        exporter.export(batch()).await.unwrap();
+
+        assert_eq!(collector.received(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn acknowledgements_are_observed() {
+        let collector = StreamCollector::new(Reply::FailFirstStream(Code::Unavailable));
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let mut exporter = {
+            let observed = observed.clone();
+            builder(collector.start().await, Duration::from_secs(5))
+                .with_retry_policy(RetryPolicy {
+                    initial_backoff: Duration::from_millis(10),
+                    ..Default::default()
+                })
+                .with_response_observer(move |response| {
+                    observed.lock().unwrap().push(response.accepted)
+                })
+                .build_log_exporter()
+                .unwrap()
+        };
+
+        exporter.export(batch()).await.unwrap();
+        exporter.export(batch()).await.unwrap();
+
+        assert_eq!(*observed.lock().unwrap(), vec![false, true, true]);
+    }
+
+    #[tokio::test]
+    async fn non_transient_failures_fail_the_export() {
+        let collector = StreamCollector::new(Reply::FailFirstStream(Code::InvalidArgument));
+        let mut exporter = retrying_exporter(collector.start().await, Duration::from_secs(5));
+
+        assert!(exporter.export(batch()).await.is_err());
+        // the batch is not sent again, the next batch opens a new stream
+        assert_eq!(collector.received(), vec![0]);
+        exporter.export(batch()).await.unwrap();
+        assert_eq!(collector.received(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_with_streaming_export() {
+        let builder = crate::TonicExporterBuilder::default();
+        assert!(builder.log_stream.is_none());
+
+        let builder = builder.with_streaming_export();
+        let config = builder.log_stream.unwrap();
+        assert_eq!(config.path.as_str(), DEFAULT_LOG_STREAM_PATH);
+    }
+}
@@ -9,16 +9,23 @@ use opentelemetry_proto::tonic::collector::metrics::v1::{
 use opentelemetry_sdk::metrics::data::ResourceMetrics;
 use tonic::{codegen::CompressionEncoding, service::Interceptor, transport::Channel, Request};
 
-use super::BoxInterceptor;
+use super::{export_with_retry, BoxInterceptor, ReadinessProbe, SharedInterceptor};
+use crate::exporter::canonical::SortAttributes;
+use crate::exporter::retry::RetryConfig;
+use crate::exporter::ResponseObserver;
 use crate::metric::MetricsClient;
 
 pub(crate) struct TonicMetricsClient {
     inner: Mutex<Option<ClientInner>>,
+    probe: Option<ReadinessProbe>,
+    retry: Option<RetryConfig>,
+    observer: Option<ResponseObserver>,
+    sort_attributes: bool,
 }
 
 struct ClientInner {
     client: MetricsServiceClient<Channel>,
-    interceptor: BoxInterceptor,
+    interceptor: SharedInterceptor,
 }
 
 impl fmt::Debug for TonicMetricsClient {
@@ -32,6 +39,10 @@ impl TonicMetricsClient {
         channel: Channel,
         interceptor: BoxInterceptor,
         compression: Option<CompressionEncoding>,
+        probe: Option<ReadinessProbe>,
+        retry: Option<RetryConfig>,
+        observer: Option<ResponseObserver>,
+        sort_attributes: bool,
     ) -> Self {
         let mut client = MetricsServiceClient::new(channel);
         if let Some(compression) = compression {
@@ -43,8 +54,12 @@ impl TonicMetricsClient {
         TonicMetricsClient {
             inner: Mutex::new(Some(ClientInner {
                 client,
-                interceptor,
+                interceptor: SharedInterceptor::new(interceptor),
             })),
+            probe,
+            retry,
+            observer,
+            sort_attributes,
         }
     }
 }
@@ -52,34 +67,47 @@ impl TonicMetricsClient {
 #[async_trait]
 impl MetricsClient for TonicMetricsClient {
     async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
-        let (mut client, metadata, extensions) =
-            self.inner
-                .lock()
-                .map_err(Into::into)
-                .and_then(|mut inner| match &mut *inner {
-                    Some(inner) => {
-                        let (m, e, _) = inner
-                            .interceptor
-                            .call(Request::new(()))
-                            .map_err(|e| {
-                                MetricsError::Other(format!(
-                                    "unexpected status while exporting {e:?}"
-                                ))
-                            })?
-                            .into_parts();
-                        Ok((inner.client.clone(), m, e))
-                    }
-                    None => Err(MetricsError::Other("exporter is already shut down".into())),
-                })?;
+        let (client, interceptor, metadata, extensions) = self
+            .inner
+            .lock()
+            .map_err(Into::into)
+            .and_then(|mut inner| match &mut *inner {
+                Some(inner) => {
+                    let (m, e, _) = inner
+                        .interceptor
+                        .call(Request::new(()))
+                        .map_err(|e| {
+                            MetricsError::Other(format!("unexpected status while exporting {e:?}"))
+                        })?
+                        .into_parts();
+                    Ok((inner.client.clone(), inner.interceptor.clone(), m, e))
+                }
+                None => Err(MetricsError::Other("exporter is already shut down".into())),
+            })?;
 
-        client
-            .export(Request::from_parts(
-                metadata,
-                extensions,
-                ExportMetricsServiceRequest::from(&*metrics),
-            ))
-            .await
-            .map_err(crate::Error::from)?;
+        let mut request = ExportMetricsServiceRequest::from(&*metrics);
+        if self.sort_attributes {
+            request.sort_attributes();
+        }
+
+        if let Some(probe) = &self.probe {
+            probe.wait_before_first_export().await;
+        }
+
+        export_with_retry(
+            self.retry.as_ref(),
+            self.observer.as_ref(),
+            interceptor,
+            metadata,
+            extensions,
+            request,
+            |request| {
+                let mut client = client.clone();
+                async move { client.export(request).await }
+            },
+        )
+        .await
+        .map_err(crate::Error::from)?;
 
         Ok(())
     }
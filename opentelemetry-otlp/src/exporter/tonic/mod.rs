@@ -1,6 +1,10 @@
 use std::env;
 use std::fmt::{Debug, Formatter};
+use std::future::Future;
+#[cfg(all(feature = "uds", unix))]
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use http::{HeaderMap, HeaderName, HeaderValue};
@@ -10,8 +14,13 @@ use tonic::service::Interceptor;
 use tonic::transport::Channel;
 #[cfg(feature = "tls")]
 use tonic::transport::ClientTlsConfig;
+use tonic::{Code, Extensions, Request, Response, Status};
 
-use super::{default_headers, parse_header_string, OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT};
+use super::retry::{retry, RetryConfig, RetryDecision};
+use super::{
+    default_headers, parse_header_string, ExportResponse, ResponseObserver,
+    OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT,
+};
 use crate::exporter::Compression;
 use crate::{
     ExportConfig, OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_ENDPOINT,
@@ -21,12 +30,25 @@ use crate::{
 #[cfg(feature = "logs")]
 mod logs;
 
+#[cfg(feature = "experimental-grpc-logs-stream")]
+mod logs_stream;
+
 #[cfg(feature = "metrics")]
 mod metrics;
 
 #[cfg(feature = "trace")]
 mod trace;
 
+mod readiness;
+mod retry_info;
+mod transport;
+
+pub(crate) use readiness::ReadinessProbe;
+
+#[cfg(feature = "experimental-grpc-logs-stream")]
+pub use logs_stream::LogStreamConfig;
+pub use transport::{OtlpTransport, OtlpTransportBuilder};
+
 /// Configuration for [tonic]
 ///
 /// [tonic]: https://github.com/hyperium/tonic
@@ -55,6 +77,12 @@ impl TryFrom<Compression> for tonic::codec::CompressionEncoding {
             Compression::Gzip => Err(crate::Error::UnsupportedCompressionAlgorithm(
                 value.to_string(),
             )),
+            #[cfg(feature = "zstd-tonic")]
+            Compression::Zstd => Ok(tonic::codec::CompressionEncoding::Zstd),
+            #[cfg(not(feature = "zstd-tonic"))]
+            Compression::Zstd => Err(crate::Error::UnsupportedCompressionAlgorithm(
+                value.to_string(),
+            )),
         }
     }
 }
@@ -80,6 +108,8 @@ fn resolve_compression(
 /// - add additional metadata
 /// - set tls config (via the  `tls` feature)
 /// - specify custom [channel]s
+/// - share a connection between exporters with an [`OtlpTransport`]
+/// - export over a Unix domain socket (via the `uds` feature)
 ///
 /// [tonic]: <https://github.com/hyperium/tonic>
 /// [channel]: tonic::transport::Channel
@@ -117,7 +147,14 @@ pub struct TonicExporterBuilder {
     pub(crate) exporter_config: ExportConfig,
     pub(crate) tonic_config: TonicConfig,
     pub(crate) channel: Option<tonic::transport::Channel>,
+    pub(crate) transport: Option<OtlpTransport>,
     pub(crate) interceptor: Option<BoxInterceptor>,
+    pub(crate) readiness_timeout: Option<Duration>,
+    pub(crate) response_observer: Option<ResponseObserver>,
+    #[cfg(feature = "experimental-grpc-logs-stream")]
+    pub(crate) log_stream: Option<LogStreamConfig>,
+    #[cfg(all(feature = "uds", unix))]
+    pub(crate) uds_path: Option<PathBuf>,
 }
 
 pub(crate) struct BoxInterceptor(Box<dyn Interceptor + Send + Sync>);
@@ -133,6 +170,26 @@ impl Debug for BoxInterceptor {
     }
 }
 
+/// An interceptor shared with the exports in flight, so that each retried
+/// attempt runs it again: the extensions it sets can not be cloned.
+#[derive(Clone, Debug)]
+pub(crate) struct SharedInterceptor(Arc<Mutex<BoxInterceptor>>);
+
+impl SharedInterceptor {
+    pub(crate) fn new(interceptor: BoxInterceptor) -> Self {
+        SharedInterceptor(Arc::new(Mutex::new(interceptor)))
+    }
+}
+
+impl Interceptor for SharedInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        match self.0.lock() {
+            Ok(mut interceptor) => interceptor.call(request),
+            Err(_) => Err(Status::internal("the interceptor lock has been poisoned")),
+        }
+    }
+}
+
 impl Default for TonicExporterBuilder {
     fn default() -> Self {
         let tonic_config = TonicConfig {
@@ -153,7 +210,14 @@ impl Default for TonicExporterBuilder {
             },
             tonic_config,
             channel: Option::default(),
+            transport: Option::default(),
             interceptor: Option::default(),
+            readiness_timeout: Option::default(),
+            response_observer: Option::default(),
+            #[cfg(feature = "experimental-grpc-logs-stream")]
+            log_stream: Option::default(),
+            #[cfg(all(feature = "uds", unix))]
+            uds_path: Option::default(),
         }
     }
 }
@@ -198,6 +262,89 @@ impl TonicExporterBuilder {
         self
     }
 
+    /// Export over a `transport` shared with other exporters.
+    ///
+    /// The transport's channel, metadata and interceptor are used, this
+    /// overrides the endpoint, timeout and TLS config of this builder as well
+    /// as any custom channel. The metadata and interceptor of this builder are
+    /// applied after the transport's.
+    pub fn with_transport(mut self, transport: OtlpTransport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Connect to the collector over the Unix domain socket at `path`, e.g.
+    /// of a collector running as a sidecar, instead of over TCP.
+    ///
+    /// This overrides the endpoint and TLS config of this builder, the
+    /// timeouts and [readiness probe](Self::with_readiness_probe) still apply.
+    /// The socket is connected lazily like TCP endpoints.
+    #[cfg(all(feature = "uds", unix))]
+    pub fn with_uds_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.uds_path = Some(path.into());
+        self
+    }
+
+    /// Wait up to `timeout` for the collector to accept connections before the
+    /// first export.
+    ///
+    /// The exporter connects lazily, so batches exported while the collector
+    /// is still starting fail. With this option the first export retries
+    /// connecting with exponential backoff first, later exports never wait.
+    /// If the collector is not ready in time, the export proceeds and fails as
+    /// usual.
+    ///
+    /// The timeout also applies to the exporter's `ready` method. Readiness
+    /// can not be probed with a custom [channel](Self::with_channel).
+    pub fn with_readiness_probe(mut self, timeout: Duration) -> Self {
+        self.readiness_timeout = Some(timeout);
+        self
+    }
+
+    /// Call `observer` with the metadata of every response of the collector,
+    /// including those of failed and retried attempts.
+    ///
+    /// Failures without a response from the collector, e.g. connection
+    /// errors, are observed with empty metadata. Logs exported over a
+    /// [stream](Self::with_log_stream) are observed once per acknowledged or
+    /// failed batch, with the metadata of the stream. The observer is called
+    /// on the task exporting the batch and should return quickly.
+    pub fn with_response_observer<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&ExportResponse<'_>) + Send + Sync + 'static,
+    {
+        self.response_observer = Some(ResponseObserver::new(observer));
+        self
+    }
+
+    /// Export logs over a long-lived gRPC stream instead of one unary call
+    /// per batch.
+    ///
+    /// This is experimental and requires a collector receiver implementing
+    /// the streaming method configured in `config`, which is not part of
+    /// OTLP but defined by `proto/logs_stream.proto` in this crate. An export
+    /// completes once the collector acknowledged its batch, which holds back
+    /// the batch processor while the collector is behind, and fails with the
+    /// status the stream failed with. Only affects log exporters.
+    #[cfg(feature = "experimental-grpc-logs-stream")]
+    pub fn with_log_stream(mut self, config: LogStreamConfig) -> Self {
+        self.log_stream = Some(config);
+        self
+    }
+
+    /// Export logs over a long-lived gRPC stream with the default
+    /// [`LogStreamConfig`], see [`with_log_stream`](Self::with_log_stream).
+    ///
+    /// If the stream fails with a transient status before the collector
+    /// acknowledged a batch, the export opens a new stream and sends the batch
+    /// again as configured with
+    /// [`with_retry_policy`](crate::WithExportConfig::with_retry_policy) and
+    /// [`with_export_deadline`](crate::WithExportConfig::with_export_deadline).
+    #[cfg(feature = "experimental-grpc-logs-stream")]
+    pub fn with_streaming_export(self) -> Self {
+        self.with_log_stream(LogStreamConfig::default())
+    }
+
     /// Use a custom `interceptor` to modify each outbound request.
     /// this can be used to modify the grpc metadata, for example
     /// to inject auth tokens.
@@ -209,21 +356,37 @@ impl TonicExporterBuilder {
         self
     }
 
+    // tonic interceptors return `tonic::Status` errors, which can't be boxed
+    #[allow(clippy::result_large_err)]
     fn build_channel(
         self,
         signal_endpoint_var: &str,
         signal_timeout_var: &str,
         signal_compression_var: &str,
         signal_headers_var: &str,
-    ) -> Result<(Channel, BoxInterceptor, Option<CompressionEncoding>), crate::Error> {
+    ) -> Result<
+        (
+            Channel,
+            BoxInterceptor,
+            Option<CompressionEncoding>,
+            Option<ReadinessProbe>,
+        ),
+        crate::Error,
+    > {
         let tonic_config = self.tonic_config;
         let compression = resolve_compression(&tonic_config, signal_compression_var)?;
 
         let headers_from_env = parse_headers_from_env(signal_headers_var);
-        let metadata = merge_metadata_with_headers_from_env(
-            tonic_config.metadata.unwrap_or_default(),
-            headers_from_env,
-        );
+        let metadata = tonic_config.metadata.unwrap_or_default();
+        let metadata = match &self.transport {
+            Some(transport) => {
+                let mut headers = transport.metadata.clone().into_headers();
+                headers.extend(metadata.into_headers());
+                MetadataMap::from_headers(headers)
+            }
+            None => metadata,
+        };
+        let metadata = merge_metadata_with_headers_from_env(metadata, headers_from_env);
 
         let add_metadata = move |mut req: tonic::Request<()>| {
             for key_and_value in metadata.iter() {
@@ -247,9 +410,26 @@ impl TonicExporterBuilder {
             None => BoxInterceptor(Box::new(add_metadata)),
         };
 
+        // The interceptor of a shared transport runs before the exporter's own
+        if let Some(transport) = self.transport {
+            let interceptor = match transport.interceptor {
+                Some(mut shared) => {
+                    let mut interceptor = interceptor;
+                    BoxInterceptor(Box::new(move |req| interceptor.call(shared.call(req)?)))
+                }
+                None => interceptor,
+            };
+            let probe = ReadinessProbe::new(
+                transport.endpoint,
+                self.readiness_timeout.unwrap_or(transport.timeout),
+                self.readiness_timeout.is_some(),
+            );
+            return Ok((transport.channel, interceptor, compression, Some(probe)));
+        }
+
         // If a custom channel was provided, use that channel instead of creating one
         if let Some(channel) = self.channel {
-            return Ok((channel, interceptor, compression));
+            return Ok((channel, interceptor, compression, None));
         }
 
         let config = self.exporter_config;
@@ -273,7 +453,6 @@ impl TonicExporterBuilder {
             }
         };
 
-        let endpoint = Channel::from_shared(endpoint).map_err(crate::Error::from)?;
         let timeout = match env::var(signal_timeout_var)
             .ok()
             .or(env::var(OTEL_EXPORTER_OTLP_TIMEOUT).ok())
@@ -285,20 +464,52 @@ impl TonicExporterBuilder {
             None => config.timeout,
         };
 
+        #[cfg(all(feature = "uds", unix))]
+        if let Some(path) = self.uds_path {
+            // requests need an authority, the connector ignores it
+            let endpoint = Channel::from_static("http://localhost");
+            let endpoint = match config.connect_timeout {
+                Some(connect_timeout) => endpoint.connect_timeout(connect_timeout),
+                None => endpoint,
+            };
+            let endpoint = endpoint.timeout(timeout);
+            let probe = ReadinessProbe::new(
+                endpoint.clone(),
+                self.readiness_timeout.unwrap_or(timeout),
+                self.readiness_timeout.is_some(),
+            )
+            .with_uds_path(path.clone());
+            let channel =
+                endpoint.connect_with_connector_lazy(tower::service_fn(move |_: http::Uri| {
+                    tokio::net::UnixStream::connect(path.clone())
+                }));
+            return Ok((channel, interceptor, compression, Some(probe)));
+        }
+
+        let endpoint = Channel::from_shared(endpoint).map_err(crate::Error::from)?;
+
         #[cfg(feature = "tls")]
-        let channel = match tonic_config.tls_config {
+        let endpoint = match tonic_config.tls_config {
             Some(tls_config) => endpoint
                 .tls_config(tls_config)
                 .map_err(crate::Error::from)?,
             None => endpoint,
-        }
-        .timeout(timeout)
-        .connect_lazy();
+        };
 
-        #[cfg(not(feature = "tls"))]
-        let channel = endpoint.timeout(timeout).connect_lazy();
+        let endpoint = match config.connect_timeout {
+            Some(connect_timeout) => endpoint.connect_timeout(connect_timeout),
+            None => endpoint,
+        };
 
-        Ok((channel, interceptor, compression))
+        let endpoint = endpoint.timeout(timeout);
+        let probe = ReadinessProbe::new(
+            endpoint.clone(),
+            self.readiness_timeout.unwrap_or(timeout),
+            self.readiness_timeout.is_some(),
+        );
+        let channel = endpoint.connect_lazy();
+
+        Ok((channel, interceptor, compression, Some(probe)))
     }
 
     /// Build a new tonic log exporter
@@ -308,16 +519,45 @@ impl TonicExporterBuilder {
     ) -> Result<crate::logs::LogExporter, opentelemetry::logs::LogError> {
         use crate::exporter::tonic::logs::TonicLogsClient;
 
-        let (channel, interceptor, compression) = self.build_channel(
+        let retry_config = RetryConfig::from_export_config(&self.exporter_config);
+        let response_observer = self.response_observer.clone();
+        let sort_attributes = self.exporter_config.sort_attributes;
+        #[cfg(feature = "experimental-grpc-logs-stream")]
+        let (log_stream, timeout) = (self.log_stream.clone(), self.exporter_config.timeout);
+        let (channel, interceptor, compression, probe) = self.build_channel(
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_ENDPOINT,
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_COMPRESSION,
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_HEADERS,
         )?;
 
-        let client = TonicLogsClient::new(channel, interceptor, compression);
+        #[cfg(feature = "experimental-grpc-logs-stream")]
+        if let Some(config) = log_stream {
+            let client = logs_stream::TonicLogsStreamClient::new(
+                channel,
+                interceptor,
+                compression,
+                probe.clone(),
+                retry_config,
+                response_observer,
+                config,
+                timeout,
+                sort_attributes,
+            );
+            return Ok(crate::logs::LogExporter::new(client).with_probe(probe));
+        }
 
-        Ok(crate::logs::LogExporter::new(client))
+        let client = TonicLogsClient::new(
+            channel,
+            interceptor,
+            compression,
+            probe.clone(),
+            retry_config,
+            response_observer,
+            sort_attributes,
+        );
+
+        Ok(crate::logs::LogExporter::new(client).with_probe(probe))
     }
 
     /// Build a new tonic metrics exporter
@@ -330,20 +570,30 @@ impl TonicExporterBuilder {
         use crate::MetricsExporter;
         use metrics::TonicMetricsClient;
 
-        let (channel, interceptor, compression) = self.build_channel(
+        let retry_config = RetryConfig::from_export_config(&self.exporter_config);
+        let response_observer = self.response_observer.clone();
+        let sort_attributes = self.exporter_config.sort_attributes;
+        let (channel, interceptor, compression, probe) = self.build_channel(
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_HEADERS,
         )?;
 
-        let client = TonicMetricsClient::new(channel, interceptor, compression);
+        let client = TonicMetricsClient::new(
+            channel,
+            interceptor,
+            compression,
+            probe.clone(),
+            retry_config,
+            response_observer,
+            sort_attributes,
+        );
 
-        Ok(MetricsExporter::new(
-            client,
-            temporality_selector,
-            aggregation_selector,
-        ))
+        Ok(
+            MetricsExporter::new(client, temporality_selector, aggregation_selector)
+                .with_probe(probe),
+        )
     }
 
     /// Build a new tonic span exporter
@@ -353,16 +603,27 @@ impl TonicExporterBuilder {
     ) -> Result<crate::SpanExporter, opentelemetry::trace::TraceError> {
         use crate::exporter::tonic::trace::TonicTracesClient;
 
-        let (channel, interceptor, compression) = self.build_channel(
+        let retry_config = RetryConfig::from_export_config(&self.exporter_config);
+        let response_observer = self.response_observer.clone();
+        let sort_attributes = self.exporter_config.sort_attributes;
+        let (channel, interceptor, compression, probe) = self.build_channel(
             crate::span::OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
             crate::span::OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
             crate::span::OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
             crate::span::OTEL_EXPORTER_OTLP_TRACES_HEADERS,
         )?;
 
-        let client = TonicTracesClient::new(channel, interceptor, compression);
+        let client = TonicTracesClient::new(
+            channel,
+            interceptor,
+            compression,
+            probe.clone(),
+            retry_config,
+            response_observer,
+            sort_attributes,
+        );
 
-        Ok(crate::SpanExporter::new(client))
+        Ok(crate::SpanExporter::new(client).with_probe(probe))
     }
 }
 
@@ -380,6 +641,124 @@ fn merge_metadata_with_headers_from_env(
     }
 }
 
+/// Send `message` with `send`, retrying failures the collector reports as
+/// transient according to `retry_config`.
+///
+/// The first attempt carries `metadata` and `extensions`, retried attempts
+/// run `interceptor` again. The response of every attempt is passed to
+/// `observer`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn export_with_retry<M, R, F, Fut>(
+    retry_config: Option<&RetryConfig>,
+    observer: Option<&ResponseObserver>,
+    mut interceptor: SharedInterceptor,
+    metadata: MetadataMap,
+    extensions: Extensions,
+    message: M,
+    send: F,
+) -> Result<Response<R>, Status>
+where
+    M: Clone,
+    F: Fn(Request<M>) -> Fut,
+    Fut: Future<Output = Result<Response<R>, Status>>,
+{
+    let send = |request: Request<M>| {
+        let response = send(request);
+        async move {
+            let result = response.await;
+            if let Some(observer) = observer {
+                observe_response(observer, &result);
+            }
+            result
+        }
+    };
+
+    let Some(retry_config) = retry_config else {
+        return send(Request::from_parts(metadata, extensions, message)).await;
+    };
+
+    let mut first_attempt = Some((metadata, extensions));
+    retry(
+        retry_config,
+        |remaining| {
+            let parts = match first_attempt.take() {
+                Some(parts) => Ok(parts),
+                None => interceptor.call(Request::new(())).map(|request| {
+                    let (metadata, extensions, _) = request.into_parts();
+                    (metadata, extensions)
+                }),
+            };
+            let response = parts.map(|(metadata, extensions)| {
+                let mut request = Request::from_parts(metadata, extensions, message.clone());
+                if let Some(remaining) = remaining {
+                    request.set_timeout(remaining);
+                }
+                send(request)
+            });
+            async move {
+                let response = response?;
+                match remaining {
+                    Some(remaining) => match tokio::time::timeout(remaining, response).await {
+                        Ok(result) => result,
+                        Err(_) => Err(Status::deadline_exceeded("export deadline exceeded")),
+                    },
+                    None => response.await,
+                }
+            }
+        },
+        |result| match result {
+            Ok(_) => RetryDecision::Stop,
+            Err(status) => retry_decision(status),
+        },
+        tokio::time::sleep,
+    )
+    .await
+}
+
+fn observe_response<R>(observer: &ResponseObserver, result: &Result<Response<R>, Status>) {
+    let (metadata, retry_after) = match result {
+        Ok(response) => (response.metadata(), None),
+        Err(status) => (status.metadata(), retry_info::retry_delay(status)),
+    };
+    let headers = metadata.clone().into_headers();
+    observer.observe(&ExportResponse {
+        headers: &headers,
+        accepted: result.is_ok(),
+        retry_after,
+    });
+}
+
+/// Whether an export failing with `status` may succeed if retried, see
+/// <https://github.com/open-telemetry/opentelemetry-proto/blob/main/docs/specification.md#failures>
+///
+/// A `RetryInfo` in the status details sets the delay before retrying, and
+/// is required to retry `RESOURCE_EXHAUSTED` failures.
+fn retry_decision(status: &Status) -> RetryDecision {
+    let retry_delay = retry_info::retry_delay(status);
+    match (status.code(), retry_delay) {
+        (
+            Code::Cancelled
+            | Code::DeadlineExceeded
+            | Code::Aborted
+            | Code::OutOfRange
+            | Code::Unavailable
+            | Code::DataLoss
+            | Code::ResourceExhausted,
+            Some(delay),
+        ) => RetryDecision::After(delay),
+        (
+            Code::Cancelled
+            | Code::DeadlineExceeded
+            | Code::Aborted
+            | Code::OutOfRange
+            | Code::Unavailable
+            | Code::DataLoss,
+            None,
+        ) => RetryDecision::Backoff,
+        _ => RetryDecision::Stop,
+    }
+}
+
 fn parse_headers_from_env(signal_headers_var: &str) -> HeaderMap {
     env::var(signal_headers_var)
         .or_else(|_| env::var(OTEL_EXPORTER_OTLP_HEADERS))
@@ -446,6 +825,56 @@ mod tests {
         assert_eq!(builder.tonic_config.compression.unwrap(), Compression::Gzip);
     }
 
+    #[tokio::test]
+    #[cfg(feature = "trace")]
+    #[allow(clippy::result_large_err)]
+    async fn test_with_transport() {
+        use crate::OtlpTransport;
+        use tonic::service::Interceptor;
+
+        let mut transport_metadata = MetadataMap::new();
+        transport_metadata.insert("authorization", "token".parse().unwrap());
+        transport_metadata.insert("foo", "transport".parse().unwrap());
+        let transport = OtlpTransport::tonic("http://localhost:4317")
+            .with_metadata(transport_metadata)
+            .with_interceptor(|mut req: tonic::Request<()>| {
+                req.metadata_mut()
+                    .insert("intercepted", "transport".parse().unwrap());
+                Ok(req)
+            })
+            .build()
+            .unwrap();
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert("foo", "exporter".parse().unwrap());
+        let (_, mut interceptor, _, _) = TonicExporterBuilder::default()
+            .with_transport(transport)
+            .with_metadata(metadata)
+            .build_channel(
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            )
+            .unwrap();
+
+        let request = interceptor.call(tonic::Request::new(())).unwrap();
+        let result = request.metadata();
+        // transport metadata is shared, the exporter's takes precedence
+        assert_eq!(
+            result.get("authorization").unwrap(),
+            MetadataValue::from_static("token")
+        );
+        assert_eq!(
+            result.get("foo").unwrap(),
+            MetadataValue::from_static("exporter")
+        );
+        assert_eq!(
+            result.get("intercepted").unwrap(),
+            MetadataValue::from_static("transport")
+        );
+    }
+
     #[test]
     fn test_parse_headers_from_env() {
         run_env_test(
@@ -503,4 +932,195 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn test_retry_decision() {
+        use super::retry_decision;
+        use crate::exporter::retry::RetryDecision;
+        use tonic::{Code, Status};
+
+        assert_eq!(
+            retry_decision(&Status::unavailable("unavailable")),
+            RetryDecision::Backoff
+        );
+        assert_eq!(
+            retry_decision(&Status::deadline_exceeded("timed out")),
+            RetryDecision::Backoff
+        );
+        assert_eq!(
+            retry_decision(&Status::invalid_argument("bad request")),
+            RetryDecision::Stop
+        );
+        // only retried if the collector says when
+        assert_eq!(
+            retry_decision(&Status::new(Code::ResourceExhausted, "too many requests")),
+            RetryDecision::Stop
+        );
+    }
+
+    #[test]
+    fn test_observe_response() {
+        use super::{observe_response, ResponseObserver};
+        use std::sync::{Arc, Mutex};
+        use tonic::metadata::MetadataMap;
+        use tonic::{Code, Response, Status};
+
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let observer = {
+            let observed = Arc::clone(&observed);
+            ResponseObserver::new(move |response| {
+                observed.lock().unwrap().push((
+                    response.accepted,
+                    response.headers.get("x-server-version").cloned(),
+                ))
+            })
+        };
+
+        let mut accepted = Response::new(());
+        accepted
+            .metadata_mut()
+            .insert("x-server-version", "1.2.3".parse().unwrap());
+        observe_response(&observer, &Ok(accepted));
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-server-version", "1.2.3".parse().unwrap());
+        let rejected = Status::with_metadata(Code::ResourceExhausted, "slow down", metadata);
+        observe_response::<()>(&observer, &Err(rejected));
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            vec![
+                (true, Some("1.2.3".parse().unwrap())),
+                (false, Some("1.2.3".parse().unwrap())),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)]
+    async fn test_retried_exports_run_the_interceptor_again() {
+        use super::{export_with_retry, BoxInterceptor, SharedInterceptor};
+        use crate::exporter::retry::RetryConfig;
+        use crate::{ExportConfig, RetryPolicy};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+        use tonic::service::Interceptor;
+        use tonic::{Request, Response, Status};
+
+        #[derive(Clone)]
+        struct Attempt(usize);
+
+        let intercepted = Arc::new(AtomicUsize::new(0));
+        let counter = intercepted.clone();
+        let mut interceptor =
+            SharedInterceptor::new(BoxInterceptor(Box::new(move |mut req: Request<()>| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                req.extensions_mut().insert(Attempt(attempt));
+                Ok(req)
+            })));
+        let retry_config = RetryConfig::from_export_config(&ExportConfig {
+            retry_policy: Some(RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            }),
+            ..ExportConfig::default()
+        })
+        .unwrap();
+
+        let (metadata, extensions, _) = interceptor.call(Request::new(())).unwrap().into_parts();
+        let attempts = Mutex::new(Vec::new());
+        let result = export_with_retry(
+            Some(&retry_config),
+            None,
+            interceptor,
+            metadata,
+            extensions,
+            (),
+            |request: Request<()>| {
+                let attempt = request.extensions().get::<Attempt>().map(|a| a.0);
+                let mut attempts = attempts.lock().unwrap();
+                attempts.push(attempt);
+                let result = if attempts.len() < 3 {
+                    Err(Status::unavailable("collector is starting"))
+                } else {
+                    Ok(Response::new(()))
+                };
+                async move { result }
+            },
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(intercepted.load(Ordering::SeqCst), 3);
+        assert_eq!(*attempts.lock().unwrap(), vec![Some(1), Some(2), Some(3)]);
+    }
+
+    #[tokio::test]
+    #[cfg(all(feature = "uds", feature = "trace", unix))]
+    async fn test_with_uds_path() {
+        use crate::WithExportConfig;
+        use opentelemetry_proto::tonic::collector::trace::v1::{
+            trace_service_client::TraceServiceClient, ExportTraceServiceRequest,
+        };
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("otlp-uds-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        let (channel, _, _, probe) = TonicExporterBuilder::default()
+            .with_endpoint("http://unreachable:4317")
+            .with_uds_path(&path)
+            .build_channel(
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            )
+            .unwrap();
+        assert!(probe.is_some());
+
+        // the channel connects lazily, on the first export
+        let export = tokio::spawn(async move {
+            TraceServiceClient::new(channel)
+                .export(ExportTraceServiceRequest::default())
+                .await
+        });
+        let accepted = tokio::time::timeout(Duration::from_secs(5), listener.accept()).await;
+        assert!(accepted.expect("connected over the socket").is_ok());
+
+        export.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    #[cfg(all(feature = "uds", feature = "trace", unix))]
+    async fn test_uds_readiness_probe() {
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("otlp-uds-ready-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let (_, _, _, probe) = TonicExporterBuilder::default()
+            .with_uds_path(&path)
+            .with_readiness_probe(Duration::from_millis(300))
+            .build_channel(
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            )
+            .unwrap();
+        let probe = probe.expect("the socket is probed");
+
+        // nothing listens on the socket yet
+        assert!(probe.ready().await.is_err());
+
+        let _listener = tokio::net::UnixListener::bind(&path).unwrap();
+        assert!(probe.ready().await.is_ok());
+
+        let _ = std::fs::remove_file(&path);
+    }
 }
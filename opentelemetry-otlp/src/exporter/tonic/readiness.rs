@@ -0,0 +1,163 @@
+#[cfg(all(feature = "uds", unix))]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::OnceCell;
+use tonic::transport::Endpoint;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Waits until the collector accepts connections.
+///
+/// The probe dials a separate connection to the exporter's endpoint, or Unix
+/// domain socket, the exporter's own channel keeps connecting lazily on
+/// export.
+#[derive(Clone, Debug)]
+pub(crate) struct ReadinessProbe {
+    endpoint: Endpoint,
+    #[cfg(all(feature = "uds", unix))]
+    uds_path: Option<PathBuf>,
+    timeout: Duration,
+    // whether exports should wait for the probe, and whether they already did.
+    // Exports starting while the probe runs all wait for it to complete.
+    wait_before_export: bool,
+    waited: Arc<OnceCell<()>>,
+}
+
+impl ReadinessProbe {
+    pub(crate) fn new(endpoint: Endpoint, timeout: Duration, wait_before_export: bool) -> Self {
+        ReadinessProbe {
+            endpoint,
+            #[cfg(all(feature = "uds", unix))]
+            uds_path: None,
+            timeout,
+            wait_before_export,
+            waited: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Probe the Unix domain socket at `path` instead of the endpoint, which
+    /// then only provides the connection settings.
+    #[cfg(all(feature = "uds", unix))]
+    pub(crate) fn with_uds_path(mut self, path: PathBuf) -> Self {
+        self.uds_path = Some(path);
+        self
+    }
+
+    /// Retry connecting with exponential backoff until it succeeds or the
+    /// probe timeout elapses, returning the last connection error.
+    ///
+    /// Connection attempts are bounded by the probe timeout as well, as they
+    /// may otherwise hang, e.g. if the collector's host drops packets.
+    pub(crate) async fn ready(&self) -> Result<(), crate::Error> {
+        let deadline = Instant::now() + self.timeout;
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let err =
+                match tokio::time::timeout(remaining, self.connect()).await {
+                    Ok(Ok(_)) => return Ok(()),
+                    Ok(Err(err)) => err.into(),
+                    Err(_) => return Err(tonic::Status::deadline_exceeded(
+                        "the collector did not accept a connection before the readiness timeout",
+                    )
+                    .into()),
+                };
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(err);
+            }
+            tokio::time::sleep(backoff.min(deadline - now)).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    async fn connect(&self) -> Result<(), tonic::transport::Error> {
+        #[cfg(all(feature = "uds", unix))]
+        if let Some(path) = self.uds_path.clone() {
+            return self
+                .endpoint
+                .connect_with_connector(tower::service_fn(move |_: http::Uri| {
+                    tokio::net::UnixStream::connect(path.clone())
+                }))
+                .await
+                .map(|_| ());
+        }
+        self.endpoint.connect().await.map(|_| ())
+    }
+
+    /// Wait for readiness before the first export if configured to.
+    ///
+    /// Failures are ignored, the export then fails as it would have without
+    /// the probe. Exports concurrent with the first one wait for the same
+    /// probe, later exports never wait.
+    pub(crate) async fn wait_before_first_export(&self) {
+        if self.wait_before_export {
+            self.waited
+                .get_or_init(|| async {
+                    let _ = self.ready().await;
+                })
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tonic::transport::Endpoint;
+
+    use super::ReadinessProbe;
+
+    // nothing listens on port 1, connecting is refused immediately
+    fn unreachable_probe(wait_before_export: bool) -> ReadinessProbe {
+        ReadinessProbe::new(
+            Endpoint::from_static("http://127.0.0.1:1"),
+            Duration::from_millis(300),
+            wait_before_export,
+        )
+    }
+
+    #[tokio::test]
+    async fn ready_retries_until_timeout() {
+        let start = Instant::now();
+        assert!(unreachable_probe(false).ready().await.is_err());
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn waits_before_first_export_only() {
+        let probe = unreachable_probe(true);
+
+        let start = Instant::now();
+        probe.wait_before_first_export().await;
+        assert!(start.elapsed() >= Duration::from_millis(300));
+
+        let start = Instant::now();
+        probe.wait_before_first_export().await;
+        assert!(start.elapsed() < Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn does_not_wait_unless_configured() {
+        let start = Instant::now();
+        unreachable_probe(false).wait_before_first_export().await;
+        assert!(start.elapsed() < Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn concurrent_first_exports_wait_for_the_probe() {
+        let probe = unreachable_probe(true);
+
+        let start = Instant::now();
+        let (_, second) = tokio::join!(probe.wait_before_first_export(), async {
+            probe.wait_before_first_export().await;
+            start.elapsed()
+        });
+        assert!(second >= Duration::from_millis(300));
+    }
+}
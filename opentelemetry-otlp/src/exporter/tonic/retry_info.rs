@@ -0,0 +1,119 @@
+//! Decoding the `google.rpc.RetryInfo` a collector may attach to a failure.
+//!
+//! Only the fields needed to find the retry delay are declared, prost skips
+//! the others.
+use std::time::Duration;
+
+use prost::Message;
+use tonic::Status;
+
+const RETRY_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+/// `google.rpc.Status`, the encoding of [`Status::details`].
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<Any>,
+}
+
+/// `google.protobuf.Any`
+#[derive(Clone, PartialEq, Message)]
+struct Any {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+/// `google.rpc.RetryInfo`
+#[derive(Clone, PartialEq, Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<ProtoDuration>,
+}
+
+/// `google.protobuf.Duration`
+#[derive(Clone, PartialEq, Message)]
+struct ProtoDuration {
+    #[prost(int64, tag = "1")]
+    seconds: i64,
+    #[prost(int32, tag = "2")]
+    nanos: i32,
+}
+
+/// The delay before retrying the collector asked for in the details of
+/// `status`, if any.
+pub(super) fn retry_delay(status: &Status) -> Option<Duration> {
+    if status.details().is_empty() {
+        return None;
+    }
+    let details = RpcStatus::decode(status.details()).ok()?.details;
+    let retry_info = details
+        .iter()
+        .find(|detail| detail.type_url == RETRY_INFO_TYPE_URL)?;
+    let delay = RetryInfo::decode(retry_info.value.as_slice())
+        .ok()?
+        .retry_delay?;
+    Some(Duration::new(
+        u64::try_from(delay.seconds).ok()?,
+        u32::try_from(delay.nanos).ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use prost::Message;
+    use tonic::{Code, Status};
+
+    use super::{retry_delay, Any, ProtoDuration, RetryInfo, RpcStatus, RETRY_INFO_TYPE_URL};
+
+    fn status_with_details(details: Vec<Any>) -> Status {
+        Status::with_details(
+            Code::Unavailable,
+            "collector is overloaded",
+            RpcStatus { details }.encode_to_vec().into(),
+        )
+    }
+
+    #[test]
+    fn reads_retry_delay_from_status_details() {
+        let retry_info = RetryInfo {
+            retry_delay: Some(ProtoDuration {
+                seconds: 2,
+                nanos: 500_000_000,
+            }),
+        };
+        let status = status_with_details(vec![
+            Any {
+                type_url: "type.googleapis.com/google.rpc.ErrorInfo".into(),
+                value: vec![],
+            },
+            Any {
+                type_url: RETRY_INFO_TYPE_URL.into(),
+                value: retry_info.encode_to_vec(),
+            },
+        ]);
+
+        assert_eq!(retry_delay(&status), Some(Duration::from_millis(2500)));
+    }
+
+    #[test]
+    fn no_retry_delay_without_valid_retry_info() {
+        assert_eq!(retry_delay(&Status::unavailable("unavailable")), None);
+        assert_eq!(retry_delay(&status_with_details(vec![])), None);
+
+        let negative = RetryInfo {
+            retry_delay: Some(ProtoDuration {
+                seconds: -1,
+                nanos: 0,
+            }),
+        };
+        let status = status_with_details(vec![Any {
+            type_url: RETRY_INFO_TYPE_URL.into(),
+            value: negative.encode_to_vec(),
+        }]);
+        assert_eq!(retry_delay(&status), None);
+    }
+}
@@ -10,10 +10,17 @@ use tonic::{codegen::CompressionEncoding, service::Interceptor, transport::Chann
 
 use opentelemetry_proto::transform::trace::tonic::group_spans_by_resource_and_scope;
 
-use super::BoxInterceptor;
+use super::{export_with_retry, BoxInterceptor, ReadinessProbe, SharedInterceptor};
+use crate::exporter::canonical::SortAttributes;
+use crate::exporter::retry::RetryConfig;
+use crate::exporter::ResponseObserver;
 
 pub(crate) struct TonicTracesClient {
     inner: Option<ClientInner>,
+    probe: Option<ReadinessProbe>,
+    retry: Option<RetryConfig>,
+    observer: Option<ResponseObserver>,
+    sort_attributes: bool,
     #[allow(dead_code)]
     // <allow dead> would be removed once we support set_resource for metrics.
     resource: opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema,
@@ -21,7 +28,7 @@ pub(crate) struct TonicTracesClient {
 
 struct ClientInner {
     client: TraceServiceClient<Channel>,
-    interceptor: BoxInterceptor,
+    interceptor: SharedInterceptor,
 }
 
 impl fmt::Debug for TonicTracesClient {
@@ -35,6 +42,10 @@ impl TonicTracesClient {
         channel: Channel,
         interceptor: BoxInterceptor,
         compression: Option<CompressionEncoding>,
+        probe: Option<ReadinessProbe>,
+        retry: Option<RetryConfig>,
+        observer: Option<ResponseObserver>,
+        sort_attributes: bool,
     ) -> Self {
         let mut client = TraceServiceClient::new(channel);
         if let Some(compression) = compression {
@@ -46,8 +57,12 @@ impl TonicTracesClient {
         TonicTracesClient {
             inner: Some(ClientInner {
                 client,
-                interceptor,
+                interceptor: SharedInterceptor::new(interceptor),
             }),
+            probe,
+            retry,
+            observer,
+            sort_attributes,
             resource: Default::default(),
         }
     }
@@ -55,7 +70,7 @@ impl TonicTracesClient {
 
 impl SpanExporter for TonicTracesClient {
     fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
-        let (mut client, metadata, extensions) = match &mut self.inner {
+        let (client, interceptor, metadata, extensions) = match &mut self.inner {
             Some(inner) => {
                 let (m, e, _) = match inner.interceptor.call(Request::new(())) {
                     Ok(res) => res.into_parts(),
@@ -63,7 +78,7 @@ impl SpanExporter for TonicTracesClient {
                         return Box::pin(std::future::ready(Err(TraceError::Other(Box::new(e)))))
                     }
                 };
-                (inner.client.clone(), m, e)
+                (inner.client.clone(), inner.interceptor.clone(), m, e)
             }
             None => {
                 return Box::pin(std::future::ready(Err(TraceError::Other(
@@ -73,16 +88,33 @@ impl SpanExporter for TonicTracesClient {
         };
 
         let resource_spans = group_spans_by_resource_and_scope(batch, &self.resource);
+        let mut request = ExportTraceServiceRequest { resource_spans };
+        if self.sort_attributes {
+            request.sort_attributes();
+        }
+        let probe = self.probe.clone();
+        let retry = self.retry.clone();
+        let observer = self.observer.clone();
 
         Box::pin(async move {
-            client
-                .export(Request::from_parts(
-                    metadata,
-                    extensions,
-                    ExportTraceServiceRequest { resource_spans },
-                ))
-                .await
-                .map_err(crate::Error::from)?;
+            if let Some(probe) = probe {
+                probe.wait_before_first_export().await;
+            }
+
+            export_with_retry(
+                retry.as_ref(),
+                observer.as_ref(),
+                interceptor,
+                metadata,
+                extensions,
+                request,
+                |request| {
+                    let mut client = client.clone();
+                    async move { client.export(request).await }
+                },
+            )
+            .await
+            .map_err(crate::Error::from)?;
 
             Ok(())
         })
@@ -0,0 +1,208 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tonic::metadata::MetadataMap;
+use tonic::service::Interceptor;
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
+use tonic::transport::{Channel, Endpoint};
+
+use crate::exporter::OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT;
+
+/// A gRPC connection to the collector shared by several exporters.
+///
+/// By default each exporter built by [`TonicExporterBuilder`] dials its own
+/// [`Channel`], so an application exporting traces, metrics and logs opens
+/// three connections to the collector. Passing clones of the same transport
+/// to each builder with [`TonicExporterBuilder::with_transport`] multiplexes
+/// all exports over one connection.
+///
+/// The connection is established lazily on the first export. If it breaks it
+/// is reestablished on the next export, configure
+/// [`with_keep_alive_interval`] to also detect broken idle connections.
+///
+/// The metadata and interceptor of the transport are applied to the requests
+/// of every exporter using it, e.g. to authenticate once for all signals.
+/// Exporters can still add their own metadata and interceptor, which are
+/// applied after the transport's and take precedence on conflicting metadata.
+///
+/// The endpoint, timeout and TLS settings of the transport replace those of
+/// the exporter builders, including the signal specific environment
+/// variables.
+///
+/// [`TonicExporterBuilder`]: crate::TonicExporterBuilder
+/// [`TonicExporterBuilder::with_transport`]: crate::TonicExporterBuilder::with_transport
+/// [`with_keep_alive_interval`]: OtlpTransportBuilder::with_keep_alive_interval
+///
+/// ## Examples
+///
+/// ```no_run
+/// # #[cfg(all(feature = "trace", feature = "logs"))]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use opentelemetry_otlp::OtlpTransport;
+/// use tonic::metadata::MetadataMap;
+///
+/// let mut metadata = MetadataMap::new();
+/// metadata.insert("authorization", "Bearer token".parse()?);
+///
+/// let transport = OtlpTransport::tonic("http://localhost:4317")
+///     .with_metadata(metadata)
+///     .build()?;
+///
+/// let span_exporter = opentelemetry_otlp::new_exporter()
+///     .tonic()
+///     .with_transport(transport.clone())
+///     .build_span_exporter()?;
+/// let log_exporter = opentelemetry_otlp::new_exporter()
+///     .tonic()
+///     .with_transport(transport)
+///     .build_log_exporter()?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(all(feature = "trace", feature = "logs")))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug)]
+pub struct OtlpTransport {
+    pub(crate) channel: Channel,
+    pub(crate) endpoint: Endpoint,
+    pub(crate) timeout: Duration,
+    pub(crate) metadata: MetadataMap,
+    pub(crate) interceptor: Option<SharedInterceptor>,
+}
+
+impl OtlpTransport {
+    /// Configure a tonic transport connecting to the collector at `endpoint`.
+    pub fn tonic(endpoint: impl Into<String>) -> OtlpTransportBuilder {
+        OtlpTransportBuilder {
+            endpoint: endpoint.into(),
+            timeout: Duration::from_secs(OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT),
+            connect_timeout: None,
+            keep_alive_interval: None,
+            #[cfg(feature = "tls")]
+            tls_config: None,
+            metadata: MetadataMap::new(),
+            interceptor: None,
+        }
+    }
+}
+
+/// An interceptor shared by all exporters using an [`OtlpTransport`].
+#[derive(Clone)]
+pub(crate) struct SharedInterceptor(Arc<Mutex<dyn Interceptor + Send>>);
+
+impl Interceptor for SharedInterceptor {
+    fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> {
+        match self.0.lock() {
+            Ok(mut interceptor) => interceptor.call(request),
+            Err(_) => Err(tonic::Status::internal("transport interceptor poisoned")),
+        }
+    }
+}
+
+impl Debug for SharedInterceptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedInterceptor(..)")
+    }
+}
+
+/// Configuration of a tonic [`OtlpTransport`].
+#[derive(Debug)]
+pub struct OtlpTransportBuilder {
+    endpoint: String,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    keep_alive_interval: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls_config: Option<ClientTlsConfig>,
+    metadata: MetadataMap,
+    interceptor: Option<SharedInterceptor>,
+}
+
+impl OtlpTransportBuilder {
+    /// Set the timeout of each export request.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the timeout of establishing the connection, bounded by the
+    /// [request timeout](Self::with_timeout) if not set.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send HTTP/2 pings at the given interval, including when no export is in
+    /// progress, so that broken connections are detected and reestablished
+    /// before the next export.
+    pub fn with_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Set the TLS settings for the collector endpoint.
+    #[cfg(feature = "tls")]
+    pub fn with_tls_config(mut self, tls_config: ClientTlsConfig) -> Self {
+        self.tls_config = Some(tls_config);
+        self
+    }
+
+    /// Set metadata entries sent with the requests of every exporter.
+    pub fn with_metadata(mut self, metadata: MetadataMap) -> Self {
+        let mut headers = self.metadata.into_headers();
+        headers.extend(metadata.into_headers());
+        self.metadata = MetadataMap::from_headers(headers);
+        self
+    }
+
+    /// Use `interceptor` to modify the requests of every exporter, for example
+    /// to inject auth tokens.
+    pub fn with_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: Interceptor + Send + 'static,
+    {
+        self.interceptor = Some(SharedInterceptor(Arc::new(Mutex::new(interceptor))));
+        self
+    }
+
+    /// Create the transport.
+    ///
+    /// This does not connect to the collector yet, so it succeeds while the
+    /// collector is unavailable.
+    pub fn build(self) -> Result<OtlpTransport, crate::Error> {
+        let endpoint = Channel::from_shared(self.endpoint).map_err(crate::Error::from)?;
+
+        #[cfg(feature = "tls")]
+        let endpoint = match self.tls_config {
+            Some(tls_config) => endpoint
+                .tls_config(tls_config)
+                .map_err(crate::Error::from)?,
+            None => endpoint,
+        };
+
+        let endpoint = match self.keep_alive_interval {
+            Some(interval) => endpoint
+                .http2_keep_alive_interval(interval)
+                .keep_alive_while_idle(true),
+            None => endpoint,
+        };
+
+        let endpoint = match self.connect_timeout {
+            Some(connect_timeout) => endpoint.connect_timeout(connect_timeout),
+            None => endpoint,
+        };
+
+        let endpoint = endpoint.timeout(self.timeout);
+        Ok(OtlpTransport {
+            channel: endpoint.clone().connect_lazy(),
+            endpoint,
+            timeout: self.timeout,
+            metadata: self.metadata,
+            interceptor: self.interceptor,
+        })
+    }
+}
@@ -52,6 +52,10 @@
 //! }
 //! ```
 //!
+//! The [`presets`] module configures the tracer, meter and logger providers in
+//! a single call, e.g. [`presets::otlp_grpc_batch`] exporting every signal to
+//! a collector over gRPC with batching.
+//!
 //! ## Performance
 //!
 //! For optimal performance, a batch exporter is recommended as the simple
@@ -97,13 +101,17 @@
 //! For users uses `tonic` as grpc layer:
 //! * `grpc-tonic`: Use `tonic` as grpc layer. This is enabled by default.
 //! * `gzip-tonic`: Use gzip compression for `tonic` grpc layer.
+//! * `zstd-tonic`: Use zstd compression for `tonic` grpc layer.
 //! * `tls-tonic`: Enable TLS.
 //! * `tls-roots`: Adds system trust roots to rustls-based gRPC clients using the rustls-native-certs crate
 //! * `tls-webkpi-roots`: Embeds Mozilla's trust roots to rustls-based gRPC clients using the webkpi-roots crate
+//! * `uds`: Export over a Unix domain socket with `TonicExporterBuilder::with_uds_path`.
 //!
 //! The following feature flags offer additional configurations on http:
 //!
 //! * `http-proto`: Use http as transport layer, protobuf as body format.
+//! * `gzip-http`: Use gzip compression for the http transport.
+//! * `zstd-http`: Use zstd compression for the http transport.
 //! * `reqwest-blocking-client`: Use reqwest blocking http client.
 //! * `reqwest-client`: Use reqwest http client.
 //! * `reqwest-rustls`: Use reqwest with TLS with system trust roots via `rustls-native-certs` crate.
@@ -167,7 +175,8 @@
 //!     let export_config = ExportConfig {
 //!         endpoint: "http://localhost:4317".to_string(),
 //!         timeout: Duration::from_secs(3),
-//!         protocol: Protocol::Grpc
+//!         protocol: Protocol::Grpc,
+//!         ..ExportConfig::default()
 //!     };
 //!
 //!     let meter = opentelemetry_otlp::new_pipeline()
@@ -218,11 +227,22 @@ mod exporter;
 mod logs;
 #[cfg(feature = "metrics")]
 mod metric;
+#[cfg(all(feature = "metrics", feature = "grpc-tonic"))]
+mod metric_pull;
+#[cfg(all(
+    feature = "grpc-tonic",
+    feature = "trace",
+    feature = "metrics",
+    feature = "logs"
+))]
+pub mod presets;
 #[cfg(feature = "trace")]
 mod span;
 
 pub use crate::exporter::Compression;
-pub use crate::exporter::ExportConfig;
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto", feature = "http-json"))]
+pub use crate::exporter::ExportResponse;
+pub use crate::exporter::{ExportConfig, RetryPolicy};
 #[cfg(feature = "trace")]
 pub use crate::span::{
     OtlpTracePipeline, SpanExporter, SpanExporterBuilder, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
@@ -236,6 +256,8 @@ pub use crate::metric::{
     OTEL_EXPORTER_OTLP_METRICS_COMPRESSION, OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
     OTEL_EXPORTER_OTLP_METRICS_HEADERS, OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
 };
+#[cfg(all(feature = "metrics", feature = "grpc-tonic"))]
+pub use crate::metric_pull::{MetricsPullServer, OtlpPullReader, OtlpPullReaderBuilder};
 
 #[cfg(feature = "logs")]
 pub use crate::logs::{
@@ -257,7 +279,12 @@ use opentelemetry_sdk::export::ExportError;
 pub use crate::exporter::http::HttpExporterBuilder;
 
 #[cfg(feature = "grpc-tonic")]
-pub use crate::exporter::tonic::{TonicConfig, TonicExporterBuilder};
+pub use crate::exporter::tonic::{
+    OtlpTransport, OtlpTransportBuilder, TonicConfig, TonicExporterBuilder,
+};
+
+#[cfg(feature = "experimental-grpc-logs-stream")]
+pub use crate::exporter::tonic::LogStreamConfig;
 
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
@@ -369,6 +396,11 @@ pub enum Error {
     #[error("the lock of the {0} has been poisoned")]
     PoisonedLock(&'static str),
 
+    /// Compressing the request payload failed.
+    #[cfg(any(feature = "gzip-http", feature = "zstd-http"))]
+    #[error("compression failed with {0}")]
+    CompressionFailed(std::io::Error),
+
     /// Unsupported compression algorithm.
     #[error("unsupported compression algorithm '{0}'")]
     UnsupportedCompressionAlgorithm(String),
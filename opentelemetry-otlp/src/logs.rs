@@ -65,6 +65,19 @@ impl LogExporterBuilder {
             LogExporterBuilder::Http(builder) => builder.build_log_exporter(),
         }
     }
+
+    /// Delay retried http exports with the timer of the runtime exports run on.
+    #[cfg_attr(not(feature = "http-proto"), allow(unused_variables))]
+    fn set_runtime<R: RuntimeChannel>(&mut self, runtime: &R) {
+        match self {
+            #[cfg(feature = "http-proto")]
+            LogExporterBuilder::Http(builder) => {
+                *builder = std::mem::take(builder).with_runtime(runtime.clone())
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
 }
 
 #[cfg(feature = "grpc-tonic")]
@@ -85,6 +98,8 @@ impl From<HttpExporterBuilder> for LogExporterBuilder {
 #[derive(Debug)]
 pub struct LogExporter {
     client: Box<dyn opentelemetry_sdk::export::logs::LogExporter>,
+    #[cfg(feature = "grpc-tonic")]
+    probe: Option<crate::exporter::tonic::ReadinessProbe>,
 }
 
 impl LogExporter {
@@ -92,8 +107,36 @@ impl LogExporter {
     pub fn new(client: impl opentelemetry_sdk::export::logs::LogExporter + 'static) -> Self {
         LogExporter {
             client: Box::new(client),
+            #[cfg(feature = "grpc-tonic")]
+            probe: None,
         }
     }
+
+    /// Wait until the collector accepts connections.
+    ///
+    /// Connecting is retried with exponential backoff for up to the timeout of
+    /// [`TonicExporterBuilder::with_readiness_probe`], or the export timeout if
+    /// no probe was configured. This is mostly useful in tests starting a
+    /// collector alongside the application. Exporters using HTTP or a custom
+    /// tonic channel are always considered ready.
+    ///
+    /// [`TonicExporterBuilder::with_readiness_probe`]: crate::TonicExporterBuilder::with_readiness_probe
+    pub async fn ready(&self) -> Result<(), crate::Error> {
+        #[cfg(feature = "grpc-tonic")]
+        if let Some(probe) = &self.probe {
+            return probe.ready().await;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "grpc-tonic")]
+    pub(crate) fn with_probe(
+        mut self,
+        probe: Option<crate::exporter::tonic::ReadinessProbe>,
+    ) -> Self {
+        self.probe = probe;
+        self
+    }
 }
 
 #[async_trait]
@@ -171,8 +214,10 @@ impl OtlpLogPipeline<LogExporterBuilder> {
         self,
         runtime: R,
     ) -> Result<opentelemetry_sdk::logs::LoggerProvider, LogError> {
+        let mut exporter_builder = self.exporter_builder;
+        exporter_builder.set_runtime(&runtime);
         Ok(build_batch_with_exporter(
-            self.exporter_builder.build_log_exporter()?,
+            exporter_builder.build_log_exporter()?,
             self.resource,
             runtime,
             self.batch_config,
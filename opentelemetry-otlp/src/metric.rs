@@ -62,6 +62,7 @@ impl OtlpPipeline {
 
 /// OTLP metrics exporter builder.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
 pub enum MetricsExporterBuilder {
     /// Tonic metrics exporter builder
@@ -103,6 +104,19 @@ impl MetricsExporterBuilder {
             }
         }
     }
+
+    /// Delay retried http exports with the timer of the runtime exports run on.
+    #[cfg_attr(not(feature = "http-proto"), allow(unused_variables))]
+    fn set_runtime<R: Runtime>(&mut self, runtime: &R) {
+        match self {
+            #[cfg(feature = "http-proto")]
+            MetricsExporterBuilder::Http(builder) => {
+                *builder = std::mem::take(builder).with_runtime(runtime.clone())
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
 }
 
 #[cfg(feature = "grpc-tonic")]
@@ -215,7 +229,9 @@ where
 {
     /// Build MeterProvider
     pub fn build(self) -> Result<SdkMeterProvider> {
-        let exporter = self.exporter_pipeline.build_metrics_exporter(
+        let mut exporter_pipeline = self.exporter_pipeline;
+        exporter_pipeline.set_runtime(&self.rt);
+        let exporter = exporter_pipeline.build_metrics_exporter(
             self.temporality_selector
                 .unwrap_or_else(|| Box::new(DefaultTemporalitySelector::new())),
             self.aggregator_selector
@@ -296,6 +312,8 @@ pub struct MetricsExporter {
     client: Box<dyn MetricsClient>,
     temporality_selector: Box<dyn TemporalitySelector>,
     aggregation_selector: Box<dyn AggregationSelector>,
+    #[cfg(feature = "grpc-tonic")]
+    probe: Option<crate::exporter::tonic::ReadinessProbe>,
 }
 
 impl Debug for MetricsExporter {
@@ -343,6 +361,34 @@ impl MetricsExporter {
             client: Box::new(client),
             temporality_selector,
             aggregation_selector,
+            #[cfg(feature = "grpc-tonic")]
+            probe: None,
         }
     }
+
+    /// Wait until the collector accepts connections.
+    ///
+    /// Connecting is retried with exponential backoff for up to the timeout of
+    /// [`TonicExporterBuilder::with_readiness_probe`], or the export timeout if
+    /// no probe was configured. This is mostly useful in tests starting a
+    /// collector alongside the application. Exporters using HTTP or a custom
+    /// tonic channel are always considered ready.
+    ///
+    /// [`TonicExporterBuilder::with_readiness_probe`]: crate::TonicExporterBuilder::with_readiness_probe
+    pub async fn ready(&self) -> std::result::Result<(), crate::Error> {
+        #[cfg(feature = "grpc-tonic")]
+        if let Some(probe) = &self.probe {
+            return probe.ready().await;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "grpc-tonic")]
+    pub(crate) fn with_probe(
+        mut self,
+        probe: Option<crate::exporter::tonic::ReadinessProbe>,
+    ) -> Self {
+        self.probe = probe;
+        self
+    }
 }
@@ -0,0 +1,314 @@
+//! Pull-based exposition of metrics.
+//!
+//! Instead of pushing metrics on an interval like [`MetricsExporter`], an
+//! [`OtlpPullReader`] only collects metrics when an external agent asks for
+//! them. The collected metrics are encoded as an OTLP
+//! `ExportMetricsServiceRequest`, which is either served by the gRPC
+//! [`MetricsPullServer`], or returned by [`OtlpPullReader::collect_request`]
+//! to be served from an existing endpoint, e.g. as the protobuf body of an
+//! HTTP `GET` response.
+//!
+//! [`MetricsExporter`]: crate::MetricsExporter
+use core::fmt;
+use opentelemetry::metrics::Result;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_sdk::{
+    metrics::{
+        data::{ResourceMetrics, Temporality},
+        reader::{AggregationSelector, MetricReader, TemporalitySelector},
+        Aggregation, InstrumentKind, ManualReader, ManualReaderBuilder, Pipeline,
+    },
+    Resource,
+};
+use std::sync::{Arc, Weak};
+use tonic::codegen::{empty_body, http, Body, BoxFuture, Context, Poll, StdError};
+
+/// A [MetricReader] collecting metrics on demand, for agents pulling the
+/// metrics of an application.
+///
+/// The reader is cloned to be registered with a meter provider and served by
+/// a [`MetricsPullServer`], all clones share the same underlying reader.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use opentelemetry_otlp::{MetricsPullServer, OtlpPullReader};
+/// use opentelemetry_sdk::metrics::SdkMeterProvider;
+///
+/// let reader = OtlpPullReader::builder().build();
+/// let _provider = SdkMeterProvider::builder()
+///     .with_reader(reader.clone())
+///     .build();
+///
+/// tonic::transport::Server::builder()
+///     .add_service(MetricsPullServer::new(reader))
+///     .serve("0.0.0.0:4320".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OtlpPullReader {
+    reader: Arc<ManualReader>,
+}
+
+impl OtlpPullReader {
+    /// Configuration for a pull reader.
+    pub fn builder() -> OtlpPullReaderBuilder {
+        OtlpPullReaderBuilder::default()
+    }
+
+    /// Collects the current metrics, encoded as an OTLP export request.
+    pub fn collect_request(&self) -> Result<ExportMetricsServiceRequest> {
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![],
+        };
+        self.reader.collect(&mut metrics)?;
+        Ok(ExportMetricsServiceRequest::from(&metrics))
+    }
+}
+
+impl fmt::Debug for OtlpPullReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtlpPullReader")
+            .field("reader", &self.reader)
+            .finish()
+    }
+}
+
+impl TemporalitySelector for OtlpPullReader {
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.reader.temporality(kind)
+    }
+}
+
+impl AggregationSelector for OtlpPullReader {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.reader.aggregation(kind)
+    }
+}
+
+impl MetricReader for OtlpPullReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.reader.register_pipeline(pipeline)
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> Result<()> {
+        self.reader.collect(rm)
+    }
+
+    fn force_flush(&self) -> Result<()> {
+        self.reader.force_flush()
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        self.reader.shutdown()
+    }
+}
+
+/// Configuration for an [`OtlpPullReader`].
+#[derive(Debug, Default)]
+pub struct OtlpPullReaderBuilder {
+    reader: ManualReaderBuilder,
+}
+
+impl OtlpPullReaderBuilder {
+    /// Sets the [TemporalitySelector] used by the reader, cumulative
+    /// temporality is used by default.
+    ///
+    /// Agents pulling delta temporality metrics must be the only agent
+    /// pulling from the reader, since each collection resets the metrics.
+    pub fn with_temporality_selector(
+        mut self,
+        temporality_selector: impl TemporalitySelector + 'static,
+    ) -> Self {
+        self.reader = self.reader.with_temporality_selector(temporality_selector);
+        self
+    }
+
+    /// Sets the [AggregationSelector] used by the reader.
+    pub fn with_aggregation_selector(
+        mut self,
+        aggregation_selector: impl AggregationSelector + 'static,
+    ) -> Self {
+        self.reader = self.reader.with_aggregation_selector(aggregation_selector);
+        self
+    }
+
+    /// Creates the reader.
+    pub fn build(self) -> OtlpPullReader {
+        OtlpPullReader {
+            reader: Arc::new(self.reader.build()),
+        }
+    }
+}
+
+/// A gRPC service serving the metrics collected by an [`OtlpPullReader`].
+///
+/// The service is not part of OTLP, it is defined by `proto/metrics_pull.proto`
+/// in this crate. It has a single unary method,
+/// `/opentelemetry.rust.metrics.v1.MetricsPullService/Collect`, taking a
+/// `google.protobuf.Empty` request and returning the collected metrics as an
+/// `opentelemetry.proto.collector.metrics.v1.ExportMetricsServiceRequest`.
+#[derive(Clone, Debug)]
+pub struct MetricsPullServer {
+    reader: OtlpPullReader,
+}
+
+impl MetricsPullServer {
+    /// Creates a service serving the metrics collected by `reader`.
+    pub fn new(reader: OtlpPullReader) -> Self {
+        MetricsPullServer { reader }
+    }
+}
+
+const COLLECT_PATH: &str = "/opentelemetry.rust.metrics.v1.MetricsPullService/Collect";
+
+struct CollectSvc(OtlpPullReader);
+
+impl tonic::server::UnaryService<()> for CollectSvc {
+    type Response = ExportMetricsServiceRequest;
+    type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+
+    fn call(&mut self, _request: tonic::Request<()>) -> Self::Future {
+        let result = self
+            .0
+            .collect_request()
+            .map(tonic::Response::new)
+            .map_err(|err| tonic::Status::internal(err.to_string()));
+        Box::pin(async move { result })
+    }
+}
+
+impl<B> tonic::codegen::Service<http::Request<B>> for MetricsPullServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        if req.uri().path() != COLLECT_PATH {
+            return Box::pin(async move {
+                // grpc-status 12 is UNIMPLEMENTED
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .expect("valid response"))
+            });
+        }
+
+        let method = CollectSvc(self.reader.clone());
+        Box::pin(async move {
+            let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+            Ok(grpc.unary(method, req).await)
+        })
+    }
+}
+
+impl tonic::server::NamedService for MetricsPullServer {
+    const NAME: &'static str = "opentelemetry.rust.metrics.v1.MetricsPullService";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry::KeyValue;
+    use opentelemetry_proto::tonic::metrics::v1::metric::Data;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    #[test]
+    fn collect_request_returns_current_metrics() {
+        let reader = OtlpPullReader::builder().build();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .with_resource(Resource::new(vec![KeyValue::new("service.name", "pull")]))
+            .build();
+        let counter = provider.meter("test").u64_counter("requests").init();
+
+        counter.add(1, &[]);
+        counter.add(2, &[]);
+        let request = reader.collect_request().unwrap();
+
+        let resource_metrics = &request.resource_metrics[0];
+        assert_eq!(
+            resource_metrics.resource.as_ref().unwrap().attributes[0].key,
+            "service.name"
+        );
+        let metric = &resource_metrics.scope_metrics[0].metrics[0];
+        assert_eq!(metric.name, "requests");
+        match &metric.data {
+            Some(Data::Sum(sum)) => assert_eq!(sum.data_points.len(), 1),
+            other => panic!("unexpected data {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn pull_server_serves_collected_metrics() {
+        let reader = OtlpPullReader::builder().build();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .build();
+        let counter = provider.meter("test").u64_counter("requests").init();
+        counter.add(3, &[]);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(MetricsPullServer::new(reader))
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await
+        });
+
+        // an agent calling `Collect` of `proto/metrics_pull.proto`
+        let channel = tonic::transport::Endpoint::from_shared(endpoint)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let client = tonic::client::Grpc::new(channel);
+        let collect = |path: &'static str| {
+            let mut client = client.clone();
+            async move {
+                client.ready().await.unwrap();
+                client
+                    .unary::<(), ExportMetricsServiceRequest, _>(
+                        tonic::Request::new(()),
+                        http::uri::PathAndQuery::from_static(path),
+                        tonic::codec::ProstCodec::default(),
+                    )
+                    .await
+            }
+        };
+
+        let request = collect(COLLECT_PATH).await.unwrap().into_inner();
+        let metric = &request.resource_metrics[0].scope_metrics[0].metrics[0];
+        assert_eq!(metric.name, "requests");
+        match &metric.data {
+            Some(Data::Sum(sum)) => assert_eq!(
+                sum.data_points[0].value,
+                Some(opentelemetry_proto::tonic::metrics::v1::number_data_point::Value::AsInt(3))
+            ),
+            other => panic!("unexpected data {other:?}"),
+        }
+
+        let status = collect("/opentelemetry.rust.metrics.v1.MetricsPullService/Unknown")
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unimplemented);
+    }
+}
@@ -0,0 +1,166 @@
+//! # OTLP - Presets
+//!
+//! One-call configurations of the tracer, meter and logger providers for
+//! common deployments, with the settings recommended by the specification.
+//!
+//! Every preset uses the same [`Resource`] for all signals, built from the
+//! default resource detectors, so that `OTEL_SERVICE_NAME` and
+//! `OTEL_RESOURCE_ATTRIBUTES` are honored. Batch and export settings keep
+//! their defaults and can still be tuned with the standard `OTEL_BSP_*`,
+//! `OTEL_BLRP_*`, `OTEL_METRIC_EXPORT_*` and `OTEL_EXPORTER_OTLP_*`
+//! environment variables.
+
+use opentelemetry::global;
+use opentelemetry::logs::LogError;
+use opentelemetry::metrics::MetricsError;
+use opentelemetry::trace::TraceError;
+use opentelemetry_sdk::logs::LoggerProvider;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::propagation::propagators_from_env;
+use opentelemetry_sdk::runtime::RuntimeChannel;
+use opentelemetry_sdk::trace::{Config, TracerProvider};
+use opentelemetry_sdk::Resource;
+
+use crate::WithExportConfig;
+
+/// Wrap type for errors building the providers of a preset.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum PresetError {
+    /// The tracer provider could not be built.
+    #[error("failed to build the tracer provider: {0}")]
+    Trace(#[from] TraceError),
+
+    /// The meter provider could not be built.
+    #[error("failed to build the meter provider: {0}")]
+    Metrics(#[from] MetricsError),
+
+    /// The logger provider could not be built.
+    #[error("failed to build the logger provider: {0}")]
+    Logs(#[from] LogError),
+}
+
+/// The providers of every signal configured by a preset.
+#[derive(Debug)]
+pub struct Providers {
+    /// Provider of the tracers, exporting spans in batches.
+    pub tracer_provider: TracerProvider,
+    /// Provider of the meters, exporting metrics periodically.
+    pub meter_provider: SdkMeterProvider,
+    /// Provider of the loggers used by log appenders, exporting logs in
+    /// batches.
+    pub logger_provider: LoggerProvider,
+}
+
+impl Providers {
+    /// Sets the tracer and meter providers as the global providers, and the
+    /// propagators listed in the `OTEL_PROPAGATORS` environment variable as
+    /// the global propagator, W3C trace context and baggage by default.
+    ///
+    /// See [`propagators_from_env`] for the supported propagators, others can
+    /// be set afterwards with
+    /// [`init_propagators_from_env_with`](opentelemetry_sdk::propagation::init_propagators_from_env_with).
+    ///
+    /// There is no global logger provider, the [`logger_provider`] has to be
+    /// passed to the log appenders.
+    ///
+    /// [`logger_provider`]: Providers::logger_provider
+    pub fn install_global(&self) {
+        global::set_text_map_propagator(propagators_from_env(|_| None));
+        global::set_tracer_provider(self.tracer_provider.clone());
+        global::set_meter_provider(self.meter_provider.clone());
+    }
+
+    /// Flushes and shuts down every provider, returning the first error.
+    ///
+    /// Every provider is shut down even if another one failed.
+    pub fn shutdown(&self) -> Result<(), PresetError> {
+        let traces = self.tracer_provider.shutdown();
+        let metrics = self.meter_provider.shutdown();
+        let logs = self.logger_provider.shutdown();
+        traces?;
+        metrics?;
+        logs?;
+        Ok(())
+    }
+}
+
+/// Build providers exporting every signal over OTLP/gRPC to `endpoint`, with
+/// batch span and log processors and a periodic metric reader running on
+/// `runtime`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use opentelemetry::trace::Tracer;
+///
+/// # async fn run() -> Result<(), opentelemetry_otlp::presets::PresetError> {
+/// let providers = opentelemetry_otlp::presets::otlp_grpc_batch(
+///     "http://localhost:4317",
+///     opentelemetry_sdk::runtime::Tokio,
+/// )?;
+/// providers.install_global();
+///
+/// opentelemetry::global::tracer("my_app").in_span("doing_work", |_cx| {
+///     // Traced app logic here...
+/// });
+///
+/// providers.shutdown()
+/// # }
+/// ```
+pub fn otlp_grpc_batch<R: RuntimeChannel>(
+    endpoint: impl Into<String>,
+    runtime: R,
+) -> Result<Providers, PresetError> {
+    let endpoint = endpoint.into();
+    let resource = Resource::default();
+
+    let tracer_provider = crate::new_pipeline()
+        .tracing()
+        .with_exporter(crate::new_exporter().tonic().with_endpoint(&endpoint))
+        .with_trace_config(Config::default().with_resource(resource.clone()))
+        .install_batch(runtime.clone())?;
+
+    let meter_provider = crate::new_pipeline()
+        .metrics(runtime.clone())
+        .with_exporter(crate::new_exporter().tonic().with_endpoint(&endpoint))
+        .with_resource(resource.clone())
+        .build()?;
+
+    let logger_provider = crate::new_pipeline()
+        .logging()
+        .with_exporter(crate::new_exporter().tonic().with_endpoint(endpoint))
+        .with_resource(resource)
+        .install_batch(runtime)?;
+
+    Ok(Providers {
+        tracer_provider,
+        meter_provider,
+        logger_provider,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn install_global_uses_propagators_from_env() {
+        let providers =
+            otlp_grpc_batch("http://localhost:4317", opentelemetry_sdk::runtime::Tokio).unwrap();
+
+        temp_env::with_var("OTEL_PROPAGATORS", Some("b3"), || {
+            providers.install_global()
+        });
+
+        let fields = global::get_text_map_propagator(|propagator| {
+            propagator
+                .fields()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(fields, vec!["b3"]);
+        // nothing listens on the endpoint, the final exports fail
+        let _ = providers.shutdown();
+    }
+}
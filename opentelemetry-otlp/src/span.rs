@@ -118,8 +118,10 @@ impl OtlpTracePipeline<SpanExporterBuilder> {
         self,
         runtime: R,
     ) -> Result<sdk::trace::TracerProvider, TraceError> {
+        let mut exporter_builder = self.exporter_builder;
+        exporter_builder.set_runtime(&runtime);
         Ok(build_batch_with_exporter(
-            self.exporter_builder.build_span_exporter()?,
+            exporter_builder.build_span_exporter()?,
             self.trace_config,
             runtime,
             self.batch_config,
@@ -182,6 +184,22 @@ impl SpanExporterBuilder {
             SpanExporterBuilder::Http(builder) => builder.build_span_exporter(),
         }
     }
+
+    /// Delay retried http exports with the timer of the runtime exports run on.
+    #[cfg_attr(
+        not(any(feature = "http-proto", feature = "http-json")),
+        allow(unused_variables)
+    )]
+    fn set_runtime<R: RuntimeChannel>(&mut self, runtime: &R) {
+        match self {
+            #[cfg(any(feature = "http-proto", feature = "http-json"))]
+            SpanExporterBuilder::Http(builder) => {
+                *builder = std::mem::take(builder).with_runtime(runtime.clone())
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
 }
 
 #[cfg(feature = "grpc-tonic")]
@@ -200,21 +218,55 @@ impl From<HttpExporterBuilder> for SpanExporterBuilder {
 
 /// OTLP exporter that sends tracing information
 #[derive(Debug)]
-pub struct SpanExporter(Box<dyn opentelemetry_sdk::export::trace::SpanExporter>);
+pub struct SpanExporter {
+    client: Box<dyn opentelemetry_sdk::export::trace::SpanExporter>,
+    #[cfg(feature = "grpc-tonic")]
+    probe: Option<crate::exporter::tonic::ReadinessProbe>,
+}
 
 impl SpanExporter {
     /// Build a new span exporter from a client
     pub fn new(client: impl opentelemetry_sdk::export::trace::SpanExporter + 'static) -> Self {
-        SpanExporter(Box::new(client))
+        SpanExporter {
+            client: Box::new(client),
+            #[cfg(feature = "grpc-tonic")]
+            probe: None,
+        }
+    }
+
+    /// Wait until the collector accepts connections.
+    ///
+    /// Connecting is retried with exponential backoff for up to the timeout of
+    /// [`TonicExporterBuilder::with_readiness_probe`], or the export timeout if
+    /// no probe was configured. This is mostly useful in tests starting a
+    /// collector alongside the application. Exporters using HTTP or a custom
+    /// tonic channel are always considered ready.
+    ///
+    /// [`TonicExporterBuilder::with_readiness_probe`]: crate::TonicExporterBuilder::with_readiness_probe
+    pub async fn ready(&self) -> Result<(), crate::Error> {
+        #[cfg(feature = "grpc-tonic")]
+        if let Some(probe) = &self.probe {
+            return probe.ready().await;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "grpc-tonic")]
+    pub(crate) fn with_probe(
+        mut self,
+        probe: Option<crate::exporter::tonic::ReadinessProbe>,
+    ) -> Self {
+        self.probe = probe;
+        self
     }
 }
 
 impl opentelemetry_sdk::export::trace::SpanExporter for SpanExporter {
     fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
-        self.0.export(batch)
+        self.client.export(batch)
     }
 
     fn set_resource(&mut self, resource: &opentelemetry_sdk::Resource) {
-        self.0.set_resource(resource);
+        self.client.set_resource(resource);
     }
 }
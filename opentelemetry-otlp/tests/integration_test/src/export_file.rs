@@ -0,0 +1,50 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use tempfile::NamedTempFile;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// open a file written by the collector file exporter, transparently
+// decompressing it if it is gzip compressed
+pub fn open_export_file(file: File) -> Box<dyn Read> {
+    let mut reader = BufReader::new(file);
+    let is_gzip = reader
+        .fill_buf()
+        .map(|buf| buf.starts_with(&GZIP_MAGIC))
+        .unwrap_or(false);
+
+    if is_gzip {
+        Box::new(BufReader::new(GzDecoder::new(reader)))
+    } else {
+        Box::new(reader)
+    }
+}
+
+// read every json document in the file. The file exporter writes one document
+// per export, so a single file usually holds several chunks.
+pub fn read_json_chunks<T: DeserializeOwned>(file: File) -> Vec<T> {
+    serde_json::Deserializer::from_reader(open_export_file(file))
+        .into_iter::<T>()
+        .collect::<Result<_, _>>()
+        .expect("Failed to read json file")
+}
+
+// write `chunks` copies of the json document at `path` into a gzip compressed
+// temporary file, the way the file exporter writes one document per export
+pub fn write_gzip_chunks(path: &str, chunks: usize) -> NamedTempFile {
+    let document = std::fs::read(path).expect("Failed to read json file");
+    let file = NamedTempFile::new().expect("Failed to create temporary file");
+
+    let mut encoder = GzEncoder::new(file.reopen().unwrap(), Compression::default());
+    for _ in 0..chunks {
+        encoder.write_all(&document).unwrap();
+        encoder.write_all(b"\n").unwrap();
+    }
+    encoder.finish().unwrap();
+
+    file
+}
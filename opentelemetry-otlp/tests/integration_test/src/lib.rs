@@ -1,3 +1,4 @@
+pub mod export_file;
 pub mod images;
 pub mod logs_asserter;
 pub mod trace_asserter;
@@ -1,3 +1,4 @@
+use crate::export_file::read_json_chunks;
 use opentelemetry_proto::tonic::logs::v1::{LogRecord, LogsData, ResourceLogs};
 use std::fs::File;
 
@@ -93,10 +94,11 @@ impl std::fmt::Debug for LogRecordWrapper {
     }
 }
 
-// read a file contains ResourceSpans in json format
+// read a file contains ResourceLogs in json format, optionally gzip compressed
+// and split into several chunks
 pub fn read_logs_from_json(file: File) -> Vec<ResourceLogs> {
-    let reader = std::io::BufReader::new(file);
-
-    let log_data: LogsData = serde_json::from_reader(reader).unwrap();
-    log_data.resource_logs
+    read_json_chunks::<LogsData>(file)
+        .into_iter()
+        .flat_map(|log_data| log_data.resource_logs)
+        .collect()
 }
@@ -1,3 +1,4 @@
+use crate::export_file::read_json_chunks;
 use opentelemetry_proto::tonic::trace::v1::{ResourceSpans, Span, TracesData};
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
@@ -212,10 +213,11 @@ fn span_eq(left: &Span, right: &Span) -> bool {
     true
 }
 
-// read a file contains ResourceSpans in json format
+// read a file contains ResourceSpans in json format, optionally gzip compressed
+// and split into several chunks
 pub fn read_spans_from_json(file: File) -> Vec<ResourceSpans> {
-    let reader = std::io::BufReader::new(file);
-
-    let trace_data: TracesData = serde_json::from_reader(reader).expect("Failed to read json file");
-    trace_data.resource_spans
+    read_json_chunks::<TracesData>(file)
+        .into_iter()
+        .flat_map(|trace_data| trace_data.resource_spans)
+        .collect()
 }
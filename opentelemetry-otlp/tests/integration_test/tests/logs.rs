@@ -1,5 +1,6 @@
 #![cfg(unix)]
 
+use integration_test_runner::export_file::write_gzip_chunks;
 use integration_test_runner::logs_asserter::{read_logs_from_json, LogsAsserter};
 use log::{info, Level};
 use opentelemetry::logs::LogError;
@@ -54,3 +55,16 @@ pub fn test_assert_logs_eq() {
     let logs = read_logs_from_json(File::open("./expected/logs.json").unwrap());
     LogsAsserter::new(logs.clone(), logs).assert();
 }
+
+#[test]
+pub fn test_read_gzip_chunked_logs() {
+    let chunked_file = write_gzip_chunks("./expected/logs.json", 2);
+
+    let logs = read_logs_from_json(File::open("./expected/logs.json").unwrap());
+    let chunked = read_logs_from_json(chunked_file.reopen().unwrap());
+
+    assert_eq!(chunked.len(), logs.len() * 2);
+    let (first, second) = chunked.split_at(logs.len());
+    LogsAsserter::new(logs.clone(), first.to_vec()).assert();
+    LogsAsserter::new(logs, second.to_vec()).assert();
+}
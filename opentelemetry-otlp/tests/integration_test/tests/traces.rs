@@ -1,5 +1,6 @@
 #![cfg(unix)]
 
+use integration_test_runner::export_file::write_gzip_chunks;
 use integration_test_runner::trace_asserter::{read_spans_from_json, TraceAsserter};
 use opentelemetry::global;
 use opentelemetry::global::shutdown_tracer_provider;
@@ -110,3 +111,16 @@ pub fn test_serde() {
 
     TraceAsserter::new(left, right).assert();
 }
+
+#[test]
+pub fn test_read_gzip_chunked_spans() {
+    let chunked_file = write_gzip_chunks("./expected/traces.json", 2);
+
+    let spans = read_spans_from_json(File::open("./expected/traces.json").unwrap());
+    let chunked = read_spans_from_json(chunked_file.reopen().unwrap());
+
+    assert_eq!(chunked.len(), spans.len() * 2);
+    let (first, second) = chunked.split_at(spans.len());
+    TraceAsserter::new(spans.clone(), first.to_vec()).assert();
+    TraceAsserter::new(spans, second.to_vec()).assert();
+}
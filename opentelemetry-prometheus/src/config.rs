@@ -7,7 +7,7 @@ use opentelemetry_sdk::metrics::{
 };
 use std::sync::{Arc, Mutex};
 
-use crate::{Collector, PrometheusExporter, ResourceSelector};
+use crate::{Collector, ExemplarStore, PrometheusExporter, ResourceSelector};
 
 /// [PrometheusExporter] configuration options
 #[derive(Default)]
@@ -20,6 +20,8 @@ pub struct ExporterBuilder {
     disable_scope_info: bool,
     reader: ManualReaderBuilder,
     resource_selector: ResourceSelector,
+    with_exemplars: bool,
+    utf8_names: bool,
 }
 
 impl fmt::Debug for ExporterBuilder {
@@ -31,6 +33,8 @@ impl fmt::Debug for ExporterBuilder {
             .field("without_counter_suffixes", &self.without_counter_suffixes)
             .field("namespace", &self.namespace)
             .field("disable_scope_info", &self.disable_scope_info)
+            .field("with_exemplars", &self.with_exemplars)
+            .field("utf8_names", &self.utf8_names)
             .finish()
     }
 }
@@ -80,6 +84,31 @@ impl ExporterBuilder {
         self
     }
 
+    /// Configures the exporter to record the exemplars of counters and
+    /// histograms.
+    ///
+    /// The `prometheus` crate does not support exemplars, they are only written
+    /// by the encoder returned by [PrometheusExporter::open_metrics_encoder].
+    pub fn with_exemplars(mut self) -> Self {
+        self.with_exemplars = true;
+        self
+    }
+
+    /// Configures the exporter to keep metric and label names as they are
+    /// instead of replacing the characters that are not valid in Prometheus
+    /// names.
+    ///
+    /// The encoder returned by [PrometheusExporter::open_metrics_encoder]
+    /// escapes these names for scrapers that did not negotiate UTF-8 names, see
+    /// [OpenMetricsEncoder::for_accept_header]. Other encoders write the names
+    /// as they are.
+    ///
+    /// [OpenMetricsEncoder::for_accept_header]: crate::OpenMetricsEncoder::for_accept_header
+    pub fn with_utf8_names(mut self) -> Self {
+        self.utf8_names = true;
+        self
+    }
+
     /// Configures the exporter to prefix metrics with the given namespace.
     ///
     /// Metrics such as `target_info` and `otel_scope_info` are not prefixed since
@@ -140,6 +169,7 @@ impl ExporterBuilder {
     /// Creates a new [PrometheusExporter] from this configuration.
     pub fn build(self) -> Result<PrometheusExporter> {
         let reader = Arc::new(self.reader.build());
+        let exemplars = self.with_exemplars.then(ExemplarStore::default);
 
         let collector = Collector {
             reader: Arc::clone(&reader),
@@ -152,6 +182,8 @@ impl ExporterBuilder {
             inner: Mutex::new(Default::default()),
             resource_selector: self.resource_selector,
             resource_labels_once: OnceCell::new(),
+            exemplars: exemplars.clone(),
+            utf8_names: self.utf8_names,
         };
 
         let registry = self.registry.unwrap_or_default();
@@ -159,6 +191,6 @@ impl ExporterBuilder {
             .register(Box::new(collector))
             .map_err(|e| MetricsError::Other(e.to_string()))?;
 
-        Ok(PrometheusExporter { reader })
+        Ok(PrometheusExporter { reader, exemplars })
     }
 }
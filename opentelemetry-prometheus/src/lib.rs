@@ -135,12 +135,16 @@ const SCOPE_INFO_KEYS: [&str; 2] = ["otel_scope_name", "otel_scope_version"];
 const COUNTER_SUFFIX: &str = "_total";
 
 mod config;
+mod open_metrics;
 mod resource_selector;
 mod utils;
 
 pub use config::ExporterBuilder;
+pub use open_metrics::OpenMetricsEncoder;
 pub use resource_selector::ResourceSelector;
 
+use open_metrics::{ExemplarStore, Exemplars};
+
 /// Creates a builder to configure a [PrometheusExporter]
 pub fn exporter() -> ExporterBuilder {
     ExporterBuilder::default()
@@ -150,6 +154,19 @@ pub fn exporter() -> ExporterBuilder {
 #[derive(Debug)]
 pub struct PrometheusExporter {
     reader: Arc<ManualReader>,
+    exemplars: Option<ExemplarStore>,
+}
+
+impl PrometheusExporter {
+    /// Creates an [OpenMetricsEncoder] for the metric families gathered from
+    /// the registry of this exporter.
+    ///
+    /// The encoder writes the exemplars of counters and histograms if the
+    /// exporter was built with
+    /// [`with_exemplars`](ExporterBuilder::with_exemplars).
+    pub fn open_metrics_encoder(&self) -> OpenMetricsEncoder {
+        OpenMetricsEncoder::new(self.exemplars.clone())
+    }
 }
 
 impl TemporalitySelector for PrometheusExporter {
@@ -182,6 +199,12 @@ impl MetricReader for PrometheusExporter {
     fn shutdown(&self) -> Result<()> {
         self.reader.shutdown()
     }
+
+    /// Exponential histograms can't be encoded in the Prometheus exposition
+    /// formats, histograms advising them keep explicit buckets.
+    fn supports_aggregation(&self, aggregation: &Aggregation) -> bool {
+        !matches!(aggregation, Aggregation::Base2ExponentialHistogram { .. })
+    }
 }
 
 struct Collector {
@@ -195,6 +218,8 @@ struct Collector {
     namespace: Option<String>,
     inner: Mutex<CollectorInner>,
     resource_selector: ResourceSelector,
+    exemplars: Option<ExemplarStore>,
+    utf8_names: bool,
 }
 
 #[derive(Default)]
@@ -263,7 +288,11 @@ impl Collector {
     }
 
     fn get_name(&self, m: &data::Metric) -> Cow<'static, str> {
-        let name = utils::sanitize_name(&m.name);
+        let name = if self.utf8_names {
+            m.name.clone()
+        } else {
+            utils::sanitize_name(&m.name)
+        };
         let unit_suffixes = if self.without_units {
             None
         } else {
@@ -300,20 +329,28 @@ impl prometheus::core::Collector for Collector {
             global::handle_error(err);
             return vec![];
         }
+
+        let mut exemplars = self.exemplars.as_ref().map(|_| Exemplars::default());
         let mut res = Vec::with_capacity(metrics.scope_metrics.len() + 1);
 
         let target_info = self.create_target_info_once.get_or_init(|| {
             // Resource should be immutable, we don't need to compute again
-            create_info_metric(TARGET_INFO_NAME, TARGET_INFO_DESCRIPTION, &metrics.resource)
+            create_info_metric(
+                TARGET_INFO_NAME,
+                TARGET_INFO_DESCRIPTION,
+                &metrics.resource,
+                self.utf8_names,
+            )
         });
 
         if !self.disable_target_info && !metrics.resource.is_empty() {
             res.push(target_info.clone())
         }
 
-        let resource_labels = self
-            .resource_labels_once
-            .get_or_init(|| self.resource_selector.select(&metrics.resource));
+        let resource_labels = self.resource_labels_once.get_or_init(|| {
+            self.resource_selector
+                .select(&metrics.resource, self.utf8_names)
+        });
 
         for scope_metrics in metrics.scope_metrics {
             let scope_labels = if !self.disable_scope_info {
@@ -361,40 +398,66 @@ impl prometheus::core::Collector for Collector {
                 let description = help.unwrap_or_else(|| metrics.description.into());
                 let data = metrics.data.as_any();
 
+                let labels = Labels {
+                    extra: &scope_labels,
+                    utf8_names: self.utf8_names,
+                };
+                let exemplars = exemplars.as_mut();
+
                 if let Some(hist) = data.downcast_ref::<data::Histogram<i64>>() {
-                    add_histogram_metric(&mut res, hist, description, &scope_labels, name);
+                    add_histogram_metric(&mut res, hist, description, labels, name, exemplars);
                 } else if let Some(hist) = data.downcast_ref::<data::Histogram<u64>>() {
-                    add_histogram_metric(&mut res, hist, description, &scope_labels, name);
+                    add_histogram_metric(&mut res, hist, description, labels, name, exemplars);
                 } else if let Some(hist) = data.downcast_ref::<data::Histogram<f64>>() {
-                    add_histogram_metric(&mut res, hist, description, &scope_labels, name);
+                    add_histogram_metric(&mut res, hist, description, labels, name, exemplars);
                 } else if let Some(sum) = data.downcast_ref::<data::Sum<u64>>() {
-                    add_sum_metric(&mut res, sum, description, &scope_labels, name);
+                    add_sum_metric(&mut res, sum, description, labels, name, exemplars);
                 } else if let Some(sum) = data.downcast_ref::<data::Sum<i64>>() {
-                    add_sum_metric(&mut res, sum, description, &scope_labels, name);
+                    add_sum_metric(&mut res, sum, description, labels, name, exemplars);
                 } else if let Some(sum) = data.downcast_ref::<data::Sum<f64>>() {
-                    add_sum_metric(&mut res, sum, description, &scope_labels, name);
+                    add_sum_metric(&mut res, sum, description, labels, name, exemplars);
                 } else if let Some(g) = data.downcast_ref::<data::Gauge<u64>>() {
-                    add_gauge_metric(&mut res, g, description, &scope_labels, name);
+                    add_gauge_metric(&mut res, g, description, labels, name);
                 } else if let Some(g) = data.downcast_ref::<data::Gauge<i64>>() {
-                    add_gauge_metric(&mut res, g, description, &scope_labels, name);
+                    add_gauge_metric(&mut res, g, description, labels, name);
                 } else if let Some(g) = data.downcast_ref::<data::Gauge<f64>>() {
-                    add_gauge_metric(&mut res, g, description, &scope_labels, name);
+                    add_gauge_metric(&mut res, g, description, labels, name);
                 }
             }
         }
 
+        if let (Some(store), Some(exemplars)) = (&self.exemplars, exemplars) {
+            store.publish(exemplars);
+        }
+
         res
     }
 }
 
+/// Options of the labels of the data points of a metric.
+#[derive(Clone, Copy)]
+struct Labels<'a> {
+    extra: &'a [LabelPair],
+    utf8_names: bool,
+}
+
 /// Maps attributes into Prometheus-style label pairs.
 ///
-/// It sanitizes invalid characters and handles duplicate keys (due to
-/// sanitization) by sorting and concatenating the values following the spec.
-fn get_attrs(kvs: &mut dyn Iterator<Item = (&Key, &Value)>, extra: &[LabelPair]) -> Vec<LabelPair> {
+/// Unless UTF-8 names are enabled, it sanitizes invalid characters and handles
+/// duplicate keys (due to sanitization) by sorting and concatenating the values
+/// following the spec.
+fn get_attrs(
+    kvs: &mut dyn Iterator<Item = (&Key, &Value)>,
+    extra: &[LabelPair],
+    utf8_names: bool,
+) -> Vec<LabelPair> {
     let mut keys_map = BTreeMap::<String, Vec<String>>::new();
     for (key, value) in kvs {
-        let key = utils::sanitize_prom_kv(key.as_str());
+        let key = if utf8_names {
+            key.as_str().to_string()
+        } else {
+            utils::sanitize_prom_kv(key.as_str())
+        };
         keys_map
             .entry(key)
             .and_modify(|v| v.push(value.to_string()))
@@ -450,14 +513,23 @@ fn add_histogram_metric<T: Numeric>(
     res: &mut Vec<MetricFamily>,
     histogram: &data::Histogram<T>,
     description: String,
-    extra: &[LabelPair],
+    labels: Labels<'_>,
     name: Cow<'static, str>,
+    mut exemplars: Option<&mut Exemplars>,
 ) {
-    // Consider supporting exemplars when `prometheus` crate has the feature
+    // The `prometheus` crate does not support exemplars, they are recorded
+    // aside for the `OpenMetricsEncoder`.
     // See: https://github.com/tikv/rust-prometheus/issues/393
 
     for dp in &histogram.data_points {
-        let kvs = get_attrs(&mut dp.attributes.iter(), extra);
+        let kvs = get_attrs(
+            &mut dp.attributes.iter().map(|kv| (&kv.key, &kv.value)),
+            labels.extra,
+            labels.utf8_names,
+        );
+        if let Some(exemplars) = exemplars.as_deref_mut() {
+            exemplars.record_histogram(&name, &kvs, &dp.bounds, &dp.exemplars, labels.utf8_names);
+        }
         let bounds_len = dp.bounds.len();
         let (bucket, _) = dp.bounds.iter().enumerate().fold(
             (Vec::with_capacity(bounds_len), 0),
@@ -493,8 +565,9 @@ fn add_sum_metric<T: Numeric>(
     res: &mut Vec<MetricFamily>,
     sum: &data::Sum<T>,
     description: String,
-    extra: &[LabelPair],
+    labels: Labels<'_>,
     name: Cow<'static, str>,
+    mut exemplars: Option<&mut Exemplars>,
 ) {
     let metric_type = if sum.is_monotonic {
         MetricType::COUNTER
@@ -503,7 +576,16 @@ fn add_sum_metric<T: Numeric>(
     };
 
     for dp in &sum.data_points {
-        let kvs = get_attrs(&mut dp.attributes.iter(), extra);
+        let kvs = get_attrs(
+            &mut dp.attributes.iter().map(|kv| (&kv.key, &kv.value)),
+            labels.extra,
+            labels.utf8_names,
+        );
+        if sum.is_monotonic {
+            if let Some(exemplars) = exemplars.as_deref_mut() {
+                exemplars.record_counter(&name, &kvs, &dp.exemplars, labels.utf8_names);
+            }
+        }
 
         let mut pm = prometheus::proto::Metric::default();
         pm.set_label(protobuf::RepeatedField::from_vec(kvs));
@@ -531,11 +613,15 @@ fn add_gauge_metric<T: Numeric>(
     res: &mut Vec<MetricFamily>,
     gauge: &data::Gauge<T>,
     description: String,
-    extra: &[LabelPair],
+    labels: Labels<'_>,
     name: Cow<'static, str>,
 ) {
     for dp in &gauge.data_points {
-        let kvs = get_attrs(&mut dp.attributes.iter(), extra);
+        let kvs = get_attrs(
+            &mut dp.attributes.iter().map(|kv| (&kv.key, &kv.value)),
+            labels.extra,
+            labels.utf8_names,
+        );
 
         let mut g = prometheus::proto::Gauge::default();
         g.set_value(dp.value.as_f64());
@@ -556,6 +642,7 @@ fn create_info_metric(
     target_info_name: &str,
     target_info_description: &str,
     resource: &Resource,
+    utf8_names: bool,
 ) -> MetricFamily {
     let mut g = prometheus::proto::Gauge::default();
     g.set_value(1.0);
@@ -564,6 +651,7 @@ fn create_info_metric(
     m.set_label(protobuf::RepeatedField::from_vec(get_attrs(
         &mut resource.iter(),
         &[],
+        utf8_names,
     )));
     m.set_gauge(g);
 
@@ -0,0 +1,660 @@
+use opentelemetry::global;
+use opentelemetry_sdk::metrics::data;
+use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    io::Write,
+    sync::{Arc, Mutex},
+    time::UNIX_EPOCH,
+};
+
+use crate::{utils, Numeric, COUNTER_SUFFIX};
+
+/// The content type of the OpenMetrics text format.
+const OPEN_METRICS_FORMAT: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The content type parameter negotiating UTF-8 metric and label names.
+const ALLOW_UTF8_ESCAPING: &str = "escaping=allow-utf-8";
+
+/// The exemplars of the last collection, keyed by series.
+///
+/// Exemplars are not part of the `prometheus` crate data model, they are
+/// recorded by the collector here and looked up by the [OpenMetricsEncoder]
+/// for the series it encodes.
+///
+/// Each collection publishes a new set of exemplars, and each encoding takes a
+/// snapshot of the latest set under the lock, so that concurrent scrapes never
+/// observe a set that is being recorded.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExemplarStore(Arc<Mutex<Arc<Exemplars>>>);
+
+impl ExemplarStore {
+    /// Replaces the exemplars with those of a new collection.
+    pub(crate) fn publish(&self, exemplars: Exemplars) {
+        match self.0.lock() {
+            Ok(mut latest) => *latest = Arc::new(exemplars),
+            Err(err) => global::handle_error(err),
+        }
+    }
+
+    /// The exemplars of the latest collection.
+    pub(crate) fn snapshot(&self) -> Option<Arc<Exemplars>> {
+        match self.0.lock() {
+            Ok(latest) => Some(Arc::clone(&latest)),
+            Err(err) => {
+                global::handle_error(err);
+                None
+            }
+        }
+    }
+}
+
+type SeriesKey = (String, Vec<(String, String)>);
+
+#[derive(Debug, Default)]
+pub(crate) struct Exemplars {
+    /// Exemplars of each series by bucket index, counters only use the first.
+    series: HashMap<SeriesKey, BTreeMap<usize, OpenMetricsExemplar>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct OpenMetricsExemplar {
+    labels: Vec<(String, String)>,
+    value: f64,
+    timestamp: Option<f64>,
+}
+
+impl Exemplars {
+    /// Records the latest exemplar of a counter series.
+    pub(crate) fn record_counter<T: Numeric>(
+        &mut self,
+        name: &str,
+        labels: &[LabelPair],
+        exemplars: &[data::Exemplar<T>],
+        utf8_names: bool,
+    ) {
+        if let Some(exemplar) = exemplars.iter().max_by_key(|e| e.time) {
+            self.series
+                .entry(series_key(name, labels))
+                .or_default()
+                .insert(0, convert_exemplar(exemplar, utf8_names));
+        }
+    }
+
+    /// Records the latest exemplar of each bucket of a histogram series.
+    pub(crate) fn record_histogram<T: Numeric>(
+        &mut self,
+        name: &str,
+        labels: &[LabelPair],
+        bounds: &[f64],
+        exemplars: &[data::Exemplar<T>],
+        utf8_names: bool,
+    ) {
+        if exemplars.is_empty() {
+            return;
+        }
+
+        let buckets = self.series.entry(series_key(name, labels)).or_default();
+        for exemplar in exemplars {
+            let value = exemplar.value.as_f64();
+            let bucket = bounds
+                .iter()
+                .position(|bound| value <= *bound)
+                .unwrap_or(bounds.len());
+            let exemplar = convert_exemplar(exemplar, utf8_names);
+            match buckets.get(&bucket) {
+                Some(existing) if existing.timestamp > exemplar.timestamp => {}
+                _ => {
+                    buckets.insert(bucket, exemplar);
+                }
+            }
+        }
+    }
+
+    fn series(
+        &self,
+        name: &str,
+        labels: &[LabelPair],
+    ) -> Option<&BTreeMap<usize, OpenMetricsExemplar>> {
+        if self.series.is_empty() {
+            return None;
+        }
+        self.series.get(&series_key(name, labels))
+    }
+}
+
+fn series_key(name: &str, labels: &[LabelPair]) -> SeriesKey {
+    let mut labels = labels
+        .iter()
+        .map(|l| (l.get_name().to_string(), l.get_value().to_string()))
+        .collect::<Vec<_>>();
+    labels.sort_unstable();
+    (name.to_string(), labels)
+}
+
+fn convert_exemplar<T: Numeric>(
+    exemplar: &data::Exemplar<T>,
+    utf8_names: bool,
+) -> OpenMetricsExemplar {
+    let mut labels = Vec::with_capacity(2 + exemplar.filtered_attributes.len());
+    if exemplar.trace_id != [0; 16] {
+        labels.push(("trace_id".to_string(), hex(&exemplar.trace_id)));
+        labels.push(("span_id".to_string(), hex(&exemplar.span_id)));
+    }
+    for kv in &exemplar.filtered_attributes {
+        let key = if utf8_names {
+            kv.key.as_str().to_string()
+        } else {
+            utils::sanitize_prom_kv(kv.key.as_str())
+        };
+        labels.push((key, kv.value.to_string()));
+    }
+
+    OpenMetricsExemplar {
+        labels,
+        value: exemplar.value.as_f64(),
+        timestamp: exemplar
+            .time
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs_f64()),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        })
+}
+
+/// An encoder of the [OpenMetrics] text format.
+///
+/// Unlike the encoders of the `prometheus` crate, it writes the exemplars of
+/// counters and histograms when the exporter is built with
+/// [`with_exemplars`](crate::ExporterBuilder::with_exemplars), and it can
+/// write metric and label names that are not valid Prometheus names when the
+/// scraper negotiated UTF-8 names, see [`for_accept_header`].
+///
+/// Names are escaped to valid Prometheus names unless UTF-8 names are
+/// allowed.
+///
+/// [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+/// [`for_accept_header`]: OpenMetricsEncoder::for_accept_header
+#[derive(Clone, Debug)]
+pub struct OpenMetricsEncoder {
+    exemplars: Option<ExemplarStore>,
+    utf8_names: bool,
+}
+
+impl OpenMetricsEncoder {
+    pub(crate) fn new(exemplars: Option<ExemplarStore>) -> Self {
+        OpenMetricsEncoder {
+            exemplars,
+            utf8_names: false,
+        }
+    }
+
+    /// Allows metric and label names that are not valid Prometheus names.
+    ///
+    /// Such names are quoted in the output, only scrapers that negotiated
+    /// UTF-8 names are able to parse them.
+    pub fn with_utf8_names(mut self, utf8_names: bool) -> Self {
+        self.utf8_names = utf8_names;
+        self
+    }
+
+    /// Allows UTF-8 metric and label names if the `Accept` header of the
+    /// scrape request negotiates them with the `escaping=allow-utf-8`
+    /// parameter.
+    pub fn for_accept_header(self, accept: &str) -> Self {
+        let utf8_names = accept
+            .split(',')
+            .filter(|media_range| media_range.contains("openmetrics-text"))
+            .any(|media_range| {
+                media_range
+                    .split(';')
+                    .any(|param| param.trim() == ALLOW_UTF8_ESCAPING)
+            });
+        self.with_utf8_names(utf8_names)
+    }
+
+    fn name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if self.utf8_names || is_valid_metric_name(name) {
+            Cow::Borrowed(name)
+        } else {
+            Cow::Owned(utils::sanitize_name(&Cow::Owned(name.to_string())).into_owned())
+        }
+    }
+
+    fn label_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if self.utf8_names || is_valid_label_name(name) {
+            Cow::Borrowed(name)
+        } else {
+            Cow::Owned(utils::sanitize_prom_kv(name))
+        }
+    }
+
+    fn write_family(
+        &self,
+        mf: &MetricFamily,
+        exemplars: Option<&Exemplars>,
+        w: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        let name = mf.get_name();
+        let (family_name, type_name) = match mf.get_field_type() {
+            MetricType::COUNTER => (name.strip_suffix(COUNTER_SUFFIX).unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+            MetricType::SUMMARY => (name, "summary"),
+            MetricType::UNTYPED => (name, "unknown"),
+        };
+        let family_name = self.name(family_name);
+
+        writeln!(
+            w,
+            "# TYPE {} {type_name}",
+            MetricName(&family_name, self.utf8_names)
+        )?;
+        if !mf.get_help().is_empty() {
+            writeln!(
+                w,
+                "# HELP {} {}",
+                MetricName(&family_name, self.utf8_names),
+                escape(mf.get_help())
+            )?;
+        }
+
+        for m in mf.get_metric() {
+            let series = exemplars.and_then(|e| e.series(name, m.get_label()));
+            match mf.get_field_type() {
+                MetricType::COUNTER => {
+                    let exemplar = series.and_then(|s| s.get(&0));
+                    let sample = format!("{family_name}{COUNTER_SUFFIX}");
+                    self.write_sample(w, &sample, m, None, m.get_counter().get_value(), exemplar)?;
+                }
+                MetricType::GAUGE => {
+                    self.write_sample(w, &family_name, m, None, m.get_gauge().get_value(), None)?;
+                }
+                MetricType::HISTOGRAM => {
+                    let h = m.get_histogram();
+                    let sample = format!("{family_name}_bucket");
+                    let mut inf_seen = false;
+                    for (i, bucket) in h.get_bucket().iter().enumerate() {
+                        let upper_bound = bucket.get_upper_bound();
+                        inf_seen |= upper_bound == f64::INFINITY;
+                        self.write_sample(
+                            w,
+                            &sample,
+                            m,
+                            Some(("le", upper_bound)),
+                            bucket.get_cumulative_count() as f64,
+                            series.and_then(|s| s.get(&i)),
+                        )?;
+                    }
+                    if !inf_seen {
+                        self.write_sample(
+                            w,
+                            &sample,
+                            m,
+                            Some(("le", f64::INFINITY)),
+                            h.get_sample_count() as f64,
+                            series.and_then(|s| s.get(&h.get_bucket().len())),
+                        )?;
+                    }
+                    let count = format!("{family_name}_count");
+                    self.write_sample(w, &count, m, None, h.get_sample_count() as f64, None)?;
+                    let sum = format!("{family_name}_sum");
+                    self.write_sample(w, &sum, m, None, h.get_sample_sum(), None)?;
+                }
+                MetricType::SUMMARY => {
+                    let s = m.get_summary();
+                    for q in s.get_quantile() {
+                        self.write_sample(
+                            w,
+                            &family_name,
+                            m,
+                            Some(("quantile", q.get_quantile())),
+                            q.get_value(),
+                            None,
+                        )?;
+                    }
+                    let count = format!("{family_name}_count");
+                    self.write_sample(w, &count, m, None, s.get_sample_count() as f64, None)?;
+                    let sum = format!("{family_name}_sum");
+                    self.write_sample(w, &sum, m, None, s.get_sample_sum(), None)?;
+                }
+                MetricType::UNTYPED => {
+                    self.write_sample(w, &family_name, m, None, m.get_untyped().get_value(), None)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_sample(
+        &self,
+        w: &mut dyn Write,
+        name: &str,
+        m: &Metric,
+        extra_label: Option<(&str, f64)>,
+        value: f64,
+        exemplar: Option<&OpenMetricsExemplar>,
+    ) -> std::io::Result<()> {
+        let quoted = self.utf8_names && !is_valid_metric_name(name);
+        let mut labels = m
+            .get_label()
+            .iter()
+            .map(|l| (self.label_name(l.get_name()), Cow::Borrowed(l.get_value())))
+            .collect::<Vec<_>>();
+        if let Some((label, value)) = extra_label {
+            labels.push((Cow::Borrowed(label), Cow::Owned(float(value))));
+        }
+
+        if quoted {
+            write!(w, "{{{}", MetricName(name, true))?;
+            if !labels.is_empty() {
+                w.write_all(b",")?;
+            }
+            self.write_labels(w, labels.iter().map(|(k, v)| (&**k, &**v)))?;
+            w.write_all(b"}")?;
+        } else {
+            w.write_all(name.as_bytes())?;
+            if !labels.is_empty() {
+                w.write_all(b"{")?;
+                self.write_labels(w, labels.iter().map(|(k, v)| (&**k, &**v)))?;
+                w.write_all(b"}")?;
+            }
+        }
+        write!(w, " {}", float(value))?;
+        if m.get_timestamp_ms() != 0 {
+            write!(w, " {}", float(m.get_timestamp_ms() as f64 / 1000.0))?;
+        }
+
+        if let Some(exemplar) = exemplar {
+            w.write_all(b" # {")?;
+            let labels = exemplar
+                .labels
+                .iter()
+                .map(|(k, v)| (self.label_name(k), v.as_str()))
+                .collect::<Vec<_>>();
+            self.write_labels(w, labels.iter().map(|(k, v)| (&**k, *v)))?;
+            write!(w, "}} {}", float(exemplar.value))?;
+            if let Some(timestamp) = exemplar.timestamp {
+                write!(w, " {}", float(timestamp))?;
+            }
+        }
+
+        w.write_all(b"\n")
+    }
+
+    fn write_labels<'a>(
+        &self,
+        w: &mut dyn Write,
+        labels: impl Iterator<Item = (&'a str, &'a str)>,
+    ) -> std::io::Result<()> {
+        for (i, (name, value)) in labels.enumerate() {
+            if i > 0 {
+                w.write_all(b",")?;
+            }
+            if is_valid_label_name(name) {
+                w.write_all(name.as_bytes())?;
+            } else {
+                write!(w, "\"{}\"", escape(name))?;
+            }
+            write!(w, "=\"{}\"", escape(value))?;
+        }
+        Ok(())
+    }
+}
+
+impl prometheus::Encoder for OpenMetricsEncoder {
+    fn encode<W: Write>(&self, mfs: &[MetricFamily], writer: &mut W) -> prometheus::Result<()> {
+        let exemplars = self.exemplars.as_ref().and_then(ExemplarStore::snapshot);
+        for mf in mfs {
+            if mf.get_metric().is_empty() {
+                continue;
+            }
+            self.write_family(mf, exemplars.as_deref(), writer)?;
+        }
+        writer.write_all(b"# EOF\n")?;
+        Ok(())
+    }
+
+    fn format_type(&self) -> &str {
+        if self.utf8_names {
+            "application/openmetrics-text; version=1.0.0; charset=utf-8; escaping=allow-utf-8"
+        } else {
+            OPEN_METRICS_FORMAT
+        }
+    }
+}
+
+/// A metric name, quoted if it is not a valid Prometheus name.
+struct MetricName<'a>(&'a str, bool);
+
+impl std::fmt::Display for MetricName<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.1 && !is_valid_metric_name(self.0) {
+            write!(f, "\"{}\"", escape(self.0))
+        } else {
+            f.write_str(self.0)
+        }
+    }
+}
+
+fn is_valid_metric_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.char_indices().all(|(i, c)| {
+            c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit())
+        })
+}
+
+fn is_valid_label_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .char_indices()
+            .all(|(i, c)| c.is_ascii_alphabetic() || c == '_' || (i > 0 && c.is_ascii_digit()))
+}
+
+fn escape(s: &str) -> Cow<'_, str> {
+    if !s.contains(['\\', '\n', '"']) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut escaped = String::with_capacity(s.len() + 2);
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '"' => escaped.push_str("\\\""),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+fn float(v: f64) -> String {
+    if v == f64::INFINITY {
+        "+Inf".to_string()
+    } else if v == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if v.is_nan() {
+        "NaN".to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::KeyValue;
+    use prometheus::Encoder;
+    use std::time::{Duration, SystemTime};
+
+    fn label(name: &str, value: &str) -> LabelPair {
+        let mut lp = LabelPair::new();
+        lp.set_name(name.into());
+        lp.set_value(value.into());
+        lp
+    }
+
+    fn counter(name: &str, labels: Vec<LabelPair>, value: f64) -> MetricFamily {
+        let mut c = prometheus::proto::Counter::default();
+        c.set_value(value);
+        let mut m = Metric::default();
+        m.set_label(protobuf::RepeatedField::from_vec(labels));
+        m.set_counter(c);
+
+        let mut mf = MetricFamily::default();
+        mf.set_name(name.into());
+        mf.set_help("a counter".into());
+        mf.set_field_type(MetricType::COUNTER);
+        mf.set_metric(protobuf::RepeatedField::from_vec(vec![m]));
+        mf
+    }
+
+    fn exemplar(value: f64, secs: u64) -> data::Exemplar<f64> {
+        data::Exemplar {
+            filtered_attributes: vec![KeyValue::new("user.id", "42")],
+            time: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            value,
+            span_id: [1; 8],
+            trace_id: [2; 16],
+        }
+    }
+
+    fn encode(encoder: &OpenMetricsEncoder, mfs: &[MetricFamily]) -> String {
+        let mut output = Vec::new();
+        encoder.encode(mfs, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn encode_counter_with_exemplar() {
+        let store = ExemplarStore::default();
+        let labels = vec![label("A", "B")];
+        let mut exemplars = Exemplars::default();
+        exemplars.record_counter(
+            "foo_total",
+            &labels,
+            &[exemplar(3.0, 10), exemplar(5.0, 20)],
+            false,
+        );
+        store.publish(exemplars);
+
+        let output = encode(
+            &OpenMetricsEncoder::new(Some(store)),
+            &[counter("foo_total", labels, 8.0)],
+        );
+
+        assert_eq!(
+            output,
+            "# TYPE foo counter\n\
+             # HELP foo a counter\n\
+             foo_total{A=\"B\"} 8 # {trace_id=\"02020202020202020202020202020202\",span_id=\"0101010101010101\",user_id=\"42\"} 5 20\n\
+             # EOF\n"
+        );
+    }
+
+    #[test]
+    fn encode_histogram_with_exemplars() {
+        let store = ExemplarStore::default();
+        let bounds = [1.0, 5.0];
+        let mut exemplars = Exemplars::default();
+        exemplars.record_histogram(
+            "bar",
+            &[],
+            &bounds,
+            &[exemplar(0.5, 1), exemplar(7.0, 2)],
+            false,
+        );
+        store.publish(exemplars);
+
+        let buckets = bounds
+            .iter()
+            .zip([1, 1])
+            .map(|(bound, count)| {
+                let mut b = prometheus::proto::Bucket::default();
+                b.set_upper_bound(*bound);
+                b.set_cumulative_count(count);
+                b
+            })
+            .collect();
+        let mut h = prometheus::proto::Histogram::default();
+        h.set_sample_sum(7.5);
+        h.set_sample_count(2);
+        h.set_bucket(protobuf::RepeatedField::from_vec(buckets));
+        let mut m = Metric::default();
+        m.set_histogram(h);
+        let mut mf = MetricFamily::default();
+        mf.set_name("bar".into());
+        mf.set_field_type(MetricType::HISTOGRAM);
+        mf.set_metric(protobuf::RepeatedField::from_vec(vec![m]));
+
+        let output = encode(&OpenMetricsEncoder::new(Some(store)), &[mf]);
+
+        assert_eq!(
+            output,
+            "# TYPE bar histogram\n\
+             bar_bucket{le=\"1\"} 1 # {trace_id=\"02020202020202020202020202020202\",span_id=\"0101010101010101\",user_id=\"42\"} 0.5 1\n\
+             bar_bucket{le=\"5\"} 1\n\
+             bar_bucket{le=\"+Inf\"} 2 # {trace_id=\"02020202020202020202020202020202\",span_id=\"0101010101010101\",user_id=\"42\"} 7 2\n\
+             bar_count 2\n\
+             bar_sum 7.5\n\
+             # EOF\n"
+        );
+    }
+
+    #[test]
+    fn encode_utf8_names() {
+        let mfs = [counter(
+            "http.requests_total",
+            vec![label("http.method", "GET")],
+            1.0,
+        )];
+
+        let escaped = encode(&OpenMetricsEncoder::new(None), &mfs);
+        assert_eq!(
+            escaped,
+            "# TYPE http_requests counter\n\
+             # HELP http_requests a counter\n\
+             http_requests_total{http_method=\"GET\"} 1\n\
+             # EOF\n"
+        );
+
+        let encoder = OpenMetricsEncoder::new(None).for_accept_header(
+            "application/openmetrics-text;version=1.0.0;escaping=allow-utf-8,text/plain;q=0.5",
+        );
+        assert_eq!(
+            encoder.format_type(),
+            "application/openmetrics-text; version=1.0.0; charset=utf-8; escaping=allow-utf-8"
+        );
+        assert_eq!(
+            encode(&encoder, &mfs),
+            "# TYPE \"http.requests\" counter\n\
+             # HELP \"http.requests\" a counter\n\
+             {\"http.requests_total\",\"http.method\"=\"GET\"} 1\n\
+             # EOF\n"
+        );
+
+        let encoder = OpenMetricsEncoder::new(None).for_accept_header("text/plain;version=0.0.4");
+        assert_eq!(encoder.format_type(), OPEN_METRICS_FORMAT);
+    }
+
+    #[test]
+    fn escape_label_values() {
+        let mfs = [counter("foo", vec![label("a", "x\"y\\z\nw")], 1.0)];
+        let output = encode(&OpenMetricsEncoder::new(None), &mfs);
+        assert!(
+            output.contains("foo_total{a=\"x\\\"y\\\\z\\nw\"} 1\n"),
+            "{output}"
+        );
+    }
+}
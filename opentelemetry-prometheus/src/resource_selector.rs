@@ -31,13 +31,15 @@ impl From<HashSet<Key>> for ResourceSelector {
 }
 
 impl ResourceSelector {
-    pub(crate) fn select(&self, resource: &Resource) -> Vec<LabelPair> {
+    pub(crate) fn select(&self, resource: &Resource, utf8_names: bool) -> Vec<LabelPair> {
         match self {
-            ResourceSelector::All => get_attrs(&mut resource.iter(), &[]),
+            ResourceSelector::All => get_attrs(&mut resource.iter(), &[], utf8_names),
             ResourceSelector::None => Vec::new(),
-            ResourceSelector::KeyAllowList(keys) => {
-                get_attrs(&mut resource.iter().filter(|(k, _)| keys.contains(k)), &[])
-            }
+            ResourceSelector::KeyAllowList(keys) => get_attrs(
+                &mut resource.iter().filter(|(k, _)| keys.contains(k)),
+                &[],
+                utf8_names,
+            ),
         }
     }
 }
@@ -0,0 +1,33 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use opentelemetry::logs::AnyValue as LogsAnyValue;
+use opentelemetry_proto::tonic::common::v1::AnyValue;
+use prost::Message;
+
+fn has_nan(value: &LogsAnyValue) -> bool {
+    match value {
+        LogsAnyValue::Double(d) => d.is_nan(),
+        LogsAnyValue::ListAny(values) => values.iter().any(has_nan),
+        LogsAnyValue::Map(map) => map.values().any(has_nan),
+        _ => false,
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(proto) = AnyValue::decode(data) else {
+        return;
+    };
+    // empty values have no SDK representation
+    let Ok(value) = LogsAnyValue::try_from(proto) else {
+        return;
+    };
+    if has_nan(&value) {
+        return;
+    }
+
+    let encoded = AnyValue::from(value.clone()).encode_to_vec();
+    let decoded = AnyValue::decode(encoded.as_slice()).expect("encoded value must decode");
+    let roundtrip = LogsAnyValue::try_from(decoded).expect("SDK values are never empty");
+    assert_eq!(roundtrip, value);
+});
@@ -22,6 +22,8 @@ pub mod tonic {
     use opentelemetry::{Array, Value};
     use std::borrow::Cow;
 
+    /// The attributes and schema URL of a [`Resource`], as exported in the
+    /// `resource` and `schema_url` fields of `ResourceSpans` and `ResourceLogs`.
     #[cfg(any(feature = "trace", feature = "logs"))]
     #[derive(Debug, Default)]
     pub struct ResourceAttributesWithSchema {
@@ -147,6 +149,9 @@ pub mod tonic {
                     Value::I64(val) => Some(any_value::Value::IntValue(val)),
                     Value::F64(val) => Some(any_value::Value::DoubleValue(val)),
                     Value::String(val) => Some(any_value::Value::StringValue(val.to_string())),
+                    Value::Duration(val) => Some(any_value::Value::IntValue(
+                        i64::try_from(val.as_nanos()).unwrap_or(i64::MAX),
+                    )),
                     Value::Array(array) => Some(any_value::Value::ArrayValue(match array {
                         Array::Bool(vals) => array_into_proto(vals),
                         Array::I64(vals) => array_into_proto(vals),
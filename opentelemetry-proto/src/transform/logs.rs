@@ -13,6 +13,7 @@ pub mod tonic {
         transform::common::{to_nanos, tonic::ResourceAttributesWithSchema},
     };
     use opentelemetry::logs::{AnyValue as LogsAnyValue, Severity};
+    use opentelemetry::Key;
     use std::borrow::Cow;
     use std::collections::HashMap;
 
@@ -55,9 +56,64 @@ pub mod tonic {
         }
     }
 
+    /// Converts an OTLP value back into the SDK representation.
+    ///
+    /// Every variant, including arbitrarily nested lists and maps, maps back to
+    /// the value it was created from. Fails if the value or any nested value is
+    /// empty, since empty values have no SDK representation.
+    impl TryFrom<AnyValue> for LogsAnyValue {
+        type Error = ();
+
+        fn try_from(value: AnyValue) -> Result<Self, Self::Error> {
+            value.value.ok_or(())?.try_into()
+        }
+    }
+
+    impl TryFrom<Value> for LogsAnyValue {
+        type Error = ();
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(match value {
+                Value::DoubleValue(f) => LogsAnyValue::Double(f),
+                Value::IntValue(i) => LogsAnyValue::Int(i),
+                Value::StringValue(s) => LogsAnyValue::String(s.into()),
+                Value::BoolValue(b) => LogsAnyValue::Boolean(b),
+                Value::ArrayValue(array) => LogsAnyValue::ListAny(
+                    array
+                        .values
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                ),
+                Value::KvlistValue(list) => LogsAnyValue::Map(
+                    list.values
+                        .into_iter()
+                        .map(|kv| Ok((kv.key.into(), kv.value.ok_or(())?.try_into()?)))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Value::BytesValue(v) => LogsAnyValue::Bytes(v),
+            })
+        }
+    }
+
     impl From<opentelemetry_sdk::logs::LogRecord> for LogRecord {
         fn from(log_record: opentelemetry_sdk::logs::LogRecord) -> Self {
-            let trace_context = log_record.trace_context.as_ref();
+            let trace_context = log_record.trace_context;
+            // OTLP has no fields for the numeric event id and version
+            let event_attributes = [
+                log_record.event_id.map(|id| {
+                    (
+                        Key::from_static_str("event.id"),
+                        LogsAnyValue::Int(id.into()),
+                    )
+                }),
+                log_record.event_version.map(|version| {
+                    (
+                        Key::from_static_str("event.version"),
+                        LogsAnyValue::Int(version.into()),
+                    )
+                }),
+            ];
             let severity_number = match log_record.severity_number {
                 Some(Severity::Trace) => SeverityNumber::Trace,
                 Some(Severity::Trace2) => SeverityNumber::Trace2,
@@ -92,12 +148,15 @@ pub mod tonic {
                 severity_number: severity_number.into(),
                 severity_text: log_record.severity_text.map(Into::into).unwrap_or_default(),
                 body: log_record.body.map(Into::into),
-                attributes: log_record
-                    .attributes
-                    .map(Attributes::from_iter)
-                    .unwrap_or_default()
-                    .0,
-                dropped_attributes_count: 0,
+                attributes: Attributes::from_iter(
+                    log_record
+                        .attributes
+                        .into_iter()
+                        .flatten()
+                        .chain(event_attributes.into_iter().flatten()),
+                )
+                .0,
+                dropped_attributes_count: log_record.dropped_attributes_count,
                 flags: trace_context
                     .map(|ctx| {
                         ctx.trace_flags
@@ -153,6 +212,45 @@ pub mod tonic {
         logs: Vec<opentelemetry_sdk::export::logs::LogData>,
         resource: &ResourceAttributesWithSchema,
     ) -> Vec<ResourceLogs> {
+        // Group logs by the resource they override the exporter's resource
+        // with, logs without one are grouped under the exporter's resource
+        let mut resource_groups: Vec<(
+            Option<opentelemetry_sdk::Resource>,
+            Vec<opentelemetry_sdk::export::logs::LogData>,
+        )> = vec![(None, Vec::new())];
+        for log in logs {
+            match resource_groups
+                .iter_mut()
+                .find(|(group_resource, _)| *group_resource == log.resource)
+            {
+                Some((_, group)) => group.push(log),
+                None => resource_groups.push((log.resource.clone(), vec![log])),
+            }
+        }
+        if resource_groups.len() > 1 && resource_groups[0].1.is_empty() {
+            resource_groups.remove(0);
+        }
+
+        resource_groups
+            .into_iter()
+            .map(|(log_resource, logs)| {
+                let log_resource = log_resource
+                    .as_ref()
+                    .map(ResourceAttributesWithSchema::from);
+                let resource = log_resource.as_ref().unwrap_or(resource);
+                ResourceLogs {
+                    resource: Some(Resource {
+                        attributes: resource.attributes.0.clone(),
+                        dropped_attributes_count: 0,
+                    }),
+                    scope_logs: group_logs_by_scope(&logs),
+                    schema_url: resource.schema_url.clone().unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    fn group_logs_by_scope(logs: &[opentelemetry_sdk::export::logs::LogData]) -> Vec<ScopeLogs> {
         // Group logs by target or instrumentation name
         let scope_map = logs.iter().fold(
             HashMap::new(),
@@ -171,29 +269,24 @@ pub mod tonic {
             },
         );
 
-        let scope_logs = scope_map
+        scope_map
             .into_iter()
-            .map(|(key, log_data)| ScopeLogs {
-                scope: Some(InstrumentationScope::from((
-                    &log_data.first().unwrap().instrumentation,
-                    Some(key),
-                ))),
-                schema_url: resource.schema_url.clone().unwrap_or_default(),
-                log_records: log_data
-                    .into_iter()
-                    .map(|log_data| log_data.record.clone().into())
-                    .collect(),
+            .map(|(key, log_data)| {
+                let instrumentation = &log_data.first().unwrap().instrumentation;
+                ScopeLogs {
+                    scope: Some(InstrumentationScope::from((instrumentation, Some(key)))),
+                    schema_url: instrumentation
+                        .schema_url
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    log_records: log_data
+                        .into_iter()
+                        .map(|log_data| log_data.record.clone().into())
+                        .collect(),
+                }
             })
-            .collect();
-
-        vec![ResourceLogs {
-            resource: Some(Resource {
-                attributes: resource.attributes.0.clone(),
-                dropped_attributes_count: 0,
-            }),
-            scope_logs,
-            schema_url: resource.schema_url.clone().unwrap_or_default(),
-        }]
+            .collect()
     }
 }
 
@@ -215,6 +308,7 @@ mod tests {
             )
             .build(),
             record: logrecord,
+            resource: None,
         }
     }
 
@@ -267,4 +361,193 @@ mod tests {
         assert_eq!(scope_logs_1.log_records.len(), 1);
         assert_eq!(scope_logs_2.log_records.len(), 1);
     }
+
+    #[test]
+    fn test_group_logs_by_resource_and_scope_keeps_schema_urls() {
+        use crate::tonic::collector::logs::v1::ExportLogsServiceRequest;
+        use prost::Message;
+
+        let resource = Resource::from_schema_url(
+            vec![opentelemetry::KeyValue::new("service.name", "test")],
+            "https://opentelemetry.io/schemas/resource",
+        );
+        let mut log = create_test_log_data("lib1", "Log 1");
+        log.instrumentation = opentelemetry_sdk::InstrumentationLibrary::builder("lib1")
+            .with_schema_url("https://opentelemetry.io/schemas/1.25.0")
+            .build();
+        let resource: ResourceAttributesWithSchema = (&resource).into();
+
+        let request = ExportLogsServiceRequest {
+            resource_logs: crate::transform::logs::tonic::group_logs_by_resource_and_scope(
+                vec![log],
+                &resource,
+            ),
+        };
+        let decoded = ExportLogsServiceRequest::decode(request.encode_to_vec().as_slice())
+            .expect("request should decode");
+
+        let resource_logs = &decoded.resource_logs[0];
+        assert_eq!(
+            resource_logs.schema_url,
+            "https://opentelemetry.io/schemas/resource"
+        );
+        assert_eq!(
+            resource_logs.scope_logs[0].schema_url,
+            "https://opentelemetry.io/schemas/1.25.0"
+        );
+    }
+
+    #[test]
+    fn test_group_logs_by_resource_and_scope_multiple_resources() {
+        let resource = Resource::new(vec![opentelemetry::KeyValue::new("service.name", "proxy")]);
+        let upstream = Resource::new(vec![opentelemetry::KeyValue::new(
+            "service.name",
+            "upstream",
+        )]);
+        let mut upstream_log1 = create_test_log_data("lib1", "Log 1");
+        upstream_log1.resource = Some(upstream.clone());
+        let mut upstream_log2 = create_test_log_data("lib1", "Log 2");
+        upstream_log2.resource = Some(upstream);
+        let logs = vec![
+            upstream_log1,
+            create_test_log_data("lib1", "Log 3"),
+            upstream_log2,
+        ];
+        let resource: ResourceAttributesWithSchema = (&resource).into();
+
+        let grouped_logs =
+            crate::transform::logs::tonic::group_logs_by_resource_and_scope(logs, &resource);
+
+        assert_eq!(grouped_logs.len(), 2);
+        let service_name = |resource_logs: &crate::tonic::logs::v1::ResourceLogs| {
+            resource_logs.resource.as_ref().unwrap().attributes[0]
+                .value
+                .clone()
+                .unwrap()
+                .value
+                .unwrap()
+        };
+        assert_eq!(
+            service_name(&grouped_logs[0]),
+            crate::tonic::common::v1::any_value::Value::StringValue("proxy".to_string())
+        );
+        assert_eq!(grouped_logs[0].scope_logs[0].log_records.len(), 1);
+        assert_eq!(
+            service_name(&grouped_logs[1]),
+            crate::tonic::common::v1::any_value::Value::StringValue("upstream".to_string())
+        );
+        assert_eq!(grouped_logs[1].scope_logs[0].log_records.len(), 2);
+    }
+
+    #[test]
+    fn test_event_id_and_version_are_attributes() {
+        use crate::tonic::common::v1::any_value::Value;
+
+        let mut log_data = create_test_log_data("test-lib", "Log 1");
+        log_data.record.add_attribute("key", "value");
+        log_data.record.set_event_id(4624);
+        log_data.record.set_event_version(2);
+
+        let otlp: crate::tonic::logs::v1::LogRecord = log_data.record.into();
+        let attributes: Vec<_> = otlp
+            .attributes
+            .into_iter()
+            .map(|kv| (kv.key, kv.value.and_then(|value| value.value)))
+            .collect();
+        assert_eq!(
+            attributes,
+            [
+                ("key".into(), Some(Value::StringValue("value".into()))),
+                ("event.id".into(), Some(Value::IntValue(4624))),
+                ("event.version".into(), Some(Value::IntValue(2))),
+            ]
+        );
+    }
+
+    mod any_value {
+        use crate::tonic::common::v1::AnyValue;
+        use opentelemetry::logs::AnyValue as LogsAnyValue;
+        use opentelemetry::Key;
+        use prost::Message;
+        use std::collections::HashMap;
+
+        /// Every scalar variant, including edge values.
+        fn scalars() -> Vec<LogsAnyValue> {
+            vec![
+                LogsAnyValue::Int(0),
+                LogsAnyValue::Int(i64::MIN),
+                LogsAnyValue::Int(i64::MAX),
+                LogsAnyValue::Double(0.0),
+                LogsAnyValue::Double(-1.5e300),
+                LogsAnyValue::Double(f64::INFINITY),
+                LogsAnyValue::String("".into()),
+                LogsAnyValue::String("unicode \u{1F600} \"quoted\"".into()),
+                LogsAnyValue::Boolean(true),
+                LogsAnyValue::Boolean(false),
+                LogsAnyValue::Bytes(vec![]),
+                LogsAnyValue::Bytes(vec![0, 1, 0xfe, 0xff]),
+            ]
+        }
+
+        /// All values nested up to `depth` levels of lists and maps.
+        fn values(depth: usize) -> Vec<LogsAnyValue> {
+            if depth == 0 {
+                return scalars();
+            }
+
+            let children = values(depth - 1);
+            let mut values = scalars();
+            values.push(LogsAnyValue::ListAny(vec![]));
+            values.push(LogsAnyValue::Map(HashMap::new()));
+            for (i, child) in children.iter().enumerate() {
+                values.push(LogsAnyValue::ListAny(vec![child.clone(), child.clone()]));
+                values.push(LogsAnyValue::Map(HashMap::from([
+                    (Key::new(format!("key{}", i)), child.clone()),
+                    (Key::from_static_str("scalar"), LogsAnyValue::Int(i as i64)),
+                ])));
+            }
+            values.push(LogsAnyValue::ListAny(children.clone()));
+            values.push(LogsAnyValue::Map(
+                children
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, child)| (Key::new(format!("key{}", i)), child))
+                    .collect(),
+            ));
+
+            values
+        }
+
+        #[test]
+        fn round_trips_all_variants_through_otlp() {
+            for value in values(2) {
+                let otlp = AnyValue::from(value.clone());
+                assert_eq!(LogsAnyValue::try_from(otlp.clone()), Ok(value.clone()));
+
+                let decoded =
+                    AnyValue::decode(otlp.encode_to_vec().as_slice()).expect("value should decode");
+                assert_eq!(LogsAnyValue::try_from(decoded), Ok(value));
+            }
+        }
+
+        #[test]
+        fn round_trips_deeply_nested_values() {
+            let mut value = LogsAnyValue::Bytes(vec![42]);
+            for depth in 0..32 {
+                value = if depth % 2 == 0 {
+                    LogsAnyValue::ListAny(vec![value, LogsAnyValue::Int(depth)])
+                } else {
+                    LogsAnyValue::Map(HashMap::from([(Key::from_static_str("nested"), value)]))
+                };
+            }
+
+            let otlp = AnyValue::from(value.clone());
+            assert_eq!(LogsAnyValue::try_from(otlp), Ok(value));
+        }
+
+        #[test]
+        fn empty_values_are_rejected() {
+            assert_eq!(LogsAnyValue::try_from(AnyValue { value: None }), Err(()));
+        }
+    }
 }
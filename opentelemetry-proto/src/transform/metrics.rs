@@ -336,10 +336,85 @@ pub mod tonic {
                     .map(|kv| (&kv.key, &kv.value).into())
                     .collect(),
                 time_unix_nano: to_nanos(ex.time),
-                span_id: ex.span_id.into(),
-                trace_id: ex.trace_id.into(),
+                // Exemplars recorded outside of a sampled span have no IDs,
+                // which OTLP represents as empty bytes.
+                span_id: if ex.span_id == [0; 8] {
+                    vec![]
+                } else {
+                    ex.span_id.into()
+                },
+                trace_id: if ex.trace_id == [0; 16] {
+                    vec![]
+                } else {
+                    ex.trace_id.into()
+                },
                 value: Some(ex.value.into()),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use crate::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+    use crate::tonic::metrics::v1::{exemplar, Exemplar as TonicExemplar};
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::metrics::data::{Exemplar, ResourceMetrics, ScopeMetrics};
+    use opentelemetry_sdk::{InstrumentationLibrary, Resource};
+    use prost::Message;
+
+    #[test]
+    fn test_exemplar_ids_are_empty_without_span() {
+        let mut exemplar = Exemplar {
+            filtered_attributes: vec![KeyValue::new("user", "alice")],
+            time: SystemTime::now(),
+            value: 3u64,
+            span_id: [0; 8],
+            trace_id: [0; 16],
+        };
+
+        let tonic_exemplar = TonicExemplar::from(&exemplar);
+        assert!(tonic_exemplar.span_id.is_empty());
+        assert!(tonic_exemplar.trace_id.is_empty());
+        assert_eq!(tonic_exemplar.filtered_attributes.len(), 1);
+        assert_eq!(tonic_exemplar.value, Some(exemplar::Value::AsInt(3)));
+
+        exemplar.span_id = [1; 8];
+        exemplar.trace_id = [2; 16];
+        let tonic_exemplar = TonicExemplar::from(&exemplar);
+        assert_eq!(tonic_exemplar.span_id, vec![1; 8]);
+        assert_eq!(tonic_exemplar.trace_id, vec![2; 16]);
+    }
+
+    #[test]
+    fn test_resource_metrics_keeps_schema_urls() {
+        let resource_metrics = ResourceMetrics {
+            resource: Resource::from_schema_url(
+                vec![KeyValue::new("service.name", "test")],
+                "https://opentelemetry.io/schemas/resource",
+            ),
+            scope_metrics: vec![ScopeMetrics {
+                scope: InstrumentationLibrary::builder("lib1")
+                    .with_schema_url("https://opentelemetry.io/schemas/1.25.0")
+                    .build(),
+                metrics: vec![],
+            }],
+        };
+
+        let request = ExportMetricsServiceRequest::from(&resource_metrics);
+        let decoded = ExportMetricsServiceRequest::decode(request.encode_to_vec().as_slice())
+            .expect("request should decode");
+
+        let tonic_resource_metrics = &decoded.resource_metrics[0];
+        assert_eq!(
+            tonic_resource_metrics.schema_url,
+            "https://opentelemetry.io/schemas/resource"
+        );
+        assert_eq!(
+            tonic_resource_metrics.scope_metrics[0].schema_url,
+            "https://opentelemetry.io/schemas/1.25.0"
+        );
+    }
+}
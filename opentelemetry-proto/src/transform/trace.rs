@@ -155,6 +155,43 @@ pub mod tonic {
         spans: Vec<SpanData>,
         resource: &ResourceAttributesWithSchema,
     ) -> Vec<ResourceSpans> {
+        // Group spans by the resource they override the exporter's resource
+        // with, spans without one are grouped under the exporter's resource
+        let mut resource_groups: Vec<(Option<opentelemetry_sdk::Resource>, Vec<SpanData>)> =
+            vec![(None, Vec::new())];
+        for span in spans {
+            match resource_groups
+                .iter_mut()
+                .find(|(group_resource, _)| *group_resource == span.resource)
+            {
+                Some((_, group)) => group.push(span),
+                None => resource_groups.push((span.resource.clone(), vec![span])),
+            }
+        }
+        if resource_groups.len() > 1 && resource_groups[0].1.is_empty() {
+            resource_groups.remove(0);
+        }
+
+        resource_groups
+            .into_iter()
+            .map(|(span_resource, spans)| {
+                let span_resource = span_resource
+                    .as_ref()
+                    .map(ResourceAttributesWithSchema::from);
+                let resource = span_resource.as_ref().unwrap_or(resource);
+                ResourceSpans {
+                    resource: Some(Resource {
+                        attributes: resource.attributes.0.clone(),
+                        dropped_attributes_count: 0,
+                    }),
+                    scope_spans: group_spans_by_scope(&spans),
+                    schema_url: resource.schema_url.clone().unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    fn group_spans_by_scope(spans: &[SpanData]) -> Vec<ScopeSpans> {
         // Group spans by their instrumentation library
         let scope_map = spans.iter().fold(
             HashMap::new(),
@@ -167,27 +204,21 @@ pub mod tonic {
         );
 
         // Convert the grouped spans into ScopeSpans
-        let scope_spans = scope_map
+        scope_map
             .into_iter()
             .map(|(instrumentation, span_records)| ScopeSpans {
                 scope: Some((instrumentation, None).into()),
-                schema_url: resource.schema_url.clone().unwrap_or_default(),
+                schema_url: instrumentation
+                    .schema_url
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
                 spans: span_records
                     .into_iter()
                     .map(|span_data| span_data.clone().into())
                     .collect(),
             })
-            .collect();
-
-        // Wrap ScopeSpans into a single ResourceSpans
-        vec![ResourceSpans {
-            resource: Some(Resource {
-                attributes: resource.attributes.0.clone(),
-                dropped_attributes_count: 0,
-            }),
-            scope_spans,
-            schema_url: resource.schema_url.clone().unwrap_or_default(),
-        }]
+            .collect()
     }
 }
 
@@ -228,6 +259,7 @@ mod tests {
             links: SpanLinks::default(),
             status: Status::Unset,
             instrumentation_lib: InstrumentationLibrary::builder(instrumentation_name).build(),
+            resource: None,
         }
     }
 
@@ -347,4 +379,107 @@ mod tests {
             span_data3.span_context.trace_id().to_bytes().to_vec()
         );
     }
+
+    #[test]
+    fn test_group_spans_by_resource_and_scope_keeps_schema_urls() {
+        use crate::tonic::collector::trace::v1::ExportTraceServiceRequest;
+        use prost::Message;
+
+        let resource = Resource::from_schema_url(
+            vec![KeyValue::new("resource_key", "resource_value")],
+            "https://opentelemetry.io/schemas/resource",
+        );
+        let mut span_data = create_test_span_data("lib1");
+        span_data.instrumentation_lib = InstrumentationLibrary::builder("lib1")
+            .with_schema_url("https://opentelemetry.io/schemas/1.25.0")
+            .build();
+        let resource: ResourceAttributesWithSchema = (&resource).into();
+
+        let request = ExportTraceServiceRequest {
+            resource_spans: crate::transform::trace::tonic::group_spans_by_resource_and_scope(
+                vec![span_data],
+                &resource,
+            ),
+        };
+        let decoded = ExportTraceServiceRequest::decode(request.encode_to_vec().as_slice())
+            .expect("request should decode");
+
+        let resource_spans = &decoded.resource_spans[0];
+        assert_eq!(
+            resource_spans.schema_url,
+            "https://opentelemetry.io/schemas/resource"
+        );
+        assert_eq!(
+            resource_spans.scope_spans[0].schema_url,
+            "https://opentelemetry.io/schemas/1.25.0"
+        );
+    }
+
+    #[test]
+    fn test_group_spans_by_resource_and_scope_multiple_resources() {
+        let resource = Resource::new(vec![KeyValue::new("service.name", "proxy")]);
+        let upstream = Resource::from_schema_url(
+            vec![KeyValue::new("service.name", "upstream")],
+            "https://opentelemetry.io/schemas/upstream",
+        );
+        let mut upstream_span1 = create_test_span_data("lib1");
+        upstream_span1.resource = Some(upstream.clone());
+        let mut upstream_span2 = create_test_span_data("lib2");
+        upstream_span2.resource = Some(upstream);
+        let spans = vec![
+            upstream_span1,
+            create_test_span_data("lib1"),
+            upstream_span2,
+        ];
+        let resource: ResourceAttributesWithSchema = (&resource).into();
+
+        let grouped_spans =
+            crate::transform::trace::tonic::group_spans_by_resource_and_scope(spans, &resource);
+
+        assert_eq!(grouped_spans.len(), 2);
+        let service_name = |resource_spans: &crate::tonic::trace::v1::ResourceSpans| {
+            resource_spans.resource.as_ref().unwrap().attributes[0]
+                .value
+                .clone()
+                .unwrap()
+                .value
+                .unwrap()
+        };
+
+        assert_eq!(
+            service_name(&grouped_spans[0]),
+            Value::StringValue("proxy".to_string())
+        );
+        assert_eq!(grouped_spans[0].schema_url, "");
+        assert_eq!(grouped_spans[0].scope_spans.len(), 1);
+
+        assert_eq!(
+            service_name(&grouped_spans[1]),
+            Value::StringValue("upstream".to_string())
+        );
+        assert_eq!(
+            grouped_spans[1].schema_url,
+            "https://opentelemetry.io/schemas/upstream"
+        );
+        assert_eq!(grouped_spans[1].scope_spans.len(), 2);
+    }
+
+    #[test]
+    fn test_group_spans_by_resource_and_scope_only_overridden_resources() {
+        let resource = Resource::new(vec![KeyValue::new("service.name", "proxy")]);
+        let mut span_data = create_test_span_data("lib1");
+        span_data.resource = Some(Resource::new(vec![KeyValue::new(
+            "service.name",
+            "upstream",
+        )]));
+        let resource: ResourceAttributesWithSchema = (&resource).into();
+
+        let grouped_spans = crate::transform::trace::tonic::group_spans_by_resource_and_scope(
+            vec![span_data],
+            &resource,
+        );
+
+        assert_eq!(grouped_spans.len(), 1);
+        assert_eq!(grouped_spans[0].scope_spans[0].spans.len(), 1);
+    }
 }
@@ -0,0 +1,435 @@
+//! Property based tests of the SDK to OTLP transforms.
+//!
+//! Every generated value is converted, encoded and decoded with prost, and
+//! every field is checked against the original, so that fields silently
+//! dropped by a transform are caught. Values with a reverse transform are
+//! converted back and compared as a whole.
+#![cfg(all(
+    feature = "gen-tonic-messages",
+    feature = "trace",
+    feature = "logs",
+    feature = "metrics"
+))]
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opentelemetry::logs::{AnyValue as LogsAnyValue, Severity};
+use opentelemetry::trace::{
+    Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+};
+use opentelemetry::{InstrumentationLibrary, Key, KeyValue, Value};
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue};
+use opentelemetry_proto::tonic::logs::v1::LogRecord;
+use opentelemetry_proto::tonic::metrics::v1::{metric, number_data_point};
+use opentelemetry_proto::tonic::trace::v1::{span, status, ResourceSpans, Span};
+use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
+use opentelemetry_sdk::export::trace::SpanData;
+use opentelemetry_sdk::metrics::data::{Aggregation, DataPoint, Sum, Temporality};
+use opentelemetry_sdk::trace::{SpanEvents, SpanLinks};
+use opentelemetry_sdk::Resource;
+use proptest::collection::{hash_map, vec};
+use proptest::prelude::*;
+use prost::Message;
+
+fn roundtrip<M: Message + Default>(message: M) -> M {
+    M::decode(message.encode_to_vec().as_slice()).expect("encoded message must decode")
+}
+
+fn nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64
+}
+
+// finite values only, NaN never equals itself
+fn finite_f64() -> impl Strategy<Value = f64> {
+    use prop::num::f64::{NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO
+}
+
+fn time() -> impl Strategy<Value = SystemTime> {
+    (0u64..1 << 62).prop_map(|nanos| UNIX_EPOCH + Duration::from_nanos(nanos))
+}
+
+fn key() -> impl Strategy<Value = Key> {
+    "[a-z][a-z0-9._]{0,15}".prop_map(Key::from)
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::I64),
+        finite_f64().prop_map(Value::F64),
+        ".{0,16}".prop_map(Value::from),
+        any::<Duration>().prop_map(Value::Duration),
+    ]
+}
+
+fn attributes() -> impl Strategy<Value = Vec<KeyValue>> {
+    vec((key(), value()).prop_map(|(k, v)| KeyValue::new(k, v)), 0..8)
+}
+
+fn logs_any_value() -> impl Strategy<Value = LogsAnyValue> {
+    let leaf = prop_oneof![
+        any::<bool>().prop_map(LogsAnyValue::Boolean),
+        any::<i64>().prop_map(LogsAnyValue::Int),
+        finite_f64().prop_map(LogsAnyValue::Double),
+        ".{0,16}".prop_map(LogsAnyValue::from),
+        vec(any::<u8>(), 0..16).prop_map(LogsAnyValue::Bytes),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..8).prop_map(LogsAnyValue::ListAny),
+            hash_map(key(), inner, 0..8).prop_map(LogsAnyValue::Map),
+        ]
+    })
+}
+
+fn span_context() -> impl Strategy<Value = SpanContext> {
+    (1..=u128::MAX, 1..=u64::MAX, any::<bool>(), any::<bool>()).prop_map(
+        |(trace_id, span_id, sampled, remote)| {
+            SpanContext::new(
+                TraceId::from_u128(trace_id),
+                SpanId::from_u64(span_id),
+                TraceFlags::default().with_sampled(sampled),
+                remote,
+                TraceState::from_key_value([("vendor", "value")]).unwrap(),
+            )
+        },
+    )
+}
+
+fn span_kind() -> impl Strategy<Value = SpanKind> {
+    prop_oneof![
+        Just(SpanKind::Client),
+        Just(SpanKind::Server),
+        Just(SpanKind::Producer),
+        Just(SpanKind::Consumer),
+        Just(SpanKind::Internal),
+    ]
+}
+
+fn span_status() -> impl Strategy<Value = Status> {
+    prop_oneof![
+        Just(Status::Unset),
+        Just(Status::Ok),
+        ".{0,16}".prop_map(Status::error),
+    ]
+}
+
+fn event() -> impl Strategy<Value = Event> {
+    (".{0,16}", time(), attributes(), any::<u32>()).prop_map(
+        |(name, timestamp, attributes, dropped)| {
+            Event::new(name, timestamp, attributes, dropped)
+        },
+    )
+}
+
+fn link() -> impl Strategy<Value = Link> {
+    (span_context(), attributes(), any::<u32>()).prop_map(
+        |(span_context, attributes, dropped)| Link::new(span_context, attributes, dropped),
+    )
+}
+
+prop_compose! {
+    fn span_data()(
+        span_context in span_context(),
+        parent_span_id in any::<u64>(),
+        span_kind in span_kind(),
+        name in ".{0,32}",
+        start_time in time(),
+        duration in 0u64..1 << 40,
+        attributes in attributes(),
+        dropped_attributes_count in any::<u32>(),
+        events in vec(event(), 0..4),
+        dropped_events in any::<u32>(),
+        links in vec(link(), 0..4),
+        dropped_links in any::<u32>(),
+        status in span_status(),
+    ) -> SpanData {
+        let mut span_events = SpanEvents::default();
+        span_events.events = events;
+        span_events.dropped_count = dropped_events;
+        let mut span_links = SpanLinks::default();
+        span_links.links = links;
+        span_links.dropped_count = dropped_links;
+
+        SpanData {
+            span_context,
+            parent_span_id: SpanId::from_u64(parent_span_id),
+            span_kind,
+            name: name.into(),
+            start_time,
+            end_time: start_time + Duration::from_nanos(duration),
+            attributes,
+            dropped_attributes_count,
+            events: span_events,
+            links: span_links,
+            status,
+            instrumentation_lib: InstrumentationLibrary::builder("proptest").build(),
+            resource: None,
+        }
+    }
+}
+
+fn severity() -> impl Strategy<Value = Severity> {
+    (1i32..=24).prop_map(|number| match number {
+        1 => Severity::Trace,
+        2 => Severity::Trace2,
+        3 => Severity::Trace3,
+        4 => Severity::Trace4,
+        5 => Severity::Debug,
+        6 => Severity::Debug2,
+        7 => Severity::Debug3,
+        8 => Severity::Debug4,
+        9 => Severity::Info,
+        10 => Severity::Info2,
+        11 => Severity::Info3,
+        12 => Severity::Info4,
+        13 => Severity::Warn,
+        14 => Severity::Warn2,
+        15 => Severity::Warn3,
+        16 => Severity::Warn4,
+        17 => Severity::Error,
+        18 => Severity::Error2,
+        19 => Severity::Error3,
+        20 => Severity::Error4,
+        21 => Severity::Fatal,
+        22 => Severity::Fatal2,
+        23 => Severity::Fatal3,
+        _ => Severity::Fatal4,
+    })
+}
+
+prop_compose! {
+    fn log_record()(
+        timestamp in prop::option::of(time()),
+        observed_timestamp in time(),
+        severity_number in prop::option::of(severity()),
+        severity_text in prop::option::of(".{0,8}"),
+        body in prop::option::of(logs_any_value()),
+        attributes in prop::option::of(vec((key(), logs_any_value()), 0..8)),
+        trace_context in prop::option::of(span_context()),
+    ) -> opentelemetry_sdk::logs::LogRecord {
+        let mut record = opentelemetry_sdk::logs::LogRecord::default();
+        record.timestamp = timestamp;
+        record.observed_timestamp = Some(observed_timestamp);
+        record.severity_number = severity_number;
+        record.severity_text = severity_text.map(Cow::Owned);
+        record.body = body;
+        record.attributes = attributes;
+        record.trace_context = trace_context.as_ref().map(Into::into);
+        record
+    }
+}
+
+fn assert_attributes_eq(
+    actual: &[opentelemetry_proto::tonic::common::v1::KeyValue],
+    expected: &[KeyValue],
+) {
+    assert_eq!(actual.len(), expected.len(), "attribute dropped or added");
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert_eq!(actual.key, expected.key.as_str());
+        let actual = actual.value.as_ref().and_then(|v| v.value.as_ref());
+        match (&expected.value, actual) {
+            (Value::Bool(b), Some(any_value::Value::BoolValue(a))) => assert_eq!(a, b),
+            (Value::I64(i), Some(any_value::Value::IntValue(a))) => assert_eq!(a, i),
+            (Value::F64(f), Some(any_value::Value::DoubleValue(a))) => assert_eq!(a, f),
+            (Value::String(s), Some(any_value::Value::StringValue(a))) => {
+                assert_eq!(a, s.as_str())
+            }
+            (Value::Duration(d), Some(any_value::Value::IntValue(a))) => {
+                assert_eq!(*a, i64::try_from(d.as_nanos()).unwrap_or(i64::MAX))
+            }
+            (expected, actual) => panic!("{:?} converted into {:?}", expected, actual),
+        }
+    }
+}
+
+fn assert_span_eq(actual: &Span, expected: &SpanData) {
+    let context = &expected.span_context;
+    assert_eq!(actual.trace_id, context.trace_id().to_bytes());
+    assert_eq!(actual.span_id, context.span_id().to_bytes());
+    assert_eq!(actual.trace_state, context.trace_state().header());
+    assert_eq!(actual.flags, context.trace_flags().to_u8() as u32);
+    if expected.parent_span_id == SpanId::INVALID {
+        assert!(actual.parent_span_id.is_empty());
+    } else {
+        assert_eq!(actual.parent_span_id, expected.parent_span_id.to_bytes());
+    }
+    assert_eq!(actual.name, expected.name);
+    assert_eq!(
+        actual.kind,
+        span::SpanKind::from(expected.span_kind.clone()) as i32
+    );
+    assert_eq!(actual.start_time_unix_nano, nanos(expected.start_time));
+    assert_eq!(actual.end_time_unix_nano, nanos(expected.end_time));
+    assert_attributes_eq(&actual.attributes, &expected.attributes);
+    assert_eq!(
+        actual.dropped_attributes_count,
+        expected.dropped_attributes_count
+    );
+
+    assert_eq!(actual.dropped_events_count, expected.events.dropped_count);
+    assert_eq!(actual.events.len(), expected.events.len());
+    for (actual, expected) in actual.events.iter().zip(expected.events.iter()) {
+        assert_eq!(actual.name, expected.name);
+        assert_eq!(actual.time_unix_nano, nanos(expected.timestamp));
+        assert_attributes_eq(&actual.attributes, &expected.attributes);
+        assert_eq!(
+            actual.dropped_attributes_count,
+            expected.dropped_attributes_count
+        );
+    }
+
+    assert_eq!(actual.dropped_links_count, expected.links.dropped_count);
+    assert_eq!(actual.links.len(), expected.links.len());
+    for (actual, expected) in actual.links.iter().zip(expected.links.iter()) {
+        let context = &expected.span_context;
+        assert_eq!(actual.trace_id, context.trace_id().to_bytes());
+        assert_eq!(actual.span_id, context.span_id().to_bytes());
+        assert_eq!(actual.trace_state, context.trace_state().header());
+        assert_eq!(actual.flags, context.trace_flags().to_u8() as u32);
+        assert_attributes_eq(&actual.attributes, &expected.attributes);
+        assert_eq!(
+            actual.dropped_attributes_count,
+            expected.dropped_attributes_count
+        );
+    }
+
+    let actual_status = actual.status.as_ref().expect("status is always set");
+    match &expected.status {
+        Status::Unset => assert_eq!(actual_status.code, status::StatusCode::Unset as i32),
+        Status::Ok => assert_eq!(actual_status.code, status::StatusCode::Ok as i32),
+        Status::Error { description } => {
+            assert_eq!(actual_status.code, status::StatusCode::Error as i32);
+            assert_eq!(actual_status.message, description.as_ref());
+        }
+    }
+}
+
+fn to_sdk(value: AnyValue) -> LogsAnyValue {
+    LogsAnyValue::try_from(value).expect("values created from the SDK are never empty")
+}
+
+proptest! {
+    #[test]
+    fn any_value_roundtrip(value in logs_any_value()) {
+        let proto = roundtrip(AnyValue::from(value.clone()));
+        prop_assert_eq!(to_sdk(proto), value);
+    }
+
+    #[test]
+    fn span_transform_keeps_all_fields(span_data in span_data()) {
+        let proto = roundtrip(Span::from(span_data.clone()));
+        assert_span_eq(&proto, &span_data);
+    }
+
+    #[test]
+    fn resource_spans_transform_keeps_all_fields(span_data in span_data()) {
+        let resource = Resource::new([KeyValue::new("service.name", "proptest")]);
+        let proto = roundtrip(ResourceSpans::new(
+            span_data.clone(),
+            &ResourceAttributesWithSchema::from(&resource),
+        ));
+        prop_assert_eq!(proto.scope_spans.len(), 1);
+        prop_assert_eq!(proto.scope_spans[0].spans.len(), 1);
+        assert_span_eq(&proto.scope_spans[0].spans[0], &span_data);
+    }
+
+    #[test]
+    fn log_record_transform_keeps_all_fields(record in log_record()) {
+        let proto = roundtrip(LogRecord::from(record.clone()));
+
+        prop_assert_eq!(
+            proto.time_unix_nano,
+            record.timestamp.map(nanos).unwrap_or_default()
+        );
+        prop_assert_eq!(
+            proto.observed_time_unix_nano,
+            nanos(record.observed_timestamp.unwrap())
+        );
+        prop_assert_eq!(
+            proto.severity_number,
+            record.severity_number.map(|s| s as i32).unwrap_or_default()
+        );
+        prop_assert_eq!(
+            proto.severity_text,
+            record.severity_text.clone().unwrap_or_default()
+        );
+        prop_assert_eq!(proto.body.map(to_sdk), record.body.clone());
+
+        let attributes = proto
+            .attributes
+            .into_iter()
+            .map(|kv| (Key::from(kv.key), to_sdk(kv.value.unwrap())))
+            .collect::<Vec<_>>();
+        prop_assert_eq!(attributes, record.attributes.clone().unwrap_or_default());
+
+        match record.trace_context {
+            Some(trace_context) => {
+                prop_assert_eq!(proto.trace_id, trace_context.trace_id.to_bytes());
+                prop_assert_eq!(proto.span_id, trace_context.span_id.to_bytes());
+                prop_assert_eq!(
+                    proto.flags,
+                    trace_context.trace_flags.map(|f| f.to_u8() as u32).unwrap_or_default()
+                );
+            }
+            None => {
+                prop_assert!(proto.trace_id.is_empty());
+                prop_assert!(proto.span_id.is_empty());
+                prop_assert_eq!(proto.flags, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn sum_transform_keeps_all_fields(
+        points in vec((attributes(), time(), time(), any::<i64>()), 0..8),
+        is_monotonic in any::<bool>(),
+        cumulative in any::<bool>(),
+    ) {
+        let sum = Sum {
+            data_points: points
+                .into_iter()
+                .map(|(attributes, start_time, time, value)| DataPoint {
+                    attributes,
+                    start_time: Some(start_time),
+                    time: Some(time),
+                    value,
+                    exemplars: vec![],
+                })
+                .collect(),
+            temporality: if cumulative { Temporality::Cumulative } else { Temporality::Delta },
+            is_monotonic,
+        };
+
+        let proto = match metric::Data::try_from(sum.as_any()) {
+            Ok(metric::Data::Sum(proto)) => roundtrip(proto),
+            other => panic!("unexpected metric data {:?}", other),
+        };
+
+        prop_assert_eq!(proto.is_monotonic, sum.is_monotonic);
+        prop_assert_eq!(proto.aggregation_temporality, if cumulative { 2 } else { 1 });
+        prop_assert_eq!(proto.data_points.len(), sum.data_points.len());
+        for (actual, expected) in proto.data_points.iter().zip(&sum.data_points) {
+            assert_attributes_eq(&actual.attributes, &expected.attributes);
+            prop_assert_eq!(actual.start_time_unix_nano, nanos(expected.start_time.unwrap()));
+            prop_assert_eq!(actual.time_unix_nano, nanos(expected.time.unwrap()));
+            prop_assert_eq!(actual.value, Some(number_data_point::Value::AsInt(expected.value)));
+        }
+    }
+}
+
+#[test]
+fn map_values_keep_every_entry() {
+    // a single deterministic case documenting the expected shape
+    let value = LogsAnyValue::Map(HashMap::from([
+        (Key::from("a"), LogsAnyValue::Int(1)),
+        (
+            Key::from("b"),
+            LogsAnyValue::ListAny(vec![LogsAnyValue::Boolean(true)]),
+        ),
+    ]));
+    assert_eq!(to_sdk(roundtrip(AnyValue::from(value.clone()))), value);
+}
@@ -1,7 +1,11 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use opentelemetry::KeyValue;
+use opentelemetry::{Key, KeyValue};
 use opentelemetry_sdk::metrics::AttributeSet;
 
+const HTTP_METHOD: Key = Key::from_static_str("http.request.method");
+const HTTP_ROUTE: Key = Key::from_static_str("http.route");
+const HTTP_STATUS: Key = Key::from_static_str("http.response.status_code");
+
 // Run this benchmark with:
 // cargo bench --bench attribute_set
 
@@ -33,6 +37,31 @@ fn attribute_set(c: &mut Criterion) {
             let _attribute_set: AttributeSet = attributes.into();
         });
     });
+
+    // Typical metrics hot path where the same few keys are recorded over and
+    // over. Keys declared with `Key::from_static_str` have their hash computed
+    // once.
+    c.bench_function("AttributeSet_3_str_keys", |b| {
+        b.iter(|| {
+            let attributes: &[KeyValue] = &[
+                KeyValue::new("http.request.method", "GET"),
+                KeyValue::new("http.route", "/users/{id}"),
+                KeyValue::new("http.response.status_code", 200),
+            ];
+            let _attribute_set: AttributeSet = attributes.into();
+        });
+    });
+
+    c.bench_function("AttributeSet_3_const_keys", |b| {
+        b.iter(|| {
+            let attributes: &[KeyValue] = &[
+                KeyValue::new(HTTP_METHOD, "GET"),
+                KeyValue::new(HTTP_ROUTE, "/users/{id}"),
+                KeyValue::new(HTTP_STATUS, 200),
+            ];
+            let _attribute_set: AttributeSet = attributes.into();
+        });
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
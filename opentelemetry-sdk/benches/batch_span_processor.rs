@@ -33,6 +33,7 @@ fn get_span_data() -> Vec<SpanData> {
             links: SpanLinks::default(),
             status: Status::Unset,
             instrumentation_lib: Default::default(),
+            resource: None,
         })
         .collect::<Vec<SpanData>>()
 }
@@ -54,7 +55,7 @@ fn criterion_benchmark(c: &mut Criterion) {
                                 .with_batch_config(
                                     BatchConfigBuilder::default()
                                         .with_max_queue_size(10_000)
-                                        .build(),
+                                        .build_or_panic(),
                                 )
                                 .build();
                         let mut shared_span_processor = Arc::new(span_processor);
@@ -16,7 +16,7 @@ use opentelemetry::trace::Tracer;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::Key;
 use opentelemetry_sdk::export::logs::{LogData, LogExporter};
-use opentelemetry_sdk::logs::{Logger, LoggerProvider};
+use opentelemetry_sdk::logs::{LogProcessor, Logger, LoggerProvider};
 use opentelemetry_sdk::trace;
 use opentelemetry_sdk::trace::{Sampler, TracerProvider};
 
@@ -30,6 +30,23 @@ impl LogExporter for VoidExporter {
     }
 }
 
+/// A processor registered with `with_log_processor`, which is boxed by the
+/// provider unlike the processors of `with_simple_exporter`.
+#[derive(Debug)]
+struct VoidProcessor;
+
+impl LogProcessor for VoidProcessor {
+    fn emit(&self, _data: &mut LogData) {}
+
+    fn force_flush(&self) -> LogResult<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> LogResult<()> {
+        Ok(())
+    }
+}
+
 fn log_benchmark_group<F: Fn(&Logger)>(c: &mut Criterion, name: &str, f: F) {
     let mut group = c.benchmark_group(name);
 
@@ -62,6 +79,28 @@ fn log_benchmark_group<F: Fn(&Logger)>(c: &mut Criterion, name: &str, f: F) {
         });
     });
 
+    group.bench_function("two-processors", |b| {
+        let provider = LoggerProvider::builder()
+            .with_simple_exporter(VoidExporter)
+            .with_simple_exporter(VoidExporter)
+            .build();
+
+        let logger = provider.logger("two-processors");
+
+        b.iter(|| f(&logger));
+    });
+
+    group.bench_function("two-boxed-processors", |b| {
+        let provider = LoggerProvider::builder()
+            .with_log_processor(VoidProcessor)
+            .with_log_processor(VoidProcessor)
+            .build();
+
+        let logger = provider.logger("two-boxed-processors");
+
+        b.iter(|| f(&logger));
+    });
+
     group.finish();
 }
 
@@ -0,0 +1,77 @@
+/*
+    Measures the cost of `Counter<f64>::add` when the same time series is
+    updated concurrently from a growing number of threads. With the sharded
+    f64 tracker the per-operation cost should stay roughly flat at 8 threads
+    and above instead of growing with the thread count.
+*/
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use opentelemetry::{
+    metrics::{Counter, MeterProvider as _},
+    KeyValue,
+};
+use opentelemetry_sdk::metrics::{ManualReader, SdkMeterProvider};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Run this benchmark with:
+// cargo bench --bench metric_f64_counter
+fn create_counter() -> (SdkMeterProvider, Counter<f64>) {
+    let meter_provider: SdkMeterProvider = SdkMeterProvider::builder()
+        .with_reader(ManualReader::builder().build())
+        .build();
+    let counter = meter_provider
+        .meter("benchmarks")
+        .f64_counter("f64_counter_bench")
+        .init();
+
+    (meter_provider, counter)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("F64_Counter_Contended");
+    for threads in [1, 2, 4, 8, 16] {
+        for (name, attributes) in [
+            ("no_attributes", vec![]),
+            ("one_attribute", vec![KeyValue::new("key", "value")]),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, threads), &threads, |b, &threads| {
+                b.iter_custom(|iters| contended_add(threads, iters, &attributes))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Performs `iters` additions spread over `threads` threads, all updating the
+/// same time series, and returns the elapsed wall-clock time.
+fn contended_add(threads: u64, iters: u64, attributes: &[KeyValue]) -> Duration {
+    let (_provider, counter) = create_counter();
+    let per_thread = iters / threads + 1;
+    let barrier = Arc::new(Barrier::new(threads as usize + 1));
+
+    let handles = (0..threads)
+        .map(|_| {
+            let counter = counter.clone();
+            let attributes = attributes.to_vec();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..per_thread {
+                    counter.add(1.5, &attributes);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    barrier.wait();
+    let start = Instant::now();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    start.elapsed()
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use opentelemetry::{
+    propagation::TextMapPropagator,
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    Context,
+};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// Run this benchmark with:
+// cargo bench --bench propagator
+
+fn criterion_benchmark(c: &mut Criterion) {
+    trace_context(c);
+}
+
+fn trace_context(c: &mut Criterion) {
+    let propagator = TraceContextPropagator::new();
+
+    let mut group = c.benchmark_group("TraceContextPropagator");
+    for (name, trace_state) in [
+        ("empty_tracestate", TraceState::default()),
+        (
+            "tracestate",
+            TraceState::from_str("rojo=00f067aa0ba902b7,congo=t61rcWkgMzE").unwrap(),
+        ),
+    ] {
+        let cx = Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736),
+            SpanId::from_u64(0x00f0_67aa_0ba9_02b7),
+            TraceFlags::SAMPLED,
+            true,
+            trace_state,
+        ));
+
+        group.bench_function(format!("inject/{name}"), |b| {
+            b.iter(|| {
+                let mut injector = HashMap::new();
+                propagator.inject_context(&cx, &mut injector);
+                injector
+            })
+        });
+
+        let mut carrier = HashMap::new();
+        propagator.inject_context(&cx, &mut carrier);
+        group.bench_function(format!("extract/{name}"), |b| {
+            b.iter(|| propagator.extract(&carrier))
+        });
+    }
+
+    let mut invalid = HashMap::new();
+    invalid.insert(
+        "traceparent".to_string(),
+        "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01".to_string(),
+    );
+    group.bench_function("extract/invalid_traceparent", |b| {
+        b.iter(|| propagator.extract(&invalid))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+
+criterion_main!(benches);
@@ -0,0 +1,220 @@
+//! # Attribute sanitization
+//!
+//! Attribute values coming from user input can be arbitrarily large or carry
+//! control characters which some exporters and backends reject. An
+//! [`AttributeSanitizer`] configured on a provider rewrites string values when
+//! they are recorded. Providers without a sanitizer never inspect values.
+use opentelemetry::StringValue;
+#[cfg(feature = "trace")]
+use opentelemetry::{Array, KeyValue, Value};
+
+/// Sanitizes the string values of attributes when they are recorded.
+///
+/// String values are always valid UTF-8, truncation only happens on character
+/// boundaries so that they stay valid. Values which need no change are not
+/// copied.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_sdk::AttributeSanitizer;
+///
+/// // Keep values under 4 KiB and drop control characters.
+/// let sanitizer = AttributeSanitizer::new()
+///     .with_max_length(4096)
+///     .with_control_chars_stripped();
+/// # drop(sanitizer);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AttributeSanitizer {
+    max_length: Option<usize>,
+    strip_control_chars: bool,
+}
+
+impl AttributeSanitizer {
+    /// Create a sanitizer which does not change values.
+    pub fn new() -> Self {
+        AttributeSanitizer::default()
+    }
+
+    /// Truncate string values to at most `max_length` bytes.
+    ///
+    /// A character which would be split is dropped entirely.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Remove control characters from string values, except tabs and line
+    /// breaks.
+    pub fn with_control_chars_stripped(mut self) -> Self {
+        self.strip_control_chars = true;
+        self
+    }
+
+    /// Returns the sanitized `value`, or `None` if it needs no change.
+    fn sanitize_str(&self, value: &str) -> Option<String> {
+        let is_stripped = |c: char| c.is_control() && !matches!(c, '\t' | '\n' | '\r');
+        let strip = self.strip_control_chars && value.chars().any(is_stripped);
+        let truncate = self.max_length.filter(|max| value.len() > *max);
+        if !strip && truncate.is_none() {
+            return None;
+        }
+
+        let mut sanitized = if strip {
+            value.chars().filter(|c| !is_stripped(*c)).collect()
+        } else {
+            value.to_owned()
+        };
+        if let Some(max_length) = self.max_length {
+            if sanitized.len() > max_length {
+                let mut end = max_length;
+                while !sanitized.is_char_boundary(end) {
+                    end -= 1;
+                }
+                sanitized.truncate(end);
+            }
+        }
+        Some(sanitized)
+    }
+
+    fn sanitize_string_value(&self, value: &mut StringValue) {
+        if let Some(sanitized) = self.sanitize_str(value.as_str()) {
+            *value = sanitized.into();
+        }
+    }
+
+    /// Sanitize the string values of `value`, including in arrays.
+    #[cfg(feature = "trace")]
+    pub(crate) fn sanitize_value(&self, value: &mut Value) {
+        match value {
+            Value::String(value) => self.sanitize_string_value(value),
+            Value::Array(Array::String(values)) => {
+                for value in values {
+                    self.sanitize_string_value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sanitize the values of `attributes`.
+    #[cfg(feature = "trace")]
+    pub(crate) fn sanitize_attributes(&self, attributes: &mut [KeyValue]) {
+        for attribute in attributes {
+            self.sanitize_value(&mut attribute.value);
+        }
+    }
+
+    /// Sanitize the string values of `value`, including nested ones.
+    #[cfg(feature = "logs")]
+    pub(crate) fn sanitize_any_value(&self, value: &mut opentelemetry::logs::AnyValue) {
+        use opentelemetry::logs::AnyValue;
+
+        match value {
+            AnyValue::String(value) => self.sanitize_string_value(value),
+            AnyValue::ListAny(values) => {
+                for value in values {
+                    self.sanitize_any_value(value);
+                }
+            }
+            AnyValue::Map(values) => {
+                for value in values.values_mut() {
+                    self.sanitize_any_value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_values_are_not_copied() {
+        let sanitizer = AttributeSanitizer::new()
+            .with_max_length(8)
+            .with_control_chars_stripped();
+        assert_eq!(sanitizer.sanitize_str("short"), None);
+        assert_eq!(sanitizer.sanitize_str("tab\tnl\n"), None);
+        assert_eq!(AttributeSanitizer::new().sanitize_str("\u{0}"), None);
+    }
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        let sanitizer = AttributeSanitizer::new().with_max_length(5);
+        assert_eq!(sanitizer.sanitize_str("abcdefgh").as_deref(), Some("abcde"));
+        // 'é' is two bytes, it would be split at byte 5
+        assert_eq!(sanitizer.sanitize_str("abcdéfgh").as_deref(), Some("abcd"));
+        assert_eq!(
+            AttributeSanitizer::new()
+                .with_max_length(0)
+                .sanitize_str("a"),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn strips_control_chars() {
+        let sanitizer = AttributeSanitizer::new().with_control_chars_stripped();
+        assert_eq!(
+            sanitizer
+                .sanitize_str("a\u{0}b\u{1b}[31mc\u{7f}\td")
+                .as_deref(),
+            Some("ab[31mc\td")
+        );
+
+        // stripping happens before truncation
+        let sanitizer = sanitizer.with_max_length(3);
+        assert_eq!(
+            sanitizer.sanitize_str("\u{0}\u{0}abcd").as_deref(),
+            Some("abc")
+        );
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn sanitizes_attribute_values() {
+        let sanitizer = AttributeSanitizer::new().with_max_length(2);
+        let mut attributes = vec![
+            KeyValue::new("string", "abc"),
+            KeyValue::new(
+                "array",
+                Value::Array(Array::String(vec!["abc".into(), "d".into()])),
+            ),
+            KeyValue::new("int", 12345),
+        ];
+        sanitizer.sanitize_attributes(&mut attributes);
+
+        assert_eq!(attributes[0].value, Value::from("ab"));
+        assert_eq!(
+            attributes[1].value,
+            Value::Array(Array::String(vec!["ab".into(), "d".into()]))
+        );
+        assert_eq!(attributes[2].value, Value::I64(12345));
+    }
+
+    #[cfg(feature = "logs")]
+    #[test]
+    fn sanitizes_nested_any_values() {
+        use opentelemetry::logs::AnyValue;
+        use std::collections::HashMap;
+
+        let sanitizer = AttributeSanitizer::new().with_max_length(2);
+        let mut value = AnyValue::Map(HashMap::from([(
+            "list".into(),
+            AnyValue::ListAny(vec!["abc".into(), AnyValue::Int(1)]),
+        )]));
+        sanitizer.sanitize_any_value(&mut value);
+
+        assert_eq!(
+            value,
+            AnyValue::Map(HashMap::from([(
+                "list".into(),
+                AnyValue::ListAny(vec!["ab".into(), AnyValue::Int(1)]),
+            )]))
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! A memory budget shared by the telemetry buffered across signals.
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use opentelemetry::{Array, KeyValue, Value};
+
+/// The priority of buffered telemetry, deciding what is evicted first when a
+/// [`TelemetryBudget`] is exhausted.
+///
+/// Priorities are ordered from the first evicted to the never evicted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum BudgetPriority {
+    /// Log records less severe than `Info`.
+    DebugLogs,
+    /// Spans with links which all point to unsampled spans, whose linked
+    /// traces can't be followed anyway.
+    UnsampledLinkedSpans,
+    /// Exemplars sampled by metric streams.
+    Exemplars,
+    /// All other spans and log records, which are never evicted.
+    Regular,
+}
+
+impl BudgetPriority {
+    const ALL: [BudgetPriority; 4] = [
+        BudgetPriority::DebugLogs,
+        BudgetPriority::UnsampledLinkedSpans,
+        BudgetPriority::Exemplars,
+        BudgetPriority::Regular,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The maximum number of bytes of telemetry buffered in memory, shared by the
+/// span queues, log queues and exemplar reservoirs it is configured on.
+///
+/// Each buffered item reserves its estimated size in the budget until it is
+/// exported. Once the budget is exhausted, an item can only be buffered by
+/// evicting items of lower [`BudgetPriority`]: debug logs first, then spans
+/// only linked to unsampled spans, then exemplars. Evicted items are freed by
+/// their queue or reservoir the next time it is used, items which can't be
+/// buffered are dropped. Dropped and evicted spans and log records are counted
+/// as [`DroppedTelemetry::memory_budget`](crate::DroppedTelemetry::memory_budget)
+/// by their processor.
+///
+/// The budget is meant for memory-constrained deployments such as sidecars.
+/// Metric data points are bounded by the cardinality limits of their streams
+/// rather than by the budget.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "trace", feature = "logs", feature = "metrics", feature = "testing"))]
+/// # {
+/// use opentelemetry_sdk::{
+///     logs::{BatchLogProcessor, LoggerProvider},
+///     metrics::SdkMeterProvider,
+///     runtime,
+///     testing::{logs::InMemoryLogsExporter, trace::NoopSpanExporter},
+///     trace::{BatchSpanProcessor, TracerProvider},
+///     TelemetryBudget,
+/// };
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// // buffer at most 8 MiB of telemetry
+/// let budget = TelemetryBudget::new(8 << 20);
+///
+/// let tracer_provider = TracerProvider::builder()
+///     .with_span_processor(
+///         BatchSpanProcessor::builder(NoopSpanExporter::new(), runtime::Tokio)
+///             .with_budget(budget.clone())
+///             .build(),
+///     )
+///     .build();
+/// let logger_provider = LoggerProvider::builder()
+///     .with_log_processor(
+///         BatchLogProcessor::builder(InMemoryLogsExporter::default(), runtime::Tokio)
+///             .with_budget(budget.clone())
+///             .build(),
+///     )
+///     .build();
+/// let meter_provider = SdkMeterProvider::builder().with_budget(budget).build();
+/// # drop((tracer_provider, logger_provider, meter_provider));
+/// # }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TelemetryBudget {
+    inner: Arc<BudgetInner>,
+}
+
+struct BudgetInner {
+    max_bytes: usize,
+    state: Mutex<BudgetState>,
+}
+
+/// The bytes reserved and requested to be evicted, per priority.
+#[derive(Debug, Default)]
+struct BudgetState {
+    used: [usize; 4],
+    evicting: [usize; 4],
+}
+
+impl BudgetState {
+    /// The bytes which remain reserved once the requested evictions are done.
+    fn committed(&self) -> usize {
+        let used: usize = self.used.iter().sum();
+        let evicting: usize = self.evicting.iter().sum();
+        used - evicting
+    }
+
+    fn release(&mut self, reservation: Reservation, evicted: bool) {
+        let i = reservation.priority.index();
+        self.used[i] = self.used[i].saturating_sub(reservation.bytes);
+        if evicted {
+            self.evicting[i] = self.evicting[i].saturating_sub(reservation.bytes);
+        }
+        self.evicting[i] = self.evicting[i].min(self.used[i]);
+    }
+}
+
+/// Bytes reserved in a [`TelemetryBudget`] by a buffered item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Reservation {
+    pub(crate) priority: BudgetPriority,
+    pub(crate) bytes: usize,
+}
+
+impl TelemetryBudget {
+    /// Create a budget of `max_bytes` bytes.
+    pub fn new(max_bytes: usize) -> Self {
+        TelemetryBudget {
+            inner: Arc::new(BudgetInner {
+                max_bytes,
+                state: Mutex::new(BudgetState::default()),
+            }),
+        }
+    }
+
+    /// The maximum number of bytes of the budget.
+    pub fn max_bytes(&self) -> usize {
+        self.inner.max_bytes
+    }
+
+    /// The bytes currently reserved by buffered telemetry of `priority`,
+    /// including items waiting to be evicted.
+    pub fn used_bytes(&self, priority: BudgetPriority) -> usize {
+        self.state().used[priority.index()]
+    }
+
+    fn state(&self) -> MutexGuard<'_, BudgetState> {
+        // the state is consistent after every update
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reserves `bytes` for an item of `priority`, requesting the eviction of
+    /// lower priority items if the budget is exhausted.
+    ///
+    /// Returns `None` if the item doesn't fit even after evicting all lower
+    /// priority items.
+    pub(crate) fn try_reserve(
+        &self,
+        priority: BudgetPriority,
+        bytes: usize,
+    ) -> Option<Reservation> {
+        let mut state = self.state();
+        let committed = state.committed() + bytes;
+        if committed > self.inner.max_bytes {
+            let mut needed = committed - self.inner.max_bytes;
+            let evictable = |state: &BudgetState, lower: BudgetPriority| {
+                state.used[lower.index()] - state.evicting[lower.index()]
+            };
+            let lower_priorities = BudgetPriority::ALL
+                .into_iter()
+                .take_while(|lower| *lower < priority);
+            let freeable: usize = lower_priorities
+                .clone()
+                .map(|lower| evictable(&state, lower))
+                .sum();
+            if freeable < needed {
+                return None;
+            }
+
+            // evict the lowest priorities first
+            for lower in lower_priorities {
+                let evicted = evictable(&state, lower).min(needed);
+                state.evicting[lower.index()] += evicted;
+                needed -= evicted;
+            }
+        }
+
+        state.used[priority.index()] += bytes;
+        Some(Reservation { priority, bytes })
+    }
+
+    /// Releases the bytes of an item which left the buffer, e.g. once it was
+    /// exported.
+    pub(crate) fn release(&self, reservation: Reservation) {
+        self.state().release(reservation, false);
+    }
+
+    /// Releases the bytes of an item evicted from the buffer.
+    pub(crate) fn evict(&self, reservation: Reservation) {
+        self.state().release(reservation, true);
+    }
+
+    /// Whether items of `priority` should be evicted to make room for higher
+    /// priority items.
+    pub(crate) fn eviction_requested(&self, priority: BudgetPriority) -> bool {
+        self.state().evicting[priority.index()] > 0
+    }
+}
+
+impl fmt::Debug for TelemetryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        f.debug_struct("TelemetryBudget")
+            .field("max_bytes", &self.inner.max_bytes)
+            .field("used", &state.used)
+            .field("evicting", &state.evicting)
+            .finish()
+    }
+}
+
+/// The estimated size of `attributes` in memory.
+#[cfg_attr(not(any(feature = "trace", feature = "metrics")), allow(dead_code))]
+pub(crate) fn attributes_size(attributes: &[KeyValue]) -> usize {
+    attributes.iter().map(key_value_size).sum()
+}
+
+/// The estimated size of an attribute in memory.
+#[cfg_attr(not(feature = "trace"), allow(dead_code))]
+pub(crate) fn key_value_size(kv: &KeyValue) -> usize {
+    kv.key.as_str().len() + value_size(&kv.value)
+}
+
+fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Bool(_) => 1,
+        Value::I64(_) | Value::F64(_) | Value::Duration(_) => 8,
+        Value::String(s) => s.as_str().len(),
+        Value::Array(Array::Bool(values)) => values.len(),
+        Value::Array(Array::I64(values)) => values.len() * 8,
+        Value::Array(Array::F64(values)) => values.len() * 8,
+        Value::Array(Array::String(values)) => values.iter().map(|s| s.as_str().len()).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BudgetPriority, Reservation, TelemetryBudget};
+
+    #[test]
+    fn reserve_within_budget() {
+        let budget = TelemetryBudget::new(100);
+        let reservation = budget
+            .try_reserve(BudgetPriority::Regular, 60)
+            .expect("fits in the budget");
+        assert_eq!(budget.used_bytes(BudgetPriority::Regular), 60);
+        assert!(budget.try_reserve(BudgetPriority::Regular, 60).is_none());
+
+        budget.release(reservation);
+        assert_eq!(budget.used_bytes(BudgetPriority::Regular), 0);
+        assert!(budget.try_reserve(BudgetPriority::Regular, 60).is_some());
+    }
+
+    #[test]
+    fn evict_lowest_priority_first() {
+        let budget = TelemetryBudget::new(100);
+        let debug = budget.try_reserve(BudgetPriority::DebugLogs, 30).unwrap();
+        let linked = budget
+            .try_reserve(BudgetPriority::UnsampledLinkedSpans, 30)
+            .unwrap();
+        budget.try_reserve(BudgetPriority::Exemplars, 30).unwrap();
+
+        // debug logs can't evict anything
+        assert!(budget.try_reserve(BudgetPriority::DebugLogs, 20).is_none());
+        assert!(!budget.eviction_requested(BudgetPriority::DebugLogs));
+
+        // regular telemetry evicts debug logs, then linked spans
+        budget.try_reserve(BudgetPriority::Regular, 50).unwrap();
+        assert!(budget.eviction_requested(BudgetPriority::DebugLogs));
+        assert!(budget.eviction_requested(BudgetPriority::UnsampledLinkedSpans));
+        assert!(!budget.eviction_requested(BudgetPriority::Exemplars));
+
+        budget.evict(debug);
+        assert!(!budget.eviction_requested(BudgetPriority::DebugLogs));
+        // 10 of the 30 bytes of the linked spans are evicted
+        budget.evict(Reservation {
+            priority: BudgetPriority::UnsampledLinkedSpans,
+            bytes: 10,
+        });
+        assert!(!budget.eviction_requested(BudgetPriority::UnsampledLinkedSpans));
+        budget.release(Reservation {
+            bytes: 20,
+            ..linked
+        });
+
+        // exemplars can't evict regular telemetry
+        assert!(budget.try_reserve(BudgetPriority::Exemplars, 30).is_none());
+    }
+}
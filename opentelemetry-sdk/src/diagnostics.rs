@@ -0,0 +1,449 @@
+//! Dumping the state of the telemetry pipelines, e.g. to debug telemetry
+//! which is not exported.
+use std::io::{self, Write};
+
+use crate::{DroppedTelemetry, Resource};
+
+/// The state of a span processor, log processor or metric reader, reported
+/// by [`PipelineDiagnostics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProcessorDiagnostics {
+    /// The spans or log records queued by the processor and not exported yet,
+    /// `None` if the processor doesn't queue them.
+    pub queued: Option<u64>,
+    /// The error of the last failed export, if any export failed.
+    pub last_export_error: Option<String>,
+}
+
+impl ProcessorDiagnostics {
+    /// The diagnostics of processors composed into one: the sum of their
+    /// queue depths and the last export error of the last failed processor.
+    #[cfg_attr(not(feature = "trace"), allow(dead_code))]
+    pub(crate) fn combine(diagnostics: impl IntoIterator<Item = Self>) -> Self {
+        diagnostics
+            .into_iter()
+            .fold(ProcessorDiagnostics::default(), |combined, diagnostics| {
+                ProcessorDiagnostics {
+                    queued: match (combined.queued, diagnostics.queued) {
+                        (Some(a), Some(b)) => Some(a + b),
+                        (a, b) => a.or(b),
+                    },
+                    last_export_error: diagnostics.last_export_error.or(combined.last_export_error),
+                }
+            })
+    }
+}
+
+/// Writes reports of the state of the telemetry pipelines of providers: their
+/// resource, sampler, dropped telemetry, and the queue depth and last export
+/// error of their processors and readers.
+///
+/// With the `diagnostics_signal` feature, the report can be dumped on demand
+/// by sending a signal to the process, see
+/// [`install_signal_handler`](PipelineDiagnostics::install_signal_handler).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "trace", feature = "logs"))]
+/// # {
+/// use opentelemetry_sdk::{logs::LoggerProvider, trace::TracerProvider, PipelineDiagnostics};
+///
+/// let tracer_provider = TracerProvider::builder().build();
+/// let logger_provider = LoggerProvider::builder().build();
+///
+/// let diagnostics = PipelineDiagnostics::new()
+///     .with_tracer_provider(&tracer_provider)
+///     .with_logger_provider(&logger_provider);
+/// diagnostics.write_report(std::io::stderr()).unwrap();
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PipelineDiagnostics {
+    #[cfg(feature = "trace")]
+    tracer_provider: Option<crate::trace::TracerProvider>,
+    #[cfg(feature = "logs")]
+    logger_provider: Option<crate::logs::LoggerProvider>,
+    #[cfg(feature = "metrics")]
+    meter_provider: Option<crate::metrics::SdkMeterProvider>,
+}
+
+impl PipelineDiagnostics {
+    /// Create diagnostics reporting no provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the state of `provider` and its span processors.
+    ///
+    /// The diagnostics keep a handle to the provider, which is only shut down
+    /// on drop once the diagnostics are dropped too.
+    #[cfg(feature = "trace")]
+    pub fn with_tracer_provider(mut self, provider: &crate::trace::TracerProvider) -> Self {
+        self.tracer_provider = Some(provider.clone());
+        self
+    }
+
+    /// Report the state of `provider` and its log processors.
+    ///
+    /// The diagnostics keep a handle to the provider, which is only shut down
+    /// on drop once the diagnostics are dropped too.
+    #[cfg(feature = "logs")]
+    pub fn with_logger_provider(mut self, provider: &crate::logs::LoggerProvider) -> Self {
+        self.logger_provider = Some(provider.clone());
+        self
+    }
+
+    /// Report the state of `provider` and its readers.
+    #[cfg(feature = "metrics")]
+    pub fn with_meter_provider(mut self, provider: &crate::metrics::SdkMeterProvider) -> Self {
+        self.meter_provider = Some(provider.clone());
+        self
+    }
+
+    /// Writes a report of the state of the providers to `out`.
+    pub fn write_report<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "OpenTelemetry SDK diagnostics")?;
+
+        #[cfg(feature = "trace")]
+        if let Some(provider) = &self.tracer_provider {
+            writeln!(out, "tracer provider:")?;
+            write_resource(&mut out, &provider.config().resource)?;
+            writeln!(out, "  sampler: {}", provider.sampler_description())?;
+            write_dropped(&mut out, provider.dropped_telemetry())?;
+            for (i, processor) in provider.span_processors().iter().enumerate() {
+                write_processor(&mut out, "span processor", i, processor.diagnostics())?;
+            }
+        }
+
+        #[cfg(feature = "logs")]
+        if let Some(provider) = &self.logger_provider {
+            writeln!(out, "logger provider:")?;
+            write_resource(&mut out, provider.resource())?;
+            write_dropped(&mut out, provider.dropped_telemetry())?;
+            for (i, processor) in provider.log_processors().iter().enumerate() {
+                let diagnostics = crate::logs::LogProcessor::diagnostics(processor);
+                write_processor(&mut out, "log processor", i, diagnostics)?;
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(provider) = &self.meter_provider {
+            writeln!(out, "meter provider:")?;
+            if let Some(resource) = provider.resource() {
+                write_resource(&mut out, &resource)?;
+            }
+            write_dropped(&mut out, provider.dropped_telemetry())?;
+            for (i, diagnostics) in provider.reader_diagnostics().into_iter().enumerate() {
+                write_processor(&mut out, "metric reader", i, diagnostics)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Force flushes the providers, and writes the outcome of each flush to
+    /// `out`.
+    pub fn force_flush<W: Write>(&self, mut out: W) -> io::Result<()> {
+        #[cfg(feature = "trace")]
+        if let Some(provider) = &self.tracer_provider {
+            let errors: Vec<_> = provider
+                .force_flush()
+                .into_iter()
+                .filter_map(|result| result.err().map(|err| err.to_string()))
+                .collect();
+            if errors.is_empty() {
+                writeln!(out, "tracer provider flushed")?;
+            } else {
+                writeln!(out, "tracer provider flush failed: {}", errors.join(", "))?;
+            }
+        }
+
+        #[cfg(feature = "logs")]
+        if let Some(provider) = &self.logger_provider {
+            match provider.force_flush() {
+                Ok(()) => writeln!(out, "logger provider flushed")?,
+                Err(err) => writeln!(out, "logger provider flush failed: {err}")?,
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(provider) = &self.meter_provider {
+            match provider.force_flush() {
+                Ok(()) => writeln!(out, "meter provider flushed")?,
+                Err(err) => writeln!(out, "meter provider flush failed: {err}")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a report to `output` and force flushes the providers each time
+    /// the process receives `SIGUSR2`, or `SIGBREAK` (Ctrl+Break) on Windows.
+    ///
+    /// The report is written and the providers are flushed on a dedicated
+    /// thread, until the returned handler is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "trace")]
+    /// # {
+    /// use opentelemetry_sdk::{trace::TracerProvider, DiagnosticsOutput, PipelineDiagnostics};
+    ///
+    /// let tracer_provider = TracerProvider::builder().build();
+    ///
+    /// // `kill -USR2 <pid>` dumps the diagnostics to stderr
+    /// let _handler = PipelineDiagnostics::new()
+    ///     .with_tracer_provider(&tracer_provider)
+    ///     .install_signal_handler(DiagnosticsOutput::Stderr)
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "diagnostics_signal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "diagnostics_signal")))]
+    pub fn install_signal_handler(
+        self,
+        output: DiagnosticsOutput,
+    ) -> io::Result<DiagnosticsSignalHandler> {
+        DiagnosticsSignalHandler::install(self, output)
+    }
+
+    /// Writes a report followed by the outcome of flushing the providers to
+    /// `output`.
+    #[cfg(feature = "diagnostics_signal")]
+    fn dump(&self, output: &DiagnosticsOutput) -> io::Result<()> {
+        // written at once, so that reports of concurrent dumps don't interleave
+        let mut report = Vec::new();
+        self.write_report(&mut report)?;
+        self.force_flush(&mut report)?;
+        match output {
+            DiagnosticsOutput::Stderr => io::stderr().write_all(&report),
+            DiagnosticsOutput::File(path) => std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(&report),
+        }
+    }
+}
+
+fn write_resource(out: &mut impl Write, resource: &Resource) -> io::Result<()> {
+    let attributes: Vec<_> = resource
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    writeln!(out, "  resource: {}", attributes.join(", "))
+}
+
+fn write_dropped(out: &mut impl Write, dropped: DroppedTelemetry) -> io::Result<()> {
+    writeln!(
+        out,
+        "  dropped: queue_full={} export_failed={} memory_budget={} limited_parts={}",
+        dropped.queue_full, dropped.export_failed, dropped.memory_budget, dropped.limited_parts
+    )
+}
+
+fn write_processor(
+    out: &mut impl Write,
+    kind: &str,
+    index: usize,
+    diagnostics: ProcessorDiagnostics,
+) -> io::Result<()> {
+    write!(out, "  {kind} {index}:")?;
+    if let Some(queued) = diagnostics.queued {
+        write!(out, " queued={queued}")?;
+    }
+    match diagnostics.last_export_error {
+        Some(err) => writeln!(out, " last_export_error={err:?}"),
+        None => writeln!(out, " no failed export"),
+    }
+}
+
+/// Where [`PipelineDiagnostics`] writes the reports dumped on a signal.
+#[cfg(feature = "diagnostics_signal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics_signal")))]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum DiagnosticsOutput {
+    /// Write the reports to the standard error.
+    Stderr,
+    /// Append the reports to a file, created if it doesn't exist.
+    File(std::path::PathBuf),
+}
+
+/// Dumps [`PipelineDiagnostics`] when the process receives a signal, until it
+/// is dropped.
+///
+/// Created by [`PipelineDiagnostics::install_signal_handler`].
+#[cfg(feature = "diagnostics_signal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics_signal")))]
+#[derive(Debug)]
+pub struct DiagnosticsSignalHandler {
+    signal_id: signal_hook::SigId,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(feature = "diagnostics_signal")]
+impl DiagnosticsSignalHandler {
+    /// How often the worker checks whether the signal was received.
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+    #[cfg(unix)]
+    const SIGNAL: std::os::raw::c_int = signal_hook::consts::SIGUSR2;
+    #[cfg(windows)]
+    const SIGNAL: std::os::raw::c_int = signal_hook::consts::SIGBREAK;
+
+    fn install(diagnostics: PipelineDiagnostics, output: DiagnosticsOutput) -> io::Result<Self> {
+        use std::sync::{atomic::AtomicBool, atomic::Ordering, Arc};
+
+        // the signal handler only sets the flag, which is async signal safe
+        let signaled = Arc::new(AtomicBool::new(false));
+        let signal_id = signal_hook::flag::register(Self::SIGNAL, Arc::clone(&signaled))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = Arc::clone(&stop);
+
+        let worker = std::thread::Builder::new()
+            .name("OpenTelemetry.Diagnostics".to_owned())
+            .spawn(move || {
+                while !worker_stop.load(Ordering::SeqCst) {
+                    if signaled.swap(false, Ordering::SeqCst) {
+                        if let Err(err) = diagnostics.dump(&output) {
+                            opentelemetry::global::handle_error(
+                                opentelemetry::global::Error::Other(format!(
+                                    "failed to dump the diagnostics: {err}"
+                                )),
+                            );
+                        }
+                    }
+                    std::thread::park_timeout(Self::POLL_INTERVAL);
+                }
+            });
+        let worker = match worker {
+            Ok(worker) => worker,
+            Err(err) => {
+                signal_hook::low_level::unregister(signal_id);
+                return Err(err);
+            }
+        };
+
+        Ok(DiagnosticsSignalHandler {
+            signal_id,
+            stop,
+            worker: Some(worker),
+        })
+    }
+}
+
+#[cfg(feature = "diagnostics_signal")]
+impl Drop for DiagnosticsSignalHandler {
+    fn drop(&mut self) {
+        signal_hook::low_level::unregister(self.signal_id);
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use opentelemetry::{
+        logs::{Logger as _, LoggerProvider as _},
+        trace::{Span as _, Tracer as _, TracerProvider as _},
+        KeyValue,
+    };
+
+    use super::PipelineDiagnostics;
+    use crate::{
+        logs::{FinishedLogBatchOnDemandProcessor, LoggerProvider},
+        testing::{logs::InMemoryLogsExporter, trace::FailingSpanExporter},
+        trace::{Config, TracerProvider},
+        Resource,
+    };
+
+    fn providers() -> (TracerProvider, LoggerProvider) {
+        let tracer_provider = TracerProvider::builder()
+            .with_simple_exporter(FailingSpanExporter::new())
+            .with_config(
+                Config::default()
+                    .with_resource(Resource::new([KeyValue::new("service.name", "checkout")])),
+            )
+            .build();
+        let logger_provider = LoggerProvider::builder()
+            .with_log_processor(
+                FinishedLogBatchOnDemandProcessor::builder(InMemoryLogsExporter::default()).build(),
+            )
+            .build();
+        (tracer_provider, logger_provider)
+    }
+
+    #[test]
+    fn report_processor_state() {
+        let (tracer_provider, logger_provider) = providers();
+        tracer_provider.tracer("test").start("span").end();
+        let logger = logger_provider.logger("test");
+        logger.emit(logger.create_log_record());
+
+        let diagnostics = PipelineDiagnostics::new()
+            .with_tracer_provider(&tracer_provider)
+            .with_logger_provider(&logger_provider);
+        let mut report = Vec::new();
+        diagnostics.write_report(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+
+        assert!(report.contains("tracer provider:\n  resource: service.name=checkout"));
+        assert!(report.contains("  sampler: ParentBased{root:AlwaysOnSampler"));
+        assert!(report
+            .contains("  dropped: queue_full=0 export_failed=1 memory_budget=0 limited_parts=0"));
+        assert!(report.contains("  span processor 0: last_export_error=\"cannot export\"\n"));
+        assert!(report.contains("  log processor 0: queued=1 no failed export\n"));
+
+        let mut flushed = Vec::new();
+        diagnostics.force_flush(&mut flushed).unwrap();
+        assert_eq!(
+            String::from_utf8(flushed).unwrap(),
+            "tracer provider flushed\nlogger provider flushed\n"
+        );
+        let mut report = Vec::new();
+        diagnostics.write_report(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("  log processor 0: queued=0 no failed export\n"));
+    }
+
+    #[cfg(all(unix, feature = "diagnostics_signal"))]
+    #[test]
+    fn dump_on_signal() {
+        use std::time::Duration;
+
+        use crate::DiagnosticsOutput;
+
+        let (tracer_provider, _) = providers();
+        let path = std::env::temp_dir().join(format!(
+            "opentelemetry-diagnostics-{}.txt",
+            std::process::id()
+        ));
+        let handler = PipelineDiagnostics::new()
+            .with_tracer_provider(&tracer_provider)
+            .install_signal_handler(DiagnosticsOutput::File(path.clone()))
+            .unwrap();
+
+        signal_hook::low_level::raise(signal_hook::consts::SIGUSR2).unwrap();
+        let mut report = String::new();
+        for _ in 0..100 {
+            report = std::fs::read_to_string(&path).unwrap_or_default();
+            if report.contains("tracer provider flushed") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        drop(handler);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(report.starts_with("OpenTelemetry SDK diagnostics\ntracer provider:\n"));
+        assert!(report.ends_with("tracer provider flushed\n"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Counts of telemetry dropped by the SDK.
+use std::ops::{Add, AddAssign};
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+use crate::ProcessorDiagnostics;
+
+/// A summary of the telemetry dropped by a provider, per reason.
+///
+/// Spans and log records are counted by their processors, metric data points
+/// by their readers. The summary remains available after the provider is shut
+/// down, so tests and operators can assert that no telemetry was lost.
+///
+/// Whole items, i.e. spans, log records and metric data points, are counted
+/// separately from the parts of items dropped to respect the limits, see
+/// [`total_items`](Self::total_items) and
+/// [`limited_parts`](Self::limited_parts).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DroppedTelemetry {
+    /// Spans or log records dropped because the queue of a batch processor
+    /// was full.
+    pub queue_full: u64,
+    /// Spans, log records or metric data points whose export failed or timed
+    /// out.
+    pub export_failed: u64,
+    /// Spans or log records dropped or evicted from the queue of a batch
+    /// processor to stay within its [`TelemetryBudget`](crate::TelemetryBudget).
+    pub memory_budget: u64,
+    /// Parts of items dropped to respect the configured limits: attributes,
+    /// events and links of spans, attributes of log records, and metric
+    /// measurements recorded into the overflow data point of a stream.
+    ///
+    /// The items themselves are still exported, these parts are not counted
+    /// by [`total_items`](Self::total_items).
+    pub limited_parts: u64,
+}
+
+impl DroppedTelemetry {
+    /// The total count of whole spans, log records and metric data points
+    /// dropped, over all reasons.
+    pub fn total_items(&self) -> u64 {
+        self.queue_full + self.export_failed + self.memory_budget
+    }
+}
+
+impl Add for DroppedTelemetry {
+    type Output = DroppedTelemetry;
+
+    fn add(mut self, other: DroppedTelemetry) -> DroppedTelemetry {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for DroppedTelemetry {
+    fn add_assign(&mut self, other: DroppedTelemetry) {
+        self.queue_full += other.queue_full;
+        self.export_failed += other.export_failed;
+        self.memory_budget += other.memory_budget;
+        self.limited_parts += other.limited_parts;
+    }
+}
+
+impl std::iter::Sum for DroppedTelemetry {
+    fn sum<I: Iterator<Item = DroppedTelemetry>>(iter: I) -> Self {
+        iter.fold(DroppedTelemetry::default(), Add::add)
+    }
+}
+
+/// Counters of dropped telemetry, updated concurrently.
+///
+/// Processors and readers also track the state reported in their
+/// [`ProcessorDiagnostics`] with it.
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+#[derive(Debug, Default)]
+pub(crate) struct DroppedCounters {
+    queue_full: AtomicU64,
+    export_failed: AtomicU64,
+    memory_budget: AtomicU64,
+    limited_parts: AtomicU64,
+    queued: AtomicU64,
+    last_export_error: Mutex<Option<String>>,
+}
+
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+impl DroppedCounters {
+    // only spans and log records are queued or limited
+    #[cfg_attr(not(any(feature = "trace", feature = "logs")), allow(dead_code))]
+    pub(crate) fn queue_full(&self, count: u64) {
+        self.queue_full.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn export_failed(&self, count: u64, err: impl Display) {
+        self.export_failed.fetch_add(count, Ordering::Relaxed);
+        *self
+            .last_export_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(err.to_string());
+    }
+
+    #[cfg_attr(not(any(feature = "trace", feature = "logs")), allow(dead_code))]
+    pub(crate) fn limited_parts(&self, count: u64) {
+        if count > 0 {
+            self.limited_parts.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "trace", feature = "logs")), allow(dead_code))]
+    pub(crate) fn memory_budget(&self, count: u64) {
+        self.memory_budget.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Counts items sent to the worker of a batch processor, before they are
+    /// sent so that the worker never dequeues them first.
+    #[cfg_attr(not(any(feature = "trace", feature = "logs")), allow(dead_code))]
+    pub(crate) fn enqueued(&self, count: u64) {
+        self.queued.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Counts items which left the queue of a batch processor: exported,
+    /// evicted or not sent after all.
+    #[cfg_attr(not(any(feature = "trace", feature = "logs")), allow(dead_code))]
+    pub(crate) fn dequeued(&self, count: u64) {
+        self.queued.fetch_sub(count, Ordering::Relaxed);
+    }
+
+    /// The diagnostics of a processor, with the queue depth tracked by
+    /// [`enqueued`](Self::enqueued) if `queues` is set.
+    pub(crate) fn diagnostics(&self, queues: bool) -> ProcessorDiagnostics {
+        ProcessorDiagnostics {
+            queued: queues.then(|| self.queued.load(Ordering::Relaxed)),
+            last_export_error: self
+                .last_export_error
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> DroppedTelemetry {
+        DroppedTelemetry {
+            queue_full: self.queue_full.load(Ordering::Relaxed),
+            export_failed: self.export_failed.load(Ordering::Relaxed),
+            memory_budget: self.memory_budget.load(Ordering::Relaxed),
+            limited_parts: self.limited_parts.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Observes the telemetry lost and queued by the processors of a provider
+/// with instruments of `meter`: the `otel.sdk.{signal}.dropped` counter, per
+/// `reason`, and the `otel.sdk.processor.{signal}.queue.size` gauge.
+///
+/// Parts dropped to respect the limits are not counted, they are parts of
+/// spans and log records rather than whole ones. `observe` returns `None`
+/// once the provider is gone.
+#[cfg(all(feature = "metrics", any(feature = "trace", feature = "logs")))]
+pub(crate) fn observe_processors<F>(
+    meter: &opentelemetry::metrics::Meter,
+    signal: &'static str,
+    unit: &'static str,
+    items: &'static str,
+    observe: F,
+) where
+    F: Fn() -> Option<(DroppedTelemetry, ProcessorDiagnostics)> + Send + Sync + 'static,
+{
+    use opentelemetry::KeyValue;
+    use std::sync::Arc;
+
+    let observe = Arc::new(observe);
+    let observe_dropped = Arc::clone(&observe);
+    let _ = meter
+        .u64_observable_counter(format!("otel.sdk.{signal}.dropped"))
+        .with_unit(unit)
+        .with_description(format!(
+            "{items} dropped by the processors of the provider, by reason"
+        ))
+        .with_callback(move |observer| {
+            let Some((dropped, _)) = observe_dropped() else {
+                return;
+            };
+            for (reason, count) in [
+                ("queue_full", dropped.queue_full),
+                ("export_failed", dropped.export_failed),
+                ("memory_budget", dropped.memory_budget),
+            ] {
+                observer.observe(count, &[KeyValue::new("reason", reason)]);
+            }
+        })
+        .init();
+    let _ = meter
+        .u64_observable_gauge(format!("otel.sdk.processor.{signal}.queue.size"))
+        .with_unit(unit)
+        .with_description(format!(
+            "{items} waiting in the queues of the batch processors of the provider"
+        ))
+        .with_callback(move |observer| {
+            // only batch processors have a queue
+            if let Some(queued) = observe().and_then(|(_, diagnostics)| diagnostics.queued) {
+                observer.observe(queued, &[]);
+            }
+        })
+        .init();
+}
@@ -0,0 +1,23 @@
+//! Errors returned when building SDK components.
+use thiserror::Error;
+
+/// Errors returned by SDK builders when the provided configuration is invalid.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// A setting that must be greater than zero was set to zero.
+    #[error("`{0}` must be greater than zero")]
+    ZeroValue(&'static str),
+
+    /// The maximum export batch size is larger than the queue that feeds it.
+    #[error(
+        "`max_export_batch_size` ({max_export_batch_size}) must not exceed `max_queue_size` \
+         ({max_queue_size}), lower the batch size or raise the queue size"
+    )]
+    ExportBatchSizeExceedsQueueSize {
+        /// The configured maximum export batch size.
+        max_export_batch_size: usize,
+        /// The configured maximum queue size.
+        max_queue_size: usize,
+    },
+}
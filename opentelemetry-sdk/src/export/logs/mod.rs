@@ -11,6 +11,12 @@ use opentelemetry::{
 use std::borrow::Cow;
 use std::fmt::Debug;
 
+mod router;
+mod tee;
+
+pub use router::{RoutingLogExporter, RoutingLogExporterBuilder};
+pub use tee::{TeeLogExporter, TeeLogExporterBuilder};
+
 /// `LogExporter` defines the interface that log exporters should implement.
 #[async_trait]
 pub trait LogExporter: Send + Sync + Debug {
@@ -34,6 +40,13 @@ pub struct LogData {
     pub record: LogRecord,
     /// Instrumentation details for the emitter who produced this `LogEvent`.
     pub instrumentation: InstrumentationLibrary,
+    /// Resource of the application that produced this log, if it differs
+    /// from the resource of the exporter.
+    ///
+    /// Logs emitted through the SDK leave it unset. Proxies re-exporting logs
+    /// of other applications set it so that a single exporter can export logs
+    /// of many resources, exporters which support it group logs by resource.
+    pub resource: Option<Resource>,
 }
 
 /// Describes the result of an export.
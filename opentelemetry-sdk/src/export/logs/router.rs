@@ -0,0 +1,550 @@
+use crate::export::logs::{LogData, LogExporter};
+use crate::runtime::Runtime;
+use crate::Resource;
+use async_trait::async_trait;
+use futures_channel::mpsc;
+use futures_util::future::{self, Either};
+use futures_util::lock::Mutex;
+use futures_util::StreamExt as _;
+use opentelemetry::global;
+#[cfg(feature = "logs_level_enabled")]
+use opentelemetry::logs::Severity;
+use opentelemetry::logs::{LogError, LogResult};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default maximum duration a single route may take to export a batch.
+const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default maximum number of batches queued for a single route.
+const DEFAULT_QUEUE_SIZE: usize = 32;
+
+/// Minimum interval between reports of log records dropped for matching no
+/// route.
+const UNMATCHED_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+type LogPredicate = Box<dyn Fn(&LogData) -> bool + Send + Sync>;
+
+/// Messages sent to the worker of a route.
+#[derive(Debug)]
+enum RouteMessage {
+    /// Export a batch of the log records matching the route.
+    Export(Vec<Cow<'static, LogData>>),
+    /// Set the resource of the exporter.
+    SetResource(Resource),
+}
+
+#[derive(Debug)]
+enum RouteState {
+    /// No batch was routed yet, the worker is not spawned.
+    Idle,
+    /// The worker receives the queued messages.
+    Running(mpsc::Sender<RouteMessage>),
+    ShutDown,
+}
+
+#[derive(Debug)]
+struct Destination {
+    // shared with the worker, which holds the lock while exporting
+    exporter: Arc<Mutex<Box<dyn LogExporter>>>,
+    state: RouteState,
+    timeout: Duration,
+}
+
+impl Destination {
+    fn new(exporter: Box<dyn LogExporter>, timeout: Duration) -> Self {
+        Destination {
+            exporter: Arc::new(Mutex::new(exporter)),
+            state: RouteState::Idle,
+            timeout,
+        }
+    }
+
+    /// The queue of the worker of the route, spawning it on first use.
+    ///
+    /// The worker is only spawned once log records are routed, so that the
+    /// exporter can be built outside of the runtime.
+    fn queue<R: Runtime>(
+        &mut self,
+        idx: usize,
+        runtime: &R,
+        queue_size: usize,
+    ) -> Option<&mut mpsc::Sender<RouteMessage>> {
+        if matches!(self.state, RouteState::Idle) {
+            let (sender, receiver) = mpsc::channel(queue_size);
+            let worker = run_route(
+                idx,
+                Arc::clone(&self.exporter),
+                self.timeout,
+                runtime.clone(),
+                receiver,
+            );
+            runtime.spawn(Box::pin(worker));
+            self.state = RouteState::Running(sender);
+        }
+        match &mut self.state {
+            RouteState::Running(sender) => Some(sender),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool {
+        // the route is considered enabled while its worker is exporting
+        match self.exporter.try_lock() {
+            Some(exporter) => exporter.event_enabled(level, target, name),
+            None => true,
+        }
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        match &mut self.state {
+            RouteState::Idle => {
+                if let Some(mut exporter) = self.exporter.try_lock() {
+                    exporter.set_resource(resource);
+                }
+            }
+            RouteState::Running(sender) => {
+                let _ = sender.try_send(RouteMessage::SetResource(resource.clone()));
+            }
+            RouteState::ShutDown => {}
+        }
+    }
+
+    fn shutdown(&mut self) {
+        match std::mem::replace(&mut self.state, RouteState::ShutDown) {
+            RouteState::Idle => {
+                if let Some(mut exporter) = self.exporter.try_lock() {
+                    exporter.shutdown();
+                }
+            }
+            // the worker exports the queued batches and shuts down the exporter
+            RouteState::Running(mut sender) => sender.close_channel(),
+            RouteState::ShutDown => {}
+        }
+    }
+}
+
+/// Exports the batches queued for route `idx` one at a time, each within
+/// `timeout`, until the routing exporter is shut down or dropped.
+async fn run_route<R: Runtime>(
+    idx: usize,
+    exporter: Arc<Mutex<Box<dyn LogExporter>>>,
+    timeout: Duration,
+    runtime: R,
+    mut messages: mpsc::Receiver<RouteMessage>,
+) {
+    while let Some(message) = messages.next().await {
+        let mut exporter = exporter.lock().await;
+        match message {
+            RouteMessage::Export(batch) => {
+                let export = exporter.export(batch);
+                let err = match future::select(export, runtime.delay(timeout)).await {
+                    Either::Left((Ok(()), _)) => continue,
+                    Either::Left((Err(err), _)) => format!("route {idx}: {err}"),
+                    Either::Right(_) => format!("route {idx}: export timed out"),
+                };
+                global::handle_error(LogError::from(err));
+            }
+            RouteMessage::SetResource(resource) => exporter.set_resource(&resource),
+        }
+    }
+    exporter.lock().await.shutdown();
+}
+
+struct Route {
+    predicate: LogPredicate,
+    destination: Destination,
+}
+
+impl fmt::Debug for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Route")
+            .field("destination", &self.destination)
+            .finish()
+    }
+}
+
+/// A [`LogExporter`] that routes each log record to a destination exporter
+/// selected by a user-supplied predicate.
+///
+/// Routes are evaluated in the order they were added and each record is sent
+/// to the first route whose predicate matches. Records that match no route are
+/// sent to the default route if one is configured, and dropped otherwise.
+/// Dropped records are reported to the global error handler at most once a
+/// minute.
+///
+/// Each batch is split into one batch per route, queued for the route. Every
+/// route exports its queued batches from its own task spawned on the runtime,
+/// one at a time and each within the timeout of the route, so a failing or
+/// slow route only delays its own log records. Exports return once the
+/// batches are queued. When the queue of a route is full, the records routed
+/// to it are dropped. Failed exports and dropped records are reported to the
+/// global error handler and do not fail the export of the records of the
+/// other routes.
+///
+/// Shutting the exporter down lets every route export its queued batches
+/// before its exporter is shut down.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature="testing")]
+/// # {
+/// use opentelemetry::logs::Severity;
+/// use opentelemetry_sdk::export::logs::RoutingLogExporter;
+/// use opentelemetry_sdk::runtime;
+/// use opentelemetry_sdk::testing::logs::InMemoryLogsExporter;
+///
+/// let errors = InMemoryLogsExporter::default();
+/// let others = InMemoryLogsExporter::default();
+/// let exporter = RoutingLogExporter::builder(runtime::Tokio)
+///     .with_route(
+///         |log| matches!(log.record.severity_number, Some(s) if s >= Severity::Error),
+///         errors,
+///     )
+///     .with_default_route(others)
+///     .build();
+/// # drop(exporter);
+/// # }
+/// ```
+pub struct RoutingLogExporter<R: Runtime> {
+    routes: Vec<Route>,
+    default_route: Option<Destination>,
+    queue_size: usize,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for RoutingLogExporter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutingLogExporter")
+            .field("routes", &self.routes)
+            .field("default_route", &self.default_route)
+            .field("queue_size", &self.queue_size)
+            .finish()
+    }
+}
+
+impl<R: Runtime> RoutingLogExporter<R> {
+    /// Create a new [`RoutingLogExporterBuilder`] using `runtime` to spawn the
+    /// worker of each route and enforce the per-route timeouts.
+    pub fn builder(runtime: R) -> RoutingLogExporterBuilder<R> {
+        RoutingLogExporterBuilder {
+            routes: Vec::new(),
+            default_route: None,
+            queue_size: DEFAULT_QUEUE_SIZE,
+            runtime,
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn destinations(&self) -> impl Iterator<Item = &Destination> {
+        self.routes
+            .iter()
+            .map(|route| &route.destination)
+            .chain(self.default_route.iter())
+    }
+
+    fn destinations_mut(&mut self) -> impl Iterator<Item = &mut Destination> {
+        self.routes
+            .iter_mut()
+            .map(|route| &mut route.destination)
+            .chain(self.default_route.iter_mut())
+    }
+}
+
+#[async_trait]
+impl<R: Runtime> LogExporter for RoutingLogExporter<R> {
+    async fn export<'a>(&mut self, batch: Vec<Cow<'a, LogData>>) -> LogResult<()> {
+        // one partition per route, followed by the default route
+        let mut partitions: Vec<Vec<Cow<'a, LogData>>> = Vec::new();
+        partitions.resize_with(self.routes.len() + 1, Vec::new);
+
+        for log in batch {
+            let idx = self
+                .routes
+                .iter()
+                .position(|route| (route.predicate)(&log))
+                .unwrap_or(self.routes.len());
+            partitions[idx].push(log);
+        }
+
+        let unmatched = partitions.last().map_or(0, Vec::len);
+        if self.default_route.is_none() && unmatched > 0 {
+            global::handle_error_rate_limited(
+                "routing_log_exporter.unmatched",
+                UNMATCHED_REPORT_INTERVAL,
+                LogError::from(format!("dropped {unmatched} log records matching no route")),
+            );
+        }
+
+        let runtime = self.runtime.clone();
+        let queue_size = self.queue_size;
+        for (idx, (destination, partition)) in self.destinations_mut().zip(partitions).enumerate() {
+            if partition.is_empty() {
+                continue;
+            }
+            let count = partition.len();
+            let reason = match destination.queue(idx, &runtime, queue_size) {
+                Some(queue) => {
+                    let batch = partition
+                        .into_iter()
+                        .map(|log| Cow::Owned(log.into_owned()))
+                        .collect();
+                    match queue.try_send(RouteMessage::Export(batch)) {
+                        Ok(()) => continue,
+                        Err(_) => "the queue is full",
+                    }
+                }
+                None => "the route was shut down",
+            };
+            global::handle_error(LogError::from(format!(
+                "route {idx}: dropped {count} log records, {reason}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        for destination in self.destinations_mut() {
+            destination.shutdown();
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool {
+        self.destinations()
+            .any(|destination| destination.event_enabled(level, target, name))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        for destination in self.destinations_mut() {
+            destination.set_resource(resource);
+        }
+    }
+}
+
+/// Builder for [`RoutingLogExporter`].
+pub struct RoutingLogExporterBuilder<R: Runtime> {
+    routes: Vec<Route>,
+    default_route: Option<Destination>,
+    queue_size: usize,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for RoutingLogExporterBuilder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutingLogExporterBuilder")
+            .field("routes", &self.routes)
+            .field("default_route", &self.default_route)
+            .field("queue_size", &self.queue_size)
+            .finish()
+    }
+}
+
+impl<R: Runtime> RoutingLogExporterBuilder<R> {
+    /// Add a route sending log records matching `predicate` to `exporter`,
+    /// using the default export timeout of 30 seconds.
+    pub fn with_route<P, E>(self, predicate: P, exporter: E) -> Self
+    where
+        P: Fn(&LogData) -> bool + Send + Sync + 'static,
+        E: LogExporter + 'static,
+    {
+        self.with_route_timeout(predicate, exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Add a route sending log records matching `predicate` to `exporter`,
+    /// which must finish exporting a batch within `timeout`.
+    pub fn with_route_timeout<P, E>(mut self, predicate: P, exporter: E, timeout: Duration) -> Self
+    where
+        P: Fn(&LogData) -> bool + Send + Sync + 'static,
+        E: LogExporter + 'static,
+    {
+        self.routes.push(Route {
+            predicate: Box::new(predicate),
+            destination: Destination::new(Box::new(exporter), timeout),
+        });
+        self
+    }
+
+    /// Set the exporter receiving log records that match no route, using the
+    /// default export timeout of 30 seconds.
+    ///
+    /// If no default route is configured, such records are dropped.
+    pub fn with_default_route<E: LogExporter + 'static>(self, exporter: E) -> Self {
+        self.with_default_route_timeout(exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Set the exporter receiving log records that match no route, which must
+    /// finish exporting a batch within `timeout`.
+    pub fn with_default_route_timeout<E: LogExporter + 'static>(
+        mut self,
+        exporter: E,
+        timeout: Duration,
+    ) -> Self {
+        self.default_route = Some(Destination::new(Box::new(exporter), timeout));
+        self
+    }
+
+    /// Set the maximum number of batches queued for each route, 32 by default.
+    ///
+    /// Batches routed to a route whose queue is full are dropped.
+    pub fn with_queue_size(self, queue_size: usize) -> Self {
+        RoutingLogExporterBuilder { queue_size, ..self }
+    }
+
+    /// Build the [`RoutingLogExporter`].
+    pub fn build(self) -> RoutingLogExporter<R> {
+        RoutingLogExporter {
+            routes: self.routes,
+            default_route: self.default_route,
+            queue_size: self.queue_size,
+            runtime: self.runtime,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::logs::LogRecord;
+    use crate::runtime;
+    use crate::testing::logs::InMemoryLogsExporter;
+    use opentelemetry::logs::Severity;
+    use opentelemetry::InstrumentationLibrary;
+
+    fn log_with_severity(severity: Severity) -> LogData {
+        LogData {
+            record: LogRecord {
+                severity_number: Some(severity),
+                ..Default::default()
+            },
+            instrumentation: InstrumentationLibrary::default(),
+            resource: None,
+        }
+    }
+
+    /// Waits for the workers of the routes to export `count` log records to
+    /// `exporter`.
+    async fn wait_for_logs(exporter: &InMemoryLogsExporter, count: usize) {
+        for _ in 0..500 {
+            if exporter.get_emitted_logs().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[derive(Debug)]
+    struct HangingExporter;
+
+    #[async_trait]
+    impl LogExporter for HangingExporter {
+        async fn export<'a>(&mut self, _batch: Vec<Cow<'a, LogData>>) -> LogResult<()> {
+            future::pending().await
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn routes_logs_by_predicate() {
+        let errors = InMemoryLogsExporter::default();
+        let fallback = InMemoryLogsExporter::default();
+        let mut exporter = RoutingLogExporter::builder(runtime::Tokio)
+            .with_route(
+                |log| matches!(log.record.severity_number, Some(s) if s >= Severity::Error),
+                errors.clone(),
+            )
+            .with_default_route(fallback.clone())
+            .build();
+
+        let error = log_with_severity(Severity::Error);
+        let info = log_with_severity(Severity::Info);
+        let fatal = log_with_severity(Severity::Fatal);
+        let result = exporter
+            .export(vec![
+                Cow::Borrowed(&error),
+                Cow::Borrowed(&info),
+                Cow::Borrowed(&fatal),
+            ])
+            .await;
+
+        assert!(result.is_ok());
+        wait_for_logs(&errors, 2).await;
+        wait_for_logs(&fallback, 1).await;
+        assert_eq!(errors.get_emitted_logs().unwrap().len(), 2);
+        assert_eq!(fallback.get_emitted_logs().unwrap().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn slow_route_does_not_block_other_routes() {
+        let fallback = InMemoryLogsExporter::default();
+        let mut exporter = RoutingLogExporter::builder(runtime::Tokio)
+            .with_route(
+                |log| log.record.severity_number == Some(Severity::Error),
+                HangingExporter,
+            )
+            .with_default_route(fallback.clone())
+            .build();
+
+        let error = log_with_severity(Severity::Error);
+        let info = log_with_severity(Severity::Info);
+        // the slow route never finishes its first export
+        for _ in 0..3 {
+            let result = tokio::time::timeout(
+                Duration::from_secs(1),
+                exporter.export(vec![Cow::Borrowed(&error), Cow::Borrowed(&info)]),
+            )
+            .await;
+            assert!(matches!(result, Ok(Ok(()))));
+        }
+
+        wait_for_logs(&fallback, 3).await;
+        assert_eq!(fallback.get_emitted_logs().unwrap().len(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn slow_route_times_out() {
+        let fallback = InMemoryLogsExporter::default();
+        let mut exporter = RoutingLogExporter::builder(runtime::Tokio)
+            .with_route_timeout(
+                |log| log.record.severity_number == Some(Severity::Error),
+                HangingExporter,
+                Duration::from_millis(10),
+            )
+            .with_default_route(fallback.clone())
+            .build();
+
+        let error = log_with_severity(Severity::Error);
+        let info = log_with_severity(Severity::Info);
+        for _ in 0..2 {
+            let result = exporter
+                .export(vec![Cow::Borrowed(&error), Cow::Borrowed(&info)])
+                .await;
+            assert!(result.is_ok());
+        }
+
+        wait_for_logs(&fallback, 2).await;
+        assert_eq!(fallback.get_emitted_logs().unwrap().len(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn drops_unmatched_logs_without_default_route() {
+        let errors = InMemoryLogsExporter::default();
+        let mut exporter = RoutingLogExporter::builder(runtime::Tokio)
+            .with_route(
+                |log| log.record.severity_number == Some(Severity::Error),
+                errors.clone(),
+            )
+            .build();
+
+        let info = log_with_severity(Severity::Info);
+        let result = exporter.export(vec![Cow::Borrowed(&info)]).await;
+
+        assert!(result.is_ok());
+        assert!(errors.get_emitted_logs().unwrap().is_empty());
+        assert!(global::error_report_stats().contains_key("routing_log_exporter.unmatched"));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::export::logs::{LogData, LogExporter};
+use crate::runtime::Runtime;
+use crate::Resource;
+use async_trait::async_trait;
+use futures_util::future::{self, Either};
+#[cfg(feature = "logs_level_enabled")]
+use opentelemetry::logs::Severity;
+use opentelemetry::logs::{LogError, LogResult};
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+
+/// Default maximum duration a single child exporter may take to export a batch.
+const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct TeeChild {
+    exporter: Box<dyn LogExporter>,
+    timeout: Duration,
+}
+
+/// A [`LogExporter`] that exports every batch to multiple child exporters.
+///
+/// Each child receives every batch and is exported to concurrently, with its
+/// own timeout. A failing or slow child does not prevent the other children
+/// from receiving the batch. If any child fails or times out, the returned
+/// error lists each failure by the child's index.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature="testing")]
+/// # {
+/// use opentelemetry_sdk::export::logs::TeeLogExporter;
+/// use opentelemetry_sdk::runtime;
+/// use opentelemetry_sdk::testing::logs::InMemoryLogsExporter;
+/// use std::time::Duration;
+///
+/// let exporter = TeeLogExporter::builder(runtime::Tokio)
+///     .with_exporter(InMemoryLogsExporter::default())
+///     .with_exporter_timeout(InMemoryLogsExporter::default(), Duration::from_secs(5))
+///     .build();
+/// # drop(exporter);
+/// # }
+/// ```
+pub struct TeeLogExporter<R: Runtime> {
+    children: Vec<TeeChild>,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for TeeLogExporter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeLogExporter")
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl<R: Runtime> TeeLogExporter<R> {
+    /// Create a new [`TeeLogExporterBuilder`] using `runtime` to enforce the
+    /// per-exporter timeouts.
+    pub fn builder(runtime: R) -> TeeLogExporterBuilder<R> {
+        TeeLogExporterBuilder {
+            children: Vec::new(),
+            runtime,
+        }
+    }
+}
+
+#[async_trait]
+impl<R: Runtime> LogExporter for TeeLogExporter<R> {
+    async fn export<'a>(&mut self, batch: Vec<Cow<'a, LogData>>) -> LogResult<()> {
+        let runtime = &self.runtime;
+        let exports = self
+            .children
+            .iter_mut()
+            .enumerate()
+            .map(|(idx, child)| {
+                let batch = batch.clone();
+                let timeout = runtime.delay(child.timeout);
+                async move {
+                    let export = child.exporter.export(batch);
+                    match future::select(export, timeout).await {
+                        Either::Left((Ok(()), _)) => None,
+                        Either::Left((Err(err), _)) => Some(format!("exporter {idx}: {err}")),
+                        Either::Right(_) => Some(format!("exporter {idx}: export timed out")),
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let errs = future::join_all(exports)
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(LogError::from(errs.join(", ")))
+        }
+    }
+
+    fn shutdown(&mut self) {
+        for child in &mut self.children {
+            child.exporter.shutdown();
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool {
+        self.children
+            .iter()
+            .any(|child| child.exporter.event_enabled(level, target, name))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        for child in &mut self.children {
+            child.exporter.set_resource(resource);
+        }
+    }
+}
+
+/// Builder for [`TeeLogExporter`].
+pub struct TeeLogExporterBuilder<R: Runtime> {
+    children: Vec<TeeChild>,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for TeeLogExporterBuilder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeLogExporterBuilder")
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl<R: Runtime> TeeLogExporterBuilder<R> {
+    /// Add a child exporter using the default export timeout of 30 seconds.
+    pub fn with_exporter<E: LogExporter + 'static>(self, exporter: E) -> Self {
+        self.with_exporter_timeout(exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Add a child exporter that must finish exporting a batch within `timeout`.
+    pub fn with_exporter_timeout<E: LogExporter + 'static>(
+        mut self,
+        exporter: E,
+        timeout: Duration,
+    ) -> Self {
+        self.children.push(TeeChild {
+            exporter: Box::new(exporter),
+            timeout,
+        });
+        self
+    }
+
+    /// Build the [`TeeLogExporter`].
+    pub fn build(self) -> TeeLogExporter<R> {
+        TeeLogExporter {
+            children: self.children,
+            runtime: self.runtime,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::logs::LogRecord;
+    use crate::runtime;
+    use crate::testing::logs::InMemoryLogsExporter;
+    use opentelemetry::InstrumentationLibrary;
+
+    #[derive(Debug)]
+    struct FailingLogExporter;
+
+    #[async_trait]
+    impl LogExporter for FailingLogExporter {
+        async fn export<'a>(&mut self, _batch: Vec<Cow<'a, LogData>>) -> LogResult<()> {
+            Err(LogError::from("failed".to_string()))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn exports_to_all_children_despite_failures() {
+        let first = InMemoryLogsExporter::default();
+        let second = InMemoryLogsExporter::default();
+        let mut exporter = TeeLogExporter::builder(runtime::Tokio)
+            .with_exporter(first.clone())
+            .with_exporter(FailingLogExporter)
+            .with_exporter(second.clone())
+            .build();
+
+        let log = LogData {
+            record: LogRecord::default(),
+            instrumentation: InstrumentationLibrary::default(),
+            resource: None,
+        };
+        let result = exporter.export(vec![Cow::Borrowed(&log)]).await;
+
+        let err = result.expect_err("failing child should be reported");
+        assert!(err.to_string().contains("exporter 1"));
+        assert_eq!(first.get_emitted_logs().unwrap().len(), 1);
+        assert_eq!(second.get_emitted_logs().unwrap().len(), 1);
+    }
+}
@@ -7,6 +7,12 @@ use std::borrow::Cow;
 use std::fmt::Debug;
 use std::time::SystemTime;
 
+mod router;
+mod tee;
+
+pub use router::{RoutingSpanExporter, RoutingSpanExporterBuilder};
+pub use tee::{TeeSpanExporter, TeeSpanExporterBuilder};
+
 /// Describes the result of an export.
 pub type ExportResult = Result<(), TraceError>;
 
@@ -97,4 +103,11 @@ pub struct SpanData {
     pub status: Status,
     /// Instrumentation library that produced this span
     pub instrumentation_lib: crate::InstrumentationLibrary,
+    /// Resource of the application that produced this span, if it differs
+    /// from the resource of the exporter.
+    ///
+    /// Spans created by the SDK leave it unset. Proxies re-exporting spans of
+    /// other applications set it so that a single exporter can export spans
+    /// of many resources, exporters which support it group spans by resource.
+    pub resource: Option<Resource>,
 }
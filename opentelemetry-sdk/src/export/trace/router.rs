@@ -0,0 +1,544 @@
+use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+use crate::runtime::Runtime;
+use crate::Resource;
+use futures_channel::{mpsc, oneshot};
+use futures_util::future::{self, BoxFuture, Either};
+use futures_util::StreamExt as _;
+use opentelemetry::global;
+use opentelemetry::trace::TraceError;
+use std::fmt;
+use std::mem;
+use std::time::Duration;
+
+/// Default maximum duration a single route may take to export a batch.
+const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default maximum number of batches queued for a single route.
+const DEFAULT_QUEUE_SIZE: usize = 32;
+
+/// Minimum interval between reports of spans dropped for matching no route.
+const UNMATCHED_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+type SpanPredicate = Box<dyn Fn(&SpanData) -> bool + Send + Sync>;
+
+/// Messages sent to the worker of a route.
+#[derive(Debug)]
+enum RouteMessage {
+    /// Export a batch of the spans matching the route.
+    Export(Vec<SpanData>),
+    /// Flush the exporter once the batches queued before are exported.
+    Flush(oneshot::Sender<ExportResult>),
+    /// Set the resource of the exporter.
+    SetResource(Resource),
+}
+
+#[derive(Debug)]
+enum RouteState {
+    /// No batch was routed yet, the worker is not spawned.
+    Idle(Box<dyn SpanExporter>),
+    /// The worker owns the exporter and receives the queued messages.
+    Running(mpsc::Sender<RouteMessage>),
+    ShutDown,
+}
+
+#[derive(Debug)]
+struct Destination {
+    state: RouteState,
+    timeout: Duration,
+}
+
+impl Destination {
+    fn new(exporter: Box<dyn SpanExporter>, timeout: Duration) -> Self {
+        Destination {
+            state: RouteState::Idle(exporter),
+            timeout,
+        }
+    }
+
+    /// The queue of the worker of the route, spawning it on first use.
+    ///
+    /// The worker is only spawned once spans are routed, so that the exporter
+    /// can be built outside of the runtime.
+    fn queue<R: Runtime>(
+        &mut self,
+        idx: usize,
+        runtime: &R,
+        queue_size: usize,
+    ) -> Option<&mut mpsc::Sender<RouteMessage>> {
+        if matches!(self.state, RouteState::Idle(_)) {
+            let (sender, receiver) = mpsc::channel(queue_size);
+            if let RouteState::Idle(exporter) =
+                mem::replace(&mut self.state, RouteState::Running(sender))
+            {
+                let worker = run_route(idx, exporter, self.timeout, runtime.clone(), receiver);
+                runtime.spawn(Box::pin(worker));
+            }
+        }
+        match &mut self.state {
+            RouteState::Running(sender) => Some(sender),
+            _ => None,
+        }
+    }
+
+    fn force_flush(
+        &mut self,
+        idx: usize,
+        runtime: &impl Runtime,
+        queue_size: usize,
+    ) -> BoxFuture<'static, ExportResult> {
+        if let RouteState::Idle(exporter) = &mut self.state {
+            return exporter.force_flush();
+        }
+        let (sender, receiver) = oneshot::channel();
+        let err = match self.queue(idx, runtime, queue_size) {
+            Some(queue) => match queue.try_send(RouteMessage::Flush(sender)) {
+                Ok(()) => {
+                    return Box::pin(async move {
+                        receiver
+                            .await
+                            .unwrap_or_else(|_| Err(TraceError::from("the route was shut down")))
+                    })
+                }
+                Err(_) => "the queue is full",
+            },
+            None => "the route was shut down",
+        };
+        Box::pin(future::ready(Err(TraceError::from(err))))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        match &mut self.state {
+            RouteState::Idle(exporter) => exporter.set_resource(resource),
+            RouteState::Running(sender) => {
+                let _ = sender.try_send(RouteMessage::SetResource(resource.clone()));
+            }
+            RouteState::ShutDown => {}
+        }
+    }
+
+    fn shutdown(&mut self) {
+        match mem::replace(&mut self.state, RouteState::ShutDown) {
+            RouteState::Idle(mut exporter) => exporter.shutdown(),
+            // the worker exports the queued batches and shuts down the exporter
+            RouteState::Running(mut sender) => sender.close_channel(),
+            RouteState::ShutDown => {}
+        }
+    }
+}
+
+/// Exports the batches queued for route `idx` one at a time, each within
+/// `timeout`, until the routing exporter is shut down or dropped.
+async fn run_route<R: Runtime>(
+    idx: usize,
+    mut exporter: Box<dyn SpanExporter>,
+    timeout: Duration,
+    runtime: R,
+    mut messages: mpsc::Receiver<RouteMessage>,
+) {
+    while let Some(message) = messages.next().await {
+        match message {
+            RouteMessage::Export(batch) => {
+                let export = exporter.export(batch);
+                let err = match future::select(export, runtime.delay(timeout)).await {
+                    Either::Left((Ok(()), _)) => continue,
+                    Either::Left((Err(err), _)) => format!("route {idx}: {err}"),
+                    Either::Right(_) => format!("route {idx}: export timed out"),
+                };
+                global::handle_error(TraceError::Other(err.into()));
+            }
+            RouteMessage::Flush(sender) => {
+                let flush = exporter.force_flush();
+                let result = match future::select(flush, runtime.delay(timeout)).await {
+                    Either::Left((result, _)) => result,
+                    Either::Right(_) => Err(TraceError::ExportTimedOut(timeout)),
+                };
+                let _ = sender.send(result);
+            }
+            RouteMessage::SetResource(resource) => exporter.set_resource(&resource),
+        }
+    }
+    exporter.shutdown();
+}
+
+struct Route {
+    predicate: SpanPredicate,
+    destination: Destination,
+}
+
+impl fmt::Debug for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Route")
+            .field("destination", &self.destination)
+            .finish()
+    }
+}
+
+/// A [`SpanExporter`] that routes each span to a destination exporter selected
+/// by a user-supplied predicate.
+///
+/// Routes are evaluated in the order they were added and each span is sent to
+/// the first route whose predicate matches. Spans that match no route are sent
+/// to the default route if one is configured, and dropped otherwise. Dropped
+/// spans are reported to the global error handler at most once a minute.
+///
+/// Each batch is split into one batch per route, queued for the route. Every
+/// route exports its queued batches from its own task spawned on the runtime,
+/// one at a time and each within the timeout of the route, so a failing or
+/// slow route only delays its own spans. Exports return once the batches are
+/// queued. When the queue of a route is full, the spans routed to it are
+/// dropped. Failed exports and dropped spans are reported to the global error
+/// handler and do not fail the export of the spans of the other routes.
+///
+/// Flushing the exporter waits for the batches queued for every route, and
+/// shutting it down lets every route export its queued batches before its
+/// exporter is shut down.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature="testing")]
+/// # {
+/// use opentelemetry::Key;
+/// use opentelemetry_sdk::export::trace::RoutingSpanExporter;
+/// use opentelemetry_sdk::runtime;
+/// use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+///
+/// let tenant_a = InMemorySpanExporter::default();
+/// let others = InMemorySpanExporter::default();
+/// let exporter = RoutingSpanExporter::builder(runtime::Tokio)
+///     .with_route(
+///         |span| {
+///             span.attributes
+///                 .iter()
+///                 .any(|kv| kv.key == Key::from_static_str("tenant") && kv.value.as_str() == "a")
+///         },
+///         tenant_a,
+///     )
+///     .with_default_route(others)
+///     .build();
+/// # drop(exporter);
+/// # }
+/// ```
+pub struct RoutingSpanExporter<R: Runtime> {
+    routes: Vec<Route>,
+    default_route: Option<Destination>,
+    queue_size: usize,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for RoutingSpanExporter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutingSpanExporter")
+            .field("routes", &self.routes)
+            .field("default_route", &self.default_route)
+            .field("queue_size", &self.queue_size)
+            .finish()
+    }
+}
+
+impl<R: Runtime> RoutingSpanExporter<R> {
+    /// Create a new [`RoutingSpanExporterBuilder`] using `runtime` to spawn
+    /// the worker of each route and enforce the per-route timeouts.
+    pub fn builder(runtime: R) -> RoutingSpanExporterBuilder<R> {
+        RoutingSpanExporterBuilder {
+            routes: Vec::new(),
+            default_route: None,
+            queue_size: DEFAULT_QUEUE_SIZE,
+            runtime,
+        }
+    }
+
+    fn destinations_mut(&mut self) -> impl Iterator<Item = &mut Destination> {
+        self.routes
+            .iter_mut()
+            .map(|route| &mut route.destination)
+            .chain(self.default_route.iter_mut())
+    }
+}
+
+impl<R: Runtime> SpanExporter for RoutingSpanExporter<R> {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        // one partition per route, followed by the default route
+        let mut partitions: Vec<Vec<SpanData>> = Vec::new();
+        partitions.resize_with(self.routes.len() + 1, Vec::new);
+
+        for span in batch {
+            let idx = self
+                .routes
+                .iter()
+                .position(|route| (route.predicate)(&span))
+                .unwrap_or(self.routes.len());
+            partitions[idx].push(span);
+        }
+
+        let unmatched = partitions.last().map_or(0, Vec::len);
+        if self.default_route.is_none() && unmatched > 0 {
+            global::handle_error_rate_limited(
+                "routing_span_exporter.unmatched",
+                UNMATCHED_REPORT_INTERVAL,
+                TraceError::Other(format!("dropped {unmatched} spans matching no route").into()),
+            );
+        }
+
+        let runtime = self.runtime.clone();
+        let queue_size = self.queue_size;
+        for (idx, (destination, partition)) in self.destinations_mut().zip(partitions).enumerate() {
+            if partition.is_empty() {
+                continue;
+            }
+            let count = partition.len();
+            let reason = match destination.queue(idx, &runtime, queue_size) {
+                Some(queue) => match queue.try_send(RouteMessage::Export(partition)) {
+                    Ok(()) => continue,
+                    Err(_) => "the queue is full",
+                },
+                None => "the route was shut down",
+            };
+            global::handle_error(TraceError::Other(
+                format!("route {idx}: dropped {count} spans, {reason}").into(),
+            ));
+        }
+
+        Box::pin(future::ready(Ok(())))
+    }
+
+    fn shutdown(&mut self) {
+        for destination in self.destinations_mut() {
+            destination.shutdown();
+        }
+    }
+
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        let runtime = self.runtime.clone();
+        let queue_size = self.queue_size;
+        let flushes = self
+            .destinations_mut()
+            .enumerate()
+            .map(|(idx, destination)| destination.force_flush(idx, &runtime, queue_size))
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            let errs = future::join_all(flushes)
+                .await
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, res)| res.err().map(|err| format!("route {idx}: {err}")))
+                .collect::<Vec<_>>();
+
+            if errs.is_empty() {
+                Ok(())
+            } else {
+                Err(TraceError::Other(errs.join(", ").into()))
+            }
+        })
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        for destination in self.destinations_mut() {
+            destination.set_resource(resource);
+        }
+    }
+}
+
+/// Builder for [`RoutingSpanExporter`].
+pub struct RoutingSpanExporterBuilder<R: Runtime> {
+    routes: Vec<Route>,
+    default_route: Option<Destination>,
+    queue_size: usize,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for RoutingSpanExporterBuilder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutingSpanExporterBuilder")
+            .field("routes", &self.routes)
+            .field("default_route", &self.default_route)
+            .field("queue_size", &self.queue_size)
+            .finish()
+    }
+}
+
+impl<R: Runtime> RoutingSpanExporterBuilder<R> {
+    /// Add a route sending spans matching `predicate` to `exporter`, using the
+    /// default export timeout of 30 seconds.
+    pub fn with_route<P, E>(self, predicate: P, exporter: E) -> Self
+    where
+        P: Fn(&SpanData) -> bool + Send + Sync + 'static,
+        E: SpanExporter + 'static,
+    {
+        self.with_route_timeout(predicate, exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Add a route sending spans matching `predicate` to `exporter`, which
+    /// must finish exporting a batch within `timeout`.
+    pub fn with_route_timeout<P, E>(mut self, predicate: P, exporter: E, timeout: Duration) -> Self
+    where
+        P: Fn(&SpanData) -> bool + Send + Sync + 'static,
+        E: SpanExporter + 'static,
+    {
+        self.routes.push(Route {
+            predicate: Box::new(predicate),
+            destination: Destination::new(Box::new(exporter), timeout),
+        });
+        self
+    }
+
+    /// Set the exporter receiving spans that match no route, using the default
+    /// export timeout of 30 seconds.
+    ///
+    /// If no default route is configured, such spans are dropped.
+    pub fn with_default_route<E: SpanExporter + 'static>(self, exporter: E) -> Self {
+        self.with_default_route_timeout(exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Set the exporter receiving spans that match no route, which must finish
+    /// exporting a batch within `timeout`.
+    pub fn with_default_route_timeout<E: SpanExporter + 'static>(
+        mut self,
+        exporter: E,
+        timeout: Duration,
+    ) -> Self {
+        self.default_route = Some(Destination::new(Box::new(exporter), timeout));
+        self
+    }
+
+    /// Set the maximum number of batches queued for each route, 32 by default.
+    ///
+    /// Batches routed to a route whose queue is full are dropped.
+    pub fn with_queue_size(self, queue_size: usize) -> Self {
+        RoutingSpanExporterBuilder { queue_size, ..self }
+    }
+
+    /// Build the [`RoutingSpanExporter`].
+    pub fn build(self) -> RoutingSpanExporter<R> {
+        RoutingSpanExporter {
+            routes: self.routes,
+            default_route: self.default_route,
+            queue_size: self.queue_size,
+            runtime: self.runtime,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::runtime;
+    use crate::testing::trace::{new_test_export_span_data, InMemorySpanExporter};
+    use futures_util::future::pending;
+
+    fn span_named(name: &'static str) -> SpanData {
+        let mut span = new_test_export_span_data();
+        span.name = name.into();
+        span
+    }
+
+    #[derive(Debug)]
+    struct HangingExporter;
+
+    impl SpanExporter for HangingExporter {
+        fn export(&mut self, _batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            Box::pin(pending())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn routes_spans_by_predicate() {
+        let first = InMemorySpanExporter::default();
+        let second = InMemorySpanExporter::default();
+        let fallback = InMemorySpanExporter::default();
+        let mut exporter = RoutingSpanExporter::builder(runtime::Tokio)
+            .with_route(|span| span.name.starts_with("db"), first.clone())
+            .with_route(|span| span.name.starts_with("http"), second.clone())
+            .with_default_route(fallback.clone())
+            .build();
+
+        let result = exporter
+            .export(vec![
+                span_named("db.query"),
+                span_named("http.request"),
+                span_named("db.commit"),
+                span_named("other"),
+            ])
+            .await;
+
+        assert!(result.is_ok());
+        exporter.force_flush().await.unwrap();
+        let names = |exporter: &InMemorySpanExporter| {
+            exporter
+                .get_finished_spans()
+                .unwrap()
+                .into_iter()
+                .map(|span| span.name.into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&first), vec!["db.query", "db.commit"]);
+        assert_eq!(names(&second), vec!["http.request"]);
+        assert_eq!(names(&fallback), vec!["other"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn drops_unmatched_spans_without_default_route() {
+        let first = InMemorySpanExporter::default();
+        let mut exporter = RoutingSpanExporter::builder(runtime::Tokio)
+            .with_route(|span| span.name == "matched", first.clone())
+            .build();
+
+        let result = exporter
+            .export(vec![span_named("matched"), span_named("unmatched")])
+            .await;
+
+        assert!(result.is_ok());
+        exporter.force_flush().await.unwrap();
+        assert_eq!(first.get_finished_spans().unwrap().len(), 1);
+        assert!(global::error_report_stats().contains_key("routing_span_exporter.unmatched"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn slow_route_does_not_block_other_routes() {
+        let fast = InMemorySpanExporter::default();
+        let mut exporter = RoutingSpanExporter::builder(runtime::Tokio)
+            .with_route(|span| span.name == "slow", HangingExporter)
+            .with_default_route(fast.clone())
+            .build();
+
+        // the slow route never finishes its first export
+        for _ in 0..3 {
+            let result = tokio::time::timeout(
+                Duration::from_secs(1),
+                exporter.export(vec![span_named("slow"), span_named("fast")]),
+            )
+            .await;
+            assert!(matches!(result, Ok(Ok(()))));
+        }
+
+        for _ in 0..500 {
+            if fast.get_finished_spans().unwrap().len() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(fast.get_finished_spans().unwrap().len(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn slow_route_times_out() {
+        let fast = InMemorySpanExporter::default();
+        let mut exporter = RoutingSpanExporter::builder(runtime::Tokio)
+            .with_route_timeout(
+                |span| span.name == "slow",
+                HangingExporter,
+                Duration::from_millis(10),
+            )
+            .with_default_route(fast.clone())
+            .build();
+
+        let result = exporter
+            .export(vec![span_named("slow"), span_named("fast")])
+            .await;
+
+        assert!(result.is_ok());
+        exporter.force_flush().await.unwrap();
+        assert_eq!(fast.get_finished_spans().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+use crate::runtime::Runtime;
+use crate::Resource;
+use futures_util::future::{self, BoxFuture, Either};
+use opentelemetry::trace::TraceError;
+use std::fmt;
+use std::time::Duration;
+
+/// Default maximum duration a single child exporter may take to export a batch.
+const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct TeeChild {
+    exporter: Box<dyn SpanExporter>,
+    timeout: Duration,
+}
+
+/// A [`SpanExporter`] that exports every batch to multiple child exporters.
+///
+/// Each child receives its own copy of every batch and is exported to
+/// concurrently, with its own timeout. A failing or slow child does not prevent
+/// the other children from receiving the batch. If any child fails or times
+/// out, the returned error lists each failure by the child's index.
+///
+/// This is useful to dual-write telemetry to several destinations, e.g. while
+/// migrating from one backend to another.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature="testing")]
+/// # {
+/// use opentelemetry_sdk::export::trace::TeeSpanExporter;
+/// use opentelemetry_sdk::runtime;
+/// use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+/// use std::time::Duration;
+///
+/// let exporter = TeeSpanExporter::builder(runtime::Tokio)
+///     .with_exporter(InMemorySpanExporter::default())
+///     .with_exporter_timeout(InMemorySpanExporter::default(), Duration::from_secs(5))
+///     .build();
+/// # drop(exporter);
+/// # }
+/// ```
+pub struct TeeSpanExporter<R: Runtime> {
+    children: Vec<TeeChild>,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for TeeSpanExporter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeSpanExporter")
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl<R: Runtime> TeeSpanExporter<R> {
+    /// Create a new [`TeeSpanExporterBuilder`] using `runtime` to enforce the
+    /// per-exporter timeouts.
+    pub fn builder(runtime: R) -> TeeSpanExporterBuilder<R> {
+        TeeSpanExporterBuilder {
+            children: Vec::new(),
+            runtime,
+        }
+    }
+}
+
+impl<R: Runtime> SpanExporter for TeeSpanExporter<R> {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let exports = self
+            .children
+            .iter_mut()
+            .enumerate()
+            .map(|(idx, child)| {
+                let export = child.exporter.export(batch.clone());
+                let timeout = self.runtime.delay(child.timeout);
+                async move {
+                    match future::select(export, timeout).await {
+                        Either::Left((Ok(()), _)) => None,
+                        Either::Left((Err(err), _)) => Some(format!("exporter {idx}: {err}")),
+                        Either::Right(_) => Some(format!("exporter {idx}: export timed out")),
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            let errs = future::join_all(exports)
+                .await
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+            if errs.is_empty() {
+                Ok(())
+            } else {
+                Err(TraceError::Other(errs.join(", ").into()))
+            }
+        })
+    }
+
+    fn shutdown(&mut self) {
+        for child in &mut self.children {
+            child.exporter.shutdown();
+        }
+    }
+
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        let flushes = self
+            .children
+            .iter_mut()
+            .map(|child| child.exporter.force_flush())
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            let errs = future::join_all(flushes)
+                .await
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, res)| res.err().map(|err| format!("exporter {idx}: {err}")))
+                .collect::<Vec<_>>();
+
+            if errs.is_empty() {
+                Ok(())
+            } else {
+                Err(TraceError::Other(errs.join(", ").into()))
+            }
+        })
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        for child in &mut self.children {
+            child.exporter.set_resource(resource);
+        }
+    }
+}
+
+/// Builder for [`TeeSpanExporter`].
+pub struct TeeSpanExporterBuilder<R: Runtime> {
+    children: Vec<TeeChild>,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for TeeSpanExporterBuilder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeSpanExporterBuilder")
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl<R: Runtime> TeeSpanExporterBuilder<R> {
+    /// Add a child exporter using the default export timeout of 30 seconds.
+    pub fn with_exporter<E: SpanExporter + 'static>(self, exporter: E) -> Self {
+        self.with_exporter_timeout(exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Add a child exporter that must finish exporting a batch within `timeout`.
+    pub fn with_exporter_timeout<E: SpanExporter + 'static>(
+        mut self,
+        exporter: E,
+        timeout: Duration,
+    ) -> Self {
+        self.children.push(TeeChild {
+            exporter: Box::new(exporter),
+            timeout,
+        });
+        self
+    }
+
+    /// Build the [`TeeSpanExporter`].
+    pub fn build(self) -> TeeSpanExporter<R> {
+        TeeSpanExporter {
+            children: self.children,
+            runtime: self.runtime,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::runtime;
+    use crate::testing::trace::{
+        new_test_export_span_data, FailingSpanExporter, InMemorySpanExporter,
+    };
+
+    #[derive(Debug)]
+    struct PendingSpanExporter;
+
+    impl SpanExporter for PendingSpanExporter {
+        fn export(&mut self, _batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            Box::pin(future::pending())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn exports_to_all_children() {
+        let first = InMemorySpanExporter::default();
+        let second = InMemorySpanExporter::default();
+        let mut exporter = TeeSpanExporter::builder(runtime::Tokio)
+            .with_exporter(first.clone())
+            .with_exporter(second.clone())
+            .build();
+
+        let result = exporter.export(vec![new_test_export_span_data()]).await;
+
+        assert!(result.is_ok());
+        assert_eq!(first.get_finished_spans().unwrap().len(), 1);
+        assert_eq!(second.get_finished_spans().unwrap().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn failing_children_do_not_affect_others() {
+        let healthy = InMemorySpanExporter::default();
+        let mut exporter = TeeSpanExporter::builder(runtime::Tokio)
+            .with_exporter(FailingSpanExporter::new())
+            .with_exporter_timeout(PendingSpanExporter, Duration::from_millis(10))
+            .with_exporter(healthy.clone())
+            .build();
+
+        let result = exporter.export(vec![new_test_export_span_data()]).await;
+
+        let err = result.expect_err("failing children should be reported");
+        assert!(err.to_string().contains("exporter 0"));
+        assert!(err.to_string().contains("exporter 1: export timed out"));
+        assert_eq!(healthy.get_finished_spans().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,50 @@
+//! Flushing and shutting down providers when their guard is dropped.
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use opentelemetry::global;
+
+/// The default time a provider guard waits for its provider to shut down.
+pub(crate) const DEFAULT_GUARD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs `exit`, which flushes and shuts down the provider `name`, on a
+/// dedicated thread and waits at most `timeout` for it to complete.
+///
+/// The dropping thread may drive the runtime the exporters depend on, e.g. a
+/// current thread Tokio runtime, so it never blocks longer than `timeout`. A
+/// failure or panic of `exit` is reported to the global error handler instead
+/// of panicking in a thread which may already be unwinding.
+pub(crate) fn shutdown_on_drop<E>(
+    name: &'static str,
+    timeout: Duration,
+    exit: impl FnOnce() -> Result<(), E> + Send + 'static,
+) where
+    E: Into<global::Error> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let spawned = thread::Builder::new()
+        .name(format!("opentelemetry-{name}-shutdown"))
+        .spawn(move || {
+            let _ = sender.send(exit());
+        });
+    if let Err(err) = spawned {
+        global::handle_error(global::Error::Other(format!(
+            "failed to shut down the {name} provider: {err}"
+        )));
+        return;
+    }
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => global::handle_error(err),
+        Err(RecvTimeoutError::Timeout) => global::handle_error(global::Error::Other(format!(
+            "the {name} provider did not shut down within {timeout:?}"
+        ))),
+        Err(RecvTimeoutError::Disconnected) => global::handle_error(global::Error::Other(format!(
+            "the {name} provider panicked while shutting down"
+        ))),
+    }
+}
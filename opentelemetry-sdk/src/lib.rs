@@ -75,6 +75,23 @@
 //! [examples]: https://github.com/open-telemetry/opentelemetry-rust/tree/main/examples
 //! [`metrics`]: https://docs.rs/opentelemetry/latest/opentelemetry/metrics/index.html
 //!
+//! # Shutting Down With Guards
+//!
+//! The `guard` method of the tracer, meter and logger providers returns a guard
+//! which flushes and shuts down the provider when dropped, e.g. when `main`
+//! returns or panics. The provider is shut down on a dedicated thread, and
+//! dropping the guard waits at most the timeout of the guard for the shutdown
+//! to complete, 10 seconds by default. Errors, timeouts and panics of the
+//! shutdown are reported to the global error handler.
+//!
+//! Dropping a guard blocks the dropping thread. If the exporters of the
+//! provider depend on a runtime driven by that thread, e.g. an exporter using
+//! a [tokio] current thread runtime and a guard dropped at the end of its
+//! `block_on`, the exporters cannot make progress while the guard waits: the
+//! drop blocks for the whole timeout and the pending telemetry is lost. Use a
+//! multi-thread runtime with such exporters, or lower the timeout of the guard
+//! with its `with_timeout` method.
+//!
 //! ## Crate Feature Flags
 //!
 //! The following feature flags can used to control the telemetry signals to use:
@@ -87,15 +104,36 @@
 //!
 //! * `jaeger_remote_sampler`: Enables the [Jaeger remote sampler](https://www.jaegertracing.io/docs/1.53/sampling/).
 //!
+//! For `metrics` the following feature flags are available:
+//!
+//! * `experimental_metrics_measurement_processor`: Enables measurement processors
+//!   transforming the attributes of measurements before aggregation, including
+//!   the `BaggageMetricsAttributesExtractor` copying baggage entries into
+//!   attributes. This API is experimental and may change in minor releases.
+//! * `experimental_metrics_custom_aggregation`: Enables user-defined
+//!   aggregations of metric streams with the `Aggregator` trait, selected by
+//!   views. This API is experimental and may change in minor releases.
+//!
 //! For `logs` the following feature flags are available:
 //!
 //! * `logs_level_enabled`: control the log level
 //!
+//! The following feature flags are available for all signals:
+//!
+//! * `serialize`: Implements serde's `Serialize` and `Deserialize` for
+//!   exported `SpanData` and `LogRecord`, e.g. to snapshot test the telemetry
+//!   emitted by instrumentation libraries.
+//! * `diagnostics_signal`: Dumps a [`PipelineDiagnostics`] report and force
+//!   flushes the providers when the process receives `SIGUSR2`, or `SIGBREAK`
+//!   on Windows.
+//!
 //! Support for recording and exporting telemetry asynchronously and perform
 //! metrics aggregation can be added via the following flags:
 //!
 //! * `rt-tokio`: Spawn telemetry tasks using [tokio]'s multi-thread runtime.
 //! * `rt-tokio-current-thread`: Spawn telemetry tasks on a separate runtime so that the main runtime won't be blocked.
+//! * `rt-tokio-dedicated-thread`: Spawn telemetry tasks on dedicated threads
+//!   which can be named, pinned to cores and given a priority.
 //! * `rt-async-std`: Spawn telemetry tasks using [async-std]'s runtime.
 //!
 //! [tokio]: https://crates.io/crates/tokio
@@ -120,7 +158,17 @@
 )]
 #![cfg_attr(test, deny(warnings))]
 
+#[cfg(any(feature = "trace", feature = "logs"))]
+mod attribute_sanitizer;
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+mod budget;
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+mod diagnostics;
+mod dropped;
+mod error;
 pub mod export;
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+mod guard;
 mod instrumentation;
 #[cfg(feature = "logs")]
 #[cfg_attr(docsrs, doc(cfg(feature = "logs")))]
@@ -133,6 +181,9 @@ pub mod metrics;
 pub mod propagation;
 pub mod resource;
 pub mod runtime;
+#[cfg(feature = "serialize")]
+mod serialize;
+mod shutdown;
 #[cfg(any(feature = "testing", test))]
 #[cfg_attr(docsrs, doc(cfg(any(feature = "testing", test))))]
 pub mod testing;
@@ -145,6 +196,17 @@ pub mod trace;
 #[doc(hidden)]
 pub mod util;
 
+#[cfg(any(feature = "trace", feature = "logs"))]
+pub use attribute_sanitizer::AttributeSanitizer;
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub use budget::{BudgetPriority, TelemetryBudget};
+#[cfg(feature = "diagnostics_signal")]
+pub use diagnostics::{DiagnosticsOutput, DiagnosticsSignalHandler};
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub use diagnostics::{PipelineDiagnostics, ProcessorDiagnostics};
+pub use dropped::DroppedTelemetry;
+pub use error::BuildError;
 pub use instrumentation::{InstrumentationLibrary, Scope};
 #[doc(inline)]
 pub use resource::Resource;
+pub use shutdown::{ShutdownError, ShutdownResult};
@@ -0,0 +1,102 @@
+//! Logs SDK Configuration
+//!
+//! Configuration represents the global logging configuration, overrides can
+//! be set for the default OpenTelemetry log record limits.
+use crate::logs::LogLimits;
+use std::env;
+use std::str::FromStr;
+
+/// Logger provider configuration
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Config {
+    /// log record limits
+    pub log_limits: LogLimits,
+}
+
+impl Config {
+    /// Specify the number of attributes to be recorded per log record.
+    pub fn with_max_attributes_per_log_record(mut self, max_attributes: u32) -> Self {
+        self.log_limits.max_attributes_per_log_record = max_attributes;
+        self
+    }
+
+    /// Specify the max length in bytes of attribute string values, see
+    /// [`LogLimits::max_attribute_value_length`].
+    pub fn with_max_attribute_value_length(mut self, max_length: u32) -> Self {
+        self.log_limits.max_attribute_value_length = Some(max_length);
+        self
+    }
+
+    /// Specify all limits via the log_limits
+    pub fn with_log_limits(mut self, log_limits: LogLimits) -> Self {
+        self.log_limits = log_limits;
+        self
+    }
+}
+
+impl Default for Config {
+    /// Create default logs sdk configuration.
+    fn default() -> Self {
+        let mut config = Config {
+            log_limits: LogLimits::default(),
+        };
+
+        if let Some(max_attributes) = env::var("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT")
+            .ok()
+            .and_then(|count_limit| u32::from_str(&count_limit).ok())
+        {
+            config.log_limits.max_attributes_per_log_record = max_attributes;
+        }
+
+        if let Some(max_length) = env::var("OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT")
+            .ok()
+            .and_then(|length_limit| u32::from_str(&length_limit).ok())
+        {
+            config.log_limits.max_attribute_value_length = Some(max_length);
+        }
+
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        temp_env::with_vars_unset(
+            [
+                "OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT",
+                "OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+            ],
+            || {
+                let config = Config::default();
+                assert_eq!(config.log_limits, LogLimits::default());
+            },
+        );
+    }
+
+    #[test]
+    fn test_config_from_env() {
+        temp_env::with_vars(
+            [
+                ("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT", Some("10")),
+                ("OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT", Some("256")),
+            ],
+            || {
+                let config = Config::default();
+                assert_eq!(config.log_limits.max_attributes_per_log_record, 10);
+                assert_eq!(config.log_limits.max_attribute_value_length, Some(256));
+            },
+        );
+
+        temp_env::with_var("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT", Some("x"), || {
+            assert_eq!(
+                Config::default().log_limits.max_attributes_per_log_record,
+                128
+            );
+        });
+    }
+}
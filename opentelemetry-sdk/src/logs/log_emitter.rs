@@ -1,8 +1,13 @@
-use super::{BatchLogProcessor, LogProcessor, LogRecord, SimpleLogProcessor, TraceContext};
+use super::{
+    BatchLogProcessor, Config, LogLimits, LogProcessor, LogRecord, SimpleLogProcessor, TraceContext,
+};
 use crate::{
+    dropped::DroppedCounters,
     export::logs::{LogData, LogExporter},
+    guard::{self, DEFAULT_GUARD_TIMEOUT},
     runtime::RuntimeChannel,
-    Resource,
+    shutdown::{self, ShutdownError, ShutdownResult},
+    AttributeSanitizer, DroppedTelemetry, ProcessorDiagnostics, Resource,
 };
 use opentelemetry::{
     global,
@@ -14,19 +19,28 @@ use opentelemetry::{
 #[cfg(feature = "logs_level_enabled")]
 use opentelemetry::logs::Severity;
 
+use futures_util::future::{self, BoxFuture};
+use smallvec::SmallVec;
 use std::{
     borrow::Cow,
+    future::Future,
     sync::{atomic::Ordering, Arc},
 };
-use std::{sync::atomic::AtomicBool, time::SystemTime};
+use std::{
+    sync::atomic::AtomicBool,
+    time::{Duration, Instant, SystemTime},
+};
 
 use once_cell::sync::Lazy;
 
 // a no nop logger provider used as placeholder when the provider is shutdown
 static NOOP_LOGGER_PROVIDER: Lazy<LoggerProvider> = Lazy::new(|| LoggerProvider {
     inner: Arc::new(LoggerProviderInner {
-        processors: Vec::new(),
+        processors: SmallVec::new(),
         resource: Resource::empty(),
+        attribute_sanitizer: None,
+        log_limits: LogLimits::default(),
+        dropped: DroppedCounters::default(),
     }),
     is_shutdown: Arc::new(AtomicBool::new(true)),
 });
@@ -90,7 +104,7 @@ impl LoggerProvider {
         Builder::default()
     }
 
-    pub(crate) fn log_processors(&self) -> &[Box<dyn LogProcessor>] {
+    pub(crate) fn log_processors(&self) -> &[RegisteredLogProcessor] {
         &self.inner.processors
     }
 
@@ -98,12 +112,60 @@ impl LoggerProvider {
         &self.inner.resource
     }
 
-    /// Force flush all remaining logs in log processors and return results.
-    pub fn force_flush(&self) -> Vec<LogResult<()>> {
-        self.log_processors()
+    pub(crate) fn attribute_sanitizer(&self) -> Option<&AttributeSanitizer> {
+        self.inner.attribute_sanitizer.as_ref()
+    }
+
+    pub(crate) fn log_limits(&self) -> &LogLimits {
+        &self.inner.log_limits
+    }
+
+    /// The log records dropped by the processors of this provider, and the
+    /// attributes dropped from its records by the log limits.
+    ///
+    /// The counts keep accumulating over the lifetime of the provider and
+    /// remain available after it was shut down.
+    pub fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.inner.dropped.snapshot()
+            + self
+                .log_processors()
+                .iter()
+                .map(|processor| processor.dropped_telemetry())
+                .sum()
+    }
+
+    /// Force flush all remaining logs in log processors, e.g. before the
+    /// process forks or is frozen.
+    ///
+    /// The processors are flushed one after the other in the order they were
+    /// registered. All processors are flushed even if some of them fail, their
+    /// errors are combined into the returned error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_sdk::logs::LoggerProvider;
+    ///
+    /// let provider = LoggerProvider::builder().build();
+    ///
+    /// // emit log records
+    ///
+    /// if let Err(err) = provider.force_flush() {
+    ///     eprintln!("failed to flush log records: {err}");
+    /// }
+    /// ```
+    pub fn force_flush(&self) -> LogResult<()> {
+        let errs: Vec<_> = self
+            .log_processors()
             .iter()
-            .map(|processor| processor.force_flush())
-            .collect()
+            .filter_map(|processor| processor.force_flush().err())
+            .collect();
+
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(LogError::Other(format!("{errs:?}").into()))
+        }
     }
 
     /// Shuts down this `LoggerProvider`
@@ -131,12 +193,210 @@ impl LoggerProvider {
             Err(LogError::Other("logger provider already shut down".into()))
         }
     }
+
+    /// Shuts down this `LoggerProvider`, waiting at most `timeout` for its
+    /// processors to export the remaining log records.
+    ///
+    /// Processors are shut down in the order they were registered, each with
+    /// the time left until the timeout. All processors are shut down even if
+    /// some of them fail or time out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use opentelemetry_sdk::{logs::LoggerProvider, ShutdownError};
+    ///
+    /// let provider = LoggerProvider::builder().build();
+    /// match provider.shutdown_with_timeout(Duration::from_secs(5)) {
+    ///     Ok(()) => {}
+    ///     Err(ShutdownError::Timeout(_)) => eprintln!("some logs may be lost"),
+    ///     Err(err) => eprintln!("failed to shut down: {err}"),
+    /// }
+    /// ```
+    pub fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        if self
+            .is_shutdown
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(ShutdownError::AlreadyShutdown);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let results: Vec<_> = self
+            .inner
+            .processors
+            .iter()
+            .map(|processor| processor.shutdown_with_timeout(shutdown::remaining(deadline)))
+            .collect();
+        shutdown::combine(results)
+    }
+
+    /// Returns a guard flushing and shutting down this provider when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_sdk::logs::LoggerProvider;
+    ///
+    /// fn main() {
+    ///     let provider = LoggerProvider::builder().build();
+    ///     let _guard = provider.guard();
+    ///
+    ///     // emit logs..
+    ///
+    ///     // the logs are exported when `_guard` goes out of scope, even if
+    ///     // `main` panics
+    /// }
+    /// ```
+    pub fn guard(&self) -> LoggerProviderGuard {
+        LoggerProviderGuard {
+            provider: self.clone(),
+            timeout: DEFAULT_GUARD_TIMEOUT,
+        }
+    }
+}
+
+/// Flushes and shuts down a [`LoggerProvider`] when dropped.
+///
+/// Created with [`LoggerProvider::guard`]. See [shutting down with
+/// guards](crate#shutting-down-with-guards) for how the provider is shut down,
+/// and how long dropping the guard may block.
+#[must_use = "the provider is shut down when the guard is dropped"]
+#[derive(Debug)]
+pub struct LoggerProviderGuard {
+    provider: LoggerProvider,
+    timeout: Duration,
+}
+
+impl LoggerProviderGuard {
+    /// Sets the maximum time to wait for the provider to shut down when the
+    /// guard is dropped, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The provider shut down by this guard.
+    pub fn provider(&self) -> &LoggerProvider {
+        &self.provider
+    }
+}
+
+impl Drop for LoggerProviderGuard {
+    fn drop(&mut self) {
+        if self.provider.is_shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let provider = self.provider.clone();
+        guard::shutdown_on_drop("logger", self.timeout, move || {
+            if let Err(err) = provider.force_flush() {
+                global::handle_error(err);
+            }
+            provider.shutdown()
+        });
+    }
+}
+
+/// The number of processors stored inline by a [`LoggerProvider`], most
+/// providers have one or two processors.
+const INLINE_PROCESSORS: usize = 2;
+
+/// The processors of a [`LoggerProvider`].
+type LogProcessors = SmallVec<[RegisteredLogProcessor; INLINE_PROCESSORS]>;
+
+/// A processor registered with a [`LoggerProvider`].
+///
+/// The processors of [`Builder::with_simple_exporter`] are dispatched
+/// statically, which avoids a virtual call per processor for every emitted
+/// record. Processors registered with [`Builder::with_log_processor`] are
+/// boxed.
+#[derive(Debug)]
+pub(crate) enum RegisteredLogProcessor {
+    Simple(SimpleLogProcessor),
+    Boxed(Box<dyn LogProcessor>),
+}
+
+impl LogProcessor for RegisteredLogProcessor {
+    fn emit(&self, data: &mut LogData) {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.emit(data),
+            RegisteredLogProcessor::Boxed(processor) => processor.emit(data),
+        }
+    }
+
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.emit_with_ack(data),
+            RegisteredLogProcessor::Boxed(processor) => processor.emit_with_ack(data),
+        }
+    }
+
+    fn force_flush(&self) -> LogResult<()> {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.force_flush(),
+            RegisteredLogProcessor::Boxed(processor) => processor.force_flush(),
+        }
+    }
+
+    fn shutdown(&self) -> LogResult<()> {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.shutdown(),
+            RegisteredLogProcessor::Boxed(processor) => processor.shutdown(),
+        }
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.shutdown_with_timeout(timeout),
+            RegisteredLogProcessor::Boxed(processor) => processor.shutdown_with_timeout(timeout),
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => {
+                processor.event_enabled(level, target, name)
+            }
+            RegisteredLogProcessor::Boxed(processor) => {
+                processor.event_enabled(level, target, name)
+            }
+        }
+    }
+
+    fn set_resource(&self, resource: &Resource) {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.set_resource(resource),
+            RegisteredLogProcessor::Boxed(processor) => processor.set_resource(resource),
+        }
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.dropped_telemetry(),
+            RegisteredLogProcessor::Boxed(processor) => processor.dropped_telemetry(),
+        }
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        match self {
+            RegisteredLogProcessor::Simple(processor) => processor.diagnostics(),
+            RegisteredLogProcessor::Boxed(processor) => processor.diagnostics(),
+        }
+    }
 }
 
 #[derive(Debug)]
 struct LoggerProviderInner {
-    processors: Vec<Box<dyn LogProcessor>>,
+    processors: LogProcessors,
     resource: Resource,
+    attribute_sanitizer: Option<AttributeSanitizer>,
+    log_limits: LogLimits,
+    // attributes dropped from records by the log limits
+    dropped: DroppedCounters,
 }
 
 impl Drop for LoggerProviderInner {
@@ -152,15 +412,22 @@ impl Drop for LoggerProviderInner {
 #[derive(Debug, Default)]
 /// Builder for provider attributes.
 pub struct Builder {
-    processors: Vec<Box<dyn LogProcessor>>,
+    processors: LogProcessors,
     resource: Option<Resource>,
+    attribute_sanitizer: Option<AttributeSanitizer>,
+    config: Option<Config>,
+    // observes the dropped and queued log records of the built provider
+    #[cfg(feature = "metrics")]
+    meter: Option<opentelemetry::metrics::Meter>,
 }
 
 impl Builder {
     /// The `LogExporter` that this provider should use.
     pub fn with_simple_exporter<T: LogExporter + 'static>(self, exporter: T) -> Self {
         let mut processors = self.processors;
-        processors.push(Box::new(SimpleLogProcessor::new(Box::new(exporter))));
+        processors.push(RegisteredLogProcessor::Simple(SimpleLogProcessor::new(
+            Box::new(exporter),
+        )));
 
         Builder { processors, ..self }
     }
@@ -178,7 +445,7 @@ impl Builder {
     /// The `LogProcessor` that this provider should use.
     pub fn with_log_processor<T: LogProcessor + 'static>(self, processor: T) -> Self {
         let mut processors = self.processors;
-        processors.push(Box::new(processor));
+        processors.push(RegisteredLogProcessor::Boxed(Box::new(processor)));
 
         Builder { processors, ..self }
     }
@@ -191,14 +458,60 @@ impl Builder {
         }
     }
 
+    /// The configuration, e.g. the [`LogLimits`], of this provider.
+    pub fn with_config(self, config: Config) -> Self {
+        Builder {
+            config: Some(config),
+            ..self
+        }
+    }
+
+    /// Sanitize the string values of log record attributes with `sanitizer`
+    /// when records are emitted, before they reach the processors.
+    pub fn with_attribute_sanitizer(self, sanitizer: AttributeSanitizer) -> Self {
+        Builder {
+            attribute_sanitizer: Some(sanitizer),
+            ..self
+        }
+    }
+
+    /// Record metrics about the provider itself with meters of `meter_provider`.
+    ///
+    /// The `otel.sdk.log.dropped` counter records the log records lost by the
+    /// log processors, with a `reason` attribute of `queue_full`,
+    /// `export_failed` or `memory_budget`, like
+    /// [`LoggerProvider::dropped_telemetry`]. The
+    /// `otel.sdk.processor.log.queue.size` gauge records the log records
+    /// waiting in the queues of batch log processors.
+    #[cfg(feature = "metrics")]
+    pub fn with_meter_provider<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        let meter = meter_provider.versioned_meter(
+            "opentelemetry_sdk",
+            Some(env!("CARGO_PKG_VERSION")),
+            None::<&'static str>,
+            None,
+        );
+        Builder {
+            meter: Some(meter),
+            ..self
+        }
+    }
+
     /// Create a new provider from this configuration.
     pub fn build(self) -> LoggerProvider {
         let resource = self.resource.unwrap_or_default();
+        let config = self.config.unwrap_or_default();
 
         let logger_provider = LoggerProvider {
             inner: Arc::new(LoggerProviderInner {
                 processors: self.processors,
                 resource,
+                attribute_sanitizer: self.attribute_sanitizer,
+                log_limits: config.log_limits,
+                dropped: DroppedCounters::default(),
             }),
             is_shutdown: Arc::new(AtomicBool::new(false)),
         };
@@ -207,6 +520,26 @@ impl Builder {
         for processor in logger_provider.log_processors() {
             processor.set_resource(logger_provider.resource());
         }
+
+        #[cfg(feature = "metrics")]
+        if let Some(meter) = &self.meter {
+            let inner = Arc::downgrade(&logger_provider.inner);
+            crate::dropped::observe_processors(
+                meter,
+                "log",
+                "{log_record}",
+                "Log records",
+                move || {
+                    let inner = inner.upgrade()?;
+                    let processors = &inner.processors;
+                    Some((
+                        processors.iter().map(|p| p.dropped_telemetry()).sum(),
+                        ProcessorDiagnostics::combine(processors.iter().map(|p| p.diagnostics())),
+                    ))
+                },
+            );
+        }
+
         logger_provider
     }
 }
@@ -240,37 +573,96 @@ impl Logger {
     pub fn instrumentation_library(&self) -> &InstrumentationLibrary {
         &self.instrumentation_lib
     }
-}
 
-impl opentelemetry::logs::Logger for Logger {
-    type LogRecord = LogRecord;
+    /// Emit a `LogRecord` which must not be lost, e.g. an audit log.
+    ///
+    /// The returned future resolves once every log processor has handed the
+    /// record to its exporter and the export succeeded, or with the first
+    /// error which prevented it. Records are still emitted if the future is
+    /// dropped without being awaited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::logs::{LogRecord as _, Logger as _, LoggerProvider as _};
+    /// use opentelemetry_sdk::logs::LoggerProvider;
+    ///
+    /// # async fn audit(provider: LoggerProvider) -> opentelemetry::logs::LogResult<()> {
+    /// let logger = provider.logger("audit");
+    /// let mut record = logger.create_log_record();
+    /// record.set_body("user deleted".into());
+    /// logger.emit_with_ack(record).await
+    /// # }
+    /// ```
+    pub fn emit_with_ack(
+        &self,
+        record: LogRecord,
+    ) -> impl Future<Output = LogResult<()>> + Send + 'static {
+        let mut data = self.log_data(record);
+        let acks = future::join_all(
+            self.provider()
+                .log_processors()
+                .iter()
+                .map(|p| p.emit_with_ack(&mut data)),
+        );
 
-    fn create_log_record(&self) -> Self::LogRecord {
-        LogRecord::default()
+        async move { acks.await.into_iter().collect() }
     }
 
-    /// Emit a `LogRecord`.
-    fn emit(&self, record: Self::LogRecord) {
-        let provider = self.provider();
-        let processors = provider.log_processors();
-        let trace_context = Context::map_current(|cx| {
+    fn log_data(&self, mut log_record: LogRecord) -> LogData {
+        if let Some(trace_context) = Context::map_current(|cx| {
             cx.has_active_span()
                 .then(|| TraceContext::from(cx.span().span_context()))
-        });
-        let mut log_record = record;
-        if let Some(ref trace_context) = trace_context {
-            log_record.trace_context = Some(trace_context.clone());
+        }) {
+            log_record.trace_context = Some(trace_context);
         }
         if log_record.observed_timestamp.is_none() {
             log_record.observed_timestamp = Some(SystemTime::now());
         }
+        if let Some(attributes) = log_record.attributes.as_mut() {
+            let provider = self.provider();
+            let log_limits = provider.log_limits();
+            let attributes_limit = log_limits.max_attributes_per_log_record as usize;
+            if attributes.len() > attributes_limit {
+                let dropped = attributes.len() - attributes_limit;
+                log_record.dropped_attributes_count += dropped as u32;
+                provider.inner.dropped.limited_parts(dropped as u64);
+                attributes.truncate(attributes_limit);
+            }
+
+            if let Some(sanitizer) = provider.attribute_sanitizer() {
+                for (_, value) in attributes.iter_mut() {
+                    sanitizer.sanitize_any_value(value);
+                }
+            }
+            if let Some(max_length) = log_limits.max_attribute_value_length {
+                let truncation = AttributeSanitizer::new().with_max_length(max_length as usize);
+                for (_, value) in attributes.iter_mut() {
+                    truncation.sanitize_any_value(value);
+                }
+            }
+        }
 
-        let mut data = LogData {
+        LogData {
             record: log_record,
             instrumentation: self.instrumentation_library().clone(),
-        };
+            resource: None,
+        }
+    }
+}
+
+impl opentelemetry::logs::Logger for Logger {
+    type LogRecord = LogRecord;
 
-        for p in processors {
+    fn create_log_record(&self) -> Self::LogRecord {
+        LogRecord::default()
+    }
+
+    /// Emit a `LogRecord`.
+    fn emit(&self, record: Self::LogRecord) {
+        let mut data = self.log_data(record);
+
+        for p in self.provider().log_processors() {
             p.emit(&mut data);
         }
     }
@@ -491,6 +883,208 @@ mod tests {
         assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 3);
     }
 
+    #[test]
+    fn guard_shuts_down_provider_when_dropped() {
+        let counter = Arc::new(AtomicU64::new(0));
+        let logger_provider = LoggerProvider::builder()
+            .with_log_processor(ShutdownTestLogProcessor::new(counter.clone()))
+            .build();
+        let logger = logger_provider.logger("test-logger");
+
+        let guard = logger_provider.guard();
+        logger.emit(logger.create_log_record());
+        drop(guard);
+        logger.emit(logger.create_log_record());
+
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(logger_provider.shutdown().is_err());
+    }
+
+    #[cfg(all(feature = "metrics", feature = "testing"))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn meter_provider_observes_queued_logs() {
+        use crate::logs::{BatchConfigBuilder, DedicatedThreadBatchLogProcessor};
+        use crate::metrics::{data, PeriodicReader, SdkMeterProvider};
+        use crate::runtime;
+        use crate::testing::{logs::InMemoryLogsExporter, metrics::InMemoryMetricsExporter};
+
+        let metrics_exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(metrics_exporter.clone(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+
+        // the batch processor keeps the log record queued until it is shut down
+        let batch = DedicatedThreadBatchLogProcessor::builder(InMemoryLogsExporter::default())
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_secs(60 * 60 * 24))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+        let logger_provider = LoggerProvider::builder()
+            .with_log_processor(batch)
+            .with_meter_provider(&meter_provider)
+            .build();
+        let logger = logger_provider.logger("test-logger");
+        logger.emit(logger.create_log_record());
+
+        meter_provider.force_flush().unwrap();
+        let resource_metrics = metrics_exporter.get_finished_metrics().unwrap();
+        let metrics = &resource_metrics[0].scope_metrics[0].metrics;
+        let dropped = metrics
+            .iter()
+            .find(|metric| metric.name == "otel.sdk.log.dropped")
+            .expect("dropped log records are observed")
+            .data
+            .as_any()
+            .downcast_ref::<data::Sum<u64>>()
+            .expect("sum aggregation expected");
+        assert!(dropped.data_points.iter().all(|dp| dp.value == 0));
+
+        let queue_size = metrics
+            .iter()
+            .find(|metric| metric.name == "otel.sdk.processor.log.queue.size")
+            .expect("queued log records are observed");
+        assert_eq!(queue_size.unit, "{log_record}");
+        let queue_size = queue_size
+            .data
+            .as_any()
+            .downcast_ref::<data::Gauge<u64>>()
+            .expect("gauge aggregation expected");
+        assert_eq!(queue_size.data_points[0].value, 1);
+
+        assert!(logger_provider.shutdown().is_ok());
+    }
+
+    #[test]
+    fn simple_exporter_processors_are_dispatched_statically() {
+        use crate::testing::logs::InMemoryLogsExporter;
+
+        let exporter = InMemoryLogsExporter::default();
+        let counter = Arc::new(AtomicU64::new(0));
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .with_log_processor(SimpleLogProcessor::new(Box::new(exporter.clone())))
+            .with_log_processor(ShutdownTestLogProcessor::new(counter.clone()))
+            .build();
+
+        let processors = logger_provider.log_processors();
+        assert_eq!(processors.len(), 3);
+        assert!(matches!(processors[0], RegisteredLogProcessor::Simple(_)));
+        assert!(matches!(processors[1], RegisteredLogProcessor::Boxed(_)));
+        assert!(matches!(processors[2], RegisteredLogProcessor::Boxed(_)));
+
+        let logger = logger_provider.logger("test-logger");
+        logger.emit(logger.create_log_record());
+
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 2);
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn emit_sanitizes_attributes() {
+        use crate::testing::logs::InMemoryLogsExporter;
+        use opentelemetry::logs::{AnyValue, LogRecord as _};
+
+        let exporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .with_attribute_sanitizer(AttributeSanitizer::new().with_max_length(3))
+            .build();
+        let logger = logger_provider.logger("test-logger");
+
+        let mut record = logger.create_log_record();
+        record.set_body("body is not an attribute".into());
+        record.add_attribute("key", "abcdef");
+        record.add_attribute("int", 12345);
+        logger.emit(record);
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(
+            logs[0].record.body,
+            Some(AnyValue::from("body is not an attribute"))
+        );
+        assert_eq!(
+            logs[0].record.attributes,
+            Some(vec![
+                (Key::new("key"), AnyValue::from("abc")),
+                (Key::new("int"), AnyValue::Int(12345)),
+            ])
+        );
+    }
+
+    #[test]
+    fn emit_applies_log_limits() {
+        use crate::testing::logs::InMemoryLogsExporter;
+        use opentelemetry::logs::{AnyValue, LogRecord as _};
+
+        let exporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .with_config(
+                Config::default()
+                    .with_max_attributes_per_log_record(2)
+                    .with_max_attribute_value_length(3),
+            )
+            .build();
+        let logger = logger_provider.logger("test-logger");
+
+        let mut record = logger.create_log_record();
+        record.add_attribute("a", "abcdef");
+        record.add_attribute("b", AnyValue::ListAny(vec!["uvwxyz".into()]));
+        record.add_attribute("c", "dropped");
+        record.add_attribute("d", "dropped");
+        logger.emit(record);
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(
+            logs[0].record.attributes,
+            Some(vec![
+                (Key::new("a"), AnyValue::from("abc")),
+                (Key::new("b"), AnyValue::ListAny(vec!["uvw".into()])),
+            ])
+        );
+        assert_eq!(logs[0].record.dropped_attributes_count, 2);
+        assert_eq!(logger_provider.dropped_telemetry().limited_parts, 2);
+    }
+
+    #[test]
+    fn emit_captures_active_trace_context() {
+        use crate::testing::logs::InMemoryLogsExporter;
+        use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+
+        let exporter = InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let logger = logger_provider.logger("test-logger");
+
+        logger.emit(logger.create_log_record());
+        {
+            let _guard = Context::new()
+                .with_remote_span_context(SpanContext::new(
+                    TraceId::from_u128(1),
+                    SpanId::from_u64(2),
+                    TraceFlags::SAMPLED,
+                    true,
+                    TraceState::default(),
+                ))
+                .attach();
+            logger.emit(logger.create_log_record());
+        }
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(logs[0].record.trace_context, None);
+        assert_eq!(
+            logs[1].record.trace_context,
+            Some(TraceContext {
+                trace_id: TraceId::from_u128(1),
+                span_id: SpanId::from_u64(2),
+                trace_flags: Some(TraceFlags::SAMPLED),
+            })
+        );
+    }
+
     #[test]
     fn shutdown_idempotent_test() {
         let counter = Arc::new(AtomicU64::new(0));
@@ -544,6 +1138,50 @@ mod tests {
         assert!(!*flush_called.lock().unwrap());
     }
 
+    #[test]
+    fn force_flush_flushes_all_processors_and_combines_errors() {
+        // cargo test force_flush_flushes_all_processors_and_combines_errors --features=testing
+
+        #[derive(Debug)]
+        struct FailingFlushProcessor;
+
+        impl LogProcessor for FailingFlushProcessor {
+            fn emit(&self, _data: &mut LogData) {}
+
+            fn force_flush(&self) -> LogResult<()> {
+                Err(LogError::Other("export failed".into()))
+            }
+
+            fn shutdown(&self) -> LogResult<()> {
+                Ok(())
+            }
+
+            #[cfg(feature = "logs_level_enabled")]
+            fn event_enabled(&self, _level: Severity, _target: &str, _name: &str) -> bool {
+                true
+            }
+        }
+
+        // Arrange
+        let flush_called = Arc::new(Mutex::new(false));
+        let logger_provider = LoggerProvider::builder()
+            .with_log_processor(FailingFlushProcessor)
+            .with_log_processor(LazyLogProcessor::new(
+                Arc::new(Mutex::new(false)),
+                flush_called.clone(),
+            ))
+            .build();
+
+        // Act
+        let result = logger_provider.force_flush();
+
+        // Assert
+        let err = result.expect_err("the failed flush should be reported");
+        assert!(err.to_string().contains("export failed"));
+        // the processors after the failed one are still flushed
+        assert!(*flush_called.lock().unwrap());
+    }
+
     #[derive(Debug)]
     pub(crate) struct LazyLogProcessor {
         shutdown_called: Arc<Mutex<bool>>,
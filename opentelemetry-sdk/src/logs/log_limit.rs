@@ -0,0 +1,35 @@
+//! # Log record limits
+//! Erroneous code can add unintended attributes to a log record. If the attributes are unbounded,
+//! they can quickly exhaust available memory or be rejected by the backend.
+//!
+//! To protect against those errors, users can use log limits to configure
+//!  - Maximum allowed attribute count per log record
+//!  - Maximum allowed length of attribute string values
+//!
+//! If the attribute count limit has been breached, the attributes added later are dropped first
+//! and counted in [`LogRecord::dropped_attributes_count`]. String values above the length limit
+//! are truncated.
+//!
+//! [`LogRecord::dropped_attributes_count`]: crate::logs::LogRecord::dropped_attributes_count
+
+pub(crate) const DEFAULT_MAX_ATTRIBUTES_PER_LOG_RECORD: u32 = 128;
+
+/// Log record limit configuration to keep attributes of a log record in a reasonable size.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LogLimits {
+    /// The max attributes that can be added to a log record.
+    pub max_attributes_per_log_record: u32,
+    /// The max length in bytes of attribute string values, including those
+    /// nested in lists and maps. Values are truncated on character
+    /// boundaries. `None` disables the limit.
+    pub max_attribute_value_length: Option<u32>,
+}
+
+impl Default for LogLimits {
+    fn default() -> Self {
+        LogLimits {
+            max_attributes_per_log_record: DEFAULT_MAX_ATTRIBUTES_PER_LOG_RECORD,
+            max_attribute_value_length: None,
+        }
+    }
+}
@@ -1,25 +1,32 @@
 use crate::{
+    budget::{BudgetPriority, Reservation, TelemetryBudget},
+    dropped::DroppedCounters,
     export::logs::{ExportResult, LogData, LogExporter},
-    runtime::{RuntimeChannel, TrySend},
-    Resource,
+    runtime::{RuntimeChannel, TrySend, TrySendError},
+    shutdown::{self, ShutdownError, ShutdownResult},
+    util::positive_from_env,
+    BuildError, DroppedTelemetry, ProcessorDiagnostics, Resource,
 };
 use futures_channel::oneshot;
 use futures_util::{
-    future::{self, Either},
+    future::{self, BoxFuture, Either},
+    lock::Mutex as AsyncMutex,
     {pin_mut, stream, StreamExt as _},
 };
-#[cfg(feature = "logs_level_enabled")]
-use opentelemetry::logs::Severity;
 use opentelemetry::{
     global,
-    logs::{LogError, LogResult},
+    logs::{AnyValue, LogError, LogResult, Severity},
 };
 use std::borrow::Cow;
-use std::sync::atomic::AtomicBool;
-use std::{cmp::min, env, sync::Mutex};
+use std::future::Future;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+use std::{cmp::min, sync::Mutex};
 use std::{
     fmt::{self, Debug, Formatter},
-    str::FromStr,
     sync::Arc,
     time::Duration,
 };
@@ -56,18 +63,53 @@ pub trait LogProcessor: Send + Sync + Debug {
     /// # Parameters
     /// - `data`: A mutable reference to `LogData` representing the log record.
     fn emit(&self, data: &mut LogData);
+    /// Called instead of [`emit`] for log records which must not be lost.
+    ///
+    /// The returned future resolves once the record has been accepted by the
+    /// exporter, or with the error which prevented it. The default
+    /// implementation emits the record and then force flushes the processor,
+    /// blocking the calling thread. Processors should override it to only
+    /// wait for the export of the record.
+    ///
+    /// [`emit`]: LogProcessor::emit
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        self.emit(data);
+        Box::pin(future::ready(self.force_flush()))
+    }
     /// Force the logs lying in the cache to be exported.
     fn force_flush(&self) -> LogResult<()>;
     /// Shuts down the processor.
     /// After shutdown returns the log processor should stop processing any logs.
     /// It's up to the implementation on when to drop the LogProcessor.
     fn shutdown(&self) -> LogResult<()>;
+    /// Shuts down the processor, waiting at most `timeout` for it to complete.
+    ///
+    /// The default implementation ignores the timeout and calls [`shutdown`].
+    /// Processors which wait for their exporter while shutting down should
+    /// override it.
+    ///
+    /// [`shutdown`]: LogProcessor::shutdown
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> ShutdownResult {
+        self.shutdown()
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))
+    }
     #[cfg(feature = "logs_level_enabled")]
     /// Check if logging is enabled
     fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool;
 
     /// Set the resource for the log processor.
     fn set_resource(&self, _resource: &Resource) {}
+
+    /// The log records dropped by the processor, none by default.
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        DroppedTelemetry::default()
+    }
+
+    /// The queue depth and last export error of the processor, reported by
+    /// [`PipelineDiagnostics`](crate::PipelineDiagnostics), none by default.
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        ProcessorDiagnostics::default()
+    }
 }
 
 /// A [LogProcessor] that passes logs to the configured `LogExporter`, as soon
@@ -78,6 +120,7 @@ pub trait LogProcessor: Send + Sync + Debug {
 pub struct SimpleLogProcessor {
     exporter: Mutex<Box<dyn LogExporter>>,
     is_shutdown: AtomicBool,
+    dropped: DroppedCounters,
 }
 
 impl SimpleLogProcessor {
@@ -85,6 +128,7 @@ impl SimpleLogProcessor {
         SimpleLogProcessor {
             exporter: Mutex::new(exporter),
             is_shutdown: AtomicBool::new(false),
+            dropped: DroppedCounters::default(),
         }
     }
 }
@@ -104,10 +148,31 @@ impl LogProcessor for SimpleLogProcessor {
                 futures_executor::block_on(exporter.export(vec![Cow::Borrowed(data)]))
             });
         if let Err(err) = result {
+            self.dropped.export_failed(1, &err);
             global::handle_error(err);
         }
     }
 
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        if self.is_shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+            return Box::pin(future::ready(Err(LogError::Other(
+                "simple logprocessor is already shut down".into(),
+            ))));
+        }
+
+        let result = self
+            .exporter
+            .lock()
+            .map_err(|_| LogError::Other("simple logprocessor mutex poison".into()))
+            .and_then(|mut exporter| {
+                futures_executor::block_on(exporter.export(vec![Cow::Borrowed(data)]))
+            });
+        if let Err(err) = &result {
+            self.dropped.export_failed(1, err);
+        }
+        Box::pin(future::ready(result))
+    }
+
     fn force_flush(&self) -> LogResult<()> {
         Ok(())
     }
@@ -131,37 +196,364 @@ impl LogProcessor for SimpleLogProcessor {
         }
     }
 
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        self.dropped.diagnostics(false)
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, _level: Severity, _target: &str, _name: &str) -> bool {
+        true
+    }
+}
+
+/// A [`LogProcessor`] buffering log records in memory until it is flushed,
+/// for environments freezing the process between invocations such as AWS
+/// Lambda and other FaaS platforms.
+///
+/// Unlike [`BatchLogProcessor`], it spawns no background task and never
+/// exports on its own: the buffered log records are exported when
+/// [`flush_async`] is awaited, typically by the hook running at the end of
+/// each invocation, or when the processor is force flushed or shut down
+/// through the [`LoggerProvider`]. Log records emitted while `max_queue_size`
+/// records are buffered are dropped.
+///
+/// Force flushing blocks the calling thread until the export completes, async
+/// invocation handlers should await [`flush_async`] instead.
+///
+/// The processor is a handle which can be cloned cheaply, keep a clone to
+/// flush it once it is registered with the logger provider.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "testing")]
+/// # {
+/// use opentelemetry_sdk::{
+///     logs::{FinishedLogBatchOnDemandProcessor, LoggerProvider},
+///     testing::logs::InMemoryLogsExporter,
+/// };
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let processor =
+///     FinishedLogBatchOnDemandProcessor::builder(InMemoryLogsExporter::default()).build();
+/// let provider = LoggerProvider::builder()
+///     .with_log_processor(processor.clone())
+///     .build();
+///
+/// // handle an invocation, then export its logs before the environment is
+/// // frozen
+/// processor.flush_async().await.unwrap();
+/// # }
+/// # }
+/// ```
+///
+/// [`flush_async`]: FinishedLogBatchOnDemandProcessor::flush_async
+/// [`LoggerProvider`]: crate::logs::LoggerProvider
+#[derive(Clone, Debug)]
+pub struct FinishedLogBatchOnDemandProcessor {
+    inner: Arc<OnDemandInner>,
+}
+
+#[derive(Debug)]
+struct OnDemandInner {
+    logs: Mutex<Vec<LogData>>,
+    max_queue_size: usize,
+    // held while the exporter borrows the exported batch
+    exporter: AsyncMutex<Box<dyn LogExporter>>,
+    is_shutdown: AtomicBool,
+    dropped: DroppedCounters,
+}
+
+impl FinishedLogBatchOnDemandProcessor {
+    /// Create a new builder of an on demand processor exporting to `exporter`.
+    pub fn builder<E>(exporter: E) -> FinishedLogBatchOnDemandProcessorBuilder<E>
+    where
+        E: LogExporter + 'static,
+    {
+        FinishedLogBatchOnDemandProcessorBuilder {
+            exporter,
+            max_queue_size: OTEL_BLRP_MAX_QUEUE_SIZE_DEFAULT,
+        }
+    }
+
+    /// Exports the buffered log records.
+    ///
+    /// The returned future runs on the caller's executor and is not bounded by
+    /// a timeout, the exporter is expected to time out its requests. Log
+    /// records emitted while the export is in progress are kept for the next
+    /// flush.
+    pub fn flush_async(&self) -> impl Future<Output = LogResult<()>> + Send + 'static {
+        let inner = Arc::clone(&self.inner);
+        async move {
+            let logs = inner
+                .logs
+                .lock()
+                .map(|mut logs| mem::take(&mut *logs))
+                .map_err(|_| LogError::from("FinishedLogBatchOnDemandProcessor mutex poison"))?;
+            if logs.is_empty() {
+                return Ok(());
+            }
+
+            let count = logs.len() as u64;
+            let batch = logs.into_iter().map(Cow::Owned).collect();
+            let result = inner.exporter.lock().await.export(batch).await;
+            if let Err(err) = &result {
+                inner.dropped.export_failed(count, err);
+            }
+            result
+        }
+    }
+
+    /// Buffers `data`, returning `false` if it was dropped.
+    fn push(&self, data: &LogData) -> bool {
+        if self.inner.is_shutdown.load(Ordering::Relaxed) {
+            return false;
+        }
+        let Ok(mut logs) = self.inner.logs.lock() else {
+            return false;
+        };
+        if logs.len() < self.inner.max_queue_size {
+            logs.push(data.clone());
+            true
+        } else {
+            self.inner.dropped.queue_full(1);
+            false
+        }
+    }
+}
+
+impl OnDemandInner {
+    fn with_exporter<T>(&self, f: impl FnOnce(&mut dyn LogExporter) -> T) -> T {
+        // only blocks while an export is in progress
+        match self.exporter.try_lock() {
+            Some(mut exporter) => f(exporter.as_mut()),
+            None => f(futures_executor::block_on(self.exporter.lock()).as_mut()),
+        }
+    }
+}
+
+impl LogProcessor for FinishedLogBatchOnDemandProcessor {
+    fn emit(&self, data: &mut LogData) {
+        self.push(data);
+    }
+
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        if !self.push(data) {
+            return Box::pin(future::ready(Err(LogError::from(
+                "log record dropped by FinishedLogBatchOnDemandProcessor",
+            ))));
+        }
+        Box::pin(self.flush_async())
+    }
+
     #[cfg(feature = "logs_level_enabled")]
     fn event_enabled(&self, _level: Severity, _target: &str, _name: &str) -> bool {
         true
     }
+
+    fn force_flush(&self) -> LogResult<()> {
+        futures_executor::block_on(self.flush_async())
+    }
+
+    fn shutdown(&self) -> LogResult<()> {
+        if self.inner.is_shutdown.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let result = self.force_flush();
+        self.inner.with_exporter(|exporter| exporter.shutdown());
+        result
+    }
+
+    fn set_resource(&self, resource: &Resource) {
+        self.inner
+            .with_exporter(|exporter| exporter.set_resource(resource));
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.inner.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        let queued = self.inner.logs.lock().map_or(0, |logs| logs.len());
+        ProcessorDiagnostics {
+            queued: Some(queued as u64),
+            ..self.inner.dropped.diagnostics(false)
+        }
+    }
+}
+
+/// A builder for creating [`FinishedLogBatchOnDemandProcessor`] instances.
+#[derive(Debug)]
+pub struct FinishedLogBatchOnDemandProcessorBuilder<E> {
+    exporter: E,
+    max_queue_size: usize,
+}
+
+impl<E> FinishedLogBatchOnDemandProcessorBuilder<E>
+where
+    E: LogExporter + 'static,
+{
+    /// Set max_queue_size for [`FinishedLogBatchOnDemandProcessorBuilder`].
+    /// It's the maximum number of log records buffered until the next flush,
+    /// further records are dropped. The default value is 2048.
+    pub fn with_max_queue_size(self, max_queue_size: usize) -> Self {
+        FinishedLogBatchOnDemandProcessorBuilder {
+            max_queue_size,
+            ..self
+        }
+    }
+
+    /// Build an on demand processor.
+    pub fn build(self) -> FinishedLogBatchOnDemandProcessor {
+        FinishedLogBatchOnDemandProcessor {
+            inner: Arc::new(OnDemandInner {
+                logs: Mutex::new(Vec::new()),
+                max_queue_size: self.max_queue_size,
+                exporter: AsyncMutex::new(Box::new(self.exporter)),
+                is_shutdown: AtomicBool::new(false),
+                dropped: DroppedCounters::default(),
+            }),
+        }
+    }
 }
 
 /// A [`LogProcessor`] that asynchronously buffers log records and reports
 /// them at a pre-configured interval.
 pub struct BatchLogProcessor<R: RuntimeChannel> {
     message_sender: R::Sender<BatchMessage>,
+    // shared with the worker, which counts the logs of failed exports
+    dropped: Arc<DroppedCounters>,
+    budget: Option<TelemetryBudget>,
 }
 
 impl<R: RuntimeChannel> Debug for BatchLogProcessor<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("BatchLogProcessor")
             .field("message_sender", &self.message_sender)
+            .field("dropped", &self.dropped)
+            .field("budget", &self.budget)
             .finish()
     }
 }
 
+/// Reserves the size of `data` in the budget, if any.
+///
+/// Returns `Err` if the log record doesn't fit in the budget and is dropped.
+fn reserve(
+    budget: Option<&TelemetryBudget>,
+    dropped: &DroppedCounters,
+    data: &LogData,
+    acked: bool,
+) -> Result<Option<Reservation>, ()> {
+    let Some(budget) = budget else {
+        return Ok(None);
+    };
+    match budget.try_reserve(budget_priority(data, acked), log_size(data)) {
+        Some(reservation) => Ok(Some(reservation)),
+        None => {
+            dropped.memory_budget(1);
+            Err(())
+        }
+    }
+}
+
+fn release(budget: Option<&TelemetryBudget>, reservation: Option<Reservation>) {
+    if let (Some(budget), Some(reservation)) = (budget, reservation) {
+        budget.release(reservation);
+    }
+}
+
+/// Log records less severe than `Info` are evicted first, unless their export
+/// is awaited.
+fn budget_priority(data: &LogData, acked: bool) -> BudgetPriority {
+    match data.record.severity_number {
+        Some(severity) if !acked && severity < Severity::Info => BudgetPriority::DebugLogs,
+        _ => BudgetPriority::Regular,
+    }
+}
+
+/// The estimated size of `data` in memory.
+fn log_size(data: &LogData) -> usize {
+    let attributes: usize = data
+        .record
+        .attributes
+        .iter()
+        .flatten()
+        .map(|(key, value)| key.as_str().len() + any_value_size(value))
+        .sum();
+    std::mem::size_of::<LogData>()
+        + data.record.body.as_ref().map_or(0, any_value_size)
+        + attributes
+}
+
+fn any_value_size(value: &AnyValue) -> usize {
+    match value {
+        AnyValue::Int(_) | AnyValue::Double(_) => 8,
+        AnyValue::Boolean(_) => 1,
+        AnyValue::String(s) => s.as_str().len(),
+        AnyValue::Bytes(bytes) => bytes.len(),
+        AnyValue::ListAny(values) => values.iter().map(any_value_size).sum(),
+        AnyValue::Map(map) => map
+            .iter()
+            .map(|(key, value)| key.as_str().len() + any_value_size(value))
+            .sum(),
+    }
+}
+
 impl<R: RuntimeChannel> LogProcessor for BatchLogProcessor<R> {
     fn emit(&self, data: &mut LogData) {
-        let result = self
-            .message_sender
-            .try_send(BatchMessage::ExportLog(data.clone()));
+        let Ok(reservation) = reserve(self.budget.as_ref(), &self.dropped, data, false) else {
+            return;
+        };
+        self.dropped.enqueued(1);
+        let result =
+            self.message_sender
+                .try_send(BatchMessage::ExportLog(data.clone(), reservation, None));
 
         if let Err(err) = result {
+            self.dropped.dequeued(1);
+            if matches!(err, TrySendError::ChannelFull) {
+                self.dropped.queue_full(1);
+            }
+            release(self.budget.as_ref(), reservation);
             global::handle_error(LogError::Other(err.into()));
         }
     }
 
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        let Ok(reservation) = reserve(self.budget.as_ref(), &self.dropped, data, true) else {
+            return Box::pin(future::ready(Err(LogError::from(
+                "log record dropped to stay within the memory budget",
+            ))));
+        };
+        let (ack_sender, ack_receiver) = oneshot::channel();
+        self.dropped.enqueued(1);
+        if let Err(err) = self.message_sender.try_send(BatchMessage::ExportLog(
+            data.clone(),
+            reservation,
+            Some(ack_sender),
+        )) {
+            self.dropped.dequeued(1);
+            if matches!(err, TrySendError::ChannelFull) {
+                self.dropped.queue_full(1);
+            }
+            release(self.budget.as_ref(), reservation);
+            return Box::pin(future::ready(Err(LogError::Other(err.into()))));
+        }
+
+        Box::pin(async move {
+            ack_receiver
+                .await
+                .map_err(|err| LogError::Other(err.into()))
+                .and_then(std::convert::identity)
+        })
+    }
+
     #[cfg(feature = "logs_level_enabled")]
     fn event_enabled(&self, _level: Severity, _target: &str, _name: &str) -> bool {
         true
@@ -189,33 +581,71 @@ impl<R: RuntimeChannel> LogProcessor for BatchLogProcessor<R> {
             .and_then(std::convert::identity)
     }
 
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        let (res_sender, res_receiver) = oneshot::channel();
+        self.message_sender
+            .try_send(BatchMessage::Shutdown(res_sender))?;
+
+        shutdown::block_on_with_timeout(res_receiver, timeout)?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))
+    }
+
     fn set_resource(&self, resource: &Resource) {
         let resource = Arc::new(resource.clone());
         let _ = self
             .message_sender
             .try_send(BatchMessage::SetResource(resource));
     }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        self.dropped.diagnostics(true)
+    }
 }
 
 impl<R: RuntimeChannel> BatchLogProcessor<R> {
-    pub(crate) fn new(mut exporter: Box<dyn LogExporter>, config: BatchConfig, runtime: R) -> Self {
+    pub(crate) fn new(
+        mut exporter: Box<dyn LogExporter>,
+        config: BatchConfig,
+        runtime: R,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
         let (message_sender, message_receiver) =
             runtime.batch_message_channel(config.max_queue_size);
         let ticker = runtime
             .interval(config.scheduled_delay)
             .map(|_| BatchMessage::Flush(None));
         let timeout_runtime = runtime.clone();
+        let dropped = Arc::new(DroppedCounters::default());
+        let worker_dropped = Arc::clone(&dropped);
+        let worker_budget = budget.clone();
 
         // Spawn worker process via user-defined spawn function.
         runtime.spawn(Box::pin(async move {
             let mut logs = Vec::new();
+            // acknowledgements of the logs emitted with `emit_with_ack`
+            let mut acks = Vec::new();
+            // reservations of `logs` in the budget, empty without a budget
+            let mut reservations = Vec::new();
             let mut messages = Box::pin(stream::select(message_receiver, ticker));
 
             while let Some(message) = messages.next().await {
                 match message {
                     // Log has finished, add to buffer of pending logs.
-                    BatchMessage::ExportLog(log) => {
+                    BatchMessage::ExportLog(log, reservation, ack) => {
                         logs.push(Cow::Owned(log));
+                        reservations.extend(reservation);
+                        acks.extend(ack);
+                        evict(
+                            worker_budget.as_ref(),
+                            &mut logs,
+                            &mut reservations,
+                            &worker_dropped,
+                        );
 
                         if logs.len() == config.max_export_batch_size {
                             let result = export_with_timeout(
@@ -223,8 +653,12 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
                                 exporter.as_mut(),
                                 &timeout_runtime,
                                 logs.split_off(0),
+                                reservations.split_off(0),
+                                worker_budget.as_ref(),
+                                &worker_dropped,
                             )
                             .await;
+                            acknowledge(&mut acks, &result);
 
                             if let Err(err) = result {
                                 global::handle_error(err);
@@ -233,13 +667,23 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
                     }
                     // Log batch interval time reached or a force flush has been invoked, export current spans.
                     BatchMessage::Flush(res_channel) => {
+                        evict(
+                            worker_budget.as_ref(),
+                            &mut logs,
+                            &mut reservations,
+                            &worker_dropped,
+                        );
                         let result = export_with_timeout(
                             config.max_export_timeout,
                             exporter.as_mut(),
                             &timeout_runtime,
                             logs.split_off(0),
+                            reservations.split_off(0),
+                            worker_budget.as_ref(),
+                            &worker_dropped,
                         )
                         .await;
+                        acknowledge(&mut acks, &result);
 
                         if let Some(channel) = res_channel {
                             if let Err(result) = channel.send(result) {
@@ -259,8 +703,12 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
                             exporter.as_mut(),
                             &timeout_runtime,
                             logs.split_off(0),
+                            reservations.split_off(0),
+                            worker_budget.as_ref(),
+                            &worker_dropped,
                         )
                         .await;
+                        acknowledge(&mut acks, &result);
 
                         exporter.shutdown();
 
@@ -283,7 +731,11 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
         }));
 
         // Return batch processor with link to worker
-        BatchLogProcessor { message_sender }
+        BatchLogProcessor {
+            message_sender,
+            dropped,
+            budget,
+        }
     }
 
     /// Create a new batch processor builder
@@ -295,8 +747,43 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
             exporter,
             config: Default::default(),
             runtime,
+            budget: None,
+        }
+    }
+}
+
+/// Evicts the oldest buffered debug logs while the budget requests it to make
+/// room for higher priority telemetry.
+fn evict(
+    budget: Option<&TelemetryBudget>,
+    logs: &mut Vec<Cow<'_, LogData>>,
+    reservations: &mut Vec<Reservation>,
+    dropped: &DroppedCounters,
+) {
+    let Some(budget) = budget else {
+        return;
+    };
+    let mut evicted = vec![false; logs.len()];
+    for (evicted, reservation) in evicted.iter_mut().zip(reservations.iter()) {
+        if !budget.eviction_requested(BudgetPriority::DebugLogs) {
+            break;
         }
+        if reservation.priority == BudgetPriority::DebugLogs {
+            budget.evict(*reservation);
+            *evicted = true;
+        }
+    }
+
+    let count = evicted.iter().filter(|evicted| **evicted).count();
+    if count == 0 {
+        return;
     }
+    let mut keep = evicted.iter().map(|evicted| !evicted);
+    logs.retain(|_| keep.next().unwrap_or(true));
+    let mut keep = evicted.iter().map(|evicted| !evicted);
+    reservations.retain(|_| keep.next().unwrap_or(true));
+    dropped.memory_budget(count as u64);
+    dropped.dequeued(count as u64);
 }
 
 async fn export_with_timeout<'a, R, E>(
@@ -304,6 +791,9 @@ async fn export_with_timeout<'a, R, E>(
     exporter: &mut E,
     runtime: &R,
     batch: Vec<Cow<'a, LogData>>,
+    reservations: Vec<Reservation>,
+    budget: Option<&TelemetryBudget>,
+    dropped: &DroppedCounters,
 ) -> ExportResult
 where
     R: RuntimeChannel,
@@ -313,13 +803,72 @@ where
         return Ok(());
     }
 
+    let count = batch.len() as u64;
     let export = exporter.export(batch);
     let timeout = runtime.delay(time_out);
     pin_mut!(export);
     pin_mut!(timeout);
-    match future::select(export, timeout).await {
+    let result = match future::select(export, timeout).await {
         Either::Left((export_res, _)) => export_res,
         Either::Right((_, _)) => ExportResult::Err(LogError::ExportTimedOut(time_out)),
+    };
+    finish_export(&result, count, reservations, budget, dropped);
+    result
+}
+
+/// Exports `batch` on the calling thread, waiting at most `time_out`.
+fn export_blocking<E>(
+    time_out: Duration,
+    exporter: &mut E,
+    batch: Vec<Cow<'_, LogData>>,
+    reservations: Vec<Reservation>,
+    budget: Option<&TelemetryBudget>,
+    dropped: &DroppedCounters,
+) -> ExportResult
+where
+    E: LogExporter + ?Sized,
+{
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let count = batch.len() as u64;
+    let result = shutdown::block_on_with_timeout(exporter.export(batch), time_out)
+        .unwrap_or(Err(LogError::ExportTimedOut(time_out)));
+    finish_export(&result, count, reservations, budget, dropped);
+    result
+}
+
+/// Counts the log records of a failed export, and releases the reservations
+/// of the exported batch.
+fn finish_export(
+    result: &ExportResult,
+    count: u64,
+    reservations: Vec<Reservation>,
+    budget: Option<&TelemetryBudget>,
+    dropped: &DroppedCounters,
+) {
+    dropped.dequeued(count);
+    if let Err(err) = result {
+        dropped.export_failed(count, err);
+    }
+    if let Some(budget) = budget {
+        reservations
+            .into_iter()
+            .for_each(|reservation| budget.release(reservation));
+    }
+}
+
+/// Resolve the acknowledgements of an exported batch with the export result.
+fn acknowledge(acks: &mut Vec<oneshot::Sender<ExportResult>>, result: &ExportResult) {
+    for ack in acks.drain(..) {
+        // the receiver may have stopped waiting for the acknowledgement
+        let _ = ack.send(
+            result
+                .as_ref()
+                .map(|_| ())
+                .map_err(|err| LogError::Other(err.to_string().into())),
+        );
     }
 }
 
@@ -347,7 +896,7 @@ pub struct BatchConfig {
 
 impl Default for BatchConfig {
     fn default() -> Self {
-        BatchConfigBuilder::default().build()
+        BatchConfigBuilder::default().build_clamped()
     }
 }
 
@@ -415,9 +964,44 @@ impl BatchConfigBuilder {
         self
     }
 
-    /// Builds a `BatchConfig` enforcing the following invariants:
+    /// Builds a `BatchConfig`, returning an error if any of the following
+    /// invariants does not hold:
+    /// * `max_queue_size` and `max_export_batch_size` must be greater than zero.
+    /// * `scheduled_delay` must be greater than zero.
     /// * `max_export_batch_size` must be less than or equal to `max_queue_size`.
-    pub fn build(self) -> BatchConfig {
+    pub fn build(self) -> Result<BatchConfig, BuildError> {
+        if self.max_queue_size == 0 {
+            return Err(BuildError::ZeroValue("max_queue_size"));
+        }
+        if self.max_export_batch_size == 0 {
+            return Err(BuildError::ZeroValue("max_export_batch_size"));
+        }
+        if self.scheduled_delay.is_zero() {
+            return Err(BuildError::ZeroValue("scheduled_delay"));
+        }
+        if self.max_export_batch_size > self.max_queue_size {
+            return Err(BuildError::ExportBatchSizeExceedsQueueSize {
+                max_export_batch_size: self.max_export_batch_size,
+                max_queue_size: self.max_queue_size,
+            });
+        }
+
+        Ok(self.build_clamped())
+    }
+
+    /// Builds a `BatchConfig`, panicking if the configuration is invalid.
+    ///
+    /// See [`BatchConfigBuilder::build`] for the invariants that are checked.
+    pub fn build_or_panic(self) -> BatchConfig {
+        match self.build() {
+            Ok(config) => config,
+            Err(err) => panic!("invalid batch log processor configuration: {err}"),
+        }
+    }
+
+    /// Builds a `BatchConfig` from environment-provided values, which the
+    /// specification requires to be tolerated rather than rejected.
+    fn build_clamped(self) -> BatchConfig {
         // max export batch size must be less or equal to max queue size.
         // we set max export batch size to max queue size if it's larger than max queue size.
         let max_export_batch_size = min(self.max_export_batch_size, self.max_queue_size);
@@ -431,30 +1015,22 @@ impl BatchConfigBuilder {
     }
 
     fn init_from_env_vars(mut self) -> Self {
-        if let Some(max_queue_size) = env::var(OTEL_BLRP_MAX_QUEUE_SIZE)
-            .ok()
-            .and_then(|queue_size| usize::from_str(&queue_size).ok())
-        {
+        if let Some(max_queue_size) = positive_from_env(OTEL_BLRP_MAX_QUEUE_SIZE, LogError::from) {
             self.max_queue_size = max_queue_size;
         }
 
-        if let Some(max_export_batch_size) = env::var(OTEL_BLRP_MAX_EXPORT_BATCH_SIZE)
-            .ok()
-            .and_then(|batch_size| usize::from_str(&batch_size).ok())
+        if let Some(max_export_batch_size) =
+            positive_from_env(OTEL_BLRP_MAX_EXPORT_BATCH_SIZE, LogError::from)
         {
             self.max_export_batch_size = max_export_batch_size;
         }
 
-        if let Some(scheduled_delay) = env::var(OTEL_BLRP_SCHEDULE_DELAY)
-            .ok()
-            .and_then(|delay| u64::from_str(&delay).ok())
-        {
+        if let Some(scheduled_delay) = positive_from_env(OTEL_BLRP_SCHEDULE_DELAY, LogError::from) {
             self.scheduled_delay = Duration::from_millis(scheduled_delay);
         }
 
-        if let Some(max_export_timeout) = env::var(OTEL_BLRP_EXPORT_TIMEOUT)
-            .ok()
-            .and_then(|s| u64::from_str(&s).ok())
+        if let Some(max_export_timeout) =
+            positive_from_env(OTEL_BLRP_EXPORT_TIMEOUT, LogError::from)
         {
             self.max_export_timeout = Duration::from_millis(max_export_timeout);
         }
@@ -470,6 +1046,7 @@ pub struct BatchLogProcessorBuilder<E, R> {
     exporter: E,
     config: BatchConfig,
     runtime: R,
+    budget: Option<TelemetryBudget>,
 }
 
 impl<E, R> BatchLogProcessorBuilder<E, R>
@@ -482,9 +1059,445 @@ where
         BatchLogProcessorBuilder { config, ..self }
     }
 
+    /// Bound the memory of the queued log records by `budget`, shared with
+    /// other processors and providers.
+    ///
+    /// Log records which don't fit in the budget are dropped. Queued log
+    /// records less severe than `Info` are evicted to make room for higher
+    /// priority telemetry.
+    pub fn with_budget(self, budget: TelemetryBudget) -> Self {
+        BatchLogProcessorBuilder {
+            budget: Some(budget),
+            ..self
+        }
+    }
+
     /// Build a batch processor
     pub fn build(self) -> BatchLogProcessor<R> {
-        BatchLogProcessor::new(Box::new(self.exporter), self.config, self.runtime)
+        BatchLogProcessor::new(
+            Box::new(self.exporter),
+            self.config,
+            self.runtime,
+            self.budget,
+        )
+    }
+}
+
+/// A [`LogProcessor`] buffering log records and exporting them in batches
+/// from its own worker thread, without an async runtime.
+///
+/// It behaves like [`BatchLogProcessor`] and takes the same [`BatchConfig`],
+/// but the worker is a plain OS thread waiting on a bounded channel, so it
+/// can be used in applications without an async runtime, or with a Tokio
+/// current thread runtime which the batch processor could deadlock.
+///
+/// Exports are driven on the worker thread, the exporter must not require an
+/// async runtime, e.g. it should use a blocking HTTP client.
+///
+/// Flush, shutdown and resource requests are sent to the worker on their own
+/// channel, so they are not rejected while the queue of log records is full.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "testing")]
+/// # {
+/// use opentelemetry_sdk::{
+///     logs::{DedicatedThreadBatchLogProcessor, LoggerProvider},
+///     testing::logs::InMemoryLogsExporter,
+/// };
+///
+/// let provider = LoggerProvider::builder()
+///     .with_log_processor(
+///         DedicatedThreadBatchLogProcessor::builder(InMemoryLogsExporter::default()).build(),
+///     )
+///     .build();
+/// # drop(provider);
+/// # }
+/// ```
+pub struct DedicatedThreadBatchLogProcessor {
+    logs_sender: SyncSender<QueuedLog>,
+    control_sender: SyncSender<ControlMessage>,
+    // the number of log records in the logs channel, and whether the worker
+    // was asked to export the full batches among them
+    pending: Arc<AtomicUsize>,
+    export_requested: Arc<AtomicBool>,
+    max_export_batch_size: usize,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    // shared with the worker, which counts the logs of failed exports
+    dropped: Arc<DroppedCounters>,
+    budget: Option<TelemetryBudget>,
+}
+
+impl Debug for DedicatedThreadBatchLogProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedicatedThreadBatchLogProcessor")
+            .field("logs_sender", &self.logs_sender)
+            .field("control_sender", &self.control_sender)
+            .field("dropped", &self.dropped)
+            .field("budget", &self.budget)
+            .finish()
+    }
+}
+
+impl DedicatedThreadBatchLogProcessor {
+    /// Create a new builder of a processor exporting to `exporter`.
+    pub fn builder<E>(exporter: E) -> DedicatedThreadBatchLogProcessorBuilder<E>
+    where
+        E: LogExporter,
+    {
+        DedicatedThreadBatchLogProcessorBuilder {
+            exporter,
+            config: Default::default(),
+            budget: None,
+        }
+    }
+
+    fn new(
+        exporter: Box<dyn LogExporter>,
+        config: BatchConfig,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
+        let (logs_sender, logs_receiver) = mpsc::sync_channel(config.max_queue_size);
+        let (control_sender, control_receiver) = mpsc::sync_channel(CONTROL_QUEUE_SIZE);
+        let pending = Arc::new(AtomicUsize::new(0));
+        let export_requested = Arc::new(AtomicBool::new(false));
+        let dropped = Arc::new(DroppedCounters::default());
+        let max_export_batch_size = config.max_export_batch_size;
+
+        let mut worker = DedicatedWorker {
+            exporter,
+            logs_receiver,
+            pending: Arc::clone(&pending),
+            config,
+            budget: budget.clone(),
+            dropped: Arc::clone(&dropped),
+        };
+        let worker_export_requested = Arc::clone(&export_requested);
+        let worker = thread::Builder::new()
+            .name("OpenTelemetry.Logs.BatchProcessor".to_owned())
+            .spawn(move || {
+                let mut next_export = Instant::now() + worker.config.scheduled_delay;
+                loop {
+                    let message =
+                        match control_receiver.recv_timeout(shutdown::remaining(next_export)) {
+                            Ok(message) => message,
+                            Err(RecvTimeoutError::Timeout) => {
+                                if let Err(err) = worker.export_queued() {
+                                    global::handle_error(err);
+                                }
+                                next_export = Instant::now() + worker.config.scheduled_delay;
+                                continue;
+                            }
+                            // the processor was dropped without being shut down
+                            Err(RecvTimeoutError::Disconnected) => {
+                                let (sender, _) = oneshot::channel();
+                                ControlMessage::Shutdown(sender)
+                            }
+                        };
+
+                    match message {
+                        ControlMessage::ExportLogs => {
+                            // cleared first, so records emitted while exporting
+                            // request another export
+                            worker_export_requested.store(false, Ordering::Relaxed);
+                            if let Err(err) = worker.export_full_batches() {
+                                global::handle_error(err);
+                            }
+                        }
+                        ControlMessage::Flush(sender) => {
+                            let result = worker.export_queued();
+                            next_export = Instant::now() + worker.config.scheduled_delay;
+                            if let Err(result) = sender.send(result) {
+                                global::handle_error(LogError::from(format!(
+                                    "failed to send flush result: {:?}",
+                                    result
+                                )));
+                            }
+                        }
+                        ControlMessage::Shutdown(sender) => {
+                            let result = worker.export_queued();
+                            worker.exporter.shutdown();
+
+                            // the receiver is gone if the processor was dropped
+                            let _ = sender.send(result);
+                            break;
+                        }
+                        ControlMessage::SetResource(resource) => {
+                            worker.exporter.set_resource(&resource);
+                        }
+                    }
+                }
+            });
+
+        // without a worker, the channels are closed and the logs are dropped
+        let worker = match worker {
+            Ok(worker) => Some(worker),
+            Err(err) => {
+                global::handle_error(LogError::Other(err.into()));
+                None
+            }
+        };
+
+        DedicatedThreadBatchLogProcessor {
+            logs_sender,
+            control_sender,
+            pending,
+            export_requested,
+            max_export_batch_size,
+            worker: Mutex::new(worker),
+            dropped,
+            budget,
+        }
+    }
+
+    /// Queues a log record for export, asking the worker to export once a
+    /// full batch is queued.
+    fn enqueue(&self, log: QueuedLog) -> Result<(), TrySendError> {
+        self.dropped.enqueued(1);
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        if let Err(err) = TrySend::try_send(&self.logs_sender, log) {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            self.dropped.dequeued(1);
+            if matches!(err, TrySendError::ChannelFull) {
+                self.dropped.queue_full(1);
+            }
+            return Err(err);
+        }
+
+        if self.pending.load(Ordering::Relaxed) >= self.max_export_batch_size
+            && !self.export_requested.swap(true, Ordering::Relaxed)
+        {
+            // a full control channel already wakes the worker
+            let _ = TrySend::try_send(&self.control_sender, ControlMessage::ExportLogs);
+        }
+        Ok(())
+    }
+
+    /// Waits for the worker thread to exit once it was shut down.
+    fn join_worker(&self) {
+        let worker = self.worker.lock().ok().and_then(|mut worker| worker.take());
+        if let Some(worker) = worker {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl LogProcessor for DedicatedThreadBatchLogProcessor {
+    fn emit(&self, data: &mut LogData) {
+        let Ok(reservation) = reserve(self.budget.as_ref(), &self.dropped, data, false) else {
+            return;
+        };
+        if let Err(err) = self.enqueue((data.clone(), reservation, None)) {
+            release(self.budget.as_ref(), reservation);
+            global::handle_error(LogError::Other(err.into()));
+        }
+    }
+
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        let Ok(reservation) = reserve(self.budget.as_ref(), &self.dropped, data, true) else {
+            return Box::pin(future::ready(Err(LogError::from(
+                "log record dropped to stay within the memory budget",
+            ))));
+        };
+        let (ack_sender, ack_receiver) = oneshot::channel();
+        if let Err(err) = self.enqueue((data.clone(), reservation, Some(ack_sender))) {
+            release(self.budget.as_ref(), reservation);
+            return Box::pin(future::ready(Err(LogError::Other(err.into()))));
+        }
+
+        Box::pin(async move {
+            ack_receiver
+                .await
+                .map_err(|err| LogError::Other(err.into()))
+                .and_then(std::convert::identity)
+        })
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, _level: Severity, _target: &str, _name: &str) -> bool {
+        true
+    }
+
+    fn force_flush(&self) -> LogResult<()> {
+        let (res_sender, res_receiver) = oneshot::channel();
+        TrySend::try_send(&self.control_sender, ControlMessage::Flush(res_sender))
+            .map_err(|err| LogError::Other(err.into()))?;
+
+        futures_executor::block_on(res_receiver)
+            .map_err(|err| LogError::Other(err.into()))
+            .and_then(std::convert::identity)
+    }
+
+    fn shutdown(&self) -> LogResult<()> {
+        let (res_sender, res_receiver) = oneshot::channel();
+        TrySend::try_send(&self.control_sender, ControlMessage::Shutdown(res_sender))
+            .map_err(|err| LogError::Other(err.into()))?;
+
+        let result = futures_executor::block_on(res_receiver)
+            .map_err(|err| LogError::Other(err.into()))
+            .and_then(std::convert::identity);
+        self.join_worker();
+        result
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        let (res_sender, res_receiver) = oneshot::channel();
+        TrySend::try_send(&self.control_sender, ControlMessage::Shutdown(res_sender))?;
+
+        let result = shutdown::block_on_with_timeout(res_receiver, timeout)?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()));
+        self.join_worker();
+        result
+    }
+
+    fn set_resource(&self, resource: &Resource) {
+        let resource = Arc::new(resource.clone());
+        let _ = TrySend::try_send(&self.control_sender, ControlMessage::SetResource(resource));
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        self.dropped.diagnostics(true)
+    }
+}
+
+/// The capacity of the control channel of a
+/// [`DedicatedThreadBatchLogProcessor`], it only holds flush, shutdown and
+/// resource requests and at most one export request.
+const CONTROL_QUEUE_SIZE: usize = 64;
+
+/// A log record queued by a [`DedicatedThreadBatchLogProcessor`], with its
+/// reservation in the memory budget if any and the channel acknowledging the
+/// export if the log was emitted with `emit_with_ack`.
+type QueuedLog = (
+    LogData,
+    Option<Reservation>,
+    Option<oneshot::Sender<ExportResult>>,
+);
+
+/// Messages controlling the worker of a [`DedicatedThreadBatchLogProcessor`].
+///
+/// They are sent on their own channel, so a queue full of log records does
+/// not keep the processor from being flushed or shut down.
+#[derive(Debug)]
+enum ControlMessage {
+    /// Export the full batches of queued log records.
+    ExportLogs,
+    /// Export all queued log records.
+    Flush(oneshot::Sender<ExportResult>),
+    /// Export all queued log records and shut down the exporter.
+    Shutdown(oneshot::Sender<ExportResult>),
+    /// Set the resource for the exporter.
+    SetResource(Arc<Resource>),
+}
+
+/// The state of the worker thread of a [`DedicatedThreadBatchLogProcessor`].
+struct DedicatedWorker {
+    exporter: Box<dyn LogExporter>,
+    logs_receiver: mpsc::Receiver<QueuedLog>,
+    pending: Arc<AtomicUsize>,
+    config: BatchConfig,
+    budget: Option<TelemetryBudget>,
+    dropped: Arc<DroppedCounters>,
+}
+
+impl DedicatedWorker {
+    /// Exports the queued log records in full batches, leaving the rest
+    /// queued until the next scheduled export.
+    fn export_full_batches(&mut self) -> ExportResult {
+        let mut result = Ok(());
+        while self.pending.load(Ordering::Relaxed) >= self.config.max_export_batch_size {
+            match self.export_batch() {
+                Some(batch_result) => result = result.and(batch_result),
+                None => break,
+            }
+        }
+        result
+    }
+
+    /// Exports all queued log records, returning the first error.
+    fn export_queued(&mut self) -> ExportResult {
+        let mut result = Ok(());
+        while let Some(batch_result) = self.export_batch() {
+            result = result.and(batch_result);
+        }
+        result
+    }
+
+    /// Exports the next batch of queued log records, `None` if none are
+    /// queued.
+    fn export_batch(&mut self) -> Option<ExportResult> {
+        let mut logs = Vec::new();
+        let mut reservations = Vec::new();
+        let mut acks = Vec::new();
+        while logs.len() < self.config.max_export_batch_size {
+            let Ok((log, reservation, ack)) = self.logs_receiver.try_recv() else {
+                break;
+            };
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            logs.push(Cow::Owned(log));
+            reservations.extend(reservation);
+            acks.extend(ack);
+        }
+        if logs.is_empty() {
+            return None;
+        }
+
+        evict(
+            self.budget.as_ref(),
+            &mut logs,
+            &mut reservations,
+            &self.dropped,
+        );
+        let result = export_blocking(
+            self.config.max_export_timeout,
+            self.exporter.as_mut(),
+            logs,
+            reservations,
+            self.budget.as_ref(),
+            &self.dropped,
+        );
+        acknowledge(&mut acks, &result);
+        Some(result)
+    }
+}
+
+/// A builder for creating [`DedicatedThreadBatchLogProcessor`] instances.
+#[derive(Debug)]
+pub struct DedicatedThreadBatchLogProcessorBuilder<E> {
+    exporter: E,
+    config: BatchConfig,
+    budget: Option<TelemetryBudget>,
+}
+
+impl<E> DedicatedThreadBatchLogProcessorBuilder<E>
+where
+    E: LogExporter + 'static,
+{
+    /// Set the BatchConfig for [`DedicatedThreadBatchLogProcessorBuilder`]
+    pub fn with_batch_config(self, config: BatchConfig) -> Self {
+        DedicatedThreadBatchLogProcessorBuilder { config, ..self }
+    }
+
+    /// Bound the memory of the queued log records by `budget`, shared with
+    /// other processors and providers.
+    ///
+    /// See [`BatchLogProcessorBuilder::with_budget`].
+    pub fn with_budget(self, budget: TelemetryBudget) -> Self {
+        DedicatedThreadBatchLogProcessorBuilder {
+            budget: Some(budget),
+            ..self
+        }
+    }
+
+    /// Build a processor, spawning its worker thread.
+    pub fn build(self) -> DedicatedThreadBatchLogProcessor {
+        DedicatedThreadBatchLogProcessor::new(Box::new(self.exporter), self.config, self.budget)
     }
 }
 
@@ -492,8 +1505,14 @@ where
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 enum BatchMessage {
-    /// Export logs, usually called when the log is emitted.
-    ExportLog(LogData),
+    /// Export logs, usually called when the log is emitted, with its
+    /// reservation in the memory budget if any and the channel acknowledging
+    /// the export if the log was emitted with `emit_with_ack`.
+    ExportLog(
+        LogData,
+        Option<Reservation>,
+        Option<oneshot::Sender<ExportResult>>,
+    ),
     /// Flush the current buffer to the backend, it can be triggered by
     /// pre configured interval or a call to `force_push` function.
     Flush(Option<oneshot::Sender<ExportResult>>),
@@ -506,8 +1525,9 @@ enum BatchMessage {
 #[cfg(all(test, feature = "testing", feature = "logs"))]
 mod tests {
     use super::{
-        BatchLogProcessor, OTEL_BLRP_EXPORT_TIMEOUT, OTEL_BLRP_MAX_EXPORT_BATCH_SIZE,
-        OTEL_BLRP_MAX_QUEUE_SIZE, OTEL_BLRP_SCHEDULE_DELAY,
+        log_size, BatchLogProcessor, DedicatedThreadBatchLogProcessor,
+        FinishedLogBatchOnDemandProcessor, OTEL_BLRP_EXPORT_TIMEOUT,
+        OTEL_BLRP_MAX_EXPORT_BATCH_SIZE, OTEL_BLRP_MAX_QUEUE_SIZE, OTEL_BLRP_SCHEDULE_DELAY,
     };
     use crate::testing::logs::InMemoryLogsExporterBuilder;
     use crate::{
@@ -521,11 +1541,10 @@ mod tests {
         },
         runtime,
         testing::logs::InMemoryLogsExporter,
-        Resource,
+        BudgetPriority, Resource, TelemetryBudget,
     };
     use async_trait::async_trait;
     use opentelemetry::logs::AnyValue;
-    #[cfg(feature = "logs_level_enabled")]
     use opentelemetry::logs::Severity;
     use opentelemetry::logs::{Logger, LoggerProvider as _};
     use opentelemetry::Key;
@@ -623,6 +1642,35 @@ mod tests {
         assert_eq!(config.max_export_batch_size, 1024);
     }
 
+    #[test]
+    fn test_batch_config_ignores_zero_env_vars() {
+        let env_vars = vec![
+            (OTEL_BLRP_SCHEDULE_DELAY, Some("0")),
+            (OTEL_BLRP_EXPORT_TIMEOUT, Some("0")),
+            (OTEL_BLRP_MAX_QUEUE_SIZE, Some("0")),
+            (OTEL_BLRP_MAX_EXPORT_BATCH_SIZE, Some("0")),
+        ];
+
+        let config = temp_env::with_vars(env_vars.clone(), BatchConfig::default);
+        assert_eq!(
+            config.scheduled_delay,
+            Duration::from_millis(OTEL_BLRP_SCHEDULE_DELAY_DEFAULT)
+        );
+        assert_eq!(
+            config.max_export_timeout,
+            Duration::from_millis(OTEL_BLRP_EXPORT_TIMEOUT_DEFAULT)
+        );
+        assert_eq!(config.max_queue_size, OTEL_BLRP_MAX_QUEUE_SIZE_DEFAULT);
+        assert_eq!(
+            config.max_export_batch_size,
+            OTEL_BLRP_MAX_EXPORT_BATCH_SIZE_DEFAULT
+        );
+
+        // the validating build doesn't see the ignored values either
+        let built = temp_env::with_vars(env_vars, || BatchConfigBuilder::default().build());
+        assert!(built.is_ok());
+    }
+
     #[test]
     fn test_batch_config_max_export_batch_size_validation() {
         let env_vars = vec![
@@ -651,7 +1699,8 @@ mod tests {
             .with_scheduled_delay(Duration::from_millis(2))
             .with_max_export_timeout(Duration::from_millis(3))
             .with_max_queue_size(4)
-            .build();
+            .build()
+            .unwrap();
 
         assert_eq!(batch.max_export_batch_size, 1);
         assert_eq!(batch.scheduled_delay, Duration::from_millis(2));
@@ -702,7 +1751,8 @@ mod tests {
             .with_scheduled_delay(Duration::from_millis(2))
             .with_max_export_timeout(Duration::from_millis(3))
             .with_max_queue_size(4)
-            .build();
+            .build()
+            .unwrap();
 
         let builder = BatchLogProcessor::builder(InMemoryLogsExporter::default(), runtime::Tokio)
             .with_batch_config(expected);
@@ -742,6 +1792,7 @@ mod tests {
             Box::new(exporter.clone()),
             BatchConfig::default(),
             runtime::Tokio,
+            None,
         );
         let provider = LoggerProvider::builder()
             .with_log_processor(processor)
@@ -769,10 +1820,12 @@ mod tests {
             Box::new(exporter.clone()),
             BatchConfig::default(),
             runtime::Tokio,
+            None,
         );
         let mut log_data = LogData {
             record: Default::default(),
             instrumentation: Default::default(),
+            resource: None,
         };
         processor.emit(&mut log_data);
         processor.force_flush().unwrap();
@@ -782,6 +1835,279 @@ mod tests {
         assert_eq!(1, exporter.get_emitted_logs().unwrap().len())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_emit_with_ack() {
+        let exporter = InMemoryLogsExporterBuilder::default().build();
+        let processor = BatchLogProcessor::new(
+            Box::new(exporter.clone()),
+            BatchConfig::default(),
+            runtime::Tokio,
+            None,
+        );
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+
+        let ack = processor.emit_with_ack(&mut log_data);
+        processor.emit(&mut log_data);
+        processor.force_flush().unwrap();
+
+        ack.await.unwrap();
+        assert_eq!(2, exporter.get_emitted_logs().unwrap().len());
+        processor.shutdown().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_emit_with_ack_after_shutdown() {
+        let exporter = InMemoryLogsExporterBuilder::default().build();
+        let processor = BatchLogProcessor::new(
+            Box::new(exporter.clone()),
+            BatchConfig::default(),
+            runtime::Tokio,
+            None,
+        );
+        processor.shutdown().unwrap();
+
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+        assert!(processor.emit_with_ack(&mut log_data).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_on_demand_processor_exports_on_flush() {
+        let exporter = InMemoryLogsExporterBuilder::default()
+            .keep_records_on_shutdown()
+            .build();
+        let processor = FinishedLogBatchOnDemandProcessor::builder(exporter.clone())
+            .with_max_queue_size(2)
+            .build();
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+        for _ in 0..3 {
+            processor.emit(&mut log_data);
+        }
+        assert!(exporter.get_emitted_logs().unwrap().is_empty());
+        assert_eq!(processor.dropped_telemetry().queue_full, 1);
+
+        processor.flush_async().await.unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 2);
+
+        // the acknowledgement resolves once the record is exported
+        processor.emit_with_ack(&mut log_data).await.unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 3);
+
+        processor.emit(&mut log_data);
+        processor.shutdown().unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 4);
+        processor.emit(&mut log_data);
+        processor.force_flush().unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_dedicated_thread_processor() {
+        let exporter = InMemoryLogsExporterBuilder::default()
+            .keep_records_on_shutdown()
+            .build();
+        let processor = DedicatedThreadBatchLogProcessor::builder(exporter.clone()).build();
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+
+        processor.emit(&mut log_data);
+        processor.force_flush().unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 1);
+
+        let ack = processor.emit_with_ack(&mut log_data);
+        processor.force_flush().unwrap();
+        futures_executor::block_on(ack).unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 2);
+
+        processor.emit(&mut log_data);
+        processor.shutdown().unwrap();
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 3);
+        processor.emit(&mut log_data);
+        assert!(processor.force_flush().is_err());
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 3);
+    }
+
+    // the worker exports on its own, even while the current thread runtime
+    // of the application is blocked
+    #[tokio::test]
+    async fn test_dedicated_thread_processor_scheduled_export() {
+        let exporter = InMemoryLogsExporterBuilder::default()
+            .keep_records_on_shutdown()
+            .build();
+        let processor = DedicatedThreadBatchLogProcessor::builder(exporter.clone())
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_millis(10))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+
+        processor.emit(&mut log_data);
+        for _ in 0..500 {
+            if !exporter.get_emitted_logs().unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 1);
+
+        // dropping the processor exports the remaining logs
+        processor.emit(&mut log_data);
+        drop(processor);
+        for _ in 0..500 {
+            if exporter.get_emitted_logs().unwrap().len() == 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 2);
+    }
+
+    /// Signals each export it starts, and waits for a release to finish it.
+    #[derive(Debug)]
+    struct BlockingExporter {
+        started: std::sync::mpsc::Sender<()>,
+        release: Arc<Mutex<std::sync::mpsc::Receiver<()>>>,
+        exported: Arc<Mutex<usize>>,
+    }
+
+    #[async_trait]
+    impl LogExporter for BlockingExporter {
+        async fn export<'a>(&mut self, batch: Vec<Cow<'a, LogData>>) -> LogResult<()> {
+            let _ = self.started.send(());
+            let _ = self.release.lock().unwrap().recv();
+            *self.exported.lock().unwrap() += batch.len();
+            Ok(())
+        }
+    }
+
+    // flush and shutdown requests are not rejected while the queue is full
+    #[test]
+    fn test_dedicated_thread_processor_flush_with_full_queue() {
+        let (started_sender, started) = std::sync::mpsc::channel();
+        let (release, release_receiver) = std::sync::mpsc::channel();
+        let exported = Arc::new(Mutex::new(0));
+        let exporter = BlockingExporter {
+            started: started_sender,
+            release: Arc::new(Mutex::new(release_receiver)),
+            exported: exported.clone(),
+        };
+        let processor = Arc::new(
+            DedicatedThreadBatchLogProcessor::builder(exporter)
+                .with_batch_config(
+                    BatchConfigBuilder::default()
+                        .with_max_queue_size(2)
+                        .with_max_export_batch_size(1)
+                        .with_scheduled_delay(Duration::from_secs(3600))
+                        .build()
+                        .unwrap(),
+                )
+                .build(),
+        );
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+
+        // the worker blocks exporting the first log, the queue fills up
+        processor.emit(&mut log_data);
+        started.recv().unwrap();
+        for _ in 0..3 {
+            processor.emit(&mut log_data);
+        }
+        assert_eq!(processor.dropped_telemetry().queue_full, 1);
+
+        let flushing = processor.clone();
+        let flush = std::thread::spawn(move || flushing.force_flush());
+        std::thread::sleep(Duration::from_millis(10));
+        for _ in 0..3 {
+            release.send(()).unwrap();
+        }
+
+        assert!(flush.join().unwrap().is_ok());
+        assert_eq!(*exported.lock().unwrap(), 3);
+        assert!(processor.shutdown().is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_budget_evicts_debug_logs() {
+        let exporter = InMemoryLogsExporterBuilder::default().build();
+        let mut debug_log = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+        debug_log.record.severity_number = Some(Severity::Debug);
+        let mut info_log = debug_log.clone();
+        info_log.record.severity_number = Some(Severity::Info);
+
+        // fits two log records and a half
+        let budget = TelemetryBudget::new(log_size(&debug_log) * 5 / 2);
+        let processor = BatchLogProcessor::builder(exporter.clone(), runtime::Tokio)
+            .with_budget(budget.clone())
+            .build();
+
+        processor.emit(&mut debug_log.clone());
+        processor.emit(&mut debug_log.clone());
+        // each evicts a debug log
+        processor.emit(&mut info_log.clone());
+        processor.emit(&mut info_log.clone());
+        // dropped, nothing is left to evict
+        processor.emit(&mut info_log);
+        processor.force_flush().unwrap();
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        let severities: Vec<_> = logs
+            .iter()
+            .map(|log| log.record.severity_number.unwrap())
+            .collect();
+        assert_eq!(severities, [Severity::Info, Severity::Info]);
+        assert_eq!(processor.dropped_telemetry().memory_budget, 3);
+        assert_eq!(budget.used_bytes(BudgetPriority::DebugLogs), 0);
+        assert_eq!(budget.used_bytes(BudgetPriority::Regular), 0);
+        processor.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_simple_emit_with_ack() {
+        let exporter = InMemoryLogsExporterBuilder::default()
+            .keep_records_on_shutdown()
+            .build();
+        let processor = SimpleLogProcessor::new(Box::new(exporter.clone()));
+        let mut log_data = LogData {
+            record: Default::default(),
+            instrumentation: Default::default(),
+            resource: None,
+        };
+
+        futures_executor::block_on(processor.emit_with_ack(&mut log_data)).unwrap();
+        assert_eq!(1, exporter.get_emitted_logs().unwrap().len());
+
+        processor.shutdown().unwrap();
+        assert!(futures_executor::block_on(processor.emit_with_ack(&mut log_data)).is_err());
+    }
+
     #[test]
     fn test_simple_shutdown() {
         let exporter = InMemoryLogsExporterBuilder::default()
@@ -792,6 +2118,7 @@ mod tests {
         let mut log_data = LogData {
             record: Default::default(),
             instrumentation: Default::default(),
+            resource: None,
         };
 
         processor.emit(&mut log_data);
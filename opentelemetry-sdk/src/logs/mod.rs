@@ -1,15 +1,23 @@
 //! # OpenTelemetry Log SDK
 
+mod config;
 mod log_emitter;
+mod log_limit;
 mod log_processor;
 mod record;
+mod trace_based_filter;
 
-pub use log_emitter::{Builder, Logger, LoggerProvider};
+pub use config::Config;
+pub use log_emitter::{Builder, Logger, LoggerProvider, LoggerProviderGuard};
+pub use log_limit::LogLimits;
 pub use log_processor::{
-    BatchConfig, BatchConfigBuilder, BatchLogProcessor, BatchLogProcessorBuilder, LogProcessor,
+    BatchConfig, BatchConfigBuilder, BatchLogProcessor, BatchLogProcessorBuilder,
+    DedicatedThreadBatchLogProcessor, DedicatedThreadBatchLogProcessorBuilder,
+    FinishedLogBatchOnDemandProcessor, FinishedLogBatchOnDemandProcessorBuilder, LogProcessor,
     SimpleLogProcessor,
 };
 pub use record::{LogRecord, TraceContext};
+pub use trace_based_filter::TraceBasedLogFilterProcessor;
 
 #[cfg(all(test, feature = "testing"))]
 mod tests {
@@ -13,6 +13,13 @@ pub struct LogRecord {
     /// Event name. Optional as not all the logging API support it.
     pub event_name: Option<Cow<'static, str>>,
 
+    /// Numeric event id, for consumers identifying events by number such as
+    /// the Windows Event Log.
+    pub event_id: Option<u32>,
+
+    /// Version of the event identified by `event_id`.
+    pub event_version: Option<u8>,
+
     /// Target of the log record
     pub target: Option<Cow<'static, str>>,
 
@@ -35,6 +42,10 @@ pub struct LogRecord {
 
     /// Additional attributes associated with this record
     pub attributes: Option<Vec<(Key, AnyValue)>>,
+
+    /// The number of attributes dropped because they were above the
+    /// configured [`LogLimits`](crate::logs::LogLimits).
+    pub dropped_attributes_count: u32,
 }
 
 impl opentelemetry::logs::LogRecord for LogRecord {
@@ -45,6 +56,14 @@ impl opentelemetry::logs::LogRecord for LogRecord {
         self.event_name = Some(name.into());
     }
 
+    fn set_event_id(&mut self, id: u32) {
+        self.event_id = Some(id);
+    }
+
+    fn set_event_version(&mut self, version: u8) {
+        self.event_version = Some(version);
+    }
+
     // Sets the `target` of a record
     fn set_target<T>(&mut self, _target: T)
     where
@@ -99,7 +118,10 @@ impl opentelemetry::logs::LogRecord for LogRecord {
 
 /// TraceContext stores the trace context for logs that have an associated
 /// span.
-#[derive(Debug, Clone)]
+///
+/// Only the ids and flags of the span are kept, so capturing the trace
+/// context of a log record is a plain copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct TraceContext {
     /// Trace id
@@ -134,6 +156,15 @@ mod tests {
         assert_eq!(log_record.event_name, Some(Cow::Borrowed("test_event")));
     }
 
+    #[test]
+    fn test_set_event_id_and_version() {
+        let mut log_record = LogRecord::default();
+        log_record.set_event_id(4624);
+        log_record.set_event_version(2);
+        assert_eq!(log_record.event_id, Some(4624));
+        assert_eq!(log_record.event_version, Some(2));
+    }
+
     #[test]
     fn test_set_target() {
         let mut log_record = LogRecord::default();
@@ -0,0 +1,187 @@
+use crate::{export::logs::LogData, logs::LogProcessor, Resource, ShutdownResult};
+use futures_util::future::{self, BoxFuture};
+use opentelemetry::logs::{LogResult, Severity};
+#[cfg(feature = "logs_level_enabled")]
+use opentelemetry::trace::TraceContextExt;
+use std::time::Duration;
+
+/// A [`LogProcessor`] dropping log records below a severity when the trace
+/// they were emitted in is not sampled.
+///
+/// Keeping verbose logs only for sampled traces keeps the volume of logs
+/// proportional to the volume of traces, and the verbose logs which are
+/// exported can be correlated with their trace. Records emitted outside of a
+/// span, and records without a severity, are always passed on.
+///
+/// Records which are not dropped are passed to the wrapped processor.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature="testing")]
+/// # {
+/// use opentelemetry::logs::Severity;
+/// use opentelemetry_sdk::export::logs::LogExporter;
+/// use opentelemetry_sdk::logs::{BatchLogProcessor, LoggerProvider, TraceBasedLogFilterProcessor};
+/// use opentelemetry_sdk::runtime;
+///
+/// fn init_logs<E: LogExporter + 'static>(exporter: E) -> LoggerProvider {
+///     let batch = BatchLogProcessor::builder(exporter, runtime::Tokio).build();
+///     // Debug records are only exported for sampled traces.
+///     LoggerProvider::builder()
+///         .with_log_processor(TraceBasedLogFilterProcessor::new(batch, Severity::Info))
+///         .build()
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TraceBasedLogFilterProcessor<P> {
+    inner: P,
+    min_severity: Severity,
+}
+
+impl<P: LogProcessor> TraceBasedLogFilterProcessor<P> {
+    /// Wraps `inner`, dropping records below `min_severity` emitted in an
+    /// unsampled trace.
+    pub fn new(inner: P, min_severity: Severity) -> Self {
+        TraceBasedLogFilterProcessor {
+            inner,
+            min_severity,
+        }
+    }
+
+    fn is_dropped(&self, data: &LogData) -> bool {
+        let unsampled = data
+            .record
+            .trace_context
+            .and_then(|cx| cx.trace_flags)
+            .map_or(false, |flags| !flags.is_sampled());
+
+        unsampled
+            && data
+                .record
+                .severity_number
+                .map_or(false, |severity| severity < self.min_severity)
+    }
+}
+
+impl<P: LogProcessor> LogProcessor for TraceBasedLogFilterProcessor<P> {
+    fn emit(&self, data: &mut LogData) {
+        if !self.is_dropped(data) {
+            self.inner.emit(data);
+        }
+    }
+
+    fn emit_with_ack(&self, data: &mut LogData) -> BoxFuture<'static, LogResult<()>> {
+        if self.is_dropped(data) {
+            Box::pin(future::ready(Ok(())))
+        } else {
+            self.inner.emit_with_ack(data)
+        }
+    }
+
+    fn force_flush(&self) -> LogResult<()> {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> LogResult<()> {
+        self.inner.shutdown()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool {
+        let unsampled = opentelemetry::Context::map_current(|cx| {
+            cx.has_active_span() && !cx.span().span_context().is_sampled()
+        });
+
+        !(unsampled && level < self.min_severity) && self.inner.event_enabled(level, target, name)
+    }
+
+    fn set_resource(&self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::logs::{LoggerProvider, SimpleLogProcessor};
+    use crate::testing::logs::InMemoryLogsExporter;
+    use opentelemetry::logs::{LogRecord as _, Logger as _, LoggerProvider as _};
+    #[cfg(not(feature = "logs_level_enabled"))]
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+    use opentelemetry::Context;
+
+    fn span_context(trace_flags: TraceFlags) -> Context {
+        Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from(1),
+            SpanId::from(2),
+            trace_flags,
+            true,
+            TraceState::default(),
+        ))
+    }
+
+    #[test]
+    fn drops_records_below_min_severity_in_unsampled_traces() {
+        let exporter = InMemoryLogsExporter::default();
+        let provider = LoggerProvider::builder()
+            .with_log_processor(TraceBasedLogFilterProcessor::new(
+                SimpleLogProcessor::new(Box::new(exporter.clone())),
+                Severity::Info,
+            ))
+            .build();
+        let logger = provider.logger("test-logger");
+        let emit = |severity: Severity, body: &'static str| {
+            let mut record = logger.create_log_record();
+            record.set_severity_number(severity);
+            record.set_body(body.into());
+            logger.emit(record);
+        };
+
+        {
+            let _guard = span_context(TraceFlags::default()).attach();
+            emit(Severity::Debug, "unsampled debug");
+            emit(Severity::Info, "unsampled info");
+        }
+        {
+            let _guard = span_context(TraceFlags::SAMPLED).attach();
+            emit(Severity::Debug, "sampled debug");
+        }
+        emit(Severity::Debug, "no span debug");
+
+        let bodies = exporter
+            .get_emitted_logs()
+            .unwrap()
+            .into_iter()
+            .map(|log| log.record.body.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bodies,
+            vec![
+                "unsampled info".into(),
+                "sampled debug".into(),
+                "no span debug".into()
+            ]
+        );
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    #[test]
+    fn event_disabled_below_min_severity_in_unsampled_traces() {
+        let processor = TraceBasedLogFilterProcessor::new(
+            SimpleLogProcessor::new(Box::new(InMemoryLogsExporter::default())),
+            Severity::Info,
+        );
+
+        assert!(processor.event_enabled(Severity::Debug, "target", "name"));
+        let _guard = span_context(TraceFlags::default()).attach();
+        assert!(!processor.event_enabled(Severity::Debug, "target", "name"));
+        assert!(processor.event_enabled(Severity::Info, "target", "name"));
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 
+#[cfg(feature = "experimental_metrics_custom_aggregation")]
+use crate::metrics::aggregator::CustomAggregation;
 use crate::metrics::internal::{EXPO_MAX_SCALE, EXPO_MIN_SCALE};
 use opentelemetry::metrics::{MetricsError, Result};
 
@@ -89,6 +91,12 @@ pub enum Aggregation {
         /// `false`
         record_min_max: bool,
     },
+
+    /// A user-defined aggregation, see [`Aggregator`].
+    ///
+    /// [`Aggregator`]: crate::metrics::Aggregator
+    #[cfg(feature = "experimental_metrics_custom_aggregation")]
+    Custom(CustomAggregation),
 }
 
 impl fmt::Display for Aggregation {
@@ -101,6 +109,8 @@ impl fmt::Display for Aggregation {
             Aggregation::LastValue => "LastValue",
             Aggregation::ExplicitBucketHistogram { .. } => "ExplicitBucketHistogram",
             Aggregation::Base2ExponentialHistogram { .. } => "Base2ExponentialHistogram",
+            #[cfg(feature = "experimental_metrics_custom_aggregation")]
+            Aggregation::Custom(_) => "Custom",
         };
 
         f.write_str(name)
@@ -143,6 +153,26 @@ impl Aggregation {
 
                 Ok(())
             }
+            #[cfg(feature = "experimental_metrics_custom_aggregation")]
+            Aggregation::Custom(_) => Ok(()),
+        }
+    }
+
+    /// Whether the values of this aggregation can be converted to another
+    /// unit, see [`Stream::unit_conversion`](crate::metrics::Stream::unit_conversion).
+    ///
+    /// Exponential histogram buckets depend on the recorded values and can not
+    /// be rescaled, custom aggregations are opaque.
+    pub(crate) fn supports_unit_conversion(&self) -> bool {
+        match self {
+            Aggregation::Drop
+            | Aggregation::Default
+            | Aggregation::Sum
+            | Aggregation::LastValue
+            | Aggregation::ExplicitBucketHistogram { .. } => true,
+            Aggregation::Base2ExponentialHistogram { .. } => false,
+            #[cfg(feature = "experimental_metrics_custom_aggregation")]
+            Aggregation::Custom(_) => false,
         }
     }
 }
@@ -0,0 +1,175 @@
+//! User-defined aggregations of metric streams.
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use super::data::{self, Temporality};
+use super::AttributeSet;
+
+/// A user-defined aggregation of the measurements of a metric stream, for
+/// example a t-digest of latencies or a distinct count of users.
+///
+/// Aggregators are selected for the streams of a view with
+/// [`Aggregation::Custom`]. Every stream of every reader gets its own
+/// aggregator, built by the [`CustomAggregation`] factory when the instrument
+/// is created.
+///
+/// # Collect cycle
+///
+/// * [`measure`] is called on the thread recording the measurement, possibly
+///   concurrently, with the attributes kept by the view. Measurements are
+///   converted to `f64`. The cardinality limit of the stream is not applied,
+///   the aggregator is responsible for bounding its memory.
+/// * [`collect`] is called once per collection of the reader, with the
+///   temporality the reader selected for the instrument. It must not block on
+///   [`measure`] for long, as recording threads wait meanwhile.
+///
+/// [`measure`]: Aggregator::measure
+/// [`collect`]: Aggregator::collect
+/// [`Aggregation::Custom`]: crate::metrics::Aggregation::Custom
+///
+/// # Examples
+///
+/// An aggregator reporting the maximum value recorded per attribute set as a
+/// gauge:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::sync::Mutex;
+/// use std::time::SystemTime;
+///
+/// use opentelemetry::KeyValue;
+/// use opentelemetry_sdk::metrics::data::{self, DataPoint, Gauge, Temporality};
+/// use opentelemetry_sdk::metrics::{
+///     new_view, Aggregation, Aggregator, AttributeSet, CustomAggregation, Instrument,
+///     SdkMeterProvider, Stream,
+/// };
+///
+/// #[derive(Default)]
+/// struct Max(Mutex<HashMap<AttributeSet, f64>>);
+///
+/// impl Aggregator for Max {
+///     fn measure(&self, value: f64, attributes: &AttributeSet) {
+///         if let Ok(mut values) = self.0.lock() {
+///             let max = values.entry(attributes.clone()).or_insert(value);
+///             *max = max.max(value);
+///         }
+///     }
+///
+///     fn collect(
+///         &self,
+///         temporality: Temporality,
+///         _dest: Option<&mut dyn data::Aggregation>,
+///     ) -> (usize, Option<Box<dyn data::Aggregation>>) {
+///         let Ok(mut values) = self.0.lock() else {
+///             return (0, None);
+///         };
+///         let time = Some(SystemTime::now());
+///         let data_points = values
+///             .iter()
+///             .map(|(attributes, max)| DataPoint {
+///                 attributes: attributes
+///                     .iter()
+///                     .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+///                     .collect(),
+///                 start_time: None,
+///                 time,
+///                 value: *max,
+///                 exemplars: vec![],
+///             })
+///             .collect::<Vec<_>>();
+///         if temporality == Temporality::Delta {
+///             values.clear();
+///         }
+///
+///         (data_points.len(), Some(Box::new(Gauge { data_points })))
+///     }
+/// }
+///
+/// let view = new_view(
+///     Instrument::new().name("latency"),
+///     Stream::new().aggregation(Aggregation::Custom(CustomAggregation::new(
+///         "max",
+///         Max::default,
+///     ))),
+/// )
+/// .unwrap();
+/// let provider = SdkMeterProvider::builder().with_view(view).build();
+/// # drop(provider);
+/// ```
+pub trait Aggregator: Send + Sync + 'static {
+    /// Records a measurement with its attributes.
+    fn measure(&self, value: f64, attributes: &AttributeSet);
+
+    /// Returns the number of data points collected and, if `dest` cannot be
+    /// reused, a new aggregation holding them.
+    ///
+    /// `dest` is the aggregation returned for this stream by the previous
+    /// collection, if any. It can be reused by downcasting it with
+    /// [`data::Aggregation::as_mut`] and replacing its data points, in which
+    /// case `None` is returned. Otherwise, or if `dest` is `None`, a new
+    /// aggregation must be returned.
+    ///
+    /// With [`Temporality::Delta`] the aggregator resets its state after
+    /// collecting, with [`Temporality::Cumulative`] it keeps it. If no data
+    /// point is collected, `0` is returned and the stream is not exported.
+    fn collect(
+        &self,
+        temporality: Temporality,
+        dest: Option<&mut dyn data::Aggregation>,
+    ) -> (usize, Option<Box<dyn data::Aggregation>>);
+}
+
+/// A named factory of [`Aggregator`]s, used with [`Aggregation::Custom`].
+///
+/// Two custom aggregations are equal if they were cloned from the same one.
+///
+/// [`Aggregation::Custom`]: crate::metrics::Aggregation::Custom
+#[derive(Clone)]
+pub struct CustomAggregation {
+    name: Cow<'static, str>,
+    factory: Arc<dyn Fn() -> Arc<dyn Aggregator> + Send + Sync>,
+}
+
+impl CustomAggregation {
+    /// Create a custom aggregation building an aggregator with `factory` for
+    /// every stream it is applied to.
+    pub fn new<A, F>(name: impl Into<Cow<'static, str>>, factory: F) -> Self
+    where
+        A: Aggregator,
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        CustomAggregation {
+            name: name.into(),
+            factory: Arc::new(move || Arc::new(factory()) as Arc<dyn Aggregator>),
+        }
+    }
+
+    /// The name of this aggregation.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Builds the aggregator of a new stream.
+    pub(crate) fn build(&self) -> Arc<dyn Aggregator> {
+        (self.factory)()
+    }
+}
+
+impl fmt::Debug for CustomAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomAggregation")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl PartialEq for CustomAggregation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && std::ptr::eq(
+                Arc::as_ptr(&self.factory) as *const (),
+                Arc::as_ptr(&other.factory) as *const (),
+            )
+    }
+}
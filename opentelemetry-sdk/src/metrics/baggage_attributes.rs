@@ -0,0 +1,233 @@
+//! Measurement processor copying baggage entries into metric attributes.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::RwLock;
+
+use opentelemetry::{baggage::BaggageExt, Context, Key, KeyValue, Value};
+
+use super::MeasurementProcessor;
+
+/// The value recorded for a baggage key once its distinct values limit is
+/// reached.
+pub const BAGGAGE_OVERFLOW_VALUE: &str = "_OTHER";
+
+const DEFAULT_MAX_VALUES_PER_KEY: usize = 100;
+
+/// A [`MeasurementProcessor`] appending selected baggage entries of the
+/// current [`Context`] to the attributes of every measurement.
+///
+/// This allows e.g. per-tenant metrics, by propagating the tenant as baggage,
+/// without passing it to every call site recording a measurement.
+///
+/// Only the configured keys are extracted, baggage is often set by remote
+/// callers and must not add arbitrary attributes. Attributes passed at the
+/// call site take precedence over baggage entries with the same key.
+///
+/// # Cardinality
+///
+/// Each key records at most [`with_max_values_per_key`] distinct values, 100
+/// by default. Further values are recorded as [`BAGGAGE_OVERFLOW_VALUE`], so
+/// that callers cannot grow the number of data points without bounds.
+///
+/// [`with_max_values_per_key`]: BaggageMetricsAttributesExtractorBuilder::with_max_values_per_key
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::{baggage::BaggageExt, metrics::MeterProvider, Context, KeyValue};
+/// use opentelemetry_sdk::metrics::{BaggageMetricsAttributesExtractor, SdkMeterProvider};
+///
+/// let provider = SdkMeterProvider::builder()
+///     .with_measurement_processor(
+///         BaggageMetricsAttributesExtractor::builder()
+///             .with_key("tenant.id")
+///             .with_max_values_per_key(1_000)
+///             .build(),
+///     )
+///     .build();
+/// let counter = provider.meter("example").u64_counter("requests").init();
+///
+/// let _guard = Context::current_with_baggage([KeyValue::new("tenant.id", "acme")]).attach();
+/// // recorded with the attributes `method=GET` and `tenant.id=acme`
+/// counter.add(1, &[KeyValue::new("method", "GET")]);
+/// ```
+pub struct BaggageMetricsAttributesExtractor {
+    keys: Vec<Key>,
+    max_values_per_key: usize,
+    seen_values: RwLock<HashMap<Key, HashSet<String>>>,
+}
+
+impl BaggageMetricsAttributesExtractor {
+    /// Configure a new extractor.
+    pub fn builder() -> BaggageMetricsAttributesExtractorBuilder {
+        BaggageMetricsAttributesExtractorBuilder::default()
+    }
+
+    /// Returns `value`, or the overflow value if `key` already recorded the
+    /// maximum number of distinct values.
+    fn guard_cardinality(&self, key: &Key, value: &Value) -> Value {
+        let value_str = value.as_str();
+        if let Ok(seen) = self.seen_values.read() {
+            if seen
+                .get(key)
+                .map_or(false, |values| values.contains(value_str.as_ref()))
+            {
+                return value.clone();
+            }
+        }
+
+        let Ok(mut seen) = self.seen_values.write() else {
+            return Value::from(BAGGAGE_OVERFLOW_VALUE);
+        };
+        let values = seen.entry(key.clone()).or_default();
+        if values.contains(value_str.as_ref()) {
+            value.clone()
+        } else if values.len() < self.max_values_per_key {
+            values.insert(value_str.into_owned());
+            value.clone()
+        } else {
+            Value::from(BAGGAGE_OVERFLOW_VALUE)
+        }
+    }
+}
+
+impl MeasurementProcessor for BaggageMetricsAttributesExtractor {
+    fn process(&self, attributes: &mut Vec<KeyValue>) {
+        let cx = Context::current();
+        let baggage = cx.baggage();
+        if baggage.is_empty() {
+            return;
+        }
+
+        for key in &self.keys {
+            if attributes.iter().any(|kv| &kv.key == key) {
+                continue;
+            }
+            if let Some(value) = baggage.get(key.clone()) {
+                let value = self.guard_cardinality(key, value);
+                attributes.push(KeyValue::new(key.clone(), value));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for BaggageMetricsAttributesExtractor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BaggageMetricsAttributesExtractor")
+            .field("keys", &self.keys)
+            .field("max_values_per_key", &self.max_values_per_key)
+            .finish()
+    }
+}
+
+/// Configuration of a [`BaggageMetricsAttributesExtractor`].
+#[derive(Debug)]
+pub struct BaggageMetricsAttributesExtractorBuilder {
+    keys: Vec<Key>,
+    max_values_per_key: usize,
+}
+
+impl Default for BaggageMetricsAttributesExtractorBuilder {
+    fn default() -> Self {
+        BaggageMetricsAttributesExtractorBuilder {
+            keys: Vec::new(),
+            max_values_per_key: DEFAULT_MAX_VALUES_PER_KEY,
+        }
+    }
+}
+
+impl BaggageMetricsAttributesExtractorBuilder {
+    /// Extract the baggage entry with the given key, if present.
+    pub fn with_key(mut self, key: impl Into<Key>) -> Self {
+        let key = key.into();
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+        self
+    }
+
+    /// Extract the baggage entries with the given keys, if present.
+    pub fn with_keys<K: Into<Key>>(self, keys: impl IntoIterator<Item = K>) -> Self {
+        keys.into_iter()
+            .fold(self, |builder, key| builder.with_key(key))
+    }
+
+    /// Set the maximum number of distinct values recorded for each key.
+    ///
+    /// Defaults to 100.
+    pub fn with_max_values_per_key(mut self, max_values_per_key: usize) -> Self {
+        self.max_values_per_key = max_values_per_key;
+        self
+    }
+
+    /// Create the extractor.
+    pub fn build(self) -> BaggageMetricsAttributesExtractor {
+        BaggageMetricsAttributesExtractor {
+            keys: self.keys,
+            max_values_per_key: self.max_values_per_key,
+            seen_values: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    fn process(extractor: &BaggageMetricsAttributesExtractor, tenant: &str) -> Vec<KeyValue> {
+        let _guard = Context::current_with_baggage([
+            KeyValue::new("tenant.id", tenant.to_string()),
+            KeyValue::new("user.id", "secret"),
+        ])
+        .attach();
+        let mut attributes = vec![KeyValue::new("method", "GET")];
+        extractor.process(&mut attributes);
+        attributes
+    }
+
+    #[test]
+    fn extracts_configured_keys_only() {
+        let extractor = BaggageMetricsAttributesExtractor::builder()
+            .with_key("tenant.id")
+            .build();
+
+        assert_eq!(
+            process(&extractor, "acme"),
+            vec![
+                KeyValue::new("method", "GET"),
+                KeyValue::new("tenant.id", "acme")
+            ]
+        );
+    }
+
+    #[test]
+    fn call_site_attributes_take_precedence() {
+        let extractor = BaggageMetricsAttributesExtractor::builder()
+            .with_keys(["method", "tenant.id"])
+            .build();
+        let _guard = Context::current_with_baggage([KeyValue::new("method", "POST")]).attach();
+
+        let mut attributes = vec![KeyValue::new("method", "GET")];
+        extractor.process(&mut attributes);
+
+        assert_eq!(attributes, vec![KeyValue::new("method", "GET")]);
+    }
+
+    #[test]
+    fn values_over_limit_overflow() {
+        let extractor = BaggageMetricsAttributesExtractor::builder()
+            .with_key("tenant.id")
+            .with_max_values_per_key(2)
+            .build();
+
+        let tenant = |attributes: Vec<KeyValue>| attributes[1].value.clone();
+        assert_eq!(tenant(process(&extractor, "a")), Value::from("a"));
+        assert_eq!(tenant(process(&extractor, "b")), Value::from("b"));
+        assert_eq!(
+            tenant(process(&extractor, "c")),
+            Value::from(BAGGAGE_OVERFLOW_VALUE)
+        );
+        // values seen before the limit was reached are still recorded
+        assert_eq!(tenant(process(&extractor, "a")), Value::from("a"));
+    }
+}
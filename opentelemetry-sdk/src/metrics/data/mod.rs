@@ -19,6 +19,40 @@ pub struct ResourceMetrics {
     pub scope_metrics: Vec<ScopeMetrics>,
 }
 
+impl ResourceMetrics {
+    /// Copies the metrics, or returns `None` if they contain aggregations of
+    /// types defined outside of this crate, which can't be copied.
+    pub(crate) fn try_clone(&self) -> Option<ResourceMetrics> {
+        let scope_metrics = self
+            .scope_metrics
+            .iter()
+            .map(|scope_metrics| {
+                let metrics = scope_metrics
+                    .metrics
+                    .iter()
+                    .map(|metric| {
+                        Some(Metric {
+                            name: metric.name.clone(),
+                            description: metric.description.clone(),
+                            unit: metric.unit.clone(),
+                            data: clone_aggregation(metric.data.as_ref())?,
+                        })
+                    })
+                    .collect::<Option<_>>()?;
+                Some(ScopeMetrics {
+                    scope: scope_metrics.scope.clone(),
+                    metrics,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        Some(ResourceMetrics {
+            resource: self.resource.clone(),
+            scope_metrics,
+        })
+    }
+}
+
 /// A collection of metrics produced by a meter.
 #[derive(Default, Debug)]
 pub struct ScopeMetrics {
@@ -55,6 +89,42 @@ pub trait Aggregation: fmt::Debug + any::Any + Send + Sync {
     fn as_mut(&mut self) -> &mut dyn any::Any;
 }
 
+fn clone_aggregation(data: &dyn Aggregation) -> Option<Box<dyn Aggregation>> {
+    clone_aggregation_of::<i64>(data)
+        .or_else(|| clone_aggregation_of::<u64>(data))
+        .or_else(|| clone_aggregation_of::<f64>(data))
+}
+
+fn clone_aggregation_of<T>(data: &dyn Aggregation) -> Option<Box<dyn Aggregation>>
+where
+    T: Copy + fmt::Debug + Send + Sync + 'static,
+{
+    let data = data.as_any();
+    if let Some(gauge) = data.downcast_ref::<Gauge<T>>() {
+        Some(Box::new(Gauge {
+            data_points: gauge.data_points.clone(),
+        }))
+    } else if let Some(sum) = data.downcast_ref::<Sum<T>>() {
+        Some(Box::new(Sum {
+            data_points: sum.data_points.clone(),
+            temporality: sum.temporality,
+            is_monotonic: sum.is_monotonic,
+        }))
+    } else if let Some(histogram) = data.downcast_ref::<Histogram<T>>() {
+        Some(Box::new(Histogram {
+            data_points: histogram.data_points.clone(),
+            temporality: histogram.temporality,
+        }))
+    } else if let Some(histogram) = data.downcast_ref::<ExponentialHistogram<T>>() {
+        Some(Box::new(ExponentialHistogram {
+            data_points: histogram.data_points.clone(),
+            temporality: histogram.temporality,
+        }))
+    } else {
+        None
+    }
+}
+
 /// A measurement of the current value of an instrument.
 #[derive(Debug)]
 pub struct Gauge<T> {
@@ -255,8 +325,28 @@ pub struct ExponentialHistogramDataPoint<T> {
     pub exemplars: Vec<Exemplar<T>>,
 }
 
+impl<T: Copy> Clone for ExponentialHistogramDataPoint<T> {
+    fn clone(&self) -> Self {
+        Self {
+            attributes: self.attributes.clone(),
+            start_time: self.start_time,
+            time: self.time,
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            sum: self.sum,
+            scale: self.scale,
+            zero_count: self.zero_count,
+            positive_bucket: self.positive_bucket.clone(),
+            negative_bucket: self.negative_bucket.clone(),
+            zero_threshold: self.zero_threshold,
+            exemplars: self.exemplars.clone(),
+        }
+    }
+}
+
 /// A set of bucket counts, encoded in a contiguous array of counts.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ExponentialBucket {
     /// The bucket index of the first entry in the `counts` vec.
     pub offset: i32,
@@ -0,0 +1,69 @@
+//! Configuration of the measurements sampled as exemplars.
+use std::env;
+
+/// The environment variable selecting the [ExemplarFilter] of a meter provider.
+const OTEL_METRICS_EXEMPLAR_FILTER: &str = "OTEL_METRICS_EXEMPLAR_FILTER";
+
+/// Determines which measurements are eligible to be sampled as
+/// [Exemplar]s.
+///
+/// Exemplars are recorded for sums and histograms, they hold the trace and
+/// span IDs of the span active when the measurement was made, as well as the
+/// attributes removed from the measurement by a view's attribute filter.
+///
+/// If not configured through [MeterProviderBuilder::with_exemplar_filter], the
+/// filter is read from the `OTEL_METRICS_EXEMPLAR_FILTER` environment
+/// variable, which accepts `always_on`, `always_off` and `trace_based`.
+/// [ExemplarFilter::TraceBased] is used if it is unset or invalid.
+///
+/// [Exemplar]: crate::metrics::data::Exemplar
+/// [MeterProviderBuilder::with_exemplar_filter]: crate::metrics::MeterProviderBuilder::with_exemplar_filter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExemplarFilter {
+    /// Every measurement is offered to the exemplar reservoirs.
+    AlwaysOn,
+    /// No exemplars are recorded.
+    AlwaysOff,
+    /// Only measurements made while a sampled span is active are offered to
+    /// the exemplar reservoirs.
+    ///
+    /// Exemplars are only recorded with this filter if the `trace` feature is
+    /// enabled.
+    #[default]
+    TraceBased,
+}
+
+impl ExemplarFilter {
+    /// Reads the filter from the `OTEL_METRICS_EXEMPLAR_FILTER` environment
+    /// variable, falling back to the default filter.
+    pub(crate) fn from_env() -> Self {
+        match env::var(OTEL_METRICS_EXEMPLAR_FILTER)
+            .map(|filter| filter.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Ok("always_on") => ExemplarFilter::AlwaysOn,
+            Ok("always_off") => ExemplarFilter::AlwaysOff,
+            _ => ExemplarFilter::TraceBased,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exemplar_filter_from_env() {
+        for (value, expected) in [
+            (Some("always_on"), ExemplarFilter::AlwaysOn),
+            (Some("ALWAYS_OFF"), ExemplarFilter::AlwaysOff),
+            (Some("trace_based"), ExemplarFilter::TraceBased),
+            (Some("invalid"), ExemplarFilter::TraceBased),
+            (None, ExemplarFilter::TraceBased),
+        ] {
+            temp_env::with_var(OTEL_METRICS_EXEMPLAR_FILTER, value, || {
+                assert_eq!(ExemplarFilter::from_env(), expected, "{:?}", value);
+            });
+        }
+    }
+}
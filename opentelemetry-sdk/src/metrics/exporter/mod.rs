@@ -0,0 +1,78 @@
+//! Interfaces for exporting metrics
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use opentelemetry::metrics::Result;
+
+use crate::metrics::{
+    data::ResourceMetrics,
+    reader::{AggregationSelector, TemporalitySelector},
+};
+
+mod tee;
+
+pub use tee::{TeePushMetricsExporter, TeePushMetricsExporterBuilder};
+
+/// The reason a [PushMetricsExporter] is asked to export metric data.
+///
+/// Exporters can use this to prioritize work, e.g. to avoid retrying an export
+/// that happens during shutdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FlushReason {
+    /// The export was triggered by the regular collection interval.
+    Interval,
+    /// The export was triggered by an explicit force flush.
+    ForceFlush,
+    /// The export was triggered by the reader shutting down.
+    Shutdown,
+}
+
+/// Exporter handles the delivery of metric data to external receivers.
+///
+/// This is the final component in the metric push pipeline.
+#[async_trait]
+pub trait PushMetricsExporter:
+    AggregationSelector + TemporalitySelector + Send + Sync + 'static
+{
+    /// Export serializes and transmits metric data to a receiver.
+    ///
+    /// All retry logic must be contained in this function. The SDK does not
+    /// implement any retry logic. All errors returned by this function are
+    /// considered unrecoverable and will be reported to a configured error
+    /// Handler.
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()>;
+
+    /// Export serializes and transmits metric data to a receiver, given the
+    /// [FlushReason] that triggered the export.
+    ///
+    /// The default implementation ignores the reason and calls [export].
+    ///
+    /// [export]: PushMetricsExporter::export
+    async fn export_with_reason(
+        &self,
+        metrics: &mut ResourceMetrics,
+        _reason: FlushReason,
+    ) -> Result<()> {
+        self.export(metrics).await
+    }
+
+    /// Flushes any metric data held by an exporter.
+    async fn force_flush(&self) -> Result<()>;
+
+    /// Releases any held computational resources.
+    ///
+    /// After Shutdown is called, calls to Export will perform no operation and
+    /// instead will return an error indicating the shutdown state.
+    fn shutdown(&self) -> Result<()>;
+
+    /// Releases any held computational resources, completing within `deadline`.
+    ///
+    /// The default implementation ignores the deadline and calls [shutdown].
+    ///
+    /// [shutdown]: PushMetricsExporter::shutdown
+    fn shutdown_with_deadline(&self, _deadline: Duration) -> Result<()> {
+        self.shutdown()
+    }
+}
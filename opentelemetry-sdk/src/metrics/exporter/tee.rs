@@ -0,0 +1,335 @@
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use futures_util::future::{self, Either};
+use opentelemetry::metrics::{MetricsError, Result};
+use std::iter;
+
+use crate::metrics::{
+    data::{ResourceMetrics, Temporality},
+    exporter::{FlushReason, PushMetricsExporter},
+    reader::{AggregationSelector, TemporalitySelector},
+    Aggregation, InstrumentKind,
+};
+use crate::runtime::Runtime;
+
+/// Default maximum duration a single child exporter may take to export.
+const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const INSTRUMENT_KINDS: [InstrumentKind; 7] = [
+    InstrumentKind::Counter,
+    InstrumentKind::UpDownCounter,
+    InstrumentKind::Histogram,
+    InstrumentKind::ObservableCounter,
+    InstrumentKind::ObservableUpDownCounter,
+    InstrumentKind::Gauge,
+    InstrumentKind::ObservableGauge,
+];
+
+struct TeeChild {
+    exporter: Box<dyn PushMetricsExporter>,
+    timeout: Duration,
+}
+
+/// A [PushMetricsExporter] that exports metric data to multiple child
+/// exporters.
+///
+/// Children are exported to concurrently, each with its own copy of the data
+/// and its own timeout. A failing or slow child does not prevent the remaining
+/// children from receiving the data. If any child fails or times out, the
+/// returned error lists each failure by the child's index. Data of custom
+/// aggregations, which can't be copied, is exported to one child after
+/// another instead.
+///
+/// All children receive data collected by the same reader, so they must
+/// select the same temporality for every instrument kind, and the aggregation
+/// of the first child is used for every child. To export with different
+/// temporalities, register one reader per exporter instead.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature="testing")]
+/// # {
+/// use opentelemetry_sdk::metrics::exporter::TeePushMetricsExporter;
+/// use opentelemetry_sdk::runtime;
+/// use opentelemetry_sdk::testing::metrics::InMemoryMetricsExporter;
+/// use std::time::Duration;
+///
+/// let exporter = TeePushMetricsExporter::builder(runtime::Tokio)
+///     .with_exporter(InMemoryMetricsExporter::default())
+///     .with_exporter_timeout(InMemoryMetricsExporter::default(), Duration::from_secs(5))
+///     .build()
+///     .expect("children select the same temporality");
+/// # drop(exporter);
+/// # }
+/// ```
+pub struct TeePushMetricsExporter<R: Runtime> {
+    children: Vec<TeeChild>,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for TeePushMetricsExporter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeePushMetricsExporter")
+            .field("children", &self.children.len())
+            .finish()
+    }
+}
+
+impl<R: Runtime> TeePushMetricsExporter<R> {
+    /// Create a new [TeePushMetricsExporterBuilder] using `runtime` to enforce
+    /// the per-exporter timeouts.
+    pub fn builder(runtime: R) -> TeePushMetricsExporterBuilder<R> {
+        TeePushMetricsExporterBuilder {
+            children: Vec::new(),
+            runtime,
+        }
+    }
+
+    async fn export_child(
+        &self,
+        idx: usize,
+        child: &TeeChild,
+        metrics: &mut ResourceMetrics,
+        reason: FlushReason,
+    ) -> Option<String> {
+        let export = child.exporter.export_with_reason(metrics, reason);
+        let timeout = self.runtime.delay(child.timeout);
+        match future::select(export, timeout).await {
+            Either::Left((Ok(()), _)) => None,
+            Either::Left((Err(err), _)) => Some(format!("exporter {idx}: {err}")),
+            Either::Right(_) => Some(format!("exporter {idx}: export timed out")),
+        }
+    }
+
+    fn into_result(errs: Vec<String>) -> Result<()> {
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(MetricsError::Other(errs.join(", ")))
+        }
+    }
+}
+
+impl<R: Runtime> AggregationSelector for TeePushMetricsExporter<R> {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.children
+            .first()
+            .map(|child| child.exporter.aggregation(kind))
+            .unwrap_or(Aggregation::Default)
+    }
+}
+
+impl<R: Runtime> TemporalitySelector for TeePushMetricsExporter<R> {
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.children
+            .first()
+            .map(|child| child.exporter.temporality(kind))
+            .unwrap_or(Temporality::Cumulative)
+    }
+}
+
+#[async_trait]
+impl<R: Runtime> PushMetricsExporter for TeePushMetricsExporter<R> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        self.export_with_reason(metrics, FlushReason::Interval)
+            .await
+    }
+
+    async fn export_with_reason(
+        &self,
+        metrics: &mut ResourceMetrics,
+        reason: FlushReason,
+    ) -> Result<()> {
+        let copies = (1..self.children.len())
+            .map(|_| metrics.try_clone())
+            .collect::<Option<Vec<_>>>();
+        let errs =
+            match copies {
+                Some(mut copies) => {
+                    let targets = iter::once(metrics).chain(copies.iter_mut());
+                    let exports = self.children.iter().zip(targets).enumerate().map(
+                        |(idx, (child, metrics))| self.export_child(idx, child, metrics, reason),
+                    );
+                    future::join_all(exports)
+                        .await
+                        .into_iter()
+                        .flatten()
+                        .collect()
+                }
+                None => {
+                    let mut errs = Vec::new();
+                    for (idx, child) in self.children.iter().enumerate() {
+                        errs.extend(self.export_child(idx, child, metrics, reason).await);
+                    }
+                    errs
+                }
+            };
+
+        Self::into_result(errs)
+    }
+
+    async fn force_flush(&self) -> Result<()> {
+        let flushes = self
+            .children
+            .iter()
+            .enumerate()
+            .map(|(idx, child)| async move {
+                child
+                    .exporter
+                    .force_flush()
+                    .await
+                    .err()
+                    .map(|err| format!("exporter {idx}: {err}"))
+            });
+        let errs = future::join_all(flushes)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+
+        Self::into_result(errs)
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        let errs = self
+            .children
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, child)| {
+                child
+                    .exporter
+                    .shutdown()
+                    .err()
+                    .map(|err| format!("exporter {idx}: {err}"))
+            })
+            .collect();
+
+        Self::into_result(errs)
+    }
+
+    fn shutdown_with_deadline(&self, deadline: Duration) -> Result<()> {
+        let errs = self
+            .children
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, child)| {
+                child
+                    .exporter
+                    .shutdown_with_deadline(deadline)
+                    .err()
+                    .map(|err| format!("exporter {idx}: {err}"))
+            })
+            .collect();
+
+        Self::into_result(errs)
+    }
+}
+
+/// Builder for [TeePushMetricsExporter].
+pub struct TeePushMetricsExporterBuilder<R: Runtime> {
+    children: Vec<TeeChild>,
+    runtime: R,
+}
+
+impl<R: Runtime> fmt::Debug for TeePushMetricsExporterBuilder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeePushMetricsExporterBuilder")
+            .field("children", &self.children.len())
+            .finish()
+    }
+}
+
+impl<R: Runtime> TeePushMetricsExporterBuilder<R> {
+    /// Add a child exporter using the default export timeout of 30 seconds.
+    pub fn with_exporter<E: PushMetricsExporter>(self, exporter: E) -> Self {
+        self.with_exporter_timeout(exporter, DEFAULT_EXPORT_TIMEOUT)
+    }
+
+    /// Add a child exporter that must finish exporting within `timeout`.
+    pub fn with_exporter_timeout<E: PushMetricsExporter>(
+        mut self,
+        exporter: E,
+        timeout: Duration,
+    ) -> Self {
+        self.children.push(TeeChild {
+            exporter: Box::new(exporter),
+            timeout,
+        });
+        self
+    }
+
+    /// Build the [TeePushMetricsExporter].
+    ///
+    /// Returns an error if the children select different temporalities for
+    /// the same instrument kind.
+    pub fn build(self) -> Result<TeePushMetricsExporter<R>> {
+        if let Some((first, rest)) = self.children.split_first() {
+            for kind in INSTRUMENT_KINDS {
+                let temporality = first.exporter.temporality(kind);
+                if let Some(idx) = rest
+                    .iter()
+                    .position(|child| child.exporter.temporality(kind) != temporality)
+                {
+                    return Err(MetricsError::Config(format!(
+                        "exporter {} selects a different temporality than exporter 0 for {kind:?}",
+                        idx + 1
+                    )));
+                }
+            }
+        }
+
+        Ok(TeePushMetricsExporter {
+            children: self.children,
+            runtime: self.runtime,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::metrics::{InMemoryMetricsExporter, InMemoryMetricsExporterBuilder};
+    use crate::{runtime, Resource};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn exports_to_all_children() {
+        let first = InMemoryMetricsExporter::default();
+        let second = InMemoryMetricsExporter::default();
+        let exporter = TeePushMetricsExporter::builder(runtime::Tokio)
+            .with_exporter(first.clone())
+            .with_exporter(second.clone())
+            .build()
+            .unwrap();
+        let mut rm = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: Vec::new(),
+        };
+
+        exporter.export(&mut rm).await.unwrap();
+
+        assert_eq!(first.get_finished_metrics().unwrap().len(), 1);
+        assert_eq!(second.get_finished_metrics().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_children_with_different_temporalities() {
+        struct DeltaTemporalitySelector;
+        impl TemporalitySelector for DeltaTemporalitySelector {
+            fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+                Temporality::Delta
+            }
+        }
+
+        let delta = InMemoryMetricsExporterBuilder::new()
+            .with_temporality_selector(DeltaTemporalitySelector)
+            .build();
+        let result = TeePushMetricsExporter::builder(runtime::Tokio)
+            .with_exporter(InMemoryMetricsExporter::default())
+            .with_exporter(delta)
+            .build();
+
+        assert!(matches!(result, Err(MetricsError::Config(_))));
+    }
+}
@@ -1,6 +1,15 @@
-use std::{any::Any, borrow::Cow, collections::HashSet, hash::Hash, marker, sync::Arc};
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::HashSet,
+    hash::Hash,
+    marker,
+    sync::atomic::{AtomicU64, Ordering},
+    sync::Arc,
+};
 
 use opentelemetry::{
+    global,
     metrics::{
         AsyncInstrument, MetricsError, Result, SyncCounter, SyncGauge, SyncHistogram,
         SyncUpDownCounter,
@@ -11,9 +20,15 @@ use opentelemetry::{
 use crate::{
     instrumentation::Scope,
     metrics::AttributeSet,
-    metrics::{aggregation::Aggregation, internal::Measure},
+    metrics::{
+        aggregation::Aggregation,
+        internal::{Measure, Number},
+    },
 };
 
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+use crate::metrics::measurement_processor::MeasurementProcessors;
+
 pub(crate) const EMPTY_MEASURE_MSG: &str = "no aggregators for observable instrument";
 
 /// The identifier of a group of instruments that all perform the same function.
@@ -72,6 +87,12 @@ pub struct Instrument {
     pub unit: Cow<'static, str>,
     /// The instrumentation that created the instrument.
     pub scope: Scope,
+    /// The aggregation advised by the author of the instrument, used when no
+    /// view configures one.
+    pub(crate) advised_aggregation: Option<Aggregation>,
+    /// The explicit bucket boundaries advised by the author of the
+    /// instrument, used when it is aggregated with the default boundaries.
+    pub(crate) advised_boundaries: Option<Vec<f64>>,
 }
 
 impl Instrument {
@@ -122,7 +143,7 @@ impl Instrument {
     }
 
     pub(crate) fn matches_name(&self, other: &Instrument) -> bool {
-        self.name.is_empty() || self.name.as_ref() == other.name.as_ref()
+        self.name.is_empty() || self.name.eq_ignore_ascii_case(&other.name)
     }
 
     pub(crate) fn matches_description(&self, other: &Instrument) -> bool {
@@ -180,6 +201,20 @@ pub struct Stream {
     /// dropped. If the set is empty, all attributes will be dropped, if `None` all
     /// attributes will be kept.
     pub allowed_attribute_keys: Option<Arc<HashSet<Key>>>,
+    /// Overrides whether histogram aggregations record the min and max of the
+    /// distribution.
+    ///
+    /// If `None`, the `record_min_max` setting of the stream aggregation is used.
+    pub record_min_max: Option<bool>,
+    /// The maximum number of data points of the stream, including the overflow
+    /// data point.
+    ///
+    /// If `None`, the cardinality limit of the meter provider is used.
+    pub cardinality_limit: Option<usize>,
+    /// The factor the values of the stream are multiplied by at collection
+    /// time, set together with the converted unit by
+    /// [`Stream::unit_conversion`].
+    pub unit_scale: Option<f64>,
 }
 
 impl Stream {
@@ -221,6 +256,49 @@ impl Stream {
 
         self
     }
+
+    /// Set whether histogram aggregations record the min and max of the
+    /// distribution.
+    ///
+    /// Min and max are recorded by default. Disabling them reduces the memory
+    /// and computation spent per data point, which can be useful for
+    /// memory-constrained deployments. This setting has no effect on
+    /// non-histogram aggregations.
+    pub fn record_min_max(mut self, record_min_max: bool) -> Self {
+        self.record_min_max = Some(record_min_max);
+        self
+    }
+
+    /// Set the maximum number of data points of the stream.
+    ///
+    /// Once the stream holds `limit - 1` distinct attribute sets, measurements
+    /// of new attribute sets are aggregated into a single data point with the
+    /// `otel.metric.overflow=true` attribute until the next collection of
+    /// delta streams. A limit of `0` is ignored.
+    pub fn cardinality_limit(mut self, limit: usize) -> Self {
+        self.cardinality_limit = (limit > 0).then_some(limit);
+        self
+    }
+
+    /// Convert the values of the stream to `unit` by multiplying them by
+    /// `scale` at collection time.
+    ///
+    /// For example, `unit_conversion("MiB", 1.0 / 1048576.0)` reports an
+    /// instrument recording bytes in mebibytes. Conversions apply to the sum,
+    /// last value and explicit bucket histogram aggregations, including the
+    /// histogram boundaries, min, max and exemplars, which are still
+    /// configured in the unit of the instrument.
+    ///
+    /// Views with a scale which is not finite and positive, or with an
+    /// aggregation that does not support conversions, are dropped. Integer
+    /// instruments keep reporting integers, so their streams are only
+    /// converted by integer scales, e.g. from `KiBy` to `By` by `1024.0`, and
+    /// dropped with an error otherwise.
+    pub fn unit_conversion(mut self, unit: impl Into<Cow<'static, str>>, scale: f64) -> Self {
+        self.unit = unit.into();
+        self.unit_scale = Some(scale);
+        self
+    }
 }
 
 /// The identifying properties of an instrument.
@@ -256,37 +334,94 @@ impl InstrumentId {
 
 pub(crate) struct ResolvedMeasures<T> {
     pub(crate) measures: Vec<Arc<dyn Measure<T>>>,
+    /// Set for instruments whose aggregations cannot represent negative values.
+    pub(crate) negative_value_guard: Option<NegativeValueGuard>,
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    pub(crate) measurement_processors: MeasurementProcessors,
 }
 
-impl<T: Copy + 'static> SyncCounter<T> for ResolvedMeasures<T> {
-    fn add(&self, val: T, attrs: &[KeyValue]) {
+impl<T: Number<T>> ResolvedMeasures<T> {
+    fn measure(&self, val: T, attrs: &[KeyValue]) {
+        if let Some(guard) = &self.negative_value_guard {
+            if !guard.accepts(val) {
+                return;
+            }
+        }
+
+        #[cfg(feature = "experimental_metrics_measurement_processor")]
+        if !self.measurement_processors.is_empty() {
+            let attrs = self.measurement_processors.process(attrs);
+            for measure in &self.measures {
+                measure.call(val, AttributeSet::from(attrs.as_slice()))
+            }
+            return;
+        }
+
         for measure in &self.measures {
             measure.call(val, AttributeSet::from(attrs))
         }
     }
 }
 
-impl<T: Copy + 'static> SyncUpDownCounter<T> for ResolvedMeasures<T> {
-    fn add(&self, val: T, attrs: &[KeyValue]) {
-        for measure in &self.measures {
-            measure.call(val, AttributeSet::from(attrs))
+/// Drops negative values recorded on monotonic counters and histograms.
+///
+/// The sums produced for these instruments are monotonic, so a negative
+/// measurement would silently corrupt them. Every rejected value is counted,
+/// and the count is reported to the global error handler when it reaches a
+/// power of two, so a persistent misuse stays visible without flooding the
+/// handler.
+pub(crate) struct NegativeValueGuard {
+    instrument_name: Cow<'static, str>,
+    dropped: AtomicU64,
+}
+
+impl NegativeValueGuard {
+    pub(crate) fn new(instrument_name: Cow<'static, str>) -> Self {
+        NegativeValueGuard {
+            instrument_name,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns `false` if `val` must be dropped.
+    fn accepts<T: Number<T>>(&self, val: T) -> bool {
+        if val >= T::default() {
+            return true;
         }
+
+        let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped.is_power_of_two() {
+            global::handle_error(MetricsError::Other(format!(
+                "Warning: negative value {:?} recorded on instrument {} which only accepts non-negative values, the value was dropped. {} negative values have been dropped from this instrument so far.",
+                val, self.instrument_name, dropped
+            )));
+        }
+
+        false
     }
 }
 
-impl<T: Copy + 'static> SyncGauge<T> for ResolvedMeasures<T> {
+impl<T: Number<T>> SyncCounter<T> for ResolvedMeasures<T> {
+    fn add(&self, val: T, attrs: &[KeyValue]) {
+        self.measure(val, attrs)
+    }
+}
+
+impl<T: Number<T>> SyncUpDownCounter<T> for ResolvedMeasures<T> {
+    fn add(&self, val: T, attrs: &[KeyValue]) {
+        self.measure(val, attrs)
+    }
+}
+
+impl<T: Number<T>> SyncGauge<T> for ResolvedMeasures<T> {
     fn record(&self, val: T, attrs: &[KeyValue]) {
-        for measure in &self.measures {
-            measure.call(val, AttributeSet::from(attrs))
-        }
+        self.measure(val, attrs)
     }
 }
 
-impl<T: Copy + 'static> SyncHistogram<T> for ResolvedMeasures<T> {
+impl<T: Number<T>> SyncHistogram<T> for ResolvedMeasures<T> {
     fn record(&self, val: T, attrs: &[KeyValue]) {
-        for measure in &self.measures {
-            measure.call(val, AttributeSet::from(attrs))
-        }
+        self.measure(val, attrs)
     }
 }
 
@@ -328,7 +463,8 @@ impl<T> Eq for ObservableId<T> {}
 #[derive(Clone)]
 pub(crate) struct Observable<T> {
     pub(crate) id: ObservableId<T>,
-    measures: Vec<Arc<dyn Measure<T>>>,
+    /// The measures of each pipeline, indexed by pipeline.
+    measures: Vec<Vec<Arc<dyn Measure<T>>>>,
 }
 
 impl<T> Observable<T> {
@@ -338,7 +474,7 @@ impl<T> Observable<T> {
         name: Cow<'static, str>,
         description: Cow<'static, str>,
         unit: Cow<'static, str>,
-        measures: Vec<Arc<dyn Measure<T>>>,
+        measures: Vec<Vec<Arc<dyn Measure<T>>>>,
     ) -> Self {
         Self {
             id: ObservableId {
@@ -362,7 +498,7 @@ impl<T> Observable<T> {
     /// any aggregators. Also, an error is returned if scope defines a Meter other
     /// than the observable it was created by.
     pub(crate) fn registerable(&self, scope: &Scope) -> Result<()> {
-        if self.measures.is_empty() {
+        if self.measures.iter().all(Vec::is_empty) {
             return Err(MetricsError::Other(EMPTY_MEASURE_MSG.into()));
         }
         if &self.id.inner.scope != scope {
@@ -374,11 +510,36 @@ impl<T> Observable<T> {
 
         Ok(())
     }
+
+    /// Records `measurement` only into the aggregators of the given pipeline.
+    ///
+    /// Callbacks are run once per pipeline collection, so recording into the
+    /// aggregators of other pipelines would count the observation several times
+    /// for readers using delta temporality.
+    pub(crate) fn observe_in_pipeline(&self, pipeline: usize, measurement: T, attrs: &[KeyValue])
+    where
+        T: Copy + 'static,
+    {
+        if let Some(measures) = self.measures.get(pipeline) {
+            for measure in measures {
+                measure.call(measurement, AttributeSet::from(attrs))
+            }
+        }
+    }
+
+    /// Returns a view of this observable recording only into the aggregators of
+    /// the given pipeline.
+    pub(crate) fn in_pipeline(&self, pipeline: usize) -> PipelineObservable<'_, T> {
+        PipelineObservable {
+            observable: self,
+            pipeline,
+        }
+    }
 }
 
 impl<T: Copy + Send + Sync + 'static> AsyncInstrument<T> for Observable<T> {
     fn observe(&self, measurement: T, attrs: &[KeyValue]) {
-        for measure in &self.measures {
+        for measure in self.measures.iter().flatten() {
             measure.call(measurement, AttributeSet::from(attrs))
         }
     }
@@ -387,3 +548,39 @@ impl<T: Copy + Send + Sync + 'static> AsyncInstrument<T> for Observable<T> {
         Arc::new(self.clone())
     }
 }
+
+/// An [Observable] passed to callbacks run during the collection of a single
+/// pipeline.
+pub(crate) struct PipelineObservable<'a, T> {
+    observable: &'a Observable<T>,
+    pipeline: usize,
+}
+
+impl<T: Copy + Send + Sync + 'static> AsyncInstrument<T> for PipelineObservable<'_, T> {
+    fn observe(&self, measurement: T, attrs: &[KeyValue]) {
+        self.observable
+            .observe_in_pipeline(self.pipeline, measurement, attrs)
+    }
+
+    fn as_any(&self) -> Arc<dyn Any> {
+        self.observable.as_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::NegativeValueGuard;
+
+    #[test]
+    fn negative_value_guard_counts_dropped_values() {
+        let guard = NegativeValueGuard::new("my_counter".into());
+
+        assert!(guard.accepts(0u64));
+        assert!(guard.accepts(1.5));
+        assert!(!guard.accepts(-1.5));
+        assert!(!guard.accepts(-3i64));
+        assert_eq!(guard.dropped.load(Ordering::Relaxed), 2);
+    }
+}
@@ -1,14 +1,24 @@
-use std::{marker, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    marker,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use once_cell::sync::Lazy;
 use opentelemetry::KeyValue;
 
 use crate::{
     metrics::data::{Aggregation, Gauge, Temporality},
-    metrics::AttributeSet,
+    metrics::{AttributeSet, ExemplarFilter},
+    TelemetryBudget,
 };
 
 use super::{
+    exemplar::ExemplarCandidate,
     exponential_histogram::ExpoHistogram,
     histogram::Histogram,
     last_value::LastValue,
@@ -16,15 +26,93 @@ use super::{
     Number,
 };
 
-const STREAM_CARDINALITY_LIMIT: u32 = 2000;
+/// The default maximum number of data points of a metric stream, including
+/// the overflow data point.
+pub(crate) const DEFAULT_CARDINALITY_LIMIT: usize = 2000;
 pub(crate) static STREAM_OVERFLOW_ATTRIBUTE_SET: Lazy<AttributeSet> = Lazy::new(|| {
     let key_values: [KeyValue; 1] = [KeyValue::new("otel.metric.overflow", "true")];
     AttributeSet::from(&key_values[..])
 });
 
-/// Checks whether aggregator has hit cardinality limit for metric streams
-pub(crate) fn is_under_cardinality_limit(size: usize) -> bool {
-    size < STREAM_CARDINALITY_LIMIT as usize
+/// The cardinality limit of a metric stream.
+///
+/// Measurements of new attribute sets are recorded into the overflow data
+/// point once the stream reaches its limit, and counted in `overflows`.
+#[derive(Clone, Debug)]
+pub(crate) struct CardinalityLimit {
+    limit: usize,
+    overflows: Arc<AtomicU64>,
+}
+
+impl Default for CardinalityLimit {
+    fn default() -> Self {
+        CardinalityLimit::new(DEFAULT_CARDINALITY_LIMIT, Default::default())
+    }
+}
+
+impl CardinalityLimit {
+    pub(crate) fn new(limit: usize, overflows: Arc<AtomicU64>) -> Self {
+        CardinalityLimit { limit, overflows }
+    }
+
+    /// Checks whether a stream of `size` data points can add a data point,
+    /// keeping room for the overflow data point.
+    pub(crate) fn is_under(&self, size: usize) -> bool {
+        size + 1 < self.limit
+    }
+
+    /// Counts a measurement recorded into the overflow data point.
+    pub(crate) fn record_overflow(&self) {
+        self.overflows.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The count of measurements recorded into the overflow data point of each
+/// metric stream, shared by the pipelines of a meter provider.
+///
+/// Streams are identified by the name of their instrumentation scope and their
+/// own name. Each reader aggregates the stream separately and keeps its own
+/// count, a measurement overflowing the stream of several readers is only
+/// counted once by reporting the largest count among them.
+#[derive(Debug, Default)]
+pub(crate) struct CardinalityOverflows(Mutex<HashMap<OverflowKey, Arc<AtomicU64>>>);
+
+/// The scope name, stream name and reader index of an overflow counter.
+type OverflowKey = (Cow<'static, str>, Cow<'static, str>, usize);
+
+impl CardinalityOverflows {
+    /// Returns the overflow counter of the stream `name` of `scope` aggregated
+    /// by the reader at index `reader`.
+    pub(crate) fn counter(&self, scope: &str, name: &str, reader: usize) -> Arc<AtomicU64> {
+        match self.0.lock() {
+            Ok(mut counters) => Arc::clone(
+                counters
+                    .entry((scope.to_owned().into(), name.to_owned().into(), reader))
+                    .or_default(),
+            ),
+            Err(_) => Default::default(),
+        }
+    }
+
+    /// Returns the scope and name of the streams which overflowed with their
+    /// overflow count.
+    pub(crate) fn snapshot(&self) -> Vec<(Cow<'static, str>, Cow<'static, str>, u64)> {
+        let Ok(counters) = self.0.lock() else {
+            return Vec::new();
+        };
+        let mut streams: HashMap<(&Cow<'static, str>, &Cow<'static, str>), u64> = HashMap::new();
+        for ((scope, name, _), count) in counters.iter() {
+            let count = count.load(Ordering::Relaxed);
+            if count > 0 {
+                let max = streams.entry((scope, name)).or_default();
+                *max = (*max).max(count);
+            }
+        }
+        streams
+            .into_iter()
+            .map(|((scope, name), count)| (scope.clone(), name.clone(), count))
+            .collect()
+    }
 }
 
 /// Receives measurements to be aggregated.
@@ -77,6 +165,16 @@ pub(crate) struct AggregateBuilder<T> {
     /// measurements.
     filter: Option<Filter>,
 
+    /// The filter selecting the measurements offered to exemplar reservoirs.
+    exemplar_filter: ExemplarFilter,
+
+    /// The cardinality limit of the streams of the returned aggregate functions.
+    cardinality_limit: CardinalityLimit,
+
+    /// The memory budget the exemplars of the returned aggregate functions
+    /// reserve their size in.
+    budget: Option<TelemetryBudget>,
+
     _marker: marker::PhantomData<T>,
 }
 
@@ -87,10 +185,31 @@ impl<T: Number<T>> AggregateBuilder<T> {
         AggregateBuilder {
             temporality,
             filter,
+            exemplar_filter: ExemplarFilter::default(),
+            cardinality_limit: CardinalityLimit::default(),
+            budget: None,
             _marker: marker::PhantomData,
         }
     }
 
+    /// Sets the filter selecting the measurements sampled as exemplars.
+    pub(crate) fn with_exemplar_filter(mut self, exemplar_filter: ExemplarFilter) -> Self {
+        self.exemplar_filter = exemplar_filter;
+        self
+    }
+
+    /// Sets the cardinality limit of the aggregated streams.
+    pub(crate) fn with_cardinality_limit(mut self, cardinality_limit: CardinalityLimit) -> Self {
+        self.cardinality_limit = cardinality_limit;
+        self
+    }
+
+    /// Sets the memory budget of the sampled exemplars.
+    pub(crate) fn with_budget(mut self, budget: Option<TelemetryBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
     /// Wraps the passed in measure with an attribute filtering function.
     fn filter(&self, f: impl Measure<T>) -> impl Measure<T> {
         let filter = self.filter.clone();
@@ -102,14 +221,41 @@ impl<T: Number<T>> AggregateBuilder<T> {
         }
     }
 
+    /// Wraps the passed in measure with an attribute filtering function,
+    /// capturing the context of measurements sampled by the exemplar filter.
+    ///
+    /// Attributes removed by the attribute filter are kept on the exemplar.
+    fn filter_sampled(
+        &self,
+        f: impl Fn(T, AttributeSet, Option<ExemplarCandidate>) + Send + Sync + 'static,
+    ) -> impl Measure<T> {
+        let filter = self.filter.clone();
+        let exemplar_filter = self.exemplar_filter;
+        move |n, mut attrs: AttributeSet| {
+            let mut candidate = ExemplarCandidate::capture(exemplar_filter);
+            if let Some(filter) = &filter {
+                if let Some(candidate) = candidate.as_mut() {
+                    candidate.filtered_attributes = attrs
+                        .iter()
+                        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+                        .filter(|kv| !filter(kv))
+                        .collect();
+                }
+                attrs.retain(filter.as_ref());
+            }
+            f(n, attrs, candidate)
+        }
+    }
+
     /// Builds a last-value aggregate function input and output.
     ///
-    /// [Builder::temporality] is ignored and delta is always used.
+    /// With cumulative temporality, attribute sets keep being reported with
+    /// their last value after they stop being measured. Delta is used if no
+    /// temporality is provided.
     pub(crate) fn last_value(&self) -> (impl Measure<T>, impl ComputeAggregation) {
-        // Delta temporality is the only temporality that makes semantic sense for
-        // a last-value aggregate.
-        let lv_filter = Arc::new(LastValue::new());
+        let lv_filter = Arc::new(LastValue::new(self.cardinality_limit.clone()));
         let lv_agg = Arc::clone(&lv_filter);
+        let t = self.temporality;
 
         (
             self.filter(move |n, a| lv_filter.measure(n, a)),
@@ -124,19 +270,40 @@ impl<T: Number<T>> AggregateBuilder<T> {
                 };
                 let g = g.unwrap_or_else(|| new_agg.as_mut().expect("present if g is none"));
 
-                lv_agg.compute_aggregation(&mut g.data_points);
+                match t {
+                    Some(Temporality::Cumulative) => lv_agg.cumulative(&mut g.data_points),
+                    _ => lv_agg.delta(&mut g.data_points),
+                }
 
                 (g.data_points.len(), new_agg.map(|a| Box::new(a) as Box<_>))
             },
         )
     }
 
+    /// Builds a last-value aggregate function input and output for
+    /// asynchronous instruments, reporting only the attribute sets observed
+    /// during the collection whatever the temporality.
+    pub(crate) fn precomputed_last_value(&self) -> (impl Measure<T>, impl ComputeAggregation) {
+        AggregateBuilder {
+            temporality: Some(Temporality::Delta),
+            filter: self.filter.clone(),
+            exemplar_filter: self.exemplar_filter,
+            cardinality_limit: self.cardinality_limit.clone(),
+            budget: self.budget.clone(),
+            _marker: marker::PhantomData,
+        }
+        .last_value()
+    }
+
     /// Builds a precomputed sum aggregate function input and output.
     pub(crate) fn precomputed_sum(
         &self,
         monotonic: bool,
     ) -> (impl Measure<T>, impl ComputeAggregation) {
-        let s = Arc::new(PrecomputedSum::new(monotonic));
+        let s = Arc::new(PrecomputedSum::new(
+            monotonic,
+            self.cardinality_limit.clone(),
+        ));
         let agg_sum = Arc::clone(&s);
         let t = self.temporality;
 
@@ -151,12 +318,16 @@ impl<T: Number<T>> AggregateBuilder<T> {
 
     /// Builds a sum aggregate function input and output.
     pub(crate) fn sum(&self, monotonic: bool) -> (impl Measure<T>, impl ComputeAggregation) {
-        let s = Arc::new(Sum::new(monotonic));
+        let s = Arc::new(Sum::new(
+            monotonic,
+            self.cardinality_limit.clone(),
+            self.budget.clone(),
+        ));
         let agg_sum = Arc::clone(&s);
         let t = self.temporality;
 
         (
-            self.filter(move |n, a| s.measure(n, a)),
+            self.filter_sampled(move |n, a, e| s.measure(n, a, e)),
             move |dest: Option<&mut dyn Aggregation>| match t {
                 Some(Temporality::Delta) => agg_sum.delta(dest),
                 _ => agg_sum.cumulative(dest),
@@ -171,12 +342,18 @@ impl<T: Number<T>> AggregateBuilder<T> {
         record_min_max: bool,
         record_sum: bool,
     ) -> (impl Measure<T>, impl ComputeAggregation) {
-        let h = Arc::new(Histogram::new(boundaries, record_min_max, record_sum));
+        let h = Arc::new(Histogram::new(
+            boundaries,
+            record_min_max,
+            record_sum,
+            self.cardinality_limit.clone(),
+            self.budget.clone(),
+        ));
         let agg_h = Arc::clone(&h);
         let t = self.temporality;
 
         (
-            self.filter(move |n, a| h.measure(n, a)),
+            self.filter_sampled(move |n, a, e| h.measure(n, a, e)),
             move |dest: Option<&mut dyn Aggregation>| match t {
                 Some(Temporality::Delta) => agg_h.delta(dest),
                 _ => agg_h.cumulative(dest),
@@ -184,6 +361,22 @@ impl<T: Number<T>> AggregateBuilder<T> {
         )
     }
 
+    /// Builds the aggregate function input and output of a user-defined
+    /// aggregator.
+    #[cfg(feature = "experimental_metrics_custom_aggregation")]
+    pub(crate) fn custom(
+        &self,
+        aggregator: Arc<dyn crate::metrics::Aggregator>,
+    ) -> (impl Measure<T>, impl ComputeAggregation) {
+        let agg = Arc::clone(&aggregator);
+        let t = self.temporality.unwrap_or(Temporality::Cumulative);
+
+        (
+            self.filter(move |n: T, a: AttributeSet| aggregator.measure(n.into_float(), &a)),
+            move |dest: Option<&mut dyn Aggregation>| agg.collect(t, dest),
+        )
+    }
+
     /// Builds an exponential histogram aggregate function input and output.
     pub(crate) fn exponential_bucket_histogram(
         &self,
@@ -197,12 +390,14 @@ impl<T: Number<T>> AggregateBuilder<T> {
             max_scale,
             record_min_max,
             record_sum,
+            self.cardinality_limit.clone(),
+            self.budget.clone(),
         ));
         let agg_h = Arc::clone(&h);
         let t = self.temporality;
 
         (
-            self.filter(move |n, a| h.measure(n, a)),
+            self.filter_sampled(move |n, a, e| h.measure(n, a, e)),
             move |dest: Option<&mut dyn Aggregation>| match t {
                 Some(Temporality::Delta) => agg_h.delta(dest),
                 _ => agg_h.cumulative(dest),
@@ -245,6 +440,29 @@ mod tests {
         assert_eq!(a.data_points[0].value, 2);
     }
 
+    #[test]
+    fn last_value_aggregation_by_temporality() {
+        for temporality in [Temporality::Delta, Temporality::Cumulative] {
+            let (measure, agg) = AggregateBuilder::<u64>::new(Some(temporality), None).last_value();
+            measure.call(2, AttributeSet::from(&[KeyValue::new("a", 1)][..]));
+
+            let (count, new_agg) = agg.call(None);
+            assert_eq!(count, 1);
+            let mut gauge = new_agg.unwrap();
+
+            // no new measurement
+            let (count, _) = agg.call(Some(gauge.as_mut()));
+            let gauge = gauge.as_any().downcast_ref::<Gauge<u64>>().unwrap();
+            if temporality == Temporality::Cumulative {
+                assert_eq!(count, 1);
+                assert_eq!(gauge.data_points[0].value, 2);
+                assert!(gauge.data_points[0].start_time.is_some());
+            } else {
+                assert_eq!(count, 0);
+            }
+        }
+    }
+
     #[test]
     fn precomputed_sum_aggregation() {
         for temporality in [Temporality::Delta, Temporality::Cumulative] {
@@ -430,4 +648,113 @@ mod tests {
             assert!(a.data_points[0].negative_bucket.counts.is_empty());
         }
     }
+
+    fn drop_key_filter() -> Option<Filter> {
+        Some(Arc::new(|kv: &KeyValue| kv.key.as_str() != "dropped"))
+    }
+
+    fn colliding_attributes(dropped: i64) -> AttributeSet {
+        AttributeSet::from(&[KeyValue::new("kept", 1), KeyValue::new("dropped", dropped)][..])
+    }
+
+    #[test]
+    fn sum_merges_attribute_sets_collapsed_by_filter() {
+        for temporality in [Temporality::Delta, Temporality::Cumulative] {
+            let (measure, agg) =
+                AggregateBuilder::<u64>::new(Some(temporality), drop_key_filter()).sum(true);
+            measure.call(1, colliding_attributes(1));
+            measure.call(2, colliding_attributes(2));
+
+            let (count, new_agg) = agg.call(None);
+            let sum = new_agg.unwrap();
+            let sum = sum.as_any().downcast_ref::<Sum<u64>>().unwrap();
+
+            assert_eq!(count, 1);
+            assert_eq!(
+                sum.data_points[0].attributes,
+                vec![KeyValue::new("kept", 1)]
+            );
+            assert_eq!(sum.data_points[0].value, 3);
+        }
+    }
+
+    #[test]
+    fn precomputed_sum_merges_observations_of_each_cycle() {
+        for (temporality, expected) in [(Temporality::Delta, 10), (Temporality::Cumulative, 40)] {
+            let (measure, agg) = AggregateBuilder::<u64>::new(Some(temporality), drop_key_filter())
+                .precomputed_sum(true);
+
+            measure.call(10, colliding_attributes(1));
+            measure.call(20, colliding_attributes(2));
+            measure.call(100, AttributeSet::default());
+            let (_, new_agg) = agg.call(None);
+            let mut a = new_agg.unwrap();
+
+            // the next cycle observes the new pre-computed sums, which replace
+            // the previous ones instead of adding up
+            measure.call(15, colliding_attributes(1));
+            measure.call(25, colliding_attributes(2));
+            measure.call(110, AttributeSet::default());
+            let (count, _) = agg.call(Some(a.as_mut()));
+
+            let sum = a.as_any().downcast_ref::<Sum<u64>>().unwrap();
+            let value = |attributes: &[KeyValue]| {
+                sum.data_points
+                    .iter()
+                    .find(|dp| dp.attributes == attributes)
+                    .map(|dp| dp.value)
+            };
+            assert_eq!(count, 2);
+            assert_eq!(value(&[KeyValue::new("kept", 1)]), Some(expected));
+            assert_eq!(
+                value(&[]),
+                Some(if temporality == Temporality::Delta {
+                    10
+                } else {
+                    110
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn histogram_merges_buckets_of_attribute_sets_collapsed_by_filter() {
+        for temporality in [Temporality::Delta, Temporality::Cumulative] {
+            let (measure, agg) = AggregateBuilder::<u64>::new(Some(temporality), drop_key_filter())
+                .explicit_bucket_histogram(vec![5.0], true, true);
+            measure.call(1, colliding_attributes(1));
+            measure.call(7, colliding_attributes(2));
+
+            let (count, new_agg) = agg.call(None);
+            let histogram = new_agg.unwrap();
+            let histogram = histogram.as_any().downcast_ref::<Histogram<u64>>().unwrap();
+
+            assert_eq!(count, 1);
+            let data_point = &histogram.data_points[0];
+            assert_eq!(data_point.attributes, vec![KeyValue::new("kept", 1)]);
+            assert_eq!(data_point.count, 2);
+            assert_eq!(data_point.bucket_counts, vec![1, 1]);
+            assert_eq!(data_point.min, Some(1));
+            assert_eq!(data_point.max, Some(7));
+            assert_eq!(data_point.sum, 8);
+        }
+    }
+
+    #[test]
+    fn last_value_keeps_latest_of_attribute_sets_collapsed_by_filter() {
+        let (measure, agg) = AggregateBuilder::<u64>::new(None, drop_key_filter()).last_value();
+        measure.call(1, colliding_attributes(1));
+        measure.call(2, colliding_attributes(2));
+
+        let (count, new_agg) = agg.call(None);
+        let gauge = new_agg.unwrap();
+        let gauge = gauge.as_any().downcast_ref::<Gauge<u64>>().unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            gauge.data_points[0].attributes,
+            vec![KeyValue::new("kept", 1)]
+        );
+        assert_eq!(gauge.data_points[0].value, 2);
+    }
 }
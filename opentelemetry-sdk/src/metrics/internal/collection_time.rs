@@ -0,0 +1,38 @@
+use std::cell::Cell;
+use std::time::SystemTime;
+
+thread_local! {
+    /// The time of the collection in progress on this thread, if any.
+    static COLLECTION_TIME: Cell<Option<SystemTime>> = const { Cell::new(None) };
+}
+
+/// Gives every data point and observation of a collection the same timestamp,
+/// so that the instruments observed together by a callback are exported as a
+/// consistent snapshot.
+///
+/// The time is set for the current thread until the guard is dropped.
+pub(crate) struct CollectionTime {
+    previous: Option<SystemTime>,
+}
+
+impl CollectionTime {
+    /// Starts a collection at the current time.
+    pub(crate) fn start() -> Self {
+        let previous = COLLECTION_TIME.with(|time| time.replace(Some(SystemTime::now())));
+        CollectionTime { previous }
+    }
+
+    /// Returns the time of the collection in progress on this thread, or the
+    /// current time outside of collections.
+    pub(crate) fn now() -> SystemTime {
+        COLLECTION_TIME
+            .with(Cell::get)
+            .unwrap_or_else(SystemTime::now)
+    }
+}
+
+impl Drop for CollectionTime {
+    fn drop(&mut self) {
+        COLLECTION_TIME.with(|time| time.set(self.previous));
+    }
+}
@@ -0,0 +1,362 @@
+//! Exemplar reservoirs sampling the measurements of aggregated time series.
+use std::{cell::RefCell, mem, time::SystemTime};
+
+use opentelemetry::KeyValue;
+use rand::{rngs, Rng, SeedableRng};
+
+use crate::budget::{attributes_size, BudgetPriority, Reservation, TelemetryBudget};
+use crate::metrics::{data::Exemplar, ExemplarFilter};
+
+use super::Number;
+
+/// The reservoir size of aggregations without buckets.
+pub(crate) const DEFAULT_RESERVOIR_SIZE: usize = 1;
+
+/// The maximum reservoir size of exponential histograms.
+pub(crate) const MAX_EXPO_RESERVOIR_SIZE: usize = 20;
+
+thread_local! {
+    /// Random number generator used to sample measurements of each thread.
+    static CURRENT_RNG: RefCell<rngs::SmallRng> = RefCell::new(rngs::SmallRng::from_entropy());
+}
+
+/// The context of a measurement offered to exemplar reservoirs.
+#[derive(Debug)]
+pub(crate) struct ExemplarCandidate {
+    /// The attributes removed from the measurement by the attribute filter.
+    pub(crate) filtered_attributes: Vec<KeyValue>,
+    span_id: [u8; 8],
+    trace_id: [u8; 16],
+}
+
+impl ExemplarCandidate {
+    /// Captures the context of the current measurement if `filter` samples it.
+    pub(crate) fn capture(filter: ExemplarFilter) -> Option<Self> {
+        let (span_id, trace_id) = match filter {
+            ExemplarFilter::AlwaysOff => return None,
+            ExemplarFilter::AlwaysOn => sampled_span_context().unwrap_or_default(),
+            ExemplarFilter::TraceBased => sampled_span_context()?,
+        };
+
+        Some(ExemplarCandidate {
+            filtered_attributes: vec![],
+            span_id,
+            trace_id,
+        })
+    }
+
+    /// Returns the exemplar of a measurement kept by a reservoir, timestamped
+    /// when it is kept so that discarded measurements don't read the clock.
+    fn into_exemplar<T>(self, value: T) -> Exemplar<T> {
+        Exemplar {
+            filtered_attributes: self.filtered_attributes,
+            time: SystemTime::now(),
+            value,
+            span_id: self.span_id,
+            trace_id: self.trace_id,
+        }
+    }
+}
+
+/// Returns the span and trace IDs of the active span if it is sampled.
+#[cfg(feature = "trace")]
+fn sampled_span_context() -> Option<([u8; 8], [u8; 16])> {
+    use opentelemetry::trace::TraceContextExt;
+
+    opentelemetry::Context::map_current(|cx| {
+        let span = cx.span();
+        let span_context = span.span_context();
+        span_context.is_sampled().then(|| {
+            (
+                span_context.span_id().to_bytes(),
+                span_context.trace_id().to_bytes(),
+            )
+        })
+    })
+}
+
+#[cfg(not(feature = "trace"))]
+fn sampled_span_context() -> Option<([u8; 8], [u8; 16])> {
+    None
+}
+
+/// The bytes reserved in a [TelemetryBudget] by the exemplars of a reservoir.
+///
+/// The bytes are released when the reservoir is emptied or dropped.
+#[derive(Debug, Default)]
+pub(crate) struct ExemplarReservations {
+    budget: Option<TelemetryBudget>,
+    bytes: usize,
+}
+
+impl ExemplarReservations {
+    pub(crate) fn new(budget: Option<TelemetryBudget>) -> Self {
+        ExemplarReservations { budget, bytes: 0 }
+    }
+
+    /// Reserves the size of an exemplar stored in a free slot, returning
+    /// `false` if it doesn't fit in the budget.
+    ///
+    /// Exemplars replacing another one keep its reservation.
+    fn reserve<T>(&mut self, exemplar: &Exemplar<T>) -> bool {
+        let Some(budget) = &self.budget else {
+            return true;
+        };
+        let bytes = mem::size_of::<Exemplar<T>>() + attributes_size(&exemplar.filtered_attributes);
+        match budget.try_reserve(BudgetPriority::Exemplars, bytes) {
+            Some(reservation) => {
+                self.bytes += reservation.bytes;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the budget requests exemplars to be evicted to make room for
+    /// higher priority telemetry.
+    fn eviction_requested(&self) -> bool {
+        self.bytes > 0
+            && self.budget.as_ref().map_or(false, |budget| {
+                budget.eviction_requested(BudgetPriority::Exemplars)
+            })
+    }
+
+    /// Releases the reserved bytes once the exemplars are collected or
+    /// evicted.
+    fn release(&mut self, evicted: bool) {
+        let (Some(budget), bytes) = (&self.budget, mem::take(&mut self.bytes)) else {
+            return;
+        };
+        let reservation = Reservation {
+            priority: BudgetPriority::Exemplars,
+            bytes,
+        };
+        if evicted {
+            budget.evict(reservation);
+        } else {
+            budget.release(reservation);
+        }
+    }
+}
+
+impl Drop for ExemplarReservations {
+    fn drop(&mut self) {
+        self.release(false);
+    }
+}
+
+/// Samples at most `size` measurements of a time series with equal
+/// probability.
+///
+/// This is the spec's `SimpleFixedSizeExemplarReservoir`, using reservoir
+/// sampling to keep a uniformly random subset of the offered measurements.
+/// Each time series owns its reservoir, so offering a measurement only
+/// contends with the measurements of the same series.
+#[derive(Debug)]
+pub(crate) struct FixedSizeReservoir<T> {
+    size: usize,
+    measurements_seen: u64,
+    exemplars: Vec<Exemplar<T>>,
+    reservations: ExemplarReservations,
+}
+
+impl<T: Number<T>> FixedSizeReservoir<T> {
+    pub(crate) fn new(size: usize, budget: Option<TelemetryBudget>) -> Self {
+        FixedSizeReservoir {
+            size,
+            measurements_seen: 0,
+            exemplars: Vec::new(),
+            reservations: ExemplarReservations::new(budget),
+        }
+    }
+
+    pub(crate) fn offer(&mut self, value: T, candidate: ExemplarCandidate) {
+        if self.reservations.eviction_requested() {
+            self.exemplars.clear();
+            self.measurements_seen = 0;
+            self.reservations.release(true);
+        }
+
+        if self.exemplars.len() < self.size {
+            let exemplar = candidate.into_exemplar(value);
+            if self.reservations.reserve(&exemplar) {
+                self.exemplars.push(exemplar);
+            }
+        } else {
+            let idx =
+                CURRENT_RNG.with(|rng| rng.borrow_mut().gen_range(0..=self.measurements_seen));
+            if let Some(exemplar) = self.exemplars.get_mut(idx as usize) {
+                *exemplar = candidate.into_exemplar(value);
+            }
+        }
+        self.measurements_seen += 1;
+    }
+
+    /// Returns the sampled exemplars, emptying the reservoir if `reset` is set.
+    pub(crate) fn collect(&mut self, reset: bool) -> Vec<Exemplar<T>> {
+        if reset {
+            self.measurements_seen = 0;
+            self.reservations.release(false);
+            mem::take(&mut self.exemplars)
+        } else {
+            self.exemplars.clone()
+        }
+    }
+}
+
+/// Keeps the last measurement of each bucket of an explicit bucket histogram.
+///
+/// This is the spec's `AlignedHistogramBucketExemplarReservoir`.
+#[derive(Debug)]
+pub(crate) struct HistogramBucketReservoir<T> {
+    exemplars: Vec<Option<Exemplar<T>>>,
+    reservations: ExemplarReservations,
+}
+
+impl<T: Number<T>> HistogramBucketReservoir<T> {
+    /// Returns a reservoir for `n` buckets.
+    pub(crate) fn new(n: usize, budget: Option<TelemetryBudget>) -> Self {
+        HistogramBucketReservoir {
+            exemplars: (0..n).map(|_| None).collect(),
+            reservations: ExemplarReservations::new(budget),
+        }
+    }
+
+    pub(crate) fn offer(&mut self, bucket: usize, value: T, candidate: ExemplarCandidate) {
+        if self.reservations.eviction_requested() {
+            self.exemplars
+                .iter_mut()
+                .for_each(|exemplar| *exemplar = None);
+            self.reservations.release(true);
+        }
+
+        if let Some(slot) = self.exemplars.get_mut(bucket) {
+            let exemplar = candidate.into_exemplar(value);
+            if slot.is_some() || self.reservations.reserve(&exemplar) {
+                *slot = Some(exemplar);
+            }
+        }
+    }
+
+    /// Returns the sampled exemplars, emptying the reservoir if `reset` is set.
+    pub(crate) fn collect(&mut self, reset: bool) -> Vec<Exemplar<T>> {
+        if reset {
+            self.reservations.release(false);
+            self.exemplars.iter_mut().filter_map(Option::take).collect()
+        } else {
+            self.exemplars.iter().flatten().cloned().collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate() -> ExemplarCandidate {
+        ExemplarCandidate {
+            filtered_attributes: vec![KeyValue::new("filtered", true)],
+            span_id: [1; 8],
+            trace_id: [2; 16],
+        }
+    }
+
+    #[test]
+    fn capture_respects_filter() {
+        assert!(ExemplarCandidate::capture(ExemplarFilter::AlwaysOff).is_none());
+        // No span is active.
+        assert!(ExemplarCandidate::capture(ExemplarFilter::TraceBased).is_none());
+
+        let candidate = ExemplarCandidate::capture(ExemplarFilter::AlwaysOn)
+            .expect("always on filter samples every measurement");
+        assert_eq!(candidate.span_id, [0; 8]);
+        assert_eq!(candidate.trace_id, [0; 16]);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn capture_sampled_span_context() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+        use opentelemetry::Context;
+
+        let span_context = SpanContext::new(
+            TraceId::from(7),
+            SpanId::from(3),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        );
+        let _guard = Context::current()
+            .with_remote_span_context(span_context)
+            .attach();
+
+        let candidate =
+            ExemplarCandidate::capture(ExemplarFilter::TraceBased).expect("span is sampled");
+        assert_eq!(candidate.span_id, SpanId::from(3).to_bytes());
+        assert_eq!(candidate.trace_id, TraceId::from(7).to_bytes());
+    }
+
+    #[test]
+    fn fixed_size_reservoir_keeps_at_most_size_exemplars() {
+        let mut reservoir = FixedSizeReservoir::new(2, None);
+        for v in 0..100u64 {
+            reservoir.offer(v, candidate());
+        }
+
+        let exemplars = reservoir.collect(false);
+        assert_eq!(exemplars.len(), 2);
+        assert_eq!(
+            exemplars[0].filtered_attributes,
+            candidate().filtered_attributes
+        );
+        assert_eq!(exemplars[0].span_id, [1; 8]);
+        assert_eq!(exemplars[0].trace_id, [2; 16]);
+
+        assert_eq!(reservoir.collect(true).len(), 2);
+        assert!(reservoir.collect(true).is_empty());
+    }
+
+    #[test]
+    fn histogram_bucket_reservoir_keeps_last_measurement_per_bucket() {
+        let mut reservoir = HistogramBucketReservoir::new(3, None);
+        reservoir.offer(0, 1u64, candidate());
+        reservoir.offer(2, 7u64, candidate());
+        reservoir.offer(2, 9u64, candidate());
+        reservoir.offer(5, 11u64, candidate());
+
+        let values =
+            |exemplars: Vec<Exemplar<u64>>| exemplars.iter().map(|e| e.value).collect::<Vec<_>>();
+        assert_eq!(values(reservoir.collect(false)), vec![1, 9]);
+        assert_eq!(values(reservoir.collect(true)), vec![1, 9]);
+        assert!(reservoir.collect(true).is_empty());
+    }
+
+    #[test]
+    fn reservoirs_stay_within_budget() {
+        let budget = TelemetryBudget::new(1024);
+        let mut reservoir = FixedSizeReservoir::new(100, Some(budget.clone()));
+        for v in 0..100u64 {
+            reservoir.offer(v, candidate());
+        }
+        let used = budget.used_bytes(BudgetPriority::Exemplars);
+        assert!(used > 0 && used <= 1024);
+        assert!(reservoir.collect(false).len() < 100);
+
+        // regular telemetry evicts the exemplars on the next offer
+        let regular = budget
+            .try_reserve(BudgetPriority::Regular, 1024)
+            .expect("exemplars are evictable");
+        reservoir.offer(100, candidate());
+        assert!(reservoir.collect(false).is_empty());
+        assert_eq!(budget.used_bytes(BudgetPriority::Exemplars), 0);
+
+        budget.release(regular);
+        let mut reservoir = HistogramBucketReservoir::new(3, Some(budget.clone()));
+        reservoir.offer(1, 1u64, candidate());
+        assert!(budget.used_bytes(BudgetPriority::Exemplars) > 0);
+        drop(reservoir);
+        assert_eq!(budget.used_bytes(BudgetPriority::Exemplars), 0);
+    }
+}
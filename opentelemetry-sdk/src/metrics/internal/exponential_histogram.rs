@@ -6,9 +6,14 @@ use opentelemetry::{metrics::MetricsError, KeyValue};
 use crate::{
     metrics::data::{self, Aggregation, Temporality},
     metrics::AttributeSet,
+    TelemetryBudget,
 };
 
-use super::Number;
+use super::{
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    exemplar::{ExemplarCandidate, FixedSizeReservoir, MAX_EXPO_RESERVOIR_SIZE},
+    CollectionTime, Number,
+};
 
 pub(crate) const EXPO_MAX_SCALE: i8 = 20;
 pub(crate) const EXPO_MIN_SCALE: i8 = -10;
@@ -306,6 +311,10 @@ impl ExpoBuckets {
     }
 }
 
+/// The histogram of a time series and the exemplars sampled from its
+/// measurements.
+type ExpoHistogramValue<T> = (ExpoHistogramDataPoint<T>, FixedSizeReservoir<T>);
+
 /// An aggregator that summarizes a set of measurements as an exponential
 /// histogram.
 ///
@@ -317,7 +326,10 @@ pub(crate) struct ExpoHistogram<T> {
     max_size: i32,
     max_scale: i8,
 
-    values: Mutex<HashMap<AttributeSet, ExpoHistogramDataPoint<T>>>,
+    values: Mutex<HashMap<AttributeSet, ExpoHistogramValue<T>>>,
+    reservoir_size: usize,
+    cardinality_limit: CardinalityLimit,
+    budget: Option<TelemetryBudget>,
 
     start: Mutex<SystemTime>,
 }
@@ -329,6 +341,8 @@ impl<T: Number<T>> ExpoHistogram<T> {
         max_scale: i8,
         record_min_max: bool,
         record_sum: bool,
+        cardinality_limit: CardinalityLimit,
+        budget: Option<TelemetryBudget>,
     ) -> Self {
         ExpoHistogram {
             record_sum,
@@ -336,11 +350,19 @@ impl<T: Number<T>> ExpoHistogram<T> {
             max_size: max_size as i32,
             max_scale,
             values: Mutex::new(HashMap::default()),
+            reservoir_size: MAX_EXPO_RESERVOIR_SIZE.min(max_size as usize),
+            cardinality_limit,
+            budget,
             start: Mutex::new(SystemTime::now()),
         }
     }
 
-    pub(crate) fn measure(&self, value: T, attrs: AttributeSet) {
+    pub(crate) fn measure(
+        &self,
+        value: T,
+        attrs: AttributeSet,
+        exemplar: Option<ExemplarCandidate>,
+    ) {
         let f_value = value.into_float();
         // Ignore NaN and infinity.
         if f_value.is_infinite() || f_value.is_nan() {
@@ -348,15 +370,28 @@ impl<T: Number<T>> ExpoHistogram<T> {
         }
 
         if let Ok(mut values) = self.values.lock() {
-            let v = values.entry(attrs).or_insert_with(|| {
-                ExpoHistogramDataPoint::new(
-                    self.max_size,
-                    self.max_scale,
-                    self.record_min_max,
-                    self.record_sum,
+            let attrs =
+                if values.contains_key(&attrs) || self.cardinality_limit.is_under(values.len()) {
+                    attrs
+                } else {
+                    self.cardinality_limit.record_overflow();
+                    STREAM_OVERFLOW_ATTRIBUTE_SET.clone()
+                };
+            let (v, exemplars) = values.entry(attrs).or_insert_with(|| {
+                (
+                    ExpoHistogramDataPoint::new(
+                        self.max_size,
+                        self.max_scale,
+                        self.record_min_max,
+                        self.record_sum,
+                    ),
+                    FixedSizeReservoir::new(self.reservoir_size, self.budget.clone()),
                 )
             });
-            v.record(value)
+            v.record(value);
+            if let Some(candidate) = exemplar {
+                exemplars.offer(value, candidate);
+            }
         }
     }
 
@@ -364,7 +399,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
         let start = self
             .start
             .lock()
@@ -394,7 +429,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
             h.data_points.reserve_exact(n - h.data_points.capacity());
         }
 
-        for (a, b) in values.drain() {
+        for (a, (b, mut exemplars)) in values.drain() {
             h.data_points.push(data::ExponentialHistogramDataPoint {
                 attributes: a
                     .iter()
@@ -425,7 +460,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
                     counts: b.neg_buckets.counts.clone(),
                 },
                 zero_threshold: 0.0,
-                exemplars: vec![],
+                exemplars: exemplars.collect(true),
             });
         }
 
@@ -441,7 +476,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
         let start = self
             .start
             .lock()
@@ -460,7 +495,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
         let h = h.unwrap_or_else(|| new_agg.as_mut().expect("present if h is none"));
         h.temporality = Temporality::Cumulative;
 
-        let values = match self.values.lock() {
+        let mut values = match self.values.lock() {
             Ok(g) => g,
             Err(_) => return (0, None),
         };
@@ -475,7 +510,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
         // are unbounded number of attribute sets being aggregated. Attribute
         // sets that become "stale" need to be forgotten so this will not
         // overload the system.
-        for (a, b) in values.iter() {
+        for (a, (b, exemplars)) in values.iter_mut() {
             h.data_points.push(data::ExponentialHistogramDataPoint {
                 attributes: a
                     .iter()
@@ -506,7 +541,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
                     counts: b.neg_buckets.counts.clone(),
                 },
                 zero_threshold: 0.0,
-                exemplars: vec![],
+                exemplars: exemplars.collect(false),
             });
         }
 
@@ -679,12 +714,12 @@ mod tests {
         ];
 
         for test in test_cases {
-            let h = ExpoHistogram::new(4, 20, true, true);
+            let h = ExpoHistogram::new(4, 20, true, true, CardinalityLimit::default(), None);
             for v in test.values {
-                h.measure(v, alice.clone());
+                h.measure(v, alice.clone(), None);
             }
             let values = h.values.lock().unwrap();
-            let dp = values.get(&alice).unwrap();
+            let (dp, _) = values.get(&alice).unwrap();
 
             assert_eq!(test.expected.max, dp.max);
             assert_eq!(test.expected.min, dp.min);
@@ -730,12 +765,12 @@ mod tests {
         ];
 
         for test in test_cases {
-            let h = ExpoHistogram::new(4, 20, true, true);
+            let h = ExpoHistogram::new(4, 20, true, true, CardinalityLimit::default(), None);
             for v in test.values {
-                h.measure(v, alice.clone());
+                h.measure(v, alice.clone(), None);
             }
             let values = h.values.lock().unwrap();
-            let dp = values.get(&alice).unwrap();
+            let (dp, _) = values.get(&alice).unwrap();
 
             assert_eq!(test.expected.max, dp.max);
             assert_eq!(test.expected.min, dp.min);
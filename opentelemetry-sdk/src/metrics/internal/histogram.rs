@@ -1,13 +1,14 @@
 use std::{collections::HashMap, sync::Mutex, time::SystemTime};
 
 use crate::metrics::data::{self, Aggregation, Temporality};
-use crate::{metrics::data::HistogramDataPoint, metrics::AttributeSet};
+use crate::{metrics::data::HistogramDataPoint, metrics::AttributeSet, TelemetryBudget};
 use opentelemetry::KeyValue;
 use opentelemetry::{global, metrics::MetricsError};
 
 use super::{
-    aggregate::{is_under_cardinality_limit, STREAM_OVERFLOW_ATTRIBUTE_SET},
-    Number,
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    exemplar::{ExemplarCandidate, HistogramBucketReservoir},
+    CollectionTime, Number,
 };
 
 #[derive(Default)]
@@ -17,6 +18,7 @@ struct Buckets<T> {
     total: T,
     min: T,
     max: T,
+    exemplars: Option<HistogramBucketReservoir<T>>,
 }
 
 impl<T: Number<T>> Buckets<T> {
@@ -32,9 +34,33 @@ impl<T: Number<T>> Buckets<T> {
         self.total += value;
     }
 
-    fn bin(&mut self, idx: usize, value: T) {
+    fn bin(&mut self, idx: usize) {
         self.counts[idx] += 1;
         self.count += 1;
+    }
+
+    fn exemplar(
+        &mut self,
+        idx: usize,
+        value: T,
+        candidate: ExemplarCandidate,
+        budget: &Option<TelemetryBudget>,
+    ) {
+        let n = self.counts.len();
+        self.exemplars
+            .get_or_insert_with(|| HistogramBucketReservoir::new(n, budget.clone()))
+            .offer(idx, value, candidate);
+    }
+
+    /// Returns the sampled exemplars, resetting them if `reset` is set.
+    fn collect_exemplars(&mut self, reset: bool) -> Vec<data::Exemplar<T>> {
+        self.exemplars
+            .as_mut()
+            .map(|exemplars| exemplars.collect(reset))
+            .unwrap_or_default()
+    }
+
+    fn min_max(&mut self, value: T) {
         if value < self.min {
             self.min = value;
         } else if value > self.max {
@@ -46,25 +72,37 @@ impl<T: Number<T>> Buckets<T> {
 /// Summarizes a set of measurements with explicitly defined buckets.
 struct HistValues<T> {
     record_sum: bool,
+    record_min_max: bool,
     bounds: Vec<f64>,
     values: Mutex<HashMap<AttributeSet, Buckets<T>>>,
+    cardinality_limit: CardinalityLimit,
+    budget: Option<TelemetryBudget>,
 }
 
 impl<T: Number<T>> HistValues<T> {
-    fn new(mut bounds: Vec<f64>, record_sum: bool) -> Self {
+    fn new(
+        mut bounds: Vec<f64>,
+        record_min_max: bool,
+        record_sum: bool,
+        cardinality_limit: CardinalityLimit,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
         bounds.retain(|v| !v.is_nan());
         bounds.sort_by(|a, b| a.partial_cmp(b).expect("NaNs filtered out"));
 
         HistValues {
             record_sum,
+            record_min_max,
             bounds,
             values: Mutex::new(Default::default()),
+            cardinality_limit,
+            budget,
         }
     }
 }
 
 impl<T: Number<T>> HistValues<T> {
-    fn measure(&self, measurement: T, attrs: AttributeSet) {
+    fn measure(&self, measurement: T, attrs: AttributeSet, exemplar: Option<ExemplarCandidate>) {
         let f = measurement.into_float();
 
         // This search will return an index in the range `[0, bounds.len()]`, where
@@ -94,9 +132,10 @@ impl<T: Number<T>> HistValues<T> {
             // Ensure min and max are recorded values (not zero), for new buckets.
             (b.min, b.max) = (measurement, measurement);
 
-            if is_under_cardinality_limit(size) {
+            if self.cardinality_limit.is_under(size) {
                 values.entry(attrs).or_insert(b)
             } else {
+                self.cardinality_limit.record_overflow();
                 global::handle_error(MetricsError::Other("Warning: Maximum data points for metric stream exceeded. Entry added to overflow.".into()));
                 values
                     .entry(STREAM_OVERFLOW_ATTRIBUTE_SET.clone())
@@ -104,7 +143,13 @@ impl<T: Number<T>> HistValues<T> {
             }
         };
 
-        b.bin(idx, measurement);
+        b.bin(idx);
+        if let Some(candidate) = exemplar {
+            b.exemplar(idx, measurement, candidate, &self.budget);
+        }
+        if self.record_min_max {
+            b.min_max(measurement);
+        }
         if self.record_sum {
             b.sum(measurement)
         }
@@ -115,21 +160,36 @@ impl<T: Number<T>> HistValues<T> {
 /// buckets.
 pub(crate) struct Histogram<T> {
     hist_values: HistValues<T>,
-    record_min_max: bool,
     start: Mutex<SystemTime>,
 }
 
 impl<T: Number<T>> Histogram<T> {
-    pub(crate) fn new(boundaries: Vec<f64>, record_min_max: bool, record_sum: bool) -> Self {
+    pub(crate) fn new(
+        boundaries: Vec<f64>,
+        record_min_max: bool,
+        record_sum: bool,
+        cardinality_limit: CardinalityLimit,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
         Histogram {
-            hist_values: HistValues::new(boundaries, record_sum),
-            record_min_max,
+            hist_values: HistValues::new(
+                boundaries,
+                record_min_max,
+                record_sum,
+                cardinality_limit,
+                budget,
+            ),
             start: Mutex::new(SystemTime::now()),
         }
     }
 
-    pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
-        self.hist_values.measure(measurement, attrs)
+    pub(crate) fn measure(
+        &self,
+        measurement: T,
+        attrs: AttributeSet,
+        exemplar: Option<ExemplarCandidate>,
+    ) {
+        self.hist_values.measure(measurement, attrs, exemplar)
     }
 
     pub(crate) fn delta(
@@ -140,7 +200,7 @@ impl<T: Number<T>> Histogram<T> {
             Ok(guard) if !guard.is_empty() => guard,
             _ => return (0, None),
         };
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
         let start = self
             .start
             .lock()
@@ -164,7 +224,7 @@ impl<T: Number<T>> Histogram<T> {
             h.data_points.reserve_exact(n - h.data_points.capacity());
         }
 
-        for (a, b) in values.drain() {
+        for (a, mut b) in values.drain() {
             h.data_points.push(HistogramDataPoint {
                 attributes: a
                     .iter()
@@ -180,17 +240,17 @@ impl<T: Number<T>> Histogram<T> {
                 } else {
                     T::default()
                 },
-                min: if self.record_min_max {
+                min: if self.hist_values.record_min_max {
                     Some(b.min)
                 } else {
                     None
                 },
-                max: if self.record_min_max {
+                max: if self.hist_values.record_min_max {
                     Some(b.max)
                 } else {
                     None
                 },
-                exemplars: vec![],
+                exemplars: b.collect_exemplars(true),
             });
         }
 
@@ -206,11 +266,11 @@ impl<T: Number<T>> Histogram<T> {
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let values = match self.hist_values.values.lock() {
+        let mut values = match self.hist_values.values.lock() {
             Ok(guard) if !guard.is_empty() => guard,
             _ => return (0, None),
         };
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
         let start = self
             .start
             .lock()
@@ -238,7 +298,7 @@ impl<T: Number<T>> Histogram<T> {
         // are unbounded number of attribute sets being aggregated. Attribute
         // sets that become "stale" need to be forgotten so this will not
         // overload the system.
-        for (a, b) in values.iter() {
+        for (a, b) in values.iter_mut() {
             h.data_points.push(HistogramDataPoint {
                 attributes: a
                     .iter()
@@ -254,17 +314,17 @@ impl<T: Number<T>> Histogram<T> {
                 } else {
                     T::default()
                 },
-                min: if self.record_min_max {
+                min: if self.hist_values.record_min_max {
                     Some(b.min)
                 } else {
                     None
                 },
-                max: if self.record_min_max {
+                max: if self.hist_values.record_min_max {
                     Some(b.max)
                 } else {
                     None
                 },
-                exemplars: vec![],
+                exemplars: b.collect_exemplars(false),
             });
         }
 
@@ -8,8 +8,8 @@ use crate::{metrics::data::DataPoint, metrics::AttributeSet};
 use opentelemetry::{global, metrics::MetricsError, KeyValue};
 
 use super::{
-    aggregate::{is_under_cardinality_limit, STREAM_OVERFLOW_ATTRIBUTE_SET},
-    Number,
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    CollectionTime, Number,
 };
 
 /// Timestamped measurement data.
@@ -19,31 +19,42 @@ struct DataPointValue<T> {
 }
 
 /// Summarizes a set of measurements as the last one made.
-#[derive(Default)]
 pub(crate) struct LastValue<T> {
     values: Mutex<HashMap<AttributeSet, DataPointValue<T>>>,
+    cardinality_limit: CardinalityLimit,
+    start: SystemTime,
 }
 
 impl<T: Number<T>> LastValue<T> {
-    pub(crate) fn new() -> Self {
-        Self::default()
+    pub(crate) fn new(cardinality_limit: CardinalityLimit) -> Self {
+        LastValue {
+            values: Mutex::new(HashMap::new()),
+            cardinality_limit,
+            start: SystemTime::now(),
+        }
     }
 
     pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
         let d: DataPointValue<T> = DataPointValue {
-            timestamp: SystemTime::now(),
+            timestamp: CollectionTime::now(),
             value: measurement,
         };
         if let Ok(mut values) = self.values.lock() {
             let size = values.len();
             match values.entry(attrs) {
                 Entry::Occupied(mut occupied_entry) => {
-                    occupied_entry.insert(d);
+                    // Several attribute sets may be merged into this one by a
+                    // view, keep the latest measurement of any of them even if
+                    // measurements raced for the lock.
+                    if occupied_entry.get().timestamp <= d.timestamp {
+                        occupied_entry.insert(d);
+                    }
                 }
                 Entry::Vacant(vacant_entry) => {
-                    if is_under_cardinality_limit(size) {
+                    if self.cardinality_limit.is_under(size) {
                         vacant_entry.insert(d);
                     } else {
+                        self.cardinality_limit.record_overflow();
                         values.insert(STREAM_OVERFLOW_ATTRIBUTE_SET.clone(), d);
                         global::handle_error(MetricsError::Other("Warning: Maximum data points for metric stream exceeded. Entry added to overflow.".into()));
                     }
@@ -52,7 +63,9 @@ impl<T: Number<T>> LastValue<T> {
         }
     }
 
-    pub(crate) fn compute_aggregation(&self, dest: &mut Vec<DataPoint<T>>) {
+    /// Collects the last value of the attribute sets measured since the
+    /// previous collection.
+    pub(crate) fn delta(&self, dest: &mut Vec<DataPoint<T>>) {
         dest.clear();
         let mut values = match self.values.lock() {
             Ok(guard) if !guard.is_empty() => guard,
@@ -77,4 +90,32 @@ impl<T: Number<T>> LastValue<T> {
             });
         }
     }
+
+    /// Collects the last value of every attribute set measured since the
+    /// aggregation was created, even if it was not measured again.
+    pub(crate) fn cumulative(&self, dest: &mut Vec<DataPoint<T>>) {
+        dest.clear();
+        let values = match self.values.lock() {
+            Ok(guard) if !guard.is_empty() => guard,
+            _ => return,
+        };
+
+        let n = values.len();
+        if n > dest.capacity() {
+            dest.reserve_exact(n - dest.capacity());
+        }
+
+        for (attrs, value) in values.iter() {
+            dest.push(DataPoint {
+                attributes: attrs
+                    .iter()
+                    .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+                    .collect(),
+                time: Some(value.timestamp),
+                value: value.value,
+                start_time: Some(self.start),
+                exemplars: vec![],
+            });
+        }
+    }
 }
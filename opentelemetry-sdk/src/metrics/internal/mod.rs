@@ -1,16 +1,25 @@
 mod aggregate;
+mod collection_time;
+mod exemplar;
 mod exponential_histogram;
 mod histogram;
 mod last_value;
 mod sum;
+mod unit_conversion;
 
 use core::fmt;
 use std::ops::{Add, AddAssign, Sub};
-use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
 
-pub(crate) use aggregate::{AggregateBuilder, ComputeAggregation, Measure};
+use once_cell::sync::OnceCell;
+
+pub(crate) use aggregate::{
+    AggregateBuilder, CardinalityLimit, CardinalityOverflows, ComputeAggregation, Measure,
+    DEFAULT_CARDINALITY_LIMIT,
+};
+pub(crate) use collection_time::CollectionTime;
 pub(crate) use exponential_histogram::{EXPO_MAX_SCALE, EXPO_MIN_SCALE};
+pub(crate) use unit_conversion::convert_units;
 
 /// Marks a type that can have a value added and retrieved atomically. Required since
 /// different types have different backing atomic mechanisms
@@ -45,6 +54,13 @@ pub(crate) trait Number<T>:
     fn max() -> Self;
 
     fn into_float(self) -> f64;
+
+    /// Converts a float back to this type, integers are rounded to the nearest
+    /// value and saturate at their bounds.
+    ///
+    /// Unit conversions only scale integers by integer factors, so rounding
+    /// only loses the precision already lost by `into_float`.
+    fn from_float(value: f64) -> Self;
 }
 
 impl Number<i64> for i64 {
@@ -60,6 +76,10 @@ impl Number<i64> for i64 {
         // May have precision loss at high values
         self as f64
     }
+
+    fn from_float(value: f64) -> Self {
+        value.round() as i64
+    }
 }
 impl Number<u64> for u64 {
     fn min() -> Self {
@@ -74,6 +94,10 @@ impl Number<u64> for u64 {
         // May have precision loss at high values
         self as f64
     }
+
+    fn from_float(value: f64) -> Self {
+        value.round() as u64
+    }
 }
 impl Number<f64> for f64 {
     fn min() -> Self {
@@ -87,6 +111,10 @@ impl Number<f64> for f64 {
     fn into_float(self) -> f64 {
         self
     }
+
+    fn from_float(value: f64) -> Self {
+        value
+    }
 }
 
 impl AtomicTracker<u64> for AtomicU64 {
@@ -133,33 +161,124 @@ impl AtomicallyUpdate<i64> for i64 {
     }
 }
 
+/// Number of cells used by a [`F64AtomicTracker`] once it has become contended.
+const F64_SHARD_COUNT: usize = 16;
+
+/// Number of failed compare-and-swap attempts after which a [`F64AtomicTracker`]
+/// spreads its updates over [`F64_SHARD_COUNT`] cells.
+const F64_CONTENTION_THRESHOLD: usize = 64;
+
+/// An `AtomicU64` holding the bits of an `f64`, padded to its own cache line so
+/// that neighbouring cells do not contend through false sharing.
+#[repr(align(64))]
+struct PaddedF64Cell(AtomicU64);
+
+impl PaddedF64Cell {
+    fn new() -> Self {
+        PaddedF64Cell(AtomicU64::new(0f64.to_bits()))
+    }
+}
+
+thread_local! {
+    /// The cell index used by the current thread when updating a sharded tracker.
+    static F64_SHARD_INDEX: usize = {
+        static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+        NEXT_INDEX.fetch_add(1, Ordering::Relaxed) % F64_SHARD_COUNT
+    };
+}
+
+/// Adds `value` to the `f64` stored in `cell`, returning the number of failed
+/// compare-and-swap attempts.
+fn add_f64(cell: &AtomicU64, value: f64) -> usize {
+    let mut failures = 0;
+    let mut current = cell.load(Ordering::Relaxed);
+    loop {
+        let new = (f64::from_bits(current) + value).to_bits();
+        match cell.compare_exchange_weak(current, new, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return failures,
+            Err(actual) => {
+                failures += 1;
+                current = actual;
+            }
+        }
+    }
+}
+
+/// Tracks an `f64` sum without locking.
+///
+/// Updates are applied to a single atomic cell with a compare-and-swap loop.
+/// Once that loop has failed more than [`F64_CONTENTION_THRESHOLD`] times, the
+/// tracker allocates a set of cache-line padded cells and each thread adds into
+/// its own cell from then on, in the style of Java's `DoubleAdder`. Reads sum
+/// all cells, so uncontended trackers never pay for the extra memory.
 pub(crate) struct F64AtomicTracker {
-    inner: Mutex<f64>, // Floating points don't have true atomics, so we need to use mutex for them
+    base: AtomicU64,
+    failed_updates: AtomicUsize,
+    shards: OnceCell<Box<[PaddedF64Cell]>>,
 }
 
 impl F64AtomicTracker {
     fn new() -> Self {
         F64AtomicTracker {
-            inner: Mutex::new(0.0),
+            base: AtomicU64::new(0f64.to_bits()),
+            failed_updates: AtomicUsize::new(0),
+            shards: OnceCell::new(),
         }
     }
+
+    fn shards(&self) -> &[PaddedF64Cell] {
+        self.shards
+            .get_or_init(|| (0..F64_SHARD_COUNT).map(|_| PaddedF64Cell::new()).collect())
+    }
+
+    fn add_to_shard(shards: &[PaddedF64Cell], value: f64) {
+        let index = F64_SHARD_INDEX.with(|index| *index);
+        add_f64(&shards[index].0, value);
+    }
 }
 
 impl AtomicTracker<f64> for F64AtomicTracker {
     fn add(&self, value: f64) {
-        let mut guard = self.inner.lock().expect("F64 mutex was poisoned");
-        *guard += value;
+        if let Some(shards) = self.shards.get() {
+            Self::add_to_shard(shards, value);
+            return;
+        }
+
+        let failures = add_f64(&self.base, value);
+        if failures > 0
+            && self.failed_updates.fetch_add(failures, Ordering::Relaxed) + failures
+                >= F64_CONTENTION_THRESHOLD
+        {
+            // Contended: subsequent updates go to the per-thread cells.
+            self.shards();
+        }
     }
 
     fn get_value(&self) -> f64 {
-        let guard = self.inner.lock().expect("F64 mutex was poisoned");
-        *guard
+        let mut value = f64::from_bits(self.base.load(Ordering::Relaxed));
+        for shard in self
+            .shards
+            .get()
+            .into_iter()
+            .flat_map(|shards| shards.iter())
+        {
+            value += f64::from_bits(shard.0.load(Ordering::Relaxed));
+        }
+
+        value
     }
 
     fn get_and_reset_value(&self) -> f64 {
-        let mut guard = self.inner.lock().expect("F64 mutex was poisoned");
-        let value = *guard;
-        *guard = 0.0;
+        let zero = 0f64.to_bits();
+        let mut value = f64::from_bits(self.base.swap(zero, Ordering::Relaxed));
+        for shard in self
+            .shards
+            .get()
+            .into_iter()
+            .flat_map(|shards| shards.iter())
+        {
+            value += f64::from_bits(shard.0.swap(zero, Ordering::Relaxed));
+        }
 
         value
     }
@@ -243,4 +362,38 @@ mod tests {
         assert!(f64::abs(15.5 - value) < 0.0001, "Incorrect first value");
         assert!(f64::abs(0.0 - value2) < 0.0001, "Incorrect second value");
     }
+
+    #[test]
+    fn can_add_and_reset_sharded_f64_atomic_value() {
+        let atomic = f64::new_atomic_tracker();
+        atomic.add(1.5);
+        atomic.shards();
+        atomic.add(2.5);
+
+        assert!(f64::abs(4.0 - atomic.get_value()) < 0.0001);
+        assert!(f64::abs(4.0 - atomic.get_and_reset_value()) < 0.0001);
+        assert!(f64::abs(0.0 - atomic.get_value()) < 0.0001);
+    }
+
+    #[test]
+    fn f64_atomic_value_is_consistent_across_threads() {
+        let atomic = std::sync::Arc::new(f64::new_atomic_tracker());
+        let handles = (0..8)
+            .map(|_| {
+                let atomic = atomic.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10_000 {
+                        atomic.add(0.5);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // 0.5 is exactly representable, so the sum is exact regardless of order
+        assert_eq!(atomic.get_and_reset_value(), 40_000.0);
+        assert_eq!(atomic.get_value(), 0.0);
+    }
 }
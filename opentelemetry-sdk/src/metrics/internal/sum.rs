@@ -6,48 +6,89 @@ use std::{
     time::SystemTime,
 };
 
-use crate::metrics::data::{self, Aggregation, DataPoint, Temporality};
+use crate::metrics::data::{self, Aggregation, DataPoint, Exemplar, Temporality};
 use crate::metrics::AttributeSet;
+use crate::TelemetryBudget;
 use opentelemetry::KeyValue;
 use opentelemetry::{global, metrics::MetricsError};
 
 use super::{
-    aggregate::{is_under_cardinality_limit, STREAM_OVERFLOW_ATTRIBUTE_SET},
-    AtomicTracker, Number,
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    exemplar::{ExemplarCandidate, FixedSizeReservoir, DEFAULT_RESERVOIR_SIZE},
+    AtomicTracker, CollectionTime, Number,
 };
 
+/// The sum of a time series and the exemplars sampled from its measurements.
+struct SumValue<T: Number<T>> {
+    value: T::AtomicTracker,
+    exemplars: Mutex<FixedSizeReservoir<T>>,
+}
+
+impl<T: Number<T>> SumValue<T> {
+    fn add(&self, measurement: T, exemplar: Option<ExemplarCandidate>) {
+        self.value.add(measurement);
+        if let Some(candidate) = exemplar {
+            if let Ok(mut exemplars) = self.exemplars.lock() {
+                exemplars.offer(measurement, candidate);
+            }
+        }
+    }
+
+    /// Returns the sampled exemplars, emptying the reservoir if `reset` is set.
+    fn collect_exemplars(&self, reset: bool) -> Vec<Exemplar<T>> {
+        self.exemplars
+            .lock()
+            .map(|mut exemplars| exemplars.collect(reset))
+            .unwrap_or_default()
+    }
+}
+
 /// The storage for sums.
 struct ValueMap<T: Number<T>> {
-    values: RwLock<HashMap<AttributeSet, T::AtomicTracker>>,
+    values: RwLock<HashMap<AttributeSet, SumValue<T>>>,
     has_no_value_attribute_value: AtomicBool,
-    no_attribute_value: T::AtomicTracker,
+    no_attribute_value: SumValue<T>,
+    cardinality_limit: CardinalityLimit,
+    budget: Option<TelemetryBudget>,
 }
 
 impl<T: Number<T>> Default for ValueMap<T> {
     fn default() -> Self {
-        ValueMap::new()
+        ValueMap::new(CardinalityLimit::default(), None)
     }
 }
 
 impl<T: Number<T>> ValueMap<T> {
-    fn new() -> Self {
+    fn new(cardinality_limit: CardinalityLimit, budget: Option<TelemetryBudget>) -> Self {
         ValueMap {
             values: RwLock::new(HashMap::new()),
             has_no_value_attribute_value: AtomicBool::new(false),
-            no_attribute_value: T::new_atomic_tracker(),
+            no_attribute_value: new_sum_value(&budget),
+            cardinality_limit,
+            budget,
         }
     }
 }
 
+fn new_sum_value<T: Number<T>>(budget: &Option<TelemetryBudget>) -> SumValue<T> {
+    SumValue {
+        value: T::new_atomic_tracker(),
+        exemplars: Mutex::new(FixedSizeReservoir::new(
+            DEFAULT_RESERVOIR_SIZE,
+            budget.clone(),
+        )),
+    }
+}
+
 impl<T: Number<T>> ValueMap<T> {
-    fn measure(&self, measurement: T, attrs: AttributeSet) {
+    fn measure(&self, measurement: T, attrs: AttributeSet, exemplar: Option<ExemplarCandidate>) {
         if attrs.is_empty() {
-            self.no_attribute_value.add(measurement);
+            self.no_attribute_value.add(measurement, exemplar);
             self.has_no_value_attribute_value
                 .store(true, Ordering::Release);
         } else if let Ok(values) = self.values.read() {
             if let Some(value_to_update) = values.get(&attrs) {
-                value_to_update.add(measurement);
+                value_to_update.add(measurement, exemplar);
                 return;
             } else {
                 drop(values);
@@ -55,20 +96,21 @@ impl<T: Number<T>> ValueMap<T> {
                     // Recheck after acquiring write lock, in case another
                     // thread has added the value.
                     if let Some(value_to_update) = values.get(&attrs) {
-                        value_to_update.add(measurement);
+                        value_to_update.add(measurement, exemplar);
                         return;
-                    } else if is_under_cardinality_limit(values.len()) {
-                        let new_value = T::new_atomic_tracker();
-                        new_value.add(measurement);
+                    } else if self.cardinality_limit.is_under(values.len()) {
+                        let new_value = new_sum_value(&self.budget);
+                        new_value.add(measurement, exemplar);
                         values.insert(attrs, new_value);
-                    } else if let Some(overflow_value) =
-                        values.get_mut(&STREAM_OVERFLOW_ATTRIBUTE_SET)
-                    {
-                        overflow_value.add(measurement);
                         return;
+                    }
+
+                    self.cardinality_limit.record_overflow();
+                    if let Some(overflow_value) = values.get_mut(&STREAM_OVERFLOW_ATTRIBUTE_SET) {
+                        overflow_value.add(measurement, exemplar);
                     } else {
-                        let new_value = T::new_atomic_tracker();
-                        new_value.add(measurement);
+                        let new_value = new_sum_value(&self.budget);
+                        new_value.add(measurement, exemplar);
                         values.insert(STREAM_OVERFLOW_ATTRIBUTE_SET.clone(), new_value);
                         global::handle_error(MetricsError::Other("Warning: Maximum data points for metric stream exceeded. Entry added to overflow. Subsequent overflows to same metric until next collect will not be logged.".into()));
                     }
@@ -91,23 +133,32 @@ impl<T: Number<T>> Sum<T> {
     ///
     /// Each sum is scoped by attributes and the aggregation cycle the measurements
     /// were made in.
-    pub(crate) fn new(monotonic: bool) -> Self {
+    pub(crate) fn new(
+        monotonic: bool,
+        cardinality_limit: CardinalityLimit,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
         Sum {
-            value_map: ValueMap::new(),
+            value_map: ValueMap::new(cardinality_limit, budget),
             monotonic,
             start: Mutex::new(SystemTime::now()),
         }
     }
 
-    pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
-        self.value_map.measure(measurement, attrs)
+    pub(crate) fn measure(
+        &self,
+        measurement: T,
+        attrs: AttributeSet,
+        exemplar: Option<ExemplarCandidate>,
+    ) {
+        self.value_map.measure(measurement, attrs, exemplar)
     }
 
     pub(crate) fn delta(
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
 
         let s_data = dest.and_then(|d| d.as_mut().downcast_mut::<data::Sum<T>>());
         let mut new_agg = if s_data.is_none() {
@@ -146,8 +197,12 @@ impl<T: Number<T>> Sum<T> {
                 attributes: vec![],
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: self.value_map.no_attribute_value.get_and_reset_value(),
-                exemplars: vec![],
+                value: self
+                    .value_map
+                    .no_attribute_value
+                    .value
+                    .get_and_reset_value(),
+                exemplars: self.value_map.no_attribute_value.collect_exemplars(true),
             });
         }
 
@@ -159,8 +214,8 @@ impl<T: Number<T>> Sum<T> {
                     .collect(),
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: value.get_value(),
-                exemplars: vec![],
+                value: value.value.get_value(),
+                exemplars: value.collect_exemplars(true),
             });
         }
 
@@ -179,7 +234,7 @@ impl<T: Number<T>> Sum<T> {
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
 
         let s_data = dest.and_then(|d| d.as_mut().downcast_mut::<data::Sum<T>>());
         let mut new_agg = if s_data.is_none() {
@@ -219,8 +274,8 @@ impl<T: Number<T>> Sum<T> {
                 attributes: vec![],
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: self.value_map.no_attribute_value.get_value(),
-                exemplars: vec![],
+                value: self.value_map.no_attribute_value.value.get_value(),
+                exemplars: self.value_map.no_attribute_value.collect_exemplars(false),
             });
         }
 
@@ -236,8 +291,8 @@ impl<T: Number<T>> Sum<T> {
                     .collect(),
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: value.get_value(),
-                exemplars: vec![],
+                value: value.value.get_value(),
+                exemplars: value.collect_exemplars(false),
             });
         }
 
@@ -257,9 +312,9 @@ pub(crate) struct PrecomputedSum<T: Number<T>> {
 }
 
 impl<T: Number<T>> PrecomputedSum<T> {
-    pub(crate) fn new(monotonic: bool) -> Self {
+    pub(crate) fn new(monotonic: bool, cardinality_limit: CardinalityLimit) -> Self {
         PrecomputedSum {
-            value_map: ValueMap::new(),
+            value_map: ValueMap::new(cardinality_limit, None),
             monotonic,
             start: Mutex::new(SystemTime::now()),
             reported: Mutex::new(Default::default()),
@@ -267,14 +322,14 @@ impl<T: Number<T>> PrecomputedSum<T> {
     }
 
     pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
-        self.value_map.measure(measurement, attrs)
+        self.value_map.measure(measurement, attrs, None)
     }
 
     pub(crate) fn delta(
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
         let prev_start = self.start.lock().map(|start| *start).unwrap_or(t);
 
         let s_data = dest.and_then(|d| d.as_mut().downcast_mut::<data::Sum<T>>());
@@ -309,26 +364,34 @@ impl<T: Number<T>> PrecomputedSum<T> {
             Err(_) => return (0, None),
         };
 
+        let default = T::default();
         if self
             .value_map
             .has_no_value_attribute_value
             .swap(false, Ordering::AcqRel)
         {
+            let value = self
+                .value_map
+                .no_attribute_value
+                .value
+                .get_and_reset_value();
+            let empty = AttributeSet::default();
+            let delta = value - *reported.get(&empty).unwrap_or(&default);
+            new_reported.insert(empty, value);
             s_data.data_points.push(DataPoint {
                 attributes: vec![],
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: self.value_map.no_attribute_value.get_and_reset_value(),
+                value: delta,
                 exemplars: vec![],
             });
         }
 
-        let default = T::default();
         for (attrs, value) in values.drain() {
-            let delta = value.get_value() - *reported.get(&attrs).unwrap_or(&default);
-            if delta != default {
-                new_reported.insert(attrs.clone(), value.get_value());
-            }
+            let delta = value.value.get_value() - *reported.get(&attrs).unwrap_or(&default);
+            // Always remember the last observed value, otherwise an unchanged
+            // observation would be reported as a full delta on the next cycle.
+            new_reported.insert(attrs.clone(), value.value.get_value());
             s_data.data_points.push(DataPoint {
                 attributes: attrs
                     .iter()
@@ -359,7 +422,7 @@ impl<T: Number<T>> PrecomputedSum<T> {
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let t = SystemTime::now();
+        let t = CollectionTime::now();
         let prev_start = self.start.lock().map(|start| *start).unwrap_or(t);
 
         let s_data = dest.and_then(|d| d.as_mut().downcast_mut::<data::Sum<T>>());
@@ -377,7 +440,10 @@ impl<T: Number<T>> PrecomputedSum<T> {
         s_data.temporality = Temporality::Cumulative;
         s_data.is_monotonic = self.monotonic;
 
-        let values = match self.value_map.values.write() {
+        // Observable instruments report their full pre-computed sum on every
+        // collection, the observations of a cycle (summed when a view merged
+        // several attribute sets into one) replace the previous cycle's.
+        let mut values = match self.value_map.values.write() {
             Ok(v) => v,
             Err(_) => return (0, None),
         };
@@ -388,32 +454,26 @@ impl<T: Number<T>> PrecomputedSum<T> {
                 .data_points
                 .reserve_exact(n - s_data.data_points.capacity());
         }
-        let mut new_reported = HashMap::with_capacity(n);
-        let mut reported = match self.reported.lock() {
-            Ok(r) => r,
-            Err(_) => return (0, None),
-        };
 
         if self
             .value_map
             .has_no_value_attribute_value
-            .load(Ordering::Acquire)
+            .swap(false, Ordering::AcqRel)
         {
             s_data.data_points.push(DataPoint {
                 attributes: vec![],
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: self.value_map.no_attribute_value.get_value(),
+                value: self
+                    .value_map
+                    .no_attribute_value
+                    .value
+                    .get_and_reset_value(),
                 exemplars: vec![],
             });
         }
 
-        let default = T::default();
-        for (attrs, value) in values.iter() {
-            let delta = value.get_value() - *reported.get(attrs).unwrap_or(&default);
-            if delta != default {
-                new_reported.insert(attrs.clone(), value.get_value());
-            }
+        for (attrs, value) in values.drain() {
             s_data.data_points.push(DataPoint {
                 attributes: attrs
                     .iter()
@@ -421,14 +481,11 @@ impl<T: Number<T>> PrecomputedSum<T> {
                     .collect(),
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: delta,
+                value: value.value.get_value(),
                 exemplars: vec![],
             });
         }
 
-        *reported = new_reported;
-        drop(reported); // drop before values guard is dropped
-
         (
             s_data.data_points.len(),
             new_agg.map(|a| Box::new(a) as Box<_>),
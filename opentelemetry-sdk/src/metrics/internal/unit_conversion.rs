@@ -0,0 +1,64 @@
+use crate::metrics::data::{Aggregation, DataPoint, Exemplar, Gauge, Histogram, Sum};
+
+use super::{ComputeAggregation, Number};
+
+/// Multiplies the values of the aggregations computed by `compute` by `scale`.
+///
+/// The aggregators rebuild their data points on every collection, so the
+/// values are converted exactly once after each call.
+pub(crate) fn convert_units<T: Number<T>>(
+    compute: Box<dyn ComputeAggregation>,
+    scale: f64,
+) -> impl ComputeAggregation {
+    move |dest: Option<&mut dyn Aggregation>| {
+        let (len, mut new_agg) = match dest {
+            Some(dest) => {
+                let (len, new_agg) = compute.call(Some(&mut *dest));
+                if new_agg.is_none() {
+                    convert::<T>(dest, scale);
+                }
+                (len, new_agg)
+            }
+            None => compute.call(None),
+        };
+        if let Some(agg) = new_agg.as_mut() {
+            convert::<T>(&mut **agg, scale);
+        }
+
+        (len, new_agg)
+    }
+}
+
+fn convert<T: Number<T>>(agg: &mut dyn Aggregation, scale: f64) {
+    let agg = agg.as_mut();
+    if let Some(sum) = agg.downcast_mut::<Sum<T>>() {
+        convert_data_points(&mut sum.data_points, scale);
+    } else if let Some(gauge) = agg.downcast_mut::<Gauge<T>>() {
+        convert_data_points(&mut gauge.data_points, scale);
+    } else if let Some(histogram) = agg.downcast_mut::<Histogram<T>>() {
+        for dp in &mut histogram.data_points {
+            dp.bounds.iter_mut().for_each(|bound| *bound *= scale);
+            dp.sum = scale_value(dp.sum, scale);
+            dp.min = dp.min.map(|min| scale_value(min, scale));
+            dp.max = dp.max.map(|max| scale_value(max, scale));
+            convert_exemplars(&mut dp.exemplars, scale);
+        }
+    }
+}
+
+fn convert_data_points<T: Number<T>>(data_points: &mut [DataPoint<T>], scale: f64) {
+    for dp in data_points {
+        dp.value = scale_value(dp.value, scale);
+        convert_exemplars(&mut dp.exemplars, scale);
+    }
+}
+
+fn convert_exemplars<T: Number<T>>(exemplars: &mut [Exemplar<T>], scale: f64) {
+    for exemplar in exemplars {
+        exemplar.value = scale_value(exemplar.value, scale);
+    }
+}
+
+fn scale_value<T: Number<T>>(value: T, scale: f64) -> T {
+    T::from_float(value.into_float() * scale)
+}
@@ -0,0 +1,86 @@
+//! Hooks transforming the attributes of measurements before aggregation.
+use std::fmt;
+use std::sync::Arc;
+
+use opentelemetry::KeyValue;
+
+/// Transforms the attributes of measurements recorded on synchronous
+/// instruments before they are aggregated.
+///
+/// Processors can add, remove or rewrite attributes, for example to add the
+/// region of the request currently handled by the thread from a thread-local.
+/// Observable instrument callbacks are not processed, they can add such
+/// attributes themselves when observing.
+///
+/// # Performance
+///
+/// Processors run on the thread recording the measurement, within the call to
+/// `add`, `record`, etc.:
+///
+/// * If no processor is registered, recording a measurement costs the same as
+///   without this feature.
+/// * Otherwise, each measurement copies its attributes into a new `Vec`, then
+///   calls every processor in registration order. The processed attributes are
+///   deduplicated and sorted as usual when the attribute set is built.
+///
+/// Processors should therefore be cheap, avoiding locks and I/O. Adding
+/// attributes with many distinct values increases the number of data points
+/// and the memory used by aggregations.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::KeyValue;
+/// use opentelemetry_sdk::metrics::{MeasurementProcessor, SdkMeterProvider};
+///
+/// thread_local! {
+///     static REGION: &'static str = "eu-west-1";
+/// }
+///
+/// struct RegionProcessor;
+///
+/// impl MeasurementProcessor for RegionProcessor {
+///     fn process(&self, attributes: &mut Vec<KeyValue>) {
+///         REGION.with(|region| attributes.push(KeyValue::new("region", *region)));
+///     }
+/// }
+///
+/// let provider = SdkMeterProvider::builder()
+///     .with_measurement_processor(RegionProcessor)
+///     .build();
+/// # drop(provider);
+/// ```
+pub trait MeasurementProcessor: Send + Sync + 'static {
+    /// Transforms the attributes of a single measurement in place.
+    fn process(&self, attributes: &mut Vec<KeyValue>);
+}
+
+/// The measurement processors registered with a meter provider, in order.
+#[derive(Clone, Default)]
+pub(crate) struct MeasurementProcessors(Arc<Vec<Box<dyn MeasurementProcessor>>>);
+
+impl MeasurementProcessors {
+    pub(crate) fn new(processors: Vec<Box<dyn MeasurementProcessor>>) -> Self {
+        MeasurementProcessors(Arc::new(processors))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn process(&self, attributes: &[KeyValue]) -> Vec<KeyValue> {
+        let mut attributes = attributes.to_vec();
+        for processor in self.0.iter() {
+            processor.process(&mut attributes);
+        }
+        attributes
+    }
+}
+
+impl fmt::Debug for MeasurementProcessors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MeasurementProcessors")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
@@ -6,16 +6,20 @@ use opentelemetry::{
     metrics::{
         noop::{NoopAsyncInstrument, NoopRegistration},
         AsyncInstrument, Callback, CallbackRegistration, Counter, Gauge, Histogram,
-        InstrumentProvider, MetricsError, ObservableCounter, ObservableGauge,
-        ObservableUpDownCounter, Observer as ApiObserver, Result, UpDownCounter,
+        HistogramAdvice, HistogramAggregation, InstrumentProvider, MetricsError, ObservableCounter,
+        ObservableGauge, ObservableUpDownCounter, Observer as ApiObserver, Result, UpDownCounter,
     },
     KeyValue,
 };
 
 use crate::instrumentation::Scope;
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+use crate::metrics::measurement_processor::MeasurementProcessors;
 use crate::metrics::{
+    aggregation::Aggregation,
     instrument::{
-        Instrument, InstrumentKind, Observable, ObservableId, ResolvedMeasures, EMPTY_MEASURE_MSG,
+        Instrument, InstrumentKind, NegativeValueGuard, Observable, ObservableId, ResolvedMeasures,
+        EMPTY_MEASURE_MSG,
     },
     internal::{self, Number},
     pipeline::{Pipelines, Resolver},
@@ -53,6 +57,8 @@ pub struct SdkMeter {
     i64_resolver: Resolver<i64>,
     f64_resolver: Resolver<f64>,
     validation_policy: InstrumentValidationPolicy,
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    measurement_processors: MeasurementProcessors,
 }
 
 impl SdkMeter {
@@ -66,6 +72,19 @@ impl SdkMeter {
             i64_resolver: Resolver::new(Arc::clone(&pipes), Arc::clone(&view_cache)),
             f64_resolver: Resolver::new(pipes, view_cache),
             validation_policy: InstrumentValidationPolicy::HandleGlobalAndIgnore,
+            #[cfg(feature = "experimental_metrics_measurement_processor")]
+            measurement_processors: MeasurementProcessors::default(),
+        }
+    }
+
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    pub(crate) fn with_measurement_processors(
+        self,
+        measurement_processors: MeasurementProcessors,
+    ) -> Self {
+        Self {
+            measurement_processors,
+            ..self
         }
     }
 
@@ -113,13 +132,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableCounter<u64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.u64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableCounter,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableCounter::new(Arc::new(NoopAsyncInstrument::new())));
         }
 
@@ -135,7 +154,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableCounter::new(observable))
@@ -150,13 +169,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableCounter<f64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.f64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableCounter,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableCounter::new(Arc::new(NoopAsyncInstrument::new())));
         }
         let observable = Arc::new(Observable::new(
@@ -171,7 +190,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableCounter::new(observable))
@@ -210,13 +229,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableUpDownCounter<i64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.i64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableUpDownCounter,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableUpDownCounter::new(Arc::new(
                 NoopAsyncInstrument::new(),
             )));
@@ -234,7 +253,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableUpDownCounter::new(observable))
@@ -249,13 +268,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableUpDownCounter<f64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.f64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableUpDownCounter,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableUpDownCounter::new(Arc::new(
                 NoopAsyncInstrument::new(),
             )));
@@ -273,7 +292,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableUpDownCounter::new(observable))
@@ -324,13 +343,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableGauge<u64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.u64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableGauge,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableGauge::new(Arc::new(NoopAsyncInstrument::new())));
         }
 
@@ -346,7 +365,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableGauge::new(observable))
@@ -361,13 +380,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableGauge<i64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.i64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableGauge,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableGauge::new(Arc::new(NoopAsyncInstrument::new())));
         }
 
@@ -383,7 +402,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableGauge::new(observable))
@@ -398,13 +417,13 @@ impl InstrumentProvider for SdkMeter {
     ) -> Result<ObservableGauge<f64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.f64_resolver);
-        let ms = p.measures(
+        let ms = p.pipeline_measures(
             InstrumentKind::ObservableGauge,
             name.clone(),
             description.clone(),
             unit.clone(),
         )?;
-        if ms.is_empty() {
+        if ms.iter().all(Vec::is_empty) {
             return Ok(ObservableGauge::new(Arc::new(NoopAsyncInstrument::new())));
         }
 
@@ -420,7 +439,7 @@ impl InstrumentProvider for SdkMeter {
         for callback in callbacks {
             let cb_inst = Arc::clone(&observable);
             self.pipes
-                .register_callback(move || callback(cb_inst.as_ref()));
+                .register_callback(move |pipeline| callback(&cb_inst.in_pipeline(pipeline)));
         }
 
         Ok(ObservableGauge::new(observable))
@@ -431,10 +450,20 @@ impl InstrumentProvider for SdkMeter {
         name: Cow<'static, str>,
         description: Option<Cow<'static, str>>,
         unit: Option<Cow<'static, str>>,
+    ) -> Result<Histogram<f64>> {
+        self.f64_histogram_with_advice(name, description, unit, HistogramAdvice::default())
+    }
+
+    fn f64_histogram_with_advice(
+        &self,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        unit: Option<Cow<'static, str>>,
+        advice: HistogramAdvice,
     ) -> Result<Histogram<f64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.f64_resolver);
-        p.lookup(InstrumentKind::Histogram, name, description, unit)
+        p.lookup_histogram(name, description, unit, advice)
             .map(|i| Histogram::new(Arc::new(i)))
     }
 
@@ -443,10 +472,20 @@ impl InstrumentProvider for SdkMeter {
         name: Cow<'static, str>,
         description: Option<Cow<'static, str>>,
         unit: Option<Cow<'static, str>>,
+    ) -> Result<Histogram<u64>> {
+        self.u64_histogram_with_advice(name, description, unit, HistogramAdvice::default())
+    }
+
+    fn u64_histogram_with_advice(
+        &self,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        unit: Option<Cow<'static, str>>,
+        advice: HistogramAdvice,
     ) -> Result<Histogram<u64>> {
         validate_instrument_config(name.as_ref(), &unit, self.validation_policy)?;
         let p = InstrumentResolver::new(self, &self.u64_resolver);
-        p.lookup(InstrumentKind::Histogram, name, description, unit)
+        p.lookup_histogram(name, description, unit, advice)
             .map(|i| Histogram::new(Arc::new(i)))
     }
 
@@ -503,7 +542,12 @@ impl InstrumentProvider for SdkMeter {
             return Ok(Box::new(NoopRegistration::new()));
         }
 
-        self.pipes.register_multi_callback(move || callback(&reg))
+        self.pipes.register_multi_callback(move |pipeline| {
+            callback(&PipelineObserver {
+                observer: &reg,
+                pipeline,
+            })
+        })
     }
 }
 
@@ -516,6 +560,20 @@ enum InstrumentValidationPolicy {
     Strict,
 }
 
+/// The `aggregation` advised for instrument `name`, `None` if it is invalid.
+fn valid_advice(name: &str, aggregation: Aggregation) -> Option<Aggregation> {
+    match aggregation.validate() {
+        Ok(()) => Some(aggregation),
+        Err(err) => {
+            global::handle_error(MetricsError::Config(format!(
+                "ignoring the aggregation advised for histogram {}: {}",
+                name, err
+            )));
+            None
+        }
+    }
+}
+
 fn validate_instrument_config(
     name: &str,
     unit: &Option<Cow<'static, str>>,
@@ -601,11 +659,18 @@ impl Observer {
     }
 }
 
-impl ApiObserver for Observer {
+/// An [Observer] passed to multi-instrument callbacks run during the collection
+/// of a single pipeline.
+struct PipelineObserver<'a> {
+    observer: &'a Observer,
+    pipeline: usize,
+}
+
+impl ApiObserver for PipelineObserver<'_> {
     fn observe_f64(&self, inst: &dyn AsyncInstrument<f64>, measurement: f64, attrs: &[KeyValue]) {
         if let Some(f64_obs) = inst.as_any().downcast_ref::<Observable<f64>>() {
-            if self.f64s.contains(&f64_obs.id) {
-                f64_obs.observe(measurement, attrs)
+            if self.observer.f64s.contains(&f64_obs.id) {
+                f64_obs.observe_in_pipeline(self.pipeline, measurement, attrs)
             } else {
                 global::handle_error(
                     MetricsError::Other(format!("observable instrument not registered for callback, failed to record. name: {}, description: {}, unit: {:?}, number: f64",
@@ -623,8 +688,8 @@ impl ApiObserver for Observer {
 
     fn observe_u64(&self, inst: &dyn AsyncInstrument<u64>, measurement: u64, attrs: &[KeyValue]) {
         if let Some(u64_obs) = inst.as_any().downcast_ref::<Observable<u64>>() {
-            if self.u64s.contains(&u64_obs.id) {
-                u64_obs.observe(measurement, attrs)
+            if self.observer.u64s.contains(&u64_obs.id) {
+                u64_obs.observe_in_pipeline(self.pipeline, measurement, attrs)
             } else {
                 global::handle_error(
                     MetricsError::Other(format!("observable instrument not registered for callback, failed to record. name: {}, description: {}, unit: {:?}, number: f64",
@@ -642,8 +707,8 @@ impl ApiObserver for Observer {
 
     fn observe_i64(&self, inst: &dyn AsyncInstrument<i64>, measurement: i64, attrs: &[KeyValue]) {
         if let Some(i64_obs) = inst.as_any().downcast_ref::<Observable<i64>>() {
-            if self.i64s.contains(&i64_obs.id) {
-                i64_obs.observe(measurement, attrs)
+            if self.observer.i64s.contains(&i64_obs.id) {
+                i64_obs.observe_in_pipeline(self.pipeline, measurement, attrs)
             } else {
                 global::handle_error(
                     MetricsError::Other(format!("observable instrument not registered for callback, failed to record. name: {}, description: {}, unit: {:?}, number: f64",
@@ -688,28 +753,98 @@ where
         description: Option<Cow<'static, str>>,
         unit: Option<Cow<'static, str>>,
     ) -> Result<ResolvedMeasures<T>> {
-        let aggregators = self.measures(kind, name, description, unit)?;
+        self.resolve_measures(self.instrument(kind, name, description, unit))
+    }
+
+    /// lookup_histogram returns the resolved measures of a histogram, using
+    /// the advice of its author if it is valid.
+    fn lookup_histogram(
+        &self,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        unit: Option<Cow<'static, str>>,
+        advice: HistogramAdvice,
+    ) -> Result<ResolvedMeasures<T>> {
+        let mut inst = self.instrument(InstrumentKind::Histogram, name, description, unit);
+        inst.advised_aggregation = advice.aggregation.and_then(|aggregation| {
+            let aggregation = match aggregation {
+                HistogramAggregation::ExplicitBucket {
+                    boundaries,
+                    record_min_max,
+                } => Aggregation::ExplicitBucketHistogram {
+                    boundaries,
+                    record_min_max,
+                },
+                HistogramAggregation::Base2Exponential {
+                    max_size,
+                    max_scale,
+                    record_min_max,
+                } => Aggregation::Base2ExponentialHistogram {
+                    max_size,
+                    max_scale,
+                    record_min_max,
+                },
+                _ => return None,
+            };
+            valid_advice(&inst.name, aggregation)
+        });
+        inst.advised_boundaries = advice.boundaries.and_then(|boundaries| {
+            let aggregation = Aggregation::ExplicitBucketHistogram {
+                boundaries,
+                record_min_max: true,
+            };
+            match valid_advice(&inst.name, aggregation) {
+                Some(Aggregation::ExplicitBucketHistogram { boundaries, .. }) => Some(boundaries),
+                _ => None,
+            }
+        });
+        self.resolve_measures(inst)
+    }
+
+    fn resolve_measures(&self, inst: Instrument) -> Result<ResolvedMeasures<T>> {
+        let negative_value_guard = match inst.kind {
+            Some(InstrumentKind::Counter) | Some(InstrumentKind::Histogram) => {
+                Some(NegativeValueGuard::new(inst.name.clone()))
+            }
+            _ => None,
+        };
+        let aggregators = self.resolve.measures(inst)?;
         Ok(ResolvedMeasures {
             measures: aggregators,
+            negative_value_guard,
+            #[cfg(feature = "experimental_metrics_measurement_processor")]
+            measurement_processors: self.meter.measurement_processors.clone(),
         })
     }
 
-    fn measures(
+    /// The resolved measures grouped by the index of the pipeline they belong to.
+    fn pipeline_measures(
         &self,
         kind: InstrumentKind,
         name: Cow<'static, str>,
         description: Option<Cow<'static, str>>,
         unit: Option<Cow<'static, str>>,
-    ) -> Result<Vec<Arc<dyn internal::Measure<T>>>> {
-        let inst = Instrument {
+    ) -> Result<Vec<Vec<Arc<dyn internal::Measure<T>>>>> {
+        self.resolve
+            .pipeline_measures(self.instrument(kind, name, description, unit))
+    }
+
+    fn instrument(
+        &self,
+        kind: InstrumentKind,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        unit: Option<Cow<'static, str>>,
+    ) -> Instrument {
+        Instrument {
             name,
             description: description.unwrap_or_default(),
             unit: unit.unwrap_or_default(),
             kind: Some(kind),
             scope: self.meter.scope.clone(),
-        };
-
-        self.resolve.measures(inst)
+            advised_aggregation: None,
+            advised_boundaries: None,
+        }
     }
 }
 
@@ -725,7 +860,10 @@ mod tests {
         INSTRUMENT_UNIT_LENGTH,
     };
     use crate::{
-        metrics::{pipeline::Pipelines, SdkMeterProvider},
+        metrics::{
+            internal::DEFAULT_CARDINALITY_LIMIT, pipeline::Pipelines, ExemplarFilter,
+            SdkMeterProvider,
+        },
         Resource, Scope,
     };
 
@@ -745,7 +883,15 @@ mod tests {
         // scope and pipelines are not related to test
         let meter = SdkMeter::new(
             Scope::default(),
-            Arc::new(Pipelines::new(Resource::default(), Vec::new(), Vec::new())),
+            Arc::new(Pipelines::new(
+                Resource::default(),
+                Vec::new(),
+                Vec::new(),
+                ExemplarFilter::default(),
+                DEFAULT_CARDINALITY_LIMIT,
+                Default::default(),
+                None,
+            )),
         )
         .with_validation_policy(InstrumentValidationPolicy::Strict);
         // (name, expected error)
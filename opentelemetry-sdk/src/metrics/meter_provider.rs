@@ -2,10 +2,12 @@ use core::fmt;
 use std::{
     borrow::Cow,
     collections::HashMap,
+    env,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
+    time::{Duration, Instant},
 };
 
 use opentelemetry::{
@@ -14,9 +16,26 @@ use opentelemetry::{
     KeyValue,
 };
 
-use crate::{instrumentation::Scope, Resource};
+use crate::{
+    guard::{self, DEFAULT_GUARD_TIMEOUT},
+    instrumentation::Scope,
+    DroppedTelemetry, Resource, ShutdownError, ShutdownResult, TelemetryBudget,
+};
+
+use super::{
+    exemplar::ExemplarFilter,
+    internal::{CardinalityOverflows, DEFAULT_CARDINALITY_LIMIT},
+    meter::SdkMeter,
+    pipeline::Pipelines,
+    reader::MetricReader,
+    view::View,
+};
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+use super::{measurement_processor::MeasurementProcessors, MeasurementProcessor};
 
-use super::{meter::SdkMeter, pipeline::Pipelines, reader::MetricReader, view::View};
+/// The environment variable setting the default cardinality limit of metric
+/// streams.
+const OTEL_METRICS_CARDINALITY_LIMIT: &str = "OTEL_METRICS_CARDINALITY_LIMIT";
 
 /// Handles the creation and coordination of [Meter]s.
 ///
@@ -35,6 +54,20 @@ struct SdkMeterProviderInner {
     pipes: Arc<Pipelines>,
     meters: Arc<Mutex<HashMap<Scope, Arc<SdkMeter>>>>,
     is_shutdown: Arc<AtomicBool>,
+    scope_filter: Option<ScopeFilter>,
+    cardinality_overflows: Arc<CardinalityOverflows>,
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    measurement_processors: MeasurementProcessors,
+}
+
+/// Predicate selecting the scopes whose meters record measurements.
+#[derive(Clone)]
+struct ScopeFilter(Arc<dyn Fn(&Scope) -> bool + Send + Sync>);
+
+impl fmt::Debug for ScopeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ScopeFilter(..)")
+    }
 }
 
 impl Default for SdkMeterProvider {
@@ -51,6 +84,10 @@ impl SdkMeterProvider {
 
     /// Flushes all pending telemetry.
     ///
+    /// The readers are flushed one after the other in the order they were
+    /// registered. All readers are flushed even if some of them fail, their
+    /// errors are combined into the returned error.
+    ///
     /// There is no guaranteed that all telemetry be flushed or all resources have
     /// been released on error.
     ///
@@ -107,6 +144,140 @@ impl SdkMeterProvider {
     pub fn shutdown(&self) -> Result<()> {
         self.inner.shutdown()
     }
+
+    /// Shuts down the meter provider, waiting at most `timeout` for its
+    /// readers to export the pending telemetry.
+    ///
+    /// Readers are shut down in the order they were registered, each with the
+    /// time left until the timeout. All readers are shut down even if some of
+    /// them fail or time out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use opentelemetry_sdk::{metrics::SdkMeterProvider, ShutdownError};
+    ///
+    /// let provider = SdkMeterProvider::builder().build();
+    /// match provider.shutdown_with_timeout(Duration::from_secs(5)) {
+    ///     Ok(()) => {}
+    ///     Err(ShutdownError::Timeout(_)) => eprintln!("some metrics may be lost"),
+    ///     Err(err) => eprintln!("failed to shut down: {err}"),
+    /// }
+    /// ```
+    pub fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        if self
+            .inner
+            .is_shutdown
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(ShutdownError::AlreadyShutdown);
+        }
+
+        self.inner
+            .pipes
+            .shutdown_with_deadline(Instant::now() + timeout)
+    }
+
+    /// The metric data points dropped by the readers of this provider, and the
+    /// measurements recorded into the overflow data point of a stream once it
+    /// reached its cardinality limit.
+    ///
+    /// The counts keep accumulating over the lifetime of the provider and
+    /// remain available after it was shut down.
+    pub fn dropped_telemetry(&self) -> DroppedTelemetry {
+        let overflows = self
+            .inner
+            .cardinality_overflows
+            .snapshot()
+            .into_iter()
+            .map(|(_, _, count)| count)
+            .sum();
+
+        DroppedTelemetry {
+            limited_parts: overflows,
+            ..Default::default()
+        } + self.inner.pipes.dropped_telemetry()
+    }
+
+    /// The resource of the provider, `None` without readers.
+    pub(crate) fn resource(&self) -> Option<Resource> {
+        self.inner.pipes.resource().cloned()
+    }
+
+    /// The diagnostics of the readers of the provider.
+    pub(crate) fn reader_diagnostics(&self) -> Vec<crate::ProcessorDiagnostics> {
+        self.inner.pipes.reader_diagnostics()
+    }
+
+    /// Returns a guard flushing and shutting down this provider when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::global;
+    /// use opentelemetry_sdk::metrics::SdkMeterProvider;
+    ///
+    /// fn main() {
+    ///     let provider = SdkMeterProvider::builder().build();
+    ///     let _guard = provider.guard();
+    ///     global::set_meter_provider(provider);
+    ///
+    ///     // record measurements..
+    ///
+    ///     // the metrics are exported when `_guard` goes out of scope, even if
+    ///     // `main` panics
+    /// }
+    /// ```
+    pub fn guard(&self) -> MeterProviderGuard {
+        MeterProviderGuard {
+            provider: self.clone(),
+            timeout: DEFAULT_GUARD_TIMEOUT,
+        }
+    }
+}
+
+/// Flushes and shuts down a [`SdkMeterProvider`] when dropped.
+///
+/// Created with [`SdkMeterProvider::guard`]. See [shutting down with
+/// guards](crate#shutting-down-with-guards) for how the provider is shut down,
+/// and how long dropping the guard may block.
+#[must_use = "the provider is shut down when the guard is dropped"]
+#[derive(Debug)]
+pub struct MeterProviderGuard {
+    provider: SdkMeterProvider,
+    timeout: Duration,
+}
+
+impl MeterProviderGuard {
+    /// Sets the maximum time to wait for the provider to shut down when the
+    /// guard is dropped, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The provider shut down by this guard.
+    pub fn provider(&self) -> &SdkMeterProvider {
+        &self.provider
+    }
+}
+
+impl Drop for MeterProviderGuard {
+    fn drop(&mut self) {
+        if self.provider.inner.is_shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let provider = self.provider.clone();
+        guard::shutdown_on_drop("meter", self.timeout, move || {
+            if let Err(err) = provider.force_flush() {
+                global::handle_error(err);
+            }
+            provider.shutdown()
+        });
+    }
 }
 
 impl SdkMeterProviderInner {
@@ -162,11 +333,21 @@ impl MeterProvider for SdkMeterProvider {
 
         let scope = builder.build();
 
+        if let Some(ScopeFilter(filter)) = &self.inner.scope_filter {
+            if !filter(&scope) {
+                return Meter::new(Arc::new(NoopMeterCore::new()));
+            }
+        }
+
         if let Ok(mut meters) = self.inner.meters.lock() {
             let meter = meters
                 .entry(scope)
                 .or_insert_with_key(|scope| {
-                    Arc::new(SdkMeter::new(scope.clone(), self.inner.pipes.clone()))
+                    let meter = SdkMeter::new(scope.clone(), self.inner.pipes.clone());
+                    #[cfg(feature = "experimental_metrics_measurement_processor")]
+                    let meter = meter
+                        .with_measurement_processors(self.inner.measurement_processors.clone());
+                    Arc::new(meter)
                 })
                 .clone();
             Meter::new(meter)
@@ -182,6 +363,13 @@ pub struct MeterProviderBuilder {
     resource: Option<Resource>,
     readers: Vec<Box<dyn MetricReader>>,
     views: Vec<Arc<dyn View>>,
+    scope_filter: Option<ScopeFilter>,
+    exemplar_filter: Option<ExemplarFilter>,
+    cardinality_limit: Option<usize>,
+    budget: Option<TelemetryBudget>,
+    self_diagnostics: bool,
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    measurement_processors: Vec<Box<dyn MeasurementProcessor>>,
 }
 
 impl MeterProviderBuilder {
@@ -202,6 +390,15 @@ impl MeterProviderBuilder {
     ///
     /// By default, if this option is not used, the [MeterProvider] will perform no
     /// operations; no data will be exported without a [MetricReader].
+    ///
+    /// This option can be used multiple times to register several readers, e.g.
+    /// a pull based reader alongside a [PeriodicReader]. Each reader keeps its own
+    /// aggregation state, using the aggregation and temporality it selects, and
+    /// every measurement is recorded into the state of all readers. Observable
+    /// instrument callbacks are run once per reader collection, and their
+    /// observations are only recorded for the reader being collected.
+    ///
+    /// [PeriodicReader]: crate::metrics::PeriodicReader
     pub fn with_reader<T: MetricReader>(mut self, reader: T) -> Self {
         self.readers.push(Box::new(reader));
         self
@@ -219,29 +416,175 @@ impl MeterProviderBuilder {
         self
     }
 
+    /// Only record measurements of meters whose [Scope] matches `filter`.
+    ///
+    /// Meters of other scopes are no-op, this silences libraries creating
+    /// instruments through the global meter provider without a view per
+    /// instrument. The filter runs once per meter creation, not per
+    /// measurement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_sdk::metrics::SdkMeterProvider;
+    ///
+    /// let provider = SdkMeterProvider::builder()
+    ///     .with_scope_filter(|scope| !scope.name.starts_with("noisy_lib"))
+    ///     .build();
+    /// ```
+    pub fn with_scope_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Scope) -> bool + Send + Sync + 'static,
+    {
+        self.scope_filter = Some(ScopeFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Selects the measurements sampled as exemplars of sums and histograms.
+    ///
+    /// This option overrides any filter set through the
+    /// `OTEL_METRICS_EXEMPLAR_FILTER` environment variable. By default only
+    /// measurements made in the context of a sampled span are sampled, see
+    /// [ExemplarFilter].
+    pub fn with_exemplar_filter(mut self, filter: ExemplarFilter) -> Self {
+        self.exemplar_filter = Some(filter);
+        self
+    }
+
+    /// Sets the maximum number of data points of each metric stream, including
+    /// the overflow data point.
+    ///
+    /// Once a stream reaches its limit, measurements of new attribute sets are
+    /// aggregated into a single data point with the `otel.metric.overflow=true`
+    /// attribute. Views can override the limit of a stream with
+    /// [Stream::cardinality_limit].
+    ///
+    /// This option overrides the `OTEL_METRICS_CARDINALITY_LIMIT` environment
+    /// variable. By default the limit is 2000. A limit of `0` is ignored.
+    ///
+    /// [Stream::cardinality_limit]: crate::metrics::Stream::cardinality_limit
+    pub fn with_cardinality_limit(mut self, limit: usize) -> Self {
+        self.cardinality_limit = (limit > 0).then_some(limit);
+        self
+    }
+
+    /// Bounds the memory of the exemplars sampled by metric streams by
+    /// `budget`, shared with span and log processors.
+    ///
+    /// Exemplars which don't fit in the budget are not sampled, and sampled
+    /// exemplars are evicted to make room for spans and log records of higher
+    /// priority. Data points are bounded by the cardinality limit instead.
+    pub fn with_budget(mut self, budget: TelemetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Enables the metrics the SDK reports about itself.
+    ///
+    /// The `otel.sdk.metric.cardinality_overflows` counter of the
+    /// `opentelemetry_sdk` meter reports the number of measurements recorded
+    /// into the overflow data point of each stream, keyed by the
+    /// `otel.scope.name` and `otel.metric.name` attributes. These metrics are
+    /// disabled by default.
+    pub fn with_self_diagnostics(mut self, enabled: bool) -> Self {
+        self.self_diagnostics = enabled;
+        self
+    }
+
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    /// Associates a [MeasurementProcessor] with a [MeterProvider].
+    ///
+    /// Processors transform the attributes of measurements recorded on
+    /// synchronous instruments before aggregation, in the order they were
+    /// registered. See [MeasurementProcessor] for their performance cost.
+    pub fn with_measurement_processor<T: MeasurementProcessor>(mut self, processor: T) -> Self {
+        self.measurement_processors.push(Box::new(processor));
+        self
+    }
+
     /// Construct a new [MeterProvider] with this configuration.
 
     pub fn build(self) -> SdkMeterProvider {
-        SdkMeterProvider {
+        let cardinality_overflows = Arc::new(CardinalityOverflows::default());
+        let provider = SdkMeterProvider {
             inner: Arc::new(SdkMeterProviderInner {
                 pipes: Arc::new(Pipelines::new(
                     self.resource.unwrap_or_default(),
                     self.readers,
                     self.views,
+                    self.exemplar_filter
+                        .unwrap_or_else(ExemplarFilter::from_env),
+                    self.cardinality_limit
+                        .or_else(cardinality_limit_from_env)
+                        .unwrap_or(DEFAULT_CARDINALITY_LIMIT),
+                    Arc::clone(&cardinality_overflows),
+                    self.budget,
                 )),
                 meters: Default::default(),
                 is_shutdown: Arc::new(AtomicBool::new(false)),
+                scope_filter: self.scope_filter,
+                cardinality_overflows: Arc::clone(&cardinality_overflows),
+                #[cfg(feature = "experimental_metrics_measurement_processor")]
+                measurement_processors: MeasurementProcessors::new(self.measurement_processors),
             }),
+        };
+
+        if self.self_diagnostics {
+            register_self_diagnostics(&provider, cardinality_overflows);
         }
+
+        provider
     }
 }
 
+/// Reads the default cardinality limit from the `OTEL_METRICS_CARDINALITY_LIMIT`
+/// environment variable, ignoring invalid and zero limits.
+fn cardinality_limit_from_env() -> Option<usize> {
+    env::var(OTEL_METRICS_CARDINALITY_LIMIT)
+        .ok()
+        .and_then(|limit| limit.trim().parse::<usize>().ok())
+        .filter(|limit| *limit > 0)
+}
+
+/// Registers the metrics the SDK reports about the streams of `provider`.
+fn register_self_diagnostics(
+    provider: &SdkMeterProvider,
+    cardinality_overflows: Arc<CardinalityOverflows>,
+) {
+    let meter = provider.versioned_meter(
+        "opentelemetry_sdk",
+        Some(env!("CARGO_PKG_VERSION")),
+        None::<&'static str>,
+        None,
+    );
+    meter
+        .u64_observable_counter("otel.sdk.metric.cardinality_overflows")
+        .with_description("Measurements recorded into the overflow data point of a metric stream")
+        .with_callback(move |observer| {
+            for (scope, name, count) in cardinality_overflows.snapshot() {
+                observer.observe(
+                    count,
+                    &[
+                        KeyValue::new("otel.scope.name", scope),
+                        KeyValue::new("otel.metric.name", name),
+                    ],
+                );
+            }
+        })
+        .init();
+}
+
 impl fmt::Debug for MeterProviderBuilder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MeterProviderBuilder")
             .field("resource", &self.resource)
             .field("readers", &self.readers)
             .field("views", &self.views.len())
+            .field("scope_filter", &self.scope_filter)
+            .field("exemplar_filter", &self.exemplar_filter)
+            .field("cardinality_limit", &self.cardinality_limit)
+            .field("budget", &self.budget)
+            .field("self_diagnostics", &self.self_diagnostics)
             .finish()
     }
 }
@@ -251,11 +594,12 @@ mod tests {
         SERVICE_NAME, TELEMETRY_SDK_LANGUAGE, TELEMETRY_SDK_NAME, TELEMETRY_SDK_VERSION,
     };
     use crate::testing::metrics::metric_reader::TestMetricReader;
-    use crate::Resource;
+    use crate::{Resource, ShutdownError};
     use opentelemetry::global;
     use opentelemetry::metrics::MeterProvider;
     use opentelemetry::{Key, KeyValue, Value};
     use std::env;
+    use std::time::Duration;
 
     #[test]
     fn test_meter_provider_resource() {
@@ -414,6 +758,24 @@ mod tests {
         // While the reader is shutdown, and no collect is happening
         counter.add(1, &[]);
     }
+    #[test]
+    fn test_meter_provider_shutdown_with_timeout() {
+        let reader = TestMetricReader::new();
+        let provider = super::SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .build();
+
+        assert_eq!(
+            provider.shutdown_with_timeout(Duration::from_secs(1)),
+            Ok(())
+        );
+        assert!(reader.is_shutdown());
+        assert_eq!(
+            provider.shutdown_with_timeout(Duration::from_secs(1)),
+            Err(ShutdownError::AlreadyShutdown)
+        );
+    }
+
     #[test]
     fn test_shutdown_invoked_on_last_drop() {
         let reader = TestMetricReader::new();
@@ -440,6 +802,19 @@ mod tests {
         assert!(reader.is_shutdown());
     }
 
+    #[test]
+    fn scope_filter_drops_meters_of_denied_scopes() {
+        let provider = super::SdkMeterProvider::builder()
+            .with_scope_filter(|scope| !scope.name.starts_with("noisy_lib"))
+            .build();
+        let _noisy = provider.meter("noisy_lib_http");
+        let _allowed = provider.meter("my_app");
+
+        let meters = provider.inner.meters.lock().unwrap();
+        assert_eq!(meters.len(), 1);
+        assert!(meters.keys().all(|scope| scope.name == "my_app"));
+    }
+
     #[test]
     fn same_meter_reused_same_scope() {
         let provider = super::SdkMeterProvider::builder().build();
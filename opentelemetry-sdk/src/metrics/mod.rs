@@ -40,21 +40,39 @@
 //! [Resource]: crate::Resource
 
 pub(crate) mod aggregation;
+#[cfg(feature = "experimental_metrics_custom_aggregation")]
+pub(crate) mod aggregator;
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+pub(crate) mod baggage_attributes;
 pub mod data;
+pub(crate) mod exemplar;
 pub mod exporter;
 pub(crate) mod instrument;
 pub(crate) mod internal;
 pub(crate) mod manual_reader;
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+pub(crate) mod measurement_processor;
 pub(crate) mod meter;
 mod meter_provider;
 pub(crate) mod periodic_reader;
 pub(crate) mod pipeline;
 pub mod reader;
+pub(crate) mod temporality_conversion;
 pub(crate) mod view;
 
 pub use aggregation::*;
+#[cfg(feature = "experimental_metrics_custom_aggregation")]
+pub use aggregator::{Aggregator, CustomAggregation};
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+pub use baggage_attributes::{
+    BaggageMetricsAttributesExtractor, BaggageMetricsAttributesExtractorBuilder,
+    BAGGAGE_OVERFLOW_VALUE,
+};
+pub use exemplar::ExemplarFilter;
 pub use instrument::*;
 pub use manual_reader::*;
+#[cfg(feature = "experimental_metrics_measurement_processor")]
+pub use measurement_processor::MeasurementProcessor;
 pub use meter::*;
 pub use meter_provider::*;
 pub use periodic_reader::*;
@@ -62,7 +80,6 @@ pub use pipeline::Pipeline;
 pub use view::*;
 
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
 use opentelemetry::{Key, KeyValue, Value};
@@ -76,20 +93,15 @@ pub struct AttributeSet(Vec<KeyValue>, u64);
 
 impl From<&[KeyValue]> for AttributeSet {
     fn from(values: &[KeyValue]) -> Self {
-        let mut seen_keys = HashSet::with_capacity(values.len());
-        let vec = values
-            .iter()
-            .rev()
-            .filter_map(|kv| {
-                if seen_keys.insert(kv.key.clone()) {
-                    Some(kv.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-
-        AttributeSet::new(vec)
+        // Later values take precedence over earlier ones with the same key.
+        // Reversing before the stable sort keeps them first among equal keys,
+        // so duplicates are removed without hashing the keys.
+        let mut vec = values.iter().rev().cloned().collect::<Vec<_>>();
+        vec.sort();
+        vec.dedup_by(|kv, retained| kv.key == retained.key);
+
+        let hash = calculate_hash(&vec);
+        AttributeSet(vec, hash)
     }
 }
 
@@ -103,12 +115,6 @@ fn calculate_hash(values: &[KeyValue]) -> u64 {
 }
 
 impl AttributeSet {
-    fn new(mut values: Vec<KeyValue>) -> Self {
-        values.sort_unstable();
-        let hash = calculate_hash(&values);
-        AttributeSet(values, hash)
-    }
-
     /// Returns `true` if the set contains no elements.
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
@@ -142,10 +148,10 @@ mod tests {
     use self::data::{DataPoint, HistogramDataPoint, ScopeMetrics};
     use super::*;
     use crate::metrics::data::{ResourceMetrics, Temporality};
-    use crate::metrics::reader::TemporalitySelector;
+    use crate::metrics::reader::{AggregationSelector, TemporalitySelector};
     use crate::testing::metrics::InMemoryMetricsExporterBuilder;
     use crate::{runtime, testing::metrics::InMemoryMetricsExporter};
-    use opentelemetry::metrics::{Counter, Meter, UpDownCounter};
+    use opentelemetry::metrics::{Counter, HistogramAggregation, Meter, UpDownCounter};
     use opentelemetry::{metrics::MeterProvider as _, KeyValue};
     use rand::{rngs, Rng, SeedableRng};
     use std::borrow::Cow;
@@ -159,6 +165,31 @@ mod tests {
     // "multi_thread" tokio flavor must be used else flush won't
     // be able to make progress!
 
+    #[test]
+    fn attribute_set_keeps_last_value_of_duplicate_keys() {
+        const KEY_A: Key = Key::from_static_str("a");
+        let set = AttributeSet::from(
+            &[
+                KeyValue::new("b", 1),
+                KeyValue::new(KEY_A, 1),
+                KeyValue::new("a", 2),
+                KeyValue::new("b", 3),
+            ][..],
+        );
+
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![
+                (&Key::from("a"), &Value::I64(2)),
+                (&Key::from("b"), &Value::I64(3))
+            ]
+        );
+        assert_eq!(
+            set,
+            AttributeSet::from(&[KeyValue::new("b", 3), KeyValue::new("a", 2)][..])
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn counter_overflow_delta() {
         // Arrange
@@ -166,8 +197,9 @@ mod tests {
         let counter = test_context.u64_counter("test", "my_counter", None);
 
         // Act
-        // Record measurements with A:0, A:1,.......A:1999, which just fits in the 2000 limit
-        for v in 0..2000 {
+        // Record measurements with A:0, A:1,.......A:1998, which just fits in the 2000 limit
+        // along with the overflow point.
+        for v in 0..1999 {
             counter.add(100, &[KeyValue::new("A", v.to_string())]);
         }
 
@@ -179,8 +211,8 @@ mod tests {
 
         let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
 
-        // Expecting 2001 metric points. (2000 + 1 overflow)
-        assert_eq!(sum.data_points.len(), 2001);
+        // Expecting 2000 metric points. (1999 + 1 overflow)
+        assert_eq!(sum.data_points.len(), 2000);
 
         let data_point =
             find_datapoint_with_key_value(&sum.data_points, "otel.metric.overflow", "true")
@@ -188,6 +220,242 @@ mod tests {
         assert_eq!(data_point.value, 300);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_overflow_with_provider_cardinality_limit() {
+        // cargo test counter_overflow_with_provider_cardinality_limit --features=testing
+
+        // Arrange
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Delta,
+            SdkMeterProvider::builder().with_cardinality_limit(3),
+        );
+        let counter = test_context.u64_counter("test", "my_counter", None);
+
+        // Act
+        for v in ["a", "b", "c", "d"] {
+            counter.add(10, &[KeyValue::new("A", v)]);
+        }
+        test_context.flush_metrics();
+
+        // Assert
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
+        assert_eq!(sum.data_points.len(), 3);
+        let data_point =
+            find_datapoint_with_key_value(&sum.data_points, "otel.metric.overflow", "true")
+                .expect("overflow point expected");
+        assert_eq!(data_point.value, 20);
+        assert_eq!(
+            test_context
+                .meter_provider
+                .dropped_telemetry()
+                .limited_parts,
+            2
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_overflow_with_env_cardinality_limit() {
+        // cargo test counter_overflow_with_env_cardinality_limit --features=testing
+
+        // Arrange
+        let mut test_context =
+            temp_env::with_var("OTEL_METRICS_CARDINALITY_LIMIT", Some("2"), || {
+                TestContext::new(Temporality::Cumulative)
+            });
+        let counter = test_context.u64_counter("test", "my_counter", None);
+
+        // Act
+        for v in ["a", "b", "c"] {
+            counter.add(10, &[KeyValue::new("A", v)]);
+        }
+        test_context.flush_metrics();
+
+        // Assert
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
+        assert_eq!(sum.data_points.len(), 2);
+        let data_point =
+            find_datapoint_with_key_value(&sum.data_points, "otel.metric.overflow", "true")
+                .expect("overflow point expected");
+        assert_eq!(data_point.value, 20);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_overflow_with_view_cardinality_limit() {
+        // cargo test histogram_overflow_with_view_cardinality_limit --features=testing
+
+        // Arrange
+        let criteria = Instrument::new().name("my_histogram");
+        let stream = Stream::new().cardinality_limit(2);
+        let view = new_view(criteria, stream).expect("Expected to create a new view");
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Cumulative,
+            SdkMeterProvider::builder().with_view(view),
+        );
+        let histogram = test_context.meter().u64_histogram("my_histogram").init();
+
+        // Act
+        for v in ["a", "b", "c"] {
+            histogram.record(10, &[KeyValue::new("A", v)]);
+        }
+        test_context.flush_metrics();
+
+        // Assert
+        let histogram_data =
+            test_context.get_aggregation::<data::Histogram<u64>>("my_histogram", None);
+        assert_eq!(histogram_data.data_points.len(), 2);
+        let data_point = find_histogram_datapoint_with_key_value(
+            &histogram_data.data_points,
+            "otel.metric.overflow",
+            "true",
+        )
+        .expect("overflow point expected");
+        assert_eq!(data_point.count, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_and_histogram_with_view_unit_conversion() {
+        // cargo test counter_and_histogram_with_view_unit_conversion --features=testing
+
+        // Arrange
+        let histogram_view = new_view(
+            Instrument::new().name("my_histogram"),
+            Stream::new().unit_conversion("KiB", 1.0 / 1024.0),
+        )
+        .expect("Expected to create a new view");
+        let counter_view = new_view(
+            Instrument::new().name("my_counter"),
+            Stream::new().unit_conversion("By", 1024.0),
+        )
+        .expect("Expected to create a new view");
+        let fractional_view = new_view(
+            Instrument::new().name("my_fractional_counter"),
+            Stream::new().unit_conversion("KiB", 1.0 / 1024.0),
+        )
+        .expect("Expected to create a new view");
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Cumulative,
+            SdkMeterProvider::builder()
+                .with_view(histogram_view)
+                .with_view(counter_view)
+                .with_view(fractional_view),
+        );
+        let counter = test_context
+            .meter()
+            .u64_counter("my_counter")
+            .with_unit("KiBy")
+            .init();
+        let fractional_counter = test_context
+            .meter()
+            .u64_counter("my_fractional_counter")
+            .with_unit("By")
+            .init();
+        let histogram = test_context
+            .meter()
+            .f64_histogram("my_histogram")
+            .with_unit("By")
+            .init();
+
+        // Act
+        counter.add(2, &[]);
+        counter.add(1, &[]);
+        fractional_counter.add(1000, &[]);
+        histogram.record(512.0, &[]);
+        histogram.record(4096.0, &[]);
+        test_context.flush_metrics();
+
+        // Assert
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", Some("By"));
+        assert_eq!(sum.data_points[0].value, 3072);
+
+        let exported = test_context
+            .exporter
+            .get_finished_metrics()
+            .expect("metrics expected to be exported");
+        let metric = exported[0].scope_metrics[0]
+            .metrics
+            .iter()
+            .find(|metric| metric.name == "my_histogram")
+            .expect("histogram expected to be exported");
+        assert_eq!(metric.unit, "KiB");
+        let histogram_data = metric
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .expect("Histogram aggregation expected for Histogram instruments by default");
+        let data_point = &histogram_data.data_points[0];
+        assert_eq!(data_point.count, 2);
+        assert_eq!(data_point.sum, 4.5);
+        assert_eq!(data_point.min, Some(0.5));
+        assert_eq!(data_point.max, Some(4.0));
+        assert_eq!(data_point.bounds[1], 5.0 / 1024.0);
+
+        // integer instruments are not converted by fractional scales
+        assert!(exported[0].scope_metrics[0]
+            .metrics
+            .iter()
+            .all(|metric| metric.name != "my_fractional_counter"));
+
+        // cumulative values are converted once per collection
+        test_context.reset_metrics();
+        counter.add(1, &[]);
+        test_context.flush_metrics();
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", Some("By"));
+        assert_eq!(sum.data_points[0].value, 4096);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn cardinality_overflows_self_diagnostics() {
+        // cargo test cardinality_overflows_self_diagnostics --features=testing
+
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let other_reader =
+            PeriodicReader::builder(InMemoryMetricsExporter::default(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_reader(other_reader)
+            .with_cardinality_limit(2)
+            .with_self_diagnostics(true)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for v in ["a", "b", "c", "d"] {
+            counter.add(1, &[KeyValue::new("A", v)]);
+        }
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let resource_metrics = exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let metric = resource_metrics[0]
+            .scope_metrics
+            .iter()
+            .find(|scope_metrics| scope_metrics.scope.name == "opentelemetry_sdk")
+            .and_then(|scope_metrics| scope_metrics.metrics.first())
+            .expect("self-diagnostic metrics are expected to be exported");
+        assert_eq!(metric.name, "otel.sdk.metric.cardinality_overflows");
+        let sum = metric
+            .data
+            .as_any()
+            .downcast_ref::<data::Sum<u64>>()
+            .expect("Sum aggregation expected for observable counters");
+        let data_point =
+            find_datapoint_with_key_value(&sum.data_points, "otel.metric.name", "my_counter")
+                .expect("overflows of my_counter expected");
+        assert!(data_point
+            .attributes
+            .contains(&KeyValue::new("otel.scope.name", "test")));
+        // Overflows are counted once, not once per reader.
+        assert_eq!(data_point.value, 3);
+        assert_eq!(meter_provider.dropped_telemetry().limited_parts, 3);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn counter_aggregation_cumulative() {
         // Run this test with stdout enabled to see output.
@@ -262,7 +530,6 @@ mod tests {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-    #[ignore = "Aggregation bug! https://github.com/open-telemetry/opentelemetry-rust/issues/1517"]
     async fn observable_counter_aggregation_delta_zero_increment() {
         // Run this test with stdout enabled to see output.
         // cargo test observable_counter_aggregation_delta_zero_increment --features=testing -- --nocapture
@@ -601,6 +868,307 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_aggregation_with_view_disabling_min_max() {
+        // cargo test histogram_aggregation_with_view_disabling_min_max --features=testing
+
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let criteria = Instrument::new().name("test_histogram");
+        let stream = Stream::new().record_min_max(false);
+
+        let view = new_view(criteria, stream).expect("Expected to create a new view");
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_view(view)
+            .build();
+
+        // Act
+        let meter = meter_provider.meter("test");
+        let histogram = meter.f64_histogram("test_histogram").init();
+
+        histogram.record(1.5, &[KeyValue::new("key1", "value1")]);
+        histogram.record(4.5, &[KeyValue::new("key1", "value1")]);
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let resource_metrics = exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let metric = &resource_metrics[0].scope_metrics[0].metrics[0];
+        let histogram_data = metric
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .expect("Histogram aggregation expected for Histogram instruments by default");
+        assert_eq!(histogram_data.data_points.len(), 1);
+
+        let data_point = &histogram_data.data_points[0];
+        assert_eq!(data_point.count, 2);
+        assert_eq!(data_point.sum, 6.0);
+        assert_eq!(data_point.min, None);
+        assert_eq!(data_point.max, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_aggregation_advised_by_instrument() {
+        // cargo test histogram_aggregation_advised_by_instrument --features=testing
+
+        // Arrange
+        struct CoarseBucketsSelector;
+        impl AggregationSelector for CoarseBucketsSelector {
+            fn aggregation(&self, _kind: InstrumentKind) -> Aggregation {
+                Aggregation::ExplicitBucketHistogram {
+                    boundaries: vec![10.0, 100.0],
+                    record_min_max: true,
+                }
+            }
+        }
+
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let coarse_exporter = InMemoryMetricsExporterBuilder::new()
+            .with_aggregation_selector(CoarseBucketsSelector)
+            .build();
+        let coarse_reader =
+            PeriodicReader::builder(coarse_exporter.clone(), runtime::Tokio).build();
+        let criteria = Instrument::new().name("test_histogram_view");
+        let stream = Stream::new().aggregation(Aggregation::ExplicitBucketHistogram {
+            boundaries: vec![1.0, 5.0],
+            record_min_max: true,
+        });
+        let view = new_view(criteria, stream).expect("Expected to create a new view");
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_reader(coarse_reader)
+            .with_view(view)
+            .build();
+
+        // Act
+        let meter = meter_provider.meter("test");
+        let advice = HistogramAggregation::Base2Exponential {
+            max_size: 160,
+            max_scale: 20,
+            record_min_max: false,
+        };
+        let histogram = meter
+            .f64_histogram("test_histogram")
+            .with_aggregation(advice.clone())
+            .init();
+        let histogram_view = meter
+            .f64_histogram("test_histogram_view")
+            .with_aggregation(advice)
+            .init();
+        let histogram_invalid = meter
+            .f64_histogram("test_histogram_invalid")
+            .with_aggregation(HistogramAggregation::Base2Exponential {
+                max_size: 160,
+                max_scale: 21,
+                record_min_max: true,
+            })
+            .init();
+
+        histogram.record(1.5, &[]);
+        histogram_view.record(1.5, &[]);
+        histogram_invalid.record(1.5, &[]);
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let resource_metrics = exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let metrics = &resource_metrics[0].scope_metrics[0].metrics;
+        let find = |name: &str| {
+            metrics
+                .iter()
+                .find(|m| m.name == name)
+                .expect("metric is expected to be exported")
+        };
+
+        let exponential = find("test_histogram")
+            .data
+            .as_any()
+            .downcast_ref::<data::ExponentialHistogram<f64>>()
+            .expect("Advised exponential histogram aggregation expected");
+        assert_eq!(exponential.data_points[0].count, 1);
+        assert_eq!(exponential.data_points[0].min, None);
+
+        let explicit = find("test_histogram_view")
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .expect("View aggregation expected to take precedence over the advice");
+        assert_eq!(explicit.data_points[0].bounds, vec![1.0, 5.0]);
+
+        assert!(
+            find("test_histogram_invalid")
+                .data
+                .as_any()
+                .downcast_ref::<data::Histogram<f64>>()
+                .is_some(),
+            "Invalid advice expected to be ignored"
+        );
+
+        // the aggregation selected by a reader takes precedence over the advice
+        let resource_metrics = coarse_exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let explicit = resource_metrics[0].scope_metrics[0]
+            .metrics
+            .iter()
+            .find(|m| m.name == "test_histogram")
+            .expect("metric is expected to be exported")
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .expect("Reader aggregation expected to take precedence over the advice");
+        assert_eq!(explicit.data_points[0].bounds, vec![10.0, 100.0]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_boundaries_advised_by_instrument() {
+        // cargo test histogram_boundaries_advised_by_instrument --features=testing
+
+        // Arrange
+        struct ExponentialAggregationSelector;
+        impl AggregationSelector for ExponentialAggregationSelector {
+            fn aggregation(&self, _kind: InstrumentKind) -> Aggregation {
+                Aggregation::Base2ExponentialHistogram {
+                    max_size: 160,
+                    max_scale: 20,
+                    record_min_max: true,
+                }
+            }
+        }
+
+        let exporter = InMemoryMetricsExporter::default();
+        let exponential_exporter = InMemoryMetricsExporterBuilder::new()
+            .with_aggregation_selector(ExponentialAggregationSelector)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter.clone(), runtime::Tokio).build())
+            .with_reader(
+                PeriodicReader::builder(exponential_exporter.clone(), runtime::Tokio).build(),
+            )
+            .build();
+
+        // Act
+        let meter = meter_provider.meter("test");
+        let histogram = meter
+            .f64_histogram("test_histogram")
+            .with_boundaries(vec![1.0, 2.0, 4.0])
+            .init();
+        let histogram_invalid = meter
+            .f64_histogram("test_histogram_invalid")
+            .with_boundaries(vec![4.0, 2.0])
+            .init();
+
+        histogram.record(1.5, &[]);
+        histogram_invalid.record(1.5, &[]);
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let resource_metrics = exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let metrics = &resource_metrics[0].scope_metrics[0].metrics;
+        let bounds = |name: &str| {
+            let metric = metrics
+                .iter()
+                .find(|m| m.name == name)
+                .expect("metric is expected to be exported");
+            metric
+                .data
+                .as_any()
+                .downcast_ref::<data::Histogram<f64>>()
+                .expect("Histogram aggregation expected for Histogram instruments by default")
+                .data_points[0]
+                .bounds
+                .clone()
+        };
+        assert_eq!(bounds("test_histogram"), vec![1.0, 2.0, 4.0]);
+        assert_ne!(
+            bounds("test_histogram_invalid"),
+            vec![4.0, 2.0],
+            "Invalid boundaries expected to be ignored"
+        );
+
+        // boundaries do not apply to other aggregations
+        let resource_metrics = exponential_exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let metric = &resource_metrics[0].scope_metrics[0].metrics[0];
+        assert!(metric
+            .data
+            .as_any()
+            .downcast_ref::<data::ExponentialHistogram<f64>>()
+            .is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn observable_counter_with_multiple_readers_of_different_temporality() {
+        // cargo test observable_counter_with_multiple_readers_of_different_temporality --features=testing
+
+        // Arrange
+        struct DeltaTemporalitySelector;
+        impl TemporalitySelector for DeltaTemporalitySelector {
+            fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+                Temporality::Delta
+            }
+        }
+
+        let cumulative_exporter = InMemoryMetricsExporter::default();
+        let delta_exporter = InMemoryMetricsExporterBuilder::new()
+            .with_temporality_selector(DeltaTemporalitySelector)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(
+                PeriodicReader::builder(cumulative_exporter.clone(), runtime::Tokio).build(),
+            )
+            .with_reader(PeriodicReader::builder(delta_exporter.clone(), runtime::Tokio).build())
+            .build();
+
+        // Act
+        let meter = meter_provider.meter("test");
+        let _counter = meter
+            .u64_observable_counter("my_observable_counter")
+            .with_callback(|observer| observer.observe(100, &[KeyValue::new("key1", "value1")]))
+            .init();
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let last_value = |exporter: &InMemoryMetricsExporter| {
+            let resource_metrics = exporter
+                .get_finished_metrics()
+                .expect("metrics are expected to be exported.");
+            let metric = &resource_metrics.last().unwrap().scope_metrics[0].metrics[0];
+            let sum = metric
+                .data
+                .as_any()
+                .downcast_ref::<data::Sum<u64>>()
+                .expect("Sum aggregation expected for ObservableCounter instruments by default");
+            assert_eq!(sum.data_points.len(), 1);
+            (sum.temporality, sum.data_points[0].value)
+        };
+
+        // Each reader must only see the observations made during its own
+        // collection, even though the callback runs once per reader.
+        assert_eq!(
+            last_value(&cumulative_exporter),
+            (Temporality::Cumulative, 100)
+        );
+        assert_eq!(last_value(&delta_exporter), (Temporality::Delta, 100));
+
+        meter_provider.force_flush().unwrap();
+
+        assert_eq!(
+            last_value(&cumulative_exporter),
+            (Temporality::Cumulative, 100)
+        );
+        assert_eq!(last_value(&delta_exporter), (Temporality::Delta, 0));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn spatial_aggregation_when_view_drops_attributes_observable_counter() {
         // cargo test spatial_aggregation_when_view_drops_attributes_observable_counter --features=testing
@@ -668,7 +1236,6 @@ mod tests {
 
         // Expecting 1 time-series only, as the view drops all attributes resulting
         // in a single time-series.
-        // This is failing today, due to lack of support for spatial aggregation.
         assert_eq!(sum.data_points.len(), 1);
 
         // find and validate the single datapoint
@@ -745,7 +1312,6 @@ mod tests {
 
         // Expecting 1 time-series only, as the view drops all attributes resulting
         // in a single time-series.
-        // This is failing today, due to lack of support for spatial aggregation.
         assert_eq!(sum.data_points.len(), 1);
         // find and validate the single datapoint
         let data_point = &sum.data_points[0];
@@ -866,6 +1432,340 @@ mod tests {
         assert_eq!(data_point.value, 50, "Unexpected data point value");
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_drops_negative_values() {
+        let mut test_context = TestContext::new(Temporality::Cumulative);
+        let counter = test_context.meter().f64_counter("my_counter").init();
+
+        counter.add(50.0, &[]);
+        counter.add(-5.0, &[]);
+        counter.add(10.0, &[]);
+        test_context.flush_metrics();
+
+        let sum = test_context.get_aggregation::<data::Sum<f64>>("my_counter", None);
+
+        assert_eq!(sum.data_points.len(), 1, "Expected only one data point");
+        assert_eq!(sum.data_points[0].value, 60.0, "Negative value not dropped");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_drops_negative_values() {
+        let mut test_context = TestContext::new(Temporality::Cumulative);
+        let histogram = test_context.meter().f64_histogram("my_histogram").init();
+
+        histogram.record(5.0, &[]);
+        histogram.record(-3.0, &[]);
+        test_context.flush_metrics();
+
+        let histogram_data =
+            test_context.get_aggregation::<data::Histogram<f64>>("my_histogram", None);
+
+        let data_point = &histogram_data.data_points[0];
+        assert_eq!(data_point.count, 1, "Negative value not dropped");
+        assert_eq!(data_point.sum, 5.0);
+        assert_eq!(data_point.min, Some(5.0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn up_down_counter_keeps_negative_values() {
+        let mut test_context = TestContext::new(Temporality::Cumulative);
+        let counter = test_context
+            .meter()
+            .i64_up_down_counter("my_counter")
+            .init();
+
+        counter.add(10, &[]);
+        counter.add(-15, &[]);
+        test_context.flush_metrics();
+
+        let sum = test_context.get_aggregation::<data::Sum<i64>>("my_counter", None);
+
+        assert_eq!(sum.data_points[0].value, -5);
+    }
+
+    #[cfg(feature = "experimental_metrics_measurement_processor")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn measurement_processors_transform_attributes_in_order() {
+        struct AddRegion;
+        impl MeasurementProcessor for AddRegion {
+            fn process(&self, attributes: &mut Vec<KeyValue>) {
+                attributes.retain(|kv| kv.key.as_str() != "secret");
+                attributes.push(KeyValue::new("region", "us"));
+            }
+        }
+
+        struct OverrideRegion;
+        impl MeasurementProcessor for OverrideRegion {
+            fn process(&self, attributes: &mut Vec<KeyValue>) {
+                attributes.push(KeyValue::new("region", "eu"));
+            }
+        }
+
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Cumulative,
+            SdkMeterProvider::builder()
+                .with_measurement_processor(AddRegion)
+                .with_measurement_processor(OverrideRegion),
+        );
+        let counter = test_context.u64_counter("test", "my_counter", None);
+
+        counter.add(
+            5,
+            &[KeyValue::new("key", "value"), KeyValue::new("secret", 1)],
+        );
+        counter.add(3, &[KeyValue::new("key", "value")]);
+        test_context.flush_metrics();
+
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
+
+        assert_eq!(sum.data_points.len(), 1, "Expected only one data point");
+        let data_point = &sum.data_points[0];
+        assert_eq!(data_point.value, 8);
+        assert_eq!(
+            data_point.attributes,
+            vec![KeyValue::new("key", "value"), KeyValue::new("region", "eu")]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn batch_callback_observations_share_timestamp() {
+        // Arrange
+        let mut test_context = TestContext::new(Temporality::Cumulative);
+        let meter = test_context.meter();
+        let used = meter.u64_observable_gauge("memory.used").init();
+        let free = meter.u64_observable_gauge("memory.free").init();
+        let requests = meter.u64_counter("requests").init();
+
+        // Act
+        let (used_cb, free_cb) = (used.clone(), free.clone());
+        let _registration = meter
+            .register_callback(&[used.as_any(), free.as_any()], move |observer| {
+                observer.observe_u64(&used_cb, 60, &[]);
+                thread::sleep(Duration::from_millis(2));
+                observer.observe_u64(&free_cb, 40, &[]);
+            })
+            .expect("callback should be registered");
+        requests.add(1, &[]);
+        test_context.flush_metrics();
+
+        // Assert
+        test_context.resource_metrics = test_context
+            .exporter
+            .get_finished_metrics()
+            .expect("metrics expected to be exported");
+        let metrics = &test_context.resource_metrics[0].scope_metrics[0].metrics;
+        let times = metrics
+            .iter()
+            .map(|metric| {
+                let data = metric.data.as_any();
+                if let Some(gauge) = data.downcast_ref::<data::Gauge<u64>>() {
+                    gauge.data_points[0].time
+                } else {
+                    let sum = data.downcast_ref::<data::Sum<u64>>().unwrap();
+                    sum.data_points[0].time
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(times.len(), 3);
+        assert!(times[0].is_some());
+        assert!(times.iter().all(|time| *time == times[0]));
+    }
+
+    #[cfg(feature = "experimental_metrics_custom_aggregation")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn custom_aggregation_collects_per_cycle() {
+        // Counts the distinct values recorded in a collection cycle.
+        #[derive(Default)]
+        struct DistinctCount(Mutex<Vec<f64>>);
+        impl Aggregator for DistinctCount {
+            fn measure(&self, value: f64, _attributes: &AttributeSet) {
+                let mut values = self.0.lock().unwrap();
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+
+            fn collect(
+                &self,
+                temporality: Temporality,
+                dest: Option<&mut dyn data::Aggregation>,
+            ) -> (usize, Option<Box<dyn data::Aggregation>>) {
+                let mut values = self.0.lock().unwrap();
+                let data_points = vec![data::DataPoint {
+                    attributes: vec![],
+                    start_time: None,
+                    time: None,
+                    value: values.len() as u64,
+                    exemplars: vec![],
+                }];
+                if temporality == Temporality::Delta {
+                    values.clear();
+                }
+
+                match dest.and_then(|d| d.as_mut().downcast_mut::<data::Gauge<u64>>()) {
+                    Some(gauge) => {
+                        gauge.data_points = data_points;
+                        (1, None)
+                    }
+                    None => (1, Some(Box::new(data::Gauge { data_points }))),
+                }
+            }
+        }
+
+        let view = new_view(
+            Instrument::new().name("my_counter"),
+            Stream::new().aggregation(Aggregation::Custom(CustomAggregation::new(
+                "distinct_count",
+                DistinctCount::default,
+            ))),
+        )
+        .expect("Expected to create a new view");
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Delta,
+            SdkMeterProvider::builder().with_view(view),
+        );
+        let counter = test_context.u64_counter("test", "my_counter", None);
+
+        counter.add(5, &[KeyValue::new("key", "value1")]);
+        counter.add(5, &[KeyValue::new("key", "value2")]);
+        counter.add(7, &[]);
+        test_context.flush_metrics();
+
+        let gauge = test_context.get_aggregation::<data::Gauge<u64>>("my_counter", None);
+        assert_eq!(gauge.data_points.len(), 1);
+        assert_eq!(gauge.data_points[0].value, 2);
+
+        test_context.reset_metrics();
+        counter.add(1, &[]);
+        test_context.flush_metrics();
+
+        let gauge = test_context.get_aggregation::<data::Gauge<u64>>("my_counter", None);
+        assert_eq!(gauge.data_points[0].value, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_exemplars_keep_filtered_attributes() {
+        // cargo test counter_exemplars_keep_filtered_attributes --features=testing
+        let view = new_view(
+            Instrument::new().name("my_counter"),
+            Stream::new().allowed_attribute_keys(vec![Key::new("key")]),
+        )
+        .expect("Expected to create a new view");
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Delta,
+            SdkMeterProvider::builder()
+                .with_view(view)
+                .with_exemplar_filter(ExemplarFilter::AlwaysOn),
+        );
+        let counter = test_context.u64_counter("test", "my_counter", None);
+
+        counter.add(
+            5,
+            &[
+                KeyValue::new("key", "value"),
+                KeyValue::new("user", "alice"),
+            ],
+        );
+        test_context.flush_metrics();
+
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
+        assert_eq!(sum.data_points.len(), 1);
+        let data_point = &sum.data_points[0];
+        assert_eq!(data_point.attributes, vec![KeyValue::new("key", "value")]);
+        assert_eq!(data_point.exemplars.len(), 1);
+        let exemplar = &data_point.exemplars[0];
+        assert_eq!(exemplar.value, 5);
+        assert_eq!(
+            exemplar.filtered_attributes,
+            vec![KeyValue::new("user", "alice")]
+        );
+        // No span was active.
+        assert_eq!(exemplar.span_id, [0; 8]);
+        assert_eq!(exemplar.trace_id, [0; 16]);
+
+        // Delta reservoirs are reset each collection.
+        test_context.reset_metrics();
+        counter.add(1, &[KeyValue::new("key", "value")]);
+        test_context.flush_metrics();
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
+        assert_eq!(sum.data_points[0].exemplars.len(), 1);
+        assert_eq!(sum.data_points[0].exemplars[0].value, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_exemplars_are_sampled_per_time_series() {
+        // cargo test counter_exemplars_are_sampled_per_time_series --features=testing
+        let mut test_context = TestContext::with_provider_builder(
+            Temporality::Cumulative,
+            SdkMeterProvider::builder().with_exemplar_filter(ExemplarFilter::AlwaysOn),
+        );
+        let counter = test_context.u64_counter("test", "my_counter", None);
+
+        let before = std::time::SystemTime::now();
+        counter.add(1, &[]);
+        counter.add(2, &[KeyValue::new("key", "a")]);
+        counter.add(3, &[KeyValue::new("key", "b")]);
+        test_context.flush_metrics();
+
+        let sum = test_context.get_aggregation::<data::Sum<u64>>("my_counter", None);
+        assert_eq!(sum.data_points.len(), 3);
+        for data_point in &sum.data_points {
+            assert_eq!(data_point.exemplars.len(), 1);
+            assert_eq!(data_point.exemplars[0].value, data_point.value);
+            assert!(data_point.exemplars[0].time >= before);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_exemplars_are_trace_based() {
+        // cargo test histogram_exemplars_are_trace_based --features=testing
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+        use opentelemetry::Context;
+
+        let mut test_context = TestContext::new(Temporality::Cumulative);
+        let histogram = test_context.meter().u64_histogram("my_histogram").init();
+
+        // Not sampled, no span is active.
+        histogram.record(60, &[]);
+
+        let span_context = SpanContext::new(
+            TraceId::from(7),
+            SpanId::from(3),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        );
+        let guard = Context::current()
+            .with_remote_span_context(span_context)
+            .attach();
+        histogram.record(3, &[]);
+        histogram.record(7, &[]);
+        histogram.record(8, &[]);
+        drop(guard);
+
+        test_context.flush_metrics();
+
+        let histogram_data =
+            test_context.get_aggregation::<data::Histogram<u64>>("my_histogram", None);
+        let data_point = &histogram_data.data_points[0];
+        assert_eq!(data_point.count, 4);
+        // The last sampled measurement of each bucket is kept.
+        assert_eq!(
+            data_point
+                .exemplars
+                .iter()
+                .map(|e| e.value)
+                .collect::<Vec<_>>(),
+            vec![3, 8]
+        );
+        for exemplar in &data_point.exemplars {
+            assert_eq!(exemplar.span_id, SpanId::from(3).to_bytes());
+            assert_eq!(exemplar.trace_id, TraceId::from(7).to_bytes());
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn no_attr_cumulative_up_down_counter() {
         let mut test_context = TestContext::new(Temporality::Cumulative);
@@ -1203,6 +2103,25 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn gauge_aggregation_cumulative_reports_last_value() {
+        // Arrange
+        let mut test_context = TestContext::new(Temporality::Cumulative);
+        let gauge = test_context.meter().u64_gauge("my_gauge").init();
+
+        // Act
+        gauge.record(5, &[KeyValue::new("key1", "value1")]);
+        test_context.flush_metrics();
+        test_context.reset_metrics();
+        // the value is still reported without new measurements
+        test_context.flush_metrics();
+
+        // Assert
+        let gauge = test_context.get_aggregation::<data::Gauge<u64>>("my_gauge", None);
+        assert_eq!(gauge.data_points.len(), 1);
+        assert_eq!(gauge.data_points[0].value, 5);
+    }
+
     fn gauge_aggregation_helper(temporality: Temporality) {
         // Arrange
         let mut test_context = TestContext::new(temporality);
@@ -1460,6 +2379,10 @@ mod tests {
 
     impl TestContext {
         fn new(temporality: Temporality) -> Self {
+            Self::with_provider_builder(temporality, SdkMeterProvider::builder())
+        }
+
+        fn with_provider_builder(temporality: Temporality, builder: MeterProviderBuilder) -> Self {
             struct TestTemporalitySelector(Temporality);
             impl TemporalitySelector for TestTemporalitySelector {
                 fn temporality(&self, _kind: InstrumentKind) -> Temporality {
@@ -1472,7 +2395,7 @@ mod tests {
 
             let exporter = exporter.build();
             let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
-            let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+            let meter_provider = builder.with_reader(reader).build();
 
             TestContext {
                 exporter,
@@ -1,5 +1,5 @@
 use std::{
-    env, fmt, mem,
+    fmt, mem,
     sync::{Arc, Mutex, Weak},
     time::Duration,
 };
@@ -17,19 +17,24 @@ use opentelemetry::{
 };
 
 use crate::runtime::Runtime;
+use crate::util::positive_from_env;
 use crate::{
+    dropped::DroppedCounters,
+    instrumentation::Scope,
     metrics::{
-        exporter::PushMetricsExporter,
+        exporter::{FlushReason, PushMetricsExporter},
         reader::{MetricProducer, SdkProducer},
     },
-    Resource,
+    shutdown::{self, ShutdownError, ShutdownResult},
+    DroppedTelemetry, Resource,
 };
 
 use super::{
     aggregation::Aggregation,
-    data::{ResourceMetrics, Temporality},
+    data::{ExponentialHistogram, Gauge, Histogram, ResourceMetrics, Sum, Temporality},
     instrument::InstrumentKind,
     reader::{AggregationSelector, MetricReader, TemporalitySelector},
+    temporality_conversion::{TemporalityConversion, TemporalityConverter},
     Pipeline,
 };
 
@@ -52,13 +57,27 @@ const METRIC_EXPORT_TIMEOUT_NAME: &str = "OTEL_METRIC_EXPORT_TIMEOUT";
 /// return metric data to the user. It will not automatically send that data to
 /// the exporter outside of the predefined interval.
 ///
+/// # Environment variables
+///
+/// The defaults can be changed without rebuilding the application with the
+/// following environment variables, which are overridden by the matching
+/// builder methods. Invalid or zero values are ignored.
+///
+/// * `OTEL_METRIC_EXPORT_INTERVAL`: the interval between exports in
+///   milliseconds, see [`with_interval`].
+/// * `OTEL_METRIC_EXPORT_TIMEOUT`: the timeout of an export in milliseconds,
+///   see [`with_timeout`].
+///
 /// [collect]: MetricReader::collect
+/// [`with_interval`]: PeriodicReaderBuilder::with_interval
+/// [`with_timeout`]: PeriodicReaderBuilder::with_timeout
 #[derive(Debug)]
 pub struct PeriodicReaderBuilder<E, RT> {
     interval: Duration,
     timeout: Duration,
     exporter: E,
     producers: Vec<Box<dyn MetricProducer>>,
+    temporality_conversion: Option<TemporalityConversion>,
     runtime: RT,
 }
 
@@ -68,19 +87,14 @@ where
     RT: Runtime,
 {
     fn new(exporter: E, runtime: RT) -> Self {
-        let interval = env::var(METRIC_EXPORT_INTERVAL_NAME)
-            .ok()
-            .and_then(|v| v.parse().map(Duration::from_millis).ok())
-            .unwrap_or(DEFAULT_INTERVAL);
-        let timeout = env::var(METRIC_EXPORT_TIMEOUT_NAME)
-            .ok()
-            .and_then(|v| v.parse().map(Duration::from_millis).ok())
-            .unwrap_or(DEFAULT_TIMEOUT);
+        let interval = duration_from_env(METRIC_EXPORT_INTERVAL_NAME).unwrap_or(DEFAULT_INTERVAL);
+        let timeout = duration_from_env(METRIC_EXPORT_TIMEOUT_NAME).unwrap_or(DEFAULT_TIMEOUT);
 
         PeriodicReaderBuilder {
             interval,
             timeout,
             producers: vec![],
+            temporality_conversion: None,
             exporter,
             runtime,
         }
@@ -124,9 +138,61 @@ where
         self
     }
 
+    /// Aggregates metrics with the temporality selected by
+    /// `aggregation_temporality` and converts them to the temporality of the
+    /// exporter before every export.
+    ///
+    /// Sums and histograms are converted, including those of the registered
+    /// [MetricProducer]s, keeping the state of every stream between exports.
+    /// Streams converted to cumulative are exported every time, with their
+    /// last value if they were not measured since the previous export, and
+    /// at most 2000 streams of each metric are converted, further streams are
+    /// dropped. The sums of producers are converted as counters and up-down
+    /// counters, as their instrument kind is unknown. Gauges have no
+    /// temporality and exponential histograms are exported unchanged.
+    /// Converting cumulative metrics to delta loses the minimum and maximum of
+    /// histograms, which are unknown for the interval.
+    ///
+    /// Every reader aggregates metrics independently, so readers of the same
+    /// [SdkMeterProvider] can already export different temporalities without
+    /// conversion. This stage is useful to keep cumulative state in the SDK
+    /// for an exporter requiring delta, or to convert the data of producers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use opentelemetry_sdk::metrics::reader::DefaultTemporalitySelector;
+    /// use opentelemetry_sdk::metrics::PeriodicReader;
+    /// # fn example<E, R>(exporter: E, runtime: R)
+    /// # where
+    /// #     E: opentelemetry_sdk::metrics::exporter::PushMetricsExporter,
+    /// #     R: opentelemetry_sdk::runtime::Runtime,
+    /// # {
+    ///
+    /// // aggregate cumulatively, whatever the exporter prefers
+    /// let reader = PeriodicReader::builder(exporter, runtime)
+    ///     .with_temporality_conversion(DefaultTemporalitySelector::new())
+    ///     .build();
+    /// # drop(reader);
+    /// # }
+    /// ```
+    ///
+    /// [SdkMeterProvider]: crate::metrics::SdkMeterProvider
+    pub fn with_temporality_conversion(
+        mut self,
+        aggregation_temporality: impl TemporalitySelector + 'static,
+    ) -> Self {
+        self.temporality_conversion = Some(TemporalityConversion::new(aggregation_temporality));
+        self
+    }
+
     /// Create a [PeriodicReader] with the given config.
     pub fn build(self) -> PeriodicReader {
         let (message_sender, message_receiver) = mpsc::channel(256);
+        let converter = self
+            .temporality_conversion
+            .as_ref()
+            .map(|_| TemporalityConverter::default());
 
         let worker = move |reader: &PeriodicReader| {
             let ticker = self
@@ -147,6 +213,7 @@ where
                         resource: Resource::empty(),
                         scope_metrics: Vec::new(),
                     },
+                    converter,
                 }
                 .run(messages),
             ));
@@ -154,6 +221,8 @@ where
 
         PeriodicReader {
             exporter: Arc::new(self.exporter),
+            temporality_conversion: self.temporality_conversion,
+            dropped: Default::default(),
             inner: Arc::new(Mutex::new(PeriodicReaderInner {
                 message_sender,
                 is_shutdown: false,
@@ -164,6 +233,12 @@ where
     }
 }
 
+/// Read a non-zero duration in milliseconds from the environment variable
+/// `name`, reporting invalid values.
+fn duration_from_env(name: &str) -> Option<Duration> {
+    positive_from_env(name, MetricsError::Other).map(Duration::from_millis)
+}
+
 /// A [MetricReader] that continuously collects and exports metric data at a set
 /// interval.
 ///
@@ -204,6 +279,9 @@ where
 #[derive(Clone)]
 pub struct PeriodicReader {
     exporter: Arc<dyn PushMetricsExporter>,
+    temporality_conversion: Option<TemporalityConversion>,
+    // data points of failed exports, shared with the worker
+    dropped: Arc<DroppedCounters>,
     inner: Arc<Mutex<PeriodicReaderInner>>,
 }
 
@@ -224,6 +302,20 @@ impl fmt::Debug for PeriodicReader {
     }
 }
 
+impl PeriodicReader {
+    /// The kind of the instrument of the metric `name` of `scope`, unknown for
+    /// the metrics of producers.
+    fn instrument_kind(&self, scope: &Scope, name: &str) -> Option<InstrumentKind> {
+        let inner = self.inner.lock().ok()?;
+        match &inner.sdk_producer_or_worker {
+            ProducerOrWorker::Producer(sdk_producer) => {
+                sdk_producer.upgrade()?.instrument_kind(scope, name)
+            }
+            ProducerOrWorker::Worker(_) => None,
+        }
+    }
+}
+
 struct PeriodicReaderInner {
     message_sender: mpsc::Sender<Message>,
     is_shutdown: bool,
@@ -248,39 +340,59 @@ struct PeriodicReaderWorker<RT: Runtime> {
     timeout: Duration,
     runtime: RT,
     rm: ResourceMetrics,
+    converter: Option<TemporalityConverter>,
 }
 
 impl<RT: Runtime> PeriodicReaderWorker<RT> {
-    async fn collect_and_export(&mut self) -> Result<()> {
+    async fn collect_and_export(&mut self, reason: FlushReason) -> Result<()> {
         self.reader.collect(&mut self.rm)?;
+        if let Some(converter) = &mut self.converter {
+            let reader = &self.reader;
+            converter.convert(
+                &mut self.rm,
+                &|scope, name| reader.instrument_kind(scope, name),
+                &|kind| reader.exporter.temporality(kind),
+            );
+        }
 
-        let export = self.reader.exporter.export(&mut self.rm);
-        let timeout = self.runtime.delay(self.timeout);
-        pin_mut!(export);
-        pin_mut!(timeout);
-
-        match future::select(export, timeout).await {
-            Either::Left((res, _)) => res, // return the result.
-            Either::Right(_) => Err(MetricsError::Other("export timed out".into())),
+        let result = {
+            let export = self
+                .reader
+                .exporter
+                .export_with_reason(&mut self.rm, reason);
+            let timeout = self.runtime.delay(self.timeout);
+            pin_mut!(export);
+            pin_mut!(timeout);
+
+            match future::select(export, timeout).await {
+                Either::Left((res, _)) => res, // return the result.
+                Either::Right(_) => Err(MetricsError::Other("export timed out".into())),
+            }
+        };
+        if let Err(err) = &result {
+            self.reader
+                .dropped
+                .export_failed(data_point_count(&self.rm), err);
         }
+        result
     }
 
     async fn process_message(&mut self, message: Message) -> bool {
         match message {
             Message::Export => {
-                if let Err(err) = self.collect_and_export().await {
+                if let Err(err) = self.collect_and_export(FlushReason::Interval).await {
                     global::handle_error(err)
                 }
             }
             Message::Flush(ch) => {
-                let res = self.collect_and_export().await;
+                let res = self.collect_and_export(FlushReason::ForceFlush).await;
                 if ch.send(res).is_err() {
                     global::handle_error(MetricsError::Other("flush channel closed".into()))
                 }
             }
             Message::Shutdown(ch) => {
-                let res = self.collect_and_export().await;
-                let _ = self.reader.exporter.shutdown();
+                let res = self.collect_and_export(FlushReason::Shutdown).await;
+                let _ = self.reader.exporter.shutdown_with_deadline(self.timeout);
                 if ch.send(res).is_err() {
                     global::handle_error(MetricsError::Other("shutdown channel closed".into()))
                 }
@@ -300,6 +412,34 @@ impl<RT: Runtime> PeriodicReaderWorker<RT> {
     }
 }
 
+/// Counts the data points of all the metrics of `rm`.
+fn data_point_count(rm: &ResourceMetrics) -> u64 {
+    fn count<T: 'static>(data: &dyn std::any::Any) -> Option<usize> {
+        if let Some(sum) = data.downcast_ref::<Sum<T>>() {
+            Some(sum.data_points.len())
+        } else if let Some(gauge) = data.downcast_ref::<Gauge<T>>() {
+            Some(gauge.data_points.len())
+        } else if let Some(histogram) = data.downcast_ref::<Histogram<T>>() {
+            Some(histogram.data_points.len())
+        } else {
+            data.downcast_ref::<ExponentialHistogram<T>>()
+                .map(|histogram| histogram.data_points.len())
+        }
+    }
+
+    rm.scope_metrics
+        .iter()
+        .flat_map(|scope_metrics| &scope_metrics.metrics)
+        .map(|metric| {
+            let data = metric.data.as_any();
+            count::<u64>(data)
+                .or_else(|| count::<i64>(data))
+                .or_else(|| count::<f64>(data))
+                .unwrap_or(0) as u64
+        })
+        .sum()
+}
+
 impl AggregationSelector for PeriodicReader {
     fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
         self.exporter.aggregation(kind)
@@ -308,7 +448,10 @@ impl AggregationSelector for PeriodicReader {
 
 impl TemporalitySelector for PeriodicReader {
     fn temporality(&self, kind: InstrumentKind) -> Temporality {
-        self.exporter.temporality(kind)
+        match &self.temporality_conversion {
+            Some(conversion) => conversion.aggregation_temporality(kind),
+            None => self.exporter.temporality(kind),
+        }
     }
 }
 
@@ -404,17 +547,141 @@ impl MetricReader for PeriodicReader {
 
         shutdown_result
     }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))?;
+        if inner.is_shutdown {
+            return Err(ShutdownError::AlreadyShutdown);
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        inner
+            .message_sender
+            .try_send(Message::Shutdown(sender))
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))?;
+        // the worker stops once it received the message, even if the caller
+        // stops waiting for it
+        inner.is_shutdown = true;
+        drop(inner); // don't hold lock when blocking on future
+
+        shutdown::block_on_with_timeout(receiver, timeout)?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> crate::ProcessorDiagnostics {
+        self.dropped.diagnostics(false)
+    }
 }
 
 #[cfg(all(test, feature = "testing"))]
 mod tests {
-    use super::PeriodicReader;
+    use super::{
+        PeriodicReader, DEFAULT_INTERVAL, DEFAULT_TIMEOUT, METRIC_EXPORT_INTERVAL_NAME,
+        METRIC_EXPORT_TIMEOUT_NAME,
+    };
     use crate::{
-        metrics::data::ResourceMetrics, metrics::reader::MetricReader, metrics::SdkMeterProvider,
-        runtime, testing::metrics::InMemoryMetricsExporter, Resource,
+        metrics::data::{ResourceMetrics, Sum, Temporality},
+        metrics::exporter::{FlushReason, PushMetricsExporter},
+        metrics::reader::{
+            AggregationSelector, DefaultAggregationSelector, DefaultTemporalitySelector,
+            MetricReader, TemporalitySelector,
+        },
+        metrics::{Aggregation, InstrumentKind, SdkMeterProvider},
+        runtime,
+        testing::metrics::{InMemoryMetricsExporter, InMemoryMetricsExporterBuilder},
+        Resource,
     };
-    use opentelemetry::metrics::MeterProvider;
-    use std::sync::mpsc;
+    use async_trait::async_trait;
+    use opentelemetry::metrics::{MeterProvider, Result};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Debug, Default)]
+    struct ReasonRecordingExporter {
+        reasons: Arc<Mutex<Vec<FlushReason>>>,
+        shutdown_deadline: Arc<Mutex<Option<Duration>>>,
+    }
+
+    impl AggregationSelector for ReasonRecordingExporter {
+        fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+            DefaultAggregationSelector::new().aggregation(kind)
+        }
+    }
+
+    impl TemporalitySelector for ReasonRecordingExporter {
+        fn temporality(&self, kind: InstrumentKind) -> Temporality {
+            DefaultTemporalitySelector::new().temporality(kind)
+        }
+    }
+
+    #[async_trait]
+    impl PushMetricsExporter for ReasonRecordingExporter {
+        async fn export(&self, _metrics: &mut ResourceMetrics) -> Result<()> {
+            Ok(())
+        }
+
+        async fn export_with_reason(
+            &self,
+            _metrics: &mut ResourceMetrics,
+            reason: FlushReason,
+        ) -> Result<()> {
+            self.reasons.lock().unwrap().push(reason);
+            Ok(())
+        }
+
+        async fn force_flush(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn shutdown_with_deadline(&self, deadline: Duration) -> Result<()> {
+            *self.shutdown_deadline.lock().unwrap() = Some(deadline);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn builder_configurable_by_env_vars() {
+        let env_vars = vec![
+            (METRIC_EXPORT_INTERVAL_NAME, Some("2000")),
+            (METRIC_EXPORT_TIMEOUT_NAME, Some("500")),
+        ];
+        temp_env::with_vars(env_vars, || {
+            let builder =
+                PeriodicReader::builder(InMemoryMetricsExporter::default(), runtime::Tokio);
+            assert_eq!(builder.interval, Duration::from_millis(2000));
+            assert_eq!(builder.timeout, Duration::from_millis(500));
+
+            // builder methods override the environment
+            let builder = builder
+                .with_interval(Duration::from_secs(10))
+                .with_timeout(Duration::from_secs(1));
+            assert_eq!(builder.interval, Duration::from_secs(10));
+            assert_eq!(builder.timeout, Duration::from_secs(1));
+        });
+
+        let env_vars = vec![
+            (METRIC_EXPORT_INTERVAL_NAME, Some("0")),
+            (METRIC_EXPORT_TIMEOUT_NAME, Some("invalid")),
+        ];
+        temp_env::with_vars(env_vars, || {
+            let builder =
+                PeriodicReader::builder(InMemoryMetricsExporter::default(), runtime::Tokio);
+            assert_eq!(builder.interval, DEFAULT_INTERVAL);
+            assert_eq!(builder.timeout, DEFAULT_TIMEOUT);
+        });
+    }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn registration_triggers_collection() {
@@ -444,6 +711,87 @@ mod tests {
             .expect("message should be available in channel, indicating a collection occurred");
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn temporality_conversion_exports_deltas() {
+        struct DeltaTemporalitySelector;
+        impl TemporalitySelector for DeltaTemporalitySelector {
+            fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+                Temporality::Delta
+            }
+        }
+
+        let exporter = InMemoryMetricsExporterBuilder::new()
+            .with_temporality_selector(DeltaTemporalitySelector)
+            .build();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio)
+            .with_temporality_conversion(DefaultTemporalitySelector::new())
+            .build();
+        // the SDK aggregates cumulatively
+        assert_eq!(
+            reader.temporality(InstrumentKind::Counter),
+            Temporality::Cumulative
+        );
+
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+        let counter = meter_provider.meter("test").u64_counter("requests").init();
+
+        let mut exported = vec![];
+        for value in [5, 3] {
+            counter.add(value, &[]);
+            meter_provider.force_flush().unwrap();
+            let metrics = exporter.get_finished_metrics().unwrap();
+            let sum = metrics.last().unwrap().scope_metrics[0].metrics[0]
+                .data
+                .as_any()
+                .downcast_ref::<Sum<u64>>()
+                .unwrap();
+            assert_eq!(sum.temporality, Temporality::Delta);
+            exported.push(sum.data_points[0].value);
+        }
+
+        assert_eq!(exported, vec![5, 3]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn temporality_conversion_uses_the_instrument_kind() {
+        struct LowMemoryTemporalitySelector;
+        impl TemporalitySelector for LowMemoryTemporalitySelector {
+            fn temporality(&self, kind: InstrumentKind) -> Temporality {
+                match kind {
+                    InstrumentKind::Counter | InstrumentKind::Histogram => Temporality::Delta,
+                    _ => Temporality::Cumulative,
+                }
+            }
+        }
+
+        let exporter = InMemoryMetricsExporterBuilder::new()
+            .with_temporality_selector(LowMemoryTemporalitySelector)
+            .build();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio)
+            .with_temporality_conversion(DefaultTemporalitySelector::new())
+            .build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+        let meter = meter_provider.meter("test");
+        meter.u64_counter("requests").init().add(1, &[]);
+        let _connections = meter
+            .u64_observable_counter("connections")
+            .with_callback(|observer| observer.observe(1, &[]))
+            .init();
+        meter_provider.force_flush().unwrap();
+
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let temporality = |name: &str| {
+            metrics[0].scope_metrics[0]
+                .metrics
+                .iter()
+                .find(|metric| metric.name == name)
+                .and_then(|metric| metric.data.as_any().downcast_ref::<Sum<u64>>())
+                .map(|sum| sum.temporality)
+        };
+        assert_eq!(temporality("requests"), Some(Temporality::Delta));
+        assert_eq!(temporality("connections"), Some(Temporality::Cumulative));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn unregistered_collect() {
         // Arrange
@@ -460,4 +808,28 @@ mod tests {
         // Assert
         result.expect_err("error expected when reader is not registered");
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn exporter_receives_flush_reason_and_shutdown_deadline() {
+        // Arrange
+        let exporter = ReasonRecordingExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio)
+            .with_timeout(Duration::from_secs(5))
+            .build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+
+        // Act
+        meter_provider.force_flush().unwrap();
+        meter_provider.shutdown().unwrap();
+
+        // Assert
+        assert_eq!(
+            *exporter.reasons.lock().unwrap(),
+            vec![FlushReason::ForceFlush, FlushReason::Shutdown]
+        );
+        assert_eq!(
+            *exporter.shutdown_deadline.lock().unwrap(),
+            Some(Duration::from_secs(5))
+        );
+    }
 }
@@ -3,6 +3,7 @@ use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use opentelemetry::{
@@ -16,14 +17,16 @@ use crate::{
     metrics::{
         aggregation,
         data::{Metric, ResourceMetrics, ScopeMetrics},
+        exemplar::ExemplarFilter,
         instrument::{Instrument, InstrumentId, InstrumentKind, Stream},
         internal,
-        internal::AggregateBuilder,
         internal::Number,
+        internal::{AggregateBuilder, CardinalityLimit, CardinalityOverflows, CollectionTime},
         reader::{AggregationSelector, DefaultAggregationSelector, MetricReader, SdkProducer},
         view::View,
     },
-    Resource,
+    shutdown::{self, ShutdownResult},
+    DroppedTelemetry, Resource, TelemetryBudget,
 };
 
 /// Connects all of the instruments created by a meter provider to a [MetricReader].
@@ -39,6 +42,12 @@ pub struct Pipeline {
     pub(crate) resource: Resource,
     reader: Box<dyn MetricReader>,
     views: Vec<Arc<dyn View>>,
+    exemplar_filter: ExemplarFilter,
+    cardinality_limit: usize,
+    cardinality_overflows: Arc<CardinalityOverflows>,
+    /// The index of the reader among the readers of the provider.
+    reader_index: usize,
+    budget: Option<TelemetryBudget>,
     inner: Box<Mutex<PipelineInner>>,
 }
 
@@ -113,12 +122,19 @@ impl Pipeline {
     fn shutdown(&self) -> Result<()> {
         self.reader.shutdown()
     }
+
+    /// Shut down pipeline within `timeout`
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        self.reader.shutdown_with_timeout(timeout)
+    }
 }
 
 impl SdkProducer for Pipeline {
     /// Returns aggregated metrics from a single collection.
     fn produce(&self, rm: &mut ResourceMetrics) -> Result<()> {
         let inner = self.inner.lock()?;
+        // observations of callbacks and collected data points share a timestamp
+        let _collection_time = CollectionTime::start();
         for cb in &inner.callbacks {
             // TODO consider parallel callbacks.
             cb();
@@ -186,6 +202,16 @@ impl SdkProducer for Pipeline {
 
         Ok(())
     }
+
+    fn instrument_kind(&self, scope: &Scope, name: &str) -> Option<InstrumentKind> {
+        let inner = self.inner.lock().ok()?;
+        inner
+            .aggregations
+            .get(scope)?
+            .iter()
+            .find(|inst| inst.name == name)
+            .map(|inst| inst.kind)
+    }
 }
 
 /// A synchronization point between a [Pipeline] and an instrument's aggregate function.
@@ -193,6 +219,7 @@ struct InstrumentSync {
     name: Cow<'static, str>,
     description: Cow<'static, str>,
     unit: Cow<'static, str>,
+    kind: InstrumentKind,
     comp_agg: Box<dyn internal::ComputeAggregation>,
 }
 
@@ -202,6 +229,7 @@ impl fmt::Debug for InstrumentSync {
             .field("name", &self.name)
             .field("description", &self.description)
             .field("unit", &self.unit)
+            .field("kind", &self.kind)
             .finish()
     }
 }
@@ -277,11 +305,14 @@ where
         // The cache will return the same Aggregator instance. Use stream ids to de duplicate.
         let mut seen = HashSet::new();
         for v in &self.pipeline.views {
-            let stream = match v.match_inst(&inst) {
+            let mut stream = match v.match_inst(&inst) {
                 Some(stream) => stream,
                 None => continue,
             };
             matched = true;
+            if stream.aggregation.is_none() {
+                stream.aggregation = self.advised_aggregation(&inst, kind);
+            }
 
             let id = self.inst_id(kind, &stream);
             if seen.contains(&id) {
@@ -309,12 +340,16 @@ where
         }
 
         // Apply implicit default view if no explicit matched.
+        let aggregation = self.advised_aggregation(&inst, kind);
         let stream = Stream {
             name: inst.name,
             description: inst.description,
             unit: inst.unit,
-            aggregation: None,
+            aggregation,
             allowed_attribute_keys: None,
+            record_min_max: None,
+            cardinality_limit: None,
+            unit_scale: None,
         };
 
         match self.cached_aggregator(&inst.scope, kind, stream) {
@@ -335,6 +370,40 @@ where
         }
     }
 
+    /// The aggregation advised by the author of `inst`, for streams whose
+    /// aggregation is not configured by a view.
+    ///
+    /// An advised aggregation only replaces the default aggregation, readers
+    /// selecting another aggregation or not supporting the advised one keep
+    /// theirs. Advised bucket boundaries only replace those of the explicit
+    /// bucket histogram aggregation selected by the reader.
+    fn advised_aggregation(
+        &self,
+        inst: &Instrument,
+        kind: InstrumentKind,
+    ) -> Option<aggregation::Aggregation> {
+        let mut agg = self.pipeline.reader.aggregation(kind);
+        let default = DefaultAggregationSelector::new().aggregation(kind);
+        if matches!(agg, aggregation::Aggregation::Default) {
+            agg = default.clone();
+        }
+        if let Some(aggregation) = &inst.advised_aggregation {
+            if agg == default && self.pipeline.reader.supports_aggregation(aggregation) {
+                return Some(aggregation.clone());
+            }
+        }
+        let boundaries = inst.advised_boundaries.as_ref()?;
+        match agg {
+            aggregation::Aggregation::ExplicitBucketHistogram { record_min_max, .. } => {
+                Some(aggregation::Aggregation::ExplicitBucketHistogram {
+                    boundaries: boundaries.clone(),
+                    record_min_max,
+                })
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the appropriate aggregate functions for an instrument configuration.
     ///
     /// If the exact instrument has been created within the [Scope], that
@@ -364,6 +433,16 @@ where
             agg = DefaultAggregationSelector::new().aggregation(kind);
         }
 
+        if let Some(enabled) = stream.record_min_max {
+            match &mut agg {
+                aggregation::Aggregation::ExplicitBucketHistogram { record_min_max, .. }
+                | aggregation::Aggregation::Base2ExponentialHistogram { record_min_max, .. } => {
+                    *record_min_max = enabled
+                }
+                _ => {}
+            }
+        }
+
         if let Err(err) = is_aggregator_compatible(&kind, &agg) {
             return Err(MetricsError::Other(format!(
                 "creating aggregator with instrumentKind: {:?}, aggregation {:?}: {:?}",
@@ -371,6 +450,23 @@ where
             )));
         }
 
+        if let Some(scale) = stream.unit_scale {
+            if !agg.supports_unit_conversion() {
+                return Err(MetricsError::Config(format!(
+                    "converting the unit of stream {} is not supported by aggregation {:?}",
+                    stream.name, agg,
+                )));
+            }
+            // the converted values of integer instruments must stay exact
+            if T::from_float(scale).into_float() != scale {
+                global::handle_error(MetricsError::Config(format!(
+                    "converting the unit of stream {} of an integer instrument requires an integer scale, got {}, dropping the stream",
+                    stream.name, scale,
+                )));
+                return Ok(None);
+            }
+        }
+
         let mut id = self.inst_id(kind, &stream);
         // If there is a conflict, the specification says the view should
         // still be applied and a warning should be logged.
@@ -389,11 +485,28 @@ where
                 .clone()
                 .map(|allowed| Arc::new(move |kv: &KeyValue| allowed.contains(&kv.key)) as Arc<_>);
 
-            let b = AggregateBuilder::new(Some(self.pipeline.reader.temporality(kind)), filter);
+            let cardinality_limit = CardinalityLimit::new(
+                stream
+                    .cardinality_limit
+                    .unwrap_or(self.pipeline.cardinality_limit),
+                self.pipeline.cardinality_overflows.counter(
+                    &scope.name,
+                    &stream.name,
+                    self.pipeline.reader_index,
+                ),
+            );
+            let b = AggregateBuilder::new(Some(self.pipeline.reader.temporality(kind)), filter)
+                .with_exemplar_filter(self.pipeline.exemplar_filter)
+                .with_cardinality_limit(cardinality_limit)
+                .with_budget(self.pipeline.budget.clone());
             let (m, ca) = match aggregate_fn(b, &agg, kind) {
                 Ok(Some((m, ca))) => (m, ca),
                 other => return other.map(|fs| fs.map(|(m, _)| m)), // Drop aggregator or error
             };
+            let ca = match stream.unit_scale {
+                Some(scale) => Box::new(internal::convert_units::<T>(ca, scale)),
+                None => ca,
+            };
 
             self.pipeline.add_sync(
                 scope.clone(),
@@ -401,6 +514,7 @@ where
                     name: stream.name,
                     description: stream.description,
                     unit: stream.unit,
+                    kind,
                     comp_agg: ca,
                 },
             );
@@ -477,7 +591,10 @@ fn aggregate_fn<T: Number<T>>(
             kind,
         ),
         Aggregation::Drop => Ok(None),
-        Aggregation::LastValue => Ok(Some(box_val(b.last_value()))),
+        Aggregation::LastValue => match kind {
+            InstrumentKind::ObservableGauge => Ok(Some(box_val(b.precomputed_last_value()))),
+            _ => Ok(Some(box_val(b.last_value()))),
+        },
         Aggregation::Sum => {
             let fns = match kind {
                 InstrumentKind::ObservableCounter => box_val(b.precomputed_sum(true)),
@@ -521,6 +638,8 @@ fn aggregate_fn<T: Number<T>>(
                 record_sum,
             ))))
         }
+        #[cfg(feature = "experimental_metrics_custom_aggregation")]
+        Aggregation::Custom(custom) => Ok(Some(box_val(b.custom(custom.build())))),
     }
 }
 
@@ -582,6 +701,9 @@ fn is_aggregator_compatible(kind: &InstrumentKind, agg: &aggregation::Aggregatio
             }
         }
         Aggregation::Drop => Ok(()),
+        // Custom aggregators accept the measurements of every instrument kind
+        #[cfg(feature = "experimental_metrics_custom_aggregation")]
+        Aggregation::Custom(_) => Ok(()),
     }
 }
 
@@ -594,13 +716,22 @@ impl Pipelines {
         res: Resource,
         readers: Vec<Box<dyn MetricReader>>,
         views: Vec<Arc<dyn View>>,
+        exemplar_filter: ExemplarFilter,
+        cardinality_limit: usize,
+        cardinality_overflows: Arc<CardinalityOverflows>,
+        budget: Option<TelemetryBudget>,
     ) -> Self {
         let mut pipes = Vec::with_capacity(readers.len());
-        for r in readers {
+        for (reader_index, r) in readers.into_iter().enumerate() {
             let p = Arc::new(Pipeline {
                 resource: res.clone(),
                 reader: r,
                 views: views.clone(),
+                exemplar_filter,
+                cardinality_limit,
+                cardinality_overflows: Arc::clone(&cardinality_overflows),
+                reader_index,
+                budget: budget.clone(),
                 inner: Default::default(),
             });
             p.reader.register_pipeline(Arc::downgrade(&p));
@@ -610,29 +741,39 @@ impl Pipelines {
         Pipelines(pipes)
     }
 
+    /// Registers a single instrument callback with every pipeline.
+    ///
+    /// The callback receives the index of the pipeline being collected so that
+    /// observations are only recorded into that pipeline's aggregators.
     pub(crate) fn register_callback<F>(&self, callback: F)
     where
-        F: Fn() + Send + Sync + 'static,
+        F: Fn(usize) + Send + Sync + 'static,
     {
         let cb = Arc::new(callback);
-        for pipe in &self.0 {
-            pipe.add_callback(cb.clone())
+        for (idx, pipe) in self.0.iter().enumerate() {
+            let cb = Arc::clone(&cb);
+            pipe.add_callback(Arc::new(move || cb(idx)))
         }
     }
 
     /// Registers a multi-instrument callback to be run when `produce` is called.
+    ///
+    /// The callback receives the index of the pipeline being collected so that
+    /// observations are only recorded into that pipeline's aggregators.
     pub(crate) fn register_multi_callback<F>(&self, f: F) -> Result<Box<dyn CallbackRegistration>>
     where
-        F: Fn() + Send + Sync + 'static,
+        F: Fn(usize) + Send + Sync + 'static,
     {
         let cb = Arc::new(f);
 
         let fns = self
             .0
             .iter()
-            .map(|pipe| {
+            .enumerate()
+            .map(|(idx, pipe)| {
                 let pipe = Arc::clone(pipe);
-                let unreg = pipe.add_multi_callback(cb.clone())?;
+                let cb = Arc::clone(&cb);
+                let unreg = pipe.add_multi_callback(Arc::new(move || cb(idx)))?;
                 Ok(Box::new(move || unreg(pipe.as_ref())) as _)
             })
             .collect::<Result<_>>()?;
@@ -656,6 +797,27 @@ impl Pipelines {
         }
     }
 
+    /// The data points dropped by the readers of all pipelines.
+    pub(crate) fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.0
+            .iter()
+            .map(|pipeline| pipeline.reader.dropped_telemetry())
+            .sum()
+    }
+
+    /// The diagnostics of the readers of all pipelines.
+    pub(crate) fn reader_diagnostics(&self) -> Vec<crate::ProcessorDiagnostics> {
+        self.0
+            .iter()
+            .map(|pipeline| pipeline.reader.diagnostics())
+            .collect()
+    }
+
+    /// The resource of the pipelines, shared by all of them.
+    pub(crate) fn resource(&self) -> Option<&Resource> {
+        self.0.first().map(|pipeline| &pipeline.resource)
+    }
+
     /// Shut down all pipelines
     pub(crate) fn shutdown(&self) -> Result<()> {
         let mut errs = vec![];
@@ -671,6 +833,16 @@ impl Pipelines {
             Err(MetricsError::Other(format!("{errs:?}")))
         }
     }
+
+    /// Shut down all pipelines, each with the time left until `deadline`
+    pub(crate) fn shutdown_with_deadline(&self, deadline: Instant) -> ShutdownResult {
+        let results: Vec<_> = self
+            .0
+            .iter()
+            .map(|pipeline| pipeline.shutdown_with_timeout(shutdown::remaining(deadline)))
+            .collect();
+        shutdown::combine(results)
+    }
 }
 
 struct Unregister(Vec<Box<dyn FnOnce() -> Result<()> + Send + Sync>>);
@@ -718,11 +890,21 @@ where
 
     /// The measures that must be updated by the instrument defined by key.
     pub(crate) fn measures(&self, id: Instrument) -> Result<Vec<Arc<dyn internal::Measure<T>>>> {
+        self.pipeline_measures(id)
+            .map(|measures| measures.into_iter().flatten().collect())
+    }
+
+    /// The measures that must be updated by the instrument defined by key,
+    /// grouped by the index of the pipeline they belong to.
+    pub(crate) fn pipeline_measures(
+        &self,
+        id: Instrument,
+    ) -> Result<Vec<Vec<Arc<dyn internal::Measure<T>>>>> {
         let (mut measures, mut errs) = (vec![], vec![]);
 
         for inserter in &self.inserters {
             match inserter.instrument(id.clone()) {
-                Ok(ms) => measures.extend(ms),
+                Ok(ms) => measures.push(ms),
                 Err(err) => errs.push(err),
             }
         }
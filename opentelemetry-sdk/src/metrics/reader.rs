@@ -1,8 +1,10 @@
 //! Interfaces for reading and producing metrics
-use std::{fmt, sync::Weak};
+use std::{fmt, sync::Weak, time::Duration};
 
 use opentelemetry::metrics::Result;
 
+use crate::{instrumentation::Scope, DroppedTelemetry, ShutdownError, ShutdownResult};
+
 use super::{
     aggregation::Aggregation,
     data::{ResourceMetrics, ScopeMetrics, Temporality},
@@ -54,12 +56,47 @@ pub trait MetricReader:
     /// After `shutdown` is called, calls to `collect` will perform no operation and
     /// instead will return an error indicating the shutdown state.
     fn shutdown(&self) -> Result<()>;
+
+    /// Shuts down the reader, waiting at most `timeout` for it to complete.
+    ///
+    /// The default implementation ignores the timeout and calls [`shutdown`].
+    /// Readers which wait for their exporter while shutting down should
+    /// override it.
+    ///
+    /// [`shutdown`]: MetricReader::shutdown
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> ShutdownResult {
+        self.shutdown()
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))
+    }
+
+    /// The metric data points dropped by the reader, none by default.
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        DroppedTelemetry::default()
+    }
+
+    /// The last export error of the reader, reported by
+    /// [`PipelineDiagnostics`](crate::PipelineDiagnostics), none by default.
+    fn diagnostics(&self) -> crate::ProcessorDiagnostics {
+        crate::ProcessorDiagnostics::default()
+    }
+
+    /// Whether the reader can export streams aggregated with `aggregation`.
+    ///
+    /// Aggregations advised by the authors of instruments are ignored by the
+    /// readers that do not support them. Every aggregation is supported by
+    /// default.
+    fn supports_aggregation(&self, _aggregation: &Aggregation) -> bool {
+        true
+    }
 }
 
 /// Produces metrics for a [MetricReader].
 pub(crate) trait SdkProducer: fmt::Debug + Send + Sync {
     /// Returns aggregated metrics from a single collection.
     fn produce(&self, rm: &mut ResourceMetrics) -> Result<()>;
+
+    /// Returns the kind of the instrument of the metric `name` of `scope`.
+    fn instrument_kind(&self, scope: &Scope, name: &str) -> Option<InstrumentKind>;
 }
 
 /// Produces metrics for a [MetricReader] from an external source.
@@ -0,0 +1,763 @@
+//! Conversion of the temporality of collected metrics before they are
+//! exported.
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use opentelemetry::{global, metrics::MetricsError};
+
+use super::data::{
+    Aggregation, DataPoint, Histogram, HistogramDataPoint, Metric, ResourceMetrics, ScopeMetrics,
+    Sum, Temporality,
+};
+use super::instrument::InstrumentKind;
+use super::internal::{Number, DEFAULT_CARDINALITY_LIMIT};
+use super::reader::TemporalitySelector;
+use super::AttributeSet;
+use crate::instrumentation::Scope;
+
+/// The temporality metrics are aggregated with by a reader converting them to
+/// the temporality of its exporter.
+#[derive(Clone)]
+pub(crate) struct TemporalityConversion {
+    aggregation_temporality: Arc<dyn TemporalitySelector>,
+}
+
+impl TemporalityConversion {
+    pub(crate) fn new(aggregation_temporality: impl TemporalitySelector + 'static) -> Self {
+        TemporalityConversion {
+            aggregation_temporality: Arc::new(aggregation_temporality),
+        }
+    }
+
+    /// The temporality the SDK aggregates instruments of `kind` with.
+    pub(crate) fn aggregation_temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.aggregation_temporality.temporality(kind)
+    }
+}
+
+impl fmt::Debug for TemporalityConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemporalityConversion").finish()
+    }
+}
+
+/// Identifies a metric by its scope name and name.
+type MetricKey = (Cow<'static, str>, Cow<'static, str>);
+
+/// The maximum number of streams of a metric whose state is kept, the default
+/// cardinality limit of aggregations.
+const MAX_STREAMS_PER_METRIC: usize = DEFAULT_CARDINALITY_LIMIT;
+
+/// Converts sums and histograms between delta and cumulative temporality,
+/// keeping the state of every converted stream between collections.
+///
+/// Streams converted to cumulative are exported by every conversion, with
+/// their last value if they were not collected since the previous one. The
+/// state of streams converted to delta is dropped once they are no longer
+/// collected. The data points of the streams of a metric beyond
+/// [`MAX_STREAMS_PER_METRIC`] are dropped.
+///
+/// Gauges have no temporality and exponential histograms are left unchanged.
+#[derive(Default)]
+pub(crate) struct TemporalityConverter {
+    metrics: HashMap<MetricKey, MetricState>,
+    /// Counts the conversions, to find the streams a conversion did not see.
+    collection: u64,
+}
+
+impl TemporalityConverter {
+    /// Converts the metrics of `rm` to the temporality selected by `target`
+    /// for the kind of their instrument, returned by `instrument_kind`.
+    ///
+    /// The kind of the instruments of metrics from producers is unknown, their
+    /// sums are converted as the metrics of counters and up-down counters.
+    pub(crate) fn convert(
+        &mut self,
+        rm: &mut ResourceMetrics,
+        instrument_kind: &dyn Fn(&Scope, &str) -> Option<InstrumentKind>,
+        target: &dyn Fn(InstrumentKind) -> Temporality,
+    ) {
+        self.collection += 1;
+        let conversion = Conversion {
+            collection: self.collection,
+            time: SystemTime::now(),
+            target,
+        };
+
+        for scope_metrics in &mut rm.scope_metrics {
+            for metric in &mut scope_metrics.metrics {
+                let key = (scope_metrics.scope.name.clone(), metric.name.clone());
+                let state = self.metrics.entry(key).or_insert_with(|| MetricState {
+                    scope: scope_metrics.scope.clone(),
+                    description: metric.description.clone(),
+                    unit: metric.unit.clone(),
+                    kind: instrument_kind(&scope_metrics.scope, &metric.name),
+                    collection: conversion.collection,
+                    streams: None,
+                });
+                state.collection = conversion.collection;
+                state.description.clone_from(&metric.description);
+                state.unit.clone_from(&metric.unit);
+                state.convert(metric.data.as_mut(), &conversion);
+            }
+        }
+
+        // metrics without measurements since the previous collection are not
+        // collected, but their cumulative streams are exported again
+        let mut forgotten = Vec::new();
+        for (key, state) in &mut self.metrics {
+            if state.collection == conversion.collection {
+                continue;
+            }
+            let mut data = match &state.streams {
+                Some(streams) => streams.empty_data(),
+                None => {
+                    forgotten.push(key.clone());
+                    continue;
+                }
+            };
+            if state.convert(data.as_mut(), &conversion) == 0 {
+                forgotten.push(key.clone());
+                continue;
+            }
+
+            let metric = Metric {
+                name: key.1.clone(),
+                description: state.description.clone(),
+                unit: state.unit.clone(),
+                data,
+            };
+            match rm
+                .scope_metrics
+                .iter_mut()
+                .find(|scope_metrics| scope_metrics.scope == state.scope)
+            {
+                Some(scope_metrics) => scope_metrics.metrics.push(metric),
+                None => rm.scope_metrics.push(ScopeMetrics {
+                    scope: state.scope.clone(),
+                    metrics: vec![metric],
+                }),
+            }
+        }
+        for key in forgotten {
+            self.metrics.remove(&key);
+        }
+    }
+}
+
+/// The parameters of a conversion.
+struct Conversion<'a> {
+    collection: u64,
+    /// The time of the points of the cumulative streams exported again.
+    time: SystemTime,
+    target: &'a dyn Fn(InstrumentKind) -> Temporality,
+}
+
+/// The state of a metric between conversions.
+struct MetricState {
+    scope: Scope,
+    description: Cow<'static, str>,
+    unit: Cow<'static, str>,
+    kind: Option<InstrumentKind>,
+    /// The last conversion which collected the metric.
+    collection: u64,
+    /// The streams of the metric, if it is converted.
+    streams: Option<Box<dyn Streams>>,
+}
+
+impl MetricState {
+    /// Converts `data`, returning the number of its data points.
+    fn convert(&mut self, data: &mut dyn Aggregation, conversion: &Conversion<'_>) -> usize {
+        let data = data.as_mut();
+        if let Some(sum) = data.downcast_mut::<Sum<u64>>() {
+            self.convert_sum(sum, conversion)
+        } else if let Some(sum) = data.downcast_mut::<Sum<i64>>() {
+            self.convert_sum(sum, conversion)
+        } else if let Some(sum) = data.downcast_mut::<Sum<f64>>() {
+            self.convert_sum(sum, conversion)
+        } else if let Some(histogram) = data.downcast_mut::<Histogram<u64>>() {
+            self.convert_histogram(histogram, conversion)
+        } else if let Some(histogram) = data.downcast_mut::<Histogram<i64>>() {
+            self.convert_histogram(histogram, conversion)
+        } else if let Some(histogram) = data.downcast_mut::<Histogram<f64>>() {
+            self.convert_histogram(histogram, conversion)
+        } else {
+            0
+        }
+    }
+
+    /// Returns the streams of type `S` converted to `temporality`, replacing
+    /// the streams of another type or temporality.
+    fn streams<S: Streams>(&mut self, temporality: Temporality, new: impl FnOnce() -> S) -> &mut S {
+        let reuse = self.streams.as_mut().map_or(false, |streams| {
+            streams.temporality() == temporality && streams.as_any_mut().is::<S>()
+        });
+        if !reuse {
+            self.streams = Some(Box::new(new()));
+        }
+        self.streams
+            .as_mut()
+            .and_then(|streams| streams.as_any_mut().downcast_mut())
+            .expect("streams of this type were just stored")
+    }
+
+    fn convert_sum<T: Number<T>>(
+        &mut self,
+        sum: &mut Sum<T>,
+        conversion: &Conversion<'_>,
+    ) -> usize {
+        let kind = self.kind.unwrap_or(if sum.is_monotonic {
+            InstrumentKind::Counter
+        } else {
+            InstrumentKind::UpDownCounter
+        });
+        let target = (conversion.target)(kind);
+        if sum.temporality == target {
+            self.streams = None;
+            return sum.data_points.len();
+        }
+
+        let is_monotonic = sum.is_monotonic;
+        let streams = self.streams(target, || SumStreams {
+            temporality: target,
+            is_monotonic,
+            points: StreamPoints::default(),
+        });
+        streams.is_monotonic = is_monotonic;
+        let points = &mut streams.points;
+        sum.data_points.retain_mut(|point| {
+            let key = AttributeSet::from(point.attributes.as_slice());
+            let previous = points.previous(&key, conversion.collection);
+            let current = DataPoint {
+                exemplars: vec![],
+                ..point.clone()
+            };
+            if target == Temporality::Cumulative {
+                if let Some(previous) = previous {
+                    point.start_time = previous.start_time;
+                    point.value += previous.value;
+                }
+                let cumulative = DataPoint {
+                    start_time: point.start_time,
+                    value: point.value,
+                    ..current
+                };
+                points.store(key, conversion.collection, cumulative)
+            } else {
+                match previous {
+                    // a monotonic sum going down was reset, its value is the delta
+                    Some(previous) if !is_monotonic || previous.value <= point.value => {
+                        point.start_time = previous.time;
+                        point.value = point.value - previous.value;
+                    }
+                    _ => {}
+                }
+                points.store(key, conversion.collection, current)
+            }
+        });
+
+        if target == Temporality::Cumulative {
+            sum.data_points
+                .extend(points.quiet(conversion).map(|mut point| {
+                    point.time = Some(conversion.time);
+                    point
+                }));
+        } else {
+            points.forget_quiet(conversion.collection);
+        }
+        sum.temporality = target;
+        sum.data_points.len()
+    }
+
+    fn convert_histogram<T: Number<T>>(
+        &mut self,
+        histogram: &mut Histogram<T>,
+        conversion: &Conversion<'_>,
+    ) -> usize {
+        let target = (conversion.target)(self.kind.unwrap_or(InstrumentKind::Histogram));
+        if histogram.temporality == target {
+            self.streams = None;
+            return histogram.data_points.len();
+        }
+
+        let points = &mut self
+            .streams(target, || HistogramStreams {
+                temporality: target,
+                points: StreamPoints::default(),
+            })
+            .points;
+        histogram.data_points.retain_mut(|point| {
+            let key = AttributeSet::from(point.attributes.as_slice());
+            let previous = points.previous(&key, conversion.collection);
+            let mut current = HistogramDataPoint {
+                exemplars: vec![],
+                ..point.clone()
+            };
+            if let Some(previous) = previous {
+                if target == Temporality::Cumulative {
+                    accumulate(point, &previous);
+                    accumulate(&mut current, &previous);
+                } else {
+                    subtract(point, &previous);
+                }
+            }
+            points.store(key, conversion.collection, current)
+        });
+
+        if target == Temporality::Cumulative {
+            histogram
+                .data_points
+                .extend(points.quiet(conversion).map(|mut point| {
+                    point.time = conversion.time;
+                    point
+                }));
+        } else {
+            points.forget_quiet(conversion.collection);
+        }
+        histogram.temporality = target;
+        histogram.data_points.len()
+    }
+}
+
+/// The state of the streams of a converted metric.
+trait Streams: Send + Sync + 'static {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// The temporality the streams are converted to.
+    fn temporality(&self) -> Temporality;
+
+    /// Returns data without data points to convert, which the streams
+    /// converted to cumulative are exported again in.
+    fn empty_data(&self) -> Box<dyn Aggregation>;
+}
+
+/// The opposite of `temporality`, the temporality of the data converted to it.
+fn source_temporality(temporality: Temporality) -> Temporality {
+    match temporality {
+        Temporality::Cumulative => Temporality::Delta,
+        _ => Temporality::Cumulative,
+    }
+}
+
+struct SumStreams<T> {
+    temporality: Temporality,
+    is_monotonic: bool,
+    points: StreamPoints<DataPoint<T>>,
+}
+
+impl<T: Number<T>> Streams for SumStreams<T> {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.temporality
+    }
+
+    fn empty_data(&self) -> Box<dyn Aggregation> {
+        Box::new(Sum::<T> {
+            data_points: vec![],
+            temporality: source_temporality(self.temporality),
+            is_monotonic: self.is_monotonic,
+        })
+    }
+}
+
+struct HistogramStreams<T> {
+    temporality: Temporality,
+    points: StreamPoints<HistogramDataPoint<T>>,
+}
+
+impl<T: Number<T>> Streams for HistogramStreams<T> {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.temporality
+    }
+
+    fn empty_data(&self) -> Box<dyn Aggregation> {
+        Box::new(Histogram::<T> {
+            data_points: vec![],
+            temporality: source_temporality(self.temporality),
+        })
+    }
+}
+
+/// The point stored for each stream of a metric by the previous conversion
+/// which collected it, and that conversion.
+struct StreamPoints<P> {
+    points: HashMap<AttributeSet, (u64, P)>,
+}
+
+impl<P> Default for StreamPoints<P> {
+    fn default() -> Self {
+        StreamPoints {
+            points: HashMap::new(),
+        }
+    }
+}
+
+impl<P: Clone> StreamPoints<P> {
+    /// Returns the point stored for the stream `key`, which was collected by
+    /// `collection`.
+    fn previous(&mut self, key: &AttributeSet, collection: u64) -> Option<P> {
+        self.points.get_mut(key).map(|(seen, point)| {
+            *seen = collection;
+            point.clone()
+        })
+    }
+
+    /// Stores the point of the stream `key` collected by `collection`,
+    /// returning whether it is kept. Points of new streams are dropped once
+    /// the metric has [`MAX_STREAMS_PER_METRIC`] streams.
+    fn store(&mut self, key: AttributeSet, collection: u64, point: P) -> bool {
+        if self.points.len() >= MAX_STREAMS_PER_METRIC && !self.points.contains_key(&key) {
+            global::handle_error_once(
+                "temporality_converter.stream_limit",
+                MetricsError::Other(format!(
+                    "temporality conversion is limited to {MAX_STREAMS_PER_METRIC} streams \
+                     per metric, the data points of further streams are dropped"
+                )),
+            );
+            return false;
+        }
+        self.points.insert(key, (collection, point));
+        true
+    }
+
+    /// Returns the points of the streams not collected by `collection`.
+    fn quiet<'a>(&'a self, conversion: &Conversion<'_>) -> impl Iterator<Item = P> + 'a {
+        let collection = conversion.collection;
+        self.points
+            .values()
+            .filter(move |(seen, _)| *seen != collection)
+            .map(|(_, point)| point.clone())
+    }
+
+    /// Forgets the streams not collected by `collection`.
+    fn forget_quiet(&mut self, collection: u64) {
+        self.points.retain(|_, (seen, _)| *seen == collection);
+    }
+}
+
+/// Adds the cumulative `previous` point to the delta `point`.
+fn accumulate<T: Number<T>>(point: &mut HistogramDataPoint<T>, previous: &HistogramDataPoint<T>) {
+    if point.bounds != previous.bounds {
+        return;
+    }
+    point.start_time = previous.start_time;
+    point.count += previous.count;
+    point.sum += previous.sum;
+    for (count, previous) in point.bucket_counts.iter_mut().zip(&previous.bucket_counts) {
+        *count += previous;
+    }
+    point.min = match (point.min, previous.min) {
+        (Some(a), Some(b)) if b < a => Some(b),
+        (None, min) | (min, None) => min,
+        (min, _) => min,
+    };
+    point.max = match (point.max, previous.max) {
+        (Some(a), Some(b)) if b > a => Some(b),
+        (None, max) | (max, None) => max,
+        (max, _) => max,
+    };
+}
+
+/// Subtracts the cumulative `previous` point from the cumulative `point`,
+/// unless the stream was reset in between.
+fn subtract<T: Number<T>>(point: &mut HistogramDataPoint<T>, previous: &HistogramDataPoint<T>) {
+    let reset = point.bounds != previous.bounds
+        || point.count < previous.count
+        || point
+            .bucket_counts
+            .iter()
+            .zip(&previous.bucket_counts)
+            .any(|(count, previous)| count < previous);
+    if reset {
+        return;
+    }
+    point.start_time = previous.time;
+    point.count -= previous.count;
+    point.sum = point.sum - previous.sum;
+    for (count, previous) in point.bucket_counts.iter_mut().zip(&previous.bucket_counts) {
+        *count -= previous;
+    }
+    // the extremes of the interval are unknown
+    point.min = None;
+    point.max = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{InstrumentationLibrary, Resource};
+    use opentelemetry::KeyValue;
+
+    fn resource_metrics(data: Box<dyn crate::metrics::data::Aggregation>) -> ResourceMetrics {
+        ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![ScopeMetrics {
+                scope: InstrumentationLibrary::builder("test").build(),
+                metrics: vec![Metric {
+                    name: "requests".into(),
+                    description: "".into(),
+                    unit: "".into(),
+                    data,
+                }],
+            }],
+        }
+    }
+
+    fn no_kind(_: &Scope, _: &str) -> Option<InstrumentKind> {
+        None
+    }
+
+    fn sum(rm: &ResourceMetrics) -> &Sum<u64> {
+        rm.scope_metrics[0].metrics[0]
+            .data
+            .as_any()
+            .downcast_ref()
+            .unwrap()
+    }
+
+    fn point(method: &'static str, value: u64, time: SystemTime) -> DataPoint<u64> {
+        DataPoint {
+            attributes: vec![KeyValue::new("method", method)],
+            start_time: Some(SystemTime::UNIX_EPOCH),
+            time: Some(time),
+            value,
+            exemplars: vec![],
+        }
+    }
+
+    fn counter(temporality: Temporality, value: u64, time: SystemTime) -> ResourceMetrics {
+        counter_points(temporality, vec![point("GET", value, time)])
+    }
+
+    fn counter_points(
+        temporality: Temporality,
+        data_points: Vec<DataPoint<u64>>,
+    ) -> ResourceMetrics {
+        resource_metrics(Box::new(Sum {
+            data_points,
+            temporality,
+            is_monotonic: true,
+        }))
+    }
+
+    #[test]
+    fn cumulative_sum_to_delta() {
+        let mut converter = TemporalityConverter::default();
+        let to_delta = |_| Temporality::Delta;
+        let t1 = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        let t2 = SystemTime::UNIX_EPOCH + Duration::from_secs(2);
+
+        let mut rm = counter(Temporality::Cumulative, 5, t1);
+        converter.convert(&mut rm, &no_kind, &to_delta);
+        assert_eq!(sum(&rm).temporality, Temporality::Delta);
+        assert_eq!(sum(&rm).data_points[0].value, 5);
+
+        let mut rm = counter(Temporality::Cumulative, 8, t2);
+        converter.convert(&mut rm, &no_kind, &to_delta);
+        assert_eq!(sum(&rm).data_points[0].value, 3);
+        assert_eq!(sum(&rm).data_points[0].start_time, Some(t1));
+
+        // a reset starts a new delta
+        let mut rm = counter(Temporality::Cumulative, 2, t2);
+        converter.convert(&mut rm, &no_kind, &to_delta);
+        assert_eq!(sum(&rm).data_points[0].value, 2);
+    }
+
+    #[test]
+    fn delta_sum_to_cumulative() {
+        let mut converter = TemporalityConverter::default();
+        let to_cumulative = |_| Temporality::Cumulative;
+        let now = SystemTime::now();
+
+        for (delta, expected) in [(5, 5), (3, 8), (0, 8)] {
+            let mut rm = counter(Temporality::Delta, delta, now);
+            converter.convert(&mut rm, &no_kind, &to_cumulative);
+            assert_eq!(sum(&rm).temporality, Temporality::Cumulative);
+            assert_eq!(sum(&rm).data_points[0].value, expected);
+            assert_eq!(
+                sum(&rm).data_points[0].start_time,
+                Some(SystemTime::UNIX_EPOCH)
+            );
+        }
+    }
+
+    #[test]
+    fn histogram_to_delta_and_back() {
+        let point = |count: u64, bucket_counts: Vec<u64>, sum: f64| HistogramDataPoint {
+            attributes: vec![],
+            start_time: SystemTime::UNIX_EPOCH,
+            time: SystemTime::now(),
+            count,
+            bounds: vec![10.0],
+            bucket_counts,
+            min: Some(1.0),
+            max: Some(20.0),
+            sum,
+            exemplars: vec![],
+        };
+        let histogram = |temporality, point| {
+            resource_metrics(Box::new(Histogram {
+                data_points: vec![point],
+                temporality,
+            }))
+        };
+        let data_point = |rm: &ResourceMetrics| {
+            rm.scope_metrics[0].metrics[0]
+                .data
+                .as_any()
+                .downcast_ref::<Histogram<f64>>()
+                .unwrap()
+                .data_points[0]
+                .clone()
+        };
+
+        let mut to_delta = TemporalityConverter::default();
+        let mut to_cumulative = TemporalityConverter::default();
+        let mut cumulative = histogram(Temporality::Cumulative, point(2, vec![1, 1], 21.0));
+        to_delta.convert(&mut cumulative, &no_kind, &|_| Temporality::Delta);
+        to_cumulative.convert(&mut cumulative, &no_kind, &|_| Temporality::Cumulative);
+        assert_eq!(data_point(&cumulative).count, 2);
+
+        let mut cumulative = histogram(Temporality::Cumulative, point(5, vec![3, 2], 40.0));
+        to_delta.convert(&mut cumulative, &no_kind, &|_| Temporality::Delta);
+        let delta = data_point(&cumulative);
+        assert_eq!(delta.count, 3);
+        assert_eq!(delta.bucket_counts, vec![2, 1]);
+        assert_eq!(delta.sum, 19.0);
+        assert_eq!(delta.min, None);
+
+        to_cumulative.convert(&mut cumulative, &no_kind, &|_| Temporality::Cumulative);
+        let cumulative = data_point(&cumulative);
+        assert_eq!(cumulative.count, 5);
+        assert_eq!(cumulative.bucket_counts, vec![3, 2]);
+        assert_eq!(cumulative.sum, 40.0);
+    }
+
+    #[test]
+    fn quiet_cumulative_streams_are_exported_again() {
+        let mut converter = TemporalityConverter::default();
+        let to_cumulative = |_| Temporality::Cumulative;
+        let now = SystemTime::now();
+
+        let mut rm = counter_points(
+            Temporality::Delta,
+            vec![point("GET", 5, now), point("POST", 2, now)],
+        );
+        converter.convert(&mut rm, &no_kind, &to_cumulative);
+
+        // only GET was measured since
+        let mut rm = counter(Temporality::Delta, 1, now);
+        converter.convert(&mut rm, &no_kind, &to_cumulative);
+        let mut values = sum(&rm)
+            .data_points
+            .iter()
+            .map(|point| (point.attributes[0].value.as_str().into_owned(), point.value))
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec![("GET".into(), 6), ("POST".into(), 2)]);
+
+        // the delta aggregation does not collect metrics without measurements
+        let mut rm = resource_metrics(Box::new(Sum::<u64> {
+            data_points: vec![],
+            temporality: Temporality::Delta,
+            is_monotonic: true,
+        }));
+        rm.scope_metrics.clear();
+        converter.convert(&mut rm, &no_kind, &to_cumulative);
+        assert_eq!(rm.scope_metrics[0].scope.name, "test");
+        assert_eq!(rm.scope_metrics[0].metrics[0].name, "requests");
+        assert_eq!(sum(&rm).temporality, Temporality::Cumulative);
+        assert_eq!(sum(&rm).data_points.len(), 2);
+    }
+
+    #[test]
+    fn delta_streams_are_forgotten_once_no_longer_collected() {
+        let mut converter = TemporalityConverter::default();
+        let to_delta = |_| Temporality::Delta;
+        let now = SystemTime::now();
+
+        let mut rm = counter_points(
+            Temporality::Cumulative,
+            vec![point("GET", 5, now), point("POST", 2, now)],
+        );
+        converter.convert(&mut rm, &no_kind, &to_delta);
+        let mut rm = counter(Temporality::Cumulative, 6, now);
+        converter.convert(&mut rm, &no_kind, &to_delta);
+        assert_eq!(sum(&rm).data_points.len(), 1);
+
+        let streams = converter.metrics.values_mut().next().unwrap();
+        let streams = streams
+            .streams
+            .as_mut()
+            .unwrap()
+            .as_any_mut()
+            .downcast_mut::<SumStreams<u64>>()
+            .unwrap();
+        assert_eq!(streams.points.points.len(), 1);
+
+        converter.convert(
+            &mut ResourceMetrics {
+                resource: Resource::empty(),
+                scope_metrics: vec![],
+            },
+            &no_kind,
+            &to_delta,
+        );
+        assert!(converter.metrics.is_empty());
+    }
+
+    #[test]
+    fn streams_beyond_the_limit_are_dropped() {
+        let mut converter = TemporalityConverter::default();
+        let now = SystemTime::now();
+        let data_points = (0..MAX_STREAMS_PER_METRIC as u64 + 1)
+            .map(|i| DataPoint {
+                attributes: vec![KeyValue::new("id", i as i64)],
+                ..point("GET", i, now)
+            })
+            .collect();
+
+        let mut rm = counter_points(Temporality::Delta, data_points);
+        converter.convert(&mut rm, &no_kind, &|_| Temporality::Cumulative);
+        assert_eq!(sum(&rm).data_points.len(), MAX_STREAMS_PER_METRIC);
+    }
+
+    #[test]
+    fn sums_are_converted_for_the_kind_of_their_instrument() {
+        let mut converter = TemporalityConverter::default();
+        // e.g. the low memory preference of OTLP exporters
+        let target = |kind| match kind {
+            InstrumentKind::ObservableCounter => Temporality::Cumulative,
+            _ => Temporality::Delta,
+        };
+
+        let mut rm = counter(Temporality::Cumulative, 5, SystemTime::now());
+        converter.convert(
+            &mut rm,
+            &|_, _| Some(InstrumentKind::ObservableCounter),
+            &target,
+        );
+        assert_eq!(sum(&rm).temporality, Temporality::Cumulative);
+
+        let mut rm = counter(Temporality::Cumulative, 5, SystemTime::now());
+        TemporalityConverter::default().convert(
+            &mut rm,
+            &|_, _| Some(InstrumentKind::Counter),
+            &target,
+        );
+        assert_eq!(sum(&rm).temporality, Temporality::Delta);
+    }
+}
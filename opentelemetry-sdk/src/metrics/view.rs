@@ -1,5 +1,7 @@
-use super::instrument::{Instrument, Stream};
-use glob::Pattern;
+use super::{
+    instrument::{Instrument, Stream},
+    Aggregation,
+};
 use opentelemetry::{
     global,
     metrics::{MetricsError, Result},
@@ -80,7 +82,8 @@ impl View for Box<dyn View> {
 /// The [Instrument::name] field of criteria supports wildcard pattern matching.
 /// The wildcard `*` is recognized as matching zero or more characters, and `?`
 /// is recognized as matching exactly one character. For example, a pattern of
-/// `*` will match all instrument names.
+/// `*` will match all instrument names. All other characters match themselves,
+/// and names are compared case-insensitively, as instrument names are.
 ///
 /// The [Stream] mask only applies updates for non-empty fields. By default, the
 /// [Instrument] the [View] matches against will be use for the name,
@@ -118,12 +121,8 @@ pub fn new_view(criteria: Instrument, mask: Stream) -> Result<Box<dyn View>> {
             return Ok(Box::new(empty_view));
         }
 
-        let pattern = criteria.name.clone();
-        let glob_pattern =
-            Pattern::new(&pattern).map_err(|e| MetricsError::Config(e.to_string()))?;
-
         Box::new(move |i| {
-            glob_pattern.matches(&i.name)
+            wildcard_match(&criteria.name, &i.name)
                 && criteria.matches_description(i)
                 && criteria.matches_kind(i)
                 && criteria.matches_unit(i)
@@ -133,6 +132,19 @@ pub fn new_view(criteria: Instrument, mask: Stream) -> Result<Box<dyn View>> {
         Box::new(move |i| criteria.matches(i))
     };
 
+    if let Some(scale) = mask.unit_scale {
+        let supported = mask
+            .aggregation
+            .as_ref()
+            .map_or(true, Aggregation::supports_unit_conversion);
+        if !scale.is_finite() || scale <= 0.0 || !supported {
+            global::handle_error(MetricsError::Config(format!(
+                "invalid unit conversion, dropping view. criteria: {err_msg_criteria:?}, mask: {mask:?}"
+            )));
+            return Ok(Box::new(empty_view));
+        }
+    }
+
     let mut agg = None;
     if let Some(ma) = &mask.aggregation {
         match ma.validate() {
@@ -167,6 +179,9 @@ pub fn new_view(criteria: Instrument, mask: Stream) -> Result<Box<dyn View>> {
                 },
                 aggregation: agg.clone(),
                 allowed_attribute_keys: mask.allowed_attribute_keys.clone(),
+                record_min_max: mask.record_min_max,
+                cardinality_limit: mask.cardinality_limit,
+                unit_scale: mask.unit_scale,
             })
         } else {
             None
@@ -174,9 +189,44 @@ pub fn new_view(criteria: Instrument, mask: Stream) -> Result<Box<dyn View>> {
     }))
 }
 
+/// Returns whether `name` matches `pattern`, where `*` matches zero or more
+/// characters and `?` exactly one, ignoring ASCII case.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern and of the name when reached
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || c.eq_ignore_ascii_case(&name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // let the last `*` match one more character
+                Some((star, start)) => {
+                    p = star + 1;
+                    n = start + 1;
+                    backtrack = Some((star, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use opentelemetry::Key;
     #[test]
     fn test_new_view_matching_all() {
         let criteria = Instrument::new().name("*");
@@ -252,4 +302,102 @@ mod tests {
             "Expected not to match instrument with test_? pattern"
         );
     }
+
+    #[test]
+    fn test_wildcard_match() {
+        for (pattern, name, expected) in [
+            ("*", "", true),
+            ("*", "anything", true),
+            ("http.*.duration", "http.server.duration", true),
+            ("http.*.duration", "http.server.request.duration", true),
+            ("http.*.duration", "http.server.duration.max", false),
+            ("a*b*c", "aXbYbZc", true),
+            ("a*b*c", "aXbYbZ", false),
+            ("?", "", false),
+            ("??", "ab", true),
+            ("HTTP.*", "http.server", true),
+            // only `*` and `?` are wildcards
+            ("[ab]", "a", false),
+            ("[ab]", "[AB]", true),
+        ] {
+            assert_eq!(
+                wildcard_match(pattern, name),
+                expected,
+                "pattern {pattern:?}, name {name:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_view_case_insensitive_name() {
+        let view = new_view(Instrument::new().name("Requests"), Stream::new())
+            .expect("Expected to create a new view");
+
+        assert!(view
+            .match_inst(&Instrument::new().name("requests"))
+            .is_some());
+    }
+
+    #[test]
+    fn test_new_view_rename_and_filter_attributes() {
+        let criteria = Instrument::new().name("requests");
+        let mask = Stream::new()
+            .name("http.requests")
+            .allowed_attribute_keys([Key::new("http.method")]);
+
+        let view = new_view(criteria, mask).expect("Expected to create a new view");
+
+        let stream = view
+            .match_inst(&Instrument::new().name("requests").unit("1"))
+            .expect("Expected to match instrument");
+        assert_eq!(stream.name, "http.requests");
+        assert_eq!(stream.unit, "1");
+        assert_eq!(
+            stream.allowed_attribute_keys.as_deref(),
+            Some(&[Key::new("http.method")].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn test_new_view_wildcard_rename_is_dropped() {
+        let view = new_view(
+            Instrument::new().name("requests_*"),
+            Stream::new().name("requests"),
+        )
+        .expect("Expected to create a new view");
+
+        assert!(view
+            .match_inst(&Instrument::new().name("requests_total"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_new_view_invalid_unit_conversion_is_dropped() {
+        let view = new_view(
+            Instrument::new().name("memory"),
+            Stream::new().unit_conversion("MiB", 1.0 / 1048576.0),
+        )
+        .expect("Expected to create a new view");
+        let stream = view
+            .match_inst(&Instrument::new().name("memory").unit("By"))
+            .expect("Expected to match instrument");
+        assert_eq!(stream.unit, "MiB");
+        assert_eq!(stream.unit_scale, Some(1.0 / 1048576.0));
+
+        for mask in [
+            Stream::new().unit_conversion("MiB", 0.0),
+            Stream::new().unit_conversion("MiB", f64::NAN),
+            Stream::new()
+                .unit_conversion("MiB", 1.0 / 1048576.0)
+                .aggregation(Aggregation::Base2ExponentialHistogram {
+                    max_size: 160,
+                    max_scale: 20,
+                    record_min_max: true,
+                }),
+        ] {
+            let view = new_view(Instrument::new().name("memory"), mask)
+                .expect("Expected to create a new view");
+            assert!(view.match_inst(&Instrument::new().name("memory")).is_none());
+        }
+    }
 }
@@ -0,0 +1,554 @@
+//! # B3 Propagator
+//!
+//! Propagates `SpanContext`s in the [B3] format used by Zipkin, either in the
+//! single `b3` header or in the multiple `X-B3-*` headers.
+//!
+//! [B3]: https://github.com/openzipkin/b3-propagation
+use once_cell::sync::Lazy;
+use opentelemetry::{
+    propagation::{text_map_propagator::FieldIter, Extractor, Injector, TextMapPropagator},
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    Context,
+};
+
+const B3_SINGLE_HEADER: &str = "b3";
+// Header names are lower case as gRPC requires, HTTP headers are case
+// insensitive.
+const B3_TRACE_ID_HEADER: &str = "x-b3-traceid";
+const B3_SPAN_ID_HEADER: &str = "x-b3-spanid";
+const B3_SAMPLED_HEADER: &str = "x-b3-sampled";
+const B3_FLAGS_HEADER: &str = "x-b3-flags";
+const B3_PARENT_SPAN_ID_HEADER: &str = "x-b3-parentspanid";
+
+static B3_SINGLE_FIELDS: Lazy<[String; 1]> = Lazy::new(|| [B3_SINGLE_HEADER.to_owned()]);
+static B3_MULTI_FIELDS: Lazy<[String; 4]> = Lazy::new(|| {
+    [
+        B3_TRACE_ID_HEADER.to_owned(),
+        B3_SPAN_ID_HEADER.to_owned(),
+        B3_SAMPLED_HEADER.to_owned(),
+        B3_FLAGS_HEADER.to_owned(),
+    ]
+});
+static B3_SINGLE_AND_MULTI_FIELDS: Lazy<[String; 5]> = Lazy::new(|| {
+    [
+        B3_SINGLE_HEADER.to_owned(),
+        B3_TRACE_ID_HEADER.to_owned(),
+        B3_SPAN_ID_HEADER.to_owned(),
+        B3_SAMPLED_HEADER.to_owned(),
+        B3_FLAGS_HEADER.to_owned(),
+    ]
+});
+
+/// The headers a [`B3Propagator`] injects.
+///
+/// Both encodings are always extracted, the single header takes precedence
+/// if both are present.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum B3Encoding {
+    /// The single `b3: {trace_id}-{span_id}-{sampling_state}` header.
+    #[default]
+    SingleHeader,
+    /// The `X-B3-TraceId`, `X-B3-SpanId`, `X-B3-Sampled` and `X-B3-Flags`
+    /// headers.
+    MultipleHeader,
+    /// Both the single and the multiple headers.
+    SingleAndMultiHeader,
+}
+
+impl B3Encoding {
+    fn single(self) -> bool {
+        matches!(
+            self,
+            B3Encoding::SingleHeader | B3Encoding::SingleAndMultiHeader
+        )
+    }
+
+    fn multi(self) -> bool {
+        matches!(
+            self,
+            B3Encoding::MultipleHeader | B3Encoding::SingleAndMultiHeader
+        )
+    }
+}
+
+/// Marks a context extracted from a B3 debug sampling state, so that the
+/// debug flag is propagated downstream.
+#[derive(Clone, Copy, Debug)]
+struct B3Debug;
+
+/// The sampling state of a B3 header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SamplingState {
+    Deny,
+    Accept,
+    Debug,
+}
+
+/// Propagates `SpanContext`s in [B3] format.
+///
+/// With the default [`B3Encoding::SingleHeader`] encoding the context is
+/// injected in the `b3` header:
+///
+/// `b3: 80f198ee56343ba864fe8b2a57d3eff7-e457b5a2e4d86bd1-1`
+///
+/// With [`B3Encoding::MultipleHeader`] it is injected in the `X-B3-TraceId`,
+/// `X-B3-SpanId` and `X-B3-Sampled` headers, or `X-B3-Flags` for debug
+/// contexts. Both encodings are extracted.
+///
+/// The debug sampling state (`d` or `X-B3-Flags: 1`) is extracted as sampled
+/// and kept in the context so that it is injected again. A missing sampling
+/// state is extracted as not sampled.
+///
+/// [B3]: https://github.com/openzipkin/b3-propagation
+#[derive(Clone, Debug, Default)]
+pub struct B3Propagator {
+    encoding: B3Encoding,
+}
+
+impl B3Propagator {
+    /// Create a new `B3Propagator` injecting the single `b3` header.
+    pub fn new() -> Self {
+        B3Propagator::default()
+    }
+
+    /// Create a new `B3Propagator` injecting the headers of `encoding`.
+    pub fn with_encoding(encoding: B3Encoding) -> Self {
+        B3Propagator { encoding }
+    }
+
+    /// Extract a span context and sampling state from the single `b3` header.
+    fn extract_single_header(
+        &self,
+        extractor: &dyn Extractor,
+    ) -> Result<(SpanContext, SamplingState), ()> {
+        let header_value = extractor.get(B3_SINGLE_HEADER).ok_or(())?.trim();
+        let parts = header_value.split('-').collect::<Vec<&str>>();
+        // A lone sampling state carries no span context
+        if parts.len() < 2 || parts.len() > 4 {
+            return Err(());
+        }
+
+        let trace_id = extract_trace_id(parts[0])?;
+        let span_id = extract_span_id(parts[1])?;
+        let sampling_state = match parts.get(2) {
+            Some(state) => extract_sampling_state(state)?,
+            None => SamplingState::Deny,
+        };
+        // The parent span id is not propagated but must be valid
+        if let Some(parent_span_id) = parts.get(3) {
+            extract_span_id(parent_span_id)?;
+        }
+
+        Ok((
+            span_context(trace_id, span_id, sampling_state)?,
+            sampling_state,
+        ))
+    }
+
+    /// Extract a span context and sampling state from the `X-B3-*` headers.
+    fn extract_multi_header(
+        &self,
+        extractor: &dyn Extractor,
+    ) -> Result<(SpanContext, SamplingState), ()> {
+        let trace_id = extract_trace_id(extractor.get(B3_TRACE_ID_HEADER).ok_or(())?)?;
+        let span_id = extract_span_id(extractor.get(B3_SPAN_ID_HEADER).ok_or(())?)?;
+        if let Some(parent_span_id) = extractor.get(B3_PARENT_SPAN_ID_HEADER) {
+            extract_span_id(parent_span_id)?;
+        }
+
+        // Debug implies sampled, X-B3-Sampled should not be sent with it
+        let sampling_state = if extractor.get(B3_FLAGS_HEADER).map(str::trim) == Some("1") {
+            SamplingState::Debug
+        } else {
+            match extractor.get(B3_SAMPLED_HEADER).map(str::trim) {
+                // "true" and "false" are sent by legacy implementations
+                Some("1") | Some("true") => SamplingState::Accept,
+                Some("0") | Some("false") | None => SamplingState::Deny,
+                Some(_) => return Err(()),
+            }
+        };
+
+        Ok((
+            span_context(trace_id, span_id, sampling_state)?,
+            sampling_state,
+        ))
+    }
+}
+
+/// Extract a 64 or 128 bit trace id from lower case hex.
+fn extract_trace_id(trace_id: &str) -> Result<TraceId, ()> {
+    let trace_id = trace_id.trim();
+    if (trace_id.len() != 16 && trace_id.len() != 32) || !is_lower_hex(trace_id) {
+        return Err(());
+    }
+    TraceId::from_hex(trace_id).map_err(|_| ())
+}
+
+/// Extract a span id from lower case hex.
+fn extract_span_id(span_id: &str) -> Result<SpanId, ()> {
+    let span_id = span_id.trim();
+    if span_id.len() != 16 || !is_lower_hex(span_id) {
+        return Err(());
+    }
+    SpanId::from_hex(span_id).map_err(|_| ())
+}
+
+fn is_lower_hex(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+fn extract_sampling_state(state: &str) -> Result<SamplingState, ()> {
+    match state {
+        "0" => Ok(SamplingState::Deny),
+        "1" => Ok(SamplingState::Accept),
+        "d" => Ok(SamplingState::Debug),
+        _ => Err(()),
+    }
+}
+
+fn span_context(
+    trace_id: TraceId,
+    span_id: SpanId,
+    sampling_state: SamplingState,
+) -> Result<SpanContext, ()> {
+    let trace_flags = match sampling_state {
+        SamplingState::Deny => TraceFlags::default(),
+        SamplingState::Accept | SamplingState::Debug => TraceFlags::SAMPLED,
+    };
+    let span_context =
+        SpanContext::new(trace_id, span_id, trace_flags, true, TraceState::default());
+    if span_context.is_valid() {
+        Ok(span_context)
+    } else {
+        Err(())
+    }
+}
+
+impl TextMapPropagator for B3Propagator {
+    /// Properly encodes the values of the `Context`'s `SpanContext` and injects
+    /// them into the `Injector`.
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+
+        let sampling_state = if cx.get::<B3Debug>().is_some() {
+            SamplingState::Debug
+        } else if span_context.is_sampled() {
+            SamplingState::Accept
+        } else {
+            SamplingState::Deny
+        };
+
+        if self.encoding.single() {
+            let state = match sampling_state {
+                SamplingState::Deny => "0",
+                SamplingState::Accept => "1",
+                SamplingState::Debug => "d",
+            };
+            injector.set(
+                B3_SINGLE_HEADER,
+                format!(
+                    "{}-{}-{}",
+                    span_context.trace_id(),
+                    span_context.span_id(),
+                    state
+                ),
+            );
+        }
+        if self.encoding.multi() {
+            injector.set(B3_TRACE_ID_HEADER, span_context.trace_id().to_string());
+            injector.set(B3_SPAN_ID_HEADER, span_context.span_id().to_string());
+            match sampling_state {
+                SamplingState::Debug => injector.set(B3_FLAGS_HEADER, "1".to_string()),
+                SamplingState::Accept => injector.set(B3_SAMPLED_HEADER, "1".to_string()),
+                SamplingState::Deny => injector.set(B3_SAMPLED_HEADER, "0".to_string()),
+            }
+        }
+    }
+
+    /// Retrieves encoded data using the provided `Extractor`. If no data for this
+    /// format was retrieved OR if the retrieved data is invalid, then the current
+    /// `Context` is returned.
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let extracted = self
+            .extract_single_header(extractor)
+            .or_else(|_| self.extract_multi_header(extractor));
+
+        match extracted {
+            Ok((span_context, SamplingState::Debug)) => cx
+                .with_remote_span_context(span_context)
+                .with_value(B3Debug),
+            Ok((span_context, _)) => cx.with_remote_span_context(span_context),
+            Err(_) => cx.clone(),
+        }
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        let fields = match self.encoding {
+            B3Encoding::SingleHeader => B3_SINGLE_FIELDS.as_ref(),
+            B3Encoding::MultipleHeader => B3_MULTI_FIELDS.as_ref(),
+            B3Encoding::SingleAndMultiHeader => B3_SINGLE_AND_MULTI_FIELDS.as_ref(),
+        };
+        FieldIter::new(fields)
+    }
+}
+
+#[cfg(all(test, feature = "testing", feature = "trace"))]
+mod tests {
+    use super::*;
+    use crate::propagation::TraceContextPropagator;
+    use crate::testing::trace::TestSpan;
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const SPAN_ID: &str = "00f067aa0ba902b7";
+
+    fn span_context(trace_flags: TraceFlags) -> SpanContext {
+        SpanContext::new(
+            TraceId::from_hex(TRACE_ID).unwrap(),
+            SpanId::from_hex(SPAN_ID).unwrap(),
+            trace_flags,
+            true,
+            TraceState::default(),
+        )
+    }
+
+    fn extract(propagator: &B3Propagator, headers: &[(&str, &str)]) -> Context {
+        let extractor: HashMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        propagator.extract(&extractor)
+    }
+
+    #[test]
+    fn extract_single_header() {
+        let propagator = B3Propagator::new();
+        let not_sampled = span_context(TraceFlags::default());
+        let sampled = span_context(TraceFlags::SAMPLED);
+
+        #[rustfmt::skip]
+        let cases = [
+            ("4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7", &not_sampled),
+            ("4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-0", &not_sampled),
+            ("4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1", &sampled),
+            ("4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-d", &sampled),
+            ("4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1-00000000000000cd", &sampled),
+        ];
+        for (header, expected) in cases {
+            let cx = extract(&propagator, &[("b3", header)]);
+            assert_eq!(cx.span().span_context(), expected, "{header}");
+        }
+
+        let cx = extract(
+            &propagator,
+            &[("b3", "a3ce929d0e0e4736-00f067aa0ba902b7-1")],
+        );
+        assert_eq!(
+            cx.span().span_context().trace_id(),
+            TraceId::from_hex("0000000000000000a3ce929d0e0e4736").unwrap()
+        );
+    }
+
+    #[test]
+    fn extract_invalid_single_header() {
+        let propagator = B3Propagator::new();
+        for header in [
+            "0",
+            "1",
+            "-",
+            "4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-1",
+            "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-x",
+            "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1-xyz",
+            "00000000000000000000000000000000-00f067aa0ba902b7-1",
+            "4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-1",
+        ] {
+            let cx = extract(&propagator, &[("b3", header)]);
+            assert!(!cx.has_active_span(), "{header}");
+        }
+    }
+
+    #[test]
+    fn extract_multi_header() {
+        let propagator = B3Propagator::with_encoding(B3Encoding::MultipleHeader);
+        let not_sampled = span_context(TraceFlags::default());
+        let sampled = span_context(TraceFlags::SAMPLED);
+
+        let ids = [("x-b3-traceid", TRACE_ID), ("x-b3-spanid", SPAN_ID)];
+        #[rustfmt::skip]
+        let cases = [
+            (vec![], &not_sampled),
+            (vec![("x-b3-sampled", "0")], &not_sampled),
+            (vec![("x-b3-sampled", "false")], &not_sampled),
+            (vec![("x-b3-sampled", "1")], &sampled),
+            (vec![("x-b3-sampled", "true")], &sampled),
+            (vec![("x-b3-flags", "1")], &sampled),
+            (vec![("x-b3-flags", "1"), ("x-b3-sampled", "0")], &sampled),
+            (vec![("x-b3-parentspanid", "00000000000000cd"), ("x-b3-sampled", "1")], &sampled),
+        ];
+        for (headers, expected) in cases {
+            let headers = ids.iter().copied().chain(headers).collect::<Vec<_>>();
+            let cx = extract(&propagator, &headers);
+            assert_eq!(cx.span().span_context(), expected, "{headers:?}");
+        }
+
+        let cx = extract(&propagator, &[("x-b3-traceid", TRACE_ID)]);
+        assert!(!cx.has_active_span());
+        let cx = extract(
+            &propagator,
+            &[
+                ("x-b3-traceid", TRACE_ID),
+                ("x-b3-spanid", SPAN_ID),
+                ("x-b3-sampled", "yes"),
+            ],
+        );
+        assert!(!cx.has_active_span());
+    }
+
+    #[test]
+    fn extract_prefers_single_header() {
+        let cx = extract(
+            &B3Propagator::new(),
+            &[
+                ("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1"),
+                ("x-b3-traceid", "a3ce929d0e0e4736a3ce929d0e0e4736"),
+                ("x-b3-spanid", SPAN_ID),
+                ("x-b3-sampled", "0"),
+            ],
+        );
+        assert_eq!(cx.span().span_context(), &span_context(TraceFlags::SAMPLED));
+    }
+
+    #[test]
+    fn inject() {
+        for (encoding, trace_flags, expected) in [
+            (
+                B3Encoding::SingleHeader,
+                TraceFlags::SAMPLED,
+                vec![("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1")],
+            ),
+            (
+                B3Encoding::SingleHeader,
+                TraceFlags::default(),
+                vec![("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-0")],
+            ),
+            (
+                B3Encoding::MultipleHeader,
+                TraceFlags::SAMPLED,
+                vec![
+                    ("x-b3-traceid", TRACE_ID),
+                    ("x-b3-spanid", SPAN_ID),
+                    ("x-b3-sampled", "1"),
+                ],
+            ),
+            (
+                B3Encoding::SingleAndMultiHeader,
+                TraceFlags::default(),
+                vec![
+                    ("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-0"),
+                    ("x-b3-traceid", TRACE_ID),
+                    ("x-b3-spanid", SPAN_ID),
+                    ("x-b3-sampled", "0"),
+                ],
+            ),
+        ] {
+            let propagator = B3Propagator::with_encoding(encoding);
+            let cx = Context::current_with_span(TestSpan(span_context(trace_flags)));
+            let mut injector = HashMap::new();
+            propagator.inject_context(&cx, &mut injector);
+
+            let expected: HashMap<String, String> = expected
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            assert_eq!(injector, expected, "{encoding:?}");
+        }
+
+        let mut injector = HashMap::new();
+        B3Propagator::new().inject_context(&Context::new(), &mut injector);
+        assert!(injector.is_empty());
+    }
+
+    #[test]
+    fn debug_flag_round_trip() {
+        let single = B3Propagator::new();
+        let multi = B3Propagator::with_encoding(B3Encoding::MultipleHeader);
+
+        let cx = extract(
+            &single,
+            &[("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-d")],
+        );
+        let mut injector = HashMap::new();
+        multi.inject_context(&cx, &mut injector);
+        assert_eq!(injector.get("x-b3-flags"), Some(&"1".to_string()));
+        assert_eq!(injector.get("x-b3-sampled"), None);
+
+        let cx = extract(
+            &multi,
+            &[
+                ("x-b3-traceid", TRACE_ID),
+                ("x-b3-spanid", SPAN_ID),
+                ("x-b3-flags", "1"),
+            ],
+        );
+        let mut injector = HashMap::new();
+        single.inject_context(&cx, &mut injector);
+        assert_eq!(
+            injector.get("b3"),
+            Some(&"4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-d".to_string())
+        );
+    }
+
+    #[test]
+    fn round_trip_with_trace_context() {
+        let b3 = B3Propagator::new();
+        let trace_context = TraceContextPropagator::new();
+
+        for (traceparent, b3_header) in [
+            (
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1",
+            ),
+            (
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+                "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-0",
+            ),
+        ] {
+            // W3C to B3
+            let mut extractor = HashMap::new();
+            extractor.insert("traceparent".to_string(), traceparent.to_string());
+            let cx = trace_context.extract(&extractor);
+            let mut injector = HashMap::new();
+            b3.inject_context(&cx, &mut injector);
+            assert_eq!(injector.get("b3"), Some(&b3_header.to_string()));
+
+            // B3 to W3C
+            let cx = extract(&b3, &[("b3", b3_header)]);
+            let mut injector = HashMap::new();
+            trace_context.inject_context(&cx, &mut injector);
+            assert_eq!(injector.get("traceparent"), Some(&traceparent.to_string()));
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let fields = |encoding| {
+            B3Propagator::with_encoding(encoding)
+                .fields()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fields(B3Encoding::SingleHeader), vec!["b3"]);
+        assert_eq!(
+            fields(B3Encoding::MultipleHeader),
+            vec!["x-b3-traceid", "x-b3-spanid", "x-b3-sampled", "x-b3-flags"]
+        );
+        assert_eq!(fields(B3Encoding::SingleAndMultiHeader).len(), 5);
+    }
+}
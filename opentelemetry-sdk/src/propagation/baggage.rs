@@ -9,10 +9,26 @@ use opentelemetry::{
 use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use std::iter;
 
+use super::diagnostics;
+
 static BAGGAGE_HEADER: &str = "baggage";
-const FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b';').add(b',').add(b'=');
+/// Characters which are not `baggage-octet`s, or which are delimiters of the
+/// header, and are percent-encoded in values and property values.
+const FRAGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'%')
+    .add(b',')
+    .add(b';')
+    .add(b'=')
+    .add(b'\\');
 static BAGGAGE_FIELDS: Lazy<[String; 1]> = Lazy::new(|| [BAGGAGE_HEADER.to_owned()]);
 
+/// The maximum number of entries of a baggage header.
+const MAX_ENTRIES: usize = 180;
+/// The maximum length in bytes of a baggage header.
+const MAX_HEADER_LEN: usize = 8192;
+
 /// Propagates name-value pairs in [W3C Baggage] format.
 ///
 /// Baggage is used to annotate telemetry, adding context and
@@ -22,6 +38,12 @@ static BAGGAGE_FIELDS: Lazy<[String; 1]> = Lazy::new(|| [BAGGAGE_HEADER.to_owned
 /// `Baggage`s are serialized according to the editor's draft of
 /// the [W3C Baggage] specification.
 ///
+/// Values and property values are percent-encoded when injected and decoded
+/// when extracted. Names must be valid tokens, entries with an invalid name or
+/// format are dropped. At most 180 entries, and entries up to a total of 8192
+/// bytes, are injected or extracted, entries past these limits are dropped.
+/// Dropped entries are reported to the global error handler.
+///
 /// # Examples
 ///
 /// ```
@@ -55,96 +77,221 @@ static BAGGAGE_FIELDS: Lazy<[String; 1]> = Lazy::new(|| [BAGGAGE_HEADER.to_owned
 ///
 /// [W3C Baggage]: https://w3c.github.io/baggage
 /// [`Baggage`]: opentelemetry::baggage::Baggage
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct BaggagePropagator {
-    _private: (),
+    strict: bool,
+}
+
+impl Default for BaggagePropagator {
+    fn default() -> Self {
+        BaggagePropagator::new()
+    }
 }
 
 impl BaggagePropagator {
     /// Construct a new baggage propagator.
     pub fn new() -> Self {
-        BaggagePropagator { _private: () }
+        BaggagePropagator {
+            strict: diagnostics::strict_from_env(),
+        }
+    }
+
+    /// Report the name of the entries dropped when extracting along with the
+    /// reason they were rejected.
+    ///
+    /// Strict extraction is disabled by default, unless the
+    /// `OTEL_PROPAGATION_STRICT` environment variable is set to `true`. Only
+    /// the first characters of entry names are reported, values are not as
+    /// they may carry sensitive data.
+    pub fn with_strict_extraction(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
     }
 }
 
+/// Returns whether `name` is a `token` as defined by [RFC 7230].
+///
+/// [RFC 7230]: https://httpwg.org/specs/rfc7230.html#rule.token.separators
+fn is_token(name: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
+/// Encodes the properties of `metadata`, percent-encoding property values.
+fn encode_metadata(metadata: &str) -> String {
+    metadata
+        .split(';')
+        .map(str::trim)
+        .filter(|prop| !prop.is_empty())
+        .map(|prop| match prop.split_once('=') {
+            Some((key, value)) => format!(
+                "{}={}",
+                key.trim(),
+                utf8_percent_encode(value.trim(), FRAGMENT)
+            ),
+            None => prop.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Decodes an entry of a baggage header.
+fn decode_entry(entry: &str) -> Result<KeyValueMetadata, &'static str> {
+    let mut parts = entry.split(';');
+    let name_and_value = parts.next().unwrap_or_default();
+    let (name, value) = name_and_value
+        .split_once('=')
+        .ok_or("invalid baggage key-value format")?;
+    let name = name.trim();
+    if !is_token(name) {
+        return Err("invalid baggage key");
+    }
+    let value = percent_decode_str(value.trim())
+        .decode_utf8()
+        .map_err(|_| "invalid UTF8 string in key values")?;
+
+    // Here we don't store the first ; into baggage since it should be treated
+    // as separator rather part of metadata
+    let props = parts
+        .map(str::trim)
+        .filter(|prop| !prop.is_empty())
+        .map(|prop| match prop.split_once('=') {
+            Some((key, value)) => percent_decode_str(value.trim())
+                .decode_utf8()
+                .map(|value| format!("{}={}", key.trim(), value)),
+            None => Ok(prop.to_owned()),
+        })
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|_| "invalid UTF8 string in baggage properties")?;
+
+    Ok(KeyValueMetadata::new(
+        name.to_owned(),
+        value.into_owned(),
+        props.join(";").as_str(), // join with ; because we deleted all ; when calling split above
+    ))
+}
+
 impl TextMapPropagator for BaggagePropagator {
     /// Encodes the values of the `Context` and injects them into the provided `Injector`.
     fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
         let baggage = cx.baggage();
-        if !baggage.is_empty() {
-            let header_value = baggage
-                .iter()
-                .map(|(name, (value, metadata))| {
-                    let metadata_str = metadata.as_str().trim();
-                    let metadata_prefix = if metadata_str.is_empty() { "" } else { ";" };
-                    utf8_percent_encode(name.as_str().trim(), FRAGMENT)
-                        .chain(iter::once("="))
-                        .chain(utf8_percent_encode(value.as_str().trim(), FRAGMENT))
-                        .chain(iter::once(metadata_prefix))
-                        .chain(iter::once(metadata_str))
-                        .collect()
-                })
-                .collect::<Vec<String>>()
-                .join(",");
+        if baggage.is_empty() {
+            return;
+        }
+
+        let mut header_value = String::new();
+        let mut entries = 0;
+        let mut dropped = false;
+        for (name, (value, metadata)) in baggage.iter() {
+            let name = name.as_str().trim();
+            if !is_token(name) {
+                global::handle_error(PropagationError::inject(
+                    "invalid baggage key",
+                    "BaggagePropagator",
+                ));
+                continue;
+            }
+
+            let mut entry: String = iter::once(name)
+                .chain(iter::once("="))
+                .chain(utf8_percent_encode(value.as_str().trim(), FRAGMENT))
+                .collect();
+            let metadata = encode_metadata(metadata.as_str());
+            if !metadata.is_empty() {
+                entry.push(';');
+                entry.push_str(&metadata);
+            }
+
+            let separator_len = usize::from(entries > 0);
+            if entries == MAX_ENTRIES
+                || header_value.len() + separator_len + entry.len() > MAX_HEADER_LEN
+            {
+                dropped = true;
+                continue;
+            }
+            if entries > 0 {
+                header_value.push(',');
+            }
+            header_value.push_str(&entry);
+            entries += 1;
+        }
+
+        if dropped {
+            global::handle_error(PropagationError::inject(
+                "baggage exceeds the header limits, entries dropped",
+                "BaggagePropagator",
+            ));
+        }
+        if !header_value.is_empty() {
             injector.set(BAGGAGE_HEADER, header_value);
         }
     }
 
     /// Extracts a `Context` with baggage values from a `Extractor`.
     fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
-        if let Some(header_value) = extractor.get(BAGGAGE_HEADER) {
-            let baggage = header_value.split(',').flat_map(|context_value| {
-                if let Some((name_and_value, props)) = context_value
-                    .split(';')
-                    .collect::<Vec<&str>>()
-                    .split_first()
-                {
-                    let mut iter = name_and_value.split('=');
-                    if let (Some(name), Some(value)) = (iter.next(), iter.next()) {
-                        let decode_name = percent_decode_str(name).decode_utf8();
-                        let decode_value = percent_decode_str(value).decode_utf8();
-
-                        if let (Ok(name), Ok(value)) = (decode_name, decode_value) {
-                            // Here we don't store the first ; into baggage since it should be treated
-                            // as separator rather part of metadata
-                            let decoded_props = props
-                                .iter()
-                                .flat_map(|prop| percent_decode_str(prop).decode_utf8())
-                                .map(|prop| prop.trim().to_string())
-                                .collect::<Vec<String>>()
-                                .join(";"); // join with ; because we deleted all ; when calling split above
-
-                            Some(KeyValueMetadata::new(
-                                name.trim().to_owned(),
-                                value.trim().to_string(),
-                                decoded_props.as_str(),
-                            ))
-                        } else {
-                            global::handle_error(PropagationError::extract(
-                                "invalid UTF8 string in key values",
-                                "BaggagePropagator",
-                            ));
-                            None
-                        }
-                    } else {
-                        global::handle_error(PropagationError::extract(
-                            "invalid baggage key-value format",
-                            "BaggagePropagator",
-                        ));
-                        None
-                    }
-                } else {
-                    global::handle_error(PropagationError::extract(
-                        "invalid baggage format",
+        let Some(header_value) = extractor.get(BAGGAGE_HEADER) else {
+            return cx.clone();
+        };
+
+        let mut baggage = Vec::new();
+        let mut header_len = 0;
+        let mut dropped = false;
+        for entry in header_value.split(',') {
+            if entry.trim().is_empty() {
+                continue;
+            }
+            let separator_len = usize::from(header_len > 0);
+            if baggage.len() == MAX_ENTRIES
+                || header_len + separator_len + entry.len() > MAX_HEADER_LEN
+            {
+                dropped = true;
+                break;
+            }
+            header_len += separator_len + entry.len();
+
+            match decode_entry(entry) {
+                Ok(kvm) => baggage.push(kvm),
+                Err(message) if self.strict => {
+                    let name = entry.split(['=', ';']).next().unwrap_or_default().trim();
+                    diagnostics::report_rejected(
                         "BaggagePropagator",
-                    ));
-                    None
+                        "baggage entry",
+                        name,
+                        message,
+                    )
                 }
-            });
-            cx.with_baggage(baggage)
-        } else {
-            cx.clone()
+                Err(message) => {
+                    global::handle_error(PropagationError::extract(message, "BaggagePropagator"))
+                }
+            }
+        }
+
+        if dropped {
+            global::handle_error(PropagationError::extract(
+                "baggage exceeds the header limits, entries dropped",
+                "BaggagePropagator",
+            ));
         }
+        cx.with_baggage(baggage)
     }
 
     fn fields(&self) -> FieldIter<'_> {
@@ -310,4 +457,76 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn inject_and_extract_percent_encoded_values_and_metadata() {
+        let propagator = BaggagePropagator::new();
+        let cx = Context::current_with_baggage(vec![KeyValueMetadata::new(
+            "key1",
+            "50% off, \"today\"",
+            "prop1;prop2=a b,c",
+        )]);
+
+        let mut injector = HashMap::new();
+        propagator.inject_context(&cx, &mut injector);
+        let header_value = injector.get(BAGGAGE_HEADER).unwrap();
+        assert_eq!(
+            header_value,
+            "key1=50%25%20off%2C%20%22today%22;prop1;prop2=a%20b%2Cc"
+        );
+
+        let cx = propagator.extract(&injector);
+        let baggage = cx.baggage();
+        assert_eq!(
+            baggage.get_with_metadata("key1"),
+            Some(&(
+                Value::from("50% off, \"today\""),
+                BaggageMetadata::from("prop1;prop2=a b,c")
+            ))
+        );
+    }
+
+    #[test]
+    fn inject_drops_invalid_keys_and_entries_over_header_limit() {
+        let propagator = BaggagePropagator::new();
+        let cx = Context::current_with_baggage(vec![
+            KeyValue::new("key 1", "val1"),
+            // Percent-encoding expands the value past the header limit.
+            KeyValue::new("key2", ",".repeat(4000)),
+            KeyValue::new("key3", "val3"),
+        ]);
+
+        let mut injector = HashMap::new();
+        propagator.inject_context(&cx, &mut injector);
+        assert_eq!(injector.get(BAGGAGE_HEADER).unwrap(), "key3=val3");
+    }
+
+    #[test]
+    fn extract_drops_entries_over_limits() {
+        let propagator = BaggagePropagator::new();
+
+        let header_value = (0..MAX_ENTRIES + 20)
+            .map(|i| format!("key{}=val{}", i, i))
+            .collect::<Vec<_>>()
+            .join(",");
+        let extractor = HashMap::from([(BAGGAGE_HEADER.to_string(), header_value)]);
+        assert_eq!(propagator.extract(&extractor).baggage().len(), MAX_ENTRIES);
+
+        let header_value = format!("key1={},key2=val2", "a".repeat(MAX_HEADER_LEN));
+        let extractor = HashMap::from([(BAGGAGE_HEADER.to_string(), header_value)]);
+        assert!(propagator.extract(&extractor).baggage().is_empty());
+    }
+
+    #[test]
+    fn extract_drops_invalid_keys() {
+        let propagator = BaggagePropagator::new();
+        let extractor = HashMap::from([(
+            BAGGAGE_HEADER.to_string(),
+            "key 1=val1,=val2,key(3)=val3,key4=val4=4".to_string(),
+        )]);
+        let cx = propagator.extract(&extractor);
+        let baggage = cx.baggage();
+        assert_eq!(baggage.len(), 1);
+        assert_eq!(baggage.get("key4"), Some(&Value::from("val4=4")));
+    }
 }
@@ -0,0 +1,67 @@
+//! # Strict extraction diagnostics
+//!
+//! By default, propagators silently ignore malformed headers and extract the
+//! context they were given. In strict mode, they report why a header was
+//! rejected to the global error handler, with the offending value truncated so
+//! that secrets carried in headers are not logged in full.
+use opentelemetry::global;
+use std::env;
+
+/// The environment variable enabling strict extraction when set to `true`.
+pub(crate) const OTEL_PROPAGATION_STRICT: &str = "OTEL_PROPAGATION_STRICT";
+
+/// The maximum number of characters of a header value included in a
+/// diagnostic.
+const MAX_VALUE_CHARS: usize = 32;
+
+/// Returns whether strict extraction is enabled by the environment.
+pub(crate) fn strict_from_env() -> bool {
+    env::var(OTEL_PROPAGATION_STRICT)
+        .map(|value| value.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Returns `value` truncated for a diagnostic, with its total length if it
+/// was truncated.
+pub(crate) fn redact(value: &str) -> String {
+    match value.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((end, _)) => format!("{:?}... ({} bytes)", &value[..end], value.len()),
+        None => format!("{value:?}"),
+    }
+}
+
+/// Reports that `value`, e.g. a header, was rejected for `reason`.
+pub(crate) fn report_rejected(propagator: &str, what: &str, value: &str, reason: &str) {
+    global::handle_error(global::Error::Other(format!(
+        "{propagator} rejected {what} {}: {reason}",
+        redact(value)
+    )));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_truncates_long_values() {
+        assert_eq!(redact("short"), "\"short\"");
+        let value = "a".repeat(40);
+        assert_eq!(
+            redact(&value),
+            format!("\"{}\"... (40 bytes)", "a".repeat(32))
+        );
+        // control characters are escaped
+        assert_eq!(redact("a\nb"), "\"a\\nb\"");
+    }
+
+    #[test]
+    fn strict_mode_from_env() {
+        temp_env::with_var(OTEL_PROPAGATION_STRICT, Some("TRUE"), || {
+            assert!(strict_from_env())
+        });
+        temp_env::with_var(OTEL_PROPAGATION_STRICT, Some("1"), || {
+            assert!(!strict_from_env())
+        });
+        temp_env::with_var_unset(OTEL_PROPAGATION_STRICT, || assert!(!strict_from_env()));
+    }
+}
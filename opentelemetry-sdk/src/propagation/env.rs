@@ -0,0 +1,171 @@
+//! # Propagators configured from the environment
+//!
+//! Reads the propagators listed in the `OTEL_PROPAGATORS` environment variable
+//! so that applications can switch propagation formats without code changes.
+use opentelemetry::{
+    global,
+    propagation::{TextMapCompositePropagator, TextMapPropagator},
+};
+use std::env;
+
+use super::{B3Encoding, B3Propagator, BaggagePropagator, TraceContextPropagator};
+
+/// The environment variable listing the propagators to use.
+const OTEL_PROPAGATORS: &str = "OTEL_PROPAGATORS";
+/// The propagators used if `OTEL_PROPAGATORS` is unset or empty.
+const DEFAULT_PROPAGATORS: &str = "tracecontext,baggage";
+
+/// A boxed [`TextMapPropagator`] resolved from its `OTEL_PROPAGATORS` name.
+pub type BoxedPropagator = Box<dyn TextMapPropagator + Send + Sync>;
+
+/// Builds a [`TextMapCompositePropagator`] from the comma-separated list of
+/// propagators of the `OTEL_PROPAGATORS` environment variable.
+///
+/// `tracecontext` and `baggage` resolve to [`TraceContextPropagator`] and
+/// [`BaggagePropagator`], `b3` and `b3multi` to a [`B3Propagator`] injecting
+/// the single or the multiple B3 headers, `none` disables propagation. Other
+/// names, e.g. `jaeger` whose propagator is provided by the
+/// `opentelemetry-jaeger-propagator` crate, are passed to `resolve`. Names
+/// which cannot be resolved are reported to the global error handler and
+/// skipped.
+///
+/// Propagators are used in the listed order, duplicates are ignored. If the
+/// variable is unset or empty, `tracecontext,baggage` is used.
+pub fn propagators_from_env<F>(resolve: F) -> TextMapCompositePropagator
+where
+    F: Fn(&str) -> Option<BoxedPropagator>,
+{
+    let value = env::var(OTEL_PROPAGATORS)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PROPAGATORS.to_owned());
+
+    let mut names: Vec<String> = Vec::new();
+    for name in value
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+    {
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.iter().any(|name| name == "none") {
+        return TextMapCompositePropagator::new(Vec::new());
+    }
+
+    let propagators = names
+        .iter()
+        .filter_map(|name| {
+            let propagator: Option<BoxedPropagator> = match name.as_str() {
+                "tracecontext" => Some(Box::new(TraceContextPropagator::new())),
+                "baggage" => Some(Box::new(BaggagePropagator::new())),
+                "b3" => Some(Box::new(B3Propagator::new())),
+                "b3multi" => Some(Box::new(B3Propagator::with_encoding(
+                    B3Encoding::MultipleHeader,
+                ))),
+                other => resolve(other),
+            };
+            if propagator.is_none() {
+                global::handle_error(global::Error::Other(format!(
+                    "unsupported propagator {name:?} in {OTEL_PROPAGATORS}"
+                )));
+            }
+            propagator
+        })
+        .collect();
+
+    TextMapCompositePropagator::new(propagators)
+}
+
+/// Sets the global propagator to the propagators listed in the
+/// `OTEL_PROPAGATORS` environment variable.
+///
+/// Only the `tracecontext`, `baggage`, `b3`, `b3multi` and `none` propagators
+/// are supported, use [`init_propagators_from_env_with`] to support other
+/// propagators.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_sdk::propagation::init_propagators_from_env;
+///
+/// // e.g. OTEL_PROPAGATORS=tracecontext,baggage
+/// init_propagators_from_env();
+/// ```
+pub fn init_propagators_from_env() {
+    init_propagators_from_env_with(|_| None)
+}
+
+/// Sets the global propagator to the propagators listed in the
+/// `OTEL_PROPAGATORS` environment variable, resolving the names other than
+/// `tracecontext`, `baggage`, `b3`, `b3multi` and `none` with `resolve`.
+///
+/// See [`propagators_from_env`].
+pub fn init_propagators_from_env_with<F>(resolve: F)
+where
+    F: Fn(&str) -> Option<BoxedPropagator>,
+{
+    global::set_text_map_propagator(propagators_from_env(resolve))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(propagator: &TextMapCompositePropagator) -> Vec<&str> {
+        let mut fields: Vec<_> = propagator.fields().collect();
+        fields.sort_unstable();
+        fields
+    }
+
+    #[test]
+    fn propagators_from_env_defaults_to_tracecontext_and_baggage() {
+        for value in [None, Some(""), Some(" ")] {
+            temp_env::with_var(OTEL_PROPAGATORS, value, || {
+                let propagator = propagators_from_env(|_| None);
+                assert_eq!(
+                    fields(&propagator),
+                    vec!["baggage", "traceparent", "tracestate"]
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn propagators_from_env_resolves_other_names() {
+        temp_env::with_var(OTEL_PROPAGATORS, Some(" Baggage,custom,unknown"), || {
+            let propagator = propagators_from_env(|name| {
+                (name == "custom")
+                    .then(|| Box::new(TraceContextPropagator::new()) as BoxedPropagator)
+            });
+            assert_eq!(
+                fields(&propagator),
+                vec!["baggage", "traceparent", "tracestate"]
+            );
+        });
+
+        temp_env::with_var(OTEL_PROPAGATORS, Some("baggage,unknown"), || {
+            assert_eq!(fields(&propagators_from_env(|_| None)), vec!["baggage"]);
+        });
+    }
+
+    #[test]
+    fn propagators_from_env_supports_b3() {
+        temp_env::with_var(OTEL_PROPAGATORS, Some("b3"), || {
+            assert_eq!(fields(&propagators_from_env(|_| None)), vec!["b3"]);
+        });
+        temp_env::with_var(OTEL_PROPAGATORS, Some("b3multi"), || {
+            assert_eq!(
+                fields(&propagators_from_env(|_| None)),
+                vec!["x-b3-flags", "x-b3-sampled", "x-b3-spanid", "x-b3-traceid"]
+            );
+        });
+    }
+
+    #[test]
+    fn propagators_from_env_none_disables_propagation() {
+        temp_env::with_var(OTEL_PROPAGATORS, Some("tracecontext,none"), || {
+            assert!(fields(&propagators_from_env(|_| None)).is_empty());
+        });
+    }
+}
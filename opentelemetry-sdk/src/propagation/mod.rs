@@ -1,6 +1,14 @@
 //! OpenTelemetry Propagators
+mod b3;
 mod baggage;
+mod diagnostics;
+mod env;
 mod trace_context;
 
+pub use b3::{B3Encoding, B3Propagator};
 pub use baggage::BaggagePropagator;
+pub use env::{
+    init_propagators_from_env, init_propagators_from_env_with, propagators_from_env,
+    BoxedPropagator,
+};
 pub use trace_context::TraceContextPropagator;
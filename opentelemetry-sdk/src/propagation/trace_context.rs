@@ -9,10 +9,15 @@ use opentelemetry::{
 };
 use std::str::FromStr;
 
+use super::diagnostics;
+
 const SUPPORTED_VERSION: u8 = 0;
 const MAX_VERSION: u8 = 254;
 const TRACEPARENT_HEADER: &str = "traceparent";
 const TRACESTATE_HEADER: &str = "tracestate";
+/// Length of a version 0 `traceparent` header value.
+const TRACEPARENT_LEN: usize = 55;
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
 
 static TRACE_CONTEXT_HEADER_FIELDS: Lazy<[String; 2]> =
     Lazy::new(|| [TRACEPARENT_HEADER.to_owned(), TRACESTATE_HEADER.to_owned()]);
@@ -43,54 +48,76 @@ static TRACE_CONTEXT_HEADER_FIELDS: Lazy<[String; 2]> =
 ///
 /// [w3c trace-context docs]: https://w3c.github.io/trace-context/
 /// [W3C TraceContext]: https://www.w3.org/TR/trace-context/
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct TraceContextPropagator {
-    _private: (),
+    strict: bool,
+}
+
+impl Default for TraceContextPropagator {
+    fn default() -> Self {
+        TraceContextPropagator::new()
+    }
 }
 
 impl TraceContextPropagator {
     /// Create a new `TraceContextPropagator`.
     pub fn new() -> Self {
-        TraceContextPropagator { _private: () }
+        TraceContextPropagator {
+            strict: diagnostics::strict_from_env(),
+        }
+    }
+
+    /// Report why a malformed `traceparent` or `tracestate` header was
+    /// rejected when extracting.
+    ///
+    /// Strict extraction is disabled by default, unless the
+    /// `OTEL_PROPAGATION_STRICT` environment variable is set to `true`.
+    /// Rejected headers are reported to the global error handler with their
+    /// value truncated, the context is extracted as if they were missing.
+    pub fn with_strict_extraction(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
     }
 
     /// Extract span context from w3c trace-context header.
-    fn extract_span_context(&self, extractor: &dyn Extractor) -> Result<SpanContext, ()> {
+    fn extract_span_context(&self, extractor: &dyn Extractor) -> Result<SpanContext, &'static str> {
         let header_value = extractor.get(TRACEPARENT_HEADER).unwrap_or("").trim();
-        let parts = header_value.split_terminator('-').collect::<Vec<&str>>();
+        let mut parts = header_value.split_terminator('-');
         // Ensure parts are not out of range.
-        if parts.len() < 4 {
-            return Err(());
-        }
+        let (version, trace_id, span_id, opts) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(version), Some(trace_id), Some(span_id), Some(opts)) => {
+                    (version, trace_id, span_id, opts)
+                }
+                _ => return Err("expected 4 fields"),
+            };
 
         // Ensure version is within range, for version 0 there must be 4 parts.
-        let version = u8::from_str_radix(parts[0], 16).map_err(|_| ())?;
-        if version > MAX_VERSION || version == 0 && parts.len() != 4 {
-            return Err(());
+        let version = decode_hex(version, 2).map_err(|_| "invalid version")? as u8;
+        if version > MAX_VERSION || version == 0 && parts.next().is_some() {
+            return Err("unsupported version");
         }
 
-        // Ensure trace id is lowercase
-        if parts[1].chars().any(|c| c.is_ascii_uppercase()) {
-            return Err(());
-        }
-
-        // Parse trace id section
-        let trace_id = TraceId::from_hex(parts[1]).map_err(|_| ())?;
-
-        // Ensure span id is lowercase
-        if parts[2].chars().any(|c| c.is_ascii_uppercase()) {
-            return Err(());
-        }
+        // Parse trace id section, it must be lowercase
+        let trace_id = match decode_hex(trace_id, 32) {
+            Ok(trace_id) => TraceId::from(trace_id),
+            Err(HexError::UpperCase) => return Err("upper case trace id"),
+            Err(HexError::Invalid) => return Err("invalid trace id"),
+        };
 
-        // Parse span id section
-        let span_id = SpanId::from_hex(parts[2]).map_err(|_| ())?;
+        // Parse span id section, it must be lowercase
+        let span_id = match decode_hex(span_id, 16) {
+            Ok(span_id) => SpanId::from(span_id as u64),
+            Err(HexError::UpperCase) => return Err("upper case parent id"),
+            Err(HexError::Invalid) => return Err("invalid parent id"),
+        };
 
         // Parse trace flags section
-        let opts = u8::from_str_radix(parts[3], 16).map_err(|_| ())?;
+        let opts = decode_hex(opts, 2).map_err(|_| "invalid trace flags")? as u8;
 
         // Ensure opts are valid for version 0
         if version == 0 && opts > 2 {
-            return Err(());
+            return Err("invalid trace flags");
         }
 
         // Build trace flags clearing all flags other than the trace-context
@@ -98,9 +125,17 @@ impl TraceContextPropagator {
         let trace_flags = TraceFlags::new(opts) & TraceFlags::SAMPLED;
 
         let trace_state = match extractor.get(TRACESTATE_HEADER) {
-            Some(trace_state_str) => {
-                TraceState::from_str(trace_state_str).unwrap_or_else(|_| TraceState::default())
-            }
+            Some(trace_state_str) => TraceState::from_str(trace_state_str).unwrap_or_else(|_| {
+                if self.strict {
+                    diagnostics::report_rejected(
+                        "TraceContextPropagator",
+                        "tracestate header",
+                        trace_state_str,
+                        "invalid list member",
+                    );
+                }
+                TraceState::default()
+            }),
             None => TraceState::default(),
         };
 
@@ -109,13 +144,62 @@ impl TraceContextPropagator {
 
         // Ensure span is valid
         if !span_context.is_valid() {
-            return Err(());
+            return Err("invalid trace id or parent id");
         }
 
         Ok(span_context)
     }
 }
 
+/// Why a `traceparent` field is not valid hex.
+enum HexError {
+    UpperCase,
+    Invalid,
+}
+
+/// Decode a `traceparent` field of exactly `width` lowercase hex digits.
+///
+/// Unlike `from_str_radix`, shorter fields and a leading sign are rejected.
+fn decode_hex(field: &str, width: usize) -> Result<u128, HexError> {
+    if field.len() != width {
+        return Err(HexError::Invalid);
+    }
+    let mut value = 0u128;
+    for byte in field.bytes() {
+        let digit = match byte {
+            b'0'..=b'9' => byte - b'0',
+            b'a'..=b'f' => byte - b'a' + 10,
+            b'A'..=b'F' => return Err(HexError::UpperCase),
+            _ => return Err(HexError::Invalid),
+        };
+        value = (value << 4) | u128::from(digit);
+    }
+    Ok(value)
+}
+
+/// Encode the version 0 `traceparent` header value of a span context.
+fn encode_traceparent(span_context: &SpanContext) -> String {
+    let mut header = String::with_capacity(TRACEPARENT_LEN);
+    push_hex(&mut header, &[SUPPORTED_VERSION]);
+    header.push('-');
+    push_hex(&mut header, &span_context.trace_id().to_bytes());
+    header.push('-');
+    push_hex(&mut header, &span_context.span_id().to_bytes());
+    header.push('-');
+    push_hex(
+        &mut header,
+        &[(span_context.trace_flags() & TraceFlags::SAMPLED).to_u8()],
+    );
+    header
+}
+
+fn push_hex(header: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        header.push(HEX_DIGITS[usize::from(byte >> 4)] as char);
+        header.push(HEX_DIGITS[usize::from(byte & 0xf)] as char);
+    }
+}
+
 impl TextMapPropagator for TraceContextPropagator {
     /// Properly encodes the values of the `SpanContext` and injects them
     /// into the `Injector`.
@@ -123,14 +207,7 @@ impl TextMapPropagator for TraceContextPropagator {
         let span = cx.span();
         let span_context = span.span_context();
         if span_context.is_valid() {
-            let header_value = format!(
-                "{:02x}-{}-{}-{:02x}",
-                SUPPORTED_VERSION,
-                span_context.trace_id(),
-                span_context.span_id(),
-                span_context.trace_flags() & TraceFlags::SAMPLED
-            );
-            injector.set(TRACEPARENT_HEADER, header_value);
+            injector.set(TRACEPARENT_HEADER, encode_traceparent(span_context));
             injector.set(TRACESTATE_HEADER, span_context.trace_state().header());
         }
     }
@@ -140,9 +217,23 @@ impl TextMapPropagator for TraceContextPropagator {
     /// OR if the retrieved SpanContext is invalid then an empty `SpanContext`
     /// is returned.
     fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
-        self.extract_span_context(extractor)
-            .map(|sc| cx.with_remote_span_context(sc))
-            .unwrap_or_else(|_| cx.clone())
+        match self.extract_span_context(extractor) {
+            Ok(sc) => cx.with_remote_span_context(sc),
+            Err(reason) => {
+                if self.strict {
+                    // A missing header is not malformed
+                    if let Some(header_value) = extractor.get(TRACEPARENT_HEADER) {
+                        diagnostics::report_rejected(
+                            "TraceContextPropagator",
+                            "traceparent header",
+                            header_value,
+                            reason,
+                        );
+                    }
+                }
+                cx.clone()
+            }
+        }
     }
 
     fn fields(&self) -> FieldIter<'_> {
@@ -189,6 +280,10 @@ mod tests {
             ("00-ab000000000000000000000000000000-cd00000000000000-09",   "trace-flag unused bits set"),
             ("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",      "missing options"),
             ("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-",     "empty options"),
+            ("00-4bf92f35-00f067aa0ba902b7-01",                           "short trace ID"),
+            ("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa-01",           "short span ID"),
+            ("00-+bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",   "signed trace ID"),
+            ("0-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1",     "short version and flags"),
         ]
     }
 
@@ -239,6 +334,33 @@ mod tests {
         )
     }
 
+    #[test]
+    fn extract_w3c_strict_reports_reason() {
+        let propagator = TraceContextPropagator::new().with_strict_extraction(true);
+
+        for (invalid_header, reason) in [
+            ("00-4bf92f3577b34da6a3ce929d0e0e4736", "expected 4 fields"),
+            (
+                "00-AB000000000000000000000000000000-cd00000000000000-01",
+                "upper case trace id",
+            ),
+            (
+                "00-00000000000000000000000000000000-0000000000000000-01",
+                "invalid trace id or parent id",
+            ),
+        ] {
+            let mut extractor = HashMap::new();
+            extractor.insert(TRACEPARENT_HEADER.to_string(), invalid_header.to_string());
+
+            assert_eq!(propagator.extract_span_context(&extractor), Err(reason));
+            // the context is still extracted as if the header was missing
+            assert_eq!(
+                propagator.extract(&extractor).span().span_context(),
+                &SpanContext::empty_context()
+            );
+        }
+    }
+
     #[test]
     fn extract_w3c_reject_invalid() {
         let propagator = TraceContextPropagator::new();
@@ -30,3 +30,25 @@ pub(crate) const TELEMETRY_SDK_NAME: &str = "telemetry.sdk.name";
 ///
 /// - `1.2.3`
 pub(crate) const TELEMETRY_SDK_VERSION: &str = "telemetry.sdk.version";
+
+/// Container ID. Usually a UUID, as for example used to [identify Docker containers](https://docs.docker.com/engine/reference/run/#container-identification). The UUID might be abbreviated.
+///
+/// # Examples
+///
+/// - `a3bf90e006b2`
+pub(crate) const CONTAINER_ID: &str = "container.id";
+
+/// Unique host ID. For Cloud, this must be the instance_id assigned by the cloud provider. For non-containerized systems, this should be the `machine-id`.
+///
+/// # Examples
+///
+/// - `fdbf79e8af94cb7f9e8df36789187052`
+pub(crate) const HOST_ID: &str = "host.id";
+
+/// The operating system type.
+///
+/// # Examples
+///
+/// - `linux`
+/// - `darwin`
+pub(crate) const OS_TYPE: &str = "os.type";
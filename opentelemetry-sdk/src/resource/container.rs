@@ -0,0 +1,123 @@
+//! Container resource detector
+//!
+//! Detects the ID of the container the process runs in from its cgroups.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::fs;
+use std::time::Duration;
+
+const CGROUP_PATH: &str = "/proc/self/cgroup";
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+const CONTAINER_ID_LEN: usize = 64;
+
+/// Detect the ID of the container the process runs in (`container.id`).
+///
+/// The ID is read from the cgroup of the process in `/proc/self/cgroup` with
+/// cgroup v1, and from the mounts of the container runtime in
+/// `/proc/self/mountinfo` with cgroup v2. An empty resource is returned if
+/// the process does not run in a container or on platforms other than Linux.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/container.md)
+/// for details.
+#[derive(Debug)]
+pub struct ContainerResourceDetector;
+
+impl ResourceDetector for ContainerResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let container_id = fs::read_to_string(CGROUP_PATH)
+            .ok()
+            .and_then(|cgroup| container_id_from_cgroup(&cgroup))
+            .or_else(|| {
+                fs::read_to_string(MOUNTINFO_PATH)
+                    .ok()
+                    .and_then(|mountinfo| container_id_from_mountinfo(&mountinfo))
+            });
+
+        match container_id {
+            Some(id) => Resource::new(vec![KeyValue::new(super::CONTAINER_ID, id)]),
+            None => Resource::empty(),
+        }
+    }
+}
+
+/// Finds the container ID in the paths of cgroup v1 entries, e.g.
+/// `/docker/<id>` or `/kubepods/.../cri-containerd-<id>.scope`.
+fn container_id_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        // hierarchy-ID:controller-list:cgroup-path
+        let path = line.splitn(3, ':').nth(2)?;
+        let segment = path.rsplit('/').next()?;
+        let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+        let id = segment.rsplit(['-', ':']).next()?;
+        is_container_id(id).then(|| id.to_string())
+    })
+}
+
+/// Finds the container ID in the mounts the container runtime sets up for
+/// the container, e.g. `/var/lib/docker/containers/<id>/hostname`.
+fn container_id_from_mountinfo(mountinfo: &str) -> Option<String> {
+    mountinfo.lines().find_map(|line| {
+        line.split_whitespace()
+            .filter(|field| field.contains("/containers/"))
+            .flat_map(|field| field.split('/'))
+            .find(|segment| is_container_id(segment))
+            .map(str::to_string)
+    })
+}
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == CONTAINER_ID_LEN && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{container_id_from_cgroup, container_id_from_mountinfo};
+
+    const ID: &str = "bb7cc8e5b7d1c3c2f1d4a1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6";
+
+    #[test]
+    fn test_container_id_from_cgroup() {
+        let cases = vec![
+            (format!("12:cpu,cpuacct:/docker/{ID}\n"), Some(ID)),
+            (
+                format!("0::/kubepods.slice/kubepods-pod1.slice/cri-containerd-{ID}.scope\n"),
+                Some(ID),
+            ),
+            (
+                format!("11:devices:/kubepods/besteffort/pod1/crio-{ID}.scope"),
+                Some(ID),
+            ),
+            (
+                format!("1:name=systemd:/system.slice/docker-{ID}.scope\n2:cpu:/"),
+                Some(ID),
+            ),
+            ("0::/init.scope\n".to_string(), None),
+            (
+                "0::/user.slice/user-1000.slice/session-2.scope\n".to_string(),
+                None,
+            ),
+            (String::new(), None),
+        ];
+
+        for (cgroup, expected) in cases {
+            assert_eq!(
+                container_id_from_cgroup(&cgroup).as_deref(),
+                expected,
+                "{cgroup}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_container_id_from_mountinfo() {
+        let mountinfo = format!(
+            "1502 1487 0:318 / / rw,relatime - overlay overlay rw\n\
+             1510 1502 254:1 /docker/containers/{ID}/hostname /etc/hostname rw,relatime - ext4 /dev/vda1 rw\n"
+        );
+        assert_eq!(container_id_from_mountinfo(&mountinfo).as_deref(), Some(ID));
+
+        let mountinfo = "25 1 254:1 / / rw,relatime shared:1 - ext4 /dev/vda1 rw\n";
+        assert_eq!(container_id_from_mountinfo(mountinfo), None);
+    }
+}
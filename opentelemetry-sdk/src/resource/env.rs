@@ -3,7 +3,8 @@
 //! Implementation of `ResourceDetector` to extract a `Resource` from environment
 //! variables.
 use crate::resource::{Resource, ResourceDetector};
-use opentelemetry::{Key, KeyValue, Value};
+use opentelemetry::{global, Key, KeyValue, Value};
+use percent_encoding::percent_decode_str;
 use std::env;
 use std::time::Duration;
 
@@ -43,17 +44,33 @@ impl Default for EnvResourceDetector {
 
 /// Extract key value pairs and construct a resource from resources string like
 /// key1=value1,key2=value2,...
+///
+/// Values are percent-decoded. If a value does not decode to valid UTF-8, the
+/// whole string is discarded and an error is reported.
 fn construct_otel_resources(s: String) -> Resource {
-    Resource::new(s.split_terminator(',').filter_map(|entry| {
+    let mut attributes = Vec::new();
+    for entry in s.split_terminator(',') {
         let mut parts = entry.splitn(2, '=');
-        let key = parts.next()?.trim();
-        let value = parts.next()?.trim();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let value = value.trim();
         if value.find('=').is_some() {
-            return None;
+            continue;
         }
 
-        Some(KeyValue::new(key.to_owned(), value.to_owned()))
-    }))
+        match percent_decode_str(value).decode_utf8() {
+            Ok(value) => attributes.push(KeyValue::new(key.trim().to_owned(), value.into_owned())),
+            Err(err) => {
+                global::handle_error(global::Error::Other(format!(
+                    "discarding {OTEL_RESOURCE_ATTRIBUTES}, the value of {} is not valid percent-encoded UTF-8: {err}",
+                    key.trim()
+                )));
+                return Resource::empty();
+            }
+        }
+    }
+    Resource::new(attributes)
 }
 
 /// There are attributes which MUST be provided by the SDK as specified in
@@ -128,6 +145,35 @@ mod tests {
         assert!(resource.is_empty());
     }
 
+    #[test]
+    fn test_read_percent_encoded_values_from_env() {
+        temp_env::with_var(
+            OTEL_RESOURCE_ATTRIBUTES,
+            Some("service.name=my%20service,tags=a%2Cb%3Dc,plain=100%"),
+            || {
+                let resource = EnvResourceDetector::new().detect(Duration::from_secs(5));
+                assert_eq!(
+                    resource,
+                    Resource::new(vec![
+                        KeyValue::new("service.name", "my service"),
+                        KeyValue::new("tags", "a,b=c"),
+                        KeyValue::new("plain", "100%"),
+                    ])
+                );
+            },
+        );
+
+        // invalid UTF-8 discards all attributes
+        temp_env::with_var(
+            OTEL_RESOURCE_ATTRIBUTES,
+            Some("service.name=svc,key=%FF"),
+            || {
+                let resource = EnvResourceDetector::new().detect(Duration::from_secs(5));
+                assert!(resource.is_empty());
+            },
+        );
+    }
+
     #[test]
     fn test_sdk_provided_resource_detector() {
         // Ensure no env var set
@@ -0,0 +1,231 @@
+//! Host resource detector
+//!
+//! Detects the unique ID of the host the process runs on.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::time::Duration;
+
+/// Detect the unique ID of the host the process runs on (`host.id`).
+///
+/// The ID is read from:
+/// - `/etc/machine-id` or `/var/lib/dbus/machine-id` on Linux.
+/// - `/etc/hostid` or the `smbios.system.uuid` kernel environment on BSDs.
+/// - The `IOPlatformUUID` of `ioreg` on macOS.
+/// - The `MachineGuid` registry value on Windows.
+///
+/// An empty resource is returned if the ID cannot be read. The `kenv`,
+/// `ioreg` and `reg` commands are killed if they do not complete within the
+/// detection timeout, in which case the ID is not read either.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/host.md)
+/// for details.
+#[derive(Debug)]
+pub struct HostResourceDetector;
+
+impl ResourceDetector for HostResourceDetector {
+    fn detect(&self, timeout: Duration) -> Resource {
+        match host_id(timeout).filter(|id| !id.is_empty()) {
+            Some(id) => Resource::new(vec![KeyValue::new(super::HOST_ID, id)]),
+            None => Resource::empty(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+const MACHINE_ID_PATHS: [&str; 2] = ["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+#[cfg(target_os = "linux")]
+fn host_id(_timeout: Duration) -> Option<String> {
+    machine_id(&MACHINE_ID_PATHS)
+}
+
+/// The first non-empty ID read from `paths`.
+#[cfg(target_os = "linux")]
+fn machine_id<P: AsRef<std::path::Path>>(paths: &[P]) -> Option<String> {
+    paths.iter().find_map(read_trimmed)
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+fn host_id(timeout: Duration) -> Option<String> {
+    read_trimmed("/etc/hostid").or_else(|| {
+        command_output("kenv", &["-q", "smbios.system.uuid"], timeout)
+            .map(|out| out.trim().to_string())
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn host_id(timeout: Duration) -> Option<String> {
+    let output = command_output("ioreg", &["-rd1", "-c", "IOPlatformExpertDevice"], timeout)?;
+    output.lines().find_map(|line| {
+        // "IOPlatformUUID" = "4B1D6E8A-..."
+        let (key, value) = line.split_once('=')?;
+        (key.trim().trim_matches('"') == "IOPlatformUUID")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn host_id(timeout: Duration) -> Option<String> {
+    let output = command_output(
+        "reg",
+        &[
+            "query",
+            r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ],
+        timeout,
+    )?;
+    output.lines().find_map(|line| {
+        // MachineGuid    REG_SZ    8f1bd0c2-...
+        let mut fields = line.split_whitespace();
+        if fields.next()? != "MachineGuid" {
+            return None;
+        }
+        fields.nth(1).map(str::to_string)
+    })
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    target_os = "windows"
+)))]
+fn host_id(_timeout: Duration) -> Option<String> {
+    None
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+fn read_trimmed<P: AsRef<std::path::Path>>(path: P) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// The output of `program`, killing it if it does not exit within `timeout`.
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    target_os = "windows",
+    all(test, unix)
+))]
+fn command_output(program: &str, args: &[&str], timeout: Duration) -> Option<String> {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use std::time::Instant;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    // read on another thread so that a full pipe does not block the command
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).ok().map(|_| output)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    let output = reader.join().ok().flatten()?;
+    status.success().then_some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_host_id_is_machine_id() {
+        use super::HostResourceDetector;
+        use crate::resource::ResourceDetector;
+        use opentelemetry::Key;
+        use std::time::Duration;
+
+        let machine_id = std::fs::read_to_string("/etc/machine-id")
+            .map(|id| id.trim().to_string())
+            .unwrap_or_default();
+        let resource = HostResourceDetector.detect(Duration::from_secs(0));
+        let host_id = resource.get(Key::from_static_str(crate::resource::HOST_ID));
+
+        if !machine_id.is_empty() {
+            assert_eq!(host_id, Some(machine_id.into()));
+        } else if let Some(id) = host_id {
+            assert!(!id.as_str().is_empty());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_machine_id_falls_back_to_next_path() {
+        use super::machine_id;
+
+        let dir = std::env::temp_dir().join(format!("otel-machine-id-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing");
+        let empty = dir.join("empty");
+        let dbus = dir.join("dbus");
+        std::fs::write(&empty, "\n").unwrap();
+        std::fs::write(&dbus, "4b1d6e8a0c2f4e2a9d3b5c6d7e8f9a0b\n").unwrap();
+
+        assert_eq!(
+            machine_id(&[&missing, &empty, &dbus]),
+            Some("4b1d6e8a0c2f4e2a9d3b5c6d7e8f9a0b".to_string())
+        );
+        assert_eq!(machine_id(&[&missing, &empty]), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_output_times_out() {
+        use super::command_output;
+        use std::time::{Duration, Instant};
+
+        assert_eq!(
+            command_output("echo", &["id"], Duration::from_secs(5)),
+            Some("id\n".to_string())
+        );
+
+        let start = Instant::now();
+        assert_eq!(
+            command_output("sleep", &["10"], Duration::from_millis(50)),
+            None
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -18,19 +18,33 @@
 //! - [`EnvResourceDetector`] - detect resource from environmental variables.
 //! - [`TelemetryResourceDetector`] - detect telemetry SDK's information.
 //!
-//! The OS and Process resource detectors are packaged separately in the
+//! The following detectors are provided as well, but are not used by
+//! [`Resource::default`]. They can be combined with the default ones through
+//! [`Resource::from_detectors`].
+//!
+//! - [`ContainerResourceDetector`] - detect the ID of the container.
+//! - [`HostResourceDetector`] - detect the ID of the host.
+//! - [`OsResourceDetector`] - detect the type of the operating system.
+//!
+//! The Process resource detector is packaged separately in the
 //! [`opentelemetry-resource-detector` crate](https://github.com/open-telemetry/opentelemetry-rust-contrib/tree/main/opentelemetry-resource-detectors).
+mod container;
 mod env;
+mod host;
+mod os;
 mod telemetry;
 
 mod attributes;
 pub(crate) use attributes::*;
 
+pub use container::ContainerResourceDetector;
 pub use env::EnvResourceDetector;
 pub use env::SdkProvidedResourceDetector;
+pub use host::HostResourceDetector;
+pub use os::OsResourceDetector;
 pub use telemetry::TelemetryResourceDetector;
 
-use opentelemetry::{Key, KeyValue, Value};
+use opentelemetry::{global, Key, KeyValue, Value};
 use std::borrow::Cow;
 use std::collections::{hash_map, HashMap};
 use std::ops::Deref;
@@ -152,19 +166,21 @@ impl Resource {
     /// updated value is empty.
     ///
     /// ### [Schema url]
-    /// If both of the resource are not empty. Schema url is determined by the following rules, in order:
-    /// 1. If this resource has a schema url, it will be used.
-    /// 2. If this resource does not have a schema url, and the other resource has a schema url, it will be used.
+    /// Schema url is determined by the following rules, in order:
+    /// 1. If this resource does not have a schema url, the schema url of the other resource will be used.
+    /// 2. If the other resource does not have a schema url, the schema url of this resource will be used.
     /// 3. If both resources have a schema url and it's the same, it will be used.
-    /// 4. If both resources have a schema url and it's different, the schema url will be empty.
-    /// 5. If both resources do not have a schema url, the schema url will be empty.
+    /// 4. If both resources have a schema url and it's different, this is a merging error. The error
+    ///    is reported to the global error handler and the schema url will be empty.
+    ///
+    /// Resources without key value pairs still contribute their schema url.
     ///
     /// [Schema url]: https://github.com/open-telemetry/opentelemetry-specification/blob/v1.9.0/specification/schemas/overview.md#schema-url
     pub fn merge<T: Deref<Target = Self>>(&self, other: T) -> Self {
-        if self.is_empty() {
+        if self.is_empty() && self.inner.schema_url.is_none() {
             return other.clone();
         }
-        if other.is_empty() {
+        if other.is_empty() && other.inner.schema_url.is_none() {
             return self.clone();
         }
         let mut combined_attrs = self.inner.attrs.clone();
@@ -175,12 +191,17 @@ impl Resource {
         let combined_schema_url = match (&self.inner.schema_url, &other.inner.schema_url) {
             // If both resources have a schema URL and it's the same, use it
             (Some(url1), Some(url2)) if url1 == url2 => Some(url1.clone()),
-            // If both resources have a schema URL but they are not the same, the schema URL will be empty
-            (Some(_), Some(_)) => None,
+            // If both resources have a schema URL but they are not the same, this is a merging error
+            (Some(url1), Some(url2)) => {
+                global::handle_error(global::Error::Other(format!(
+                    "merging resources with different schema urls {url1} and {url2}, the merged resource has no schema url"
+                )));
+                None
+            }
             // If this resource does not have a schema URL, and the other resource has a schema URL, it will be used
             (None, Some(url)) => Some(url.clone()),
-            // If this resource has a schema URL, it will be used (covers case 1 and any other cases where `self` has a schema URL)
-            (Some(url), _) => Some(url.clone()),
+            // If the other resource does not have a schema URL, the schema URL of this resource will be used
+            (Some(url), None) => Some(url.clone()),
             // If both resources do not have a schema URL, the schema URL will be empty
             (None, None) => None,
         };
@@ -341,11 +362,17 @@ mod tests {
             );
         }
 
-        // if only one resource contains key value pairs
+        // if only one resource contains key value pairs, the schema url is kept
         let resource = Resource::from_schema_url(vec![], "http://schema/a");
         let other_resource = Resource::new(vec![KeyValue::new("key", "")]);
 
-        assert_eq!(resource.merge(&other_resource).schema_url(), None);
+        let merged_resource = resource.merge(&other_resource);
+        assert_eq!(merged_resource.schema_url(), Some("http://schema/a"));
+        assert_eq!(merged_resource.len(), 1);
+        assert_eq!(
+            other_resource.merge(&resource).schema_url(),
+            Some("http://schema/a")
+        );
     }
 
     #[test]
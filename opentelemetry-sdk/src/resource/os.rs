@@ -0,0 +1,53 @@
+//! Operating system resource detector
+//!
+//! Detects the type of the operating system the process runs on.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::env::consts::OS;
+use std::time::Duration;
+
+/// Detect the type of the operating system the process runs on (`os.type`).
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/os.md)
+/// for details.
+#[derive(Debug)]
+pub struct OsResourceDetector;
+
+impl ResourceDetector for OsResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        Resource::new(vec![KeyValue::new(super::OS_TYPE, os_type(OS))])
+    }
+}
+
+/// Maps the operating system names of Rust to the `os.type` values of the
+/// semantic conventions, which differ for some systems.
+fn os_type(os: &'static str) -> &'static str {
+    match os {
+        "macos" => "darwin",
+        "dragonfly" => "dragonflybsd",
+        "illumos" => "solaris",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{os_type, OsResourceDetector};
+    use crate::resource::ResourceDetector;
+    use opentelemetry::Key;
+    use std::time::Duration;
+
+    #[test]
+    fn test_os_type() {
+        assert_eq!(os_type("linux"), "linux");
+        assert_eq!(os_type("windows"), "windows");
+        assert_eq!(os_type("macos"), "darwin");
+        assert_eq!(os_type("dragonfly"), "dragonflybsd");
+
+        let resource = OsResourceDetector.detect(Duration::from_secs(0));
+        assert_eq!(
+            resource.get(Key::from_static_str(crate::resource::OS_TYPE)),
+            Some(os_type(std::env::consts::OS).into())
+        );
+    }
+}
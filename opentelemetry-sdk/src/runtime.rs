@@ -107,6 +107,161 @@ impl Runtime for TokioCurrentThread {
     }
 }
 
+/// Runtime implementation running each background task on its own thread,
+/// with a Tokio current thread runtime.
+///
+/// Unlike [TokioCurrentThread], the threads can be named, pinned to a set of
+/// cores and given a priority, so that the export work of the batch
+/// processors and periodic readers does not contend with latency critical
+/// threads of the application, e.g. in deployments pinning request threads
+/// to dedicated cores.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "rt-tokio-dedicated-thread")]
+/// # {
+/// use opentelemetry_sdk::runtime::TokioDedicatedThread;
+///
+/// // Export on cores 6 and 7, with a low priority.
+/// let runtime = TokioDedicatedThread::new()
+///     .with_thread_name("otel-export")
+///     .with_core_affinity([6, 7])
+///     .with_thread_priority(10);
+/// # drop(runtime);
+/// # }
+/// ```
+#[cfg(feature = "rt-tokio-dedicated-thread")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rt-tokio-dedicated-thread")))]
+#[derive(Debug, Clone, Default)]
+pub struct TokioDedicatedThread {
+    thread_name: Option<String>,
+    core_ids: Option<std::sync::Arc<[usize]>>,
+    next_core: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    thread_priority: Option<u8>,
+}
+
+#[cfg(feature = "rt-tokio-dedicated-thread")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rt-tokio-dedicated-thread")))]
+impl TokioDedicatedThread {
+    /// Create a runtime spawning unnamed threads, without affinity nor
+    /// priority.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the spawned threads.
+    pub fn with_thread_name<T: Into<String>>(mut self, name: T) -> Self {
+        self.thread_name = Some(name.into());
+        self
+    }
+
+    /// Pin the spawned threads to the cores with the given ids.
+    ///
+    /// Each thread is pinned to a single core, the cores of the set are
+    /// assigned in turn to the threads of successive background tasks, e.g.
+    /// the batch span processor and the batch log processor. Ids of cores
+    /// which are not available are ignored.
+    pub fn with_core_affinity<I: IntoIterator<Item = usize>>(mut self, core_ids: I) -> Self {
+        let core_ids = core_ids.into_iter().collect::<Vec<_>>();
+        self.core_ids = (!core_ids.is_empty()).then(|| core_ids.into());
+        self
+    }
+
+    /// Set the priority of the spawned threads, from 0 (lowest) to 99
+    /// (highest).
+    ///
+    /// The priority is mapped to the platform's scheduling priorities, some
+    /// platforms require elevated permissions to raise it.
+    pub fn with_thread_priority(mut self, priority: u8) -> Self {
+        self.thread_priority = Some(priority);
+        self
+    }
+
+    /// Returns the core to pin the next spawned thread to.
+    fn next_core_id(&self) -> Option<usize> {
+        self.core_ids.as_ref().map(|core_ids| {
+            let next = self
+                .next_core
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            core_ids[next % core_ids.len()]
+        })
+    }
+
+    /// Applies the affinity and priority to the current thread.
+    fn configure_current_thread(&self, core_id: Option<usize>) {
+        if let Some(core_id) = core_id {
+            let pinned = core_affinity::get_core_ids()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|core| core.id == core_id)
+                .map_or(false, core_affinity::set_for_current);
+            if !pinned {
+                opentelemetry::global::handle_error(opentelemetry::global::Error::Other(format!(
+                    "failed to pin background thread to core {}",
+                    core_id
+                )));
+            }
+        }
+
+        if let Some(priority) = self.thread_priority {
+            let result = thread_priority::ThreadPriorityValue::try_from(priority)
+                .map_err(|err| format!("{:?}", err))
+                .and_then(|value| {
+                    thread_priority::set_current_thread_priority(
+                        thread_priority::ThreadPriority::Crossplatform(value),
+                    )
+                    .map_err(|err| format!("{:?}", err))
+                });
+            if let Err(err) = result {
+                opentelemetry::global::handle_error(opentelemetry::global::Error::Other(format!(
+                    "failed to set background thread priority to {}: {}",
+                    priority, err
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rt-tokio-dedicated-thread")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rt-tokio-dedicated-thread")))]
+impl Runtime for TokioDedicatedThread {
+    type Interval = tokio_stream::wrappers::IntervalStream;
+    type Delay = ::std::pin::Pin<Box<tokio::time::Sleep>>;
+
+    fn interval(&self, duration: Duration) -> Self::Interval {
+        crate::util::tokio_interval_stream(duration)
+    }
+
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        let core_id = self.next_core_id();
+        let runtime = self.clone();
+        let mut builder = std::thread::Builder::new();
+        if let Some(name) = &self.thread_name {
+            builder = builder.name(name.clone());
+        }
+
+        let spawned = builder.spawn(move || {
+            runtime.configure_current_thread(core_id);
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to create Tokio current thead runtime for OpenTelemetry batch processing");
+            rt.block_on(future);
+        });
+        if let Err(err) = spawned {
+            opentelemetry::global::handle_error(opentelemetry::global::Error::Other(format!(
+                "failed to spawn background thread: {}",
+                err
+            )));
+        }
+    }
+
+    fn delay(&self, duration: Duration) -> Self::Delay {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
 /// Runtime implementation, which works with async-std.
 #[cfg(feature = "rt-async-std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rt-async-std")))]
@@ -176,7 +331,22 @@ pub trait TrySend: Sync + Send {
     fn try_send(&self, item: Self::Message) -> Result<(), TrySendError>;
 }
 
-#[cfg(any(feature = "rt-tokio", feature = "rt-tokio-current-thread"))]
+impl<T: Send> TrySend for std::sync::mpsc::SyncSender<T> {
+    type Message = T;
+
+    fn try_send(&self, item: Self::Message) -> Result<(), TrySendError> {
+        self.try_send(item).map_err(|err| match err {
+            std::sync::mpsc::TrySendError::Full(_) => TrySendError::ChannelFull,
+            std::sync::mpsc::TrySendError::Disconnected(_) => TrySendError::ChannelClosed,
+        })
+    }
+}
+
+#[cfg(any(
+    feature = "rt-tokio",
+    feature = "rt-tokio-current-thread",
+    feature = "rt-tokio-dedicated-thread"
+))]
 impl<T: Send> TrySend for tokio::sync::mpsc::Sender<T> {
     type Message = T;
 
@@ -224,6 +394,24 @@ impl RuntimeChannel for TokioCurrentThread {
     }
 }
 
+#[cfg(feature = "rt-tokio-dedicated-thread")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rt-tokio-dedicated-thread")))]
+impl RuntimeChannel for TokioDedicatedThread {
+    type Receiver<T: Debug + Send> = tokio_stream::wrappers::ReceiverStream<T>;
+    type Sender<T: Debug + Send> = tokio::sync::mpsc::Sender<T>;
+
+    fn batch_message_channel<T: Debug + Send>(
+        &self,
+        capacity: usize,
+    ) -> (Self::Sender<T>, Self::Receiver<T>) {
+        let (sender, receiver) = tokio::sync::mpsc::channel(capacity);
+        (
+            sender,
+            tokio_stream::wrappers::ReceiverStream::new(receiver),
+        )
+    }
+}
+
 #[cfg(feature = "rt-async-std")]
 impl<T: Send> TrySend for async_std::channel::Sender<T> {
     type Message = T;
@@ -249,3 +437,45 @@ impl RuntimeChannel for AsyncStd {
         async_std::channel::bounded(capacity)
     }
 }
+
+#[cfg(all(test, feature = "rt-tokio-dedicated-thread"))]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn dedicated_thread_runs_task_on_named_thread() {
+        let runtime = TokioDedicatedThread::new()
+            .with_thread_name("otel-test-export")
+            .with_core_affinity([0]);
+        let (sender, receiver) = mpsc::channel();
+
+        runtime.spawn(Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            let name = std::thread::current().name().map(ToOwned::to_owned);
+            sender.send(name).unwrap();
+        }));
+
+        let name = receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("task should run");
+        assert_eq!(name.as_deref(), Some("otel-test-export"));
+    }
+
+    #[test]
+    fn dedicated_thread_assigns_cores_in_turn() {
+        let runtime = TokioDedicatedThread::new().with_core_affinity([2, 3]);
+
+        // Clones share the assignment of cores.
+        let clone = runtime.clone();
+        assert_eq!(runtime.next_core_id(), Some(2));
+        assert_eq!(clone.next_core_id(), Some(3));
+        assert_eq!(runtime.next_core_id(), Some(2));
+        assert_eq!(
+            TokioDedicatedThread::new()
+                .with_core_affinity([])
+                .next_core_id(),
+            None
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use opentelemetry::logs::{AnyValue, Severity};
+use opentelemetry::trace::TraceFlags;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{parse_span_id, parse_trace_id};
+use crate::logs::{LogRecord, TraceContext};
+
+const SEVERITIES: [Severity; 24] = [
+    Severity::Trace,
+    Severity::Trace2,
+    Severity::Trace3,
+    Severity::Trace4,
+    Severity::Debug,
+    Severity::Debug2,
+    Severity::Debug3,
+    Severity::Debug4,
+    Severity::Info,
+    Severity::Info2,
+    Severity::Info3,
+    Severity::Info4,
+    Severity::Warn,
+    Severity::Warn2,
+    Severity::Warn3,
+    Severity::Warn4,
+    Severity::Error,
+    Severity::Error2,
+    Severity::Error3,
+    Severity::Error4,
+    Severity::Fatal,
+    Severity::Fatal2,
+    Severity::Fatal3,
+    Severity::Fatal4,
+];
+
+impl Serialize for LogRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LogRecordDef::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LogRecord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LogRecordDef::deserialize(deserializer)?
+            .try_into()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogRecordDef {
+    event_name: Option<String>,
+    event_id: Option<u32>,
+    event_version: Option<u8>,
+    target: Option<String>,
+    timestamp: Option<SystemTime>,
+    observed_timestamp: Option<SystemTime>,
+    trace_context: Option<TraceContextDef>,
+    severity_text: Option<String>,
+    /// The short name of the severity, e.g. `INFO2`.
+    severity_number: Option<String>,
+    body: Option<AnyValueDef>,
+    attributes: Option<Vec<KeyAnyValueDef>>,
+    #[serde(default)]
+    dropped_attributes_count: u32,
+}
+
+impl From<&LogRecord> for LogRecordDef {
+    fn from(record: &LogRecord) -> Self {
+        LogRecordDef {
+            event_name: record.event_name.as_ref().map(ToString::to_string),
+            event_id: record.event_id,
+            event_version: record.event_version,
+            target: record.target.as_ref().map(ToString::to_string),
+            timestamp: record.timestamp,
+            observed_timestamp: record.observed_timestamp,
+            trace_context: record.trace_context.as_ref().map(Into::into),
+            severity_text: record.severity_text.as_ref().map(ToString::to_string),
+            severity_number: record
+                .severity_number
+                .map(|severity| severity.name().to_string()),
+            body: record.body.as_ref().map(Into::into),
+            attributes: record.attributes.as_ref().map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|(key, value)| KeyAnyValueDef {
+                        key: key.to_string(),
+                        value: value.into(),
+                    })
+                    .collect()
+            }),
+            dropped_attributes_count: record.dropped_attributes_count,
+        }
+    }
+}
+
+impl TryFrom<LogRecordDef> for LogRecord {
+    type Error = String;
+
+    fn try_from(record: LogRecordDef) -> Result<Self, Self::Error> {
+        Ok(LogRecord {
+            event_name: record.event_name.map(Into::into),
+            event_id: record.event_id,
+            event_version: record.event_version,
+            target: record.target.map(Into::into),
+            timestamp: record.timestamp,
+            observed_timestamp: record.observed_timestamp,
+            trace_context: record.trace_context.map(TryInto::try_into).transpose()?,
+            severity_text: record.severity_text.map(Into::into),
+            severity_number: record
+                .severity_number
+                .map(|name| {
+                    SEVERITIES
+                        .into_iter()
+                        .find(|severity| severity.name() == name)
+                        .ok_or_else(|| format!("invalid severity {name:?}"))
+                })
+                .transpose()?,
+            body: record.body.map(Into::into),
+            attributes: record.attributes.map(|attributes| {
+                attributes
+                    .into_iter()
+                    .map(|kv| (kv.key.into(), kv.value.into()))
+                    .collect()
+            }),
+            dropped_attributes_count: record.dropped_attributes_count,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TraceContextDef {
+    trace_id: String,
+    span_id: String,
+    trace_flags: Option<u8>,
+}
+
+impl From<&TraceContext> for TraceContextDef {
+    fn from(trace_context: &TraceContext) -> Self {
+        TraceContextDef {
+            trace_id: trace_context.trace_id.to_string(),
+            span_id: trace_context.span_id.to_string(),
+            trace_flags: trace_context.trace_flags.map(TraceFlags::to_u8),
+        }
+    }
+}
+
+impl TryFrom<TraceContextDef> for TraceContext {
+    type Error = String;
+
+    fn try_from(trace_context: TraceContextDef) -> Result<Self, Self::Error> {
+        Ok(TraceContext {
+            trace_id: parse_trace_id(&trace_context.trace_id)?,
+            span_id: parse_span_id(&trace_context.span_id)?,
+            trace_flags: trace_context.trace_flags.map(TraceFlags::new),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyAnyValueDef {
+    key: String,
+    value: AnyValueDef,
+}
+
+#[derive(Serialize, Deserialize)]
+enum AnyValueDef {
+    Int(i64),
+    Double(f64),
+    String(String),
+    Boolean(bool),
+    Bytes(Vec<u8>),
+    ListAny(Vec<AnyValueDef>),
+    Map(BTreeMap<String, AnyValueDef>),
+}
+
+impl From<&AnyValue> for AnyValueDef {
+    fn from(value: &AnyValue) -> Self {
+        match value {
+            AnyValue::Int(v) => AnyValueDef::Int(*v),
+            AnyValue::Double(v) => AnyValueDef::Double(*v),
+            AnyValue::String(v) => AnyValueDef::String(v.to_string()),
+            AnyValue::Boolean(v) => AnyValueDef::Boolean(*v),
+            AnyValue::Bytes(v) => AnyValueDef::Bytes(v.clone()),
+            AnyValue::ListAny(v) => AnyValueDef::ListAny(v.iter().map(Into::into).collect()),
+            AnyValue::Map(v) => AnyValueDef::Map(
+                v.iter()
+                    .map(|(key, value)| (key.to_string(), value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<AnyValueDef> for AnyValue {
+    fn from(value: AnyValueDef) -> Self {
+        match value {
+            AnyValueDef::Int(v) => AnyValue::Int(v),
+            AnyValueDef::Double(v) => AnyValue::Double(v),
+            AnyValueDef::String(v) => AnyValue::String(v.into()),
+            AnyValueDef::Boolean(v) => AnyValue::Boolean(v),
+            AnyValueDef::Bytes(v) => AnyValue::Bytes(v),
+            AnyValueDef::ListAny(v) => AnyValue::ListAny(v.into_iter().map(Into::into).collect()),
+            AnyValueDef::Map(v) => AnyValue::Map(
+                v.into_iter()
+                    .map(|(key, value)| (key.into(), value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use opentelemetry::logs::{AnyValue, LogRecord as _, Severity};
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId};
+    use opentelemetry::Key;
+
+    use crate::logs::{LogRecord, TraceContext};
+
+    #[test]
+    fn log_record_json_roundtrip() {
+        let mut record = LogRecord::default();
+        record.set_target("target");
+        record.set_event_id(7);
+        record.set_timestamp(opentelemetry::time::now());
+        record.set_severity_number(Severity::Warn2);
+        record.set_body(AnyValue::Map(HashMap::from([
+            (Key::new("b"), AnyValue::Int(2)),
+            (
+                Key::new("a"),
+                AnyValue::ListAny(vec![AnyValue::Boolean(true), AnyValue::Bytes(vec![1])]),
+            ),
+        ])));
+        record.add_attribute("double", 1.5);
+        record.trace_context = Some(TraceContext {
+            trace_id: TraceId::from_u128(1),
+            span_id: SpanId::from_u64(2),
+            trace_flags: Some(TraceFlags::SAMPLED),
+        });
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""severity_number":"WARN2""#));
+        // map entries are sorted by key
+        assert!(json.contains(r#"{"Map":{"a":"#));
+
+        let roundtrip: LogRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(roundtrip.target, record.target);
+        assert_eq!(roundtrip.event_id, Some(7));
+        assert_eq!(roundtrip.event_version, None);
+        assert_eq!(roundtrip.timestamp, record.timestamp);
+        assert_eq!(roundtrip.severity_number, Some(Severity::Warn2));
+        assert_eq!(roundtrip.body, record.body);
+        assert_eq!(roundtrip.attributes, record.attributes);
+        assert_eq!(roundtrip.trace_context, record.trace_context);
+    }
+}
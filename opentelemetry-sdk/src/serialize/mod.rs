@@ -0,0 +1,27 @@
+//! Serde support for exported telemetry.
+//!
+//! [`SpanData`] and [`LogRecord`] are (de)serialized through private mirror
+//! types, as the API types they contain don't implement serde's traits. The
+//! representation is meant for snapshot testing instrumentation libraries,
+//! e.g. with `insta`, ids are written as hex strings and maps are sorted by
+//! key so that snapshots are deterministic.
+//!
+//! [`SpanData`]: crate::export::trace::SpanData
+//! [`LogRecord`]: crate::logs::LogRecord
+#[cfg(any(feature = "trace", feature = "logs"))]
+use opentelemetry::trace::{SpanId, TraceId};
+
+#[cfg(feature = "logs")]
+mod logs;
+#[cfg(feature = "trace")]
+mod trace;
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+fn parse_trace_id(hex: &str) -> Result<TraceId, String> {
+    TraceId::from_hex(hex).map_err(|err| format!("invalid trace id {hex:?}: {err}"))
+}
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+fn parse_span_id(hex: &str) -> Result<SpanId, String> {
+    SpanId::from_hex(hex).map_err(|err| format!("invalid span id {hex:?}: {err}"))
+}
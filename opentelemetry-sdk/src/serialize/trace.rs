@@ -0,0 +1,457 @@
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use opentelemetry::trace::{Event, Link, SpanContext, SpanKind, Status, TraceFlags, TraceState};
+use opentelemetry::{Array, KeyValue, Value};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{parse_span_id, parse_trace_id};
+use crate::export::trace::SpanData;
+use crate::trace::{SpanEvents, SpanLinks};
+use crate::{InstrumentationLibrary, Resource};
+
+impl Serialize for SpanData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SpanDataDef::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SpanData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SpanDataDef::deserialize(deserializer)?
+            .try_into()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SpanDataDef {
+    span_context: SpanContextDef,
+    parent_span_id: String,
+    span_kind: SpanKindDef,
+    name: String,
+    start_time: SystemTime,
+    end_time: SystemTime,
+    attributes: Vec<KeyValueDef>,
+    dropped_attributes_count: u32,
+    events: Vec<EventDef>,
+    dropped_events_count: u32,
+    links: Vec<LinkDef>,
+    dropped_links_count: u32,
+    status: StatusDef,
+    instrumentation_lib: InstrumentationLibraryDef,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource: Option<ResourceDef>,
+}
+
+impl From<&SpanData> for SpanDataDef {
+    fn from(span: &SpanData) -> Self {
+        SpanDataDef {
+            span_context: (&span.span_context).into(),
+            parent_span_id: span.parent_span_id.to_string(),
+            span_kind: (&span.span_kind).into(),
+            name: span.name.to_string(),
+            start_time: span.start_time,
+            end_time: span.end_time,
+            attributes: span.attributes.iter().map(Into::into).collect(),
+            dropped_attributes_count: span.dropped_attributes_count,
+            events: span.events.iter().map(Into::into).collect(),
+            dropped_events_count: span.events.dropped_count,
+            links: span.links.iter().map(Into::into).collect(),
+            dropped_links_count: span.links.dropped_count,
+            status: (&span.status).into(),
+            instrumentation_lib: (&span.instrumentation_lib).into(),
+            resource: span.resource.as_ref().map(Into::into),
+        }
+    }
+}
+
+impl TryFrom<SpanDataDef> for SpanData {
+    type Error = String;
+
+    fn try_from(span: SpanDataDef) -> Result<Self, Self::Error> {
+        Ok(SpanData {
+            span_context: span.span_context.try_into()?,
+            parent_span_id: parse_span_id(&span.parent_span_id)?,
+            span_kind: span.span_kind.into(),
+            name: span.name.into(),
+            start_time: span.start_time,
+            end_time: span.end_time,
+            attributes: span.attributes.into_iter().map(Into::into).collect(),
+            dropped_attributes_count: span.dropped_attributes_count,
+            events: SpanEvents {
+                events: span.events.into_iter().map(Into::into).collect(),
+                dropped_count: span.dropped_events_count,
+            },
+            links: SpanLinks {
+                links: span
+                    .links
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+                dropped_count: span.dropped_links_count,
+            },
+            status: span.status.into(),
+            instrumentation_lib: span.instrumentation_lib.into(),
+            resource: span.resource.map(Into::into),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SpanContextDef {
+    trace_id: String,
+    span_id: String,
+    trace_flags: u8,
+    is_remote: bool,
+    trace_state: String,
+}
+
+impl From<&SpanContext> for SpanContextDef {
+    fn from(span_context: &SpanContext) -> Self {
+        SpanContextDef {
+            trace_id: span_context.trace_id().to_string(),
+            span_id: span_context.span_id().to_string(),
+            trace_flags: span_context.trace_flags().to_u8(),
+            is_remote: span_context.is_remote(),
+            trace_state: span_context.trace_state().header(),
+        }
+    }
+}
+
+impl TryFrom<SpanContextDef> for SpanContext {
+    type Error = String;
+
+    fn try_from(span_context: SpanContextDef) -> Result<Self, Self::Error> {
+        Ok(SpanContext::new(
+            parse_trace_id(&span_context.trace_id)?,
+            parse_span_id(&span_context.span_id)?,
+            TraceFlags::new(span_context.trace_flags),
+            span_context.is_remote,
+            TraceState::from_str(&span_context.trace_state).map_err(|err| err.to_string())?,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum SpanKindDef {
+    Client,
+    Server,
+    Producer,
+    Consumer,
+    Internal,
+}
+
+impl From<&SpanKind> for SpanKindDef {
+    fn from(span_kind: &SpanKind) -> Self {
+        match span_kind {
+            SpanKind::Client => SpanKindDef::Client,
+            SpanKind::Server => SpanKindDef::Server,
+            SpanKind::Producer => SpanKindDef::Producer,
+            SpanKind::Consumer => SpanKindDef::Consumer,
+            SpanKind::Internal => SpanKindDef::Internal,
+        }
+    }
+}
+
+impl From<SpanKindDef> for SpanKind {
+    fn from(span_kind: SpanKindDef) -> Self {
+        match span_kind {
+            SpanKindDef::Client => SpanKind::Client,
+            SpanKindDef::Server => SpanKind::Server,
+            SpanKindDef::Producer => SpanKind::Producer,
+            SpanKindDef::Consumer => SpanKind::Consumer,
+            SpanKindDef::Internal => SpanKind::Internal,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum StatusDef {
+    Unset,
+    Error { description: String },
+    Ok,
+}
+
+impl From<&Status> for StatusDef {
+    fn from(status: &Status) -> Self {
+        match status {
+            Status::Unset => StatusDef::Unset,
+            Status::Error { description } => StatusDef::Error {
+                description: description.to_string(),
+            },
+            Status::Ok => StatusDef::Ok,
+        }
+    }
+}
+
+impl From<StatusDef> for Status {
+    fn from(status: StatusDef) -> Self {
+        match status {
+            StatusDef::Unset => Status::Unset,
+            StatusDef::Error { description } => Status::error(description),
+            StatusDef::Ok => Status::Ok,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EventDef {
+    name: String,
+    timestamp: SystemTime,
+    attributes: Vec<KeyValueDef>,
+    dropped_attributes_count: u32,
+}
+
+impl From<&Event> for EventDef {
+    fn from(event: &Event) -> Self {
+        EventDef {
+            name: event.name.to_string(),
+            timestamp: event.timestamp,
+            attributes: event.attributes.iter().map(Into::into).collect(),
+            dropped_attributes_count: event.dropped_attributes_count,
+        }
+    }
+}
+
+impl From<EventDef> for Event {
+    fn from(event: EventDef) -> Self {
+        Event::new(
+            event.name,
+            event.timestamp,
+            event.attributes.into_iter().map(Into::into).collect(),
+            event.dropped_attributes_count,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LinkDef {
+    span_context: SpanContextDef,
+    attributes: Vec<KeyValueDef>,
+    dropped_attributes_count: u32,
+}
+
+impl From<&Link> for LinkDef {
+    fn from(link: &Link) -> Self {
+        LinkDef {
+            span_context: (&link.span_context).into(),
+            attributes: link.attributes.iter().map(Into::into).collect(),
+            dropped_attributes_count: link.dropped_attributes_count,
+        }
+    }
+}
+
+impl TryFrom<LinkDef> for Link {
+    type Error = String;
+
+    fn try_from(link: LinkDef) -> Result<Self, Self::Error> {
+        Ok(Link::new(
+            link.span_context.try_into()?,
+            link.attributes.into_iter().map(Into::into).collect(),
+            link.dropped_attributes_count,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct InstrumentationLibraryDef {
+    name: String,
+    version: Option<String>,
+    schema_url: Option<String>,
+    attributes: Vec<KeyValueDef>,
+}
+
+impl From<&InstrumentationLibrary> for InstrumentationLibraryDef {
+    fn from(library: &InstrumentationLibrary) -> Self {
+        InstrumentationLibraryDef {
+            name: library.name.to_string(),
+            version: library.version.as_ref().map(ToString::to_string),
+            schema_url: library.schema_url.as_ref().map(ToString::to_string),
+            attributes: library.attributes.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<InstrumentationLibraryDef> for InstrumentationLibrary {
+    fn from(library: InstrumentationLibraryDef) -> Self {
+        let mut builder = InstrumentationLibrary::builder(library.name)
+            .with_attributes(library.attributes.into_iter().map(KeyValue::from));
+        if let Some(version) = library.version {
+            builder = builder.with_version(version);
+        }
+        if let Some(schema_url) = library.schema_url {
+            builder = builder.with_schema_url(schema_url);
+        }
+        builder.build()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResourceDef {
+    attributes: Vec<KeyValueDef>,
+    schema_url: Option<String>,
+}
+
+impl From<&Resource> for ResourceDef {
+    fn from(resource: &Resource) -> Self {
+        ResourceDef {
+            attributes: resource
+                .iter()
+                .map(|(key, value)| (&KeyValue::new(key.clone(), value.clone())).into())
+                .collect(),
+            schema_url: resource.schema_url().map(ToString::to_string),
+        }
+    }
+}
+
+impl From<ResourceDef> for Resource {
+    fn from(resource: ResourceDef) -> Self {
+        let attributes = resource.attributes.into_iter().map(KeyValue::from);
+        match resource.schema_url {
+            Some(schema_url) => Resource::from_schema_url(attributes, schema_url),
+            None => Resource::new(attributes),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyValueDef {
+    key: String,
+    value: ValueDef,
+}
+
+impl From<&KeyValue> for KeyValueDef {
+    fn from(kv: &KeyValue) -> Self {
+        KeyValueDef {
+            key: kv.key.to_string(),
+            value: (&kv.value).into(),
+        }
+    }
+}
+
+impl From<KeyValueDef> for KeyValue {
+    fn from(kv: KeyValueDef) -> Self {
+        KeyValue::new(kv.key, Value::from(kv.value))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum ValueDef {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    String(String),
+    Duration(Duration),
+    BoolArray(Vec<bool>),
+    I64Array(Vec<i64>),
+    F64Array(Vec<f64>),
+    StringArray(Vec<String>),
+}
+
+impl From<&Value> for ValueDef {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Bool(v) => ValueDef::Bool(*v),
+            Value::I64(v) => ValueDef::I64(*v),
+            Value::F64(v) => ValueDef::F64(*v),
+            Value::String(v) => ValueDef::String(v.to_string()),
+            Value::Duration(v) => ValueDef::Duration(*v),
+            Value::Array(Array::Bool(v)) => ValueDef::BoolArray(v.clone()),
+            Value::Array(Array::I64(v)) => ValueDef::I64Array(v.clone()),
+            Value::Array(Array::F64(v)) => ValueDef::F64Array(v.clone()),
+            Value::Array(Array::String(v)) => {
+                ValueDef::StringArray(v.iter().map(ToString::to_string).collect())
+            }
+        }
+    }
+}
+
+impl From<ValueDef> for Value {
+    fn from(value: ValueDef) -> Self {
+        match value {
+            ValueDef::Bool(v) => Value::Bool(v),
+            ValueDef::I64(v) => Value::I64(v),
+            ValueDef::F64(v) => Value::F64(v),
+            ValueDef::String(v) => Value::String(v.into()),
+            ValueDef::Duration(v) => Value::Duration(v),
+            ValueDef::BoolArray(v) => Value::Array(Array::Bool(v)),
+            ValueDef::I64Array(v) => Value::Array(Array::I64(v)),
+            ValueDef::F64Array(v) => Value::Array(Array::F64(v)),
+            ValueDef::StringArray(v) => {
+                Value::Array(Array::String(v.into_iter().map(Into::into).collect()))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use opentelemetry::trace::{
+        Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+    };
+    use opentelemetry::{Array, KeyValue, Value};
+
+    use crate::export::trace::SpanData;
+    use crate::testing::trace::new_test_export_span_data;
+    use crate::{InstrumentationLibrary, Resource};
+
+    #[test]
+    fn span_data_json_roundtrip() {
+        let mut span = new_test_export_span_data();
+        span.span_kind = SpanKind::Client;
+        span.parent_span_id = SpanId::from_u64(42);
+        span.attributes = vec![
+            KeyValue::new("bool", true),
+            KeyValue::new("int", 7),
+            KeyValue::new("float", 1.5),
+            KeyValue::new("string", "value"),
+            KeyValue::new(
+                "strings",
+                Value::Array(Array::String(vec!["a".into(), "b".into()])),
+            ),
+        ];
+        span.events.events.push(Event::new(
+            "event",
+            span.start_time,
+            vec![KeyValue::new("event.key", 1)],
+            2,
+        ));
+        span.links.links.push(Link::new(
+            SpanContext::new(
+                TraceId::from_u128(2),
+                SpanId::from_u64(3),
+                TraceFlags::default(),
+                true,
+                TraceState::from_key_value([("vendor", "value")]).unwrap(),
+            ),
+            vec![],
+            0,
+        ));
+        span.links.dropped_count = 1;
+        span.status = Status::error("failed");
+        span.instrumentation_lib = InstrumentationLibrary::builder("library")
+            .with_version("1.0.0")
+            .build();
+        span.resource = Some(Resource::new([KeyValue::new("service.name", "upstream")]));
+
+        let json = serde_json::to_value(&span).unwrap();
+        assert_eq!(
+            json["span_context"]["trace_id"],
+            "00000000000000000000000000000001"
+        );
+        assert_eq!(json["span_kind"], "Client");
+        assert_eq!(json["attributes"][1]["value"]["I64"], 7);
+
+        let roundtrip: SpanData = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, span);
+        assert_eq!(
+            roundtrip.links[0].span_context.trace_state().header(),
+            "vendor=value"
+        );
+        assert_eq!(
+            roundtrip.instrumentation_lib.version.as_deref(),
+            Some("1.0.0")
+        );
+    }
+}
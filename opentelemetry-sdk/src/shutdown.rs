@@ -0,0 +1,152 @@
+//! Shutting down providers and processors within a timeout.
+use std::time::Duration;
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+use std::{
+    future::Future,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread::{self, Thread},
+    time::Instant,
+};
+
+use thiserror::Error;
+
+use crate::runtime::TrySendError;
+
+/// The result of shutting down a provider or processor with a timeout.
+pub type ShutdownResult = Result<(), ShutdownError>;
+
+/// Errors returned when shutting down a provider or processor with a timeout.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownError {
+    /// The provider or processor was already shut down.
+    #[error("already shut down")]
+    AlreadyShutdown,
+
+    /// The shutdown did not complete within the timeout.
+    ///
+    /// Telemetry which was not exported yet may be lost, the shutdown keeps
+    /// going in the background.
+    #[error("shutdown did not complete within {0:?}")]
+    Timeout(Duration),
+
+    /// The shutdown failed, e.g. because the last export failed.
+    #[error("shutdown failed: {0}")]
+    InternalFailure(String),
+}
+
+impl From<TrySendError> for ShutdownError {
+    fn from(err: TrySendError) -> Self {
+        match err {
+            // the worker already stopped
+            TrySendError::ChannelClosed => ShutdownError::AlreadyShutdown,
+            err => ShutdownError::InternalFailure(err.to_string()),
+        }
+    }
+}
+
+/// The remaining time until `deadline`.
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub(crate) fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
+}
+
+/// Combines the results of shutting down several processors, a timeout
+/// takes precedence over other failures.
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub(crate) fn combine(results: impl IntoIterator<Item = ShutdownResult>) -> ShutdownResult {
+    let mut timeout = None;
+    let mut failures = Vec::new();
+    for result in results {
+        match result {
+            Ok(()) => {}
+            Err(ShutdownError::Timeout(elapsed)) => timeout = Some(elapsed),
+            Err(err) => failures.push(err.to_string()),
+        }
+    }
+
+    match timeout {
+        Some(elapsed) => Err(ShutdownError::Timeout(elapsed)),
+        None if failures.is_empty() => Ok(()),
+        None => Err(ShutdownError::InternalFailure(failures.join(", "))),
+    }
+}
+
+/// Blocks the calling thread until `future` completes, at most `timeout`.
+///
+/// The future is polled on the calling thread like
+/// `futures_executor::block_on`, but the thread stops waiting once the
+/// timeout elapsed. This bounds the shutdown of a processor whose worker can
+/// not make progress, e.g. because it runs on the runtime driven by the
+/// calling thread.
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub(crate) fn block_on_with_timeout<F: Future>(
+    future: F,
+    timeout: Duration,
+) -> Result<F::Output, ShutdownError> {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Ok(output);
+        }
+        let remaining = remaining(deadline);
+        if remaining.is_zero() {
+            return Err(ShutdownError::Timeout(timeout));
+        }
+        // wakes up early when the future is woken, or spuriously
+        thread::park_timeout(remaining);
+    }
+}
+
+#[cfg(all(test, any(feature = "trace", feature = "logs", feature = "metrics")))]
+mod tests {
+    use std::time::Duration;
+
+    use futures_channel::oneshot;
+
+    use super::{block_on_with_timeout, combine, ShutdownError};
+
+    #[test]
+    fn block_on_with_timeout_completes_or_times_out() {
+        let (sender, receiver) = oneshot::channel();
+        std::thread::spawn(move || sender.send(1));
+        assert_eq!(
+            block_on_with_timeout(receiver, Duration::from_secs(5)),
+            Ok(Ok(1))
+        );
+
+        let (_sender, receiver) = oneshot::channel::<()>();
+        assert_eq!(
+            block_on_with_timeout(receiver, Duration::from_millis(10)),
+            Err(ShutdownError::Timeout(Duration::from_millis(10)))
+        );
+    }
+
+    #[test]
+    fn combine_prefers_timeouts() {
+        assert_eq!(combine([Ok(()), Ok(())]), Ok(()));
+        assert_eq!(
+            combine([
+                Err(ShutdownError::InternalFailure("export failed".into())),
+                Err(ShutdownError::Timeout(Duration::from_secs(1))),
+            ]),
+            Err(ShutdownError::Timeout(Duration::from_secs(1)))
+        );
+        assert_eq!(
+            combine([Err(ShutdownError::AlreadyShutdown), Ok(())]),
+            Err(ShutdownError::InternalFailure("already shut down".into()))
+        );
+    }
+}
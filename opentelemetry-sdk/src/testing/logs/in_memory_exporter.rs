@@ -2,7 +2,7 @@ use crate::export::logs::{LogData, LogExporter};
 use crate::logs::LogRecord;
 use crate::Resource;
 use async_trait::async_trait;
-use opentelemetry::logs::{LogError, LogResult};
+use opentelemetry::logs::{LogError, LogResult, Severity};
 use opentelemetry::InstrumentationLibrary;
 use std::borrow::Cow;
 use std::sync::{Arc, Mutex};
@@ -11,7 +11,8 @@ use std::sync::{Arc, Mutex};
 ///
 /// This exporter is useful for testing and debugging purposes.
 /// It stores logs in a `Vec<LogData>`. Logs can be retrieved using
-/// `get_emitted_logs` method.
+/// `get_emitted_logs` method, or queried with `logs_matching`,
+/// `logs_for_target` and `logs_with_severity_at_least`.
 ///
 /// # Example
 /// ```no_run
@@ -28,7 +29,7 @@ use std::sync::{Arc, Mutex};
 ///        .with_log_processor(BatchLogProcessor::builder(exporter.clone(), runtime::Tokio).build())
 ///        .build();
 ///    // Setup Log Appenders and emit logs. (Not shown here)
-///    logger_provider.force_flush();
+///    logger_provider.force_flush().unwrap();
 
 ///    let emitted_logs = exporter.get_emitted_logs().unwrap();
 ///    for log in emitted_logs {
@@ -79,7 +80,7 @@ pub struct LogDataWithResource {
 ///        .with_log_processor(BatchLogProcessor::builder(exporter.clone(), runtime::Tokio).build())
 ///        .build();
 ///    // Setup Log Appenders and emit logs. (Not shown here)
-///    logger_provider.force_flush();
+///    logger_provider.force_flush().unwrap();
 ///    let emitted_logs = exporter.get_emitted_logs().unwrap();
 ///    for log in emitted_logs {
 ///        println!("{:?}", log);
@@ -140,6 +141,40 @@ impl InMemoryLogsExporter {
     /// ```
     ///
     pub fn get_emitted_logs(&self) -> LogResult<Vec<LogDataWithResource>> {
+        self.logs_matching(|_| true)
+    }
+
+    /// Returns the emitted logs for which `predicate` returns `true`, in the
+    /// order they were exported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentelemetry::logs::{LogRecord as _, Logger as _, LoggerProvider as _};
+    /// use opentelemetry_sdk::logs::LoggerProvider;
+    /// use opentelemetry_sdk::testing::logs::InMemoryLogsExporter;
+    ///
+    /// let exporter = InMemoryLogsExporter::default();
+    /// let provider = LoggerProvider::builder()
+    ///     .with_simple_exporter(exporter.clone())
+    ///     .build();
+    /// let logger = provider.logger("test");
+    ///
+    /// for body in ["started", "failed"] {
+    ///     let mut record = logger.create_log_record();
+    ///     record.set_body(body.into());
+    ///     logger.emit(record);
+    /// }
+    ///
+    /// let failures = exporter
+    ///     .logs_matching(|log| log.record.body == Some("failed".into()))
+    ///     .unwrap();
+    /// assert_eq!(failures.len(), 1);
+    /// ```
+    pub fn logs_matching<P>(&self, predicate: P) -> LogResult<Vec<LogDataWithResource>>
+    where
+        P: Fn(&LogDataWithResource) -> bool,
+    {
         let logs_guard = self.logs.lock().map_err(LogError::from)?;
         let resource_guard = self.resource.lock().map_err(LogError::from)?;
         let logs: Vec<LogDataWithResource> = logs_guard
@@ -149,10 +184,97 @@ impl InMemoryLogsExporter {
                 resource: Cow::Owned(resource_guard.clone()),
                 instrumentation: log_data.instrumentation.clone(),
             })
+            .filter(|log| predicate(log))
             .collect();
 
         Ok(logs)
     }
+
+    /// Returns the emitted logs whose target is `target`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentelemetry::logs::{LogRecord as _, Logger as _, LoggerProvider as _};
+    /// use opentelemetry_sdk::logs::LoggerProvider;
+    /// use opentelemetry_sdk::testing::logs::InMemoryLogsExporter;
+    ///
+    /// let exporter = InMemoryLogsExporter::default();
+    /// let provider = LoggerProvider::builder()
+    ///     .with_simple_exporter(exporter.clone())
+    ///     .build();
+    /// let logger = provider.logger("test");
+    ///
+    /// let mut record = logger.create_log_record();
+    /// record.set_target("my_app::db");
+    /// logger.emit(record);
+    /// logger.emit(logger.create_log_record());
+    ///
+    /// assert_eq!(exporter.logs_for_target("my_app::db").unwrap().len(), 1);
+    /// ```
+    pub fn logs_for_target(&self, target: &str) -> LogResult<Vec<LogDataWithResource>> {
+        self.logs_matching(|log| log.record.target.as_deref() == Some(target))
+    }
+
+    /// Returns the emitted logs with a severity of at least `severity`. Logs
+    /// without a severity number are not returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentelemetry::logs::{LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+    /// use opentelemetry_sdk::logs::LoggerProvider;
+    /// use opentelemetry_sdk::testing::logs::InMemoryLogsExporter;
+    ///
+    /// let exporter = InMemoryLogsExporter::default();
+    /// let provider = LoggerProvider::builder()
+    ///     .with_simple_exporter(exporter.clone())
+    ///     .build();
+    /// let logger = provider.logger("test");
+    ///
+    /// for severity in [Severity::Info, Severity::Warn, Severity::Error] {
+    ///     let mut record = logger.create_log_record();
+    ///     record.set_severity_number(severity);
+    ///     logger.emit(record);
+    /// }
+    ///
+    /// let logs = exporter.logs_with_severity_at_least(Severity::Warn).unwrap();
+    /// assert_eq!(logs.len(), 2);
+    /// ```
+    pub fn logs_with_severity_at_least(
+        &self,
+        severity: Severity,
+    ) -> LogResult<Vec<LogDataWithResource>> {
+        self.logs_matching(
+            |log| matches!(log.record.severity_number, Some(number) if number >= severity),
+        )
+    }
+
+    /// Returns the emitted logs and clears the internal storage, so that the
+    /// next call only returns logs exported in between.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentelemetry_sdk::testing::logs::InMemoryLogsExporter;
+    ///
+    /// let exporter = InMemoryLogsExporter::default();
+    /// let emitted_logs = exporter.take_emitted_logs().unwrap();
+    /// assert!(exporter.get_emitted_logs().unwrap().is_empty());
+    /// ```
+    pub fn take_emitted_logs(&self) -> LogResult<Vec<LogDataWithResource>> {
+        let logs = std::mem::take(&mut *self.logs.lock().map_err(LogError::from)?);
+        let resource_guard = self.resource.lock().map_err(LogError::from)?;
+        Ok(logs
+            .into_iter()
+            .map(|log_data| LogDataWithResource {
+                record: log_data.record,
+                resource: Cow::Owned(resource_guard.clone()),
+                instrumentation: log_data.instrumentation,
+            })
+            .collect())
+    }
+
     /// Clears the internal (in-memory) storage of logs.
     ///
     /// # Example
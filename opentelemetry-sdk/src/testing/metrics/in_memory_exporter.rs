@@ -1,10 +1,10 @@
-use crate::metrics::data::{Histogram, Metric, ResourceMetrics, ScopeMetrics, Temporality};
+use crate::metrics::data::{ResourceMetrics, Temporality};
 use crate::metrics::exporter::PushMetricsExporter;
 use crate::metrics::reader::{
     AggregationSelector, DefaultAggregationSelector, DefaultTemporalitySelector,
     TemporalitySelector,
 };
-use crate::metrics::{data, Aggregation, InstrumentKind};
+use crate::metrics::{Aggregation, InstrumentKind};
 use async_trait::async_trait;
 use opentelemetry::metrics::MetricsError;
 use opentelemetry::metrics::Result;
@@ -194,79 +194,8 @@ impl InMemoryMetricsExporter {
     }
 
     fn clone_metrics(metric: &ResourceMetrics) -> ResourceMetrics {
-        ResourceMetrics {
-            resource: metric.resource.clone(),
-            scope_metrics: metric
-                .scope_metrics
-                .iter()
-                .map(|scope_metric| ScopeMetrics {
-                    scope: scope_metric.scope.clone(),
-                    metrics: scope_metric
-                        .metrics
-                        .iter()
-                        .map(|metric| Metric {
-                            name: metric.name.clone(),
-                            description: metric.description.clone(),
-                            unit: metric.unit.clone(),
-                            // we don't expect any unknown data type here
-                            data: Self::clone_data(metric.data.as_ref()).unwrap(),
-                        })
-                        .collect(),
-                })
-                .collect(),
-        }
-    }
-
-    fn clone_data(data: &dyn data::Aggregation) -> Option<Box<dyn data::Aggregation>> {
-        if let Some(hist) = data.as_any().downcast_ref::<Histogram<i64>>() {
-            Some(Box::new(Histogram {
-                data_points: hist.data_points.clone(),
-                temporality: hist.temporality,
-            }))
-        } else if let Some(hist) = data.as_any().downcast_ref::<Histogram<f64>>() {
-            Some(Box::new(Histogram {
-                data_points: hist.data_points.clone(),
-                temporality: hist.temporality,
-            }))
-        } else if let Some(hist) = data.as_any().downcast_ref::<Histogram<u64>>() {
-            Some(Box::new(Histogram {
-                data_points: hist.data_points.clone(),
-                temporality: hist.temporality,
-            }))
-        } else if let Some(sum) = data.as_any().downcast_ref::<data::Sum<i64>>() {
-            Some(Box::new(data::Sum {
-                data_points: sum.data_points.clone(),
-                temporality: sum.temporality,
-                is_monotonic: sum.is_monotonic,
-            }))
-        } else if let Some(sum) = data.as_any().downcast_ref::<data::Sum<f64>>() {
-            Some(Box::new(data::Sum {
-                data_points: sum.data_points.clone(),
-                temporality: sum.temporality,
-                is_monotonic: sum.is_monotonic,
-            }))
-        } else if let Some(sum) = data.as_any().downcast_ref::<data::Sum<u64>>() {
-            Some(Box::new(data::Sum {
-                data_points: sum.data_points.clone(),
-                temporality: sum.temporality,
-                is_monotonic: sum.is_monotonic,
-            }))
-        } else if let Some(gauge) = data.as_any().downcast_ref::<data::Gauge<i64>>() {
-            Some(Box::new(data::Gauge {
-                data_points: gauge.data_points.clone(),
-            }))
-        } else if let Some(gauge) = data.as_any().downcast_ref::<data::Gauge<f64>>() {
-            Some(Box::new(data::Gauge {
-                data_points: gauge.data_points.clone(),
-            }))
-        } else if let Some(gauge) = data.as_any().downcast_ref::<data::Gauge<u64>>() {
-            Some(Box::new(data::Gauge {
-                data_points: gauge.data_points.clone(),
-            }))
-        } else {
-            // unknown data type
-            None
-        }
+        // we don't expect any unknown data type here
+        metric.try_clone().unwrap()
     }
 }
 
@@ -33,6 +33,7 @@ pub fn new_test_export_span_data() -> SpanData {
         links: SpanLinks::default(),
         status: Status::Unset,
         instrumentation_lib: InstrumentationLibrary::default(),
+        resource: None,
     }
 }
 
@@ -120,3 +121,26 @@ impl SpanExporter for NoopSpanExporter {
         Box::pin(std::future::ready(Ok(())))
     }
 }
+
+/// A [`SpanExporter`] failing every export with a "cannot export" error.
+///
+/// [`SpanExporter`]: crate::export::trace::SpanExporter
+#[derive(Debug, Default)]
+pub struct FailingSpanExporter {
+    _private: (),
+}
+
+impl FailingSpanExporter {
+    /// Create a new failing span exporter
+    pub fn new() -> Self {
+        FailingSpanExporter { _private: () }
+    }
+}
+
+impl SpanExporter for FailingSpanExporter {
+    fn export(&mut self, _: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        Box::pin(std::future::ready(Err(
+            opentelemetry::trace::TraceError::from("cannot export"),
+        )))
+    }
+}
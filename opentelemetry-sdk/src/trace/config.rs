@@ -75,6 +75,27 @@ impl Config {
         self
     }
 
+    /// Specify the number of bytes the attributes, events and links of a span
+    /// can take, see [`SpanLimits::max_bytes_per_span`].
+    pub fn with_max_bytes_per_span(mut self, max_bytes: u32) -> Self {
+        self.span_limits.max_bytes_per_span = Some(max_bytes);
+        self
+    }
+
+    /// Specify the sanitizer applied to attribute string values when they are
+    /// recorded, see [`SpanLimits::attribute_sanitizer`].
+    pub fn with_attribute_sanitizer(mut self, sanitizer: crate::AttributeSanitizer) -> Self {
+        self.span_limits.attribute_sanitizer = Some(sanitizer);
+        self
+    }
+
+    /// Specify the max length of attribute string values, see
+    /// [`SpanLimits::max_attribute_value_length`].
+    pub fn with_max_attribute_value_length(mut self, max_length: u32) -> Self {
+        self.span_limits.max_attribute_value_length = Some(max_length);
+        self
+    }
+
     /// Specify all limit via the span_limits
     pub fn with_span_limits(mut self, span_limits: SpanLimits) -> Self {
         self.span_limits = span_limits;
@@ -119,6 +140,15 @@ impl Default for Config {
             config.span_limits.max_links_per_span = max_links_per_span;
         }
 
+        // The span specific limit takes precedence over the general one
+        if let Some(max_length) = env::var("OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT")
+            .or_else(|_| env::var("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT"))
+            .ok()
+            .and_then(|length_limit| u32::from_str(&length_limit).ok())
+        {
+            config.span_limits.max_attribute_value_length = Some(max_length);
+        }
+
         let sampler_arg = env::var("OTEL_TRACES_SAMPLER_ARG").ok();
         if let Ok(sampler) = env::var("OTEL_TRACES_SAMPLER") {
             config.sampler = match sampler.as_str() {
@@ -188,3 +218,62 @@ impl Default for Config {
         config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_limits_from_env() {
+        temp_env::with_vars(
+            [
+                ("OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT", Some("10")),
+                ("OTEL_SPAN_EVENT_COUNT_LIMIT", Some("11")),
+                ("OTEL_SPAN_LINK_COUNT_LIMIT", Some("12")),
+                ("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT", Some("256")),
+                ("OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT", None),
+            ],
+            || {
+                let span_limits = Config::default().span_limits;
+                assert_eq!(span_limits.max_attributes_per_span, 10);
+                assert_eq!(span_limits.max_events_per_span, 11);
+                assert_eq!(span_limits.max_links_per_span, 12);
+                assert_eq!(span_limits.max_attribute_value_length, Some(256));
+
+                // programmatic limits take precedence
+                let span_limits = Config::default()
+                    .with_max_attributes_per_span(1)
+                    .with_max_attribute_value_length(2)
+                    .span_limits;
+                assert_eq!(span_limits.max_attributes_per_span, 1);
+                assert_eq!(span_limits.max_attribute_value_length, Some(2));
+            },
+        );
+
+        temp_env::with_vars(
+            [
+                ("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT", Some("256")),
+                ("OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT", Some("64")),
+            ],
+            || {
+                assert_eq!(
+                    Config::default().span_limits.max_attribute_value_length,
+                    Some(64)
+                );
+            },
+        );
+
+        temp_env::with_vars_unset(
+            [
+                "OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+                "OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+            ],
+            || {
+                assert_eq!(
+                    Config::default().span_limits.max_attribute_value_length,
+                    None
+                );
+            },
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! # Load Shedding
+//!
+//! A [`LoadShedder`] runs before every span is created and can force the span
+//! to be non-recording, e.g. when a burst of spans would otherwise cost the
+//! application latency or memory. Shed spans skip span start hooks, the
+//! sampler and span processors, and their span context is propagated as not
+//! sampled.
+use opentelemetry::trace::SpanKind;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Decides whether a span is shed, i.e. started as a non-recording span,
+/// registered with [`Builder::with_load_shedder`].
+///
+/// Shedders run synchronously on the thread starting the span, before the
+/// sampling decision, so implementations should return quickly. A shedder can
+/// e.g. rate limit spans with a [`TokenBucketShedder`] or shed spans while the
+/// process memory is above a watermark.
+///
+/// [`Builder::with_load_shedder`]: crate::trace::Builder::with_load_shedder
+pub trait LoadShedder: Send + Sync + Debug {
+    /// Returns `true` if the span with the given name and kind should be
+    /// shed.
+    fn should_shed(&self, name: &str, span_kind: &SpanKind) -> bool;
+}
+
+/// A [`LoadShedder`] rate limiting span creation with a token bucket.
+///
+/// The bucket holds up to `burst` tokens and is refilled with
+/// `spans_per_second` tokens per second. Each span takes a token, spans
+/// started while the bucket is empty are shed.
+///
+/// The bucket is a single atomic updated without locking, spans started
+/// concurrently on several threads do not contend on a mutex.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_sdk::trace::{TokenBucketShedder, TracerProvider};
+///
+/// // Record at most 1000 spans per second, with bursts of up to 100 spans.
+/// let provider = TracerProvider::builder()
+///     .with_load_shedder(TokenBucketShedder::new(1000, 100))
+///     .build();
+/// # drop(provider);
+/// ```
+#[derive(Debug)]
+pub struct TokenBucketShedder {
+    /// The nanoseconds it takes to refill a token, `0` if the bucket is never
+    /// refilled.
+    interval: u64,
+    /// The nanoseconds it takes to refill the whole bucket.
+    capacity: u64,
+    start: Instant,
+    /// The nanoseconds since `start` at which the bucket is full again. The
+    /// bucket holds `(capacity - (full_at - now)) / interval` tokens.
+    full_at: AtomicU64,
+}
+
+impl TokenBucketShedder {
+    /// Create a shedder allowing `spans_per_second` spans per second on
+    /// average and bursts of up to `burst` spans.
+    pub fn new(spans_per_second: u32, burst: u32) -> Self {
+        let interval = match spans_per_second {
+            0 => 0,
+            rate => (1_000_000_000 / rate as u64).max(1),
+        };
+        TokenBucketShedder {
+            interval,
+            capacity: burst as u64 * interval.max(1),
+            start: Instant::now(),
+            full_at: AtomicU64::new(0),
+        }
+    }
+
+    /// The nanoseconds elapsed since the shedder was created, frozen at `0`
+    /// if the bucket is never refilled.
+    fn now(&self) -> u64 {
+        if self.interval == 0 {
+            return 0;
+        }
+        self.start.elapsed().as_nanos().min(u64::MAX as u128) as u64
+    }
+}
+
+impl LoadShedder for TokenBucketShedder {
+    fn should_shed(&self, _name: &str, _span_kind: &SpanKind) -> bool {
+        let now = self.now();
+        let cost = self.interval.max(1);
+        self.full_at
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |full_at| {
+                let full_at = full_at.max(now).saturating_add(cost);
+                (full_at - now <= self.capacity).then_some(full_at)
+            })
+            .is_err()
+    }
+}
+
+/// Metrics the tracer provider records about shed spans.
+#[derive(Debug, Default)]
+pub(crate) struct SheddingInstrumentation {
+    #[cfg(feature = "metrics")]
+    shed_spans: Option<opentelemetry::metrics::Counter<u64>>,
+}
+
+impl SheddingInstrumentation {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new<P: opentelemetry::metrics::MeterProvider>(meter_provider: &P) -> Self {
+        let meter = meter_provider.versioned_meter(
+            "opentelemetry_sdk",
+            Some(env!("CARGO_PKG_VERSION")),
+            None::<&'static str>,
+            None,
+        );
+        let shed_spans = meter
+            .u64_counter("otel.sdk.span.shed")
+            .with_unit("{span}")
+            .with_description("Spans started as non-recording spans by the load shedder")
+            .init();
+        SheddingInstrumentation {
+            shed_spans: Some(shed_spans),
+        }
+    }
+
+    pub(crate) fn record_shed(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(shed_spans) = &self.shed_spans {
+            shed_spans.add(1, &[]);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_sheds_spans_over_burst() {
+        // No tokens are refilled during the test.
+        let shedder = TokenBucketShedder::new(0, 2);
+
+        assert!(!shedder.should_shed("span", &SpanKind::Internal));
+        assert!(!shedder.should_shed("span", &SpanKind::Internal));
+        assert!(shedder.should_shed("span", &SpanKind::Internal));
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let mut shedder = TokenBucketShedder::new(1000, 1);
+        assert!(!shedder.should_shed("span", &SpanKind::Internal));
+
+        shedder.start -= std::time::Duration::from_secs(1);
+        assert!(!shedder.should_shed("span", &SpanKind::Internal));
+    }
+
+    #[test]
+    fn token_bucket_is_shared_by_threads() {
+        let shedder = TokenBucketShedder::new(0, 100);
+
+        let kept = std::thread::scope(|scope| {
+            let threads = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..50)
+                            .filter(|_| !shedder.should_shed("span", &SpanKind::Internal))
+                            .count()
+                    })
+                })
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .sum::<usize>()
+        });
+        assert_eq!(kept, 100);
+    }
+}
@@ -10,11 +10,14 @@ mod config;
 mod events;
 mod id_generator;
 mod links;
+mod load_shedding;
 mod provider;
 mod sampler;
 mod span;
 mod span_limit;
+mod span_listener;
 mod span_processor;
+mod span_start_hook;
 mod tracer;
 
 pub use config::{config, Config};
@@ -22,14 +25,18 @@ pub use events::SpanEvents;
 
 pub use id_generator::{IdGenerator, RandomIdGenerator};
 pub use links::SpanLinks;
-pub use provider::{Builder, TracerProvider};
-pub use sampler::{Sampler, ShouldSample};
+pub use load_shedding::{LoadShedder, TokenBucketShedder};
+pub use provider::{Builder, TracerProvider, TracerProviderGuard};
+pub use sampler::{ParentBasedSampler, ParentBasedSamplerBuilder, Sampler, ShouldSample};
 pub use span::Span;
 pub use span_limit::SpanLimits;
+pub use span_listener::{SpanEndEvent, SpanListener, SpanStartEvent};
 pub use span_processor::{
     BatchConfig, BatchConfigBuilder, BatchSpanProcessor, BatchSpanProcessorBuilder,
-    SimpleSpanProcessor, SpanProcessor,
+    CompositeSpanProcessor, FinishedSpanBatchOnDemandProcessor,
+    FinishedSpanBatchOnDemandProcessorBuilder, SimpleSpanProcessor, SpanProcessor,
 };
+pub use span_start_hook::SpanStartHook;
 pub use tracer::Tracer;
 
 #[cfg(feature = "jaeger_remote_sampler")]
@@ -199,6 +206,71 @@ mod tests {
         assert_eq!(span.links.len(), DEFAULT_MAX_LINKS_PER_SPAN as usize);
     }
 
+    #[test]
+    fn lazy_span_links() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let link = || {
+            Link::with_context(SpanContext::new(
+                TraceId::from_u128(12),
+                SpanId::from_u64(12),
+                TraceFlags::default(),
+                false,
+                Default::default(),
+            ))
+        };
+        let iterated = Arc::new(AtomicUsize::new(0));
+        let counted_links = |count: u32| {
+            let iterated = iterated.clone();
+            (0..count).map(move |_| {
+                iterated.fetch_add(1, Ordering::Relaxed);
+                link()
+            })
+        };
+
+        // Links of spans which are not sampled are never iterated.
+        let provider = TracerProvider::builder()
+            .with_config(Config::default().with_sampler(Sampler::AlwaysOff))
+            .build();
+        let tracer = provider.tracer("test_tracer");
+        let span = SpanBuilder::from_name("dropped")
+            .with_links(counted_links(4))
+            .start(&tracer);
+        assert!(!span.is_recording());
+        assert_eq!(iterated.load(Ordering::Relaxed), 0);
+
+        // Links past the limit are never iterated.
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let provider = TracerProvider::builder()
+            .with_config(Config::default().with_max_links_per_span(2))
+            .with_span_processor(SimpleSpanProcessor::new(Box::new(exporter.clone())))
+            .build();
+        let tracer = provider.tracer("test_tracer");
+        let mut span = SpanBuilder::from_name("sampled")
+            .with_links(counted_links(4))
+            .start(&tracer);
+        span.end();
+
+        assert_eq!(iterated.load(Ordering::Relaxed), 2);
+        let exported_spans = exporter
+            .get_finished_spans()
+            .expect("Spans are expected to be exported.");
+        assert_eq!(exported_spans[0].links.len(), 2);
+        assert_eq!(exported_spans[0].links.dropped_count, 0);
+
+        // Unbounded iterators stop at the limit.
+        let mut span = SpanBuilder::from_name("unbounded")
+            .with_links(std::iter::repeat_with(link))
+            .start(&tracer);
+        span.end();
+
+        let exported_spans = exporter
+            .get_finished_spans()
+            .expect("Spans are expected to be exported.");
+        assert_eq!(exported_spans[1].links.len(), 2);
+    }
+
     #[test]
     fn exceed_span_events_limit() {
         // Arrange
@@ -8,18 +8,25 @@
 //! propagators) are provided by the [`TracerProvider`]. [`Tracer`] instances do
 //! not duplicate this data to avoid that different [`Tracer`] instances
 //! of the [`TracerProvider`] have different versions of these data.
+use crate::dropped::DroppedCounters;
+use crate::guard::{self, DEFAULT_GUARD_TIMEOUT};
 use crate::runtime::RuntimeChannel;
+use crate::shutdown::{self, ShutdownError, ShutdownResult};
+use crate::trace::load_shedding::SheddingInstrumentation;
 use crate::trace::{
-    BatchSpanProcessor, Config, RandomIdGenerator, Sampler, SimpleSpanProcessor, SpanLimits, Tracer,
+    BatchSpanProcessor, CompositeSpanProcessor, Config, LoadShedder, RandomIdGenerator, Sampler,
+    SimpleSpanProcessor, SpanLimits, SpanListener, SpanStartHook, Tracer,
 };
 use crate::{export::trace::SpanExporter, trace::SpanProcessor};
-use crate::{InstrumentationLibrary, Resource};
+use crate::{DroppedTelemetry, InstrumentationLibrary, Resource};
 use once_cell::sync::{Lazy, OnceCell};
 use opentelemetry::trace::TraceError;
+use opentelemetry::trace::{SpanContext, SpanId, SpanKind, TraceId};
 use opentelemetry::{global, trace::TraceResult};
 use std::borrow::Cow;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Default tracer name if empty string is provided.
 const DEFAULT_COMPONENT_NAME: &str = "rust.opentelemetry.io/sdk/tracer";
@@ -29,6 +36,10 @@ static PROVIDER_RESOURCE: OnceCell<Resource> = OnceCell::new();
 static NOOP_TRACER_PROVIDER: Lazy<TracerProvider> = Lazy::new(|| TracerProvider {
     inner: Arc::new(TracerProviderInner {
         processors: Vec::new(),
+        listeners: Vec::new(),
+        span_start_hooks: Vec::new(),
+        load_shedder: None,
+        shedding_instrumentation: SheddingInstrumentation::default(),
         config: Config {
             // cannot use default here as the default resource is not empty
             sampler: Box::new(Sampler::ParentBased(Box::new(Sampler::AlwaysOn))),
@@ -38,12 +49,19 @@ static NOOP_TRACER_PROVIDER: Lazy<TracerProvider> = Lazy::new(|| TracerProvider
         },
     }),
     is_shutdown: Arc::new(AtomicBool::new(true)),
+    span_id_collision_reported: Arc::new(AtomicBool::new(true)),
+    ended_after_shutdown_reported: Arc::new(AtomicBool::new(true)),
+    dropped: Default::default(),
 });
 
 /// TracerProvider inner type
 #[derive(Debug)]
 pub(crate) struct TracerProviderInner {
     processors: Vec<Box<dyn SpanProcessor>>,
+    listeners: Vec<Box<dyn SpanListener>>,
+    span_start_hooks: Vec<(SpanKind, Box<dyn SpanStartHook>)>,
+    load_shedder: Option<Box<dyn LoadShedder>>,
+    shedding_instrumentation: SheddingInstrumentation,
     config: crate::trace::Config,
 }
 
@@ -66,6 +84,10 @@ impl Drop for TracerProviderInner {
 pub struct TracerProvider {
     inner: Arc<TracerProviderInner>,
     is_shutdown: Arc<AtomicBool>,
+    span_id_collision_reported: Arc<AtomicBool>,
+    ended_after_shutdown_reported: Arc<AtomicBool>,
+    // items dropped from spans by the span limits
+    dropped: Arc<DroppedCounters>,
 }
 
 impl Default for TracerProvider {
@@ -80,6 +102,9 @@ impl TracerProvider {
         TracerProvider {
             inner: Arc::new(inner),
             is_shutdown: Arc::new(AtomicBool::new(false)),
+            span_id_collision_reported: Arc::new(AtomicBool::new(false)),
+            ended_after_shutdown_reported: Arc::new(AtomicBool::new(false)),
+            dropped: Default::default(),
         }
     }
 
@@ -93,19 +118,115 @@ impl TracerProvider {
         &self.inner.processors
     }
 
+    /// Span listeners associated with this provider
+    pub(crate) fn span_listeners(&self) -> &[Box<dyn SpanListener>] {
+        &self.inner.listeners
+    }
+
+    /// Span start hooks associated with this provider, with their span kind
+    pub(crate) fn span_start_hooks(&self) -> &[(SpanKind, Box<dyn SpanStartHook>)] {
+        &self.inner.span_start_hooks
+    }
+
+    /// Returns `true` if the load shedder sheds the span, recording it.
+    pub(crate) fn should_shed(&self, name: &str, span_kind: &SpanKind) -> bool {
+        match &self.inner.load_shedder {
+            Some(shedder) if shedder.should_shed(name, span_kind) => {
+                self.inner.shedding_instrumentation.record_shed();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Counts the items dropped from ended spans to respect the span limits.
+    pub(crate) fn record_dropped_by_limits(&self, count: u64) {
+        self.dropped.limited_parts(count);
+    }
+
+    /// The spans dropped by the span processors of this provider, and the
+    /// attributes, events and links dropped from its spans by the span limits.
+    ///
+    /// The counts keep accumulating over the lifetime of the provider and
+    /// remain available after it was shut down.
+    pub fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+            + self
+                .span_processors()
+                .iter()
+                .map(|processor| processor.dropped_telemetry())
+                .sum()
+    }
+
     /// Config associated with this tracer
     pub(crate) fn config(&self) -> &crate::trace::Config {
         &self.inner.config
     }
 
+    /// Description of the sampler used by this provider's tracers, e.g.
+    /// `ParentBased{root:AlwaysOnSampler,...}`.
+    ///
+    /// Useful to confirm at runtime which sampling configuration is active.
+    /// See [`ShouldSample::description`].
+    ///
+    /// [`ShouldSample::description`]: crate::trace::ShouldSample::description
+    pub fn sampler_description(&self) -> String {
+        self.inner.config.sampler.description()
+    }
+
     /// true if the provider has been shutdown
     /// Don't start span or export spans when provider is shutdown
     pub(crate) fn is_shutdown(&self) -> bool {
         self.is_shutdown.load(Ordering::Relaxed)
     }
 
+    /// Reports the first span id collision of this provider, which is usually
+    /// caused by a faulty custom [`IdGenerator`].
+    ///
+    /// [`IdGenerator`]: crate::trace::IdGenerator
+    pub(crate) fn report_span_id_collision(
+        &self,
+        trace_id: TraceId,
+        span_id: SpanId,
+        problem: &str,
+    ) {
+        if !self
+            .span_id_collision_reported
+            .swap(true, Ordering::Relaxed)
+        {
+            global::handle_error(TraceError::Other(
+                format!(
+                    "span id {span_id} of trace {trace_id} {problem}, check the configured \
+                     IdGenerator; further collisions are not reported"
+                )
+                .into(),
+            ));
+        }
+    }
+
+    /// Reports the first span of this provider ended after it shut down, whose
+    /// data is dropped.
+    pub(crate) fn report_span_ended_after_shutdown(&self, span_context: &SpanContext) {
+        if !self
+            .ended_after_shutdown_reported
+            .swap(true, Ordering::Relaxed)
+        {
+            global::handle_error(TraceError::Other(
+                format!(
+                    "span {} of trace {} was ended after its tracer provider shut down and is \
+                     dropped; further spans ended after the shutdown are not reported",
+                    span_context.span_id(),
+                    span_context.trace_id()
+                )
+                .into(),
+            ));
+        }
+    }
+
     /// Force flush all remaining spans in span processors and return results.
     ///
+    /// Span processors are flushed in the order they were registered.
+    ///
     /// # Examples
     ///
     /// ```
@@ -150,6 +271,9 @@ impl TracerProvider {
 
     /// Shuts down the current `TracerProvider`.
     ///
+    /// Span processors are shut down in the order they were registered. All
+    /// processors are shut down even if some of them fail.
+    ///
     /// Note that shut down doesn't means the TracerProvider has dropped
     pub fn shutdown(&self) -> TraceResult<()> {
         if self
@@ -177,6 +301,115 @@ impl TracerProvider {
             ))
         }
     }
+
+    /// Shuts down the current `TracerProvider`, waiting at most `timeout` for
+    /// its processors to export the remaining spans.
+    ///
+    /// Span processors are shut down in the order they were registered, each
+    /// with the time left until the timeout. All processors are shut down even
+    /// if some of them fail or time out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use opentelemetry_sdk::{trace::TracerProvider, ShutdownError};
+    ///
+    /// let provider = TracerProvider::builder().build();
+    /// match provider.shutdown_with_timeout(Duration::from_secs(5)) {
+    ///     Ok(()) => {}
+    ///     Err(ShutdownError::Timeout(_)) => eprintln!("some spans may be lost"),
+    ///     Err(err) => eprintln!("failed to shut down: {err}"),
+    /// }
+    /// ```
+    pub fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        if self
+            .is_shutdown
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(ShutdownError::AlreadyShutdown);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let results: Vec<_> = self
+            .inner
+            .processors
+            .iter()
+            .map(|processor| processor.shutdown_with_timeout(shutdown::remaining(deadline)))
+            .collect();
+        shutdown::combine(results)
+    }
+
+    /// Returns a guard flushing and shutting down this provider when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::global;
+    /// use opentelemetry_sdk::trace::TracerProvider;
+    ///
+    /// fn main() {
+    ///     let provider = TracerProvider::builder().build();
+    ///     let _guard = provider.guard();
+    ///     global::set_tracer_provider(provider);
+    ///
+    ///     // create spans..
+    ///
+    ///     // the spans are exported when `_guard` goes out of scope, even if
+    ///     // `main` panics
+    /// }
+    /// ```
+    pub fn guard(&self) -> TracerProviderGuard {
+        TracerProviderGuard {
+            provider: self.clone(),
+            timeout: DEFAULT_GUARD_TIMEOUT,
+        }
+    }
+}
+
+/// Flushes and shuts down a [`TracerProvider`] when dropped.
+///
+/// Created with [`TracerProvider::guard`]. See [shutting down with
+/// guards](crate#shutting-down-with-guards) for how the provider is shut down,
+/// and how long dropping the guard may block.
+#[must_use = "the provider is shut down when the guard is dropped"]
+#[derive(Debug)]
+pub struct TracerProviderGuard {
+    provider: TracerProvider,
+    timeout: Duration,
+}
+
+impl TracerProviderGuard {
+    /// Sets the maximum time to wait for the provider to shut down when the
+    /// guard is dropped, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The provider shut down by this guard.
+    pub fn provider(&self) -> &TracerProvider {
+        &self.provider
+    }
+}
+
+impl Drop for TracerProviderGuard {
+    fn drop(&mut self) {
+        if self.provider.is_shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let provider = self.provider.clone();
+        guard::shutdown_on_drop("tracer", self.timeout, move || {
+            for result in provider.force_flush() {
+                if let Err(err) = result {
+                    global::handle_error(err);
+                }
+            }
+            provider.shutdown()
+        });
+    }
 }
 
 impl opentelemetry::trace::TracerProvider for TracerProvider {
@@ -226,6 +459,13 @@ impl opentelemetry::trace::TracerProvider for TracerProvider {
 #[derive(Debug, Default)]
 pub struct Builder {
     processors: Vec<Box<dyn SpanProcessor>>,
+    listeners: Vec<Box<dyn SpanListener>>,
+    span_start_hooks: Vec<(SpanKind, Box<dyn SpanStartHook>)>,
+    load_shedder: Option<Box<dyn LoadShedder>>,
+    shedding_instrumentation: SheddingInstrumentation,
+    // observes the dropped and queued spans of the built provider
+    #[cfg(feature = "metrics")]
+    meter: Option<opentelemetry::metrics::Meter>,
     config: crate::trace::Config,
 }
 
@@ -256,6 +496,100 @@ impl Builder {
         Builder { processors, ..self }
     }
 
+    /// A chain of [`SpanProcessor`]s invoked in order.
+    ///
+    /// The processors are wrapped in a [`CompositeSpanProcessor`], see its
+    /// documentation for the flush and shutdown semantics of the chain.
+    pub fn with_processors(self, processors: Vec<Box<dyn SpanProcessor>>) -> Self {
+        self.with_span_processor(CompositeSpanProcessor::new(processors))
+    }
+
+    /// A [`SpanListener`] to be notified of span lifecycle events.
+    ///
+    /// Listeners are not part of the export pipeline and receive notifications
+    /// independently of the configured span processors.
+    pub fn with_span_listener<T: SpanListener + 'static>(self, listener: T) -> Self {
+        let mut listeners = self.listeners;
+        listeners.push(Box::new(listener));
+
+        Builder { listeners, ..self }
+    }
+
+    /// A [`SpanStartHook`] adding attributes to every span of the given kind
+    /// when it is started.
+    ///
+    /// Hooks registered for the same kind run in registration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::{trace::SpanKind, KeyValue};
+    /// use opentelemetry_sdk::trace::TracerProvider;
+    ///
+    /// let provider = TracerProvider::builder()
+    ///     .with_span_start_hook(
+    ///         SpanKind::Client,
+    ///         vec![KeyValue::new("server.address", "db.internal")],
+    ///     )
+    ///     .build();
+    /// # drop(provider);
+    /// ```
+    pub fn with_span_start_hook<T: SpanStartHook + 'static>(
+        self,
+        span_kind: SpanKind,
+        hook: T,
+    ) -> Self {
+        let mut span_start_hooks = self.span_start_hooks;
+        span_start_hooks.push((span_kind, Box::new(hook)));
+
+        Builder {
+            span_start_hooks,
+            ..self
+        }
+    }
+
+    /// A [`LoadShedder`] deciding whether spans are started as non-recording
+    /// spans, protecting the application when it is under pressure.
+    ///
+    /// The shedder runs before span start hooks and the sampler. Setting a
+    /// shedder replaces the previous one.
+    pub fn with_load_shedder<T: LoadShedder + 'static>(self, shedder: T) -> Self {
+        Builder {
+            load_shedder: Some(Box::new(shedder)),
+            ..self
+        }
+    }
+
+    /// Record metrics about the provider itself with meters of `meter_provider`.
+    ///
+    /// The `otel.sdk.span.shed` counter records the spans started as
+    /// non-recording spans by the [`LoadShedder`].
+    ///
+    /// The `otel.sdk.span.dropped` counter records the spans lost by the span
+    /// processors, with a `reason` attribute of `queue_full`, `export_failed`
+    /// or `memory_budget`, like [`TracerProvider::dropped_telemetry`]. The
+    /// `otel.sdk.processor.span.queue.size` gauge records the spans waiting in
+    /// the queues of batch span processors. Alerting on them lets operators
+    /// notice telemetry loss, otherwise only reported to the global error
+    /// handler.
+    #[cfg(feature = "metrics")]
+    pub fn with_meter_provider<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        let meter = meter_provider.versioned_meter(
+            "opentelemetry_sdk",
+            Some(env!("CARGO_PKG_VERSION")),
+            None::<&'static str>,
+            None,
+        );
+        Builder {
+            shedding_instrumentation: SheddingInstrumentation::new(meter_provider),
+            meter: Some(meter),
+            ..self
+        }
+    }
+
     /// The sdk [`crate::trace::Config`] that this provider will use.
     pub fn with_config(self, config: crate::trace::Config) -> Self {
         Builder { config, ..self }
@@ -292,7 +626,31 @@ impl Builder {
             p.set_resource(config.resource.as_ref());
         }
 
-        TracerProvider::new(TracerProviderInner { processors, config })
+        let provider = TracerProvider::new(TracerProviderInner {
+            processors,
+            listeners: self.listeners,
+            span_start_hooks: self.span_start_hooks,
+            load_shedder: self.load_shedder,
+            shedding_instrumentation: self.shedding_instrumentation,
+            config,
+        });
+
+        #[cfg(feature = "metrics")]
+        if let Some(meter) = &self.meter {
+            let inner = Arc::downgrade(&provider.inner);
+            crate::dropped::observe_processors(meter, "span", "{span}", "Spans", move || {
+                let inner = inner.upgrade()?;
+                let processors = &inner.processors;
+                Some((
+                    processors.iter().map(|p| p.dropped_telemetry()).sum(),
+                    crate::ProcessorDiagnostics::combine(
+                        processors.iter().map(|p| p.diagnostics()),
+                    ),
+                ))
+            });
+        }
+
+        provider
     }
 }
 
@@ -303,14 +661,15 @@ mod tests {
         SERVICE_NAME, TELEMETRY_SDK_LANGUAGE, TELEMETRY_SDK_NAME, TELEMETRY_SDK_VERSION,
     };
     use crate::trace::provider::TracerProviderInner;
-    use crate::trace::{Config, Span, SpanProcessor};
-    use crate::Resource;
-    use opentelemetry::trace::{TraceError, TraceResult, Tracer, TracerProvider};
+    use crate::trace::{Config, Span, SpanEndEvent, SpanListener, SpanProcessor, SpanStartEvent};
+    use crate::{Resource, ShutdownError};
+    use opentelemetry::trace::{Span as _, TraceError, TraceResult, Tracer, TracerProvider};
     use opentelemetry::{Context, Key, KeyValue, Value};
     use std::borrow::Cow;
     use std::env;
     use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     // fields below is wrapped with Arc so we can assert it
     #[derive(Default, Debug)]
@@ -390,6 +749,10 @@ mod tests {
                 Box::from(TestSpanProcessor::new(true)),
                 Box::from(TestSpanProcessor::new(false)),
             ],
+            listeners: Vec::new(),
+            span_start_hooks: Vec::new(),
+            load_shedder: None,
+            shedding_instrumentation: Default::default(),
             config: Default::default(),
         });
 
@@ -397,6 +760,21 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_sampler_description() {
+        let default_provider = super::TracerProvider::default();
+        assert!(default_provider
+            .sampler_description()
+            .starts_with("ParentBased{root:AlwaysOnSampler,"));
+
+        let provider = super::TracerProvider::builder()
+            .with_config(
+                Config::default().with_sampler(crate::trace::Sampler::TraceIdRatioBased(0.5)),
+            )
+            .build();
+        assert_eq!(provider.sampler_description(), "TraceIdRatioBased{0.5}");
+    }
+
     #[test]
     fn test_tracer_provider_default_resource() {
         let assert_resource = |provider: &super::TracerProvider,
@@ -533,6 +911,10 @@ mod tests {
         let assert_handle = processor.assert_info();
         let tracer_provider = super::TracerProvider::new(TracerProviderInner {
             processors: vec![Box::from(processor)],
+            listeners: Vec::new(),
+            span_start_hooks: Vec::new(),
+            load_shedder: None,
+            shedding_instrumentation: Default::default(),
             config: Default::default(),
         });
 
@@ -564,4 +946,200 @@ mod tests {
         let _ = test_tracer_1.start("test");
         assert!(assert_handle.started_span_count(2));
     }
+
+    #[test]
+    fn test_guard_shuts_down_provider() {
+        let processor = TestSpanProcessor::new(true);
+        let assert_handle = processor.assert_info();
+        let tracer_provider = super::TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+
+        let guard = tracer_provider.guard();
+        assert!(!assert_handle.0.is_shutdown.load(Ordering::SeqCst));
+        drop(guard);
+
+        assert!(assert_handle.0.is_shutdown.load(Ordering::SeqCst));
+        assert!(tracer_provider.is_shutdown.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_shutdown_with_timeout() {
+        let processor = TestSpanProcessor::new(true);
+        let assert_handle = processor.assert_info();
+        let tracer_provider = super::TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+
+        assert_eq!(
+            tracer_provider.shutdown_with_timeout(Duration::from_secs(1)),
+            Ok(())
+        );
+        assert!(assert_handle.0.is_shutdown.load(Ordering::SeqCst));
+        assert_eq!(
+            tracer_provider.shutdown_with_timeout(Duration::from_secs(1)),
+            Err(ShutdownError::AlreadyShutdown)
+        );
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct RecordingSpanListener {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SpanListener for RecordingSpanListener {
+        fn on_start(&self, event: &SpanStartEvent<'_>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("start {}", event.name));
+        }
+
+        fn on_end(&self, event: &SpanEndEvent<'_>) {
+            assert!(event.end_time >= event.start_time);
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("end {}", event.name));
+        }
+    }
+
+    #[test]
+    fn test_span_listener_notified() {
+        let listener = RecordingSpanListener::default();
+        let tracer_provider = super::TracerProvider::builder()
+            .with_span_listener(listener.clone())
+            .build();
+
+        let tracer = tracer_provider.tracer("test");
+        let mut span = tracer.start("span1");
+        span.end();
+        drop(tracer.start("span2"));
+
+        assert_eq!(
+            *listener.events.lock().unwrap(),
+            vec!["start span1", "end span1", "start span2", "end span2"]
+        );
+    }
+
+    #[test]
+    fn load_shedder_forces_non_recording_spans() {
+        #[derive(Debug)]
+        struct ShedNoisySpans;
+        impl crate::trace::LoadShedder for ShedNoisySpans {
+            fn should_shed(&self, name: &str, _span_kind: &opentelemetry::trace::SpanKind) -> bool {
+                name == "noisy"
+            }
+        }
+
+        let processor = TestSpanProcessor::new(true);
+        let assert_handle = processor.assert_info();
+        let tracer_provider = super::TracerProvider::builder()
+            .with_span_processor(processor)
+            .with_load_shedder(ShedNoisySpans)
+            .build();
+        let tracer = tracer_provider.tracer("test");
+
+        let span = tracer.start("noisy");
+        assert!(!span.is_recording());
+        assert!(!span.span_context().is_sampled());
+        assert!(span.span_context().is_valid());
+        assert!(assert_handle.started_span_count(0));
+
+        let span = tracer.start("quiet");
+        assert!(span.is_recording());
+        assert!(assert_handle.started_span_count(1));
+    }
+
+    #[test]
+    fn dropped_telemetry_counts_failed_exports_and_limits() {
+        use crate::testing::trace::FailingSpanExporter;
+
+        let tracer_provider = super::TracerProvider::builder()
+            .with_simple_exporter(FailingSpanExporter::new())
+            .with_config(Config::default().with_max_attributes_per_span(1))
+            .build();
+        let tracer = tracer_provider.tracer("test");
+
+        let mut span = tracer.start("span");
+        span.set_attribute(KeyValue::new("a", 1));
+        span.set_attribute(KeyValue::new("b", 2));
+        span.set_attribute(KeyValue::new("c", 3));
+        span.end();
+        assert!(tracer_provider.shutdown().is_ok());
+
+        let dropped = tracer_provider.dropped_telemetry();
+        assert_eq!(dropped.queue_full, 0);
+        assert_eq!(dropped.export_failed, 1);
+        assert_eq!(dropped.limited_parts, 2);
+        assert_eq!(dropped.total_items(), 1);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn meter_provider_observes_dropped_and_queued_spans() {
+        use crate::metrics::{data, PeriodicReader, SdkMeterProvider};
+        use crate::runtime;
+        use crate::testing::{
+            metrics::InMemoryMetricsExporter,
+            trace::{FailingSpanExporter, NoopSpanExporter},
+        };
+        use crate::trace::{BatchConfigBuilder, BatchSpanProcessor};
+
+        let metrics_exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(metrics_exporter.clone(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+
+        // the batch processor keeps the spans queued until it is shut down
+        let batch = BatchSpanProcessor::builder(NoopSpanExporter::new(), runtime::Tokio)
+            .with_own_thread()
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_secs(60 * 60 * 24))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+        let tracer_provider = super::TracerProvider::builder()
+            .with_simple_exporter(FailingSpanExporter::new())
+            .with_span_processor(batch)
+            .with_meter_provider(&meter_provider)
+            .build();
+        let tracer = tracer_provider.tracer("test");
+        tracer.start("first").end();
+        tracer.start("second").end();
+
+        meter_provider.force_flush().unwrap();
+        let resource_metrics = metrics_exporter.get_finished_metrics().unwrap();
+        let metrics = &resource_metrics[0].scope_metrics[0].metrics;
+        let dropped = metrics
+            .iter()
+            .find(|metric| metric.name == "otel.sdk.span.dropped")
+            .expect("dropped spans are observed");
+        assert_eq!(dropped.unit, "{span}");
+        let dropped = dropped
+            .data
+            .as_any()
+            .downcast_ref::<data::Sum<u64>>()
+            .expect("sum aggregation expected");
+        let export_failed = dropped
+            .data_points
+            .iter()
+            .find(|dp| dp.attributes == [KeyValue::new("reason", "export_failed")])
+            .expect("failed exports are observed");
+        assert_eq!(export_failed.value, 2);
+        assert_eq!(dropped.data_points.len(), 3);
+
+        let queue_size = metrics
+            .iter()
+            .find(|metric| metric.name == "otel.sdk.processor.span.queue.size")
+            .expect("queued spans are observed")
+            .data
+            .as_any()
+            .downcast_ref::<data::Gauge<u64>>()
+            .expect("gauge aggregation expected");
+        assert_eq!(queue_size.data_points[0].value, 2);
+
+        assert!(tracer_provider.shutdown().is_ok());
+    }
 }
@@ -80,6 +80,17 @@ pub trait ShouldSample: CloneShouldSample + Send + Sync + std::fmt::Debug {
         attributes: &[KeyValue],
         links: &[Link],
     ) -> SamplingResult;
+
+    /// Returns a short description of the sampler and its configuration.
+    ///
+    /// The description follows the format used by other OpenTelemetry SDKs,
+    /// e.g. `TraceIdRatioBased{0.1}`, and is meant to let operators confirm
+    /// which sampler is active at runtime. Defaults to the sampler's type name.
+    fn description(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name).to_string()
+    }
 }
 
 /// This trait should not be used directly instead users should use [`ShouldSample`].
@@ -223,6 +234,177 @@ impl ShouldSample for Sampler {
             },
         }
     }
+
+    fn description(&self) -> String {
+        match self {
+            Sampler::AlwaysOn => "AlwaysOnSampler".to_string(),
+            Sampler::AlwaysOff => "AlwaysOffSampler".to_string(),
+            Sampler::ParentBased(root) => parent_based_description(
+                root.as_ref(),
+                &Sampler::AlwaysOn,
+                &Sampler::AlwaysOff,
+                &Sampler::AlwaysOn,
+                &Sampler::AlwaysOff,
+            ),
+            Sampler::TraceIdRatioBased(prob) => format!("TraceIdRatioBased{{{}}}", prob),
+            #[cfg(feature = "jaeger_remote_sampler")]
+            Sampler::JaegerRemote(remote_sampler) => remote_sampler.description(),
+        }
+    }
+}
+
+fn parent_based_description(
+    root: &dyn ShouldSample,
+    remote_parent_sampled: &dyn ShouldSample,
+    remote_parent_not_sampled: &dyn ShouldSample,
+    local_parent_sampled: &dyn ShouldSample,
+    local_parent_not_sampled: &dyn ShouldSample,
+) -> String {
+    format!(
+        "ParentBased{{root:{},remoteParentSampled:{},remoteParentNotSampled:{},localParentSampled:{},localParentNotSampled:{}}}",
+        root.description(),
+        remote_parent_sampled.description(),
+        remote_parent_not_sampled.description(),
+        local_parent_sampled.description(),
+        local_parent_not_sampled.description(),
+    )
+}
+
+/// A sampler that respects the parent span's sampling decision, with independently
+/// configurable delegates for each kind of parent.
+///
+/// Unlike [`Sampler::ParentBased`], which only allows the root delegate to be
+/// configured, this sampler exposes all delegates defined by the
+/// [specification]. Spans without a parent use the `root` delegate, while
+/// spans with a parent use the delegate matching whether the parent is remote
+/// or local, and whether it is sampled.
+///
+/// By default, sampled parents are delegated to [`Sampler::AlwaysOn`] and
+/// unsampled parents to [`Sampler::AlwaysOff`].
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_sdk::trace::{ParentBasedSampler, Sampler};
+///
+/// // Always honor sampled remote parents, but ratio-sample spans of local
+/// // parents and new traces.
+/// let sampler = ParentBasedSampler::builder(Sampler::TraceIdRatioBased(0.1))
+///     .with_remote_parent_sampled(Sampler::AlwaysOn)
+///     .with_local_parent_sampled(Sampler::TraceIdRatioBased(0.1))
+///     .build();
+/// # drop(sampler);
+/// ```
+///
+/// [specification]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/sdk.md#parentbased
+#[derive(Clone, Debug)]
+pub struct ParentBasedSampler {
+    root: Box<dyn ShouldSample>,
+    remote_parent_sampled: Box<dyn ShouldSample>,
+    remote_parent_not_sampled: Box<dyn ShouldSample>,
+    local_parent_sampled: Box<dyn ShouldSample>,
+    local_parent_not_sampled: Box<dyn ShouldSample>,
+}
+
+impl ParentBasedSampler {
+    /// Create a [`ParentBasedSamplerBuilder`] using `root` for spans without a parent.
+    pub fn builder<T: ShouldSample + 'static>(root: T) -> ParentBasedSamplerBuilder {
+        ParentBasedSamplerBuilder {
+            sampler: ParentBasedSampler {
+                root: Box::new(root),
+                remote_parent_sampled: Box::new(Sampler::AlwaysOn),
+                remote_parent_not_sampled: Box::new(Sampler::AlwaysOff),
+                local_parent_sampled: Box::new(Sampler::AlwaysOn),
+                local_parent_not_sampled: Box::new(Sampler::AlwaysOff),
+            },
+        }
+    }
+}
+
+impl ShouldSample for ParentBasedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        let delegate = match parent_context.filter(|cx| cx.has_active_span()) {
+            None => &self.root,
+            Some(cx) => {
+                let span = cx.span();
+                let parent_span_context = span.span_context();
+                match (
+                    parent_span_context.is_remote(),
+                    parent_span_context.is_sampled(),
+                ) {
+                    (true, true) => &self.remote_parent_sampled,
+                    (true, false) => &self.remote_parent_not_sampled,
+                    (false, true) => &self.local_parent_sampled,
+                    (false, false) => &self.local_parent_not_sampled,
+                }
+            }
+        };
+
+        delegate.should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+
+    fn description(&self) -> String {
+        parent_based_description(
+            self.root.as_ref(),
+            self.remote_parent_sampled.as_ref(),
+            self.remote_parent_not_sampled.as_ref(),
+            self.local_parent_sampled.as_ref(),
+            self.local_parent_not_sampled.as_ref(),
+        )
+    }
+}
+
+/// Builder for [`ParentBasedSampler`].
+#[derive(Debug)]
+pub struct ParentBasedSamplerBuilder {
+    sampler: ParentBasedSampler,
+}
+
+impl ParentBasedSamplerBuilder {
+    /// Set the delegate for spans with a remote parent that is sampled.
+    ///
+    /// Defaults to [`Sampler::AlwaysOn`].
+    pub fn with_remote_parent_sampled<T: ShouldSample + 'static>(mut self, sampler: T) -> Self {
+        self.sampler.remote_parent_sampled = Box::new(sampler);
+        self
+    }
+
+    /// Set the delegate for spans with a remote parent that is not sampled.
+    ///
+    /// Defaults to [`Sampler::AlwaysOff`].
+    pub fn with_remote_parent_not_sampled<T: ShouldSample + 'static>(mut self, sampler: T) -> Self {
+        self.sampler.remote_parent_not_sampled = Box::new(sampler);
+        self
+    }
+
+    /// Set the delegate for spans with a local parent that is sampled.
+    ///
+    /// Defaults to [`Sampler::AlwaysOn`].
+    pub fn with_local_parent_sampled<T: ShouldSample + 'static>(mut self, sampler: T) -> Self {
+        self.sampler.local_parent_sampled = Box::new(sampler);
+        self
+    }
+
+    /// Set the delegate for spans with a local parent that is not sampled.
+    ///
+    /// Defaults to [`Sampler::AlwaysOff`].
+    pub fn with_local_parent_not_sampled<T: ShouldSample + 'static>(mut self, sampler: T) -> Self {
+        self.sampler.local_parent_not_sampled = Box::new(sampler);
+        self
+    }
+
+    /// Build the [`ParentBasedSampler`].
+    pub fn build(self) -> ParentBasedSampler {
+        self.sampler
+    }
 }
 
 pub(crate) fn sample_based_on_probability(prob: &f64, trace_id: TraceId) -> SamplingDecision {
@@ -441,4 +623,121 @@ mod tests {
             assert_eq!(result.decision, expected);
         }
     }
+
+    #[test]
+    fn parent_based_sampler_delegates() {
+        let sampler = ParentBasedSampler::builder(Sampler::AlwaysOff)
+            .with_remote_parent_sampled(Sampler::AlwaysOn)
+            .with_remote_parent_not_sampled(Sampler::AlwaysOn)
+            .with_local_parent_sampled(Sampler::AlwaysOff)
+            .with_local_parent_not_sampled(Sampler::AlwaysOff)
+            .build();
+
+        let parent_cx = |trace_flags, is_remote| {
+            Context::current_with_span(TestSpan(SpanContext::new(
+                TraceId::from_u128(1),
+                SpanId::from_u64(1),
+                trace_flags,
+                is_remote,
+                TraceState::default(),
+            )))
+        };
+
+        // name, parent context, expected decision
+        let test_cases = vec![
+            ("root", Context::new(), SamplingDecision::Drop),
+            (
+                "remote parent sampled",
+                parent_cx(TraceFlags::SAMPLED, true),
+                SamplingDecision::RecordAndSample,
+            ),
+            (
+                "remote parent not sampled",
+                parent_cx(TraceFlags::default(), true),
+                SamplingDecision::RecordAndSample,
+            ),
+            (
+                "local parent sampled",
+                parent_cx(TraceFlags::SAMPLED, false),
+                SamplingDecision::Drop,
+            ),
+            (
+                "local parent not sampled",
+                parent_cx(TraceFlags::default(), false),
+                SamplingDecision::Drop,
+            ),
+        ];
+
+        for (name, parent_cx, expected) in test_cases {
+            let result = sampler.should_sample(
+                Some(&parent_cx),
+                TraceId::from_u128(1),
+                name,
+                &SpanKind::Internal,
+                &[],
+                &[],
+            );
+
+            assert_eq!(result.decision, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn parent_based_sampler_defaults_follow_parent() {
+        let sampler = ParentBasedSampler::builder(Sampler::AlwaysOn).build();
+
+        for (is_remote, trace_flags, expected) in [
+            (true, TraceFlags::SAMPLED, SamplingDecision::RecordAndSample),
+            (true, TraceFlags::default(), SamplingDecision::Drop),
+            (
+                false,
+                TraceFlags::SAMPLED,
+                SamplingDecision::RecordAndSample,
+            ),
+            (false, TraceFlags::default(), SamplingDecision::Drop),
+        ] {
+            let parent_cx = Context::current_with_span(TestSpan(SpanContext::new(
+                TraceId::from_u128(1),
+                SpanId::from_u64(1),
+                trace_flags,
+                is_remote,
+                TraceState::default(),
+            )));
+            let result = sampler.should_sample(
+                Some(&parent_cx),
+                TraceId::from_u128(1),
+                "parent based",
+                &SpanKind::Internal,
+                &[],
+                &[],
+            );
+
+            assert_eq!(result.decision, expected);
+        }
+    }
+
+    #[test]
+    fn sampler_description() {
+        assert_eq!(Sampler::AlwaysOn.description(), "AlwaysOnSampler");
+        assert_eq!(Sampler::AlwaysOff.description(), "AlwaysOffSampler");
+        assert_eq!(
+            Sampler::TraceIdRatioBased(0.1).description(),
+            "TraceIdRatioBased{0.1}"
+        );
+        assert_eq!(
+            Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(0.25))).description(),
+            "ParentBased{root:TraceIdRatioBased{0.25},remoteParentSampled:AlwaysOnSampler,\
+             remoteParentNotSampled:AlwaysOffSampler,localParentSampled:AlwaysOnSampler,\
+             localParentNotSampled:AlwaysOffSampler}"
+        );
+        assert_eq!(
+            ParentBasedSampler::builder(Sampler::AlwaysOn)
+                .with_remote_parent_not_sampled(Sampler::AlwaysOn)
+                .build()
+                .description(),
+            "ParentBased{root:AlwaysOnSampler,remoteParentSampled:AlwaysOnSampler,\
+             remoteParentNotSampled:AlwaysOnSampler,localParentSampled:AlwaysOnSampler,\
+             localParentNotSampled:AlwaysOffSampler}"
+        );
+    }
 }
@@ -267,6 +267,13 @@ impl ShouldSample for JaegerRemoteSampler {
                 )
             })
     }
+
+    fn description(&self) -> String {
+        format!(
+            "JaegerRemoteSampler{{default:{}}}",
+            self.default_sampler.description()
+        )
+    }
 }
 
 #[cfg(test)]
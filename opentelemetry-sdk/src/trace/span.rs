@@ -8,10 +8,18 @@
 //! start time is set to the current time on span creation. After the `Span` is created, it
 //! is possible to change its name, set its `Attributes`, and add `Links` and `Events`.
 //! These cannot be changed after the `Span`'s end time has been set.
-use crate::trace::SpanLimits;
-use opentelemetry::trace::{Event, Link, SpanContext, SpanId, SpanKind, Status};
-use opentelemetry::KeyValue;
+use crate::trace::span_limit::SpanBytesBudget;
+use crate::trace::{SpanEndEvent, SpanLimits, SpanListener, SpanStartEvent};
+use futures_util::future::{self, BoxFuture};
+#[cfg(feature = "experimental_trace_sampling_veto")]
+use opentelemetry::trace::SamplingDecision;
+use opentelemetry::trace::{
+    Event, Link, SpanContext, SpanId, SpanKind, Status, TraceError, TraceResult,
+};
+use opentelemetry::{global, Key, KeyValue, Value};
 use std::borrow::Cow;
+use std::fmt;
+use std::future::Future;
 use std::time::SystemTime;
 
 /// Single operation within a trace.
@@ -21,6 +29,23 @@ pub struct Span {
     data: Option<SpanData>,
     tracer: crate::trace::Tracer,
     span_limits: SpanLimits,
+    bytes_budget: SpanBytesBudget,
+    lazy_attributes: Vec<LazyAttribute>,
+    ended: bool,
+}
+
+/// An attribute whose value is computed when the span ends.
+struct LazyAttribute {
+    key: Key,
+    value: Box<dyn FnOnce() -> Value + Send + Sync>,
+}
+
+impl fmt::Debug for LazyAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyAttribute")
+            .field("key", &self.key)
+            .finish()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -60,6 +85,36 @@ impl Span {
             data,
             tracer,
             span_limits: span_limit,
+            bytes_budget: SpanBytesBudget::new(&span_limit),
+            lazy_attributes: Vec::new(),
+            ended: false,
+        }
+    }
+
+    /// Sets the bytes left to the attributes, events and links added to the span.
+    pub(crate) fn with_bytes_budget(mut self, bytes_budget: SpanBytesBudget) -> Self {
+        self.bytes_budget = bytes_budget;
+        self
+    }
+
+    /// Applies the sampling decision of the span processors, which can only
+    /// downgrade the decision of the sampler.
+    #[cfg(feature = "experimental_trace_sampling_veto")]
+    pub(crate) fn downgrade_sampling(&mut self, decision: SamplingDecision) {
+        if decision == SamplingDecision::RecordAndSample {
+            return;
+        }
+
+        self.span_context = SpanContext::new(
+            self.span_context.trace_id(),
+            self.span_context.span_id(),
+            self.span_context.trace_flags().with_sampled(false),
+            self.span_context.is_remote(),
+            self.span_context.trace_state().clone(),
+        );
+        if decision == SamplingDecision::Drop {
+            self.data = None;
+            self.lazy_attributes.clear();
         }
     }
 
@@ -71,6 +126,22 @@ impl Span {
         self.data.as_mut().map(f)
     }
 
+    /// Notify span listeners that this span has started.
+    pub(crate) fn notify_start(&self, listeners: &[Box<dyn SpanListener>]) {
+        if let Some(data) = &self.data {
+            let event = SpanStartEvent {
+                span_context: &self.span_context,
+                parent_span_id: data.parent_span_id,
+                name: &data.name,
+                span_kind: &data.span_kind,
+                start_time: data.start_time,
+            };
+            for listener in listeners {
+                listener.on_start(&event);
+            }
+        }
+    }
+
     /// Convert information in this span into `exporter::trace::SpanData`.
     /// This function copies all data from the current span, which will create a
     /// overhead.
@@ -99,22 +170,22 @@ impl opentelemetry::trace::Span for Span {
     {
         let span_events_limit = self.span_limits.max_events_per_span as usize;
         let event_attributes_limit = self.span_limits.max_attributes_per_event as usize;
-        self.with_data(|data| {
+        if let Some(data) = self.data.as_mut() {
             if data.events.len() < span_events_limit {
                 let dropped_attributes_count =
                     attributes.len().saturating_sub(event_attributes_limit);
                 attributes.truncate(event_attributes_limit);
+                self.span_limits.limit_values(&mut attributes);
 
-                data.events.add_event(Event::new(
-                    name,
-                    timestamp,
-                    attributes,
-                    dropped_attributes_count as u32,
-                ));
-            } else {
-                data.events.dropped_count += 1;
+                let mut event =
+                    Event::new(name, timestamp, attributes, dropped_attributes_count as u32);
+                if self.bytes_budget.admit_event(&mut event) {
+                    data.events.add_event(event);
+                    return;
+                }
             }
-        });
+            data.events.dropped_count += 1;
+        }
     }
 
     /// Returns the `SpanContext` for the given `Span`.
@@ -134,15 +205,37 @@ impl opentelemetry::trace::Span for Span {
     /// Note that the OpenTelemetry project documents certain ["standard
     /// attributes"](https://github.com/open-telemetry/opentelemetry-specification/tree/v0.5.0/specification/trace/semantic_conventions/README.md)
     /// that have prescribed semantic meanings.
-    fn set_attribute(&mut self, attribute: KeyValue) {
+    fn set_attribute(&mut self, mut attribute: KeyValue) {
         let span_attribute_limit = self.span_limits.max_attributes_per_span as usize;
-        self.with_data(|data| {
-            if data.attributes.len() < span_attribute_limit {
+        if let Some(data) = self.data.as_mut() {
+            self.span_limits.limit_value(&mut attribute.value);
+            if data.attributes.len() < span_attribute_limit
+                && self.bytes_budget.admit_attribute(&attribute)
+            {
                 data.attributes.push(attribute);
             } else {
                 data.dropped_attributes_count += 1;
             }
-        });
+        }
+    }
+
+    /// Sets an attribute whose value is computed when the span ends.
+    ///
+    /// `value` is only called if the span is sampled, spans that are recorded
+    /// but not sampled and spans that are not recording never evaluate it. The
+    /// attribute is not part of [`Span::exported_data`] before the span ends.
+    fn set_attribute_lazy<K, V, F>(&mut self, key: K, value: F)
+    where
+        K: Into<Key>,
+        V: Into<Value>,
+        F: FnOnce() -> V + Send + Sync + 'static,
+    {
+        if self.data.is_some() {
+            self.lazy_attributes.push(LazyAttribute {
+                key: key.into(),
+                value: Box::new(move || value().into()),
+            });
+        }
     }
 
     /// Sets the status of this `Span`.
@@ -173,51 +266,97 @@ impl opentelemetry::trace::Span for Span {
     fn add_link(&mut self, span_context: SpanContext, attributes: Vec<KeyValue>) {
         let span_links_limit = self.span_limits.max_links_per_span as usize;
         let link_attributes_limit = self.span_limits.max_attributes_per_link as usize;
-        self.with_data(|data| {
+        if let Some(data) = self.data.as_mut() {
             if data.links.links.len() < span_links_limit {
                 let dropped_attributes_count =
                     attributes.len().saturating_sub(link_attributes_limit);
                 let mut attributes = attributes;
                 attributes.truncate(link_attributes_limit);
-                data.links.add_link(Link::new(
-                    span_context,
-                    attributes,
-                    dropped_attributes_count as u32,
-                ));
-            } else {
-                data.links.dropped_count += 1;
+                self.span_limits.limit_values(&mut attributes);
+                let mut link = Link::new(span_context, attributes, dropped_attributes_count as u32);
+                if self.bytes_budget.admit_link(&mut link) {
+                    data.links.add_link(link);
+                    return;
+                }
             }
-        });
+            data.links.dropped_count += 1;
+        }
     }
 
     /// Finishes the span with given timestamp.
     fn end_with_timestamp(&mut self, timestamp: SystemTime) {
+        self.mark_ended();
         self.ensure_ended_and_exported(Some(timestamp));
     }
 }
 
 impl Span {
+    /// End a span which must not be lost, e.g. an audit event.
+    ///
+    /// The returned future resolves once every span processor has handed the
+    /// span to its exporter and the export succeeded, or with the first error
+    /// which prevented it. Spans which are not sampled resolve immediately.
+    /// The span is still exported if the future is dropped without being
+    /// awaited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::trace::{Tracer as _, TracerProvider as _};
+    /// use opentelemetry_sdk::trace::TracerProvider;
+    ///
+    /// # async fn audit(provider: TracerProvider) -> opentelemetry::trace::TraceResult<()> {
+    /// let tracer = provider.tracer("audit");
+    /// let span = tracer.start("user deleted");
+    /// span.end_with_ack().await
+    /// # }
+    /// ```
+    pub fn end_with_ack(mut self) -> impl Future<Output = TraceResult<()>> + Send + 'static {
+        self.mark_ended();
+        let mut acks: Vec<BoxFuture<'static, TraceResult<()>>> = Vec::new();
+        if self.tracer.provider().is_shutdown() {
+            if self.data.is_some() {
+                acks.push(Box::pin(future::ready(Err(TraceError::Other(
+                    "tracer provider is already shut down".into(),
+                )))));
+            }
+        } else if let Some(data) = self.end_data(None) {
+            let processors = self.tracer.provider().span_processors();
+            if let Some((last, rest)) = processors.split_last() {
+                for processor in rest {
+                    acks.push(processor.on_end_with_ack(build_export_data(
+                        data.clone(),
+                        self.span_context.clone(),
+                        &self.tracer,
+                    )));
+                }
+                acks.push(last.on_end_with_ack(build_export_data(
+                    data,
+                    self.span_context.clone(),
+                    &self.tracer,
+                )));
+            }
+        }
+
+        async move { future::join_all(acks).await.into_iter().collect() }
+    }
+
+    /// Records an explicit end of the span, reporting it if the span was
+    /// already ended.
+    fn mark_ended(&mut self) {
+        if self.ended {
+            report_misuse(&self.span_context, "was ended more than once");
+        }
+        self.ended = true;
+    }
+
     fn ensure_ended_and_exported(&mut self, timestamp: Option<SystemTime>) {
-        // skip if data has already been exported
-        let mut data = match self.data.take() {
+        let data = match self.end_data(timestamp) {
             Some(data) => data,
             None => return,
         };
 
-        let provider = self.tracer.provider();
-        // skip if provider has been shut down
-        if provider.is_shutdown() {
-            return;
-        }
-
-        // ensure end time is set via explicit end or implicitly on drop
-        if let Some(timestamp) = timestamp {
-            data.end_time = timestamp;
-        } else if data.end_time == data.start_time {
-            data.end_time = opentelemetry::time::now();
-        }
-
-        match provider.span_processors() {
+        match self.tracer.provider().span_processors() {
             [] => {}
             [processor] => {
                 processor.on_end(build_export_data(
@@ -237,6 +376,76 @@ impl Span {
             }
         }
     }
+
+    /// End the span, returning its data unless it was already exported or
+    /// the provider has been shut down.
+    fn end_data(&mut self, timestamp: Option<SystemTime>) -> Option<SpanData> {
+        // skip if data has already been exported
+        let mut data = self.data.take()?;
+
+        let provider = self.tracer.provider();
+        // skip if provider has been shut down
+        if provider.is_shutdown() {
+            provider.report_span_ended_after_shutdown(&self.span_context);
+            return None;
+        }
+
+        // ensure end time is set via explicit end or implicitly on drop
+        if let Some(timestamp) = timestamp {
+            data.end_time = timestamp;
+        } else if data.end_time == data.start_time {
+            data.end_time = opentelemetry::time::now();
+        }
+
+        let lazy_attributes = std::mem::take(&mut self.lazy_attributes);
+        if self.span_context.is_sampled() {
+            let span_attribute_limit = self.span_limits.max_attributes_per_span as usize;
+            for attribute in lazy_attributes {
+                let mut attribute = KeyValue::new(attribute.key, (attribute.value)());
+                self.span_limits.limit_value(&mut attribute.value);
+                if data.attributes.len() < span_attribute_limit
+                    && self.bytes_budget.admit_attribute(&attribute)
+                {
+                    data.attributes.push(attribute);
+                } else {
+                    data.dropped_attributes_count += 1;
+                }
+            }
+        }
+
+        let dropped_by_limits = data.dropped_attributes_count as u64
+            + data.events.dropped_count as u64
+            + data.links.dropped_count as u64
+            + data
+                .events
+                .iter()
+                .map(|event| event.dropped_attributes_count as u64)
+                .sum::<u64>()
+            + data
+                .links
+                .iter()
+                .map(|link| link.dropped_attributes_count as u64)
+                .sum::<u64>();
+        provider.record_dropped_by_limits(dropped_by_limits);
+
+        let listeners = provider.span_listeners();
+        if !listeners.is_empty() {
+            let event = SpanEndEvent {
+                span_context: &self.span_context,
+                parent_span_id: data.parent_span_id,
+                name: &data.name,
+                span_kind: &data.span_kind,
+                start_time: data.start_time,
+                end_time: data.end_time,
+                status: &data.status,
+            };
+            for listener in listeners {
+                listener.on_end(&event);
+            }
+        }
+
+        Some(data)
+    }
 }
 
 impl Drop for Span {
@@ -246,6 +455,19 @@ impl Drop for Span {
     }
 }
 
+/// Reports a misuse of a span to the global error handler, as it would
+/// otherwise only show up as a confusing trace in the backend.
+fn report_misuse(span_context: &SpanContext, problem: &str) {
+    global::handle_error(TraceError::Other(
+        format!(
+            "span {} of trace {} {problem}",
+            span_context.span_id(),
+            span_context.trace_id()
+        )
+        .into(),
+    ));
+}
+
 fn build_export_data(
     data: SpanData,
     span_context: SpanContext,
@@ -264,20 +486,24 @@ fn build_export_data(
         links: data.links,
         status: data.status,
         instrumentation_lib: tracer.instrumentation_library().clone(),
+        resource: None,
     }
 }
 
 #[cfg(all(test, feature = "testing"))]
 mod tests {
     use super::*;
-    use crate::testing::trace::NoopSpanExporter;
+    use crate::testing::trace::{InMemorySpanExporter, NoopSpanExporter};
     use crate::trace::span_limit::{
         DEFAULT_MAX_ATTRIBUTES_PER_EVENT, DEFAULT_MAX_ATTRIBUTES_PER_LINK,
         DEFAULT_MAX_ATTRIBUTES_PER_SPAN, DEFAULT_MAX_EVENT_PER_SPAN, DEFAULT_MAX_LINKS_PER_SPAN,
     };
     use crate::trace::{SpanEvents, SpanLinks};
+    use once_cell::sync::Lazy;
     use opentelemetry::trace::{self, SpanBuilder, TraceFlags, TraceId, Tracer};
     use opentelemetry::{trace::Span as _, trace::TracerProvider};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
     use std::vec;
 
@@ -299,6 +525,31 @@ mod tests {
         (tracer, data)
     }
 
+    /// Messages of the errors passed to the global error handler. Tests run
+    /// concurrently, so they only look for the errors naming their own spans.
+    fn handled_errors() -> Vec<String> {
+        static ERRORS: Lazy<Arc<Mutex<Vec<String>>>> = Lazy::new(|| {
+            let errors = Arc::new(Mutex::new(Vec::new()));
+            let handled = errors.clone();
+            global::set_error_handler(move |err| handled.lock().unwrap().push(err.to_string()))
+                .unwrap();
+            errors
+        });
+        ERRORS.lock().unwrap().clone()
+    }
+
+    fn reports_of(span_context: &SpanContext, problem: &str) -> usize {
+        let span = format!(
+            "span {} of trace {}",
+            span_context.span_id(),
+            span_context.trace_id()
+        );
+        handled_errors()
+            .iter()
+            .filter(|err| err.contains(&span) && err.contains(problem))
+            .count()
+    }
+
     fn create_span() -> Span {
         let (tracer, data) = init();
         Span::new(
@@ -490,6 +741,49 @@ mod tests {
         span.with_data(|data| assert_eq!(data.end_time, timestamp));
     }
 
+    #[test]
+    fn end_marks_span_ended() {
+        handled_errors();
+        let provider = crate::trace::TracerProvider::default();
+        let mut span = provider.tracer("test").start("test_span");
+        let span_context = span.span_context().clone();
+        assert!(!span.ended);
+        span.end();
+        assert!(span.ended);
+        assert_eq!(reports_of(&span_context, "was ended more than once"), 0);
+
+        // ending again is reported but keeps the span ended
+        span.end();
+        assert!(span.ended);
+        assert_eq!(reports_of(&span_context, "was ended more than once"), 1);
+    }
+
+    #[test]
+    fn span_ended_after_shutdown_is_reported_once_per_provider() {
+        handled_errors();
+        let problem = "was ended after its tracer provider shut down";
+        let provider = crate::trace::TracerProvider::default();
+        let tracer = provider.tracer("test");
+        let mut first = tracer.start("first");
+        let mut second = tracer.start("second");
+        let (first_context, second_context) =
+            (first.span_context().clone(), second.span_context().clone());
+
+        provider.shutdown().unwrap();
+        first.end();
+        second.end();
+        assert_eq!(reports_of(&first_context, problem), 1);
+        assert_eq!(reports_of(&second_context, problem), 0);
+
+        // another provider reports its own first span
+        let provider = crate::trace::TracerProvider::default();
+        let mut span = provider.tracer("test").start("third");
+        let span_context = span.span_context().clone();
+        provider.shutdown().unwrap();
+        span.end();
+        assert_eq!(reports_of(&span_context, problem), 1);
+    }
+
     #[test]
     fn noop_after_end() {
         let mut span = create_span();
@@ -676,6 +970,123 @@ mod tests {
         assert_eq!(link_vec.len(), DEFAULT_MAX_LINKS_PER_SPAN as usize);
     }
 
+    #[test]
+    fn exceed_span_bytes_limit() {
+        let provider = crate::trace::TracerProvider::builder()
+            .with_simple_exporter(NoopSpanExporter::new())
+            .with_config(crate::trace::Config::default().with_max_bytes_per_span(40))
+            .build();
+        let tracer = provider.tracer("opentelemetry-test");
+
+        // 9 bytes of attributes, 24 bytes of links and 2 bytes of events.
+        let span_builder = tracer
+            .span_builder("test")
+            .with_attributes(vec![KeyValue::new("a", 1)])
+            .with_links(vec![Link::with_context(SpanContext::new(
+                TraceId::from_u128(12),
+                SpanId::from_u64(12),
+                TraceFlags::default(),
+                false,
+                Default::default(),
+            ))])
+            .with_events(vec![Event::with_name("ev")]);
+        let mut span = tracer.build(span_builder);
+
+        // Uses the last 5 bytes of the budget.
+        span.set_attribute(KeyValue::new("b", "xxxx"));
+        span.add_event("e", vec![]);
+        span.set_attribute(KeyValue::new("c", true));
+
+        let data = span.data.clone().expect("span is recording");
+        assert_eq!(
+            data.attributes,
+            vec![KeyValue::new("a", 1), KeyValue::new("b", "xxxx")]
+        );
+        assert_eq!(data.dropped_attributes_count, 1);
+        assert_eq!(data.links.len(), 1);
+        assert_eq!(data.events.len(), 1);
+        assert_eq!(data.events.dropped_count, 1);
+    }
+
+    #[test]
+    fn sanitize_span_attributes() {
+        let sanitizer = crate::AttributeSanitizer::new()
+            .with_max_length(3)
+            .with_control_chars_stripped();
+        let provider = crate::trace::TracerProvider::builder()
+            .with_simple_exporter(NoopSpanExporter::new())
+            .with_config(crate::trace::Config::default().with_attribute_sanitizer(sanitizer))
+            .build();
+        let tracer = provider.tracer("opentelemetry-test");
+
+        let span_builder = tracer
+            .span_builder("test")
+            .with_attributes(vec![KeyValue::new("a", "abcdef")])
+            .with_events(vec![Event::new(
+                "ev",
+                opentelemetry::time::now(),
+                vec![KeyValue::new("b", "a\u{0}bcd")],
+                0,
+            )]);
+        let mut span = tracer.build(span_builder);
+        span.set_attribute(KeyValue::new("c", "\u{1b}xyz"));
+        span.set_attribute(KeyValue::new("d", 12345));
+        span.add_link(
+            SpanContext::new(
+                TraceId::from_u128(12),
+                SpanId::from_u64(12),
+                TraceFlags::default(),
+                false,
+                Default::default(),
+            ),
+            vec![KeyValue::new("e", "uvwxyz")],
+        );
+
+        let data = span.data.clone().expect("span is recording");
+        assert_eq!(
+            data.attributes,
+            vec![
+                KeyValue::new("a", "abc"),
+                KeyValue::new("c", "xyz"),
+                KeyValue::new("d", 12345)
+            ]
+        );
+        assert_eq!(
+            data.events.events[0].attributes,
+            vec![KeyValue::new("b", "abc")]
+        );
+        assert_eq!(
+            data.links.links[0].attributes,
+            vec![KeyValue::new("e", "uvw")]
+        );
+    }
+
+    #[test]
+    fn truncate_span_attribute_values() {
+        let provider = crate::trace::TracerProvider::builder()
+            .with_simple_exporter(NoopSpanExporter::new())
+            .with_config(crate::trace::Config::default().with_max_attribute_value_length(2))
+            .build();
+        let tracer = provider.tracer("opentelemetry-test");
+
+        let span_builder = tracer
+            .span_builder("test")
+            .with_attributes(vec![KeyValue::new("a", "abc")]);
+        let mut span = tracer.build(span_builder);
+        span.set_attribute(KeyValue::new("b", "éa"));
+        span.add_event("ev", vec![KeyValue::new("c", "xyz")]);
+
+        let data = span.data.clone().expect("span is recording");
+        assert_eq!(
+            data.attributes,
+            vec![KeyValue::new("a", "ab"), KeyValue::new("b", "é")]
+        );
+        assert_eq!(
+            data.events.events[0].attributes,
+            vec![KeyValue::new("c", "xy")]
+        );
+    }
+
     #[test]
     fn exceed_span_events_limit() {
         let exporter = NoopSpanExporter::new();
@@ -724,4 +1135,59 @@ mod tests {
         // return none if the provider has already been dropped
         assert!(dropped_span.exported_data().is_none());
     }
+
+    #[test]
+    fn set_attribute_lazy_evaluated_on_end_of_sampled_span() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = crate::trace::TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = provider.tracer("test");
+
+        let mut span = tracer.start("test_span");
+        span.set_attribute_lazy("lazy", || "value");
+        span.with_data(|data| assert!(data.attributes.is_empty()));
+        span.end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].attributes, vec![KeyValue::new("lazy", "value")]);
+    }
+
+    #[test]
+    fn end_with_ack_resolves_after_export() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = crate::trace::TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = provider.tracer("test");
+
+        let span = tracer.start("test_span");
+        futures_executor::block_on(span.end_with_ack()).unwrap();
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 1);
+
+        let span = tracer.start("test_span");
+        let _ = provider.shutdown();
+        assert!(futures_executor::block_on(span.end_with_ack()).is_err());
+    }
+
+    #[test]
+    fn set_attribute_lazy_not_evaluated_for_unsampled_span() {
+        let evaluated = Arc::new(AtomicBool::new(false));
+        let provider = crate::trace::TracerProvider::builder()
+            .with_config(
+                crate::trace::Config::default().with_sampler(crate::trace::Sampler::AlwaysOff),
+            )
+            .build();
+        let tracer = provider.tracer("test");
+
+        let mut span = tracer.start("test_span");
+        let evaluated_clone = evaluated.clone();
+        span.set_attribute_lazy("lazy", move || {
+            evaluated_clone.store(true, Ordering::SeqCst);
+            "value"
+        });
+        span.end();
+
+        assert!(!evaluated.load(Ordering::SeqCst));
+    }
 }
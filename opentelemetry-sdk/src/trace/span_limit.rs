@@ -9,9 +9,24 @@
 ///  - Maximum allowed span link count
 ///  - Maximum allowed attribute per span event count
 ///  - Maximum allowed attribute per span link count
+///  - Maximum allowed bytes of the attributes, events and links of a span
+///  - Maximum allowed length of attribute string values
+///  - Sanitization of attribute string values, see [`AttributeSanitizer`]
 ///
 /// If the limit has been breached. The attributes, events or links will be dropped based on their
 /// index in the collection. The one added to collections later will be dropped first.
+///
+/// The bytes limit is shared by the attributes, events and links of a span. Spans started with
+/// attributes, links and events consume it in that order. Once an attribute, event or link
+/// does not fit, it is dropped along with everything added to the span afterwards, and counted in
+/// the matching dropped count. Events and links are kept with the leading attributes that fit if
+/// their name or span context fit.
+use crate::budget::key_value_size;
+use crate::AttributeSanitizer;
+use opentelemetry::{
+    trace::{Event, Link},
+    KeyValue, Value,
+};
 
 pub(crate) const DEFAULT_MAX_EVENT_PER_SPAN: u32 = 128;
 pub(crate) const DEFAULT_MAX_ATTRIBUTES_PER_SPAN: u32 = 128;
@@ -32,6 +47,18 @@ pub struct SpanLimits {
     pub max_attributes_per_event: u32,
     /// The max attributes that can be added into a `Link`
     pub max_attributes_per_link: u32,
+    /// The max bytes of the attributes, events and links of a `Span`.
+    ///
+    /// Sizes are estimated from the length of keys, names and string values
+    /// and the in-memory size of other values. `None` disables the limit.
+    pub max_bytes_per_span: Option<u32>,
+    /// Sanitizes the string values of the attributes of a `Span`, its events
+    /// and links before the other limits apply. `None` leaves values as is.
+    pub attribute_sanitizer: Option<AttributeSanitizer>,
+    /// The max length in bytes of the string values of the attributes of a
+    /// `Span`, its events and links. Values are truncated on character
+    /// boundaries. `None` disables the limit.
+    pub max_attribute_value_length: Option<u32>,
 }
 
 impl Default for SpanLimits {
@@ -42,6 +69,169 @@ impl Default for SpanLimits {
             max_links_per_span: DEFAULT_MAX_LINKS_PER_SPAN,
             max_attributes_per_link: DEFAULT_MAX_ATTRIBUTES_PER_LINK,
             max_attributes_per_event: DEFAULT_MAX_ATTRIBUTES_PER_EVENT,
+            max_bytes_per_span: None,
+            attribute_sanitizer: None,
+            max_attribute_value_length: None,
+        }
+    }
+}
+
+impl SpanLimits {
+    /// Sanitizes `value`, then truncates it to the attribute value length.
+    pub(crate) fn limit_value(&self, value: &mut Value) {
+        if let Some(sanitizer) = &self.attribute_sanitizer {
+            sanitizer.sanitize_value(value);
+        }
+        if let Some(max_length) = self.max_attribute_value_length {
+            AttributeSanitizer::new()
+                .with_max_length(max_length as usize)
+                .sanitize_value(value);
+        }
+    }
+
+    /// Sanitizes and truncates the values of `attributes`.
+    pub(crate) fn limit_values(&self, attributes: &mut [KeyValue]) {
+        if let Some(sanitizer) = &self.attribute_sanitizer {
+            sanitizer.sanitize_attributes(attributes);
+        }
+        if let Some(max_length) = self.max_attribute_value_length {
+            AttributeSanitizer::new()
+                .with_max_length(max_length as usize)
+                .sanitize_attributes(attributes);
+        }
+    }
+}
+
+/// The estimated size of the span context of a link, its trace and span IDs.
+const LINK_SPAN_CONTEXT_SIZE: usize = 24;
+
+/// Tracks the bytes left to the attributes, events and links of a span.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SpanBytesBudget {
+    remaining: Option<usize>,
+    exhausted: bool,
+}
+
+impl SpanBytesBudget {
+    pub(crate) fn new(span_limits: &SpanLimits) -> Self {
+        SpanBytesBudget {
+            remaining: span_limits.max_bytes_per_span.map(|max| max as usize),
+            exhausted: false,
+        }
+    }
+
+    /// Reserves `size` bytes, returning `false` once the budget is exceeded.
+    fn reserve(&mut self, size: usize) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        match &mut self.remaining {
+            None => true,
+            Some(remaining) if size <= *remaining => {
+                *remaining -= size;
+                true
+            }
+            Some(_) => {
+                self.exhausted = true;
+                false
+            }
+        }
+    }
+
+    /// Reserves the bytes of `attribute`.
+    pub(crate) fn admit_attribute(&mut self, attribute: &KeyValue) -> bool {
+        self.reserve(key_value_size(attribute))
+    }
+
+    /// Keeps the leading `attributes` which fit in the budget, returning the
+    /// number of attributes dropped.
+    pub(crate) fn retain_attributes(&mut self, attributes: &mut Vec<KeyValue>) -> usize {
+        let kept = attributes
+            .iter()
+            .take_while(|attribute| self.admit_attribute(attribute))
+            .count();
+        let dropped = attributes.len() - kept;
+        attributes.truncate(kept);
+        dropped
+    }
+
+    /// Reserves the bytes of `event`, dropping the attributes which do not fit.
+    ///
+    /// Returns `false` if the event should be dropped.
+    pub(crate) fn admit_event(&mut self, event: &mut Event) -> bool {
+        if !self.reserve(event.name.len()) {
+            return false;
         }
+        let dropped = self.retain_attributes(&mut event.attributes);
+        event.dropped_attributes_count += dropped as u32;
+        true
+    }
+
+    /// Reserves the bytes of `link`, dropping the attributes which do not fit.
+    ///
+    /// Returns `false` if the link should be dropped.
+    pub(crate) fn admit_link(&mut self, link: &mut Link) -> bool {
+        if !self.reserve(LINK_SPAN_CONTEXT_SIZE) {
+            return false;
+        }
+        let dropped = self.retain_attributes(&mut link.attributes);
+        link.dropped_attributes_count += dropped as u32;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(max_bytes: u32) -> SpanBytesBudget {
+        SpanBytesBudget::new(&SpanLimits {
+            max_bytes_per_span: Some(max_bytes),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn unlimited_budget_admits_everything() {
+        let mut budget = SpanBytesBudget::new(&SpanLimits::default());
+        let mut attributes = vec![KeyValue::new("key", "x".repeat(1 << 20)); 16];
+        assert_eq!(budget.retain_attributes(&mut attributes), 0);
+        assert_eq!(attributes.len(), 16);
+    }
+
+    #[test]
+    fn attributes_are_truncated_in_order() {
+        // "k" + 8 bytes per i64 attribute.
+        let mut budget = budget(20);
+        let mut attributes = vec![
+            KeyValue::new("k", 1),
+            KeyValue::new("k", 2),
+            KeyValue::new("k", 3),
+        ];
+        assert_eq!(budget.retain_attributes(&mut attributes), 1);
+        assert_eq!(attributes.len(), 2);
+
+        // Smaller items added after the budget was exceeded are dropped too.
+        assert!(!budget.admit_attribute(&KeyValue::new("k", true)));
+    }
+
+    #[test]
+    fn events_and_links_keep_leading_attributes() {
+        let mut budget = budget(40);
+        let mut link = Link::new(
+            opentelemetry::trace::SpanContext::empty_context(),
+            vec![KeyValue::new("k", 1)],
+            0,
+        );
+        assert!(budget.admit_link(&mut link));
+        assert_eq!(link.attributes.len(), 1);
+
+        let mut event = Event::with_name("event");
+        event.attributes = vec![KeyValue::new("k", "v"), KeyValue::new("k", 2)];
+        assert!(budget.admit_event(&mut event));
+        assert_eq!(event.attributes, vec![KeyValue::new("k", "v")]);
+        assert_eq!(event.dropped_attributes_count, 1);
+
+        assert!(!budget.admit_event(&mut Event::with_name("late")));
     }
 }
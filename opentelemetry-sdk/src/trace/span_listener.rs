@@ -0,0 +1,70 @@
+//! # Span Listener
+//!
+//! [`SpanListener`]s receive lightweight notifications when spans start and
+//! end. Unlike [`SpanProcessor`]s they are not part of the export pipeline:
+//! they cannot modify spans, they receive borrowed views of the span instead of
+//! owned [`SpanData`], and there are no batching or ordering guarantees between
+//! notifications delivered on different threads.
+//!
+//! This makes listeners suitable for cheap in-process consumers of span timing
+//! signals, such as live debugging UIs or adaptive concurrency controllers.
+//!
+//! [`SpanProcessor`]: crate::trace::SpanProcessor
+//! [`SpanData`]: crate::export::trace::SpanData
+use opentelemetry::trace::{SpanContext, SpanId, SpanKind, Status};
+use std::fmt::Debug;
+use std::time::SystemTime;
+
+/// Observer of span lifecycle events, registered with
+/// [`Builder::with_span_listener`].
+///
+/// Listeners are only notified for recording spans. Notifications are delivered
+/// synchronously on the thread starting or ending the span, so implementations
+/// should return quickly.
+///
+/// [`Builder::with_span_listener`]: crate::trace::Builder::with_span_listener
+pub trait SpanListener: Send + Sync + Debug {
+    /// Called when a recording span is started, after all span processors have
+    /// been notified.
+    fn on_start(&self, _event: &SpanStartEvent<'_>) {}
+
+    /// Called when a recording span is ended, before it is handed to the span
+    /// processors.
+    fn on_end(&self, _event: &SpanEndEvent<'_>) {}
+}
+
+/// Notification that a span was started.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SpanStartEvent<'a> {
+    /// The span context of the started span.
+    pub span_context: &'a SpanContext,
+    /// The span id of the parent span, or [`SpanId::INVALID`] for root spans.
+    pub parent_span_id: SpanId,
+    /// The span name.
+    pub name: &'a str,
+    /// The span kind.
+    pub span_kind: &'a SpanKind,
+    /// The time the span was started.
+    pub start_time: SystemTime,
+}
+
+/// Notification that a span was ended.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SpanEndEvent<'a> {
+    /// The span context of the ended span.
+    pub span_context: &'a SpanContext,
+    /// The span id of the parent span, or [`SpanId::INVALID`] for root spans.
+    pub parent_span_id: SpanId,
+    /// The span name.
+    pub name: &'a str,
+    /// The span kind.
+    pub span_kind: &'a SpanKind,
+    /// The time the span was started.
+    pub start_time: SystemTime,
+    /// The time the span was ended.
+    pub end_time: SystemTime,
+    /// The span status.
+    pub status: &'a Status,
+}
@@ -34,10 +34,15 @@
 //! [`is_recording`]: opentelemetry::trace::Span::is_recording()
 //! [`TracerProvider`]: opentelemetry::trace::TracerProvider
 
+use crate::budget::{attributes_size, BudgetPriority, Reservation, TelemetryBudget};
+use crate::dropped::DroppedCounters;
 use crate::export::trace::{ExportResult, SpanData, SpanExporter};
 use crate::resource::Resource;
-use crate::runtime::{RuntimeChannel, TrySend};
+use crate::runtime::{RuntimeChannel, TrySend, TrySendError};
+use crate::shutdown::{self, ShutdownError, ShutdownResult};
 use crate::trace::Span;
+use crate::util::positive_from_env;
+use crate::{BuildError, DroppedTelemetry, ProcessorDiagnostics};
 use futures_channel::oneshot;
 use futures_util::{
     future::{self, BoxFuture, Either},
@@ -46,13 +51,21 @@ use futures_util::{
     StreamExt as _,
 };
 use opentelemetry::global;
+#[cfg(feature = "experimental_trace_sampling_veto")]
+use opentelemetry::trace::SamplingDecision;
 use opentelemetry::{
     trace::{TraceError, TraceResult},
     Context,
 };
 use std::cmp::min;
+use std::future::Future;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
 use std::sync::{Arc, Mutex};
-use std::{env, fmt, str::FromStr, time::Duration};
+use std::thread::{self, JoinHandle};
+use std::time::{Instant, SystemTime};
+use std::{env, fmt, time::Duration};
 
 /// Delay interval between two consecutive exports.
 const OTEL_BSP_SCHEDULE_DELAY: &str = "OTEL_BSP_SCHEDULE_DELAY";
@@ -88,6 +101,19 @@ pub trait SpanProcessor: Send + Sync + std::fmt::Debug {
     /// already set). This method is called synchronously within the `Span::end`
     /// API, therefore it should not block or throw an exception.
     fn on_end(&self, span: SpanData);
+    /// Called instead of [`on_end`] for spans which must not be lost.
+    ///
+    /// The returned future resolves once the span has been accepted by the
+    /// exporter, or with the error which prevented it. The default
+    /// implementation calls `on_end` and then force flushes the processor,
+    /// blocking the calling thread. Processors should override it to only
+    /// wait for the export of the span.
+    ///
+    /// [`on_end`]: SpanProcessor::on_end
+    fn on_end_with_ack(&self, span: SpanData) -> BoxFuture<'static, TraceResult<()>> {
+        self.on_end(span);
+        Box::pin(future::ready(self.force_flush()))
+    }
     /// Force the spans lying in the cache to be exported.
     fn force_flush(&self) -> TraceResult<()>;
     /// Shuts down the processor. Called when SDK is shut down. This is an
@@ -95,8 +121,63 @@ pub trait SpanProcessor: Send + Sync + std::fmt::Debug {
     ///
     /// Implementation should make sure shutdown can be called multiple times.
     fn shutdown(&self) -> TraceResult<()>;
+    /// Shuts down the processor, waiting at most `timeout` for it to complete.
+    ///
+    /// The default implementation ignores the timeout and calls [`shutdown`].
+    /// Processors which wait for their exporter while shutting down should
+    /// override it.
+    ///
+    /// [`shutdown`]: SpanProcessor::shutdown
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> ShutdownResult {
+        self.shutdown()
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))
+    }
     /// Set the resource for the log processor.
     fn set_resource(&mut self, _resource: &Resource) {}
+    /// The spans dropped by the processor, none by default.
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        DroppedTelemetry::default()
+    }
+    /// The queue depth and last export error of the processor, reported by
+    /// [`PipelineDiagnostics`](crate::PipelineDiagnostics), none by default.
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        ProcessorDiagnostics::default()
+    }
+    /// Lets the processor downgrade the sampling decision of a recording span,
+    /// with all the attributes the span started with available.
+    ///
+    /// This experimental hook is called in registration order, until a
+    /// processor drops the span, before [`on_start`] is called for any of them,
+    /// e.g. to evaluate a per-tenant kill switch.
+    /// The span keeps the lowest decision returned by the processors, which
+    /// can't upgrade the decision of the sampler. `RecordOnly` clears the
+    /// sampled flag of the span and `Drop` also stops recording it, so every
+    /// processor's [`on_start`] and the children of the span observe the final
+    /// decision. The default implementation keeps the decision of the sampler.
+    ///
+    /// [`on_start`]: SpanProcessor::on_start
+    #[cfg(feature = "experimental_trace_sampling_veto")]
+    fn sampling_decision(&self, _span: &Span, _cx: &Context) -> SamplingDecision {
+        SamplingDecision::RecordAndSample
+    }
+}
+
+/// The lowest of the sampling decisions of `processors` for `span`.
+#[cfg(feature = "experimental_trace_sampling_veto")]
+pub(crate) fn lowest_sampling_decision(
+    processors: &[Box<dyn SpanProcessor>],
+    span: &Span,
+    cx: &Context,
+) -> SamplingDecision {
+    let mut lowest = SamplingDecision::RecordAndSample;
+    for processor in processors {
+        match processor.sampling_decision(span, cx) {
+            SamplingDecision::Drop => return SamplingDecision::Drop,
+            SamplingDecision::RecordOnly => lowest = SamplingDecision::RecordOnly,
+            SamplingDecision::RecordAndSample => {}
+        }
+    }
+    lowest
 }
 
 /// A [SpanProcessor] that passes finished spans to the configured
@@ -106,12 +187,14 @@ pub trait SpanProcessor: Send + Sync + std::fmt::Debug {
 #[derive(Debug)]
 pub struct SimpleSpanProcessor {
     exporter: Mutex<Box<dyn SpanExporter>>,
+    dropped: DroppedCounters,
 }
 
 impl SimpleSpanProcessor {
     pub(crate) fn new(exporter: Box<dyn SpanExporter>) -> Self {
         Self {
             exporter: Mutex::new(exporter),
+            dropped: DroppedCounters::default(),
         }
     }
 }
@@ -133,13 +216,33 @@ impl SpanProcessor for SimpleSpanProcessor {
             .and_then(|mut exporter| futures_executor::block_on(exporter.export(vec![span])));
 
         if let Err(err) = result {
+            self.dropped.export_failed(1, &err);
             global::handle_error(err);
         }
     }
 
+    fn on_end_with_ack(&self, span: SpanData) -> BoxFuture<'static, TraceResult<()>> {
+        if !span.span_context.is_sampled() {
+            return Box::pin(future::ready(Ok(())));
+        }
+
+        let result = self
+            .exporter
+            .lock()
+            .map_err(|_| TraceError::Other("SimpleSpanProcessor mutex poison".into()))
+            .and_then(|mut exporter| futures_executor::block_on(exporter.export(vec![span])));
+        if let Err(err) = &result {
+            self.dropped.export_failed(1, err);
+        }
+        Box::pin(future::ready(result))
+    }
+
     fn force_flush(&self) -> TraceResult<()> {
-        // Nothing to flush for simple span processor.
-        Ok(())
+        // Spans are exported as they end, only the exporter may buffer them.
+        self.exporter
+            .lock()
+            .map_err(|_| TraceError::Other("SimpleSpanProcessor mutex poison".into()))
+            .and_then(|mut exporter| futures_executor::block_on(exporter.force_flush()))
     }
 
     fn shutdown(&self) -> TraceResult<()> {
@@ -158,6 +261,368 @@ impl SpanProcessor for SimpleSpanProcessor {
             exporter.set_resource(resource);
         }
     }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        self.dropped.diagnostics(false)
+    }
+}
+
+/// A [`SpanProcessor`] buffering finished spans in memory until it is
+/// flushed, for environments freezing the process between invocations such as
+/// AWS Lambda and other FaaS platforms.
+///
+/// Unlike [`BatchSpanProcessor`], it spawns no background task and never
+/// exports on its own: the buffered spans are exported when
+/// [`flush_async`] is awaited, typically by the hook running at the end of
+/// each invocation, or when the processor is force flushed or shut down
+/// through the [`TracerProvider`]. Spans ending while `max_queue_size` spans
+/// are buffered are dropped.
+///
+/// Force flushing blocks the calling thread until the export completes, async
+/// invocation handlers should await [`flush_async`] instead.
+///
+/// The processor is a handle which can be cloned cheaply, keep a clone to
+/// flush it once it is registered with the tracer provider.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "testing")]
+/// # {
+/// use opentelemetry::trace::{Tracer, TracerProvider as _};
+/// use opentelemetry_sdk::{
+///     testing::trace::NoopSpanExporter,
+///     trace::{FinishedSpanBatchOnDemandProcessor, TracerProvider},
+/// };
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let processor = FinishedSpanBatchOnDemandProcessor::builder(NoopSpanExporter::new()).build();
+/// let provider = TracerProvider::builder()
+///     .with_span_processor(processor.clone())
+///     .build();
+///
+/// // handle an invocation
+/// provider.tracer("handler").in_span("invocation", |_cx| {});
+/// // export its spans before the environment is frozen
+/// processor.flush_async().await.unwrap();
+/// # }
+/// # }
+/// ```
+///
+/// [`flush_async`]: FinishedSpanBatchOnDemandProcessor::flush_async
+/// [`TracerProvider`]: crate::trace::TracerProvider
+#[derive(Clone, Debug)]
+pub struct FinishedSpanBatchOnDemandProcessor {
+    inner: Arc<OnDemandInner>,
+}
+
+#[derive(Debug)]
+struct OnDemandInner {
+    spans: Mutex<Vec<SpanData>>,
+    max_queue_size: usize,
+    exporter: Mutex<Box<dyn SpanExporter>>,
+    is_shutdown: AtomicBool,
+    dropped: DroppedCounters,
+}
+
+impl FinishedSpanBatchOnDemandProcessor {
+    /// Create a new builder of an on demand processor exporting to `exporter`.
+    pub fn builder<E>(exporter: E) -> FinishedSpanBatchOnDemandProcessorBuilder<E>
+    where
+        E: SpanExporter + 'static,
+    {
+        FinishedSpanBatchOnDemandProcessorBuilder {
+            exporter,
+            max_queue_size: OTEL_BSP_MAX_QUEUE_SIZE_DEFAULT,
+        }
+    }
+
+    /// Exports the buffered spans and then force flushes the exporter.
+    ///
+    /// The returned future runs on the caller's executor and is not bounded by
+    /// a timeout, the exporter is expected to time out its requests. Spans
+    /// ending while the export is in progress are kept for the next flush.
+    pub fn flush_async(&self) -> impl Future<Output = TraceResult<()>> + Send + 'static {
+        let inner = Arc::clone(&self.inner);
+        async move {
+            let spans = inner
+                .spans
+                .lock()
+                .map(|mut spans| mem::take(&mut *spans))
+                .map_err(|_| TraceError::from("FinishedSpanBatchOnDemandProcessor mutex poison"))?;
+
+            if !spans.is_empty() {
+                let count = spans.len() as u64;
+                let export = inner.with_exporter(|exporter| exporter.export(spans))?;
+                if let Err(err) = export.await {
+                    inner.dropped.export_failed(count, &err);
+                    return Err(err);
+                }
+            }
+
+            inner
+                .with_exporter(|exporter| exporter.force_flush())?
+                .await
+        }
+    }
+
+    /// Buffers `span`, returning `false` if it was dropped.
+    fn push(&self, span: SpanData) -> bool {
+        if !span.span_context.is_sampled() || self.inner.is_shutdown.load(Ordering::Relaxed) {
+            return false;
+        }
+        let Ok(mut spans) = self.inner.spans.lock() else {
+            return false;
+        };
+        if spans.len() < self.inner.max_queue_size {
+            spans.push(span);
+            true
+        } else {
+            self.inner.dropped.queue_full(1);
+            false
+        }
+    }
+}
+
+impl OnDemandInner {
+    fn with_exporter<T>(&self, f: impl FnOnce(&mut dyn SpanExporter) -> T) -> TraceResult<T> {
+        self.exporter
+            .lock()
+            .map(|mut exporter| f(exporter.as_mut()))
+            .map_err(|_| TraceError::from("FinishedSpanBatchOnDemandProcessor mutex poison"))
+    }
+}
+
+impl SpanProcessor for FinishedSpanBatchOnDemandProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {
+        // Ignored
+    }
+
+    fn on_end(&self, span: SpanData) {
+        self.push(span);
+    }
+
+    fn on_end_with_ack(&self, span: SpanData) -> BoxFuture<'static, TraceResult<()>> {
+        if !span.span_context.is_sampled() {
+            return Box::pin(future::ready(Ok(())));
+        }
+        if !self.push(span) {
+            return Box::pin(future::ready(Err(TraceError::from(
+                "span dropped by FinishedSpanBatchOnDemandProcessor",
+            ))));
+        }
+        Box::pin(self.flush_async())
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        futures_executor::block_on(self.flush_async())
+    }
+
+    fn shutdown(&self) -> TraceResult<()> {
+        if self.inner.is_shutdown.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let result = self.force_flush();
+        self.inner.with_exporter(|exporter| exporter.shutdown())?;
+        result
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        let _ = self
+            .inner
+            .with_exporter(|exporter| exporter.set_resource(resource));
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.inner.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        let queued = self.inner.spans.lock().map_or(0, |spans| spans.len());
+        ProcessorDiagnostics {
+            queued: Some(queued as u64),
+            ..self.inner.dropped.diagnostics(false)
+        }
+    }
+}
+
+/// A builder for creating [`FinishedSpanBatchOnDemandProcessor`] instances.
+#[derive(Debug)]
+pub struct FinishedSpanBatchOnDemandProcessorBuilder<E> {
+    exporter: E,
+    max_queue_size: usize,
+}
+
+impl<E> FinishedSpanBatchOnDemandProcessorBuilder<E>
+where
+    E: SpanExporter + 'static,
+{
+    /// Set max_queue_size for [`FinishedSpanBatchOnDemandProcessorBuilder`].
+    /// It's the maximum number of spans buffered until the next flush, further
+    /// spans are dropped. The default value is 2048.
+    pub fn with_max_queue_size(self, max_queue_size: usize) -> Self {
+        FinishedSpanBatchOnDemandProcessorBuilder {
+            max_queue_size,
+            ..self
+        }
+    }
+
+    /// Build an on demand processor.
+    pub fn build(self) -> FinishedSpanBatchOnDemandProcessor {
+        FinishedSpanBatchOnDemandProcessor {
+            inner: Arc::new(OnDemandInner {
+                spans: Mutex::new(Vec::new()),
+                max_queue_size: self.max_queue_size,
+                exporter: Mutex::new(Box::new(self.exporter)),
+                is_shutdown: AtomicBool::new(false),
+                dropped: DroppedCounters::default(),
+            }),
+        }
+    }
+}
+
+/// A [`SpanProcessor`] invoking a fixed chain of processors in order.
+///
+/// The composite processor gives the chain well-defined semantics:
+///
+/// * `on_start` and `on_end` are called on each processor in the order they
+///   were given. Every processor but the last receives a clone of the ended
+///   span.
+/// * `on_end_with_ack` resolves once every processor accepted the span, with
+///   the first error otherwise.
+/// * `force_flush` flushes every processor in order, even if an earlier one
+///   failed, and returns an error combining all failures.
+/// * `shutdown` shuts every processor down in order, even if an earlier one
+///   failed, and returns an error combining all failures. Processors
+///   buffering spans, e.g. a tail sampling processor, should therefore be
+///   placed before the processors they forward spans to.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_sdk::trace::{CompositeSpanProcessor, SpanProcessor, TracerProvider};
+///
+/// fn init(sampling: Box<dyn SpanProcessor>, batch: Box<dyn SpanProcessor>) -> TracerProvider {
+///     // spans are sampled before they are batched, and the sampling processor
+///     // is shut down first so it can hand its remaining spans to the batch
+///     TracerProvider::builder()
+///         .with_span_processor(CompositeSpanProcessor::new(vec![sampling, batch]))
+///         .build()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CompositeSpanProcessor {
+    processors: Vec<Box<dyn SpanProcessor>>,
+}
+
+impl CompositeSpanProcessor {
+    /// Create a processor invoking `processors` in order.
+    pub fn new(processors: Vec<Box<dyn SpanProcessor>>) -> Self {
+        CompositeSpanProcessor { processors }
+    }
+}
+
+/// Combines the errors of the processors of a [`CompositeSpanProcessor`].
+fn combine_errors(mut errs: Vec<TraceError>) -> TraceResult<()> {
+    match errs.len() {
+        0 => Ok(()),
+        1 => Err(errs.remove(0)),
+        _ => Err(TraceError::Other(
+            errs.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+                .into(),
+        )),
+    }
+}
+
+impl SpanProcessor for CompositeSpanProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        for processor in &self.processors {
+            processor.on_start(span, cx);
+        }
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if let Some((last, rest)) = self.processors.split_last() {
+            for processor in rest {
+                processor.on_end(span.clone());
+            }
+            last.on_end(span);
+        }
+    }
+
+    fn on_end_with_ack(&self, span: SpanData) -> BoxFuture<'static, TraceResult<()>> {
+        let mut acks = Vec::with_capacity(self.processors.len());
+        if let Some((last, rest)) = self.processors.split_last() {
+            for processor in rest {
+                acks.push(processor.on_end_with_ack(span.clone()));
+            }
+            acks.push(last.on_end_with_ack(span));
+        }
+
+        Box::pin(async move { future::join_all(acks).await.into_iter().collect() })
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        combine_errors(
+            self.processors
+                .iter()
+                .filter_map(|processor| processor.force_flush().err())
+                .collect(),
+        )
+    }
+
+    fn shutdown(&self) -> TraceResult<()> {
+        combine_errors(
+            self.processors
+                .iter()
+                .filter_map(|processor| processor.shutdown().err())
+                .collect(),
+        )
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        let deadline = Instant::now() + timeout;
+        let results: Vec<_> = self
+            .processors
+            .iter()
+            .map(|processor| processor.shutdown_with_timeout(shutdown::remaining(deadline)))
+            .collect();
+        shutdown::combine(results)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        for processor in &mut self.processors {
+            processor.set_resource(resource);
+        }
+    }
+
+    #[cfg(feature = "experimental_trace_sampling_veto")]
+    fn sampling_decision(&self, span: &Span, cx: &Context) -> SamplingDecision {
+        lowest_sampling_decision(&self.processors, span, cx)
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.processors
+            .iter()
+            .map(|processor| processor.dropped_telemetry())
+            .sum()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        ProcessorDiagnostics::combine(
+            self.processors
+                .iter()
+                .map(|processor| processor.diagnostics()),
+        )
+    }
 }
 
 /// A [`SpanProcessor`] that asynchronously buffers finished spans and reports
@@ -204,7 +669,7 @@ impl SpanProcessor for SimpleSpanProcessor {
 ///
 ///     // Create a batch span processor using an exporter and a runtime
 ///     let batch = trace::BatchSpanProcessor::builder(exporter, runtime::Tokio)
-///         .with_batch_config(BatchConfigBuilder::default().with_max_queue_size(4096).build())
+///         .with_batch_config(BatchConfigBuilder::default().with_max_queue_size(4096).build_or_panic())
 ///         .build();
 ///
 ///     // Then use the `with_batch_exporter` method to have the provider export spans in batches.
@@ -221,17 +686,96 @@ impl SpanProcessor for SimpleSpanProcessor {
 /// [`tokio`]: https://tokio.rs
 /// [`async-std`]: https://async.rs
 pub struct BatchSpanProcessor<R: RuntimeChannel> {
-    message_sender: R::Sender<BatchMessage>,
+    worker: Worker<R>,
+    // spans are only stamped with the time they are enqueued at if the queue
+    // latency is recorded
+    track_enqueue_time: bool,
+    // shared with the worker, which counts the spans of failed exports
+    dropped: Arc<DroppedCounters>,
+    budget: Option<TelemetryBudget>,
 }
 
 impl<R: RuntimeChannel> fmt::Debug for BatchSpanProcessor<R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("BatchSpanProcessor")
-            .field("message_sender", &self.message_sender)
+            .field("worker", &self.worker)
+            .field("track_enqueue_time", &self.track_enqueue_time)
+            .field("dropped", &self.dropped)
+            .field("budget", &self.budget)
             .finish()
     }
 }
 
+/// The worker exporting the spans of a [`BatchSpanProcessor`].
+enum Worker<R: RuntimeChannel> {
+    /// A task spawned on the runtime, receiving spans and control messages on
+    /// the same channel.
+    Task(R::Sender<BatchMessage>),
+    /// A thread spawned by the processor, see
+    /// [`BatchSpanProcessorBuilder::with_own_thread`].
+    Thread(OwnThread),
+}
+
+impl<R: RuntimeChannel> fmt::Debug for Worker<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Worker::Task(sender) => f.debug_tuple("Task").field(sender).finish(),
+            Worker::Thread(thread) => f.debug_tuple("Thread").field(thread).finish(),
+        }
+    }
+}
+
+/// Reserves the size of `span` in `budget`, if any.
+///
+/// Returns `Err` if the span doesn't fit in the budget and is dropped.
+fn reserve(
+    budget: Option<&TelemetryBudget>,
+    dropped: &DroppedCounters,
+    span: &SpanData,
+    acked: bool,
+) -> Result<Option<Reservation>, ()> {
+    let Some(budget) = budget else {
+        return Ok(None);
+    };
+    match budget.try_reserve(budget_priority(span, acked), span_size(span)) {
+        Some(reservation) => Ok(Some(reservation)),
+        None => {
+            dropped.memory_budget(1);
+            Err(())
+        }
+    }
+}
+
+/// Spans only linked to unsampled spans are evicted first, unless their export
+/// is awaited.
+fn budget_priority(span: &SpanData, acked: bool) -> BudgetPriority {
+    let links = &span.links.links;
+    if !acked && !links.is_empty() && links.iter().all(|link| !link.span_context.is_sampled()) {
+        BudgetPriority::UnsampledLinkedSpans
+    } else {
+        BudgetPriority::Regular
+    }
+}
+
+/// The estimated size of `span` in memory.
+fn span_size(span: &SpanData) -> usize {
+    let events: usize = span
+        .events
+        .iter()
+        .map(|event| event.name.len() + attributes_size(&event.attributes))
+        .sum();
+    let links: usize = span
+        .links
+        .iter()
+        .map(|link| attributes_size(&link.attributes))
+        .sum();
+    std::mem::size_of::<SpanData>()
+        + span.name.len()
+        + attributes_size(&span.attributes)
+        + events
+        + links
+}
+
 impl<R: RuntimeChannel> SpanProcessor for BatchSpanProcessor<R> {
     fn on_start(&self, _span: &mut Span, _cx: &Context) {
         // Ignored
@@ -242,17 +786,40 @@ impl<R: RuntimeChannel> SpanProcessor for BatchSpanProcessor<R> {
             return;
         }
 
-        let result = self.message_sender.try_send(BatchMessage::ExportSpan(span));
-
-        if let Err(err) = result {
+        let Ok(reservation) = reserve(self.budget.as_ref(), &self.dropped, &span, false) else {
+            return;
+        };
+        if let Err(err) = self.enqueue(span, reservation, None) {
             global::handle_error(TraceError::Other(err.into()));
         }
     }
 
+    fn on_end_with_ack(&self, span: SpanData) -> BoxFuture<'static, TraceResult<()>> {
+        if !span.span_context.is_sampled() {
+            return Box::pin(future::ready(Ok(())));
+        }
+
+        let Ok(reservation) = reserve(self.budget.as_ref(), &self.dropped, &span, true) else {
+            return Box::pin(future::ready(Err(TraceError::from(
+                "span dropped to stay within the memory budget",
+            ))));
+        };
+        let (ack_sender, ack_receiver) = oneshot::channel();
+        if let Err(err) = self.enqueue(span, reservation, Some(ack_sender)) {
+            return Box::pin(future::ready(Err(TraceError::Other(err.into()))));
+        }
+
+        Box::pin(async move {
+            ack_receiver
+                .await
+                .map_err(|err| TraceError::Other(err.into()))
+                .and_then(|identity| identity)
+        })
+    }
+
     fn force_flush(&self) -> TraceResult<()> {
-        let (res_sender, res_receiver) = oneshot::channel();
-        self.message_sender
-            .try_send(BatchMessage::Flush(Some(res_sender)))
+        let res_receiver = self
+            .request_flush(false)
             .map_err(|err| TraceError::Other(err.into()))?;
 
         futures_executor::block_on(res_receiver)
@@ -261,21 +828,44 @@ impl<R: RuntimeChannel> SpanProcessor for BatchSpanProcessor<R> {
     }
 
     fn shutdown(&self) -> TraceResult<()> {
-        let (res_sender, res_receiver) = oneshot::channel();
-        self.message_sender
-            .try_send(BatchMessage::Shutdown(res_sender))
+        let res_receiver = self
+            .request_flush(true)
             .map_err(|err| TraceError::Other(err.into()))?;
 
-        futures_executor::block_on(res_receiver)
+        let result = futures_executor::block_on(res_receiver)
             .map_err(|err| TraceError::Other(err.into()))
-            .and_then(|identity| identity)
+            .and_then(|identity| identity);
+        self.join_worker();
+        result
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> ShutdownResult {
+        let res_receiver = self.request_flush(true)?;
+
+        let result = shutdown::block_on_with_timeout(res_receiver, timeout)?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()))?
+            .map_err(|err| ShutdownError::InternalFailure(err.to_string()));
+        self.join_worker();
+        result
     }
 
     fn set_resource(&mut self, resource: &Resource) {
         let resource = Arc::new(resource.clone());
-        let _ = self
-            .message_sender
-            .try_send(BatchMessage::SetResource(resource));
+        let _ = match &self.worker {
+            Worker::Task(sender) => sender.try_send(BatchMessage::SetResource(resource)),
+            Worker::Thread(thread) => TrySend::try_send(
+                &thread.control_sender,
+                ControlMessage::SetResource(resource),
+            ),
+        };
+    }
+
+    fn dropped_telemetry(&self) -> DroppedTelemetry {
+        self.dropped.snapshot()
+    }
+
+    fn diagnostics(&self) -> ProcessorDiagnostics {
+        self.dropped.diagnostics(true)
     }
 }
 
@@ -286,8 +876,16 @@ impl<R: RuntimeChannel> SpanProcessor for BatchSpanProcessor<R> {
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 enum BatchMessage {
-    /// Export spans, usually called when span ends
-    ExportSpan(SpanData),
+    /// Export spans, usually called when span ends, with the time the span
+    /// was enqueued at if it is tracked, its reservation in the memory budget
+    /// if any and the channel acknowledging the export if the span was ended
+    /// with `on_end_with_ack`
+    ExportSpan(
+        SpanData,
+        Option<SystemTime>,
+        Option<Reservation>,
+        Option<oneshot::Sender<ExportResult>>,
+    ),
     /// Flush the current buffer to the backend, it can be triggered by
     /// pre configured interval or a call to `force_push` function.
     Flush(Option<oneshot::Sender<ExportResult>>),
@@ -299,38 +897,59 @@ enum BatchMessage {
 
 struct BatchSpanProcessorInternal<R> {
     spans: Vec<SpanData>,
+    // enqueue times of `spans`, empty unless tracked
+    enqueued_at: Vec<SystemTime>,
+    // acknowledgements of the spans ended with `on_end_with_ack`
+    acks: Vec<oneshot::Sender<ExportResult>>,
+    // reservations of `spans` in the budget, empty without a budget
+    reservations: Vec<Reservation>,
+    budget: Option<TelemetryBudget>,
+    instrumentation: BatchInstrumentation,
     export_tasks: FuturesUnordered<BoxFuture<'static, ExportResult>>,
     runtime: R,
     exporter: Box<dyn SpanExporter>,
     config: BatchConfig,
+    dropped: Arc<DroppedCounters>,
 }
 
 impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
+    /// Export all buffered spans.
+    ///
+    /// Flushes requested through a result channel, i.e. `force_flush` and
+    /// shutdown, also wait for in-flight exports and then invoke the exporter's
+    /// `force_flush` so that exporters buffering data internally persist it.
     async fn flush(&mut self, res_channel: Option<oneshot::Sender<ExportResult>>) {
         let export_task = self.export();
-        let task = Box::pin(async move {
-            let result = export_task.await;
-
-            if let Some(channel) = res_channel {
-                if let Err(result) = channel.send(result) {
-                    global::handle_error(TraceError::from(format!(
-                        "failed to send flush result: {:?}",
-                        result
-                    )));
-                }
-            } else if let Err(err) = result {
-                global::handle_error(err);
+        let export_tasks = &mut self.export_tasks;
+        let (mut result, _) = future::join(export_task, async {
+            while export_tasks.next().await.is_some() {}
+        })
+        .await;
+
+        if let Some(channel) = res_channel {
+            result = result.and(self.exporter_force_flush().await);
+            if let Err(result) = channel.send(result) {
+                global::handle_error(TraceError::from(format!(
+                    "failed to send flush result: {:?}",
+                    result
+                )));
             }
+        } else if let Err(err) = result {
+            global::handle_error(err);
+        }
+    }
 
-            Ok(())
-        });
+    fn exporter_force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        let flush = self.exporter.force_flush();
+        let timeout = self.runtime.delay(self.config.max_export_timeout);
+        let time_out = self.config.max_export_timeout;
 
-        if self.config.max_concurrent_exports == 1 {
-            let _ = task.await;
-        } else {
-            self.export_tasks.push(task);
-            while self.export_tasks.next().await.is_some() {}
-        }
+        Box::pin(async move {
+            match future::select(flush, timeout).await {
+                Either::Left((flush_res, _)) => flush_res,
+                Either::Right((_, _)) => ExportResult::Err(TraceError::ExportTimedOut(time_out)),
+            }
+        })
     }
 
     /// Process a single message
@@ -339,8 +958,12 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
     async fn process_message(&mut self, message: BatchMessage) -> bool {
         match message {
             // Span has finished, add to buffer of pending spans.
-            BatchMessage::ExportSpan(span) => {
+            BatchMessage::ExportSpan(span, enqueued_at, reservation, ack) => {
                 self.spans.push(span);
+                self.enqueued_at.extend(enqueued_at);
+                self.reservations.extend(reservation);
+                self.acks.extend(ack);
+                self.evict();
 
                 if self.spans.len() == self.config.max_export_batch_size {
                     // If concurrent exports are saturated, wait for one to complete.
@@ -382,11 +1005,10 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
             // abort some or all Export or ForceFlush calls it has made to achieve this
             // goal.
             //
-            // NB: `force_flush` is not currently implemented on exporters; the equivalent
-            // would be waiting for exporter tasks to complete. In the case of
-            // channel-coupled exporters, they will need a `force_flush` implementation to
-            // properly block.
+            // Explicit flushes wait for the export to complete and then invoke the
+            // exporter's `force_flush`, periodic flushes only export.
             BatchMessage::Flush(res_channel) => {
+                self.evict();
                 self.flush(res_channel).await;
             }
             // Stream has terminated or processor is shutdown, return to finish execution.
@@ -403,6 +1025,16 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
         true
     }
 
+    fn evict(&mut self) {
+        evict(
+            self.budget.as_ref(),
+            &mut self.spans,
+            &mut self.enqueued_at,
+            &mut self.reservations,
+            &self.dropped,
+        );
+    }
+
     fn export(&mut self) -> BoxFuture<'static, ExportResult> {
         // Batch size check for flush / shutdown. Those methods may be called
         // when there's no work to do.
@@ -410,15 +1042,32 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
             return Box::pin(future::ready(Ok(())));
         }
 
+        self.instrumentation
+            .record_queue_latency(&self.enqueued_at.split_off(0));
+        let count = self.spans.len() as u64;
+        self.dropped.dequeued(count);
         let export = self.exporter.export(self.spans.split_off(0));
         let timeout = self.runtime.delay(self.config.max_export_timeout);
         let time_out = self.config.max_export_timeout;
+        let acks = self.acks.split_off(0);
+        let reservations = self.reservations.split_off(0);
+        let budget = self.budget.clone();
+        let dropped = Arc::clone(&self.dropped);
 
         Box::pin(async move {
-            match future::select(export, timeout).await {
+            let result = match future::select(export, timeout).await {
                 Either::Left((export_res, _)) => export_res,
                 Either::Right((_, _)) => ExportResult::Err(TraceError::ExportTimedOut(time_out)),
-            }
+            };
+            finish_export(
+                &result,
+                count,
+                acks,
+                reservations,
+                budget.as_ref(),
+                &dropped,
+            );
+            result
         })
     }
 
@@ -445,8 +1094,94 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
     }
 }
 
+/// Evicts the oldest buffered spans only linked to unsampled spans while the
+/// budget requests it to make room for higher priority telemetry.
+fn evict(
+    budget: Option<&TelemetryBudget>,
+    spans: &mut Vec<SpanData>,
+    enqueued_at: &mut Vec<SystemTime>,
+    reservations: &mut Vec<Reservation>,
+    dropped: &DroppedCounters,
+) {
+    let Some(budget) = budget else {
+        return;
+    };
+    let mut evicted = vec![false; spans.len()];
+    for (evicted, reservation) in evicted.iter_mut().zip(reservations.iter()) {
+        if !budget.eviction_requested(BudgetPriority::UnsampledLinkedSpans) {
+            break;
+        }
+        if reservation.priority == BudgetPriority::UnsampledLinkedSpans {
+            budget.evict(*reservation);
+            *evicted = true;
+        }
+    }
+
+    let count = evicted.iter().filter(|evicted| **evicted).count();
+    if count == 0 {
+        return;
+    }
+    let mut keep = evicted.iter().map(|evicted| !evicted);
+    spans.retain(|_| keep.next().unwrap_or(true));
+    if !enqueued_at.is_empty() {
+        let mut keep = evicted.iter().map(|evicted| !evicted);
+        enqueued_at.retain(|_| keep.next().unwrap_or(true));
+    }
+    let mut keep = evicted.iter().map(|evicted| !evicted);
+    reservations.retain(|_| keep.next().unwrap_or(true));
+    dropped.memory_budget(count as u64);
+    dropped.dequeued(count as u64);
+}
+
+/// Counts the spans of a failed export, releases the reservations of the
+/// exported batch and resolves its acknowledgements with the export result.
+fn finish_export(
+    result: &ExportResult,
+    count: u64,
+    acks: Vec<oneshot::Sender<ExportResult>>,
+    reservations: Vec<Reservation>,
+    budget: Option<&TelemetryBudget>,
+    dropped: &DroppedCounters,
+) {
+    if let Err(err) = result {
+        dropped.export_failed(count, err);
+    }
+    if let Some(budget) = budget {
+        reservations
+            .into_iter()
+            .for_each(|reservation| budget.release(reservation));
+    }
+
+    for ack in acks {
+        // the receiver may have stopped waiting for the acknowledgement
+        let _ = ack.send(
+            result
+                .as_ref()
+                .map(|_| ())
+                .map_err(|err| TraceError::Other(err.to_string().into())),
+        );
+    }
+}
+
 impl<R: RuntimeChannel> BatchSpanProcessor<R> {
+    #[cfg(test)]
     pub(crate) fn new(exporter: Box<dyn SpanExporter>, config: BatchConfig, runtime: R) -> Self {
+        Self::with_instrumentation(
+            exporter,
+            config,
+            runtime,
+            BatchInstrumentation::default(),
+            None,
+        )
+    }
+
+    fn with_instrumentation(
+        exporter: Box<dyn SpanExporter>,
+        config: BatchConfig,
+        runtime: R,
+        instrumentation: BatchInstrumentation,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
         let (message_sender, message_receiver) =
             runtime.batch_message_channel(config.max_queue_size);
         let ticker = runtime
@@ -455,19 +1190,56 @@ impl<R: RuntimeChannel> BatchSpanProcessor<R> {
         let timeout_runtime = runtime.clone();
 
         let messages = Box::pin(stream::select(message_receiver, ticker));
+        let track_enqueue_time = instrumentation.is_enabled();
+        let dropped = Arc::new(DroppedCounters::default());
         let processor = BatchSpanProcessorInternal {
             spans: Vec::new(),
+            enqueued_at: Vec::new(),
+            acks: Vec::new(),
+            reservations: Vec::new(),
+            budget: budget.clone(),
+            instrumentation,
             export_tasks: FuturesUnordered::new(),
             runtime: timeout_runtime,
             config,
             exporter,
+            dropped: Arc::clone(&dropped),
         };
 
         // Spawn worker process via user-defined spawn function.
         runtime.spawn(Box::pin(processor.run(messages)));
 
         // Return batch processor with link to worker
-        BatchSpanProcessor { message_sender }
+        BatchSpanProcessor {
+            worker: Worker::Task(message_sender),
+            track_enqueue_time,
+            dropped,
+            budget,
+        }
+    }
+
+    fn with_own_thread(
+        exporter: Box<dyn SpanExporter>,
+        config: BatchConfig,
+        instrumentation: BatchInstrumentation,
+        budget: Option<TelemetryBudget>,
+    ) -> Self {
+        let track_enqueue_time = instrumentation.is_enabled();
+        let dropped = Arc::new(DroppedCounters::default());
+        let thread = OwnThread::spawn(
+            exporter,
+            config,
+            instrumentation,
+            budget.clone(),
+            Arc::clone(&dropped),
+        );
+
+        BatchSpanProcessor {
+            worker: Worker::Thread(thread),
+            track_enqueue_time,
+            dropped,
+            budget,
+        }
     }
 
     /// Create a new batch processor builder
@@ -479,6 +1251,117 @@ impl<R: RuntimeChannel> BatchSpanProcessor<R> {
             exporter,
             config: Default::default(),
             runtime,
+            own_thread: false,
+            instrumentation: Default::default(),
+            budget: None,
+        }
+    }
+
+    /// Queues `span` for export, counting it as dropped if the queue is full.
+    fn enqueue(
+        &self,
+        span: SpanData,
+        reservation: Option<Reservation>,
+        ack: Option<oneshot::Sender<ExportResult>>,
+    ) -> Result<(), TrySendError> {
+        let enqueued_at = self.track_enqueue_time.then(opentelemetry::time::now);
+        self.dropped.enqueued(1);
+        let result = match &self.worker {
+            Worker::Task(sender) => sender.try_send(BatchMessage::ExportSpan(
+                span,
+                enqueued_at,
+                reservation,
+                ack,
+            )),
+            Worker::Thread(thread) => thread.enqueue((span, enqueued_at, reservation, ack)),
+        };
+
+        if let Err(err) = result {
+            self.dropped.dequeued(1);
+            if matches!(err, TrySendError::ChannelFull) {
+                self.dropped.queue_full(1);
+            }
+            if let (Some(budget), Some(reservation)) = (&self.budget, reservation) {
+                budget.release(reservation);
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Asks the worker to flush, or to shut down if `shutdown` is set, without
+    /// waiting for the result.
+    fn request_flush(
+        &self,
+        shutdown: bool,
+    ) -> Result<oneshot::Receiver<ExportResult>, TrySendError> {
+        let (res_sender, res_receiver) = oneshot::channel();
+        match &self.worker {
+            Worker::Task(sender) if shutdown => {
+                sender.try_send(BatchMessage::Shutdown(res_sender))?
+            }
+            Worker::Task(sender) => sender.try_send(BatchMessage::Flush(Some(res_sender)))?,
+            Worker::Thread(thread) if shutdown => {
+                TrySend::try_send(&thread.control_sender, ControlMessage::Shutdown(res_sender))?
+            }
+            Worker::Thread(thread) => {
+                TrySend::try_send(&thread.control_sender, ControlMessage::Flush(res_sender))?
+            }
+        }
+        Ok(res_receiver)
+    }
+
+    /// Waits for the worker thread, if any, to exit once it was shut down.
+    fn join_worker(&self) {
+        if let Worker::Thread(thread) = &self.worker {
+            thread.join();
+        }
+    }
+}
+
+/// Metrics the batch span processor records about itself.
+#[derive(Debug, Default)]
+struct BatchInstrumentation {
+    #[cfg(feature = "metrics")]
+    queue_latency: Option<opentelemetry::metrics::Histogram<f64>>,
+}
+
+impl BatchInstrumentation {
+    #[cfg(feature = "metrics")]
+    fn new<P: opentelemetry::metrics::MeterProvider>(meter_provider: &P) -> Self {
+        let meter = meter_provider.versioned_meter(
+            "opentelemetry_sdk",
+            Some(env!("CARGO_PKG_VERSION")),
+            None::<&'static str>,
+            None,
+        );
+        let queue_latency = meter
+            .f64_histogram("otel.sdk.processor.span.queue_latency")
+            .with_unit("s")
+            .with_description(
+                "Time spans spent in the batch span processor between ending and being handed to the exporter",
+            )
+            .init();
+        BatchInstrumentation {
+            queue_latency: Some(queue_latency),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        #[cfg(feature = "metrics")]
+        return self.queue_latency.is_some();
+        #[cfg(not(feature = "metrics"))]
+        false
+    }
+
+    fn record_queue_latency(&self, _enqueued_at: &[SystemTime]) {
+        #[cfg(feature = "metrics")]
+        if let Some(queue_latency) = &self.queue_latency {
+            let now = opentelemetry::time::now();
+            for enqueued_at in _enqueued_at {
+                let latency = now.duration_since(*enqueued_at).unwrap_or_default();
+                queue_latency.record(latency.as_secs_f64(), &[]);
+            }
         }
     }
 }
@@ -514,7 +1397,7 @@ pub struct BatchConfig {
 
 impl Default for BatchConfig {
     fn default() -> Self {
-        BatchConfigBuilder::default().build()
+        BatchConfigBuilder::default().build_clamped()
     }
 }
 
@@ -596,90 +1479,481 @@ impl BatchConfigBuilder {
         self
     }
 
-    /// Builds a `BatchConfig` enforcing the following invariants:
+    /// Builds a `BatchConfig`, returning an error if any of the following
+    /// invariants does not hold:
+    /// * `max_queue_size`, `max_export_batch_size` and `max_concurrent_exports`
+    ///   must be greater than zero.
+    /// * `scheduled_delay` must be greater than zero.
     /// * `max_export_batch_size` must be less than or equal to `max_queue_size`.
-    pub fn build(self) -> BatchConfig {
+    pub fn build(self) -> Result<BatchConfig, BuildError> {
+        if self.max_queue_size == 0 {
+            return Err(BuildError::ZeroValue("max_queue_size"));
+        }
+        if self.max_export_batch_size == 0 {
+            return Err(BuildError::ZeroValue("max_export_batch_size"));
+        }
+        if self.max_concurrent_exports == 0 {
+            return Err(BuildError::ZeroValue("max_concurrent_exports"));
+        }
+        if self.scheduled_delay.is_zero() {
+            return Err(BuildError::ZeroValue("scheduled_delay"));
+        }
+        if self.max_export_batch_size > self.max_queue_size {
+            return Err(BuildError::ExportBatchSizeExceedsQueueSize {
+                max_export_batch_size: self.max_export_batch_size,
+                max_queue_size: self.max_queue_size,
+            });
+        }
+
+        Ok(self.build_clamped())
+    }
+
+    /// Builds a `BatchConfig`, panicking if the configuration is invalid.
+    ///
+    /// See [`BatchConfigBuilder::build`] for the invariants that are checked.
+    pub fn build_or_panic(self) -> BatchConfig {
+        match self.build() {
+            Ok(config) => config,
+            Err(err) => panic!("invalid batch span processor configuration: {err}"),
+        }
+    }
+
+    /// Builds a `BatchConfig` from environment-provided values, which the
+    /// specification requires to be tolerated rather than rejected.
+    fn build_clamped(self) -> BatchConfig {
         // max export batch size must be less or equal to max queue size.
         // we set max export batch size to max queue size if it's larger than max queue size.
         let max_export_batch_size = min(self.max_export_batch_size, self.max_queue_size);
 
-        BatchConfig {
-            max_queue_size: self.max_queue_size,
-            scheduled_delay: self.scheduled_delay,
-            max_export_timeout: self.max_export_timeout,
-            max_concurrent_exports: self.max_concurrent_exports,
+        BatchConfig {
+            max_queue_size: self.max_queue_size,
+            scheduled_delay: self.scheduled_delay,
+            max_export_timeout: self.max_export_timeout,
+            max_concurrent_exports: self.max_concurrent_exports,
+            max_export_batch_size,
+        }
+    }
+
+    fn init_from_env_vars(mut self) -> Self {
+        if let Some(max_concurrent_exports) =
+            positive_from_env(OTEL_BSP_MAX_CONCURRENT_EXPORTS, TraceError::from)
+        {
+            self.max_concurrent_exports = max_concurrent_exports;
+        }
+
+        if let Some(max_queue_size) = positive_from_env(OTEL_BSP_MAX_QUEUE_SIZE, TraceError::from) {
+            self.max_queue_size = max_queue_size;
+        }
+
+        if let Some(scheduled_delay) = positive_from_env(OTEL_BSP_SCHEDULE_DELAY, TraceError::from)
+        {
+            self.scheduled_delay = Duration::from_millis(scheduled_delay);
+        }
+
+        if let Some(max_export_batch_size) =
+            positive_from_env(OTEL_BSP_MAX_EXPORT_BATCH_SIZE, TraceError::from)
+        {
+            self.max_export_batch_size = max_export_batch_size;
+        }
+
+        // max export batch size must be less or equal to max queue size.
+        // we set max export batch size to max queue size if it's larger than max queue size.
+        if self.max_export_batch_size > self.max_queue_size {
+            self.max_export_batch_size = self.max_queue_size;
+        }
+
+        if let Some(max_export_timeout) =
+            positive_from_env(OTEL_BSP_EXPORT_TIMEOUT, TraceError::from)
+        {
+            self.max_export_timeout = Duration::from_millis(max_export_timeout);
+        }
+
+        self
+    }
+}
+
+/// A builder for creating [`BatchSpanProcessor`] instances.
+///
+#[derive(Debug)]
+pub struct BatchSpanProcessorBuilder<E, R> {
+    exporter: E,
+    config: BatchConfig,
+    runtime: R,
+    own_thread: bool,
+    instrumentation: BatchInstrumentation,
+    budget: Option<TelemetryBudget>,
+}
+
+impl<E, R> BatchSpanProcessorBuilder<E, R>
+where
+    E: SpanExporter + 'static,
+    R: RuntimeChannel,
+{
+    /// Set the BatchConfig for [BatchSpanProcessorBuilder]
+    pub fn with_batch_config(self, config: BatchConfig) -> Self {
+        BatchSpanProcessorBuilder { config, ..self }
+    }
+
+    /// Record metrics about the processor itself with meters of `meter_provider`.
+    ///
+    /// The `otel.sdk.processor.span.queue_latency` histogram records, in
+    /// seconds, how long each span waited in the processor between ending and
+    /// being handed to the exporter. Telemetry arriving late in the backend
+    /// despite a low queue latency points at a slow exporter or collector
+    /// rather than at batching.
+    #[cfg(feature = "metrics")]
+    pub fn with_meter_provider<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        BatchSpanProcessorBuilder {
+            instrumentation: BatchInstrumentation::new(meter_provider),
+            ..self
+        }
+    }
+
+    /// Bound the memory of the queued spans by `budget`, shared with other
+    /// processors and providers.
+    ///
+    /// Spans which don't fit in the budget are dropped. Queued spans whose
+    /// links all point to unsampled spans are evicted to make room for higher
+    /// priority telemetry.
+    pub fn with_budget(self, budget: TelemetryBudget) -> Self {
+        BatchSpanProcessorBuilder {
+            budget: Some(budget),
+            ..self
+        }
+    }
+
+    /// Export from a worker thread spawned by the processor instead of a task
+    /// spawned on the runtime.
+    ///
+    /// The worker is a plain OS thread waiting on a bounded queue and waking
+    /// up every [`BatchConfig`] scheduled delay. The runtime passed to the
+    /// builder is not used, so the processor can be used in applications
+    /// without a running async runtime, or with a Tokio current thread runtime
+    /// which the batch processor could deadlock.
+    ///
+    /// Exports are driven on the worker thread one at a time, the configured
+    /// maximum of concurrent exports is ignored. The exporter must not require
+    /// an async runtime, e.g. it should use a blocking HTTP client. Flush,
+    /// shutdown and resource requests are sent to the worker on their own
+    /// channel, so they are not rejected while the queue of spans is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "testing", feature = "rt-tokio"))]
+    /// # {
+    /// use opentelemetry_sdk::{
+    ///     runtime,
+    ///     testing::trace::NoopSpanExporter,
+    ///     trace::{BatchSpanProcessor, TracerProvider},
+    /// };
+    ///
+    /// let batch = BatchSpanProcessor::builder(NoopSpanExporter::new(), runtime::Tokio)
+    ///     .with_own_thread()
+    ///     .build();
+    /// let provider = TracerProvider::builder()
+    ///     .with_span_processor(batch)
+    ///     .build();
+    /// # drop(provider);
+    /// # }
+    /// ```
+    pub fn with_own_thread(self) -> Self {
+        BatchSpanProcessorBuilder {
+            own_thread: true,
+            ..self
+        }
+    }
+
+    /// Build a batch processor
+    pub fn build(self) -> BatchSpanProcessor<R> {
+        if self.own_thread {
+            return BatchSpanProcessor::with_own_thread(
+                Box::new(self.exporter),
+                self.config,
+                self.instrumentation,
+                self.budget,
+            );
+        }
+        BatchSpanProcessor::with_instrumentation(
+            Box::new(self.exporter),
+            self.config,
+            self.runtime,
+            self.instrumentation,
+            self.budget,
+        )
+    }
+}
+
+/// The worker thread of a [`BatchSpanProcessor`] built
+/// [`with_own_thread`](BatchSpanProcessorBuilder::with_own_thread).
+struct OwnThread {
+    spans_sender: SyncSender<QueuedSpan>,
+    control_sender: SyncSender<ControlMessage>,
+    // the number of spans in the spans channel, and whether the worker was
+    // asked to export the full batches among them
+    pending: Arc<AtomicUsize>,
+    export_requested: Arc<AtomicBool>,
+    max_export_batch_size: usize,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl fmt::Debug for OwnThread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnThread")
+            .field("spans_sender", &self.spans_sender)
+            .field("control_sender", &self.control_sender)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl OwnThread {
+    fn spawn(
+        exporter: Box<dyn SpanExporter>,
+        config: BatchConfig,
+        instrumentation: BatchInstrumentation,
+        budget: Option<TelemetryBudget>,
+        dropped: Arc<DroppedCounters>,
+    ) -> Self {
+        let (spans_sender, spans_receiver) = mpsc::sync_channel(config.max_queue_size);
+        let (control_sender, control_receiver) = mpsc::sync_channel(CONTROL_QUEUE_SIZE);
+        let pending = Arc::new(AtomicUsize::new(0));
+        let export_requested = Arc::new(AtomicBool::new(false));
+        let max_export_batch_size = config.max_export_batch_size;
+
+        let mut worker = OwnThreadWorker {
+            exporter,
+            spans_receiver,
+            pending: Arc::clone(&pending),
+            config,
+            instrumentation,
+            budget,
+            dropped,
+        };
+        let worker_export_requested = Arc::clone(&export_requested);
+        let handle = thread::Builder::new()
+            .name("OpenTelemetry.Traces.BatchProcessor".to_owned())
+            .spawn(move || {
+                let mut next_export = Instant::now() + worker.config.scheduled_delay;
+                loop {
+                    let message =
+                        match control_receiver.recv_timeout(shutdown::remaining(next_export)) {
+                            Ok(message) => message,
+                            Err(RecvTimeoutError::Timeout) => {
+                                if let Err(err) = worker.export_queued() {
+                                    global::handle_error(err);
+                                }
+                                next_export = Instant::now() + worker.config.scheduled_delay;
+                                continue;
+                            }
+                            // the processor was dropped without being shut down
+                            Err(RecvTimeoutError::Disconnected) => {
+                                let (sender, _) = oneshot::channel();
+                                ControlMessage::Shutdown(sender)
+                            }
+                        };
+
+                    match message {
+                        ControlMessage::ExportSpans => {
+                            // cleared first, so spans ending while exporting
+                            // request another export
+                            worker_export_requested.store(false, Ordering::Relaxed);
+                            if let Err(err) = worker.export_full_batches() {
+                                global::handle_error(err);
+                            }
+                        }
+                        ControlMessage::Flush(sender) => {
+                            let result = worker.export_queued().and(worker.exporter_force_flush());
+                            next_export = Instant::now() + worker.config.scheduled_delay;
+                            if let Err(result) = sender.send(result) {
+                                global::handle_error(TraceError::from(format!(
+                                    "failed to send flush result: {:?}",
+                                    result
+                                )));
+                            }
+                        }
+                        ControlMessage::Shutdown(sender) => {
+                            let result = worker.export_queued().and(worker.exporter_force_flush());
+                            worker.exporter.shutdown();
+
+                            // the receiver is gone if the processor was dropped
+                            let _ = sender.send(result);
+                            break;
+                        }
+                        ControlMessage::SetResource(resource) => {
+                            worker.exporter.set_resource(&resource);
+                        }
+                    }
+                }
+            });
+
+        // without a worker, the channels are closed and the spans are dropped
+        let handle = match handle {
+            Ok(handle) => Some(handle),
+            Err(err) => {
+                global::handle_error(TraceError::Other(err.into()));
+                None
+            }
+        };
+
+        OwnThread {
+            spans_sender,
+            control_sender,
+            pending,
+            export_requested,
             max_export_batch_size,
+            handle: Mutex::new(handle),
         }
     }
 
-    fn init_from_env_vars(mut self) -> Self {
-        if let Some(max_concurrent_exports) = env::var(OTEL_BSP_MAX_CONCURRENT_EXPORTS)
-            .ok()
-            .and_then(|max_concurrent_exports| usize::from_str(&max_concurrent_exports).ok())
-        {
-            self.max_concurrent_exports = max_concurrent_exports;
-        }
-
-        if let Some(max_queue_size) = env::var(OTEL_BSP_MAX_QUEUE_SIZE)
-            .ok()
-            .and_then(|queue_size| usize::from_str(&queue_size).ok())
-        {
-            self.max_queue_size = max_queue_size;
-        }
-
-        if let Some(scheduled_delay) = env::var(OTEL_BSP_SCHEDULE_DELAY)
-            .ok()
-            .and_then(|delay| u64::from_str(&delay).ok())
-        {
-            self.scheduled_delay = Duration::from_millis(scheduled_delay);
+    /// Queues a span for export, asking the worker to export once a full
+    /// batch is queued.
+    fn enqueue(&self, span: QueuedSpan) -> Result<(), TrySendError> {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        if let Err(err) = TrySend::try_send(&self.spans_sender, span) {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            return Err(err);
         }
 
-        if let Some(max_export_batch_size) = env::var(OTEL_BSP_MAX_EXPORT_BATCH_SIZE)
-            .ok()
-            .and_then(|batch_size| usize::from_str(&batch_size).ok())
+        if self.pending.load(Ordering::Relaxed) >= self.max_export_batch_size
+            && !self.export_requested.swap(true, Ordering::Relaxed)
         {
-            self.max_export_batch_size = max_export_batch_size;
-        }
-
-        // max export batch size must be less or equal to max queue size.
-        // we set max export batch size to max queue size if it's larger than max queue size.
-        if self.max_export_batch_size > self.max_queue_size {
-            self.max_export_batch_size = self.max_queue_size;
+            // a full control channel already wakes the worker
+            let _ = TrySend::try_send(&self.control_sender, ControlMessage::ExportSpans);
         }
+        Ok(())
+    }
 
-        if let Some(max_export_timeout) = env::var(OTEL_BSP_EXPORT_TIMEOUT)
-            .ok()
-            .and_then(|timeout| u64::from_str(&timeout).ok())
-        {
-            self.max_export_timeout = Duration::from_millis(max_export_timeout);
+    /// Waits for the worker thread to exit once it was shut down.
+    fn join(&self) {
+        let handle = self.handle.lock().ok().and_then(|mut handle| handle.take());
+        if let Some(handle) = handle {
+            let _ = handle.join();
         }
-
-        self
     }
 }
 
-/// A builder for creating [`BatchSpanProcessor`] instances.
+/// The capacity of the control channel of an [`OwnThread`] worker, it only
+/// holds flush, shutdown and resource requests and at most one export
+/// request.
+const CONTROL_QUEUE_SIZE: usize = 64;
+
+/// A span queued for an [`OwnThread`] worker, with the time it was enqueued
+/// at if it is tracked, its reservation in the memory budget if any and the
+/// channel acknowledging the export if the span was ended with
+/// `on_end_with_ack`.
+type QueuedSpan = (
+    SpanData,
+    Option<SystemTime>,
+    Option<Reservation>,
+    Option<oneshot::Sender<ExportResult>>,
+);
+
+/// Messages controlling an [`OwnThread`] worker.
 ///
+/// They are sent on their own channel, so a queue full of spans does not keep
+/// the processor from being flushed or shut down.
 #[derive(Debug)]
-pub struct BatchSpanProcessorBuilder<E, R> {
-    exporter: E,
+enum ControlMessage {
+    /// Export the full batches of queued spans.
+    ExportSpans,
+    /// Export all queued spans and flush the exporter.
+    Flush(oneshot::Sender<ExportResult>),
+    /// Export all queued spans, flush and shut down the exporter.
+    Shutdown(oneshot::Sender<ExportResult>),
+    /// Set the resource for the exporter.
+    SetResource(Arc<Resource>),
+}
+
+/// The state of an [`OwnThread`] worker.
+struct OwnThreadWorker {
+    exporter: Box<dyn SpanExporter>,
+    spans_receiver: mpsc::Receiver<QueuedSpan>,
+    pending: Arc<AtomicUsize>,
     config: BatchConfig,
-    runtime: R,
+    instrumentation: BatchInstrumentation,
+    budget: Option<TelemetryBudget>,
+    dropped: Arc<DroppedCounters>,
 }
 
-impl<E, R> BatchSpanProcessorBuilder<E, R>
-where
-    E: SpanExporter + 'static,
-    R: RuntimeChannel,
-{
-    /// Set the BatchConfig for [BatchSpanProcessorBuilder]
-    pub fn with_batch_config(self, config: BatchConfig) -> Self {
-        BatchSpanProcessorBuilder { config, ..self }
+impl OwnThreadWorker {
+    /// Exports the queued spans in full batches, leaving the rest queued until
+    /// the next scheduled export.
+    fn export_full_batches(&mut self) -> ExportResult {
+        let mut result = Ok(());
+        while self.pending.load(Ordering::Relaxed) >= self.config.max_export_batch_size {
+            match self.export_batch() {
+                Some(batch_result) => result = result.and(batch_result),
+                None => break,
+            }
+        }
+        result
     }
 
-    /// Build a batch processor
-    pub fn build(self) -> BatchSpanProcessor<R> {
-        BatchSpanProcessor::new(Box::new(self.exporter), self.config, self.runtime)
+    /// Exports all queued spans, returning the first error.
+    fn export_queued(&mut self) -> ExportResult {
+        let mut result = Ok(());
+        while let Some(batch_result) = self.export_batch() {
+            result = result.and(batch_result);
+        }
+        result
+    }
+
+    /// Exports the next batch of queued spans on the calling thread, waiting
+    /// at most the export timeout, `None` if no spans are queued.
+    fn export_batch(&mut self) -> Option<ExportResult> {
+        let mut spans = Vec::new();
+        let mut enqueued_at = Vec::new();
+        let mut reservations = Vec::new();
+        let mut acks = Vec::new();
+        while spans.len() < self.config.max_export_batch_size {
+            let Ok((span, span_enqueued_at, reservation, ack)) = self.spans_receiver.try_recv()
+            else {
+                break;
+            };
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            spans.push(span);
+            enqueued_at.extend(span_enqueued_at);
+            reservations.extend(reservation);
+            acks.extend(ack);
+        }
+        if spans.is_empty() {
+            return None;
+        }
+
+        evict(
+            self.budget.as_ref(),
+            &mut spans,
+            &mut enqueued_at,
+            &mut reservations,
+            &self.dropped,
+        );
+        self.instrumentation.record_queue_latency(&enqueued_at);
+        let count = spans.len() as u64;
+        self.dropped.dequeued(count);
+        let time_out = self.config.max_export_timeout;
+        let result = shutdown::block_on_with_timeout(self.exporter.export(spans), time_out)
+            .unwrap_or(Err(TraceError::ExportTimedOut(time_out)));
+        finish_export(
+            &result,
+            count,
+            acks,
+            reservations,
+            self.budget.as_ref(),
+            &self.dropped,
+        );
+        Some(result)
+    }
+
+    /// Invokes the exporter's `force_flush` on the calling thread, waiting at
+    /// most the export timeout.
+    fn exporter_force_flush(&mut self) -> ExportResult {
+        let time_out = self.config.max_export_timeout;
+        shutdown::block_on_with_timeout(self.exporter.force_flush(), time_out)
+            .unwrap_or(Err(TraceError::ExportTimedOut(time_out)))
     }
 }
 
@@ -687,7 +1961,8 @@ where
 mod tests {
     // cargo test trace::span_processor::tests:: --features=testing
     use super::{
-        BatchSpanProcessor, SimpleSpanProcessor, SpanProcessor, OTEL_BSP_EXPORT_TIMEOUT,
+        BatchSpanProcessor, CompositeSpanProcessor, FinishedSpanBatchOnDemandProcessor,
+        SimpleSpanProcessor, SpanProcessor, OTEL_BSP_EXPORT_TIMEOUT,
         OTEL_BSP_MAX_EXPORT_BATCH_SIZE, OTEL_BSP_MAX_QUEUE_SIZE, OTEL_BSP_MAX_QUEUE_SIZE_DEFAULT,
         OTEL_BSP_SCHEDULE_DELAY, OTEL_BSP_SCHEDULE_DELAY_DEFAULT,
     };
@@ -701,10 +1976,12 @@ mod tests {
         OTEL_BSP_MAX_CONCURRENT_EXPORTS_DEFAULT, OTEL_BSP_MAX_EXPORT_BATCH_SIZE_DEFAULT,
     };
     use crate::trace::{BatchConfig, BatchConfigBuilder, SpanEvents, SpanLinks};
+    use crate::{BuildError, ShutdownError};
     use async_trait::async_trait;
     use opentelemetry::trace::{SpanContext, SpanId, SpanKind, Status};
     use std::fmt::Debug;
     use std::future::Future;
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     #[test]
@@ -734,6 +2011,7 @@ mod tests {
             links: SpanLinks::default(),
             status: Status::Unset,
             instrumentation_lib: Default::default(),
+            resource: None,
         };
         processor.on_end(unsampled);
         assert!(exporter.get_finished_spans().unwrap().is_empty());
@@ -751,6 +2029,33 @@ mod tests {
         assert!(exporter.get_finished_spans().unwrap().is_empty());
     }
 
+    #[test]
+    fn on_demand_processor_exports_on_flush() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let processor = FinishedSpanBatchOnDemandProcessor::builder(exporter.clone())
+            .with_max_queue_size(2)
+            .build();
+        let span_data = new_test_export_span_data();
+        for _ in 0..3 {
+            processor.on_end(span_data.clone());
+        }
+        assert!(exporter.get_finished_spans().unwrap().is_empty());
+        assert_eq!(processor.dropped_telemetry().queue_full, 1);
+
+        futures_executor::block_on(processor.flush_async()).unwrap();
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 2);
+
+        // force flushing the provider flushes the processor
+        processor.on_end(span_data.clone());
+        processor.force_flush().unwrap();
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 3);
+
+        // spans ending after shutdown are ignored
+        processor.shutdown().unwrap();
+        processor.on_end(span_data);
+        assert!(processor.inner.spans.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn test_default_const_values() {
         assert_eq!(OTEL_BSP_MAX_QUEUE_SIZE, "OTEL_BSP_MAX_QUEUE_SIZE");
@@ -814,6 +2119,40 @@ mod tests {
         assert_eq!(config.max_export_batch_size, 1024);
     }
 
+    #[test]
+    fn test_batch_config_ignores_zero_env_vars() {
+        let env_vars = vec![
+            (OTEL_BSP_SCHEDULE_DELAY, Some("0")),
+            (OTEL_BSP_EXPORT_TIMEOUT, Some("0")),
+            (OTEL_BSP_MAX_QUEUE_SIZE, Some("0")),
+            (OTEL_BSP_MAX_EXPORT_BATCH_SIZE, Some("0")),
+            (OTEL_BSP_MAX_CONCURRENT_EXPORTS, Some("0")),
+        ];
+
+        let config = temp_env::with_vars(env_vars.clone(), BatchConfig::default);
+        assert_eq!(
+            config.scheduled_delay,
+            Duration::from_millis(OTEL_BSP_SCHEDULE_DELAY_DEFAULT)
+        );
+        assert_eq!(
+            config.max_export_timeout,
+            Duration::from_millis(OTEL_BSP_EXPORT_TIMEOUT_DEFAULT)
+        );
+        assert_eq!(config.max_queue_size, OTEL_BSP_MAX_QUEUE_SIZE_DEFAULT);
+        assert_eq!(
+            config.max_export_batch_size,
+            OTEL_BSP_MAX_EXPORT_BATCH_SIZE_DEFAULT
+        );
+        assert_eq!(
+            config.max_concurrent_exports,
+            OTEL_BSP_MAX_CONCURRENT_EXPORTS_DEFAULT
+        );
+
+        // the validating build doesn't see the ignored values either
+        let built = temp_env::with_vars(env_vars, || BatchConfigBuilder::default().build());
+        assert!(built.is_ok());
+    }
+
     #[test]
     fn test_batch_config_max_export_batch_size_validation() {
         let env_vars = vec![
@@ -843,7 +2182,8 @@ mod tests {
             .with_max_export_timeout(Duration::from_millis(10))
             .with_max_concurrent_exports(10)
             .with_max_queue_size(10)
-            .build();
+            .build()
+            .unwrap();
         assert_eq!(batch.max_export_batch_size, 10);
         assert_eq!(batch.scheduled_delay, Duration::from_millis(10));
         assert_eq!(batch.max_export_timeout, Duration::from_millis(10));
@@ -851,6 +2191,28 @@ mod tests {
         assert_eq!(batch.max_queue_size, 10);
     }
 
+    #[test]
+    fn test_batch_config_builder_rejects_invalid_config() {
+        let err = BatchConfigBuilder::default()
+            .with_max_queue_size(256)
+            .with_max_export_batch_size(1024)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BuildError::ExportBatchSizeExceedsQueueSize {
+                max_export_batch_size: 1024,
+                max_queue_size: 256,
+            }
+        );
+
+        let err = BatchConfigBuilder::default()
+            .with_scheduled_delay(Duration::ZERO)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, BuildError::ZeroValue("scheduled_delay"));
+    }
+
     #[test]
     fn test_build_batch_span_processor_builder() {
         let mut env_vars = vec![
@@ -922,6 +2284,268 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_batch_span_processor_on_end_with_ack() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let processor = BatchSpanProcessor::builder(exporter.clone(), runtime::TokioCurrentThread)
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_secs(60 * 60 * 24))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+
+        let ack = processor.on_end_with_ack(new_test_export_span_data());
+        assert!(processor.force_flush().is_ok());
+        assert!(tokio::time::timeout(Duration::from_secs(5), ack)
+            .await
+            .expect("acknowledgement should resolve after the export")
+            .is_ok());
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 1);
+
+        let _ = processor.shutdown();
+        assert!(processor
+            .on_end_with_ack(new_test_export_span_data())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_batch_span_processor_records_queue_latency() {
+        use crate::metrics::{data, PeriodicReader, SdkMeterProvider};
+        use crate::testing::metrics::InMemoryMetricsExporter;
+
+        let metrics_exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(metrics_exporter.clone(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let processor = BatchSpanProcessor::builder(exporter.clone(), runtime::TokioCurrentThread)
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_secs(60 * 60 * 24))
+                    .build()
+                    .unwrap(),
+            )
+            .with_meter_provider(&meter_provider)
+            .build();
+        // let the worker handle the immediate first tick of its ticker, which
+        // would otherwise export the spans as soon as they are enqueued
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        processor.on_end(new_test_export_span_data());
+        processor.on_end(new_test_export_span_data());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(processor.force_flush().is_ok());
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 2);
+
+        meter_provider.force_flush().unwrap();
+        let resource_metrics = metrics_exporter.get_finished_metrics().unwrap();
+        let metric = &resource_metrics[0].scope_metrics[0].metrics[0];
+        assert_eq!(metric.name, "otel.sdk.processor.span.queue_latency");
+        assert_eq!(metric.unit, "s");
+        let histogram = metric
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .expect("histogram aggregation expected");
+        let data_point = &histogram.data_points[0];
+        assert_eq!(data_point.count, 2);
+        assert!(data_point.min.unwrap() >= 0.02);
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct FlushRecordingExporter {
+        calls: std::sync::Arc<std::sync::Mutex<Vec<&'static str>>>,
+    }
+
+    impl FlushRecordingExporter {
+        fn calls(&self) -> Vec<&'static str> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl SpanExporter for FlushRecordingExporter {
+        fn export(
+            &mut self,
+            _batch: Vec<SpanData>,
+        ) -> futures_util::future::BoxFuture<'static, ExportResult> {
+            self.calls.lock().unwrap().push("export");
+            Box::pin(async { Ok(()) })
+        }
+
+        fn force_flush(&mut self) -> futures_util::future::BoxFuture<'static, ExportResult> {
+            self.calls.lock().unwrap().push("force_flush");
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[test]
+    fn simple_span_processor_force_flush_flushes_exporter() {
+        let exporter = FlushRecordingExporter::default();
+        let processor = SimpleSpanProcessor::new(Box::new(exporter.clone()));
+        processor.on_end(new_test_export_span_data());
+
+        assert!(processor.force_flush().is_ok());
+        assert_eq!(exporter.calls(), vec!["export", "force_flush"]);
+    }
+
+    #[test]
+    fn batch_span_processor_force_flush_flushes_exporter_after_export() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let exporter = FlushRecordingExporter::default();
+        let config = BatchConfig {
+            scheduled_delay: Duration::from_secs(60 * 60 * 24), // set the tick to 24 hours so we know the span must be exported via force_flush
+            ..Default::default()
+        };
+        let processor = rt.block_on(async {
+            BatchSpanProcessor::new(Box::new(exporter.clone()), config, runtime::Tokio)
+        });
+
+        processor.on_end(new_test_export_span_data());
+        assert!(processor.force_flush().is_ok());
+        assert_eq!(exporter.calls(), vec!["export", "force_flush"]);
+
+        // nothing left to export, but the exporter is still flushed
+        assert!(processor.force_flush().is_ok());
+        assert_eq!(
+            exporter.calls(),
+            vec!["export", "force_flush", "force_flush"]
+        );
+        let _ = processor.shutdown();
+    }
+
+    #[test]
+    fn own_thread_processor_exports_on_flush() {
+        let exporter = FlushRecordingExporter::default();
+        let processor = BatchSpanProcessor::builder(exporter.clone(), runtime::Tokio)
+            .with_own_thread()
+            .build();
+
+        processor.on_end(new_test_export_span_data());
+        assert!(processor.force_flush().is_ok());
+        assert_eq!(exporter.calls(), vec!["export", "force_flush"]);
+
+        let ack = processor.on_end_with_ack(new_test_export_span_data());
+        assert!(processor.force_flush().is_ok());
+        futures_executor::block_on(ack).unwrap();
+        assert_eq!(exporter.calls().len(), 4);
+
+        // shutting down exports the remaining spans
+        processor.on_end(new_test_export_span_data());
+        processor.shutdown().unwrap();
+        assert_eq!(exporter.calls().len(), 6);
+        processor.on_end(new_test_export_span_data());
+        assert!(processor.force_flush().is_err());
+        assert_eq!(exporter.calls().len(), 6);
+    }
+
+    // the worker exports on its own, even while the current thread runtime
+    // of the application is blocked
+    #[tokio::test]
+    async fn own_thread_processor_scheduled_export() {
+        let exporter = FlushRecordingExporter::default();
+        let processor = BatchSpanProcessor::builder(exporter.clone(), runtime::Tokio)
+            .with_own_thread()
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_millis(10))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+
+        processor.on_end(new_test_export_span_data());
+        for _ in 0..500 {
+            if !exporter.calls().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(exporter.calls(), vec!["export"]);
+        assert_eq!(processor.diagnostics().queued, Some(0));
+
+        // dropping the processor exports the remaining spans
+        processor.on_end(new_test_export_span_data());
+        drop(processor);
+        for _ in 0..500 {
+            if exporter.calls().len() == 3 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(exporter.calls(), vec!["export", "export", "force_flush"]);
+    }
+
+    /// Signals each export it starts, and waits for a release to finish it.
+    #[derive(Debug)]
+    struct GatedExporter {
+        started: std::sync::mpsc::Sender<()>,
+        release: Arc<Mutex<std::sync::mpsc::Receiver<()>>>,
+        exported: Arc<Mutex<usize>>,
+    }
+
+    impl SpanExporter for GatedExporter {
+        fn export(
+            &mut self,
+            batch: Vec<SpanData>,
+        ) -> futures_util::future::BoxFuture<'static, ExportResult> {
+            let _ = self.started.send(());
+            let _ = self.release.lock().unwrap().recv();
+            *self.exported.lock().unwrap() += batch.len();
+            Box::pin(futures_util::future::ready(Ok(())))
+        }
+    }
+
+    // flush and shutdown requests are not rejected while the queue is full
+    #[test]
+    fn own_thread_processor_flush_with_full_queue() {
+        let (started_sender, started) = std::sync::mpsc::channel();
+        let (release, release_receiver) = std::sync::mpsc::channel();
+        let exported = Arc::new(Mutex::new(0));
+        let exporter = GatedExporter {
+            started: started_sender,
+            release: Arc::new(Mutex::new(release_receiver)),
+            exported: exported.clone(),
+        };
+        let processor = Arc::new(
+            BatchSpanProcessor::builder(exporter, runtime::Tokio)
+                .with_own_thread()
+                .with_batch_config(
+                    BatchConfigBuilder::default()
+                        .with_max_queue_size(2)
+                        .with_max_export_batch_size(1)
+                        .with_scheduled_delay(Duration::from_secs(3600))
+                        .build()
+                        .unwrap(),
+                )
+                .build(),
+        );
+
+        // the worker blocks exporting the first span, the queue fills up
+        processor.on_end(new_test_export_span_data());
+        started.recv().unwrap();
+        for _ in 0..3 {
+            processor.on_end(new_test_export_span_data());
+        }
+        assert_eq!(processor.dropped_telemetry().queue_full, 1);
+
+        let flushing = processor.clone();
+        let flush = std::thread::spawn(move || flushing.force_flush());
+        std::thread::sleep(Duration::from_millis(10));
+        for _ in 0..3 {
+            release.send(()).unwrap();
+        }
+
+        assert!(flush.join().unwrap().is_ok());
+        assert_eq!(*exported.lock().unwrap(), 3);
+        assert!(processor.shutdown().is_ok());
+    }
+
     struct BlockingExporter<D> {
         delay_for: Duration,
         delay_fn: D,
@@ -1035,4 +2659,145 @@ mod tests {
         let shutdown_res = processor.shutdown();
         assert!(shutdown_res.is_ok());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_span_processor_shutdown_with_timeout() {
+        let config = BatchConfig {
+            max_export_timeout: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let exporter = BlockingExporter {
+            delay_for: Duration::from_secs(60),
+            delay_fn: tokio::time::sleep,
+        };
+        let processor = BatchSpanProcessor::new(Box::new(exporter), config, runtime::Tokio);
+        processor.on_end(new_test_export_span_data());
+
+        // the export of the remaining span outlasts the timeout
+        assert_eq!(
+            processor.shutdown_with_timeout(Duration::from_millis(50)),
+            Err(ShutdownError::Timeout(Duration::from_millis(50)))
+        );
+    }
+
+    #[derive(Debug)]
+    struct RecordingProcessor {
+        name: &'static str,
+        fail: bool,
+        calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl RecordingProcessor {
+        fn record(&self, call: &str) -> opentelemetry::trace::TraceResult<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", self.name, call));
+            if self.fail {
+                Err(opentelemetry::trace::TraceError::from(self.name))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl SpanProcessor for RecordingProcessor {
+        fn on_start(&self, _span: &mut crate::trace::Span, _cx: &opentelemetry::Context) {
+            let _ = self.record("start");
+        }
+
+        fn on_end(&self, _span: SpanData) {
+            let _ = self.record("end");
+        }
+
+        fn force_flush(&self) -> opentelemetry::trace::TraceResult<()> {
+            self.record("flush")
+        }
+
+        fn shutdown(&self) -> opentelemetry::trace::TraceResult<()> {
+            self.record("shutdown")
+        }
+    }
+
+    fn composite(
+        failing: &[bool],
+    ) -> (
+        CompositeSpanProcessor,
+        std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    ) {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let names = ["first", "second", "third"];
+        let processors = failing
+            .iter()
+            .zip(names)
+            .map(|(fail, name)| {
+                Box::new(RecordingProcessor {
+                    name,
+                    fail: *fail,
+                    calls: calls.clone(),
+                }) as Box<dyn SpanProcessor>
+            })
+            .collect();
+        (CompositeSpanProcessor::new(processors), calls)
+    }
+
+    #[test]
+    fn composite_span_processor_calls_processors_in_order() {
+        let (processor, calls) = composite(&[false, false]);
+        processor.on_end(new_test_export_span_data());
+        assert!(processor.force_flush().is_ok());
+        assert!(processor.shutdown().is_ok());
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "first:end",
+                "second:end",
+                "first:flush",
+                "second:flush",
+                "first:shutdown",
+                "second:shutdown",
+            ]
+        );
+    }
+
+    #[test]
+    fn composite_span_processor_flushes_all_and_combines_errors() {
+        let (processor, calls) = composite(&[false, true, true]);
+        let err = processor.force_flush().unwrap_err();
+
+        assert_eq!(err.to_string(), "second, third");
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["first:flush", "second:flush", "third:flush"]
+        );
+    }
+
+    #[test]
+    fn composite_span_processor_shuts_down_all_and_combines_errors() {
+        let (processor, calls) = composite(&[false, true, true]);
+        let err = processor.shutdown().unwrap_err();
+
+        assert_eq!(err.to_string(), "second, third");
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["first:shutdown", "second:shutdown", "third:shutdown"]
+        );
+
+        let (processor, calls) = composite(&[true, false, true]);
+        let err = processor
+            .shutdown_with_timeout(Duration::from_secs(1))
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            ShutdownError::InternalFailure(
+                "shutdown failed: first, shutdown failed: third".to_string()
+            )
+        );
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["first:shutdown", "second:shutdown", "third:shutdown"]
+        );
+    }
 }
@@ -0,0 +1,95 @@
+//! # Span Start Hook
+//!
+//! [`SpanStartHook`]s add default attributes to spans of a given [`SpanKind`]
+//! when they are started, e.g. the `server.address` of every `CLIENT` span
+//! created by a database client, without repeating it at every call site.
+//!
+//! Hooks run before the sampling decision, so samplers see the added
+//! attributes, and attributes set by the span builder take precedence.
+use opentelemetry::trace::SpanKind;
+use opentelemetry::KeyValue;
+use std::fmt::Debug;
+
+/// Hook adding attributes to spans when they are started, registered for a
+/// [`SpanKind`] with [`Builder::with_span_start_hook`].
+///
+/// Hooks run synchronously on the thread starting the span, for sampled and
+/// unsampled spans alike, so implementations should return quickly.
+///
+/// A `Vec<KeyValue>` is a hook adding a fixed set of attributes, e.g. read
+/// from a configuration file.
+///
+/// [`Builder::with_span_start_hook`]: crate::trace::Builder::with_span_start_hook
+pub trait SpanStartHook: Send + Sync + Debug {
+    /// Called when a span of the registered kind is started, with the span
+    /// name and the attributes set by the span builder.
+    ///
+    /// Attributes pushed with the key of an attribute already present are
+    /// ignored.
+    fn on_start(&self, name: &str, attributes: &mut Vec<KeyValue>);
+}
+
+impl SpanStartHook for Vec<KeyValue> {
+    fn on_start(&self, _name: &str, attributes: &mut Vec<KeyValue>) {
+        attributes.extend(self.iter().cloned());
+    }
+}
+
+/// Run the hooks registered for `span_kind`, dropping the attributes they
+/// added with keys already set.
+pub(crate) fn run_span_start_hooks(
+    hooks: &[(SpanKind, Box<dyn SpanStartHook>)],
+    span_kind: &SpanKind,
+    name: &str,
+    attributes: &mut Vec<KeyValue>,
+) {
+    for (kind, hook) in hooks {
+        if kind != span_kind {
+            continue;
+        }
+        let existing = attributes.len();
+        hook.on_start(name, attributes);
+        let mut index = existing;
+        while index < attributes.len() {
+            let key = &attributes[index].key;
+            if attributes[..index].iter().any(|kv| &kv.key == key) {
+                attributes.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing", feature = "trace"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_only_run_for_their_kind_and_never_override() {
+        let hooks: Vec<(SpanKind, Box<dyn SpanStartHook>)> = vec![
+            (
+                SpanKind::Client,
+                Box::new(vec![
+                    KeyValue::new("server.address", "db.local"),
+                    KeyValue::new("db.system", "postgresql"),
+                ]),
+            ),
+            (
+                SpanKind::Server,
+                Box::new(vec![KeyValue::new("server.port", 8080)]),
+            ),
+        ];
+
+        let mut attributes = vec![KeyValue::new("db.system", "mysql")];
+        run_span_start_hooks(&hooks, &SpanKind::Client, "query", &mut attributes);
+
+        assert_eq!(
+            attributes,
+            vec![
+                KeyValue::new("db.system", "mysql"),
+                KeyValue::new("server.address", "db.local"),
+            ]
+        );
+    }
+}
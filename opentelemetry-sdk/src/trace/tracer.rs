@@ -11,18 +11,21 @@ use crate::{
     trace::{
         provider::TracerProvider,
         span::{Span, SpanData},
+        span_limit::SpanBytesBudget,
         SpanLimits, SpanLinks,
     },
     InstrumentationLibrary,
 };
 use opentelemetry::{
-    trace::{SamplingDecision, SpanBuilder, SpanContext, SpanKind, TraceContextExt, TraceFlags},
+    trace::{
+        SamplingDecision, SpanBuilder, SpanContext, SpanId, SpanKind, TraceContextExt, TraceFlags,
+    },
     Context, KeyValue,
 };
 use std::fmt;
 use std::sync::Arc;
 
-use super::SpanEvents;
+use super::{span_start_hook::run_span_start_hooks, SpanEvents};
 
 /// `Tracer` implementation to create and manage spans
 #[derive(Clone)]
@@ -81,34 +84,42 @@ impl Tracer {
             .len()
             .saturating_sub(span_attributes_limit);
         attribute_options.truncate(span_attributes_limit);
+        span_limits.limit_values(&mut attribute_options);
+        // The bytes limit is consumed by attributes, then links, then events.
+        let mut bytes_budget = SpanBytesBudget::new(&span_limits);
+        let dropped_attributes_count =
+            dropped_attributes_count + bytes_budget.retain_attributes(&mut attribute_options);
         let dropped_attributes_count = dropped_attributes_count as u32;
 
-        // Links are available as Option<Vec<Link>> in the builder
-        // If it is None, then there are no links to process.
-        // In that case Span.Links will be default (empty Vec<Link>, 0 drop count)
-        // Otherwise, truncate Vec<Link> to keep until limits and use that in Span.Links.
+        // Links are available as Option<Vec<Link>> in the builder, followed
+        // by the lazy links which are only iterated now the span is recording.
+        // If there are none, Span.Links will be default (empty Vec<Link>, 0 drop count)
+        // Otherwise, keep links until limits and use that in Span.Links.
+        // Lazy links past the limit are neither iterated nor counted, the
+        // iterator may be unbounded.
         // Store the count of excess links into Span.Links.dropped_count.
-        // There is no ability today to add Links after Span creation,
-        // but such a capability will be needed in the future
-        // once the spec for that stabilizes.
 
         let spans_links_limit = span_limits.max_links_per_span as usize;
-        let span_links: SpanLinks = if let Some(mut links) = builder.links.take() {
-            let dropped_count = links.len().saturating_sub(spans_links_limit);
-            links.truncate(spans_links_limit);
-            let link_attributes_limit = span_limits.max_attributes_per_link as usize;
-            for link in links.iter_mut() {
-                let dropped_attributes_count =
-                    link.attributes.len().saturating_sub(link_attributes_limit);
-                link.attributes.truncate(link_attributes_limit);
-                link.dropped_attributes_count = dropped_attributes_count as u32;
-            }
-            SpanLinks {
-                links,
-                dropped_count: dropped_count as u32,
-            }
-        } else {
-            SpanLinks::default()
+        let mut links = builder.links.take().unwrap_or_default();
+        let dropped_count = links.len().saturating_sub(spans_links_limit);
+        links.truncate(spans_links_limit);
+        if let Some(lazy_links) = builder.lazy_links.take().and_then(|links| links.take()) {
+            links.extend(lazy_links.take(spans_links_limit - links.len()));
+        }
+        let link_attributes_limit = span_limits.max_attributes_per_link as usize;
+        for link in links.iter_mut() {
+            let dropped_attributes_count =
+                link.attributes.len().saturating_sub(link_attributes_limit);
+            link.attributes.truncate(link_attributes_limit);
+            link.dropped_attributes_count = dropped_attributes_count as u32;
+            span_limits.limit_values(&mut link.attributes);
+        }
+        let kept = links.len();
+        links.retain_mut(|link| bytes_budget.admit_link(link));
+        let dropped_count = dropped_count + kept - links.len();
+        let span_links = SpanLinks {
+            links,
+            dropped_count: dropped_count as u32,
         };
 
         let SpanBuilder {
@@ -134,7 +145,11 @@ impl Tracer {
                     .saturating_sub(event_attributes_limit);
                 event.attributes.truncate(event_attributes_limit);
                 event.dropped_attributes_count = dropped_attributes_count as u32;
+                span_limits.limit_values(&mut event.attributes);
             }
+            let kept = events.len();
+            events.retain_mut(|event| bytes_budget.admit_event(event));
+            let dropped_count = dropped_count + kept - events.len();
             SpanEvents {
                 events,
                 dropped_count: dropped_count as u32,
@@ -159,6 +174,7 @@ impl Tracer {
             self.clone(),
             span_limits,
         )
+        .with_bytes_budget(bytes_budget)
     }
 }
 
@@ -209,6 +225,32 @@ impl opentelemetry::trace::Tracer for Tracer {
                 .unwrap_or_else(|| config.id_generator.new_trace_id());
         };
 
+        if let Some(problem) = span_id_collision(psc, span_id) {
+            provider.report_span_id_collision(trace_id, span_id, problem);
+        }
+
+        // Shed spans are non-recording and skip hooks, sampling and processors.
+        if provider.should_shed(
+            &builder.name,
+            builder.span_kind.as_ref().unwrap_or(&SpanKind::Internal),
+        ) {
+            let span_context = SpanContext::new(
+                trace_id,
+                span_id,
+                TraceFlags::default(),
+                false,
+                psc.trace_state().clone(),
+            );
+            return Span::new(span_context, None, self.clone(), config.span_limits);
+        }
+
+        let span_start_hooks = provider.span_start_hooks();
+        if !span_start_hooks.is_empty() {
+            let span_kind = builder.span_kind.as_ref().unwrap_or(&SpanKind::Internal);
+            let attributes = builder.attributes.get_or_insert_with(Vec::new);
+            run_span_start_hooks(span_start_hooks, span_kind, &builder.name, attributes);
+        }
+
         // In order to accommodate use cases like `tracing-opentelemetry` we there is the ability
         // to use pre-sampling. Otherwise, the standard method of sampling is followed.
         let samplings_result = if let Some(sr) = builder.sampling_result.take() {
@@ -268,15 +310,42 @@ impl opentelemetry::trace::Tracer for Tracer {
             }
         };
 
+        #[cfg(feature = "experimental_trace_sampling_veto")]
+        if opentelemetry::trace::Span::is_recording(&span) {
+            let decision = crate::trace::span_processor::lowest_sampling_decision(
+                provider.span_processors(),
+                &span,
+                parent_cx,
+            );
+            span.downgrade_sampling(decision);
+        }
+
         // Call `on_start` for all processors
         for processor in provider.span_processors() {
             processor.on_start(&mut span, parent_cx)
         }
 
+        let listeners = provider.span_listeners();
+        if !listeners.is_empty() {
+            span.notify_start(listeners);
+        }
+
         span
     }
 }
 
+/// Returns why `span_id` cannot identify a new child of `parent`, if it
+/// cannot.
+fn span_id_collision(parent: &SpanContext, span_id: SpanId) -> Option<&'static str> {
+    if span_id == SpanId::INVALID {
+        Some("is invalid")
+    } else if parent.is_valid() && parent.span_id() == span_id {
+        Some("collides with its parent span")
+    } else {
+        None
+    }
+}
+
 #[cfg(all(test, feature = "testing", feature = "trace"))]
 mod tests {
     use crate::{
@@ -386,4 +455,150 @@ mod tests {
 
         assert!(!span.span_context().is_sampled());
     }
+
+    #[test]
+    fn span_start_hooks_add_attributes_by_kind() {
+        let tracer_provider = crate::trace::TracerProvider::builder()
+            .with_span_start_hook(
+                SpanKind::Client,
+                vec![
+                    KeyValue::new("server.address", "db.local"),
+                    KeyValue::new("peer.service", "db"),
+                ],
+            )
+            .build();
+        let tracer = tracer_provider.tracer("test");
+
+        let client = tracer
+            .span_builder("query")
+            .with_kind(SpanKind::Client)
+            .with_attributes([KeyValue::new("peer.service", "replica")])
+            .start(&tracer);
+        assert_eq!(
+            client.exported_data().unwrap().attributes,
+            vec![
+                KeyValue::new("peer.service", "replica"),
+                KeyValue::new("server.address", "db.local"),
+            ]
+        );
+
+        let internal = tracer.span_builder("work").start(&tracer);
+        assert!(internal.exported_data().unwrap().attributes.is_empty());
+    }
+
+    #[test]
+    fn detect_span_id_collisions() {
+        let parent = SpanContext::new(
+            TraceId::from_u128(128),
+            SpanId::from_u64(64),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+
+        assert_eq!(
+            super::span_id_collision(&parent, SpanId::from_u64(65)),
+            None
+        );
+        assert_eq!(
+            super::span_id_collision(&parent, SpanId::from_u64(64)),
+            Some("collides with its parent span")
+        );
+        assert_eq!(
+            super::span_id_collision(&parent, SpanId::INVALID),
+            Some("is invalid")
+        );
+        assert_eq!(
+            super::span_id_collision(&SpanContext::empty_context(), SpanId::from_u64(64)),
+            None
+        );
+    }
+
+    #[cfg(feature = "experimental_trace_sampling_veto")]
+    #[test]
+    fn processors_downgrade_sampling_before_on_start() {
+        use crate::{
+            export::trace::SpanData,
+            trace::{Span as SdkSpan, SpanProcessor},
+        };
+        use opentelemetry::trace::TraceResult;
+        use std::sync::{Arc, Mutex};
+
+        /// Vetoes spans by tenant, and records how `on_start` observed them.
+        #[derive(Debug)]
+        struct TenantKillSwitch(Arc<Mutex<Vec<(bool, bool)>>>);
+
+        impl SpanProcessor for TenantKillSwitch {
+            fn on_start(&self, span: &mut SdkSpan, _cx: &Context) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((span.is_recording(), span.span_context().is_sampled()));
+            }
+
+            fn on_end(&self, _span: SpanData) {}
+
+            fn force_flush(&self) -> TraceResult<()> {
+                Ok(())
+            }
+
+            fn shutdown(&self) -> TraceResult<()> {
+                Ok(())
+            }
+
+            fn sampling_decision(&self, span: &SdkSpan, _cx: &Context) -> SamplingDecision {
+                let tenant = span.exported_data().and_then(|data| {
+                    data.attributes
+                        .into_iter()
+                        .find(|kv| kv.key.as_str() == "tenant")
+                        .map(|kv| kv.value.to_string())
+                });
+                match tenant.as_deref() {
+                    Some("blocked") => SamplingDecision::Drop,
+                    Some("muted") => SamplingDecision::RecordOnly,
+                    _ => SamplingDecision::RecordAndSample,
+                }
+            }
+        }
+
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let tracer_provider = crate::trace::TracerProvider::builder()
+            .with_span_processor(TenantKillSwitch(started.clone()))
+            .build();
+        let tracer = tracer_provider.tracer("test");
+
+        let allowed = tracer.build(
+            tracer
+                .span_builder("allowed")
+                .with_attributes([KeyValue::new("tenant", "a")]),
+        );
+        assert!(allowed.is_recording());
+        assert!(allowed.span_context().is_sampled());
+
+        let blocked = tracer.build(
+            tracer
+                .span_builder("blocked")
+                .with_attributes([KeyValue::new("tenant", "blocked")]),
+        );
+        assert!(!blocked.is_recording());
+        assert!(!blocked.span_context().is_sampled());
+
+        let muted = tracer.build(
+            tracer
+                .span_builder("muted")
+                .with_attributes([KeyValue::new("tenant", "muted")]),
+        );
+        assert!(muted.is_recording());
+        assert!(!muted.span_context().is_sampled());
+
+        // children of a downgraded span follow its decision
+        let cx = Context::current_with_span(muted);
+        let child = tracer.start_with_context("child", &cx);
+        assert!(!child.span_context().is_sampled());
+
+        assert_eq!(
+            *started.lock().unwrap(),
+            [(true, true), (false, false), (true, false), (false, false)]
+        );
+    }
 }
@@ -1,9 +1,34 @@
 //! Internal utilities
 
 /// Helper which wraps `tokio::time::interval` and makes it return a stream
-#[cfg(any(feature = "rt-tokio", feature = "rt-tokio-current-thread"))]
+#[cfg(any(
+    feature = "rt-tokio",
+    feature = "rt-tokio-current-thread",
+    feature = "rt-tokio-dedicated-thread"
+))]
 pub fn tokio_interval_stream(
     period: std::time::Duration,
 ) -> tokio_stream::wrappers::IntervalStream {
     tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(period))
 }
+
+/// Reads a positive integer from the environment variable `name`, reporting
+/// values which are not with `to_error` and ignoring them, so that the default
+/// is used instead.
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub(crate) fn positive_from_env<T, E>(name: &str, to_error: fn(String) -> E) -> Option<T>
+where
+    T: std::str::FromStr + Default + PartialEq,
+    E: Into<opentelemetry::global::Error>,
+{
+    let value = std::env::var(name).ok()?;
+    match T::from_str(value.trim()) {
+        Ok(parsed) if parsed != T::default() => Some(parsed),
+        _ => {
+            opentelemetry::global::handle_error(to_error(format!(
+                "ignoring {name}={value:?}, expected a positive integer"
+            )));
+            None
+        }
+    }
+}
@@ -42,6 +42,16 @@ docker run --rm \
 	--output /output/resource.rs \
 	--parameters conventions=resource
 
+docker run --rm \
+	-v "${CRATE_DIR}/semantic-conventions/model:/source" \
+	-v "${CRATE_DIR}/scripts/templates:/templates" \
+	-v "${CRATE_DIR}/src:/output" \
+	otel/semconvgen:$SEMCOVGEN_VERSION \
+  --only span,event,attribute_group,scope,resource \
+  -f /source code \
+	--template /templates/deprecated_attributes.rs.j2 \
+	--output /output/migrations/deprecated_attributes.rs
+
 SED=(sed -i)
 if [[ "$(uname)" = "Darwin" ]]; then
   SED=(sed -i "")
@@ -15,6 +15,7 @@
     html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
 )]
 
+pub mod migrations;
 pub mod resource;
 pub mod trace;
 
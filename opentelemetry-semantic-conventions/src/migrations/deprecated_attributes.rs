@@ -0,0 +1,271 @@
+// DO NOT EDIT, this is an auto-generated file
+//
+// If you want to update the file:
+// - Edit the template at scripts/templates/deprecated_attributes.rs.j2
+// - Run the script at scripts/generate-consts-from-spec.sh
+
+use super::DeprecatedAttribute;
+
+/// Deprecated attributes of the registry, sorted by name.
+pub(super) static DEPRECATED_ATTRIBUTES: &[DeprecatedAttribute] = &[
+    DeprecatedAttribute {
+        name: "android.state",
+        replacement: None,
+        note: "Deprecated use the `device.app.lifecycle` event definition including `android.state` as a payload field instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.cassandra.table",
+        replacement: Some("db.collection.name"),
+        note: "Deprecated, use `db.collection.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.connection_string",
+        replacement: None,
+        note: "Deprecated, use `server.address`, `server.port` attributes instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.cosmosdb.container",
+        replacement: Some("db.collection.name"),
+        note: "Deprecated, use `db.collection.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.instance.id",
+        replacement: None,
+        note: "Deprecated, no general replacement at this time. For Elasticsearch, use `db.elasticsearch.node.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.jdbc.driver_classname",
+        replacement: None,
+        note: "Removed, no replacement at this time.",
+    },
+    DeprecatedAttribute {
+        name: "db.mongodb.collection",
+        replacement: Some("db.collection.name"),
+        note: "Deprecated, use `db.collection.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.mssql.instance_name",
+        replacement: None,
+        note: "Deprecated, SQL Server instance is now populated as a part of `db.namespace` attribute.",
+    },
+    DeprecatedAttribute {
+        name: "db.name",
+        replacement: Some("db.namespace"),
+        note: "Deprecated, use `db.namespace` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.operation",
+        replacement: Some("db.operation.name"),
+        note: "Deprecated, use `db.operation.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.redis.database_index",
+        replacement: Some("db.namespace"),
+        note: "Deprecated, use `db.namespace` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.sql.table",
+        replacement: Some("db.collection.name"),
+        note: "Deprecated, use `db.collection.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "db.user",
+        replacement: None,
+        note: "Deprecated, no replacement at this time.",
+    },
+    DeprecatedAttribute {
+        name: "http.client_ip",
+        replacement: Some("client.address"),
+        note: "Deprecated, use `client.address` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.flavor",
+        replacement: Some("network.protocol.name"),
+        note: "Deprecated, use `network.protocol.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.host",
+        replacement: None,
+        note: "Deprecated, use one of `server.address`, `client.address` or `http.request.header.host` instead, depending on the usage.",
+    },
+    DeprecatedAttribute {
+        name: "http.method",
+        replacement: Some("http.request.method"),
+        note: "Deprecated, use `http.request.method` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.request_content_length",
+        replacement: Some("http.request.header.content-length"),
+        note: "Deprecated, use `http.request.header.content-length` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.request_content_length_uncompressed",
+        replacement: Some("http.request.body.size"),
+        note: "Deprecated, use `http.request.body.size` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.response_content_length",
+        replacement: Some("http.response.header.content-length"),
+        note: "Deprecated, use `http.response.header.content-length` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.response_content_length_uncompressed",
+        replacement: Some("http.response.body.size"),
+        note: "Deprecated, use `http.response.body.size` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.scheme",
+        replacement: Some("url.scheme"),
+        note: "Deprecated, use `url.scheme` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.server_name",
+        replacement: Some("server.address"),
+        note: "Deprecated, use `server.address` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.status_code",
+        replacement: Some("http.response.status_code"),
+        note: "Deprecated, use `http.response.status_code` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.target",
+        replacement: None,
+        note: "Deprecated, use `url.path` and `url.query` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.url",
+        replacement: Some("url.full"),
+        note: "Deprecated, use `url.full` instead.",
+    },
+    DeprecatedAttribute {
+        name: "http.user_agent",
+        replacement: Some("user_agent.original"),
+        note: "Deprecated, use `user_agent.original` instead.",
+    },
+    DeprecatedAttribute {
+        name: "ios.state",
+        replacement: None,
+        note: "Deprecated use the `device.app.lifecycle` event definition including `ios.state` as a payload field instead.",
+    },
+    DeprecatedAttribute {
+        name: "message.compressed_size",
+        replacement: Some("rpc.message.compressed_size"),
+        note: "Deprecated, use `rpc.message.compressed_size` instead.",
+    },
+    DeprecatedAttribute {
+        name: "message.id",
+        replacement: Some("rpc.message.id"),
+        note: "Deprecated, use `rpc.message.id` instead.",
+    },
+    DeprecatedAttribute {
+        name: "message.type",
+        replacement: Some("rpc.message.type"),
+        note: "Deprecated, use `rpc.message.type` instead.",
+    },
+    DeprecatedAttribute {
+        name: "message.uncompressed_size",
+        replacement: Some("rpc.message.uncompressed_size"),
+        note: "Deprecated, use `rpc.message.uncompressed_size` instead.",
+    },
+    DeprecatedAttribute {
+        name: "messaging.client_id",
+        replacement: Some("messaging.client.id"),
+        note: "Deprecated, use `messaging.client.id` instead.",
+    },
+    DeprecatedAttribute {
+        name: "messaging.operation",
+        replacement: Some("messaging.operation.type"),
+        note: "Deprecated, use `messaging.operation.type` instead.",
+    },
+    DeprecatedAttribute {
+        name: "net.host.ip",
+        replacement: Some("network.local.address"),
+        note: "Deprecated, use `network.local.address`.",
+    },
+    DeprecatedAttribute {
+        name: "net.host.name",
+        replacement: Some("server.address"),
+        note: "Deprecated, use `server.address`.",
+    },
+    DeprecatedAttribute {
+        name: "net.host.port",
+        replacement: Some("server.port"),
+        note: "Deprecated, use `server.port`.",
+    },
+    DeprecatedAttribute {
+        name: "net.peer.ip",
+        replacement: Some("network.peer.address"),
+        note: "Deprecated, use `network.peer.address`.",
+    },
+    DeprecatedAttribute {
+        name: "net.peer.name",
+        replacement: None,
+        note: "Deprecated, use `server.address` on client spans and `client.address` on server spans.",
+    },
+    DeprecatedAttribute {
+        name: "net.peer.port",
+        replacement: None,
+        note: "Deprecated, use `server.port` on client spans and `client.port` on server spans.",
+    },
+    DeprecatedAttribute {
+        name: "net.protocol.name",
+        replacement: Some("network.protocol.name"),
+        note: "Deprecated, use `network.protocol.name`.",
+    },
+    DeprecatedAttribute {
+        name: "net.protocol.version",
+        replacement: Some("network.protocol.version"),
+        note: "Deprecated, use `network.protocol.version`.",
+    },
+    DeprecatedAttribute {
+        name: "net.sock.family",
+        replacement: None,
+        note: "Deprecated, use `network.transport` and `network.type`.",
+    },
+    DeprecatedAttribute {
+        name: "net.sock.host.addr",
+        replacement: Some("network.local.address"),
+        note: "Deprecated, use `network.local.address`.",
+    },
+    DeprecatedAttribute {
+        name: "net.sock.host.port",
+        replacement: Some("network.local.port"),
+        note: "Deprecated, use `network.local.port`.",
+    },
+    DeprecatedAttribute {
+        name: "net.sock.peer.addr",
+        replacement: Some("network.peer.address"),
+        note: "Deprecated, use `network.peer.address`.",
+    },
+    DeprecatedAttribute {
+        name: "net.sock.peer.name",
+        replacement: None,
+        note: "Deprecated, no replacement at this time.",
+    },
+    DeprecatedAttribute {
+        name: "net.sock.peer.port",
+        replacement: Some("network.peer.port"),
+        note: "Deprecated, use `network.peer.port`.",
+    },
+    DeprecatedAttribute {
+        name: "net.transport",
+        replacement: Some("network.transport"),
+        note: "Deprecated, use `network.transport`.",
+    },
+    DeprecatedAttribute {
+        name: "pool.name",
+        replacement: Some("db.client.connections.pool.name"),
+        note: "Deprecated, use `db.client.connections.pool.name` instead.",
+    },
+    DeprecatedAttribute {
+        name: "state",
+        replacement: Some("db.client.connections.state"),
+        note: "Deprecated, use `db.client.connections.state` instead.",
+    },
+    DeprecatedAttribute {
+        name: "system.processes.status",
+        replacement: Some("system.process.status"),
+        note: "Deprecated, use `system.process.status` instead.",
+    },
+];
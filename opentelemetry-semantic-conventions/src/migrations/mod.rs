@@ -0,0 +1,64 @@
+//! # Attribute Migrations
+//!
+//! Mapping of the attributes deprecated by the [semantic conventions] to their
+//! replacements, generated from the registry metadata, e.g. for processors
+//! migrating telemetry to the current schema or lint tooling.
+//!
+//! [semantic conventions]: https://github.com/open-telemetry/semantic-conventions/tree/main/model
+//!
+//! ## Usage
+//!
+//! ```
+//! use opentelemetry_semantic_conventions as semconv;
+//!
+//! assert_eq!(
+//!     semconv::migrations::replacement_for("http.method"),
+//!     Some("http.request.method")
+//! );
+//!
+//! // deprecated without a single replacement
+//! let net_peer_name = semconv::migrations::deprecated_attribute("net.peer.name").unwrap();
+//! assert_eq!(net_peer_name.replacement, None);
+//!
+//! // attributes which are not deprecated have no entry
+//! assert!(semconv::migrations::deprecated_attribute("http.request.method").is_none());
+//! ```
+
+mod deprecated_attributes;
+
+use deprecated_attributes::DEPRECATED_ATTRIBUTES;
+
+/// An attribute deprecated by the semantic conventions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeprecatedAttribute {
+    /// The name of the deprecated attribute.
+    pub name: &'static str,
+    /// The name of the attribute replacing it, if it was renamed.
+    ///
+    /// `None` if the attribute was removed, or replaced by several attributes
+    /// or depending on the context, see [`note`](Self::note) in that case.
+    pub replacement: Option<&'static str>,
+    /// The deprecation note of the registry.
+    pub note: &'static str,
+}
+
+/// Returns the name of the attribute replacing the deprecated attribute
+/// `name`, if it was renamed.
+pub fn replacement_for(name: &str) -> Option<&'static str> {
+    deprecated_attribute(name).and_then(|attribute| attribute.replacement)
+}
+
+/// Returns the deprecation of the attribute `name`, or `None` if it is not
+/// deprecated.
+pub fn deprecated_attribute(name: &str) -> Option<&'static DeprecatedAttribute> {
+    DEPRECATED_ATTRIBUTES
+        .binary_search_by(|attribute| attribute.name.cmp(name))
+        .ok()
+        .map(|index| &DEPRECATED_ATTRIBUTES[index])
+}
+
+/// Returns all deprecated attributes, sorted by name.
+pub fn deprecated_attributes() -> impl Iterator<Item = &'static DeprecatedAttribute> {
+    DEPRECATED_ATTRIBUTES.iter()
+}
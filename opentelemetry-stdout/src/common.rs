@@ -1,13 +1,68 @@
 use std::{
     borrow::Cow,
+    cell::Cell,
     collections::BTreeMap,
     hash::{Hash, Hasher},
+    io::Write,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use chrono::{LocalResult, TimeZone, Utc};
 use ordered_float::OrderedFloat;
-use serde::{Serialize, Serializer};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+/// The output format of the stdout exporters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// Indented JSON spanning several lines, with human readable timestamps
+    /// next to the OTLP ones.
+    Pretty,
+    /// A line of JSON per export, with human readable timestamps next to the
+    /// OTLP ones.
+    #[default]
+    Compact,
+    /// A line of [OTLP JSON] per export, e.g. for piping to `jq` or replaying
+    /// to a collector. Timestamps are only written as nanoseconds.
+    ///
+    /// [OTLP JSON]: https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding
+    Json,
+}
+
+thread_local! {
+    /// Whether values are being serialized in the `Json` output format.
+    static OTLP_JSON: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Writes `value` to `writer` in `format`, without a trailing line break.
+pub(crate) fn write_json<T: Serialize>(
+    writer: &mut dyn Write,
+    value: &T,
+    format: OutputFormat,
+) -> serde_json::Result<()> {
+    match format {
+        OutputFormat::Pretty => serde_json::to_writer_pretty(writer, value),
+        OutputFormat::Compact => serde_json::to_writer(writer, value),
+        OutputFormat::Json => {
+            struct Reset;
+            impl Drop for Reset {
+                fn drop(&mut self) {
+                    OTLP_JSON.with(|otlp_json| otlp_json.set(false));
+                }
+            }
+
+            OTLP_JSON.with(|otlp_json| otlp_json.set(true));
+            let _reset = Reset;
+            serde_json::to_writer(writer, value)
+        }
+    }
+}
+
+/// Returns whether values are being serialized as OTLP JSON, used to skip the
+/// fields which are not part of OTLP.
+pub(crate) fn is_otlp_json<T: ?Sized>(_: &T) -> bool {
+    OTLP_JSON.with(Cell::get)
+}
 
 #[derive(Debug, Serialize, Clone, Hash, Eq, PartialEq)]
 pub(crate) struct AttributeSet(pub BTreeMap<Key, Value>);
@@ -65,25 +120,77 @@ impl From<opentelemetry::Key> for Key {
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Clone)]
 #[allow(dead_code, clippy::enum_variant_names)] // we want to emphasize the *Values are collection
 pub(crate) enum Value {
-    #[serde(rename = "boolValue")]
     Bool(bool),
-    #[serde(rename = "intValue")]
     Int(i64),
-    #[serde(rename = "doubleValue")]
     Double(f64),
-    #[serde(rename = "stringValue")]
     String(String),
-    #[serde(rename = "arrayValue")]
     Array(Vec<Value>),
-    #[serde(rename = "kvListValue")]
     KeyValues(Vec<KeyValue>),
-    #[serde(rename = "bytesValue")]
     BytesValue(Vec<u8>),
 }
 
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let otlp_json = is_otlp_json(self);
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            Value::Bool(b) => map.serialize_entry("boolValue", b)?,
+            // 64 bit integers are strings in OTLP JSON
+            Value::Int(i) if otlp_json => map.serialize_entry("intValue", &i.to_string())?,
+            Value::Int(i) => map.serialize_entry("intValue", i)?,
+            Value::Double(f) => map.serialize_entry("doubleValue", f)?,
+            Value::String(s) => map.serialize_entry("stringValue", s)?,
+            Value::Array(values) if otlp_json => {
+                map.serialize_entry("arrayValue", &Values { values })?
+            }
+            Value::Array(values) => map.serialize_entry("arrayValue", values)?,
+            Value::KeyValues(values) if otlp_json => {
+                map.serialize_entry("kvlistValue", &Values { values })?
+            }
+            Value::KeyValues(values) => map.serialize_entry("kvListValue", values)?,
+            Value::BytesValue(bytes) if otlp_json => {
+                map.serialize_entry("bytesValue", &base64(bytes))?
+            }
+            Value::BytesValue(bytes) => map.serialize_entry("bytesValue", bytes)?,
+        }
+        map.end()
+    }
+}
+
+/// The `ArrayValue` and `KeyValueList` messages of OTLP, wrapping their values
+/// in a `values` field.
+#[derive(Serialize)]
+struct Values<'a, T> {
+    values: &'a [T],
+}
+
+/// Encodes `bytes` in standard, padded base64, as bytes are in OTLP JSON.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | u32::from(*byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (&self, &other) {
@@ -119,6 +226,9 @@ impl From<opentelemetry::Value> for Value {
             opentelemetry::Value::I64(i) => Value::Int(i),
             opentelemetry::Value::F64(f) => Value::Double(f),
             opentelemetry::Value::String(s) => Value::String(s.into()),
+            opentelemetry::Value::Duration(d) => {
+                Value::Int(i64::try_from(d.as_nanos()).unwrap_or(i64::MAX))
+            }
             opentelemetry::Value::Array(a) => match a {
                 opentelemetry::Array::Bool(b) => {
                     Value::Array(b.into_iter().map(Value::Bool).collect())
@@ -273,7 +383,40 @@ where
         .unwrap_or_default()
         .as_nanos();
 
-    serializer.serialize_u128(nanos)
+    // 64 bit integers are strings in OTLP JSON
+    if is_otlp_json(&()) {
+        serializer.serialize_str(&nanos.to_string())
+    } else {
+        serializer.serialize_u128(nanos)
+    }
+}
+
+/// Serializes a 64 bit integer, as a string in OTLP JSON.
+#[allow(dead_code)]
+pub(crate) fn as_int64<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ToString,
+    S: Serializer,
+{
+    if is_otlp_json(value) {
+        serializer.serialize_str(&value.to_string())
+    } else {
+        value.serialize(serializer)
+    }
+}
+
+/// Serializes 64 bit integers, as strings in OTLP JSON.
+#[allow(dead_code)]
+pub(crate) fn as_int64s<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ToString,
+    S: Serializer,
+{
+    if is_otlp_json(values) {
+        serializer.collect_seq(values.iter().map(ToString::to_string))
+    } else {
+        values.serialize(serializer)
+    }
 }
 
 #[allow(dead_code)]
@@ -54,6 +54,8 @@
 
 pub(crate) mod common;
 
+pub use common::OutputFormat;
+
 #[cfg(feature = "metrics")]
 mod metrics;
 #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
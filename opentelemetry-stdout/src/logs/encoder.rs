@@ -0,0 +1,551 @@
+use core::fmt;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{LocalResult, TimeZone, Utc};
+use opentelemetry::logs::{AnyValue, LogError, LogResult, Severity};
+use opentelemetry::Key;
+use opentelemetry_sdk::export::logs::LogData;
+use opentelemetry_sdk::Resource;
+
+use crate::common::{write_json, OutputFormat};
+
+/// Serializes a batch of log records into the output written by the stdout
+/// [`LogExporter`].
+///
+/// Encoders are responsible for writing complete output, including any line
+/// terminators, so that formats with one line per record can be supported.
+///
+/// [`LogExporter`]: crate::LogExporter
+pub trait LogEncoder: Send + Sync + fmt::Debug {
+    /// Write `batch`, emitted by entities described by `resource`, to `writer`.
+    fn encode(
+        &self,
+        writer: &mut dyn Write,
+        batch: &[LogData],
+        resource: &Resource,
+    ) -> LogResult<()>;
+}
+
+/// Encodes each batch as OTLP-JSON, by default on a single line.
+///
+/// This is the default encoder of the stdout [`LogExporter`].
+///
+/// [`LogExporter`]: crate::LogExporter
+#[derive(Debug, Default)]
+pub struct OtlpJsonEncoder {
+    format: OutputFormat,
+}
+
+impl OtlpJsonEncoder {
+    /// Create a new OTLP-JSON encoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [`OutputFormat`] of encoded batches, defaults to
+    /// [`OutputFormat::Compact`].
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl LogEncoder for OtlpJsonEncoder {
+    fn encode(
+        &self,
+        writer: &mut dyn Write,
+        batch: &[LogData],
+        resource: &Resource,
+    ) -> LogResult<()> {
+        let logs = crate::logs::transform::LogData::from((batch.to_vec(), resource));
+        write_json(&mut *writer, &logs, self.format)
+            .map_err(|err| LogError::Other(Box::new(err)))?;
+        writer.write_all(b"\n").map_err(io_error)
+    }
+}
+
+/// Encodes each log record as one line of [logfmt].
+///
+/// Every line starts with the `time`, `level` and `msg` keys, followed by the
+/// trace context and the record's attributes. Spaces, `=`, `"` and control
+/// characters in attribute keys are replaced with `_`.
+///
+/// [logfmt]: https://brandur.org/logfmt
+#[derive(Debug, Default)]
+pub struct LogfmtEncoder {
+    _private: (),
+}
+
+impl LogfmtEncoder {
+    /// Create a new logfmt encoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LogEncoder for LogfmtEncoder {
+    fn encode(
+        &self,
+        writer: &mut dyn Write,
+        batch: &[LogData],
+        _resource: &Resource,
+    ) -> LogResult<()> {
+        for log in batch {
+            let record = &log.record;
+            let mut line = String::new();
+
+            if let Some(time) = record.timestamp.or(record.observed_timestamp) {
+                push_logfmt_pair(&mut line, "time", &format_rfc3339(time));
+            }
+            if let Some(level) = record
+                .severity_text
+                .as_deref()
+                .or_else(|| record.severity_number.map(|s| s.name()))
+            {
+                push_logfmt_pair(&mut line, "level", level);
+            }
+            if let Some(body) = &record.body {
+                push_logfmt_pair(&mut line, "msg", &any_value_to_string(body));
+            }
+            if let Some(target) = &record.target {
+                push_logfmt_pair(&mut line, "target", target);
+            }
+            if let Some(trace_context) = &record.trace_context {
+                push_logfmt_pair(&mut line, "trace_id", &trace_context.trace_id.to_string());
+                push_logfmt_pair(&mut line, "span_id", &trace_context.span_id.to_string());
+            }
+            for (key, value) in record.attributes.iter().flatten() {
+                push_logfmt_pair(&mut line, key.as_str(), &any_value_to_string(value));
+            }
+
+            line.push('\n');
+            writer.write_all(line.as_bytes()).map_err(io_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes each log record as one line of [GELF] 1.1 JSON.
+///
+/// The `host` field is taken from the `host.name` resource attribute, falling
+/// back to `service.name`. Record attributes and the trace context are written
+/// as additional fields prefixed with `_`, replacing the characters GELF does
+/// not allow in field names with `_`.
+///
+/// [GELF]: https://go2docs.graylog.org/current/getting_in_log_data/gelf.html
+#[derive(Debug, Default)]
+pub struct GelfEncoder {
+    _private: (),
+}
+
+impl GelfEncoder {
+    /// Create a new GELF encoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LogEncoder for GelfEncoder {
+    fn encode(
+        &self,
+        writer: &mut dyn Write,
+        batch: &[LogData],
+        resource: &Resource,
+    ) -> LogResult<()> {
+        let host = resource
+            .get(Key::from_static_str("host.name"))
+            .or_else(|| resource.get(Key::from_static_str("service.name")))
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        for log in batch {
+            let record = &log.record;
+            let mut message = serde_json::Map::new();
+
+            message.insert("version".into(), "1.1".into());
+            message.insert("host".into(), host.clone().into());
+            message.insert(
+                "short_message".into(),
+                record
+                    .body
+                    .as_ref()
+                    .map(any_value_to_string)
+                    .unwrap_or_default()
+                    .into(),
+            );
+            if let Some(time) = record.timestamp.or(record.observed_timestamp) {
+                let secs = time
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                message.insert("timestamp".into(), secs.into());
+            }
+            if let Some(severity) = record.severity_number {
+                message.insert("level".into(), syslog_level(severity).into());
+            }
+            if let Some(target) = &record.target {
+                message.insert("_target".into(), target.to_string().into());
+            }
+            if let Some(trace_context) = &record.trace_context {
+                message.insert(
+                    "_trace_id".into(),
+                    trace_context.trace_id.to_string().into(),
+                );
+                message.insert("_span_id".into(), trace_context.span_id.to_string().into());
+            }
+            for (key, value) in record.attributes.iter().flatten() {
+                let name = gelf_field_name(key.as_str());
+                // `_id` is reserved by GELF
+                if name == "_id" {
+                    continue;
+                }
+                message.insert(name, any_value_to_json(value));
+            }
+
+            serde_json::to_writer(&mut *writer, &message)
+                .map_err(|err| LogError::Other(Box::new(err)))?;
+            writer.write_all(b"\n").map_err(io_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Adapts the closures accepted by [`LogExporterBuilder::with_encoder`].
+///
+/// [`LogExporterBuilder::with_encoder`]: crate::LogExporterBuilder::with_encoder
+pub(crate) struct FnEncoder<F>(pub(crate) F);
+
+impl<F> fmt::Debug for FnEncoder<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FnEncoder")
+    }
+}
+
+impl<F> LogEncoder for FnEncoder<F>
+where
+    F: Fn(&mut dyn Write, crate::logs::transform::LogData) -> LogResult<()> + Send + Sync,
+{
+    fn encode(
+        &self,
+        writer: &mut dyn Write,
+        batch: &[LogData],
+        resource: &Resource,
+    ) -> LogResult<()> {
+        let logs = crate::logs::transform::LogData::from((batch.to_vec(), resource));
+        (self.0)(writer, logs)?;
+        writer.write_all(b"\n").map_err(io_error)
+    }
+}
+
+fn io_error(err: std::io::Error) -> LogError {
+    LogError::Other(Box::new(err))
+}
+
+fn format_rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    match Utc.timestamp_opt(since_epoch.as_secs() as i64, since_epoch.subsec_nanos()) {
+        LocalResult::Single(datetime) => datetime.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+        _ => since_epoch.as_nanos().to_string(),
+    }
+}
+
+fn push_logfmt_pair(line: &mut String, key: &str, value: &str) {
+    if !line.is_empty() {
+        line.push(' ');
+    }
+    // keys can't be quoted
+    line.extend(key.chars().map(|c| {
+        if c == ' ' || c == '=' || c == '"' || c.is_control() {
+            '_'
+        } else {
+            c
+        }
+    }));
+    line.push('=');
+
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c.is_control());
+    if needs_quotes {
+        line.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => line.push_str("\\\""),
+                '\\' => line.push_str("\\\\"),
+                '\n' => line.push_str("\\n"),
+                '\r' => line.push_str("\\r"),
+                '\t' => line.push_str("\\t"),
+                c => line.push(c),
+            }
+        }
+        line.push('"');
+    } else {
+        line.push_str(value);
+    }
+}
+
+/// The name of the GELF additional field of an attribute, which must match
+/// `^[\w\.\-]*$` after its `_` prefix.
+fn gelf_field_name(key: &str) -> String {
+    let sanitized = key.chars().map(|c| match c {
+        'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' | '-' => c,
+        _ => '_',
+    });
+    std::iter::once('_').chain(sanitized).collect()
+}
+
+/// Map a severity to the syslog level used by GELF.
+fn syslog_level(severity: Severity) -> u8 {
+    match severity as u8 {
+        21..=24 => 2, // critical
+        17..=20 => 3, // error
+        13..=16 => 4, // warning
+        9..=12 => 6,  // informational
+        _ => 7,       // debug
+    }
+}
+
+fn any_value_to_string(value: &AnyValue) -> String {
+    match value {
+        AnyValue::String(s) => s.to_string(),
+        other => any_value_to_json(other).to_string(),
+    }
+}
+
+fn any_value_to_json(value: &AnyValue) -> serde_json::Value {
+    match value {
+        AnyValue::Int(i) => (*i).into(),
+        AnyValue::Double(d) => (*d).into(),
+        AnyValue::String(s) => s.to_string().into(),
+        AnyValue::Boolean(b) => (*b).into(),
+        AnyValue::Bytes(bytes) => bytes.clone().into(),
+        AnyValue::ListAny(values) => values.iter().map(any_value_to_json).collect(),
+        AnyValue::Map(map) => map
+            .iter()
+            .map(|(key, value)| (key.to_string(), any_value_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::logs::LogRecord as _;
+    use opentelemetry::trace::{SpanId, TraceId};
+    use opentelemetry::{InstrumentationLibrary, KeyValue};
+    use opentelemetry_sdk::logs::{LogRecord, TraceContext};
+    use std::time::Duration;
+
+    fn log_data() -> LogData {
+        let mut record = LogRecord::default();
+        record.set_timestamp(UNIX_EPOCH + Duration::from_millis(1_500));
+        record.set_observed_timestamp(UNIX_EPOCH + Duration::from_secs(2));
+        record.set_severity_number(Severity::Warn);
+        record.set_body("disk \"almost\" full".into());
+        record.set_target("app::disk");
+        record.add_attribute("mount point", "/var");
+        record.add_attribute("id", 7);
+        record.trace_context = Some(TraceContext::from(&opentelemetry::trace::SpanContext::new(
+            TraceId::from_bytes(1u128.to_be_bytes()),
+            SpanId::from_bytes(2u64.to_be_bytes()),
+            Default::default(),
+            false,
+            Default::default(),
+        )));
+        LogData {
+            record,
+            instrumentation: InstrumentationLibrary::builder("test").build(),
+            resource: None,
+        }
+    }
+
+    fn encode(encoder: &dyn LogEncoder) -> String {
+        encode_batch(encoder, &[log_data()])
+    }
+
+    fn encode_batch(encoder: &dyn LogEncoder, batch: &[LogData]) -> String {
+        let mut output = Vec::new();
+        let resource = Resource::new([KeyValue::new("host.name", "web-1")]);
+        encoder.encode(&mut output, batch, &resource).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// A record with attributes of each kind of value.
+    fn log_data_with_values() -> LogData {
+        let mut data = log_data();
+        data.record.attributes = None;
+        data.record.add_attribute("count", 1_i64 << 60);
+        data.record.add_attribute("ratio", 0.5);
+        data.record
+            .add_attribute("payload", AnyValue::Bytes(b"otel".to_vec()));
+        data.record.add_attribute(
+            "tags",
+            AnyValue::ListAny(vec![AnyValue::Int(1), AnyValue::Boolean(true)]),
+        );
+        data.record.add_attribute(
+            "user",
+            AnyValue::Map([(Key::from("id"), AnyValue::Int(7))].into_iter().collect()),
+        );
+        data
+    }
+
+    #[test]
+    fn otlp_json_pretty() {
+        let encoder = OtlpJsonEncoder::new().with_format(OutputFormat::Pretty);
+        assert_eq!(
+            encode(&encoder),
+            r#"{
+  "resourceLogs": [
+    {
+      "resource": {
+        "attributes": [
+          {
+            "key": "host.name",
+            "value": {
+              "stringValue": "web-1"
+            }
+          }
+        ]
+      },
+      "scopeLogs": [
+        {
+          "scope": {
+            "name": "test"
+          },
+          "logRecords": [
+            {
+              "timeUnixNano": 1500000000,
+              "time": "1970-01-01 00:00:01.500",
+              "observedTimeUnixNano": 2000000000,
+              "observedTime": "1970-01-01 00:00:02.000",
+              "severityNumber": 13,
+              "body": {
+                "stringValue": "disk \"almost\" full"
+              },
+              "attributes": [
+                {
+                  "key": "mount point",
+                  "value": {
+                    "stringValue": "/var"
+                  }
+                },
+                {
+                  "key": "id",
+                  "value": {
+                    "intValue": 7
+                  }
+                }
+              ],
+              "droppedAttributesCount": 0,
+              "flags": 0,
+              "spanId": "0000000000000002",
+              "traceId": "00000000000000000000000000000001"
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn otlp_json_compact() {
+        let encoder = OtlpJsonEncoder::new().with_format(OutputFormat::Compact);
+        assert_eq!(
+            encode_batch(&encoder, &[log_data_with_values()]),
+            concat!(
+                r#"{"resourceLogs":[{"resource":{"attributes":[{"key":"host.name","value":{"stringValue":"web-1"}}]},"#,
+                r#""scopeLogs":[{"scope":{"name":"test"},"logRecords":[{"#,
+                r#""timeUnixNano":1500000000,"time":"1970-01-01 00:00:01.500","#,
+                r#""observedTimeUnixNano":2000000000,"observedTime":"1970-01-01 00:00:02.000","#,
+                r#""severityNumber":13,"body":{"stringValue":"disk \"almost\" full"},"attributes":["#,
+                r#"{"key":"count","value":{"intValue":1152921504606846976}},"#,
+                r#"{"key":"ratio","value":{"doubleValue":0.5}},"#,
+                r#"{"key":"payload","value":{"bytesValue":[111,116,101,108]}},"#,
+                r#"{"key":"tags","value":{"arrayValue":[{"intValue":1},{"boolValue":true}]}},"#,
+                r#"{"key":"user","value":{"kvListValue":[{"key":"id","value":{"intValue":7}}]}}],"#,
+                r#""droppedAttributesCount":0,"flags":0,"spanId":"0000000000000002","#,
+                r#""traceId":"00000000000000000000000000000001"}]}]}]}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn otlp_json_json() {
+        let encoder = OtlpJsonEncoder::new().with_format(OutputFormat::Json);
+        assert_eq!(
+            encode_batch(&encoder, &[log_data_with_values()]),
+            concat!(
+                r#"{"resourceLogs":[{"resource":{"attributes":[{"key":"host.name","value":{"stringValue":"web-1"}}]},"#,
+                r#""scopeLogs":[{"scope":{"name":"test"},"logRecords":[{"#,
+                r#""timeUnixNano":"1500000000","observedTimeUnixNano":"2000000000","#,
+                r#""severityNumber":13,"body":{"stringValue":"disk \"almost\" full"},"attributes":["#,
+                r#"{"key":"count","value":{"intValue":"1152921504606846976"}},"#,
+                r#"{"key":"ratio","value":{"doubleValue":0.5}},"#,
+                r#"{"key":"payload","value":{"bytesValue":"b3RlbA=="}},"#,
+                r#"{"key":"tags","value":{"arrayValue":{"values":[{"intValue":"1"},{"boolValue":true}]}}},"#,
+                r#"{"key":"user","value":{"kvlistValue":{"values":[{"key":"id","value":{"intValue":"7"}}]}}}],"#,
+                r#""droppedAttributesCount":0,"flags":0,"spanId":"0000000000000002","#,
+                r#""traceId":"00000000000000000000000000000001"}]}]}]}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn logfmt() {
+        assert_eq!(
+            encode(&LogfmtEncoder::new()),
+            "time=1970-01-01T00:00:01.500000Z level=WARN msg=\"disk \\\"almost\\\" full\" \
+             target=app::disk trace_id=00000000000000000000000000000001 \
+             span_id=0000000000000002 mount_point=/var id=7\n"
+        );
+    }
+
+    #[test]
+    fn gelf() {
+        let output = encode(&GelfEncoder::new());
+        assert!(output.ends_with('\n'));
+        let message: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            message,
+            serde_json::json!({
+                "version": "1.1",
+                "host": "web-1",
+                "short_message": "disk \"almost\" full",
+                "timestamp": 1.5,
+                "level": 4,
+                "_target": "app::disk",
+                "_trace_id": "00000000000000000000000000000001",
+                "_span_id": "0000000000000002",
+                "_mount_point": "/var",
+            })
+        );
+    }
+
+    #[test]
+    fn gelf_field_names() {
+        assert_eq!(gelf_field_name("http.status_code"), "_http.status_code");
+        assert_eq!(gelf_field_name("user-agent"), "_user-agent");
+        assert_eq!(gelf_field_name("a b/c:é"), "_a_b_c__");
+    }
+
+    #[test]
+    fn otlp_json() {
+        let output = encode(&OtlpJsonEncoder::new());
+        assert_eq!(output.lines().count(), 1);
+        let logs: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let record = &logs["resourceLogs"][0]["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(record["severityNumber"], 13);
+        assert_eq!(record["body"]["stringValue"], "disk \"almost\" full");
+    }
+}
@@ -1,17 +1,13 @@
+use crate::common::OutputFormat;
+use crate::logs::encoder::{FnEncoder, LogEncoder, OtlpJsonEncoder};
 use async_trait::async_trait;
 use core::fmt;
-use opentelemetry::{
-    logs::{LogError, LogResult},
-    ExportError,
-};
+use opentelemetry::{logs::LogResult, ExportError};
 use opentelemetry_sdk::export::logs::{ExportResult, LogData};
 use opentelemetry_sdk::Resource;
 use std::borrow::Cow;
 use std::io::{stdout, Write};
 
-type Encoder =
-    Box<dyn Fn(&mut dyn Write, crate::logs::transform::LogData) -> LogResult<()> + Send + Sync>;
-
 /// A [`LogExporter`] that writes to [`Stdout`] or other configured [`Write`].
 ///
 /// [`LogExporter`]: opentelemetry_sdk::export::logs::LogExporter
@@ -19,7 +15,7 @@ type Encoder =
 /// [`Stdout`]: std::io::Stdout
 pub struct LogExporter {
     writer: Option<Box<dyn Write + Send + Sync>>,
-    encoder: Encoder,
+    encoder: Box<dyn LogEncoder>,
     resource: Resource,
 }
 
@@ -48,12 +44,10 @@ impl opentelemetry_sdk::export::logs::LogExporter for LogExporter {
     async fn export<'a>(&mut self, batch: Vec<Cow<'a, LogData>>) -> ExportResult {
         if let Some(writer) = &mut self.writer {
             // TODO - Avoid cloning logdata if it is borrowed.
-            let log_data = crate::logs::transform::LogData::from((
-                batch.into_iter().map(Cow::into_owned).collect(),
-                &self.resource,
-            ));
-            let result = (self.encoder)(writer, log_data) as LogResult<()>;
-            result.and_then(|_| writer.write_all(b"\n").map_err(|e| Error(e).into()))
+            let batch: Vec<LogData> = batch.into_iter().map(Cow::into_owned).collect();
+            self.encoder
+                .encode(writer, &batch, &self.resource)
+                .and_then(|_| writer.flush().map_err(|e| Error(e).into()))
         } else {
             Err("exporter is shut down".into())
         }
@@ -83,7 +77,7 @@ impl ExportError for Error {
 #[derive(Default)]
 pub struct LogExporterBuilder {
     writer: Option<Box<dyn Write + Send + Sync>>,
-    encoder: Option<Encoder>,
+    encoder: Option<Box<dyn LogEncoder>>,
 }
 
 impl fmt::Debug for LogExporterBuilder {
@@ -131,21 +125,51 @@ impl LogExporterBuilder {
             + Sync
             + 'static,
     {
+        self.encoder = Some(Box::new(FnEncoder(encoder)));
+        self
+    }
+
+    /// Set the [`LogEncoder`] that the exporter will use to serialize log
+    /// records, e.g. to emit logfmt or GELF instead of the default OTLP-JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_stdout::{GelfEncoder, LogExporterBuilder};
+    ///
+    /// let exporter = LogExporterBuilder::default()
+    ///     .with_log_encoder(GelfEncoder::new())
+    ///     .build();
+    /// ```
+    pub fn with_log_encoder<E: LogEncoder + 'static>(mut self, encoder: E) -> Self {
         self.encoder = Some(Box::new(encoder));
         self
     }
 
+    /// Set the [`OutputFormat`] of exported logs, replacing any encoder set
+    /// before. Defaults to [`OutputFormat::Compact`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_stdout::{LogExporterBuilder, OutputFormat};
+    ///
+    /// let exporter = LogExporterBuilder::default()
+    ///     .with_output_format(OutputFormat::Json)
+    ///     .build();
+    /// ```
+    pub fn with_output_format(self, format: OutputFormat) -> Self {
+        self.with_log_encoder(OtlpJsonEncoder::new().with_format(format))
+    }
+
     /// Create a log exporter with the current configuration.
     pub fn build(self) -> LogExporter {
         LogExporter {
             writer: Some(self.writer.unwrap_or_else(|| Box::new(stdout()))),
             resource: Resource::default(),
-            encoder: self.encoder.unwrap_or_else(|| {
-                Box::new(|writer, logs| {
-                    serde_json::to_writer(writer, &logs)
-                        .map_err(|err| LogError::Other(Box::new(err)))
-                })
-            }),
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| Box::new(OtlpJsonEncoder::new())),
         }
     }
 }
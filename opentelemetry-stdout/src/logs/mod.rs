@@ -8,8 +8,10 @@
 //! [`Write`]: std::io::Write
 //! [`Stdout`]: std::io::Stdout
 // TODO: Add an example for using this exporter.
+mod encoder;
 mod exporter;
 mod transform;
 
+pub use encoder::{GelfEncoder, LogEncoder, LogfmtEncoder, OtlpJsonEncoder};
 pub use exporter::*;
 pub use transform::*;
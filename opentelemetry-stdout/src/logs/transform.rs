@@ -1,8 +1,8 @@
 use std::{borrow::Cow, collections::HashMap, time::SystemTime};
 
 use crate::common::{
-    as_human_readable, as_opt_human_readable, as_opt_unix_nano, as_unix_nano, AttributeSet,
-    KeyValue, Resource, Scope, Value,
+    as_human_readable, as_opt_human_readable, as_opt_unix_nano, as_unix_nano, is_otlp_json,
+    AttributeSet, KeyValue, Resource, Scope, Value,
 };
 use serde::Serialize;
 
@@ -29,6 +29,8 @@ impl
         let mut resource_logs = HashMap::<AttributeSet, ResourceLogs>::new();
 
         for sdk_log in sdk_logs {
+            let log_resource = sdk_log.resource.clone();
+            let sdk_resource = log_resource.as_ref().unwrap_or(sdk_resource);
             let resource_schema_url = sdk_resource.schema_url().map(|s| s.to_string().into());
             let schema_url = sdk_log.instrumentation.schema_url.clone();
             let scope: Scope = sdk_log.instrumentation.clone().into();
@@ -42,7 +44,11 @@ impl
                     schema_url: resource_schema_url,
                 });
 
-            match rl.scope_logs.iter_mut().find(|sl| sl.scope == scope) {
+            match rl
+                .scope_logs
+                .iter_mut()
+                .find(|sl| sl.scope == scope && sl.schema_url == schema_url)
+            {
                 Some(sl) => sl.log_records.push(sdk_log.into()),
                 None => rl.scope_logs.push(ScopeLogs {
                     scope,
@@ -83,11 +89,17 @@ struct ScopeLogs {
 struct LogRecord {
     #[serde(serialize_with = "as_opt_unix_nano")]
     time_unix_nano: Option<SystemTime>,
-    #[serde(serialize_with = "as_opt_human_readable")]
+    #[serde(
+        serialize_with = "as_opt_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     time: Option<SystemTime>,
     #[serde(serialize_with = "as_unix_nano")]
     observed_time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     observed_time: SystemTime,
     severity_number: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -107,16 +119,8 @@ struct LogRecord {
 impl From<opentelemetry_sdk::export::logs::LogData> for LogRecord {
     fn from(value: opentelemetry_sdk::export::logs::LogData) -> Self {
         LogRecord {
-            trace_id: value
-                .record
-                .trace_context
-                .as_ref()
-                .map(|c| c.trace_id.to_string()),
-            span_id: value
-                .record
-                .trace_context
-                .as_ref()
-                .map(|c| c.span_id.to_string()),
+            trace_id: value.record.trace_context.map(|c| c.trace_id.to_string()),
+            span_id: value.record.trace_context.map(|c| c.span_id.to_string()),
             flags: value
                 .record
                 .trace_context
@@ -141,7 +145,7 @@ impl From<opentelemetry_sdk::export::logs::LogData> for LogRecord {
                         .collect()
                 })
                 .unwrap_or_default(),
-            dropped_attributes_count: 0,
+            dropped_attributes_count: value.record.dropped_attributes_count,
             severity_text: value.record.severity_text,
             body: value.record.body.map(|a| a.into()),
         }
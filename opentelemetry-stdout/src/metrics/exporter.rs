@@ -15,6 +15,7 @@ use std::{
     sync::Mutex,
 };
 
+use crate::common::{write_json, OutputFormat};
 use crate::MetricsData;
 
 type Encoder = Box<dyn Fn(&mut dyn Write, MetricsData) -> Result<()> + Send + Sync>;
@@ -127,6 +128,25 @@ impl MetricsExporterBuilder {
         self
     }
 
+    /// Set the [`OutputFormat`] of exported metrics, replacing any encoder set
+    /// with [`with_encoder`]. Defaults to [`OutputFormat::Compact`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_stdout::{MetricsExporterBuilder, OutputFormat};
+    ///
+    /// let exporter = MetricsExporterBuilder::default()
+    ///     .with_output_format(OutputFormat::Pretty)
+    ///     .build();
+    /// ```
+    ///
+    /// [`with_encoder`]: MetricsExporterBuilder::with_encoder
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.encoder = Some(json_encoder(format));
+        self
+    }
+
     /// Set the temporality exporter for the exporter
     pub fn with_temporality_selector(
         mut self,
@@ -149,12 +169,9 @@ impl MetricsExporterBuilder {
     pub fn build(self) -> MetricsExporter {
         MetricsExporter {
             writer: Mutex::new(Some(self.writer.unwrap_or_else(|| Box::new(stdout())))),
-            encoder: self.encoder.unwrap_or_else(|| {
-                Box::new(|writer, metrics| {
-                    serde_json::to_writer(writer, &metrics)
-                        .map_err(|err| MetricsError::Other(err.to_string()))
-                })
-            }),
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| json_encoder(OutputFormat::default())),
             temporality_selector: self
                 .temporality_selector
                 .unwrap_or_else(|| Box::new(DefaultTemporalitySelector::new())),
@@ -165,6 +182,12 @@ impl MetricsExporterBuilder {
     }
 }
 
+fn json_encoder(format: OutputFormat) -> Encoder {
+    Box::new(move |writer, metrics| {
+        write_json(writer, &metrics, format).map_err(|err| MetricsError::Other(err.to_string()))
+    })
+}
+
 impl fmt::Debug for MetricsExporterBuilder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("MetricsExporterBuilder")
@@ -1,10 +1,13 @@
 use crate::common::{KeyValue, Resource, Scope};
 use opentelemetry::{global, metrics::MetricsError};
 use opentelemetry_sdk::metrics::data;
-use serde::{Serialize, Serializer};
+use serde::{ser::SerializeMap, Serialize, Serializer};
 use std::{any::Any, borrow::Cow, time::SystemTime};
 
-use crate::common::{as_human_readable, as_opt_human_readable, as_opt_unix_nano, as_unix_nano};
+use crate::common::{
+    as_human_readable, as_int64, as_int64s, as_opt_human_readable, as_opt_unix_nano, as_unix_nano,
+    is_otlp_json,
+};
 
 /// Transformed metrics data that can be serialized
 #[derive(Serialize, Debug, Clone)]
@@ -131,6 +134,30 @@ enum DataValue {
     U64(u64),
 }
 
+/// The value of a data point or exemplar, written in the `value` field, or in
+/// the `asDouble` or `asInt` field of OTLP JSON.
+#[derive(Debug, Clone)]
+struct NumberValue(DataValue);
+
+impl Serialize for NumberValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        if is_otlp_json(self) {
+            match self.0 {
+                DataValue::F64(value) => map.serialize_entry("asDouble", &value)?,
+                DataValue::I64(value) => map.serialize_entry("asInt", &value.to_string())?,
+                DataValue::U64(value) => map.serialize_entry("asInt", &value.to_string())?,
+            }
+        } else {
+            map.serialize_entry("value", &self.0)?;
+        }
+        map.end()
+    }
+}
+
 impl From<f64> for DataValue {
     fn from(value: f64) -> Self {
         DataValue::F64(value)
@@ -176,6 +203,10 @@ impl Serialize for Temporality {
     where
         S: Serializer,
     {
+        // enums are integers in OTLP JSON
+        if is_otlp_json(self) {
+            return serializer.serialize_u8(*self as u8);
+        }
         match &self {
             Temporality::Cumulative => serializer.serialize_str("Cumulative"),
             Temporality::Delta => serializer.serialize_str("Delta"),
@@ -216,15 +247,22 @@ impl<T: Into<DataValue> + Copy> From<&data::Sum<T>> for Sum {
 #[serde(rename_all = "camelCase")]
 struct DataPoint {
     attributes: Vec<KeyValue>,
-    #[serde(serialize_with = "as_opt_human_readable")]
+    #[serde(
+        serialize_with = "as_opt_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     start_time: Option<SystemTime>,
-    #[serde(serialize_with = "as_opt_human_readable")]
+    #[serde(
+        serialize_with = "as_opt_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     time: Option<SystemTime>,
     #[serde(serialize_with = "as_opt_unix_nano")]
     start_time_unix_nano: Option<SystemTime>,
     #[serde(serialize_with = "as_opt_unix_nano")]
     time_unix_nano: Option<SystemTime>,
-    value: DataValue,
+    #[serde(flatten)]
+    value: NumberValue,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     exemplars: Vec<Exemplar>,
     #[serde(skip_serializing_if = "is_zero_u8")]
@@ -243,7 +281,7 @@ impl<T: Into<DataValue> + Copy> From<&data::DataPoint<T>> for DataPoint {
             time_unix_nano: value.time,
             start_time: value.start_time,
             time: value.time,
-            value: value.value.into(),
+            value: NumberValue(value.value.into()),
             exemplars: value.exemplars.iter().map(Into::into).collect(),
             flags: 0,
         }
@@ -274,12 +312,20 @@ struct HistogramDataPoint {
     start_time_unix_nano: SystemTime,
     #[serde(serialize_with = "as_unix_nano")]
     time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     start_time: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     time: SystemTime,
+    #[serde(serialize_with = "as_int64")]
     count: u64,
     explicit_bounds: Vec<f64>,
+    #[serde(serialize_with = "as_int64s")]
     bucket_counts: Vec<u64>,
     min: Option<DataValue>,
     max: Option<DataValue>,
@@ -331,15 +377,23 @@ struct ExponentialHistogramDataPoint {
     start_time_unix_nano: SystemTime,
     #[serde(serialize_with = "as_unix_nano")]
     time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     start_time: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     time: SystemTime,
+    #[serde(serialize_with = "as_int64")]
     count: usize,
     min: Option<DataValue>,
     max: Option<DataValue>,
     sum: DataValue,
     scale: i8,
+    #[serde(serialize_with = "as_int64")]
     zero_count: u64,
     positive: ExponentialBucket,
     negative: ExponentialBucket,
@@ -385,6 +439,7 @@ impl From<&data::ExponentialBucket> for ExponentialBucket {
 #[derive(Serialize, Debug, Clone)]
 struct ExponentialBucket {
     offset: i32,
+    #[serde(serialize_with = "as_int64s")]
     bucket_counts: Vec<u64>,
 }
 
@@ -394,9 +449,13 @@ struct Exemplar {
     filtered_attributes: Vec<KeyValue>,
     #[serde(serialize_with = "as_unix_nano")]
     time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     time: SystemTime,
-    value: DataValue,
+    #[serde(flatten)]
+    value: NumberValue,
     span_id: String,
     trace_id: String,
 }
@@ -407,9 +466,70 @@ impl<T: Into<DataValue> + Copy> From<&data::Exemplar<T>> for Exemplar {
             filtered_attributes: value.filtered_attributes.iter().map(Into::into).collect(),
             time_unix_nano: value.time,
             time: value.time,
-            value: value.value.into(),
+            value: NumberValue(value.value.into()),
             span_id: format!("{:016x}", u64::from_be_bytes(value.span_id)),
             trace_id: format!("{:032x}", u128::from_be_bytes(value.trace_id)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{write_json, OutputFormat};
+    use opentelemetry_sdk::metrics::data::Temporality;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn histogram() -> Histogram {
+        Histogram::from(&data::Histogram {
+            data_points: vec![data::HistogramDataPoint {
+                attributes: vec![opentelemetry::KeyValue::new("route", "/")],
+                start_time: UNIX_EPOCH + Duration::from_secs(1),
+                time: UNIX_EPOCH + Duration::from_secs(2),
+                count: 3,
+                bounds: vec![1.0],
+                bucket_counts: vec![1, 2],
+                min: Some(0.5),
+                max: Some(4.0),
+                sum: 6.5,
+                exemplars: vec![],
+            }],
+            temporality: Temporality::Delta,
+        })
+    }
+
+    fn encode(format: OutputFormat) -> String {
+        let mut output = Vec::new();
+        write_json(&mut output, &histogram(), format).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn histogram_compact() {
+        assert_eq!(
+            encode(OutputFormat::Compact),
+            concat!(
+                r#"{"dataPoints":[{"attributes":[{"key":"route","value":{"stringValue":"/"}}],"#,
+                r#""startTimeUnixNano":1000000000,"timeUnixNano":2000000000,"#,
+                r#""startTime":"1970-01-01 00:00:01.000","time":"1970-01-01 00:00:02.000","#,
+                r#""count":3,"explicitBounds":[1.0],"bucketCounts":[1,2],"#,
+                r#""min":0.5,"max":4.0,"sum":6.5,"exemplars":[],"flags":0}],"#,
+                r#""aggregationTemporality":"Delta"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn histogram_json() {
+        assert_eq!(
+            encode(OutputFormat::Json),
+            concat!(
+                r#"{"dataPoints":[{"attributes":[{"key":"route","value":{"stringValue":"/"}}],"#,
+                r#""startTimeUnixNano":"1000000000","timeUnixNano":"2000000000","#,
+                r#""count":"3","explicitBounds":[1.0],"bucketCounts":["1","2"],"#,
+                r#""min":0.5,"max":4.0,"sum":6.5,"exemplars":[],"flags":0}],"#,
+                r#""aggregationTemporality":1}"#
+            )
+        );
+    }
+}
@@ -4,6 +4,7 @@ use opentelemetry::trace::{TraceError, TraceResult};
 use opentelemetry_sdk::export::{self, trace::ExportResult};
 use std::io::{stdout, Write};
 
+use crate::common::{write_json, OutputFormat};
 use crate::trace::transform::SpanData;
 use opentelemetry_sdk::resource::Resource;
 
@@ -14,6 +15,7 @@ pub struct SpanExporter {
     writer: Option<Box<dyn Write + Send + Sync>>,
     encoder: Encoder,
     resource: Resource,
+    header: Option<String>,
 }
 
 impl fmt::Debug for SpanExporter {
@@ -38,13 +40,15 @@ impl Default for SpanExporter {
 impl opentelemetry_sdk::export::trace::SpanExporter for SpanExporter {
     fn export(&mut self, batch: Vec<export::trace::SpanData>) -> BoxFuture<'static, ExportResult> {
         let res = if let Some(writer) = &mut self.writer {
-            (self.encoder)(writer, crate::trace::SpanData::new(batch, &self.resource)).and_then(
-                |_| {
-                    writer
-                        .write_all(b"\n")
-                        .map_err(|err| TraceError::Other(Box::new(err)))
-                },
-            )
+            write_header(writer, self.header.take()).and_then(|_| {
+                (self.encoder)(writer, crate::trace::SpanData::new(batch, &self.resource)).and_then(
+                    |_| {
+                        writer
+                            .write_all(b"\n")
+                            .map_err(|err| TraceError::Other(Box::new(err)))
+                    },
+                )
+            })
         } else {
             Err("exporter is shut down".into())
         };
@@ -61,11 +65,24 @@ impl opentelemetry_sdk::export::trace::SpanExporter for SpanExporter {
     }
 }
 
+/// Writes the header line once, before the first exported batch.
+fn write_header(writer: &mut dyn Write, header: Option<String>) -> TraceResult<()> {
+    let Some(sampler) = header else {
+        return Ok(());
+    };
+    serde_json::to_writer(&mut *writer, &serde_json::json!({ "sampler": sampler }))
+        .map_err(|err| TraceError::Other(Box::new(err)))?;
+    writer
+        .write_all(b"\n")
+        .map_err(|err| TraceError::Other(Box::new(err)))
+}
+
 /// Configuration for the stdout trace exporter
 #[derive(Default)]
 pub struct SpanExporterBuilder {
     writer: Option<Box<dyn Write + Send + Sync>>,
     encoder: Option<Encoder>,
+    sampler_description: Option<String>,
 }
 
 impl fmt::Debug for SpanExporterBuilder {
@@ -111,17 +128,59 @@ impl SpanExporterBuilder {
         self
     }
 
+    /// Set the [`OutputFormat`] of exported spans, replacing any encoder set
+    /// with [`with_encoder`]. Defaults to [`OutputFormat::Compact`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_stdout::{OutputFormat, SpanExporterBuilder};
+    ///
+    /// let exporter = SpanExporterBuilder::default()
+    ///     .with_output_format(OutputFormat::Json)
+    ///     .build();
+    /// ```
+    ///
+    /// [`with_encoder`]: SpanExporterBuilder::with_encoder
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.encoder = Some(json_encoder(format));
+        self
+    }
+
+    /// Write a header line describing the active sampler before the first
+    /// exported batch, e.g. `{"sampler":"TraceIdRatioBased{0.1}"}`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_sdk::trace::{Sampler, ShouldSample};
+    /// use opentelemetry_stdout::SpanExporterBuilder;
+    ///
+    /// let sampler = Sampler::TraceIdRatioBased(0.1);
+    /// let exporter = SpanExporterBuilder::default()
+    ///     .with_sampler_description(sampler.description())
+    ///     .build();
+    /// ```
+    pub fn with_sampler_description(mut self, description: impl Into<String>) -> Self {
+        self.sampler_description = Some(description.into());
+        self
+    }
+
     /// Create a span exporter with the current configuration
     pub fn build(self) -> SpanExporter {
         SpanExporter {
             writer: Some(self.writer.unwrap_or_else(|| Box::new(stdout()))),
             resource: Resource::empty(),
-            encoder: self.encoder.unwrap_or_else(|| {
-                Box::new(|writer, spans| {
-                    serde_json::to_writer(writer, &spans)
-                        .map_err(|err| TraceError::Other(Box::new(err)))
-                })
-            }),
+            header: self.sampler_description,
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| json_encoder(OutputFormat::default())),
         }
     }
 }
+
+fn json_encoder(format: OutputFormat) -> Encoder {
+    Box::new(move |writer, spans| {
+        write_json(writer, &spans, format).map_err(|err| TraceError::Other(Box::new(err)))
+    })
+}
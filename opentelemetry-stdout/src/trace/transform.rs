@@ -1,4 +1,6 @@
-use crate::common::{as_human_readable, as_unix_nano, AttributeSet, KeyValue, Resource, Scope};
+use crate::common::{
+    as_human_readable, as_unix_nano, is_otlp_json, AttributeSet, KeyValue, Resource, Scope,
+};
 use serde::{Serialize, Serializer};
 use std::{borrow::Cow, collections::HashMap, time::SystemTime};
 
@@ -16,6 +18,8 @@ impl SpanData {
     ) -> Self {
         let mut resource_spans = HashMap::<AttributeSet, ResourceSpans>::new();
         for sdk_span in sdk_spans {
+            let span_resource = sdk_span.resource.clone();
+            let sdk_resource = span_resource.as_ref().unwrap_or(sdk_resource);
             let resource_schema_url = sdk_resource.schema_url().map(|s| s.to_string().into());
             let schema_url = sdk_span.instrumentation_lib.schema_url.clone();
             let scope = sdk_span.instrumentation_lib.clone().into();
@@ -29,7 +33,11 @@ impl SpanData {
                     schema_url: resource_schema_url,
                 });
 
-            match rs.scope_spans.iter_mut().find(|ss| ss.scope == scope) {
+            match rs
+                .scope_spans
+                .iter_mut()
+                .find(|ss| ss.scope == scope && ss.schema_url == schema_url)
+            {
                 Some(ss) => ss.spans.push(sdk_span.into()),
                 None => rs.scope_spans.push(ScopeSpans {
                     scope,
@@ -75,11 +83,17 @@ struct Span {
     kind: SpanKind,
     #[serde(serialize_with = "as_unix_nano")]
     start_time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     start_time: SystemTime,
     #[serde(serialize_with = "as_unix_nano")]
     end_time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     end_time: SystemTime,
     attributes: Vec<KeyValue>,
     dropped_attributes_count: u32,
@@ -160,7 +174,10 @@ struct Event {
     dropped_attributes_count: u32,
     #[serde(serialize_with = "as_unix_nano")]
     time_unix_nano: SystemTime,
-    #[serde(serialize_with = "as_human_readable")]
+    #[serde(
+        serialize_with = "as_human_readable",
+        skip_serializing_if = "is_otlp_json"
+    )]
     time: SystemTime,
 }
 
@@ -166,6 +166,7 @@ mod tests {
                 links: SpanLinks::default(),
                 status,
                 instrumentation_lib: Default::default(),
+                resource: None,
             };
             let local_endpoint = Endpoint::new("test".into(), None);
             let span = into_zipkin_span(local_endpoint, span_data);
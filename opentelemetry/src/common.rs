@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fmt, hash};
 
 /// The key part of attribute [KeyValue] pairs.
@@ -28,8 +29,20 @@ impl Key {
     }
 
     /// Create a new const `Key`.
+    ///
+    /// The hash returned by [`Key::name_hash`] is computed once when the key is
+    /// created, so declaring frequently used keys as constants avoids hashing
+    /// their name every time they are recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::Key;
+    ///
+    /// const HTTP_METHOD: Key = Key::from_static_str("http.request.method");
+    /// ```
     pub const fn from_static_str(value: &'static str) -> Self {
-        Key(OtelString::Static(value))
+        Key(OtelString::Prehashed(value, fnv1a_hash(value)))
     }
 
     /// Create a `KeyValue` pair for `bool` values.
@@ -76,6 +89,35 @@ impl Key {
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// Returns a hash of the key name.
+    ///
+    /// Equal keys always have the same hash. The hash is precomputed for keys
+    /// created with [`Key::from_static_str`] and computed on each call
+    /// otherwise. It is not randomized and must not be relied upon where
+    /// resistance against collisions is required.
+    pub fn name_hash(&self) -> u64 {
+        match &self.0 {
+            OtelString::Prehashed(_, hash) => *hash,
+            other => fnv1a_hash(other.as_str()),
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash, usable in const contexts.
+const fn fnv1a_hash(value: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let bytes = value.as_bytes();
+    let mut hash = OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(PRIME);
+        i += 1;
+    }
+    hash
 }
 
 impl From<&'static str> for Key {
@@ -119,7 +161,7 @@ impl From<Key> for String {
     fn from(key: Key) -> Self {
         match key.0 {
             OtelString::Owned(s) => s.to_string(),
-            OtelString::Static(s) => s.to_string(),
+            OtelString::Static(s) | OtelString::Prehashed(s, _) => s.to_string(),
             OtelString::RefCounted(s) => s.to_string(),
         }
     }
@@ -129,16 +171,18 @@ impl fmt::Display for Key {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
             OtelString::Owned(s) => s.fmt(fmt),
-            OtelString::Static(s) => s.fmt(fmt),
+            OtelString::Static(s) | OtelString::Prehashed(s, _) => s.fmt(fmt),
             OtelString::RefCounted(s) => s.fmt(fmt),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq)]
+#[derive(Clone, Eq)]
 enum OtelString {
     Owned(Box<str>),
     Static(&'static str),
+    /// A static string along with the hash of its content.
+    Prehashed(&'static str, u64),
     RefCounted(Arc<str>),
 }
 
@@ -146,12 +190,24 @@ impl OtelString {
     fn as_str(&self) -> &str {
         match self {
             OtelString::Owned(s) => s.as_ref(),
-            OtelString::Static(s) => s,
+            OtelString::Static(s) | OtelString::Prehashed(s, _) => s,
             OtelString::RefCounted(s) => s.as_ref(),
         }
     }
 }
 
+impl fmt::Debug for OtelString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OtelString::Owned(s) => f.debug_tuple("Owned").field(s).finish(),
+            OtelString::Static(s) | OtelString::Prehashed(s, _) => {
+                f.debug_tuple("Static").field(s).finish()
+            }
+            OtelString::RefCounted(s) => f.debug_tuple("RefCounted").field(s).finish(),
+        }
+    }
+}
+
 impl PartialOrd for OtelString {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -252,6 +308,12 @@ pub enum Value {
     String(StringValue),
     /// Array of homogeneous values
     Array(Array),
+    /// Duration values, such as latencies and timeouts
+    ///
+    /// Exporters encode durations as an integer number of nanoseconds,
+    /// saturating at `i64::MAX`. Where only strings are supported the unit is
+    /// appended, e.g. `1500000ns`.
+    Duration(Duration),
 }
 
 /// Wrapper for string-like values
@@ -268,7 +330,7 @@ impl fmt::Display for StringValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
             OtelString::Owned(s) => s.fmt(f),
-            OtelString::Static(s) => s.fmt(f),
+            OtelString::Static(s) | OtelString::Prehashed(s, _) => s.fmt(f),
             OtelString::RefCounted(s) => s.fmt(f),
         }
     }
@@ -291,7 +353,7 @@ impl From<StringValue> for String {
     fn from(s: StringValue) -> Self {
         match s.0 {
             OtelString::Owned(s) => s.to_string(),
-            OtelString::Static(s) => s.to_string(),
+            OtelString::Static(s) | OtelString::Prehashed(s, _) => s.to_string(),
             OtelString::RefCounted(s) => s.to_string(),
         }
     }
@@ -335,8 +397,32 @@ impl Value {
             Value::F64(v) => format!("{}", v).into(),
             Value::String(v) => Cow::Borrowed(v.as_str()),
             Value::Array(v) => format!("{}", v).into(),
+            Value::Duration(v) => format!("{}ns", v.as_nanos()).into(),
         }
     }
+
+    /// Create a value for a point in time.
+    ///
+    /// Timestamps are encoded as an [`Value::I64`] of the nanoseconds since the
+    /// Unix epoch, negative for times before it, saturating at the bounds of
+    /// `i64`.
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use opentelemetry::Value;
+    ///
+    /// let time = UNIX_EPOCH + Duration::from_secs(1);
+    /// assert_eq!(Value::timestamp(time), Value::I64(1_000_000_000));
+    /// ```
+    pub fn timestamp(time: SystemTime) -> Value {
+        let nanos = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_nanos()).unwrap_or(i64::MAX),
+            Err(before) => i64::try_from(before.duration().as_nanos())
+                .map(|nanos| -nanos)
+                .unwrap_or(i64::MIN),
+        };
+        Value::I64(nanos)
+    }
 }
 
 macro_rules! from_values {
@@ -360,6 +446,7 @@ from_values!(
     (i64, Value::I64);
     (f64, Value::F64);
     (StringValue, Value::String);
+    (Duration, Value::Duration);
 );
 
 impl From<&'static str> for Value {
@@ -394,6 +481,7 @@ impl fmt::Display for Value {
             Value::F64(v) => v.fmt(fmt),
             Value::String(v) => fmt.write_str(v.as_str()),
             Value::Array(v) => v.fmt(fmt),
+            Value::Duration(v) => write!(fmt, "{}ns", v.as_nanos()),
         }
     }
 }
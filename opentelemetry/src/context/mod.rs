@@ -1,3 +1,8 @@
+//! Execution-scoped context propagation.
+//!
+//! See [`Context`] for managing the current context on a thread. The optional
+//! `rayon` and `threadpool` features provide adapters that carry the current
+//! context into work executed on those thread pools.
 #[cfg(feature = "trace")]
 use crate::trace::context::SynchronizedSpan;
 use std::any::{Any, TypeId};
@@ -8,6 +13,16 @@ use std::hash::{BuildHasherDefault, Hasher};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub mod rayon;
+#[cfg(feature = "threadpool")]
+#[cfg_attr(docsrs, doc(cfg(feature = "threadpool")))]
+pub mod threadpool;
+
+#[cfg(feature = "rayon")]
+pub use self::rayon::propagate_into_rayon;
+
 thread_local! {
     static CURRENT_CONTEXT: RefCell<Context> = RefCell::new(Context::default());
 }
@@ -308,6 +323,36 @@ impl Context {
         }
     }
 
+    /// Returns a closure that runs `f` with this context attached.
+    ///
+    /// This is useful when handing work off to thread pools or executors that
+    /// do not carry the current context across threads. The previous context of
+    /// the executing thread is restored once `f` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::Context;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct ValueA(&'static str);
+    ///
+    /// let _guard = Context::new().with_value(ValueA("a")).attach();
+    /// let work = Context::current().wrap(|| Context::current().get::<ValueA>().is_some());
+    ///
+    /// let handle = std::thread::spawn(work);
+    /// assert!(handle.join().unwrap());
+    /// ```
+    pub fn wrap<F, R>(self, f: F) -> impl FnOnce() -> R
+    where
+        F: FnOnce() -> R,
+    {
+        move || {
+            let _guard = self.attach();
+            f()
+        }
+    }
+
     #[cfg(feature = "trace")]
     pub(super) fn current_with_synchronized_span(value: SynchronizedSpan) -> Self {
         Context {
@@ -413,4 +458,17 @@ mod tests {
             true
         }));
     }
+
+    #[test]
+    fn wrapped_closure_restores_previous_context() {
+        #[derive(Debug, PartialEq)]
+        struct ValueA(&'static str);
+
+        let work = Context::new().with_value(ValueA("a")).wrap(|| {
+            assert_eq!(Context::current().get(), Some(&ValueA("a")));
+        });
+        work();
+
+        assert_eq!(Context::current().get::<ValueA>(), None);
+    }
 }
@@ -0,0 +1,136 @@
+//! Adapters that carry the current [`Context`] into [rayon] tasks.
+//!
+//! Rayon executes closures on its own worker threads, which do not inherit the
+//! caller's thread-local context. The functions in this module capture the
+//! current context when the work is submitted and attach it on the worker
+//! thread for the duration of the closure.
+//!
+//! [rayon]: https://docs.rs/rayon
+use super::Context;
+
+/// Captures the current context and returns a closure that runs `op` with it
+/// attached.
+///
+/// This is the building block for the other adapters in this module and can be
+/// used directly with any rayon API accepting a closure.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::context::propagate_into_rayon;
+/// use opentelemetry::Context;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ValueA(&'static str);
+///
+/// let _guard = Context::new().with_value(ValueA("a")).attach();
+/// let (left, right) = rayon::join(
+///     propagate_into_rayon(|| Context::current().get::<ValueA>().is_some()),
+///     propagate_into_rayon(|| Context::current().get::<ValueA>().is_some()),
+/// );
+/// assert!(left && right);
+/// ```
+pub fn propagate_into_rayon<OP, R>(op: OP) -> impl FnOnce() -> R + Send
+where
+    OP: FnOnce() -> R + Send,
+{
+    Context::current().wrap(op)
+}
+
+/// Spawns `op` on the global rayon pool with the current context attached.
+///
+/// See [`rayon::spawn`].
+pub fn spawn<OP>(op: OP)
+where
+    OP: FnOnce() + Send + 'static,
+{
+    ::rayon::spawn(propagate_into_rayon(op))
+}
+
+/// Runs `oper_a` and `oper_b` in parallel with the current context attached to
+/// both.
+///
+/// See [`rayon::join`].
+pub fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    ::rayon::join(propagate_into_rayon(oper_a), propagate_into_rayon(oper_b))
+}
+
+/// Extension trait for running work on a [`rayon::ThreadPool`] with the
+/// current context attached.
+pub trait ThreadPoolExt {
+    /// Spawns `op` on this pool with the current context attached.
+    ///
+    /// See [`rayon::ThreadPool::spawn`].
+    fn spawn_with_current_context<OP>(&self, op: OP)
+    where
+        OP: FnOnce() + Send + 'static;
+
+    /// Executes `op` within this pool with the current context attached.
+    ///
+    /// See [`rayon::ThreadPool::install`].
+    fn install_with_current_context<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send;
+}
+
+impl ThreadPoolExt for ::rayon::ThreadPool {
+    fn spawn_with_current_context<OP>(&self, op: OP)
+    where
+        OP: FnOnce() + Send + 'static,
+    {
+        self.spawn(propagate_into_rayon(op))
+    }
+
+    fn install_with_current_context<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        self.install(propagate_into_rayon(op))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[derive(Debug, PartialEq)]
+    struct ValueA(&'static str);
+
+    #[test]
+    fn join_propagates_context() {
+        let _guard = Context::new().with_value(ValueA("a")).attach();
+        let (left, right) = join(
+            || Context::current().get::<ValueA>() == Some(&ValueA("a")),
+            || Context::current().get::<ValueA>() == Some(&ValueA("a")),
+        );
+        assert!(left);
+        assert!(right);
+    }
+
+    #[test]
+    fn thread_pool_propagates_context() {
+        let pool = ::rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let _guard = Context::new().with_value(ValueA("a")).attach();
+
+        assert!(pool.install_with_current_context(|| Context::current().get::<ValueA>().is_some()));
+
+        let (tx, rx) = mpsc::channel();
+        pool.spawn_with_current_context(move || {
+            tx.send(Context::current().get::<ValueA>().is_some())
+                .unwrap();
+        });
+        assert!(rx.recv().unwrap());
+    }
+}
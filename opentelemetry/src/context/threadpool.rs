@@ -0,0 +1,63 @@
+//! Adapters that carry the current [`Context`] into [threadpool] jobs.
+//!
+//! [threadpool]: https://docs.rs/threadpool
+use super::Context;
+
+/// Extension trait for executing jobs on a [`threadpool::ThreadPool`] with the
+/// current context attached.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::context::threadpool::ThreadPoolExt;
+/// use opentelemetry::Context;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ValueA(&'static str);
+///
+/// let pool = threadpool::ThreadPool::new(1);
+/// let _guard = Context::new().with_value(ValueA("a")).attach();
+/// pool.execute_with_current_context(|| {
+///     assert_eq!(Context::current().get(), Some(&ValueA("a")));
+/// });
+/// pool.join();
+/// ```
+pub trait ThreadPoolExt {
+    /// Executes `job` on this pool with the current context attached.
+    ///
+    /// See [`threadpool::ThreadPool::execute`].
+    fn execute_with_current_context<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static;
+}
+
+impl ThreadPoolExt for ::threadpool::ThreadPool {
+    fn execute_with_current_context<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.execute(Context::current().wrap(job))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[derive(Debug, PartialEq)]
+    struct ValueA(&'static str);
+
+    #[test]
+    fn execute_propagates_context() {
+        let pool = ::threadpool::ThreadPool::new(1);
+        let _guard = Context::new().with_value(ValueA("a")).attach();
+
+        let (tx, rx) = mpsc::channel();
+        pool.execute_with_current_context(move || {
+            tx.send(Context::current().get::<ValueA>().is_some())
+                .unwrap();
+        });
+        assert!(rx.recv().unwrap());
+    }
+}
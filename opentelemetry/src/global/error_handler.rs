@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::sync::PoisonError;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "logs")]
 use crate::logs::LogError;
@@ -11,6 +13,8 @@ use crate::trace::TraceError;
 use once_cell::sync::Lazy;
 
 static GLOBAL_ERROR_HANDLER: Lazy<RwLock<Option<ErrorHandler>>> = Lazy::new(|| RwLock::new(None));
+static ERROR_REPORTS: Lazy<Mutex<HashMap<&'static str, ErrorReport>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Wrapper for error from both tracing and metrics part of open telemetry.
 #[derive(thiserror::Error, Debug)]
@@ -84,3 +88,123 @@ where
         .map(|mut handler| *handler = Some(ErrorHandler(Box::new(f))))
         .map_err(Into::into)
 }
+
+/// Handle the first error reported with `key` using [`handle_error`], later
+/// errors with the same key are only counted.
+///
+/// Use this for errors which would otherwise be reported for every record,
+/// such as a missing configuration. The number of reported and suppressed
+/// errors is available from [`error_report_stats`].
+pub fn handle_error_once<T: Into<Error>>(key: &'static str, err: T) {
+    if should_report(key, None) {
+        handle_error(err);
+    }
+}
+
+/// Handle errors reported with `key` using [`handle_error`] at most once per
+/// `interval`, other errors with the same key are only counted.
+///
+/// Use this for errors which repeat while a condition lasts, such as every
+/// export failing while the collector is down. The number of reported and
+/// suppressed errors is available from [`error_report_stats`].
+pub fn handle_error_rate_limited<T: Into<Error>>(key: &'static str, interval: Duration, err: T) {
+    if should_report(key, Some(interval)) {
+        handle_error(err);
+    }
+}
+
+/// Number of errors handled and suppressed for a key of [`handle_error_once`]
+/// or [`handle_error_rate_limited`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ErrorReportStats {
+    /// Errors passed to the error handler.
+    pub reported: u64,
+    /// Errors suppressed because the key was already reported.
+    pub suppressed: u64,
+}
+
+/// Snapshot of the errors handled and suppressed by [`handle_error_once`] and
+/// [`handle_error_rate_limited`], per key.
+pub fn error_report_stats() -> HashMap<&'static str, ErrorReportStats> {
+    ERROR_REPORTS
+        .lock()
+        .map(|reports| {
+            reports
+                .iter()
+                .map(|(key, report)| (*key, report.stats))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+struct ErrorReport {
+    last_reported: Instant,
+    stats: ErrorReportStats,
+}
+
+/// Records an error for `key`, returning whether it should be handled.
+///
+/// Errors are only reported again after `interval`, or never without one.
+fn should_report(key: &'static str, interval: Option<Duration>) -> bool {
+    let mut reports = match ERROR_REPORTS.lock() {
+        Ok(reports) => reports,
+        // never lose errors because of the deduplication
+        Err(_) => return true,
+    };
+    let now = Instant::now();
+    let report = reports.entry(key).or_insert_with(|| ErrorReport {
+        last_reported: now,
+        stats: ErrorReportStats::default(),
+    });
+
+    let first = report.stats.reported == 0;
+    let due = interval.map_or(false, |interval| {
+        now.duration_since(report.last_reported) >= interval
+    });
+    if first || due {
+        report.last_reported = now;
+        report.stats.reported += 1;
+        true
+    } else {
+        report.stats.suppressed += 1;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_once_suppresses_repeated_errors() {
+        for _ in 0..3 {
+            handle_error_once("test.error_once", Error::Other("first".into()));
+        }
+
+        assert_eq!(
+            error_report_stats()["test.error_once"],
+            ErrorReportStats {
+                reported: 1,
+                suppressed: 2
+            }
+        );
+    }
+
+    #[test]
+    fn rate_limited_errors_are_reported_again_after_interval() {
+        let interval = Duration::from_millis(50);
+        handle_error_rate_limited("test.rate_limited", interval, Error::Other("a".into()));
+        handle_error_rate_limited("test.rate_limited", interval, Error::Other("b".into()));
+        std::thread::sleep(interval);
+        handle_error_rate_limited("test.rate_limited", interval, Error::Other("c".into()));
+
+        assert_eq!(
+            error_report_stats()["test.rate_limited"],
+            ErrorReportStats {
+                reported: 2,
+                suppressed: 1
+            }
+        );
+    }
+}
@@ -133,7 +133,10 @@ mod propagation;
 #[cfg(feature = "trace")]
 mod trace;
 
-pub use error_handler::{handle_error, set_error_handler, Error};
+pub use error_handler::{
+    error_report_stats, handle_error, handle_error_once, handle_error_rate_limited,
+    set_error_handler, Error, ErrorReportStats,
+};
 #[cfg(feature = "metrics")]
 #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
 pub use metrics::*;
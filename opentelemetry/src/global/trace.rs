@@ -1,6 +1,6 @@
 use crate::trace::{noop::NoopTracerProvider, SpanContext, Status};
 use crate::InstrumentationLibrary;
-use crate::{trace, trace::TracerProvider, Context, KeyValue};
+use crate::{trace, trace::TracerProvider, Context, Key, KeyValue, Value};
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
 use std::fmt;
@@ -63,6 +63,16 @@ pub trait ObjectSafeSpan {
     /// that have prescribed semantic meanings.
     fn set_attribute(&mut self, attribute: KeyValue);
 
+    /// Sets an attribute whose value is computed lazily, see
+    /// [`Span::set_attribute_lazy`] for details.
+    ///
+    /// [`Span::set_attribute_lazy`]: trace::Span::set_attribute_lazy
+    fn set_attribute_lazy(&mut self, key: Key, value: Box<dyn FnOnce() -> Value + Send + Sync>) {
+        if self.is_recording() {
+            self.set_attribute(KeyValue::new(key, value()));
+        }
+    }
+
     /// Sets the status of the `Span`. `message` MUST be ignored when the status is `OK` or
     /// `Unset`.
     ///
@@ -134,6 +144,10 @@ impl<T: trace::Span> ObjectSafeSpan for T {
         self.set_attribute(attribute)
     }
 
+    fn set_attribute_lazy(&mut self, key: Key, value: Box<dyn FnOnce() -> Value + Send + Sync>) {
+        self.set_attribute_lazy(key, value)
+    }
+
     fn set_status(&mut self, status: Status) {
         self.set_status(status)
     }
@@ -210,6 +224,17 @@ impl trace::Span for BoxedSpan {
         self.0.set_attribute(attribute)
     }
 
+    /// Sets an attribute whose value is computed lazily.
+    fn set_attribute_lazy<K, V, F>(&mut self, key: K, value: F)
+    where
+        K: Into<Key>,
+        V: Into<Value>,
+        F: FnOnce() -> V + Send + Sync + 'static,
+    {
+        self.0
+            .set_attribute_lazy(key.into(), Box::new(move || value().into()))
+    }
+
     /// Sets the status of the `Span`. If used, this will override the default `Span`
     /// status, which is `Unset`.
     fn set_status(&mut self, status: trace::Status) {
@@ -428,3 +453,76 @@ pub fn shutdown_tracer_provider() {
         GlobalTracerProvider::new(NoopTracerProvider::new()),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BoxedSpan;
+    use crate::trace::{self, Span, SpanContext, Status};
+    use crate::{Key, KeyValue, Value};
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    struct LazySpan {
+        span_context: SpanContext,
+        lazy_keys: Arc<Mutex<Vec<Key>>>,
+    }
+
+    impl trace::Span for LazySpan {
+        fn add_event_with_timestamp<T>(&mut self, _: T, _: SystemTime, _: Vec<KeyValue>)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+
+        fn span_context(&self) -> &SpanContext {
+            &self.span_context
+        }
+
+        fn is_recording(&self) -> bool {
+            true
+        }
+
+        fn set_attribute(&mut self, _attribute: KeyValue) {
+            panic!("lazy attribute evaluated eagerly")
+        }
+
+        fn set_attribute_lazy<K, V, F>(&mut self, key: K, _value: F)
+        where
+            K: Into<Key>,
+            V: Into<Value>,
+            F: FnOnce() -> V + Send + Sync + 'static,
+        {
+            self.lazy_keys.lock().unwrap().push(key.into());
+        }
+
+        fn set_status(&mut self, _status: Status) {}
+
+        fn update_name<T>(&mut self, _new_name: T)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+
+        fn add_link(&mut self, _span_context: SpanContext, _attributes: Vec<KeyValue>) {}
+
+        fn end_with_timestamp(&mut self, _timestamp: SystemTime) {}
+    }
+
+    #[test]
+    fn boxed_span_forwards_lazy_attributes() {
+        let span = LazySpan {
+            span_context: SpanContext::empty_context(),
+            lazy_keys: Arc::default(),
+        };
+        let lazy_keys = span.lazy_keys.clone();
+        let mut boxed = BoxedSpan::new(span);
+
+        boxed.set_attribute_lazy("lazy", || "value");
+
+        assert_eq!(
+            *lazy_keys.lock().unwrap(),
+            vec![Key::from_static_str("lazy")]
+        );
+    }
+}
@@ -10,6 +10,13 @@ pub trait LogRecord {
     {
     }
 
+    /// Sets the numeric id of the event, which consumers such as the Windows
+    /// Event Log identify events by.
+    fn set_event_id(&mut self, _id: u32) {}
+
+    /// Sets the version of the event identified by the event id.
+    fn set_event_version(&mut self, _version: u8) {}
+
     /// Sets the `target` of a record.
     /// Currently, both `opentelemetry-appender-tracing` and `opentelemetry-appender-log` create a single logger
     /// with a scope that doesn't accurately reflect the component emitting the logs.
@@ -119,6 +126,7 @@ impl From<Value> for AnyValue {
             Value::I64(i) => i.into(),
             Value::F64(f) => f.into(),
             Value::String(s) => s.into(),
+            Value::Duration(d) => AnyValue::Int(i64::try_from(d.as_nanos()).unwrap_or(i64::MAX)),
             Value::Array(a) => match a {
                 Array::Bool(b) => AnyValue::from_iter(b),
                 Array::F64(f) => AnyValue::from_iter(f),
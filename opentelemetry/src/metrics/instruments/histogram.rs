@@ -5,6 +5,50 @@ use crate::{
 use core::fmt;
 use std::sync::Arc;
 
+/// The aggregation advised by the author of a histogram.
+///
+/// The SDK uses it instead of the default aggregation of histograms, unless
+/// the reader selects another aggregation for histograms or a view configures
+/// the aggregation of the instrument.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum HistogramAggregation {
+    /// A histogram with explicitly defined buckets.
+    ExplicitBucket {
+        /// The increasing bucket boundary values, each one being the
+        /// inclusive upper bound of a bucket.
+        boundaries: Vec<f64>,
+        /// Indicates whether to record the min and max of the distribution.
+        record_min_max: bool,
+    },
+    /// A histogram with bucket widths that grow exponentially.
+    Base2Exponential {
+        /// The maximum number of buckets to use for the histogram.
+        max_size: u32,
+        /// The maximum resolution scale to use for the histogram, from `-10`
+        /// to `20`.
+        max_scale: i8,
+        /// Indicates whether to record the min and max of the distribution.
+        record_min_max: bool,
+    },
+}
+
+/// The advice given by the author of a histogram on how to aggregate it.
+///
+/// Advice is ignored by SDKs that do not support it, and by views that
+/// configure the aggregation of the instrument.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct HistogramAdvice {
+    /// The aggregation to use instead of the default aggregation of
+    /// histograms.
+    pub aggregation: Option<HistogramAggregation>,
+
+    /// The bucket boundaries to use when the histogram is aggregated with
+    /// explicitly defined buckets, instead of the default boundaries.
+    pub boundaries: Option<Vec<f64>>,
+}
+
 /// An SDK implemented instrument that records a distribution of values.
 pub trait SyncHistogram<T> {
     /// Adds an additional value to the distribution.
@@ -36,13 +80,53 @@ impl<T> Histogram<T> {
     }
 }
 
+impl<T> InstrumentBuilder<'_, Histogram<T>> {
+    /// Advise the aggregation of this histogram.
+    ///
+    /// The advice only replaces the default aggregation. It is ignored by SDKs
+    /// that do not support it, by readers selecting another aggregation for
+    /// histograms and by views that configure the aggregation of this
+    /// instrument.
+    pub fn with_aggregation(mut self, aggregation: HistogramAggregation) -> Self {
+        self.histogram_advice.aggregation = Some(aggregation);
+        self
+    }
+
+    /// Advise the bucket boundaries of this histogram.
+    ///
+    /// The boundaries are used when the histogram is aggregated with explicitly
+    /// defined buckets, unless a view configures the aggregation of this
+    /// instrument. The aggregation advised with
+    /// [`with_aggregation`](Self::with_aggregation) takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::global;
+    ///
+    /// let histogram = global::meter("my_app")
+    ///     .f64_histogram("http.server.request.duration")
+    ///     .with_unit("s")
+    ///     .with_boundaries(vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0])
+    ///     .init();
+    /// # drop(histogram);
+    /// ```
+    pub fn with_boundaries(mut self, boundaries: Vec<f64>) -> Self {
+        self.histogram_advice.boundaries = Some(boundaries);
+        self
+    }
+}
+
 impl TryFrom<InstrumentBuilder<'_, Histogram<f64>>> for Histogram<f64> {
     type Error = MetricsError;
 
     fn try_from(builder: InstrumentBuilder<'_, Histogram<f64>>) -> Result<Self, Self::Error> {
-        builder
-            .instrument_provider
-            .f64_histogram(builder.name, builder.description, builder.unit)
+        builder.instrument_provider.f64_histogram_with_advice(
+            builder.name,
+            builder.description,
+            builder.unit,
+            builder.histogram_advice,
+        )
     }
 }
 
@@ -50,8 +134,11 @@ impl TryFrom<InstrumentBuilder<'_, Histogram<u64>>> for Histogram<u64> {
     type Error = MetricsError;
 
     fn try_from(builder: InstrumentBuilder<'_, Histogram<u64>>) -> Result<Self, Self::Error> {
-        builder
-            .instrument_provider
-            .u64_histogram(builder.name, builder.description, builder.unit)
+        builder.instrument_provider.u64_histogram_with_advice(
+            builder.name,
+            builder.description,
+            builder.unit,
+            builder.histogram_advice,
+        )
     }
 }
@@ -6,7 +6,7 @@ use std::borrow::Cow;
 use std::marker;
 use std::sync::Arc;
 
-use super::InstrumentProvider;
+use super::{HistogramAdvice, InstrumentProvider};
 
 pub(super) mod counter;
 pub(super) mod gauge;
@@ -30,6 +30,7 @@ pub struct InstrumentBuilder<'a, T> {
     name: Cow<'static, str>,
     description: Option<Cow<'static, str>>,
     unit: Option<Cow<'static, str>>,
+    histogram_advice: HistogramAdvice,
     _marker: marker::PhantomData<T>,
 }
 
@@ -44,6 +45,7 @@ where
             name,
             description: None,
             unit: None,
+            histogram_advice: HistogramAdvice::default(),
             _marker: marker::PhantomData,
         }
     }
@@ -90,6 +92,7 @@ impl<T> fmt::Debug for InstrumentBuilder<'_, T> {
             .field("name", &self.name)
             .field("description", &self.description)
             .field("unit", &self.unit)
+            .field("histogram_advice", &self.histogram_advice)
             .field("kind", &std::any::type_name::<T>())
             .finish()
     }
@@ -404,6 +404,10 @@ impl Meter {
     /// instruments that may observe values.
     ///
     /// If no instruments are passed, the callback will not be registered.
+    ///
+    /// Related instruments, e.g. the used and free memory of a system, should
+    /// be observed by a single callback rather than one callback each, so
+    /// that SDKs can record their observations as one consistent snapshot.
     pub fn register_callback<F>(
         &self,
         instruments: &[Arc<dyn Any>],
@@ -16,7 +16,7 @@ use crate::{Array, ExportError, KeyValue, Value};
 pub use instruments::{
     counter::{Counter, ObservableCounter, SyncCounter},
     gauge::{Gauge, ObservableGauge, SyncGauge},
-    histogram::{Histogram, SyncHistogram},
+    histogram::{Histogram, HistogramAdvice, HistogramAggregation, SyncHistogram},
     up_down_counter::{ObservableUpDownCounter, SyncUpDownCounter, UpDownCounter},
     AsyncInstrument, AsyncInstrumentBuilder, Callback, InstrumentBuilder,
 };
@@ -75,7 +75,8 @@ impl Hash for F64Hashable {
 
 impl Hash for KeyValue {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.key.hash(state);
+        // avoids hashing the name of keys created with `Key::from_static_str`
+        state.write_u64(self.key.name_hash());
         match &self.value {
             Value::F64(f) => F64Hashable(*f).hash(state),
             Value::Array(a) => match a {
@@ -87,6 +88,7 @@ impl Hash for KeyValue {
             Value::Bool(b) => b.hash(state),
             Value::I64(i) => i.hash(state),
             Value::String(s) => s.hash(state),
+            Value::Duration(d) => d.hash(state),
         };
     }
 }
@@ -293,6 +295,34 @@ pub trait InstrumentProvider {
         Ok(Histogram::new(Arc::new(noop::NoopSyncInstrument::new())))
     }
 
+    /// creates an instrument for recording a distribution of values, with the
+    /// advice of its author on how to aggregate it.
+    ///
+    /// The advice is ignored by default.
+    fn f64_histogram_with_advice(
+        &self,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        unit: Option<Cow<'static, str>>,
+        _advice: HistogramAdvice,
+    ) -> Result<Histogram<f64>> {
+        self.f64_histogram(name, description, unit)
+    }
+
+    /// creates an instrument for recording a distribution of values, with the
+    /// advice of its author on how to aggregate it.
+    ///
+    /// The advice is ignored by default.
+    fn u64_histogram_with_advice(
+        &self,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        unit: Option<Cow<'static, str>>,
+        _advice: HistogramAdvice,
+    ) -> Result<Histogram<u64>> {
+        self.u64_histogram(name, description, unit)
+    }
+
     /// Captures the function that will be called during data collection.
     ///
     /// It is only valid to call `observe` within the scope of the passed function.
@@ -379,6 +409,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn kv_hash_independent_of_key_storage() {
+        use crate::Key;
+        use std::sync::Arc;
+
+        const STATIC_KEY: Key = Key::from_static_str("key");
+        let keys = [
+            STATIC_KEY,
+            Key::from("key"),
+            Key::from(String::from("key")),
+            Key::from(Arc::<str>::from("key")),
+        ];
+        for key in keys {
+            assert_eq!(key.name_hash(), STATIC_KEY.name_hash());
+            assert_eq!(
+                hash_helper(&KeyValue::new(key, "value")),
+                hash_helper(&KeyValue::new(STATIC_KEY, "value"))
+            );
+        }
+        assert_ne!(STATIC_KEY.name_hash(), Key::from("other").name_hash());
+    }
+
     #[test]
     fn kv_float_order() {
         // TODO: Extend this test to all value types, not just F64
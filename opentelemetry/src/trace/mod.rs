@@ -179,7 +179,7 @@ pub use self::{
     },
     span::{Span, SpanKind, Status},
     span_context::{SpanContext, SpanId, TraceFlags, TraceId, TraceState},
-    tracer::{SamplingDecision, SamplingResult, SpanBuilder, Tracer},
+    tracer::{LazyLinks, SamplingDecision, SamplingResult, SpanBuilder, Tracer},
     tracer_provider::TracerProvider,
 };
 use crate::{ExportError, KeyValue};
@@ -1,4 +1,4 @@
-use crate::{trace::SpanContext, KeyValue};
+use crate::{trace::SpanContext, Key, KeyValue, Value};
 use std::borrow::Cow;
 use std::error::Error;
 use std::time::SystemTime;
@@ -141,6 +141,35 @@ pub trait Span {
         }
     }
 
+    /// Set an attribute of this span whose value is computed lazily.
+    ///
+    /// `value` is never called if the span is not recording. Implementations
+    /// may defer calling it further, for example until the span ends and only
+    /// if it is sampled, so that expensive values only have to be computed for
+    /// spans that are exported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::{global, trace::{Span, Tracer}};
+    ///
+    /// let body = vec![0u8; 1024];
+    /// let mut span = global::tracer("example").start("handle_request");
+    /// span.set_attribute_lazy("http.request.body.summary", move || {
+    ///     format!("{} bytes", body.len())
+    /// });
+    /// ```
+    fn set_attribute_lazy<K, V, F>(&mut self, key: K, value: F)
+    where
+        K: Into<Key>,
+        V: Into<Value>,
+        F: FnOnce() -> V + Send + Sync + 'static,
+    {
+        if self.is_recording() {
+            self.set_attribute(KeyValue::new(key, value()));
+        }
+    }
+
     /// Sets the status of this `Span`.
     ///
     /// If used, this will override the default span status, which is [`Status::Unset`].
@@ -3,6 +3,8 @@ use crate::{
     Context, KeyValue,
 };
 use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
 /// The interface for constructing [`Span`]s.
@@ -267,9 +269,15 @@ pub struct SpanBuilder {
     /// Span events
     pub events: Option<Vec<Event>>,
 
-    /// Span Links
+    /// Span links that are provided at the span creation time.
+    ///
+    /// These links are passed to the sampler.
     pub links: Option<Vec<Link>>,
 
+    /// Span links that are only iterated once the span is sampled, see
+    /// [`SpanBuilder::with_links`].
+    pub lazy_links: Option<LazyLinks>,
+
     /// Span status
     pub status: Status,
 
@@ -348,11 +356,22 @@ impl SpanBuilder {
         }
     }
 
-    /// Assign links
-    pub fn with_links(self, mut links: Vec<Link>) -> Self {
-        links.retain(|l| l.span_context.is_valid());
+    /// Assign links from an iterable.
+    ///
+    /// The links are only iterated when the span is recording, after the
+    /// sampling decision was made, and iteration stops once the link limit of
+    /// the span is reached. Links past the limit are not counted as dropped.
+    /// Links with an invalid span context are skipped.
+    ///
+    /// Samplers are not given these links, assign [`SpanBuilder::links`]
+    /// directly to provide links to the sampler.
+    pub fn with_links<I>(self, links: I) -> Self
+    where
+        I: IntoIterator<Item = Link>,
+        I::IntoIter: Send + 'static,
+    {
         SpanBuilder {
-            links: Some(links),
+            lazy_links: Some(LazyLinks::new(links)),
             ..self
         }
     }
@@ -381,6 +400,39 @@ impl SpanBuilder {
     }
 }
 
+/// Links of a [`SpanBuilder`] that are iterated once the span is sampled.
+///
+/// Clones of a `LazyLinks` share the same iterator, the links are only
+/// returned to the first caller of [`LazyLinks::take`].
+#[derive(Clone)]
+pub struct LazyLinks(Arc<Mutex<Option<Box<dyn Iterator<Item = Link> + Send>>>>);
+
+impl LazyLinks {
+    /// Create lazy links from an iterable, skipping links with an invalid span
+    /// context.
+    pub fn new<I>(links: I) -> Self
+    where
+        I: IntoIterator<Item = Link>,
+        I::IntoIter: Send + 'static,
+    {
+        let links = links
+            .into_iter()
+            .filter(|link| link.span_context.is_valid());
+        LazyLinks(Arc::new(Mutex::new(Some(Box::new(links)))))
+    }
+
+    /// Takes the links, `None` if they were already taken.
+    pub fn take(&self) -> Option<Box<dyn Iterator<Item = Link> + Send>> {
+        self.0.lock().ok().and_then(|mut links| links.take())
+    }
+}
+
+impl fmt::Debug for LazyLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyLinks").finish_non_exhaustive()
+    }
+}
+
 /// The result of sampling logic for a given span.
 #[derive(Clone, Debug, PartialEq)]
 pub struct SamplingResult {
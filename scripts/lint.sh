@@ -14,7 +14,13 @@ if rustup component add clippy; then
                 "opentelemetry-http"
                 "opentelemetry-jaeger-propagator"
                 "opentelemetry-appender-log"
+                "opentelemetry-appender-panic"
+                "opentelemetry-appender-slog"
                 "opentelemetry-appender-tracing"
+                "opentelemetry-azure-monitor"
+                "opentelemetry-file-exporter"
+                "opentelemetry-live"
+                "opentelemetry-macros"
                 "opentelemetry-otlp"
                 "opentelemetry-prometheus"
                 "opentelemetry-proto"
@@ -39,6 +45,10 @@ if rustup component add clippy; then
   cargo_feature opentelemetry-otlp "http-proto, reqwest-rustls"
   cargo_feature opentelemetry-otlp "metrics"
 
+  cargo_feature opentelemetry-azure-monitor "trace"
+  cargo_feature opentelemetry-azure-monitor "logs"
+  cargo_feature opentelemetry-azure-monitor "metrics"
+
   cargo_feature opentelemetry-jaeger-propagator "default"
 
   cargo_feature opentelemetry-proto "default"
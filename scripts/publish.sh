@@ -3,15 +3,20 @@
 packages=(
     "opentelemetry"
     "opentelemetry-http"
+    "opentelemetry-macros"
     "opentelemetry-semantic-conventions"
     "opentelemetry-jaeger-propagator"
     "opentelemetry-sdk"    
     "opentelemetry-proto"
     "opentelemetry-otlp"
+    "opentelemetry-file-exporter"
+    "opentelemetry-azure-monitor"
     "opentelemetry-stdout"
     "opentelemetry-zipkin"
     "opentelemetry-prometheus"
     "opentelemetry-appender-log"
+    "opentelemetry-appender-panic"
+    "opentelemetry-appender-slog"
     "opentelemetry-appender-tracing"
 
     # Add more packages as needed, in the right order. A package should only be published after all it's dependencies have been published